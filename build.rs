@@ -1,3 +1,5 @@
+use std::process::Command;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Safety: set_var is used in a single-threaded build script environment.
     unsafe {
@@ -5,5 +7,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     tonic_prost_build::configure().compile_protos(&["proto/nexus.proto"], &["proto"])?;
+
+    // Build metadata reported by /v1/metrics, /metrics and GetVersion. GIT_COMMIT
+    // overrides the checkout (for builds from a source archive).
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    let git_commit = std::env::var("GIT_COMMIT")
+        .ok()
+        .filter(|c| !c.trim().is_empty())
+        .or_else(|| command_output("git", &["rev-parse", "--short=12", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=NEXUS_GIT_COMMIT={}", git_commit.trim());
+    println!("cargo:rustc-env=NEXUS_RUSTC_VERSION={}", rustc_version);
     Ok(())
 }
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let out = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!out.is_empty()).then_some(out)
+}
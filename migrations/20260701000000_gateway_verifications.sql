@@ -0,0 +1,13 @@
+-- [NEXUS-GW-02] Verification outcomes recorded by gateway services
+CREATE TABLE IF NOT EXISTS gateway_verifications (
+    service TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    verified BOOLEAN NOT NULL,
+    reason TEXT,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (service, external_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_gateway_verifications_created_at ON gateway_verifications(created_at);
@@ -0,0 +1,15 @@
+-- [NEXUS-GW-04] Audit log of requests handled by gateway services
+CREATE TABLE IF NOT EXISTS gateway_requests (
+    id BIGSERIAL PRIMARY KEY,
+    service TEXT NOT NULL,
+    request_digest TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    response TEXT NOT NULL,
+    latency_ms BIGINT NOT NULL,
+    caller TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_gateway_requests_service_created_at ON gateway_requests(service, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_gateway_requests_request_digest ON gateway_requests(request_digest);
@@ -0,0 +1,15 @@
+-- [NEXUS-GW-08] Fee ledger for metered gateway requests
+CREATE TABLE IF NOT EXISTS service_fees (
+    id BIGSERIAL PRIMARY KEY,
+    service TEXT NOT NULL,
+    command TEXT NOT NULL,
+    caller TEXT,
+    request_digest TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    payout_payload TEXT NOT NULL,
+    payout_signature TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_service_fees_created_at ON service_fees(created_at);
+CREATE INDEX IF NOT EXISTS idx_service_fees_caller_created_at ON service_fees(caller, created_at);
@@ -0,0 +1,6 @@
+-- [NEXUS-SYNC-03] Highest height whose block and transactions have fully committed
+CREATE TABLE IF NOT EXISTS sync_progress (
+    id SMALLINT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    processed_height BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
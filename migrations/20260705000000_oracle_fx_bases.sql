@@ -0,0 +1,2 @@
+-- [NEXUS-ORACLE-BASES] Indices rebased onto each additional configured base currency.
+ALTER TABLE oracle_fx_history ADD COLUMN IF NOT EXISTS bases JSONB NOT NULL DEFAULT '{}';
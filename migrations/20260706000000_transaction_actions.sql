@@ -0,0 +1,3 @@
+-- [NEXUS-SYNC-04] Decoded Conxian contract action (deposit, withdraw, liquidate, mint)
+ALTER TABLE stacks_transactions ADD COLUMN IF NOT EXISTS action TEXT;
+CREATE INDEX IF NOT EXISTS idx_stacks_transactions_action ON stacks_transactions(action) WHERE action IS NOT NULL;
@@ -0,0 +1,12 @@
+-- [NEXUS-ORACLE-01] Raw per-provider FX observations behind each aggregate in oracle_fx_history.
+CREATE TABLE IF NOT EXISTS oracle_observations (
+    id BIGSERIAL PRIMARY KEY,
+    provider TEXT NOT NULL,
+    base_currency TEXT NOT NULL,
+    rates JSONB NOT NULL,
+    timestamp BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oracle_observations_provider_timestamp
+    ON oracle_observations(provider, timestamp);
@@ -0,0 +1,3 @@
+-- [NEXUS-ORACLE-02] Contributing providers per currency and rejected outlier quotes for each aggregate.
+ALTER TABLE oracle_fx_history ADD COLUMN IF NOT EXISTS provenance JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE oracle_fx_history ADD COLUMN IF NOT EXISTS rejections JSONB NOT NULL DEFAULT '[]';
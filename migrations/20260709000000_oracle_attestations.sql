@@ -0,0 +1,2 @@
+-- [NEXUS-ORACLE-03] Verified publisher price attestations behind each aggregate.
+ALTER TABLE oracle_fx_history ADD COLUMN IF NOT EXISTS attestations JSONB NOT NULL DEFAULT '[]';
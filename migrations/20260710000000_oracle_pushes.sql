@@ -0,0 +1,13 @@
+-- [NEXUS-ORACLE-04] Every attempt to publish the aggregate PPP state on-chain.
+CREATE TABLE IF NOT EXISTS oracle_pushes (
+    id BIGSERIAL PRIMARY KEY,
+    txid TEXT,
+    payload_digest TEXT NOT NULL,
+    rates JSONB NOT NULL,
+    timestamp BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oracle_pushes_status_id ON oracle_pushes (status, id DESC);
@@ -0,0 +1,3 @@
+-- [NEXUS-EXEC-01] Per-sender replay-protection nonce of each accepted execution request.
+ALTER TABLE me_audit_log ADD COLUMN IF NOT EXISTS nonce NUMERIC(20, 0);
+CREATE INDEX IF NOT EXISTS idx_me_audit_log_sender_nonce ON me_audit_log(sender, nonce);
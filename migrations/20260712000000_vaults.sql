@@ -0,0 +1,10 @@
+-- [NEXUS-VAULT-01] Latest state of each vault, as printed by Conxian contract calls.
+CREATE TABLE IF NOT EXISTS vaults (
+    vault_id TEXT PRIMARY KEY,
+    collateral_type TEXT NOT NULL,
+    collateral_amount NUMERIC(20, 0) NOT NULL,
+    debt_amount NUMERIC(20, 0) NOT NULL,
+    ltv_ratio DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_vaults_ltv_ratio ON vaults(ltv_ratio);
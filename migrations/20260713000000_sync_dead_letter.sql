@@ -0,0 +1,10 @@
+-- [NEXUS-SYNC-05] Sync events that failed every handling attempt, kept for manual inspection and replay.
+CREATE TABLE IF NOT EXISTS sync_dead_letter (
+    id BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_sync_dead_letter_created_at ON sync_dead_letter(created_at);
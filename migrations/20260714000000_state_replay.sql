@@ -0,0 +1,24 @@
+-- [NEXUS-STATE-03] Commit order of transactions, so the state tree can be replayed from Postgres, and installed state snapshots. Rows ingested before this migration are numbered in ingestion order.
+ALTER TABLE stacks_transactions ADD COLUMN IF NOT EXISTS seq BIGINT;
+
+CREATE SEQUENCE IF NOT EXISTS stacks_transactions_seq_seq OWNED BY stacks_transactions.seq;
+UPDATE stacks_transactions t SET seq = o.n
+FROM (SELECT tx_id, ROW_NUMBER() OVER (ORDER BY created_at, tx_id) AS n FROM stacks_transactions) o
+WHERE t.tx_id = o.tx_id;
+SELECT setval('stacks_transactions_seq_seq', COALESCE((SELECT MAX(seq) FROM stacks_transactions), 0) + 1, false);
+ALTER TABLE stacks_transactions ALTER COLUMN seq SET DEFAULT nextval('stacks_transactions_seq_seq');
+ALTER TABLE stacks_transactions ALTER COLUMN seq SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_stacks_transactions_seq ON stacks_transactions(seq);
+
+-- The state tree is the latest snapshot's leaves followed by every transaction with seq > last_seq.
+CREATE TABLE IF NOT EXISTS state_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    version INTEGER NOT NULL,
+    arity TEXT NOT NULL,
+    root TEXT NOT NULL,
+    height BIGINT,
+    leaves JSONB NOT NULL,
+    last_seq BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+-- [NEXUS-STATE-04] Leaf count behind every state root sync produced. Leaves are append-only in commit order, so a root's leaf count is enough to rebuild the tree it committed to.
+CREATE TABLE IF NOT EXISTS state_root_history (
+    root TEXT PRIMARY KEY,
+    leaf_count BIGINT NOT NULL,
+    block_height BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
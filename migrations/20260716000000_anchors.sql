@@ -0,0 +1,14 @@
+-- [NEXUS-STATE-05] Every attempt to anchor the state root on Stacks L1. Failed broadcasts are kept for diagnosis; sync moves an anchor from broadcast to confirmed once its txid appears in an ingested block.
+CREATE TABLE IF NOT EXISTS anchors (
+    id BIGSERIAL PRIMARY KEY,
+    root TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    leaf_count BIGINT NOT NULL,
+    tx_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    confirmed_height BIGINT,
+    anchored_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_anchors_tx_id ON anchors (tx_id);
@@ -0,0 +1,7 @@
+-- [NEXUS-VAULT-02] Vault lifecycle events applied by the vault indexer. A vault stays in
+-- the table once closed; `last_event_*` is the position of the latest event applied to
+-- it, so a replayed or out-of-order event is skipped.
+ALTER TABLE vaults ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE vaults ADD COLUMN IF NOT EXISTS last_event_height BIGINT;
+ALTER TABLE vaults ADD COLUMN IF NOT EXISTS last_event_tx_index INTEGER;
+ALTER TABLE vaults ADD COLUMN IF NOT EXISTS last_event_index INTEGER;
@@ -0,0 +1,13 @@
+-- Vault amounts are u128 base units and LTVs whole basis points.
+ALTER TABLE vaults
+    ALTER COLUMN collateral_amount TYPE NUMERIC(39, 0),
+    ALTER COLUMN debt_amount TYPE NUMERIC(39, 0),
+    ADD COLUMN IF NOT EXISTS ltv_bps BIGINT;
+
+UPDATE vaults SET ltv_bps = ROUND(ltv_ratio * 10000)::BIGINT WHERE ltv_bps IS NULL;
+
+ALTER TABLE vaults ALTER COLUMN ltv_bps SET NOT NULL;
+
+DROP INDEX IF EXISTS idx_vaults_ltv_ratio;
+ALTER TABLE vaults DROP COLUMN IF EXISTS ltv_ratio;
+CREATE INDEX IF NOT EXISTS idx_vaults_ltv_bps ON vaults(ltv_bps);
@@ -0,0 +1,14 @@
+-- [NEXUS-EXEC-02] Sequence numbers for the execution audit log, so clients can page through executions in the order the sequencer decided them. Rows recorded before this migration are numbered in arrival order.
+ALTER TABLE me_audit_log ADD COLUMN IF NOT EXISTS sequence BIGINT;
+ALTER TABLE me_audit_log ADD COLUMN IF NOT EXISTS recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE SEQUENCE IF NOT EXISTS me_audit_log_sequence_seq OWNED BY me_audit_log.sequence;
+UPDATE me_audit_log m SET sequence = o.n
+FROM (SELECT tx_id, ROW_NUMBER() OVER (ORDER BY arrival_time, tx_id) AS n FROM me_audit_log) o
+WHERE m.tx_id = o.tx_id;
+SELECT setval('me_audit_log_sequence_seq', COALESCE((SELECT MAX(sequence) FROM me_audit_log), 0) + 1, false);
+ALTER TABLE me_audit_log ALTER COLUMN sequence SET DEFAULT nextval('me_audit_log_sequence_seq');
+ALTER TABLE me_audit_log ALTER COLUMN sequence SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_me_audit_log_sequence ON me_audit_log (sequence);
+CREATE INDEX IF NOT EXISTS idx_me_audit_log_sender_sequence ON me_audit_log (sender, sequence);
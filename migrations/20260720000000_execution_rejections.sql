@@ -0,0 +1,27 @@
+-- [NEXUS-EXEC-03] Rejected requests (expired ones included, which used to go to sync_dead_letter) are recorded with their reason and payload, numbered from the same sequence as accepted ones, and every request keeps the signature it was submitted with. `me_executions` lists both.
+ALTER TABLE me_audit_log ADD COLUMN IF NOT EXISTS signature TEXT;
+
+CREATE TABLE IF NOT EXISTS me_execution_rejections (
+    sequence BIGINT PRIMARY KEY DEFAULT nextval('me_audit_log_sequence_seq'),
+    tx_id TEXT NOT NULL,
+    payload_hash TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    payload TEXT,
+    arrival_time TIMESTAMPTZ NOT NULL,
+    nonce NUMERIC(20, 0),
+    sequencing_priority INTEGER DEFAULT 0,
+    reason TEXT NOT NULL,
+    signature TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_me_execution_rejections_tx_id ON me_execution_rejections (tx_id);
+CREATE INDEX IF NOT EXISTS idx_me_execution_rejections_sender_sequence ON me_execution_rejections (sender, sequence);
+
+CREATE OR REPLACE VIEW me_executions AS
+    SELECT sequence, tx_id, sender, nonce, sequencing_priority, payload_hash, arrival_time,
+           recorded_at, 'accepted' AS status, NULL::TEXT AS rejection_reason, signature
+    FROM me_audit_log
+    UNION ALL
+    SELECT sequence, tx_id, sender, nonce, sequencing_priority, payload_hash, arrival_time,
+           recorded_at, 'rejected', reason, signature
+    FROM me_execution_rejections;
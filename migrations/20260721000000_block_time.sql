@@ -0,0 +1,2 @@
+-- [NEXUS-SYNC-06] Blocks keep the chain's timestamp next to the time they were ingested, so per-sender activity windows follow the chain rather than when sync (or a backfill) happened to run.
+ALTER TABLE stacks_blocks ADD COLUMN IF NOT EXISTS block_time TIMESTAMPTZ;
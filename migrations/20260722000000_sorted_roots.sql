@@ -0,0 +1,3 @@
+-- [NEXUS-STATE-06] Sorted-leaf root recorded and anchored next to every state root, so non-inclusion proofs check against a committed root. NULL on rows written before it was kept.
+ALTER TABLE state_root_history ADD COLUMN IF NOT EXISTS sorted_root TEXT;
+ALTER TABLE anchors ADD COLUMN IF NOT EXISTS sorted_root TEXT;
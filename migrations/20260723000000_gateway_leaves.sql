@@ -0,0 +1,8 @@
+-- [NEXUS-GW-09] Leaves committed by gateway services for requests they verified, such as DLC attestations and RGB transfers. They draw `seq` from the transactions' sequence, so state_leaves replays both in commit order; the primary key rejects replays.
+CREATE TABLE IF NOT EXISTS gateway_leaves (
+    leaf TEXT PRIMARY KEY,
+    service TEXT NOT NULL,
+    seq BIGINT NOT NULL DEFAULT nextval('stacks_transactions_seq_seq'),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_gateway_leaves_seq ON gateway_leaves(seq);
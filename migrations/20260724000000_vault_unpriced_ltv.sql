@@ -0,0 +1,3 @@
+-- [NEXUS-VAULT-01] A vault written before its collateral has an oracle price has no LTV;
+-- it is stored as NULL rather than as zero.
+ALTER TABLE vaults ALTER COLUMN ltv_bps DROP NOT NULL;
@@ -0,0 +1,12 @@
+-- [NEXUS-VAULT-02] Positions of the vault events the indexer has applied. An event whose
+-- position is listed is skipped, so a replayed block changes nothing while a block that
+-- arrives late still applies. `last_event_*` on `vaults` no longer gates events.
+CREATE TABLE IF NOT EXISTS applied_vault_events (
+    height BIGINT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    event_index INTEGER NOT NULL,
+    vault_id TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (height, tx_index, event_index)
+);
+CREATE INDEX IF NOT EXISTS idx_applied_vault_events_vault ON applied_vault_events(vault_id);
@@ -0,0 +1,7 @@
+-- [NEXUS-SYNC-03] Heights ingested above a gap in front of the `sync_progress` watermark.
+-- The watermark only moves over a contiguous run of ingested heights, so a height that
+-- was skipped or dead-lettered holds it until the height is ingested; rows at or below
+-- the watermark are deleted as it passes them.
+CREATE TABLE IF NOT EXISTS sync_processed_heights (
+    height BIGINT PRIMARY KEY
+);
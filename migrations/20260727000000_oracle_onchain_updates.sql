@@ -0,0 +1,13 @@
+-- [NEXUS-ORACLE-04] Oracle update calls confirmed on-chain, decoded from the synced
+-- blocks. Rates and PPP indices are fixed-point decimal strings keyed by currency.
+CREATE TABLE IF NOT EXISTS oracle_onchain_updates (
+    tx_id TEXT PRIMARY KEY,
+    height BIGINT NOT NULL,
+    rates JSONB NOT NULL,
+    ppp_indices JSONB NOT NULL,
+    timestamp BIGINT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oracle_onchain_updates_height
+    ON oracle_onchain_updates (height DESC, timestamp DESC);
@@ -0,0 +1,2 @@
+-- [NEXUS-STATE-07] Burn chain height at which each anchor was broadcast, so anchoring can run every N burn blocks. NULL on rows written before it was kept.
+ALTER TABLE anchors ADD COLUMN IF NOT EXISTS burn_height BIGINT;
@@ -0,0 +1,12 @@
+-- [NEXUS-SAFETY-01] One row per safety incident, from when its trigger was raised until
+-- it cleared. At most one incident per cause is open at a time.
+CREATE TABLE IF NOT EXISTS safety_incidents (
+    id BIGSERIAL PRIMARY KEY,
+    cause TEXT NOT NULL,
+    detail JSONB NOT NULL DEFAULT '{}'::jsonb,
+    opened_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_safety_incidents_open
+    ON safety_incidents (cause) WHERE resolved_at IS NULL;
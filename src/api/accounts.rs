@@ -0,0 +1,240 @@
+//! Per-account views over ingested transactions: a principal's latest transactions,
+//! each with its inclusion proof against the current state root, so wallets can show
+//! that recent actions are committed without one `/v1/proof` call per transaction.
+
+use crate::api::rest::AppState;
+use crate::stacks::StacksAddress;
+use crate::state::{canonical_leaf, MerkleProof, NexusState};
+use crate::storage::store::NexusStore;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Default and maximum transactions per inclusions call.
+pub const DEFAULT_INCLUSIONS_LIMIT: u32 = 20;
+pub const MAX_INCLUSIONS_LIMIT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InclusionStatus {
+    /// A leaf of the state tree; `proof` proves it against `root`.
+    Included,
+    /// Ingested but not yet folded into the state tree.
+    Pending,
+}
+
+impl InclusionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Included => "included",
+            Self::Pending => "pending",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inclusion {
+    pub tx_id: String,
+    pub block_hash: String,
+    pub ingested_at: DateTime<Utc>,
+    pub status: InclusionStatus,
+    pub proof: Option<MerkleProof>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountInclusions {
+    pub principal: String,
+    /// Root every proof is against.
+    pub root: String,
+    /// Block height recorded with `root` in the root history; `None` for a root sync
+    /// has not recorded, such as the empty tree's.
+    pub height: Option<u64>,
+    /// Newest first.
+    pub inclusions: Vec<Inclusion>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InclusionsParams {
+    pub limit: Option<u32>,
+}
+
+/// `raw` as a standard Stacks principal, the only kind that signs transactions.
+pub fn parse_principal(raw: &str) -> anyhow::Result<String> {
+    let principal = raw.trim();
+    principal
+        .parse::<StacksAddress>()
+        .map_err(|e| anyhow::anyhow!("Invalid principal '{}': {}", principal, e))?;
+    Ok(principal.to_string())
+}
+
+/// The `limit` most recent transactions sent by `principal` (default
+/// [`DEFAULT_INCLUSIONS_LIMIT`], at most [`MAX_INCLUSIONS_LIMIT`]), with proofs from one
+/// snapshot of the tree, built off the async runtime. An unknown principal yields an
+/// empty list.
+pub async fn account_inclusions(
+    store: &dyn NexusStore,
+    state: &Arc<NexusState>,
+    principal: &str,
+    limit: Option<u32>,
+) -> anyhow::Result<AccountInclusions> {
+    let limit = limit
+        .unwrap_or(DEFAULT_INCLUSIONS_LIMIT)
+        .clamp(1, MAX_INCLUSIONS_LIMIT);
+    let transactions = store.transactions_by_sender(principal, limit).await?;
+
+    let keys: Vec<String> = transactions.iter().map(|tx| tx.tx_id.clone()).collect();
+    let state = state.clone();
+    let mut batch =
+        tokio::task::spawn_blocking(move || state.generate_merkle_proofs(&keys)).await?;
+    // The watermark can move past the snapshot; the height recorded with its root cannot.
+    let height = store.root_height(&batch.root).await?;
+    let inclusions = transactions
+        .into_iter()
+        .map(|tx| {
+            let proof = batch.proofs.remove(&tx.tx_id);
+            Inclusion {
+                tx_id: canonical_leaf(&tx.tx_id).into_owned(),
+                block_hash: tx.block_hash,
+                ingested_at: tx.created_at,
+                status: if proof.is_some() {
+                    InclusionStatus::Included
+                } else {
+                    InclusionStatus::Pending
+                },
+                proof,
+            }
+        })
+        .collect();
+
+    Ok(AccountInclusions {
+        principal: principal.to_string(),
+        root: batch.root,
+        height,
+        inclusions,
+    })
+}
+
+pub fn accounts_routes() -> Router<AppState> {
+    Router::new().route("/{principal}/inclusions", get(inclusions_handler))
+}
+
+async fn inclusions_handler(
+    State(state): State<AppState>,
+    Path(principal): Path<String>,
+    Query(params): Query<InclusionsParams>,
+) -> Response {
+    let principal = match parse_principal(&principal) {
+        Ok(principal) => principal,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+    match account_inclusions(
+        state.storage.as_ref(),
+        &state.nexus_state,
+        &principal,
+        params.limit,
+    )
+    .await
+    {
+        Ok(inclusions) => Json(inclusions).into_response(),
+        Err(e) => {
+            tracing::warn!(principal = %principal, error = %e, "Account inclusions unavailable");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Account inclusions unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::verify_merkle_proof;
+    use crate::storage::repo::{NewBlock, NewTransaction};
+    use crate::storage::store::InMemoryStore;
+
+    const SENDER: &str = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+
+    fn sent(tx_id: &str, sender: &str) -> NewTransaction {
+        NewTransaction::decoded(tx_id, "0xm1", None, Some(sender.to_string()), &[])
+    }
+
+    #[tokio::test]
+    async fn test_inclusions_mix_included_and_pending_newest_first() {
+        let store = InMemoryStore::new();
+        store
+            .ingest_block(
+                &NewBlock::microblock("0xm1", 7),
+                &[
+                    sent("tx1", SENDER),
+                    sent("other", "SP000000000000000000002Q6VF78"),
+                    sent("tx2", SENDER),
+                    sent("tx3", SENDER),
+                ],
+            )
+            .await
+            .unwrap();
+        let state = Arc::new(NexusState::new());
+        // tx3 is ingested but not yet in the tree.
+        state.update_state_batch(&["tx1".to_string(), "other".to_string(), "tx2".to_string()]);
+        store
+            .record_state_root(&state.commitment(), 6)
+            .await
+            .unwrap();
+
+        let result = account_inclusions(&store, &state, SENDER, None)
+            .await
+            .unwrap();
+        assert_eq!(result.root, state.get_state_root());
+        // The root's own height, not the watermark that moved past it.
+        assert_eq!(result.height, Some(6));
+        let ids: Vec<&str> = result.inclusions.iter().map(|i| i.tx_id.as_str()).collect();
+        assert_eq!(ids, ["tx3", "tx2", "tx1"]);
+
+        assert_eq!(result.inclusions[0].status, InclusionStatus::Pending);
+        assert!(result.inclusions[0].proof.is_none());
+        for inclusion in &result.inclusions[1..] {
+            assert_eq!(inclusion.status, InclusionStatus::Included);
+            let proof = inclusion.proof.as_ref().unwrap();
+            assert_eq!(proof.root, result.root);
+            assert!(verify_merkle_proof(proof, state.tree_params()));
+        }
+
+        let limited = account_inclusions(&store, &state, SENDER, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(limited.inclusions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_sender_has_no_inclusions() {
+        let store = InMemoryStore::new();
+        let state = Arc::new(NexusState::new());
+        let result = account_inclusions(&store, &state, SENDER, Some(500))
+            .await
+            .unwrap();
+        assert!(result.inclusions.is_empty());
+        assert_eq!(result.principal, SENDER);
+        assert_eq!(result.height, None);
+    }
+
+    #[test]
+    fn test_principal_must_be_a_stacks_address() {
+        assert_eq!(parse_principal(&format!(" {} ", SENDER)).unwrap(), SENDER);
+        assert!(parse_principal("not-a-principal").is_err());
+    }
+}
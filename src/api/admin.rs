@@ -1,7 +1,12 @@
 #![allow(clippy::result_large_err)]
 
+use crate::signing::RotatingSigner;
+use crate::stacks::StacksSigner;
+use crate::state::snapshot::{self, InstallError, StateSnapshot};
+use crate::storage::store::NexusStore;
 use axum::{
-    extract::{Path, Query, State},
+    body::Body,
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::{Html, IntoResponse, Response},
     routing::{get, post},
@@ -12,7 +17,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 lazy_static::lazy_static! {
@@ -49,6 +54,17 @@ struct ClaimViewQuery {
     token: String,
 }
 
+#[derive(Deserialize)]
+struct DeadLetterQuery {
+    limit: Option<u32>,
+}
+
+/// Most dead letters returned by one request.
+const MAX_DEAD_LETTERS: u32 = 500;
+
+/// Largest snapshot accepted by `POST /snapshot`; a leaf is under 100 bytes of JSON.
+const MAX_SNAPSHOT_BODY_BYTES: usize = 256 * 1024 * 1024;
+
 #[derive(Serialize)]
 pub struct ProtectedStatusResponse {
     pub status: &'static str,
@@ -152,8 +168,21 @@ pub fn admin_routes(state: crate::api::rest::AppState) -> Router<crate::api::res
         .route("/attestations", get(list_attestations))
         .route("/attestations/{id}", get(get_attestation))
         .route("/drift", get(get_drift))
+        .route("/migrations", get(get_migrations))
+        .route("/config", get(get_config))
+        .route("/config/reload", post(reload_config))
+        .route("/rotate-key", post(rotate_key))
+        .route("/sync/dead-letter", get(list_dead_letters))
+        .route(
+            "/snapshot",
+            get(export_snapshot)
+                .post(import_snapshot)
+                .layer(DefaultBodyLimit::max(MAX_SNAPSHOT_BODY_BYTES)),
+        )
         .route("/safety-mode", get(get_safety_mode))
         .route("/safety-mode/ack", post(ack_safety_mode))
+        .route("/safety/trigger", post(trigger_safety_drill))
+        .route("/safety/clear", post(clear_safety_drill))
         .route("/promotion-evidence/{release}", get(get_promotion_evidence))
         .route("/environments", get(list_environments))
         .with_state(state)
@@ -262,7 +291,7 @@ fn admin_token_not_configured_response() -> Response {
         .into_response()
 }
 
-fn bearer_token(headers: &HeaderMap) -> Option<String> {
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<String> {
     headers
         .get(header::AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
@@ -311,7 +340,7 @@ fn authorize_admin_write(
     ))
 }
 
-fn authorize_for_scope(
+pub(crate) fn authorize_for_scope(
     state: &crate::api::rest::AppState,
     headers: &HeaderMap,
     required_scope: &str,
@@ -724,6 +753,289 @@ async fn get_drift(
     })))
 }
 
+/// Applied and pending schema migrations, for spotting binary/database skew.
+async fn get_migrations(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, Response> {
+    authorize_for_scope(&state, &headers, "api.read")?;
+    match state.storage.migration_status().await {
+        Ok(status) => Ok(Json(json!(status))),
+        Err(e) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response()),
+    }
+}
+
+/// The effective configuration, secrets redacted: boot settings as loaded, with the
+/// reloadable ones as currently in force.
+async fn get_config(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, Response> {
+    authorize_for_scope(&state, &headers, "api.read")?;
+    let mut config = state.config.redacted();
+    if let (Some(fields), Ok(Value::Object(dynamic))) = (
+        config.as_object_mut(),
+        serde_json::to_value(&*state.dynamic_config.get()),
+    ) {
+        fields.extend(dynamic);
+    }
+    Ok(Json(config))
+}
+
+/// Re-reads the config file and environment and applies the reloadable settings, or
+/// rejects the reload wholesale with every validation error.
+async fn reload_config(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, Response> {
+    authorize_admin_write(&state, &headers)?;
+    match state.dynamic_config.reload() {
+        Ok(changed) => Ok(Json(json!({ "reloaded": true, "changed": changed }))),
+        Err(e) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": "Invalid configuration", "errors": e.errors })),
+        )
+            .into_response()),
+    }
+}
+
+#[derive(Deserialize)]
+struct RotateKeyRequest {
+    /// `rebalance`, `fee_payout` or `oracle`.
+    key: String,
+}
+
+/// A signer `POST /admin/v1/rotate-key` can replace.
+enum RotatableSigner<'a> {
+    Message(&'a Arc<RotatingSigner>),
+    Stacks(&'a Arc<RotatingSigner<StacksSigner>>),
+}
+
+impl RotatableSigner<'_> {
+    fn public_key(&self) -> Option<String> {
+        match self {
+            Self::Message(signer) => signer.public_key(),
+            Self::Stacks(signer) => signer.public_key(),
+        }
+    }
+
+    /// Swaps in the key `private_key_hex`, returning the previous public key.
+    fn rotate(&self, private_key_hex: &str) -> anyhow::Result<Option<String>> {
+        match self {
+            Self::Message(signer) => {
+                let (wallet, public_key) = crate::signing::wallet_from_hex(private_key_hex)?;
+                Ok(signer.rotate(wallet, Some(public_key)))
+            }
+            Self::Stacks(signer) => {
+                Ok(signer.rotate_stacks(StacksSigner::from_hex(private_key_hex.trim())?))
+            }
+        }
+    }
+}
+
+/// Replaces a signing key in place with the one now in the configuration file:
+/// signatures already being made finish with the old key and every later one uses the
+/// new key, without a restart. The key never travels over the API, and the file stays
+/// the source of truth across restarts. A key set in the environment wins over the
+/// file, so it can only change with a restart.
+async fn rotate_key(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RotateKeyRequest>,
+) -> Result<Json<Value>, Response> {
+    authorize_admin_write(&state, &headers)?;
+    let signer = match payload.key.as_str() {
+        "rebalance" => state
+            .executor
+            .rebalance_signer
+            .as_ref()
+            .map(RotatableSigner::Stacks),
+        "fee_payout" => state.gateway.payout_signer().map(RotatableSigner::Message),
+        "oracle" => state
+            .oracle
+            .as_ref()
+            .and_then(|oracle| oracle.push_signer())
+            .map(RotatableSigner::Stacks),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "key must be rebalance, fee_payout or oracle" })),
+            )
+                .into_response())
+        }
+    }
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": format!("No {} signer is configured", payload.key) })),
+        )
+            .into_response()
+    })?;
+
+    let unprocessable = |error: String| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": error })),
+        )
+            .into_response()
+    };
+    let loaded = state.dynamic_config.load().map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": "Invalid configuration", "errors": e.errors })),
+        )
+            .into_response()
+    })?;
+    let (field, private_key) = match payload.key.as_str() {
+        "rebalance" => (
+            "rebalance_private_key_hex",
+            loaded.config.rebalance_private_key_hex,
+        ),
+        "fee_payout" => (
+            "fee_payout_private_key_hex",
+            loaded.config.fee_payout_private_key_hex,
+        ),
+        // The oracle signs with the node wallet when it has no key of its own.
+        _ => (
+            "oracle_private_key",
+            loaded
+                .config
+                .oracle_private_key
+                .or(loaded.config.rebalance_private_key_hex),
+        ),
+    };
+    let private_key =
+        private_key.ok_or_else(|| unprocessable(format!("{} is not configured", field)))?;
+    let public_key = StacksSigner::from_hex(private_key.trim())
+        .map(|next| next.public_key_hex())
+        .map_err(|e| unprocessable(format!("{}: {:#}", field, e)))?;
+    if signer.public_key().as_deref() == Some(public_key.as_str()) {
+        return Err(unprocessable(format!(
+            "{} is unchanged; update the configuration file first",
+            field
+        )));
+    }
+    let previous = signer
+        .rotate(&private_key)
+        .map_err(|e| unprocessable(format!("{}: {:#}", field, e)))?;
+
+    tracing::warn!(
+        key = %payload.key,
+        previous_public_key = previous.as_deref().unwrap_or("ephemeral"),
+        public_key = %public_key,
+        "Signing key rotated by operator"
+    );
+    Ok(Json(json!({
+        "key": payload.key,
+        "previous_public_key": previous,
+        "public_key": public_key,
+        "timestamp": current_timestamp()
+    })))
+}
+
+/// Sync events that failed every attempt, newest first.
+async fn list_dead_letters(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<Value>, Response> {
+    authorize_for_scope(&state, &headers, "api.read")?;
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_DEAD_LETTERS);
+    match state.storage.dead_letters(limit).await {
+        Ok(letters) => Ok(Json(json!({ "dead_letters": letters }))),
+        Err(e) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response()),
+    }
+}
+
+/// The state tree as a snapshot file, signed with `SNAPSHOT_PRIVATE_KEY` when set, for
+/// bootstrapping another node. The leaves are streamed in chunks.
+async fn export_snapshot(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    authorize_for_scope(&state, &headers, "api.read")?;
+    let unavailable = |e: anyhow::Error| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response()
+    };
+    let signer = crate::cli::snapshot_signer(&state.config).map_err(unavailable)?;
+    let snapshot = snapshot::export(&*state.storage, &state.nexus_state, signer.as_ref())
+        .await
+        .map_err(unavailable)?;
+    let filename = format!(
+        "nexus-snapshot-{}.json",
+        snapshot
+            .height
+            .map_or("genesis".to_string(), |h| h.to_string())
+    );
+    let chunks = snapshot.into_json_chunks().map_err(unavailable)?;
+    let body = Body::from_stream(futures_util::stream::iter(
+        chunks.map(Ok::<_, std::convert::Infallible>),
+    ));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Installs a snapshot exported by a trusted node as the base of the state tree; sync
+/// resumes after its height. 403 when it is not signed by one of `SNAPSHOT_TRUSTED_KEYS`,
+/// 409 when this node already has leaves, 422 when its version, schema, arity or leaf
+/// hashing differ from this node's or its leaves do not reproduce its root.
+async fn import_snapshot(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+    Json(snapshot): Json<StateSnapshot>,
+) -> Result<Json<Value>, Response> {
+    authorize_admin_write(&state, &headers)?;
+    if let Err(e) = snapshot::install(
+        &*state.storage,
+        &state.nexus_state,
+        &snapshot,
+        &state.config.snapshot_trusted_keys,
+    )
+    .await
+    {
+        let status = match e {
+            InstallError::Untrusted(_) => StatusCode::FORBIDDEN,
+            InstallError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            InstallError::NotEmpty => StatusCode::CONFLICT,
+            InstallError::Failed(_) => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        return Err((status, Json(json!({ "error": e.to_string() }))).into_response());
+    }
+    tracing::info!(
+        root = %snapshot.root,
+        height = ?snapshot.height,
+        leaves = snapshot.leaves.len(),
+        "State snapshot installed"
+    );
+    Ok(Json(json!({
+        "installed": true,
+        "root": snapshot.root,
+        "height": snapshot.height,
+        "leaves": snapshot.leaves.len(),
+    })))
+}
+
 async fn get_safety_mode(
     State(state): State<crate::api::rest::AppState>,
     headers: HeaderMap,
@@ -732,8 +1044,20 @@ async fn get_safety_mode(
     let active = crate::safety::is_safety_mode_active(&state.storage)
         .await
         .unwrap_or(false);
+    let l1_unreachable = crate::safety::is_l1_unreachable(&state.storage)
+        .await
+        .unwrap_or(false);
+    let oracle_stale = crate::safety::is_oracle_stale(&state.storage)
+        .await
+        .unwrap_or(false);
+    let reason = crate::safety::safety_reason(&state.storage)
+        .await
+        .unwrap_or(None);
     Ok(Json(json!({
         "active": active,
+        "reason": reason,
+        "l1_unreachable": l1_unreachable,
+        "oracle_stale": oracle_stale,
         "triggered_at": Value::Null
     })))
 }
@@ -749,6 +1073,86 @@ async fn ack_safety_mode(
     })))
 }
 
+#[derive(Deserialize)]
+struct SafetyDrillRequest {
+    reason: String,
+}
+
+/// Maximum length of an operator-supplied drill reason.
+const MAX_DRILL_REASON_LEN: usize = 256;
+
+/// Enters Safety Mode as a Sovereign Handoff fire-drill, recorded as `manual_drill`.
+async fn trigger_safety_drill(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SafetyDrillRequest>,
+) -> Result<Json<Value>, Response> {
+    authorize_admin_write(&state, &headers)?;
+    let reason = payload.reason.trim();
+    if reason.is_empty() || reason.len() > MAX_DRILL_REASON_LEN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "reason must be 1-256 characters" })),
+        )
+            .into_response());
+    }
+
+    crate::safety::start_safety_drill(&state.storage, reason)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to start safety drill");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "error": "Failed to start safety drill" })),
+            )
+                .into_response()
+        })?;
+    tracing::warn!(reason, "Safety drill started by operator");
+
+    Ok(Json(json!({
+        "active": true,
+        "reason": crate::safety::SafetyTrigger::ManualDrill.as_str(),
+        "note": reason,
+        "timestamp": current_timestamp()
+    })))
+}
+
+/// Ends a fire-drill; refuses to clear Safety Mode raised by a real fault.
+async fn clear_safety_drill(
+    State(state): State<crate::api::rest::AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, Response> {
+    authorize_admin_write(&state, &headers)?;
+    let cleared = crate::safety::end_safety_drill(&state.storage)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to clear safety drill");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "error": "Failed to clear safety drill" })),
+            )
+                .into_response()
+        })?;
+    if !cleared {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "No safety drill is active" })),
+        )
+            .into_response());
+    }
+    tracing::info!("Safety drill cleared by operator");
+
+    // A fault that held Safety Mode before the drill holds it again.
+    let reason = crate::safety::safety_reason(&state.storage)
+        .await
+        .unwrap_or_default();
+    Ok(Json(json!({
+        "active": reason.is_some(),
+        "reason": reason,
+        "timestamp": current_timestamp()
+    })))
+}
+
 async fn get_promotion_evidence(
     State(state): State<crate::api::rest::AppState>,
     headers: HeaderMap,
@@ -1014,6 +1418,123 @@ mod tests {
     fn test_hash_value_changes_output() {
         assert_ne!(hash_value("a"), "a");
     }
+
+    const REBALANCE_KEY: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const NEXT_REBALANCE_KEY: &str =
+        "0202020202020202020202020202020202020202020202020202020202020202";
+
+    /// State whose rebalance signer holds `REBALANCE_KEY` and whose configuration file
+    /// holds `file`.
+    fn rotate_key_state(file: &str) -> (crate::api::rest::AppState, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("nexus-rotate-{}.toml", Uuid::new_v4()));
+        std::fs::write(&path, file).unwrap();
+        let mut config = crate::config::Config::default_test();
+        config.admin_api_token = Some("admin-token".to_string());
+        let signer = Arc::new(RotatingSigner::stacks(
+            StacksSigner::from_hex(REBALANCE_KEY).unwrap(),
+        ));
+        let executor = crate::executor::NexusExecutor::new(
+            crate::storage::Storage::for_tests(),
+            crate::executor::rgb::RGBRolloutMode::Disabled,
+            HashSet::new(),
+        )
+        .with_rebalance_signer(signer);
+        let state = crate::api::rest::AppState {
+            storage: crate::storage::Storage::for_tests(),
+            nexus_state: Arc::new(crate::state::NexusState::new()),
+            executor: Arc::new(executor),
+            oracle: None,
+            tableland: Arc::new(crate::storage::tableland::TablelandAdapter::new(
+                crate::storage::Storage::for_tests(),
+                "http://localhost".to_string(),
+            )),
+            kwil: None,
+            nostr: None,
+            gateway: Arc::new(crate::gateway::ServiceRegistry::with_builtin_services()),
+            gateway_url: None,
+            http_client: reqwest::Client::new(),
+            config: Arc::new(config),
+            dynamic_config: crate::config::dynamic::DynamicConfigHandle::default()
+                .with_config_path(Some(path.clone())),
+            metrics_feed: Default::default(),
+        };
+        (state, path)
+    }
+
+    fn admin_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer admin-token".parse().unwrap());
+        headers
+    }
+
+    async fn rotate(
+        state: &crate::api::rest::AppState,
+        headers: HeaderMap,
+        key: &str,
+    ) -> Result<Value, StatusCode> {
+        rotate_key(
+            State(state.clone()),
+            headers,
+            Json(RotateKeyRequest {
+                key: key.to_string(),
+            }),
+        )
+        .await
+        .map(|Json(body)| body)
+        .map_err(|response| response.status())
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_reloads_the_key_from_the_configuration_file() {
+        let file = format!("rebalance_private_key_hex = \"{}\"\n", NEXT_REBALANCE_KEY);
+        let (state, path) = rotate_key_state(&file);
+        let signer = state.executor.rebalance_signer.clone().unwrap();
+        let previous = signer.public_key();
+
+        let body = rotate(&state, admin_headers(), "rebalance").await.unwrap();
+        let expected = StacksSigner::from_hex(NEXT_REBALANCE_KEY)
+            .unwrap()
+            .public_key_hex();
+        assert_eq!(body["public_key"], expected.as_str());
+        assert_eq!(body["previous_public_key"], json!(previous));
+        assert_eq!(signer.public_key().as_deref(), Some(expected.as_str()));
+
+        // The file now matches the signer, so a second rotation has nothing to do.
+        assert_eq!(
+            rotate(&state, admin_headers(), "rebalance").await,
+            Err(StatusCode::UNPROCESSABLE_ENTITY)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_rejects_bad_requests() {
+        let (state, path) = rotate_key_state("");
+        assert_eq!(
+            rotate(&state, HeaderMap::new(), "rebalance").await,
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            rotate(&state, admin_headers(), "anchor").await,
+            Err(StatusCode::BAD_REQUEST)
+        );
+        assert_eq!(
+            rotate(&state, admin_headers(), "oracle").await,
+            Err(StatusCode::NOT_FOUND)
+        );
+        // Nothing to rotate to: the file has no rebalance key.
+        assert_eq!(
+            rotate(&state, admin_headers(), "rebalance").await,
+            Err(StatusCode::UNPROCESSABLE_ENTITY)
+        );
+
+        std::fs::write(&path, "rebalance_private_key_hex = \"not-hex\"\n").unwrap();
+        assert_eq!(
+            rotate(&state, admin_headers(), "rebalance").await,
+            Err(StatusCode::UNPROCESSABLE_ENTITY)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
 }
 
 #[cfg(test)]
@@ -1148,9 +1669,12 @@ mod cryptographic_hardening_tests {
             )),
             kwil: None,
             nostr: None,
+            gateway: std::sync::Arc::new(crate::gateway::ServiceRegistry::with_builtin_services()),
             gateway_url: None,
             http_client: reqwest::Client::new(),
             config: std::sync::Arc::new(config),
+            dynamic_config: Default::default(),
+            metrics_feed: Default::default(),
         };
 
         let response = login_handler(State(state), Json(login_req_with_sigs)).await;
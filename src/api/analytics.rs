@@ -10,7 +10,6 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
 
 #[derive(Debug, Deserialize)]
 pub struct AnalyticsParams {
@@ -48,82 +47,22 @@ pub async fn get_metrics_handler(
 
     let days = params.days.unwrap_or(7).clamp(1, 365);
 
-    let mut values = Vec::new();
-
-    match params.metric.as_str() {
-        "tx_count" | "tx_volume" => {
-            let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(
-                "SELECT to_char(date_trunc('day', created_at), 'YYYY-MM-DD') as day, COUNT(*) as count
-                 FROM stacks_transactions
-                 WHERE created_at >= NOW() - INTERVAL '1 day' * $1::int
-                 GROUP BY 1 ORDER BY 1 ASC",
-            )
-            .bind(days as i32)
-            .fetch_all(&state.storage.pg_pool)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-            for row in rows {
-                let day: String = row.get("day");
-                let count: i64 = row.get("count");
-                values.push(DataPoint {
-                    timestamp: day,
-                    value: count as f64,
-                });
-            }
-        }
-        "active_senders" => {
-            let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(
-                "SELECT to_char(date_trunc('day', created_at), 'YYYY-MM-DD') as day, COUNT(DISTINCT sender) as count
-                 FROM stacks_transactions
-                 WHERE created_at >= NOW() - INTERVAL '1 day' * $1::int
-                 GROUP BY 1 ORDER BY 1 ASC",
-            )
-            .bind(days as i32)
-            .fetch_all(&state.storage.pg_pool)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-            for row in rows {
-                let day: String = row.get("day");
-                let count: i64 = row.get("count");
-                values.push(DataPoint {
-                    timestamp: day,
-                    value: count as f64,
-                });
-            }
-        }
-        "whale_distribution" => {
-            let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(
-                "SELECT
-                    CASE
-                        WHEN count >= 100 THEN 'Whale'
-                        WHEN count >= 10 THEN 'Shark'
-                        ELSE 'Shrimp'
-                    END as tier,
-                    COUNT(*) as count
-                 FROM (
-                    SELECT sender, COUNT(*) as count
-                    FROM stacks_transactions
-                    GROUP BY sender
-                 ) as counts
-                 GROUP BY 1",
-            )
-            .fetch_all(&state.storage.pg_pool)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-            for row in rows {
-                let tier: String = row.get("tier");
-                let count: i64 = row.get("count");
-                values.push(DataPoint {
-                    timestamp: tier,
-                    value: count as f64,
-                });
-            }
-        }
+    let repo = state.storage.read_repo();
+    let rows = match params.metric.as_str() {
+        "tx_count" | "tx_volume" => repo.daily_transaction_counts(days as i32).await,
+        "active_senders" => repo.daily_active_senders(days as i32).await,
+        "whale_distribution" => repo.sender_tiers().await,
         _ => return Err(StatusCode::BAD_REQUEST),
     }
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let values = rows
+        .into_iter()
+        .map(|(timestamp, count)| DataPoint {
+            timestamp,
+            value: count as f64,
+        })
+        .collect();
 
     Ok(Json(AnalyticsResponse {
         asset,
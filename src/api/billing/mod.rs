@@ -3,8 +3,16 @@
 
 use crate::api::rest::AppState;
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::post, Json, Router};
-use chrono::Utc;
+use crate::gateway::fees::FeeSummary;
+use crate::storage::keys;
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
 use hmac::{Hmac, KeyInit, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -51,10 +59,24 @@ pub struct TelemetryResponse {
     pub efficiency: Option<f32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FeesQuery {
+    /// RFC 3339 lower bound on `created_at`; all time when omitted.
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeesResponse {
+    pub since: Option<DateTime<Utc>>,
+    pub total_amount: u64,
+    pub callers: Vec<FeeSummary>,
+}
+
 pub fn billing_routes() -> Router<AppState> {
     Router::new()
         .route("/generate-key", post(generate_developer_key))
         .route("/telemetry/track-signature", post(track_signature))
+        .route("/fees", get(list_fees))
 }
 
 #[derive(Debug, PartialEq)]
@@ -173,12 +195,7 @@ async fn generate_developer_key(
         )
     };
 
-    let mut conn = match state
-        .storage
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await
-    {
+    let mut conn = match state.storage.redis().await {
         Ok(c) => c,
         Err(e) => {
             tracing::error!("Failed to connect to Redis: {}", e);
@@ -186,8 +203,8 @@ async fn generate_developer_key(
         }
     };
 
-    let redis_key = format!("apikey:{}", api_key);
-    let _: redis::RedisResult<()> = redis::cmd("HSET")
+    let redis_key = state.storage.redis_key(&keys::api_key(&api_key));
+    let stored: redis::RedisResult<()> = redis::cmd("HSET")
         .arg(&redis_key)
         .arg("org_id")
         .arg(organization_id)
@@ -201,6 +218,9 @@ async fn generate_developer_key(
         .arg(0)
         .query_async(&mut conn)
         .await;
+    if let Err(e) = &stored {
+        state.storage.note_redis_error(e);
+    }
 
     Json(GenerateKeyResponse {
         api_key,
@@ -217,21 +237,17 @@ async fn track_signature(
     State(state): State<AppState>,
     Json(payload): Json<TelemetryRequest>,
 ) -> impl IntoResponse {
-    let mut conn = match state
-        .storage
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await
-    {
+    let mut conn = match state.storage.redis().await {
         Ok(c) => c,
         Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     };
 
-    let redis_key = format!("apikey:{}", payload.api_key);
+    let redis_key = state.storage.redis_key(&keys::api_key(&payload.api_key));
     let data: std::collections::HashMap<String, String> = redis::cmd("HGETALL")
         .arg(&redis_key)
         .query_async(&mut conn)
         .await
+        .inspect_err(|e| state.storage.note_redis_error(e))
         .unwrap_or_default();
 
     match validate_telemetry_auth(&data, &payload) {
@@ -315,6 +331,36 @@ async fn track_signature(
     .into_response()
 }
 
+/// [NEXUS-GW-08] Accrued gateway service fees per caller (admin).
+async fn list_fees(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FeesQuery>,
+) -> Response {
+    if let Err(unauthorized) =
+        crate::api::admin::authorize_for_scope(&state, &headers, "admin.write")
+    {
+        return unauthorized;
+    }
+
+    match state.gateway.fee_summary(query.since).await {
+        Ok(callers) => Json(FeesResponse {
+            since: query.since,
+            total_amount: callers.iter().map(|c| c.total_amount).sum(),
+            callers,
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to load service fee summary");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to load service fees" })),
+            )
+                .into_response()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,7 +2,7 @@
 //! Publishes and consumes signed telemetry events from a Nostr relay.
 //! Updated for nostr-sdk v0.43.0.
 
-use crate::storage::Storage;
+use crate::storage::{keys, Storage};
 use anyhow::{anyhow, Context};
 use nostr_sdk::prelude::*;
 use serde_json::json;
@@ -203,14 +203,10 @@ impl NostrCollector {
             api_key
         );
 
-        let mut conn = self
-            .storage
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
+        let mut conn = self.storage.redis().await?;
 
         // 2. Deduplication check using Redis
-        let dedup_key = dedup_key_for_event(&event_id);
+        let dedup_key = self.storage.redis_key(&dedup_key_for_event(&event_id));
         let is_new: bool = redis::cmd("SET")
             .arg(&dedup_key)
             .arg(1)
@@ -226,13 +222,14 @@ impl NostrCollector {
             return Ok(());
         }
 
-        let redis_key = format!("apikey:{}", api_key);
+        let redis_key = self.storage.redis_key(&keys::api_key(&api_key));
 
         // 3. Check if API Key exists
         let exists: bool = redis::cmd("EXISTS")
             .arg(&redis_key)
             .query_async::<bool>(&mut conn)
-            .await?;
+            .await
+            .inspect_err(|e| self.storage.note_redis_error(e))?;
         match determine_bridge_action(true, exists) {
             BridgeAction::Bridge => {}
             BridgeAction::IgnoreDuplicate => unreachable!("is_new is true in this branch"),
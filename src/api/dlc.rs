@@ -89,18 +89,17 @@ pub async fn create_dlc_bond_handler(
     let dlc_contract_id = format!("dlc_{}", Uuid::new_v4());
 
     // 3. Persist Bond State
-    let mut conn = match state
-        .storage
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await
-    {
+    let mut conn = match state.storage.redis().await {
         Ok(c) => c,
         Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Redis Error").into_response(),
     };
 
     let _: () = redis::cmd("HSET")
-        .arg(format!("dlc_bond:{}", dlc_contract_id))
+        .arg(
+            state
+                .storage
+                .redis_key(&format!("dlc_bond:{}", dlc_contract_id)),
+        )
         .arg("bond_id")
         .arg(&payload.bond_id)
         .arg("principal")
@@ -111,6 +110,7 @@ pub async fn create_dlc_bond_handler(
         .arg(&oracle_announcement)
         .query_async(&mut conn)
         .await
+        .inspect_err(|e| state.storage.note_redis_error(e))
         .unwrap_or(());
 
     // 4. Return initialized bond details
@@ -250,9 +250,12 @@ mod tests {
             tableland,
             kwil: None,
             nostr: None,
+            gateway: Arc::new(crate::gateway::ServiceRegistry::with_builtin_services()),
             gateway_url: None,
             http_client: reqwest::Client::new(),
             config,
+            dynamic_config: Default::default(),
+            metrics_feed: Default::default(),
         }
     }
 
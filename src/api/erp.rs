@@ -14,7 +14,7 @@ use std::collections::HashMap;
 type HmacSha256 = Hmac<Sha256>;
 
 const MAX_ERP_TX_IDS: usize = 1000;
-const ERP_ATTESTATION_REPLAY_PREFIX: &str = "nexus:erp:attestation:nonce:v1";
+const ERP_ATTESTATION_REPLAY_PREFIX: &str = "erp:attestation:nonce:v1";
 const ERP_ATTESTATION_MAX_CLOCK_SKEW_SECONDS: i64 = 60;
 const ERP_ATTESTATION_MAX_LIFETIME_SECONDS: i64 = 600;
 const ERP_ATTESTATION_SIGNATURE_HEX_LEN: usize = 64;
@@ -341,15 +341,14 @@ async fn claim_attestation_nonce(
     replay_key: &str,
     replay_ttl_seconds: u64,
 ) -> Result<(), ErpAttestationError> {
-    let mut conn: redis::aio::MultiplexedConnection = state
+    let mut conn = state
         .storage
-        .redis_client
-        .get_multiplexed_async_connection()
+        .redis()
         .await
         .map_err(|_| ErpAttestationError::ReplayStoreUnavailable)?;
 
     let claim_result: redis::RedisResult<Option<String>> = redis::cmd("SET")
-        .arg(replay_key)
+        .arg(state.storage.redis_key(replay_key))
         .arg("1")
         .arg("NX")
         .arg("EX")
@@ -360,7 +359,10 @@ async fn claim_attestation_nonce(
     let claimed = match claim_result {
         Ok(Some(_)) => true,
         Ok(None) => false,
-        Err(_) => return Err(ErpAttestationError::ReplayStoreUnavailable),
+        Err(e) => {
+            state.storage.note_redis_error(&e);
+            return Err(ErpAttestationError::ReplayStoreUnavailable);
+        }
     };
 
     ensure_attestation_not_replayed(claimed)
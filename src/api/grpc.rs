@@ -1,13 +1,26 @@
-use crate::executor::{ExecutionRequest, NexusExecutor};
+use crate::api::tls::TlsMaterial;
+use crate::executor::idempotency::{self, IdempotencyCheck, IdempotentOutcome};
+use crate::executor::queue::QueueError;
+use crate::executor::{
+    ExecutionFilter, ExecutionRecord, ExecutionRequest, ExecutionStatus, NexusExecutor,
+};
+use crate::oracle::OracleService;
 use crate::state::NexusState;
-use crate::storage::Storage;
+use crate::storage::{keys, Storage};
+use axum::http;
 use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tonic::{Request, Response, Status};
 
 // Proto generated code
+// `ExecuteRequest.timestamp` is deprecated in the schema but still generated.
+#[allow(deprecated)]
 pub mod proto {
     tonic::include_proto!("nexus");
 }
@@ -15,49 +28,162 @@ pub mod proto {
 use proto::nexus_service_server::NexusService;
 use proto::*;
 
-/// gRPC interceptor for authentication
-/// Checks for valid API key in metadata
-pub async fn grpc_auth_interceptor(req: tonic::Request<()>) -> Result<(), Status> {
-    let metadata = req.metadata();
-
-    // Check for API key in metadata
-    let api_key = metadata
-        .get("x-api-key")
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| {
-            tracing::warn!("gRPC request missing API key");
-            Status::unauthenticated("Missing API key")
-        })?;
+/// Methods that only read state; callable without a key when `GRPC_OPEN_READS` is set.
+const READ_ONLY_METHODS: &[&str] = &[
+    "GetProof",
+    "VerifyState",
+    "GetStatus",
+    "GetMetrics",
+    "GetServices",
+    "GetOracleState",
+    "GetVersion",
+    "GetAccountInclusions",
+    "GetExecution",
+    "ListExecutions",
+];
+
+/// `ListExecutions` page size when the request leaves it unset, and its cap.
+const DEFAULT_EXECUTIONS_PAGE: u32 = 20;
+const MAX_EXECUTIONS_PAGE: u32 = 100;
 
-    // Validate API key format (should be UUID or hex string)
-    if api_key.len() < 16 {
-        tracing::warn!("gRPC request with invalid API key format");
-        return Err(Status::unauthenticated("Invalid API key format"));
+/// `RESOURCE_EXHAUSTED` for a full sequencer queue, `DEADLINE_EXCEEDED` for an expired
+/// request; the message carries the queue depth and estimated drain time.
+fn queue_status(e: &anyhow::Error) -> Status {
+    match e.downcast_ref::<QueueError>() {
+        Some(QueueError::Full { .. }) => Status::resource_exhausted(e.to_string()),
+        Some(QueueError::Expired { .. }) => Status::deadline_exceeded(e.to_string()),
+        None => Status::internal(e.to_string()),
     }
+}
 
-    // TODO: Validate against credential store
-    // For now, accept any non-empty key for development
-    if api_key == "dev-mode" {
-        tracing::debug!("gRPC dev-mode authentication bypassed");
-        return Ok(());
+/// The standard gRPC health service, left open so orchestrator probes need no key.
+const HEALTH_SERVICE_PREFIX: &str = "/grpc.health.v1.Health/";
+
+/// Whether a call to `path` (`/nexus.NexusService/<Method>`) must carry an API key.
+fn requires_api_key(path: &str, open_reads: bool) -> bool {
+    if path.starts_with(HEALTH_SERVICE_PREFIX) {
+        return false;
     }
+    let method = path.rsplit('/').next().unwrap_or_default();
+    !(open_reads && READ_ONLY_METHODS.contains(&method))
+}
 
-    // TODO: Check against persistent credential store (Redis/PostgreSQL)
-    // For production, validate against stored credentials
+/// Billing API key from the `authorization` metadata, with or without `Bearer `.
+fn api_key_from_metadata(headers: &http::HeaderMap) -> Option<&str> {
+    let value = headers
+        .get(http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .trim();
+    let key = value.strip_prefix("Bearer ").unwrap_or(value).trim();
+    (!key.is_empty()).then_some(key)
+}
 
-    tracing::debug!("gRPC request authenticated");
+/// Checks the caller's key against the billing key store ([`keys::api_key`] in Redis).
+async fn authorize_call(
+    storage: &Storage,
+    path: &str,
+    headers: &http::HeaderMap,
+    open_reads: bool,
+) -> Result<(), Status> {
+    if !requires_api_key(path, open_reads) {
+        return Ok(());
+    }
+    let Some(api_key) = api_key_from_metadata(headers) else {
+        tracing::warn!(path, "gRPC request missing API key");
+        return Err(Status::unauthenticated("Missing API key"));
+    };
+    let exists = storage.api_key_exists(api_key).await.map_err(|e| {
+        tracing::error!(error = %e, path, "Redis unavailable for gRPC authentication");
+        Status::unavailable("Credential store unavailable")
+    })?;
+    if !exists {
+        tracing::warn!(path, "gRPC request with unknown API key");
+        return Err(Status::unauthenticated("Invalid API key"));
+    }
     Ok(())
 }
 
+/// Rejects unauthenticated calls before they reach the service. Tonic interceptors are
+/// synchronous, so the Redis lookup runs in this tower layer instead.
+#[derive(Clone)]
+pub struct GrpcAuthLayer {
+    storage: Arc<Storage>,
+    open_reads: bool,
+}
+
+impl GrpcAuthLayer {
+    pub fn new(storage: Arc<Storage>, open_reads: bool) -> Self {
+        Self {
+            storage,
+            open_reads,
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for GrpcAuthLayer {
+    type Service = GrpcAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcAuth {
+            inner,
+            storage: self.storage.clone(),
+            open_reads: self.open_reads,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GrpcAuth<S> {
+    inner: S,
+    storage: Arc<Storage>,
+    open_reads: bool,
+}
+
+impl<S, B, ResBody> tower::Service<http::Request<B>> for GrpcAuth<S>
+where
+    S: tower::Service<http::Request<B>, Response = http::Response<ResBody>>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // The clone may not be ready; keep the instance `poll_ready` was called on.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let storage = self.storage.clone();
+        let open_reads = self.open_reads;
+        Box::pin(async move {
+            if let Err(status) =
+                authorize_call(&storage, req.uri().path(), req.headers(), open_reads).await
+            {
+                return Ok(status.into_http());
+            }
+            inner.call(req).await
+        })
+    }
+}
+
 /// GrpcService with authentication
 pub struct NexusGrpcService {
     pub storage: Arc<Storage>,
     pub nexus_state: Arc<NexusState>,
     pub executor: Arc<NexusExecutor>,
+    pub oracle: Option<Arc<OracleService>>,
     /// Whether to skip authentication (development only)
     pub skip_auth: bool,
     metrics_counts_cache: MetricsCountsCache,
-    redis_conn: Mutex<Option<redis::aio::MultiplexedConnection>>,
 }
 
 const METRICS_COUNTS_CACHE_TTL: Duration = Duration::from_secs(10);
@@ -81,6 +207,23 @@ impl MetricsCountsCache {
 }
 
 impl NexusGrpcService {
+    pub fn new(
+        storage: Arc<Storage>,
+        nexus_state: Arc<NexusState>,
+        executor: Arc<NexusExecutor>,
+        oracle: Option<Arc<OracleService>>,
+        skip_auth: bool,
+    ) -> Self {
+        Self {
+            storage,
+            nexus_state,
+            executor,
+            oracle,
+            skip_auth,
+            metrics_counts_cache: MetricsCountsCache::new(),
+        }
+    }
+
     async fn read_fresh_cached_metrics_counts(&self) -> Option<(u64, u64)> {
         let cache_guard = self.metrics_counts_cache.state.lock().await;
         if let Some((cached_at, cached_tx_count, cached_block_count)) = cache_guard.value {
@@ -92,21 +235,12 @@ impl NexusGrpcService {
         None
     }
     async fn fetch_metrics_counts(&self) -> Result<(u64, u64), Status> {
-        let (tx_count, block_count): (i64, i64) = sqlx::query_as(
-            "SELECT \
-                    (SELECT COUNT(*) FROM stacks_transactions t \
-                     JOIN stacks_blocks b ON t.block_hash = b.hash \
-                     WHERE b.state != 'orphaned') AS tx_count, \
-                    (SELECT COUNT(*) FROM stacks_blocks WHERE state != 'orphaned') AS block_count",
-        )
-        .fetch_one(&self.storage.pg_pool)
-        .await
-        .map_err(|e| {
+        let counts = self.storage.read_repo().chain_counts().await.map_err(|e| {
             tracing::error!(error = %e, "Database error in GetMetrics (counts)");
             Status::internal("Database error in GetMetrics")
         })?;
 
-        Ok((tx_count as u64, block_count as u64))
+        Ok((counts.transactions, counts.blocks))
     }
 
     async fn read_cached_metrics_counts(&self) -> Result<(u64, u64), Status> {
@@ -128,91 +262,35 @@ impl NexusGrpcService {
 
     /// Reads safety-mode and drift flags from Redis.
     ///
-    /// If Redis is unavailable, this logs the error and returns `(true, 0)` to
-    /// keep read-only health/metrics RPCs available while remaining conservative.
-    ///
-    /// In this fallback, `drift=0` means "unknown (Redis unavailable)" and must
-    /// not be interpreted as "in-sync". This helper is intended only for the
-    /// read-only `GetStatus`/`GetMetrics` paths.
-    async fn read_safety_flags(&self, context: &str) -> Result<(bool, u64), Status> {
-        let default_flags = (true, 0);
-
-        let redis_client = self.storage.redis_client.clone();
-        let cached_conn = { self.redis_conn.lock().await.clone() };
-
-        let mut conn = match cached_conn {
-            Some(conn) => conn,
-            None => {
-                let conn = match redis_client.get_multiplexed_async_connection().await {
-                    Ok(conn) => conn,
-                    Err(e) => {
-                        tracing::error!(
-                            error = %e,
-                            context,
-                            "Redis error reading safety flags (connect); defaulting safe"
-                        );
-                        return Ok(default_flags);
-                    }
-                };
-
-                *self.redis_conn.lock().await = Some(conn.clone());
-                conn
-            }
-        };
-
-        let pipeline_result: Result<(Option<String>, Option<String>), redis::RedisError> =
-            redis::pipe()
-                .cmd("GET")
-                .arg("nexus:safety_mode")
-                .cmd("GET")
-                .arg("nexus:drift")
-                .query_async(&mut conn)
-                .await;
+    /// If Redis is unavailable, this logs the error and returns `None` so the
+    /// read-only `GetStatus`/`GetMetrics` paths can still serve the
+    /// Postgres-derived fields and report the Redis-derived ones as unknown.
+    async fn read_safety_flags(&self, context: &str) -> Option<(bool, u64)> {
+        let safety_key = self.storage.redis_key(keys::SAFETY_MODE);
+        let drift_key = self.storage.redis_key(keys::DRIFT);
+        let (safety_key, drift_key) = (safety_key.as_str(), drift_key.as_str());
+        let pipeline_result: Result<(Option<String>, Option<String>), redis::RedisError> = self
+            .storage
+            .with_redis(|mut conn| async move {
+                redis::pipe()
+                    .cmd("GET")
+                    .arg(safety_key)
+                    .cmd("GET")
+                    .arg(drift_key)
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await;
 
-        let (safety_raw, drift_raw): (Option<String>, Option<String>) = match pipeline_result {
+        let (safety_raw, drift_raw) = match pipeline_result {
             Ok(result) => result,
             Err(e) => {
-                *self.redis_conn.lock().await = None;
-                tracing::warn!(
+                tracing::error!(
                     error = %e,
                     context,
-                    "Redis error reading safety flags (pipeline); retrying once"
+                    "Redis error reading safety flags; reporting degraded"
                 );
-
-                let mut conn = match redis_client.get_multiplexed_async_connection().await {
-                    Ok(conn) => conn,
-                    Err(e) => {
-                        tracing::error!(
-                            error = %e,
-                            context,
-                            "Redis error reading safety flags (connect); defaulting safe"
-                        );
-                        return Ok(default_flags);
-                    }
-                };
-
-                match redis::pipe()
-                    .cmd("GET")
-                    .arg("nexus:safety_mode")
-                    .cmd("GET")
-                    .arg("nexus:drift")
-                    .query_async(&mut conn)
-                    .await
-                {
-                    Ok(result) => {
-                        *self.redis_conn.lock().await = Some(conn.clone());
-                        result
-                    }
-                    Err(e) => {
-                        *self.redis_conn.lock().await = None;
-                        tracing::error!(
-                            error = %e,
-                            context,
-                            "Redis error reading safety flags (pipeline); defaulting safe"
-                        );
-                        return Ok(default_flags);
-                    }
-                }
+                return None;
             }
         };
 
@@ -254,7 +332,7 @@ impl NexusGrpcService {
             }
         };
 
-        Ok((safety_mode, drift))
+        Some((safety_mode, drift))
     }
 }
 
@@ -265,18 +343,27 @@ impl NexusService for NexusGrpcService {
         request: Request<ProofRequest>,
     ) -> Result<Response<ProofResponse>, Status> {
         let req = request.into_inner();
-        let (hash, proof) = self
-            .nexus_state
-            .generate_merkle_proof(&req.key)
-            .map(|p| {
-                (
-                    p.root.clone(),
-                    serde_json::to_string(&p).unwrap_or_default(),
-                )
-            })
-            .unwrap_or_else(|| (self.nexus_state.get_state_root(), "{}".to_string()));
+        let response = match self.nexus_state.generate_merkle_proof(&req.key) {
+            Some(p) => ProofResponse {
+                hash: p.root.clone(),
+                proof: serde_json::to_string(&p).unwrap_or_default(),
+                leaf_index: p.leaf_index,
+                compact: p.to_compact_hex().unwrap_or_default(),
+                tree_size: p.tree_size,
+                path_length: p.path_length() as u32,
+            },
+            None => {
+                let (hash, tree_size) = self.nexus_state.root_and_leaf_count();
+                ProofResponse {
+                    hash,
+                    proof: "{}".to_string(),
+                    tree_size: tree_size as u64,
+                    ..Default::default()
+                }
+            }
+        };
 
-        Ok(Response::new(ProofResponse { hash, proof }))
+        Ok(Response::new(response))
     }
 
     async fn verify_state(
@@ -295,25 +382,35 @@ impl NexusService for NexusGrpcService {
         &self,
         _request: Request<StatusRequest>,
     ) -> Result<Response<StatusResponse>, Status> {
-        let max_height: Option<i64> =
-            sqlx::query_scalar("SELECT MAX(height) FROM stacks_blocks WHERE state != 'orphaned'")
-                .fetch_one(&self.storage.pg_pool)
-                .await
-                .map_err(|e| {
-                    tracing::error!(error = %e, "Database error in GetStatus (max_height)");
-                    Status::internal("Database error in GetStatus (max_height)")
-                })?;
-
-        let processed_height: u64 = max_height.unwrap_or(0).max(0) as u64;
+        let processed_height = self
+            .storage
+            .read_repo()
+            .max_processed_height()
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Database error in GetStatus (max_height)");
+                Status::internal("Database error in GetStatus (max_height)")
+            })?;
 
-        let (safety_mode, drift) = self.read_safety_flags("GetStatus").await?;
+        let flags = self.read_safety_flags("GetStatus").await;
+        let sample = crate::safety::drift_sample(&self.storage)
+            .await
+            .ok()
+            .flatten();
 
+        let (state_root, leaf_count) = self.nexus_state.root_and_leaf_count();
         Ok(Response::new(StatusResponse {
-            state_root: self.nexus_state.get_state_root(),
+            state_root,
             mmr_root: self.nexus_state.get_mmr_root(),
             processed_height,
-            safety_mode,
-            drift,
+            safety_mode: flags.map(|(safety_mode, _)| safety_mode),
+            drift: flags.map(|(_, drift)| drift),
+            degraded: flags.is_none(),
+            raw_drift: sample.map(|(raw, _)| raw),
+            smoothed_drift: sample.map(|(_, smoothed)| smoothed),
+            state_root_updated_at: self.nexus_state.last_updated().map(to_proto_timestamp),
+            leaf_count: leaf_count as u64,
+            seconds_since_last_block: self.nexus_state.seconds_since_last_block(),
         }))
     }
 
@@ -323,13 +420,28 @@ impl NexusService for NexusGrpcService {
     ) -> Result<Response<MetricsResponse>, Status> {
         let (tx_count, block_count) = self.read_cached_metrics_counts().await?;
 
-        let (safety_mode, drift) = self.read_safety_flags("GetMetrics").await?;
+        let flags = self.read_safety_flags("GetMetrics").await;
 
         Ok(Response::new(MetricsResponse {
             total_transactions: tx_count,
             total_blocks: block_count,
-            safety_mode,
-            drift,
+            safety_mode: flags.map(|(safety_mode, _)| safety_mode),
+            drift: flags.map(|(_, drift)| drift),
+            uptime_seconds: crate::api::get_uptime(),
+            degraded: flags.is_none(),
+        }))
+    }
+
+    async fn get_version(
+        &self,
+        _request: Request<VersionRequest>,
+    ) -> Result<Response<VersionResponse>, Status> {
+        let build = crate::api::build_info();
+        Ok(Response::new(VersionResponse {
+            version: build.version,
+            git_commit: build.git_commit,
+            rustc_version: build.rustc_version,
+            started_at: build.started_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
             uptime_seconds: crate::api::get_uptime(),
         }))
     }
@@ -338,35 +450,73 @@ impl NexusService for NexusGrpcService {
         &self,
         request: Request<ExecuteRequest>,
     ) -> Result<Response<ExecuteResponse>, Status> {
+        let caller = caller_from_metadata(request.metadata());
         let req = request.into_inner();
-        let timestamp = if req.timestamp.is_empty() {
-            Utc::now()
+        let now = Utc::now();
+        let timestamp = execute_timestamp(&req)?;
+        crate::executor::check_future_skew(timestamp, now)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let idempotency = if req.idempotency_key.is_empty() {
+            None
         } else {
-            req.timestamp
-                .parse::<DateTime<Utc>>()
-                .unwrap_or_else(|_| Utc::now())
+            idempotency::validate_key(&req.idempotency_key).map_err(Status::invalid_argument)?;
+            let caller = caller.as_deref().ok_or_else(|| {
+                Status::invalid_argument(
+                    "idempotency_key is scoped to an API key; send one to use it",
+                )
+            })?;
+            let key = idempotency::scoped_key(caller, &req.idempotency_key);
+            let hash = idempotency::request_hash(&prost::Message::encode_to_vec(&req));
+            match self.executor.reserve_idempotency(&key, &hash).await {
+                Ok(IdempotencyCheck::Fresh) => Some((key, hash)),
+                Ok(IdempotencyCheck::Replay(outcome)) => {
+                    return Ok(Response::new(execute_response(&outcome)))
+                }
+                Ok(IdempotencyCheck::InProgress) => {
+                    return Err(Status::aborted(
+                        "A request with this idempotency key is still being sequenced",
+                    ))
+                }
+                Ok(IdempotencyCheck::Conflict) => {
+                    return Err(Status::already_exists(
+                        "Idempotency key was already used for a different request",
+                    ))
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Idempotency store unavailable");
+                    return Err(Status::unavailable("Idempotency store unavailable"));
+                }
+            }
         };
 
         let exec_req = ExecutionRequest {
             tx_id: req.tx_id.clone(),
             payload: req.payload,
             sender: req.sender,
+            nonce: req.nonce,
             priority: 0,
             timestamp,
+            signature: (!req.signature.is_empty()).then_some(req.signature),
         };
 
-        match self.executor.validate_transaction(&exec_req).await {
-            Ok(true) => Ok(Response::new(ExecuteResponse {
-                tx_id: req.tx_id,
-                status: "Success".to_string(),
-                message: "Validated".to_string(),
-            })),
-            _ => Ok(Response::new(ExecuteResponse {
-                tx_id: req.tx_id,
-                status: "Rejected".to_string(),
-                message: "Rejected".to_string(),
-            })),
+        let tx_id = exec_req.tx_id.clone();
+        let result = self.executor.submit_as(caller.as_deref(), exec_req).await;
+        if let Some((key, hash)) = idempotency {
+            self.executor
+                .finish_idempotency(&key, hash, &tx_id, &result)
+                .await;
+        }
+        if let Err(e) = &result {
+            if e.is::<QueueError>() {
+                return Err(queue_status(e));
+            }
         }
+        let Some(outcome) = IdempotentOutcome::from_submit(String::new(), &tx_id, &result) else {
+            let e = result.unwrap_err();
+            tracing::error!(error = %e, tx_id = %tx_id, "Execute failed");
+            return Err(Status::internal("Failed to sequence transaction"));
+        };
+        Ok(Response::new(execute_response(&outcome)))
     }
 
     async fn get_services(
@@ -385,33 +535,730 @@ impl NexusService for NexusGrpcService {
             .collect();
         Ok(Response::new(ServicesResponse { services }))
     }
+
+    async fn get_account_inclusions(
+        &self,
+        request: Request<AccountInclusionsRequest>,
+    ) -> Result<Response<AccountInclusionsResponse>, Status> {
+        let req = request.into_inner();
+        let principal = crate::api::accounts::parse_principal(&req.principal)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let result = crate::api::accounts::account_inclusions(
+            self.storage.as_ref(),
+            &self.nexus_state,
+            &principal,
+            (req.limit > 0).then_some(req.limit),
+        )
+        .await
+        .map_err(|e| {
+            tracing::warn!(error = %e, "Account inclusions unavailable in GetAccountInclusions");
+            Status::unavailable("Account inclusions unavailable")
+        })?;
+
+        Ok(Response::new(AccountInclusionsResponse {
+            principal: result.principal,
+            root: result.root,
+            height: result.height,
+            inclusions: result
+                .inclusions
+                .into_iter()
+                .map(|inclusion| AccountInclusion {
+                    tx_id: inclusion.tx_id,
+                    block_hash: inclusion.block_hash,
+                    status: inclusion.status.as_str().to_string(),
+                    proof: inclusion
+                        .proof
+                        .map(|p| serde_json::to_string(&p).unwrap_or_default())
+                        .unwrap_or_default(),
+                    ingested_at: inclusion.ingested_at.to_rfc3339(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_execution(
+        &self,
+        request: Request<GetExecutionRequest>,
+    ) -> Result<Response<Execution>, Status> {
+        let req = request.into_inner();
+        let record = self
+            .executor
+            .store
+            .execution(&req.tx_id)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Database error in GetExecution");
+                Status::internal("Database error in GetExecution")
+            })?
+            .ok_or_else(|| Status::not_found(format!("No execution {}", req.tx_id)))?;
+        Ok(Response::new(to_proto_execution(record)))
+    }
+
+    async fn list_executions(
+        &self,
+        request: Request<ListExecutionsRequest>,
+    ) -> Result<Response<ListExecutionsResponse>, Status> {
+        let req = request.into_inner();
+        let status = match req.status.as_str() {
+            "" => None,
+            status => Some(
+                status
+                    .parse::<ExecutionStatus>()
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            ),
+        };
+        let after_sequence = if req.page_token.is_empty() {
+            None
+        } else {
+            Some(
+                req.page_token
+                    .parse::<u64>()
+                    .map_err(|_| Status::invalid_argument("page_token is not valid"))?,
+            )
+        };
+        let since = req
+            .since
+            .as_ref()
+            .map(|since| {
+                from_proto_timestamp(since)
+                    .ok_or_else(|| Status::invalid_argument("since is not a valid timestamp"))
+            })
+            .transpose()?;
+        let filter = ExecutionFilter {
+            sender: (!req.sender.is_empty()).then_some(req.sender),
+            status,
+            since,
+            after_sequence,
+        };
+        let page_size = match req.page_size {
+            0 => DEFAULT_EXECUTIONS_PAGE,
+            size => size.min(MAX_EXECUTIONS_PAGE),
+        };
+
+        // One extra row tells whether another page follows.
+        let mut records = self
+            .executor
+            .store
+            .executions(&filter, page_size + 1)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Database error in ListExecutions");
+                Status::internal("Database error in ListExecutions")
+            })?;
+        let next_page_token = if records.len() > page_size as usize {
+            records.truncate(page_size as usize);
+            records
+                .last()
+                .map(|r| r.sequence.to_string())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        Ok(Response::new(ListExecutionsResponse {
+            executions: records.into_iter().map(to_proto_execution).collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn get_oracle_state(
+        &self,
+        _request: Request<OracleStateRequest>,
+    ) -> Result<Response<OracleStateResponse>, Status> {
+        let oracle = self
+            .oracle
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("Oracle service disabled"))?;
+        let snapshot = oracle
+            .snapshot()
+            .await
+            .map_err(|e| {
+                tracing::warn!(error = %e, "Oracle state unavailable in GetOracleState");
+                Status::unavailable("Oracle state unavailable")
+            })?
+            .ok_or_else(|| Status::unavailable("No successful oracle fetch yet"))?;
+
+        let state = snapshot.state;
+        Ok(Response::new(OracleStateResponse {
+            base_currency: state.base_currency,
+            rates: state.rates,
+            ppp_indices: state.ppp_indices,
+            confidence_intervals: state.confidence_intervals,
+            timestamp: state.timestamp,
+            age_secs: snapshot.age_secs,
+            stale: snapshot.stale,
+            provenance: state
+                .provenance
+                .into_iter()
+                .map(|(currency, providers)| (currency, ProviderList { providers }))
+                .collect(),
+        }))
+    }
+}
+
+/// `Success` for a sequenced request, `Rejected` with the executor's reason otherwise.
+fn execute_response(outcome: &IdempotentOutcome) -> ExecuteResponse {
+    ExecuteResponse {
+        tx_id: outcome.tx_id.clone(),
+        status: if outcome.accepted {
+            "Success"
+        } else {
+            "Rejected"
+        }
+        .to_string(),
+        message: outcome.message.clone(),
+    }
+}
+
+/// Fingerprint of the API key the call was authenticated with, if any.
+fn caller_from_metadata(metadata: &tonic::metadata::MetadataMap) -> Option<String> {
+    api_key_from_metadata(&metadata.clone().into_headers())
+        .map(crate::gateway::audit::caller_fingerprint)
+}
+
+pub fn to_proto_timestamp(at: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: at.timestamp(),
+        nanos: at.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// `None` for a timestamp outside chrono's range or with negative nanos.
+fn from_proto_timestamp(at: &prost_types::Timestamp) -> Option<DateTime<Utc>> {
+    u32::try_from(at.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(at.seconds, nanos))
+}
+
+fn to_proto_execution(record: ExecutionRecord) -> Execution {
+    Execution {
+        sequence: record.sequence,
+        tx_id: record.tx_id,
+        sender: record.sender,
+        nonce: record.nonce,
+        priority: record.priority,
+        status: record.status.as_str().to_string(),
+        payload_hash: record.payload_hash,
+        issued_at: Some(to_proto_timestamp(record.timestamp)),
+        recorded_at: Some(to_proto_timestamp(record.recorded_at)),
+        rejection_reason: record
+            .rejection_reason
+            .map(|reason| reason.as_str().to_string())
+            .unwrap_or_default(),
+        signature: record.signature.unwrap_or_default(),
+    }
+}
+
+/// Request time of an `Execute` call: `issued_at`, else the deprecated RFC 3339
+/// `timestamp`. A missing or malformed value is an error rather than the current
+/// time, which would let any request pass the FSOC back-dating check.
+#[allow(deprecated)]
+fn execute_timestamp(req: &ExecuteRequest) -> Result<DateTime<Utc>, Status> {
+    if let Some(issued_at) = &req.issued_at {
+        return from_proto_timestamp(issued_at)
+            .ok_or_else(|| Status::invalid_argument("issued_at is not a valid timestamp"));
+    }
+    if req.timestamp.is_empty() {
+        return Err(Status::invalid_argument(
+            "issued_at or timestamp is required",
+        ));
+    }
+    DateTime::parse_from_rfc3339(&req.timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| {
+            Status::invalid_argument(format!(
+                "timestamp '{}' is not RFC 3339: {}",
+                req.timestamp, e
+            ))
+        })
 }
 
 pub async fn start_grpc_server(
     storage: Arc<Storage>,
     nexus_state: Arc<NexusState>,
     executor: Arc<NexusExecutor>,
-    port: u16,
+    oracle: Option<Arc<OracleService>>,
+    addr: SocketAddr,
     skip_auth: bool,
+    open_reads: bool,
+    tls: Option<TlsMaterial>,
 ) -> anyhow::Result<()> {
-    let addr = format!("0.0.0.0:{}", port).parse()?;
-    let nexus_service = NexusGrpcService {
-        storage,
-        nexus_state,
-        executor,
-        skip_auth,
-        metrics_counts_cache: MetricsCountsCache::new(),
-        redis_conn: Mutex::new(None),
-    };
+    let auth = (!skip_auth).then(|| GrpcAuthLayer::new(storage.clone(), open_reads));
+    if skip_auth {
+        tracing::warn!("gRPC authentication disabled (debug build)");
+    }
+    let nexus_service = NexusGrpcService::new(storage, nexus_state, executor, oracle, skip_auth);
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_serving::<proto::nexus_service_server::NexusServiceServer<NexusGrpcService>>()
+        .await;
 
-    tracing::info!("gRPC server listening on {}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let bound = listener.local_addr()?;
+    crate::api::record_grpc_addr(bound);
 
-    tonic::transport::Server::builder()
+    let mut builder = tonic::transport::Server::builder();
+    match &tls {
+        Some(tls) => {
+            builder = builder.tls_config(tls.grpc_config())?;
+            tracing::info!("gRPC server listening on {} (TLS)", bound);
+        }
+        None => tracing::info!("gRPC server listening on {}", bound),
+    }
+
+    builder
+        .layer(crate::latency::GrpcLatencyLayer)
+        .layer(tower::util::option_layer(auth))
+        .add_service(health_service)
         .add_service(proto::nexus_service_server::NexusServiceServer::new(
             nexus_service,
         ))
-        .serve(addr)
+        .serve_with_incoming(tonic::transport::server::TcpIncoming::from(listener))
         .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::executor::rgb::RGBRolloutMode;
+    use crate::storage::store::InMemoryStore;
+    use tower::{Layer, ServiceExt};
+
+    #[test]
+    fn test_open_reads_only_exempt_read_methods() {
+        assert!(requires_api_key("/nexus.NexusService/GetStatus", false));
+        assert!(!requires_api_key("/nexus.NexusService/GetStatus", true));
+        assert!(!requires_api_key(
+            "/nexus.NexusService/GetOracleState",
+            true
+        ));
+        assert!(requires_api_key("/nexus.NexusService/Execute", true));
+        assert!(!requires_api_key("/grpc.health.v1.Health/Check", false));
+        assert!(!requires_api_key("/grpc.health.v1.Health/Watch", false));
+        assert!(requires_api_key(
+            "/evil.Service/grpc.health.v1.Health/Check",
+            false
+        ));
+    }
+
+    #[test]
+    fn test_api_key_metadata_accepts_bearer_or_bare_key() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!(api_key_from_metadata(&headers), None);
+        headers.insert(
+            http::header::AUTHORIZATION,
+            "Bearer cxl_abc".parse().unwrap(),
+        );
+        assert_eq!(api_key_from_metadata(&headers), Some("cxl_abc"));
+        headers.insert(http::header::AUTHORIZATION, "cxl_abc".parse().unwrap());
+        assert_eq!(api_key_from_metadata(&headers), Some("cxl_abc"));
+        headers.insert(http::header::AUTHORIZATION, "Bearer  ".parse().unwrap());
+        assert_eq!(api_key_from_metadata(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_layer_rejects_missing_key_before_reaching_service() {
+        let storage = Arc::new(Storage::from_config_lazy(&Config::default_test()).unwrap());
+        let inner = tower::service_fn(|_req: http::Request<()>| async {
+            Ok::<_, std::convert::Infallible>(http::Response::new(String::from("reached")))
+        });
+        let service = GrpcAuthLayer::new(storage, true).layer(inner);
+
+        let denied = service
+            .clone()
+            .oneshot(
+                http::Request::builder()
+                    .uri("/nexus.NexusService/Execute")
+                    .body(())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = Status::from_header_map(denied.headers()).unwrap();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let open = service
+            .oneshot(
+                http::Request::builder()
+                    .uri("/nexus.NexusService/GetStatus")
+                    .body(())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(open.into_body(), "reached");
+    }
+
+    fn execute_service(store: Arc<InMemoryStore>) -> NexusGrpcService {
+        let storage = Storage::for_tests();
+        let executor = NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            std::collections::HashSet::new(),
+        )
+        .with_store(store);
+        NexusGrpcService::new(
+            storage,
+            Arc::new(NexusState::new()),
+            Arc::new(executor),
+            None,
+            true,
+        )
+    }
+
+    /// Submits `(tx_id, sender, nonce)` through the executor, a second apart from `t0`.
+    async fn seed_executions(
+        service: &NexusGrpcService,
+        t0: DateTime<Utc>,
+        seeds: &[(&str, &str, u64)],
+    ) {
+        for (i, (tx_id, sender, nonce)) in seeds.iter().enumerate() {
+            service
+                .executor
+                .submit_as(
+                    Some("seed-key"),
+                    ExecutionRequest {
+                        tx_id: tx_id.to_string(),
+                        payload: String::new(),
+                        timestamp: t0 + chrono::Duration::seconds(i as i64),
+                        sender: sender.to_string(),
+                        nonce: *nonce,
+                        priority: 0,
+                        signature: None,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    /// `request` as sent with an API key, which unsigned requests need.
+    fn authorized(request: ExecuteRequest) -> Request<ExecuteRequest> {
+        let mut request = Request::new(request);
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer client-key".parse().unwrap());
+        request
+    }
+
+    #[allow(deprecated)]
+    fn execute_request(
+        timestamp: &str,
+        issued_at: Option<prost_types::Timestamp>,
+    ) -> ExecuteRequest {
+        ExecuteRequest {
+            tx_id: "tx-1".to_string(),
+            payload: String::new(),
+            sender: "SP1".to_string(),
+            timestamp: timestamp.to_string(),
+            nonce: 1,
+            issued_at,
+            idempotency_key: String::new(),
+            signature: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_proof_reports_tree_size_and_path_length() {
+        let storage = Storage::for_tests();
+        let nexus_state = Arc::new(NexusState::new());
+        nexus_state.update_state_batch(&["a", "b", "c", "d"].map(str::to_string));
+        let executor = NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            std::collections::HashSet::new(),
+        );
+        let service = NexusGrpcService::new(storage, nexus_state, Arc::new(executor), None, true);
+        let proof = |key: &str| {
+            service.get_proof(Request::new(ProofRequest {
+                key: key.to_string(),
+            }))
+        };
+
+        let found = proof("c").await.unwrap().into_inner();
+        assert_eq!(found.leaf_index, 2);
+        assert_eq!(found.tree_size, 4);
+        assert_eq!(found.path_length, 2);
+
+        let missing = proof("zz").await.unwrap().into_inner();
+        assert_eq!(missing.tree_size, 4);
+        assert_eq!(missing.path_length, 0);
+        assert_eq!(missing.proof, "{}");
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_malformed_timestamp() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let status = service
+            .execute(Request::new(execute_request("yesterday", None)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("RFC 3339"));
+
+        let bad_nanos = prost_types::Timestamp {
+            seconds: 1_751_846_400,
+            nanos: -1,
+        };
+        let status = service
+            .execute(Request::new(execute_request("", Some(bad_nanos))))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = service
+            .execute(Request::new(execute_request("", None)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("required"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_far_future_timestamp() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let future = Utc::now() + chrono::Duration::hours(1);
+        let status = service
+            .execute(Request::new(execute_request(&future.to_rfc3339(), None)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("ahead of the node clock"));
+
+        let issued_at = prost_types::Timestamp {
+            seconds: future.timestamp(),
+            nanos: 0,
+        };
+        let status = service
+            .execute(Request::new(execute_request("", Some(issued_at))))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_execute_accepts_valid_timestamps() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let now = Utc::now();
+        let response = service
+            .execute(authorized(execute_request(&now.to_rfc3339(), None)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.status, "Success");
+
+        let issued_at = prost_types::Timestamp {
+            seconds: now.timestamp(),
+            nanos: now.timestamp_subsec_nanos() as i32,
+        };
+        let response = service
+            .execute(authorized(ExecuteRequest {
+                tx_id: "tx-2".to_string(),
+                nonce: 2,
+                ..execute_request("", Some(issued_at))
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.status, "Success");
+    }
+
+    #[tokio::test]
+    async fn test_execute_sequences_through_the_executor() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let now = Utc::now().to_rfc3339();
+        let response = service
+            .execute(authorized(execute_request(&now, None)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.status, "Success");
+        assert_eq!(response.message, "Accepted");
+
+        let execution = service
+            .get_execution(Request::new(GetExecutionRequest {
+                tx_id: "tx-1".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(execution.status, "accepted");
+        assert_eq!(execution.sequence, 1);
+
+        // A replayed nonce is refused by sequencing, not just validation.
+        let response = service
+            .execute(authorized(ExecuteRequest {
+                tx_id: "tx-2".to_string(),
+                ..execute_request(&now, None)
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.status, "Rejected");
+        assert!(!response.message.is_empty());
+
+        // Without an API key, an unsigned request is refused.
+        let response = service
+            .execute(Request::new(ExecuteRequest {
+                tx_id: "tx-3".to_string(),
+                nonce: 2,
+                ..execute_request(&now, None)
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.status, "Rejected");
+    }
+
+    #[tokio::test]
+    async fn test_execute_replays_by_idempotency_key() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let keyed = |key: &str, nonce: u64| {
+            authorized(ExecuteRequest {
+                nonce,
+                idempotency_key: key.to_string(),
+                ..execute_request("2026-01-01T00:00:00Z", None)
+            })
+        };
+
+        let first = service.execute(keyed("retry-1", 1)).await.unwrap();
+        let replay = service.execute(keyed("retry-1", 1)).await.unwrap();
+        assert_eq!(replay.into_inner(), first.into_inner());
+
+        let status = service.execute(keyed("retry-1", 2)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::AlreadyExists);
+        let status = service.execute(keyed("bad key", 1)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_execution_returns_the_record_or_not_found() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let t0 = Utc::now() - chrono::Duration::minutes(5);
+        seed_executions(&service, t0, &[("tx-1", "SP1", 1), ("tx-2", "SP2", 7)]).await;
+
+        let execution = service
+            .get_execution(Request::new(GetExecutionRequest {
+                tx_id: "tx-2".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(execution.sequence, 2);
+        assert_eq!(execution.sender, "SP2");
+        assert_eq!(execution.nonce, 7);
+        assert_eq!(execution.status, "accepted");
+        assert!(execution.rejection_reason.is_empty());
+        assert_eq!(
+            execution.issued_at,
+            Some(to_proto_timestamp(t0 + chrono::Duration::seconds(1)))
+        );
+        assert!(execution.recorded_at.is_some());
+
+        let status = service
+            .get_execution(Request::new(GetExecutionRequest {
+                tx_id: "tx-9".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_list_executions_filters_and_pages() {
+        let service = execute_service(Arc::new(InMemoryStore::new()));
+        let t0 = Utc::now() - chrono::Duration::minutes(5);
+        seed_executions(
+            &service,
+            t0,
+            &[
+                ("tx-1", "SP1", 1),
+                ("tx-2", "SP2", 1),
+                ("tx-3", "SP1", 2),
+                ("tx-4", "SP1", 3),
+                ("tx-5", "SP1", 4),
+            ],
+        )
+        .await;
+        let list = |page_token: String| ListExecutionsRequest {
+            sender: "SP1".to_string(),
+            since: Some(to_proto_timestamp(t0 + chrono::Duration::seconds(1))),
+            page_size: 2,
+            page_token,
+            ..Default::default()
+        };
+        let tx_ids = |page: &ListExecutionsResponse| -> Vec<String> {
+            page.executions.iter().map(|e| e.tx_id.clone()).collect()
+        };
+
+        let first = service
+            .list_executions(Request::new(list(String::new())))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(tx_ids(&first), ["tx-3", "tx-4"]);
+        assert!(!first.next_page_token.is_empty());
+
+        let second = service
+            .list_executions(Request::new(list(first.next_page_token)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(tx_ids(&second), ["tx-5"]);
+        assert!(second.next_page_token.is_empty());
+
+        // A request with a bad signature is rejected, recorded with its reason and
+        // signature, and listed in the same sequence.
+        let replay = ExecutionRequest {
+            tx_id: "tx-6".to_string(),
+            payload: String::new(),
+            timestamp: t0 + chrono::Duration::seconds(9),
+            sender: "SP1".to_string(),
+            nonce: 2,
+            priority: 0,
+            signature: Some("0xsig".to_string()),
+        };
+        service.executor.submit(replay).await.unwrap_err();
+        let rejected = service
+            .list_executions(Request::new(ListExecutionsRequest {
+                status: "rejected".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(tx_ids(&rejected), ["tx-6"]);
+        let execution = &rejected.executions[0];
+        assert_eq!(execution.sequence, 6);
+        assert_eq!(execution.status, "rejected");
+        assert_eq!(execution.rejection_reason, "invalid_signature");
+        assert_eq!(execution.signature, "0xsig");
+        let accepted = service
+            .list_executions(Request::new(ListExecutionsRequest {
+                status: "accepted".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(accepted.executions.len(), 5);
+        for bad in [
+            ListExecutionsRequest {
+                status: "pending".to_string(),
+                ..Default::default()
+            },
+            ListExecutionsRequest {
+                page_token: "next".to_string(),
+                ..Default::default()
+            },
+        ] {
+            let status = service
+                .list_executions(Request::new(bad))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+    }
+}
@@ -2,14 +2,15 @@
 //! Resolves BNS names and WorldID proofs for Conxian Gateway.
 
 use crate::api::rest::AppState;
+use crate::stacks::rpc::{get_json, NotFound};
 use axum::{
     extract::{Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
-use reqwest::StatusCode as ReqwestStatusCode;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Debug, Deserialize)]
 pub struct IdentityResolveRequest {
@@ -41,59 +42,28 @@ pub async fn resolve_identity_handler(
 
     match payload.protocol.as_str() {
         "BNS" => {
-            let stacks_api_base = &state.config.stacks_node_rpc_url;
-            let url = format!(
-                "{}/v1/names/{}",
-                stacks_api_base.trim_end_matches('/'),
-                payload.name
-            );
-
-            let resp = match state.http_client.get(url).send().await {
-                Ok(resp) => resp,
-                Err(err) => {
-                    tracing::warn!(error = %err, "BNS name lookup failed");
-                    return (
-                        StatusCode::BAD_GATEWAY,
-                        Json(IdentityResolveResponse {
-                            address: "".to_string(),
-                            protocol: payload.protocol,
-                            proof_of_personhood: false,
-                        }),
-                    )
-                        .into_response();
-                }
-            };
-
-            if resp.status() == ReqwestStatusCode::NOT_FOUND {
-                return (
-                    StatusCode::NOT_FOUND,
-                    Json(IdentityResolveResponse {
-                        address: "".to_string(),
-                        protocol: payload.protocol,
-                        proof_of_personhood: false,
-                    }),
-                )
-                    .into_response();
-            }
-
-            if !resp.status().is_success() {
-                return (
-                    StatusCode::BAD_GATEWAY,
-                    Json(IdentityResolveResponse {
-                        address: "".to_string(),
-                        protocol: payload.protocol,
-                        proof_of_personhood: false,
-                    }),
-                )
-                    .into_response();
-            }
-
-            let parsed: BnsNameResponse = match resp.json().await {
+            let rpc = crate::stacks::rpc::shared()
+                .unwrap_or_else(|| Arc::new(state.config.stacks_rpc_endpoints()));
+            let client = &state.http_client;
+            let name = &payload.name;
+            let lookup = rpc
+                .call(|base| async move {
+                    get_json::<BnsNameResponse>(client, &format!("{}/v1/names/{}", base, name))
+                        .await
+                })
+                .await;
+
+            let parsed = match lookup {
                 Ok(parsed) => parsed,
                 Err(err) => {
-                    tracing::warn!(error = %err, "BNS response JSON parse failed");
+                    let status = if err.downcast_ref::<NotFound>().is_some() {
+                        StatusCode::NOT_FOUND
+                    } else {
+                        tracing::warn!(error = %err, "BNS name lookup failed");
+                        StatusCode::BAD_GATEWAY
+                    };
                     return (
-                        StatusCode::BAD_GATEWAY,
+                        status,
                         Json(IdentityResolveResponse {
                             address: "".to_string(),
                             protocol: payload.protocol,
@@ -223,8 +193,29 @@ pub async fn resolve_identity_handler(
             .into_response(),
     }
 }
+
+/// Public key and address the oracle signs its on-chain pushes with: `ORACLE_PRIVATE_KEY`,
+/// or the node wallet when it is unset. 404 when the oracle is disabled or has no
+/// `ORACLE_CONTRACT_PRINCIPAL` to push to.
+pub async fn oracle_identity_handler(State(state): State<AppState>) -> impl IntoResponse {
+    match state
+        .oracle
+        .as_ref()
+        .and_then(|oracle| oracle.signer_identity())
+    {
+        Some(identity) => (StatusCode::OK, Json(identity)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Oracle on-chain pushes are not configured" })),
+        )
+            .into_response(),
+    }
+}
+
 use axum::routing::get;
 use axum::Router;
 pub fn identity_routes() -> Router<AppState> {
-    Router::new().route("/resolve", get(resolve_identity_handler))
+    Router::new()
+        .route("/resolve", get(resolve_identity_handler))
+        .route("/oracle", get(oracle_identity_handler))
 }
@@ -0,0 +1,165 @@
+//! `GET /v1/metrics/stream`: the `/v1/metrics` body as Server-Sent Events. One feed task
+//! per node computes the metrics every `METRICS_STREAM_INTERVAL_SECS`, and at once when a
+//! Safety Mode change is published on the events channel, and every connected client is
+//! sent the same serialized snapshot, so the database is read once per push however many
+//! dashboards are watching. The task starts with the first client and stops after the
+//! last one disconnects.
+
+use crate::api::rest::{collect_metrics, AppState};
+use crate::events::NexusEvent;
+use crate::storage::keys;
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// SSE event name of each push.
+pub const METRICS_EVENT: &str = "metrics";
+
+/// The latest serialized metrics, shared by every stream client.
+pub struct MetricsFeed {
+    latest: watch::Sender<Option<Arc<str>>>,
+    running: AtomicBool,
+}
+
+impl Default for MetricsFeed {
+    fn default() -> Self {
+        Self {
+            latest: watch::Sender::new(None),
+            running: AtomicBool::new(false),
+        }
+    }
+}
+
+impl MetricsFeed {
+    /// Subscribes to pushes, starting the feed task if no client is connected. The
+    /// current snapshot, if any, is delivered first.
+    pub fn subscribe(&self, state: &AppState) -> watch::Receiver<Option<Arc<str>>> {
+        let mut receiver = self.latest.subscribe();
+        receiver.mark_changed();
+        if !self.running.swap(true, Ordering::SeqCst) {
+            tokio::spawn(run_feed(state.clone()));
+        }
+        receiver
+    }
+}
+
+/// Whether an events channel payload changes the `safety_mode` field.
+pub fn is_safety_change(payload: &str) -> bool {
+    matches!(
+        NexusEvent::parse(payload),
+        Ok(NexusEvent::SafetyTriggered { .. } | NexusEvent::SafetyCleared { .. })
+    )
+}
+
+/// Safety Mode changes published on the events channel; `None` when Redis cannot be
+/// subscribed to, leaving the feed on its interval.
+async fn safety_changes(state: &AppState) -> Option<BoxStream<'static, ()>> {
+    let channel = state.storage.redis_key(keys::EVENTS_CHANNEL);
+    let subscribed = async {
+        let mut pubsub = state.storage.redis_client.get_async_pubsub().await?;
+        pubsub.subscribe(&channel).await?;
+        Ok::<_, redis::RedisError>(pubsub)
+    }
+    .await;
+    match subscribed {
+        Ok(pubsub) => Some(
+            pubsub
+                .into_on_message()
+                .filter_map(|msg| async move {
+                    let payload: String = msg.get_payload().ok()?;
+                    is_safety_change(&payload).then_some(())
+                })
+                .boxed(),
+        ),
+        Err(e) => {
+            tracing::warn!(error = %e, "Metrics stream cannot subscribe to events; pushing on its interval only");
+            None
+        }
+    }
+}
+
+async fn run_feed(state: AppState) {
+    let feed = state.metrics_feed.clone();
+    let interval = Duration::from_secs(state.config.metrics_stream_interval_secs.max(1));
+    let mut changes = safety_changes(&state).await;
+    loop {
+        let json = serde_json::to_string(&collect_metrics(&state).await).unwrap_or_default();
+        feed.latest.send_replace(Some(json.into()));
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = feed.latest.closed() => {
+                feed.latest.send_replace(None);
+                feed.running.store(false, Ordering::SeqCst);
+                // A client that subscribed after the last one left saw the task running.
+                if feed.latest.receiver_count() == 0 || feed.running.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+            }
+            change = async {
+                match changes.as_mut() {
+                    Some(changes) => changes.next().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                if change.is_none() {
+                    tracing::warn!("Events subscription for the metrics stream ended");
+                    changes = None;
+                }
+            }
+        }
+    }
+}
+
+/// Next snapshot the client has not been sent; `None` once the feed is gone.
+async fn next_snapshot(receiver: &mut watch::Receiver<Option<Arc<str>>>) -> Option<Arc<str>> {
+    loop {
+        receiver.changed().await.ok()?;
+        if let Some(json) = receiver.borrow_and_update().clone() {
+            return Some(json);
+        }
+    }
+}
+
+fn metrics_events(
+    receiver: watch::Receiver<Option<Arc<str>>>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    futures_util::stream::unfold(receiver, |mut receiver| async move {
+        let json = next_snapshot(&mut receiver).await?;
+        let event = Event::default().event(METRICS_EVENT).data(&*json);
+        Some((Ok(event), receiver))
+    })
+}
+
+/// `GET /v1/metrics/stream`.
+pub async fn metrics_stream_handler(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.metrics_feed.subscribe(&state);
+    Sse::new(metrics_events(receiver)).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_safety_events_force_a_push() {
+        let triggered = NexusEvent::SafetyTriggered {
+            cause: "drift".to_string(),
+            drift: Some(3),
+            height: None,
+        };
+        assert!(is_safety_change(&triggered.to_json()));
+        assert!(is_safety_change(
+            &NexusEvent::SafetyCleared { drill: false }.to_json()
+        ));
+        assert!(!is_safety_change(&NexusEvent::OracleFresh.to_json()));
+        assert!(!is_safety_change("not json"));
+    }
+}
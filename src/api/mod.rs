@@ -1,3 +1,4 @@
+pub mod accounts;
 pub mod admin;
 pub mod analytics;
 pub mod billing;
@@ -5,22 +6,50 @@ pub mod dlc;
 pub mod erp;
 pub mod grpc;
 pub mod identity;
+pub mod metrics_stream;
 pub mod rest;
 pub mod security;
 pub mod services;
 pub mod settlement;
+pub mod tls;
 pub mod zkml;
 
 use chrono::{DateTime, Utc};
-use std::sync::OnceLock;
+use prometheus::{opts, register_int_gauge, register_int_gauge_vec, IntGauge, IntGaugeVec};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
 
 pub static START_TIME: OnceLock<Instant> = OnceLock::new();
 pub static START_TIME_UTC: OnceLock<DateTime<Utc>> = OnceLock::new();
 
+/// Commit the binary was built from (`GIT_COMMIT` at build time, else the checkout).
+pub const GIT_COMMIT: &str = env!("NEXUS_GIT_COMMIT");
+pub const RUSTC_VERSION: &str = env!("NEXUS_RUSTC_VERSION");
+
+lazy_static::lazy_static! {
+    static ref BUILD_INFO: IntGaugeVec = register_int_gauge_vec!(
+        opts!("nexus_build_info", "Build metadata of the running binary; always 1"),
+        &["version", "git_commit", "rustc_version"]
+    )
+    .unwrap();
+    static ref PROCESS_START_TIME: IntGauge = register_int_gauge!(opts!(
+        "nexus_process_start_time_seconds",
+        "Unix time at which the process started"
+    ))
+    .unwrap();
+}
+
+/// Records the process start; called once from `main` before any service starts.
+/// Later calls keep the first record.
 pub fn init_start_time() {
-    START_TIME_UTC.get_or_init(Utc::now);
+    let started = *START_TIME_UTC.get_or_init(Utc::now);
     START_TIME.get_or_init(Instant::now);
+    BUILD_INFO
+        .with_label_values(&[env!("CARGO_PKG_VERSION"), GIT_COMMIT, RUSTC_VERSION])
+        .set(1);
+    PROCESS_START_TIME.set(started.timestamp());
 }
 
 pub fn get_start_time_utc() -> Option<DateTime<Utc>> {
@@ -30,3 +59,47 @@ pub fn get_start_time_utc() -> Option<DateTime<Utc>> {
 pub fn get_uptime() -> u64 {
     START_TIME.get().map(|t| t.elapsed().as_secs()).unwrap_or(0)
 }
+
+/// Addresses the API listeners actually bound, with ephemeral ports resolved; `None`
+/// until the listener is up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListenAddrs {
+    pub rest: Option<SocketAddr>,
+    pub grpc: Option<SocketAddr>,
+}
+
+static LISTEN_ADDRS: Mutex<ListenAddrs> = Mutex::new(ListenAddrs {
+    rest: None,
+    grpc: None,
+});
+
+pub fn listen_addrs() -> ListenAddrs {
+    *LISTEN_ADDRS.lock().unwrap()
+}
+
+pub fn record_rest_addr(addr: SocketAddr) {
+    LISTEN_ADDRS.lock().unwrap().rest = Some(addr);
+}
+
+pub fn record_grpc_addr(addr: SocketAddr) {
+    LISTEN_ADDRS.lock().unwrap().grpc = Some(addr);
+}
+
+/// Version and build metadata, as reported by `/v1/metrics` and `GetVersion`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    pub git_commit: String,
+    pub rustc_version: String,
+    /// `None` until [`init_start_time`] runs.
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: GIT_COMMIT.to_string(),
+        rustc_version: RUSTC_VERSION.to_string(),
+        started_at: get_start_time_utc(),
+    }
+}
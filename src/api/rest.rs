@@ -1,30 +1,48 @@
+use crate::api::accounts::accounts_routes;
 use crate::api::analytics::analytics_routes;
 use crate::api::billing::billing_routes;
 use crate::api::billing::nostr::NostrTelemetry;
 use crate::api::dlc::dlc_routes;
 use crate::api::erp::erp_routes;
+use crate::api::grpc::proto;
 use crate::api::identity::identity_routes;
-use crate::api::services::services_routes;
+use crate::api::metrics_stream::{metrics_stream_handler, MetricsFeed};
+use crate::api::services::{api_key_from_headers, services_routes};
 use crate::api::settlement::settlement_routes;
+use crate::api::tls::TlsMaterial;
 use crate::api::zkml::zkml_routes;
+use crate::config::dynamic::DynamicConfigHandle;
+use crate::config::network::Network;
 use crate::config::Config;
+use crate::executor::idempotency::{self, IdempotencyCheck, IdempotentOutcome, IDEMPOTENCY_HEADER};
+use crate::executor::queue::QueueError;
 use crate::executor::{ExecutionRequest, NexusExecutor};
+use crate::gateway::audit::ServiceMetricsSnapshot;
+use crate::gateway::breaker::BreakerSnapshot;
+use crate::gateway::ServiceRegistry;
 use crate::oracle::OracleService;
-use crate::state::NexusState;
+use crate::state::continuity::{self, MAX_CHAIN_ROOTS};
+use crate::state::{decode_hash, HashEncoding, MerkleProof, NexusState};
 use crate::storage::kwil::KwilAdapter;
+use crate::storage::store::NexusStore;
 use crate::storage::tableland::TablelandAdapter;
 use crate::storage::Storage;
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::{Body, Bytes},
+    extract::{rejection::BytesRejection, DefaultBodyLimit, Query, State},
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
-use prometheus::{opts, register_int_gauge, IntGauge};
+use prometheus::{opts, register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use prost::Message;
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
 
 lazy_static::lazy_static! {
     static ref TX_COUNT: IntGauge = register_int_gauge!(opts!(
@@ -38,6 +56,12 @@ lazy_static::lazy_static! {
         "Total number of rebalances executed"
     ))
     .unwrap();
+
+    static ref SHED_REQUESTS: IntCounter = register_int_counter!(opts!(
+        "nexus_rest_requests_shed_total",
+        "REST requests rejected with 503 because REST_MAX_IN_FLIGHT were already in flight"
+    ))
+    .unwrap();
 }
 
 #[derive(Clone)]
@@ -49,28 +73,116 @@ pub struct AppState {
     pub tableland: Arc<TablelandAdapter>,
     pub kwil: Option<Arc<KwilAdapter>>,
     pub nostr: Option<Arc<NostrTelemetry>>,
+    pub gateway: Arc<ServiceRegistry>,
     pub gateway_url: Option<reqwest::Url>,
     pub http_client: reqwest::Client,
     pub config: Arc<Config>,
+    /// Reloadable settings, shared with the executor.
+    pub dynamic_config: DynamicConfigHandle,
+    /// Snapshots pushed to `GET /v1/metrics/stream` clients.
+    pub metrics_feed: Arc<MetricsFeed>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct ProofParams {
     pub key: String,
+    /// Past state root to prove against; the current root when unset.
+    pub root: Option<String>,
+    /// Encoding of the returned root and proof hashes; hex when unset. Only honoured by
+    /// `GET /v1/proof`.
+    pub encoding: Option<HashEncoding>,
 }
 
 #[derive(Serialize)]
 pub struct ProofResponse {
     pub root: String,
+    /// JSON-encoded [`MerkleProof`], `{}` when the key is not a leaf.
     pub proof: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leaf_index: Option<u64>,
+    /// Leaves in the tree the proof was cut from; the current count when not found.
+    pub tree_size: u64,
+    /// Levels from leaf to root, 0 when not found.
+    pub path_length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact: Option<String>,
+}
+
+impl ProofResponse {
+    fn found(proof: MerkleProof) -> Self {
+        Self {
+            proof: serde_json::to_string(&proof).unwrap_or_default(),
+            leaf_index: Some(proof.leaf_index),
+            tree_size: proof.tree_size,
+            path_length: proof.path_length(),
+            compact: proof.to_compact_hex(),
+            root: proof.root,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BatchProofRequest {
+    pub keys: Vec<String>,
+}
+
+/// Upper bound on keys per `POST /v1/proof/batch` call.
+const MAX_BATCH_PROOF_KEYS: usize = 1_000;
+
+#[derive(Deserialize, Debug)]
+pub struct PreviewRootRequest {
+    /// Hypothetical leaves, appended in order after the current ones.
+    pub leaves: Vec<String>,
+}
+
+/// Upper bound on leaves per `POST /v1/state/preview-root` call.
+const MAX_PREVIEW_LEAVES: usize = 1_000;
+
+#[derive(Deserialize, Debug)]
+pub struct VerifyChainRequest {
+    /// Root of a confirmed on-chain anchor, hex or base64.
+    pub checkpoint: String,
+    /// Roots claimed to descend from the checkpoint, oldest first.
+    pub roots: Vec<crate::state::continuity::RootAtHeight>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LeavesParams {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
 }
 
+/// Default and maximum page sizes for `GET /v1/state/leaves`.
+const DEFAULT_LEAVES_LIMIT: usize = 1_000;
+const MAX_LEAVES_LIMIT: usize = 10_000;
+
 #[derive(Deserialize, Debug)]
 pub struct MMRProofParams {
     pub index: Option<u64>,
     pub tx_id: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct OracleHistoryParams {
+    pub currency: String,
+    /// Unix seconds, inclusive; defaults to the start of the log.
+    pub from: Option<u64>,
+    /// Unix seconds, inclusive; defaults to now.
+    pub to: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AnchorsParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct VaultsParams {
+    /// Only vaults whose oracle-priced LTV is at least this, in basis points.
+    pub min_ltv_bps: Option<u64>,
+}
+
 #[derive(Deserialize)]
 pub struct RGBContractParams {
     pub contract_id: String,
@@ -80,7 +192,85 @@ pub struct RGBContractParams {
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
-    pub safety_mode: bool,
+    /// `None` when Redis is unavailable and the flag cannot be read.
+    pub safety_mode: Option<bool>,
+    /// Set when Redis-derived fields could not be read.
+    #[serde(default)]
+    pub degraded: bool,
+    /// Highest applied migration version. Only reported by `/v1/status`; absent when
+    /// Postgres cannot be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<i64>,
+    /// Latest drift sample, in blocks. Only reported by `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_drift: Option<u64>,
+    /// Moving average of the drift that Safety Mode triggers on. Only reported by
+    /// `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothed_drift: Option<f64>,
+    /// Configured Stacks network. Only reported by `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<Network>,
+    /// Restarts per supervised background service since startup. Only reported by
+    /// `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_restarts: Option<std::collections::BTreeMap<String, u64>>,
+    /// Most recent anchor of the state root confirmed on-chain. Only reported by
+    /// `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_anchor: Option<crate::state::anchor::StateAnchor>,
+    /// Addresses the REST and gRPC listeners bound. Only reported by `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen_addrs: Option<crate::api::ListenAddrs>,
+    /// Configured Stacks API endpoints and their health. Only reported by `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacks_rpc_endpoints: Option<Vec<crate::stacks::rpc::EndpointStatus>>,
+    /// Stacks API endpoint that served the most recent call. Only reported by
+    /// `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacks_rpc_last_served: Option<String>,
+    /// Pressure on the sequencer queue. Only reported by `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_backlog: Option<crate::executor::queue::Backlog>,
+    /// When the state root was last recomputed. Only reported by `/v1/status`, once the
+    /// state has been loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_root_updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Leaves in the state tree. Only reported by `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_count: Option<u64>,
+    /// Seconds since the chain time of the newest block ingested. Only reported by
+    /// `/v1/status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds_since_last_block: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetricsResponse {
+    /// `None` when Postgres is unavailable.
+    pub total_transactions: Option<u64>,
+    pub total_blocks: Option<u64>,
+    pub safety_mode: Option<bool>,
+    pub uptime_seconds: u64,
+    /// Set when any Postgres- or Redis-derived field could not be read.
+    #[serde(default)]
+    pub degraded: bool,
+    pub gateway_services: Vec<ServiceMetricsSnapshot>,
+    #[serde(default)]
+    pub gateway_breakers: Vec<BreakerSnapshot>,
+    pub build: crate::api::BuildInfo,
+    /// p50/p95/p99 per route template over recent requests.
+    #[serde(default)]
+    pub route_latencies: Vec<crate::latency::LatencySnapshot>,
+    /// p50/p95/p99 of sync block processing; `None` before the first block.
+    #[serde(default)]
+    pub sync_block_latency: Option<crate::latency::LatencySnapshot>,
+    /// p50/p95/p99 per gRPC method over recent calls.
+    #[serde(default)]
+    pub grpc_latencies: Vec<crate::latency::LatencySnapshot>,
+    /// The named Postgres queries with the highest recent p95, slowest first.
+    #[serde(default)]
+    pub slowest_queries: Vec<crate::latency::LatencySnapshot>,
 }
 
 /// Proof manifest for the narrow proof surface (Issue #149)
@@ -140,6 +330,38 @@ pub fn app_router(
     kwil: Option<Arc<KwilAdapter>>,
     nostr: Option<Arc<NostrTelemetry>>,
     config: Arc<Config>,
+) -> anyhow::Result<Router> {
+    let gateway = Arc::new(ServiceRegistry::from_config(
+        &config,
+        storage.clone(),
+        nexus_state.clone(),
+    )?);
+    Ok(app_router_with_gateway(
+        storage,
+        nexus_state,
+        executor,
+        oracle,
+        tableland,
+        kwil,
+        nostr,
+        config,
+        gateway,
+    ))
+}
+
+/// Like [`app_router`], serving a gateway registry shared with other components
+/// (e.g. the safety monitor reading its counters).
+#[allow(clippy::too_many_arguments)]
+pub fn app_router_with_gateway(
+    storage: Arc<Storage>,
+    nexus_state: Arc<NexusState>,
+    executor: Arc<NexusExecutor>,
+    oracle: Option<Arc<OracleService>>,
+    tableland: Arc<TablelandAdapter>,
+    kwil: Option<Arc<KwilAdapter>>,
+    nostr: Option<Arc<NostrTelemetry>>,
+    config: Arc<Config>,
+    gateway: Arc<ServiceRegistry>,
 ) -> Router {
     init_prometheus_metrics();
 
@@ -154,7 +376,9 @@ pub fn app_router(
             }
         });
 
+    let submit_body_limit = config.submit_max_body_bytes;
     let state = AppState {
+        dynamic_config: executor.dynamic.clone(),
         storage,
         nexus_state,
         executor,
@@ -162,9 +386,11 @@ pub fn app_router(
         tableland,
         kwil,
         nostr,
+        gateway,
         gateway_url,
         http_client: reqwest::Client::new(),
         config,
+        metrics_feed: Arc::new(MetricsFeed::default()),
     };
 
     // Security: CORS configuration
@@ -175,8 +401,8 @@ pub fn app_router(
         .expose_headers(tower_http::cors::Any)
         .max_age(std::time::Duration::from_secs(86400));
 
-    // Security: Rate limiting via concurrency limiter (prevents overload)
-    let rate_limit = tower::limit::ConcurrencyLimitLayer::new(100);
+    // Load shedding: beyond the in-flight limit, fail fast instead of queueing
+    let in_flight = Arc::new(Semaphore::new(state.config.rest_max_in_flight));
 
     // Security: Compression for responses (gzip)
     let compression = tower_http::compression::CompressionLayer::new();
@@ -184,10 +410,28 @@ pub fn app_router(
     Router::new()
         .route("/health", get(health_handler))
         .route("/v1/proof", get(get_proof))
+        .route("/v1/proof/batch", post(get_proof_batch))
+        .route("/v1/proof/absence", get(get_non_inclusion_proof))
         .route("/v1/proof/manifest", get(get_proof_manifest)) // Narrow proof surface
-        .route("/v1/submit", post(submit_transaction))
-        .route("/v1/status", get(health_handler))
+        .route(
+            "/v1/submit",
+            post(submit_transaction).layer(DefaultBodyLimit::max(submit_body_limit)),
+        )
+        .route("/v1/executor/stats", get(executor_stats_handler))
+        .route("/v1/oracle/ppp", get(oracle_ppp_handler))
+        .route("/v1/oracle/ppp/history", get(oracle_history_handler))
+        .route("/v1/anchors", get(anchors_handler))
+        .route("/v1/vaults", get(vaults_handler))
+        .route("/v1/status", get(status_handler))
         .route("/v1/mmr-proof", get(get_mmr_proof))
+        .route("/v1/state/leaves", get(get_state_leaves))
+        .route("/v1/state/preview-root", post(preview_state_root))
+        .route("/v1/state/verify-chain", post(verify_root_chain))
+        .route("/v1/diagnostics", get(diagnostics_handler))
+        .route("/v1/metrics", get(metrics_handler))
+        .route("/v1/metrics/stream", get(metrics_stream_handler))
+        .route("/metrics", get(prometheus_handler))
+        .nest("/v1/accounts", accounts_routes())
         .nest("/v1/analytics", analytics_routes())
         .nest("/v1/billing", billing_routes())
         .nest("/v1/zkml", zkml_routes())
@@ -202,10 +446,11 @@ pub fn app_router(
         .nest("/v1/cosmos", cosmos_routes())
         .nest("/v1/stacks", stacks_routes())
         .nest("/v1/rgb", rgb_routes())
+        .layer(axum::middleware::from_fn(crate::latency::record_latency))
         .layer(cors)
-        .layer(rate_limit)
+        .layer(axum::middleware::from_fn_with_state(in_flight, shed_load))
         .layer(compression)
-        .layer(tower_http::trace::TraceLayer::new_for_http())
+        .layer(tower_http::trace::TraceLayer::new_for_http().make_span_with(request_span))
         .with_state(state)
 }
 
@@ -330,10 +575,12 @@ pub async fn start_rest_server(
     tableland: Arc<TablelandAdapter>,
     kwil: Option<Arc<KwilAdapter>>,
     nostr: Option<Arc<NostrTelemetry>>,
-    port: u16,
+    addr: SocketAddr,
     config: Arc<Config>,
+    gateway: Arc<ServiceRegistry>,
+    tls: Option<TlsMaterial>,
 ) -> anyhow::Result<()> {
-    let app = app_router(
+    let app = app_router_with_gateway(
         storage,
         nexus_state,
         executor,
@@ -342,12 +589,33 @@ pub async fn start_rest_server(
         kwil,
         nostr,
         config,
+        gateway,
     );
 
-    let addr = format!("0.0.0.0:{}", port);
-    let listener = TcpListener::bind(&addr).await?;
-    tracing::info!("REST API server listening on {}", addr);
-    axum::serve(listener, app).await?;
+    match tls {
+        Some(tls) => {
+            let rustls_config = tls.rustls_config().await?;
+            let handle = axum_server::Handle::new();
+            let server = axum_server::bind_rustls(addr, rustls_config)
+                .handle(handle.clone())
+                .serve(app.into_make_service());
+            let listening = async {
+                if let Some(bound) = handle.listening().await {
+                    crate::api::record_rest_addr(bound);
+                    tracing::info!("REST API server listening on {} (TLS)", bound);
+                }
+            };
+            let (served, ()) = tokio::join!(server, listening);
+            served?;
+        }
+        None => {
+            let listener = TcpListener::bind(addr).await?;
+            let bound = listener.local_addr()?;
+            crate::api::record_rest_addr(bound);
+            tracing::info!("REST API server listening on {}", bound);
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
@@ -356,20 +624,363 @@ pub async fn health_check() -> impl IntoResponse {
     (StatusCode::OK, "OK")
 }
 
-#[tracing::instrument(skip(state))]
+#[tracing::instrument(skip(state, headers))]
 async fn get_proof(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(params): Query<ProofParams>,
 ) -> impl IntoResponse {
-    let (root, proof) = state.nexus_state.generate_proof(&params.key);
-    (
-        StatusCode::OK,
-        Json(serde_json::json!({ "root": root, "proof": proof })),
+    let encoding = params.encoding.unwrap_or_default();
+    if let Some(root) = params.root.as_deref() {
+        if let Err(unauthorized) =
+            crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+        {
+            return unauthorized;
+        }
+        return historical_proof(&state, &params.key, root, encoding).await;
+    }
+    if let Some(proof) = state.nexus_state.generate_merkle_proof(&params.key) {
+        let body = ProofResponse::found(proof.encoded(encoding));
+        return (StatusCode::OK, Json(body)).into_response();
+    }
+    let (root, tree_size) = state.nexus_state.root_and_leaf_count();
+    let body = ProofResponse {
+        root: decode_hash(&root)
+            .map(|bytes| encoding.encode(&bytes))
+            .unwrap_or(root),
+        proof: "{}".to_string(),
+        leaf_index: None,
+        tree_size: tree_size as u64,
+        path_length: 0,
+        compact: None,
+    };
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+/// Proof that `key` was included at `root`, rebuilt off the runtime from the leaf count
+/// recorded for that root. 404 when the root is unknown or did not include the key.
+/// `root` may be given in either encoding.
+async fn historical_proof(
+    state: &AppState,
+    key: &str,
+    root: &str,
+    encoding: HashEncoding,
+) -> Response {
+    let root = match decode_hash(root) {
+        Some(bytes) => HashEncoding::Hex.encode(&bytes),
+        None => crate::state::canonical_leaf(root).into_owned(),
+    };
+    let not_found = |error: &str| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": error, "root": root })),
+        )
+            .into_response()
+    };
+    let (current_root, current_leaf_count) = state.nexus_state.root_and_leaf_count();
+    // The cached proof is only usable if no block landed since the snapshot.
+    let cached = (root == current_root)
+        .then(|| state.nexus_state.generate_merkle_proof(key))
+        .flatten()
+        .filter(|proof| proof.root == root);
+    let proof = match cached {
+        Some(proof) => Some(proof),
+        None => {
+            let leaf_count = if root == current_root {
+                current_leaf_count
+            } else {
+                match state.storage.root_leaf_count(&root).await {
+                    Ok(Some(leaf_count)) => leaf_count as usize,
+                    Ok(None) => return not_found("Unknown state root"),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "State root history unavailable");
+                        return (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            Json(serde_json::json!({ "error": "State root history unavailable" })),
+                        )
+                            .into_response();
+                    }
+                }
+            };
+            let nexus_state = state.nexus_state.clone();
+            let key = key.to_string();
+            match tokio::task::spawn_blocking(move || {
+                nexus_state.generate_merkle_proof_at(&key, leaf_count)
+            })
+            .await
+            {
+                Ok(proof) => proof,
+                Err(_) => {
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({ "error": "Historical proof failed" })),
+                    )
+                        .into_response()
+                }
+            }
+        }
+    };
+    match proof {
+        Some(proof) if proof.root == root => (
+            StatusCode::OK,
+            Json(ProofResponse::found(proof.encoded(encoding))),
+        )
+            .into_response(),
+        Some(proof) => {
+            tracing::error!(
+                requested = %root,
+                rebuilt = %proof.root,
+                "Recorded leaf count does not reproduce the requested root"
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "State root history is inconsistent" })),
+            )
+                .into_response()
+        }
+        None => not_found("Key was not included at this root"),
+    }
+}
+
+/// Proofs for many keys against one snapshot of the tree; unknown keys are listed
+/// under `missing`. Requires the `api.read` scope; the proofs are built on the blocking
+/// pool.
+#[tracing::instrument(skip(state, headers, request))]
+async fn get_proof_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<BatchProofRequest>,
+) -> Response {
+    if let Err(unauthorized) = crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+    {
+        return unauthorized;
+    }
+    if request.keys.is_empty() || request.keys.len() > MAX_BATCH_PROOF_KEYS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("keys must contain between 1 and {} entries", MAX_BATCH_PROOF_KEYS)
+            })),
+        )
+            .into_response();
+    }
+
+    let nexus_state = state.nexus_state.clone();
+    match tokio::task::spawn_blocking(move || nexus_state.generate_merkle_proofs(&request.keys))
+        .await
+    {
+        Ok(batch) => Json(batch).into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Batch proof failed" })),
+        )
+            .into_response(),
+    }
+}
+
+/// Proof that `key` is not in the state, against the sorted-leaf root recorded and
+/// anchored with the current state root, or with `root` when given. A past `root`
+/// rebuilds its sorted tree, so it requires the `api.read` scope and is proved on the
+/// blocking pool.
+#[tracing::instrument(skip(state, headers))]
+async fn get_non_inclusion_proof(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ProofParams>,
+) -> Response {
+    let proof = match params.root.as_deref() {
+        None => state.nexus_state.generate_non_inclusion_proof(&params.key),
+        Some(root) => {
+            if let Err(unauthorized) =
+                crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+            {
+                return unauthorized;
+            }
+            let root = match decode_hash(root) {
+                Some(bytes) => HashEncoding::Hex.encode(&bytes),
+                None => crate::state::canonical_leaf(root).into_owned(),
+            };
+            match state.storage.root_leaf_count(&root).await {
+                Ok(Some(leaf_count)) => {
+                    let nexus_state = state.nexus_state.clone();
+                    let key = params.key.clone();
+                    match tokio::task::spawn_blocking(move || {
+                        nexus_state.generate_non_inclusion_proof_at(&key, leaf_count as usize)
+                    })
+                    .await
+                    {
+                        Ok(proof) => proof,
+                        Err(_) => {
+                            return (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                Json(serde_json::json!({ "error": "Absence proof failed" })),
+                            )
+                                .into_response()
+                        }
+                    }
+                }
+                Ok(None) => {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        Json(serde_json::json!({ "error": "Unknown state root", "root": root })),
+                    )
+                        .into_response()
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "State root history unavailable");
+                    return (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        Json(serde_json::json!({ "error": "State root history unavailable" })),
+                    )
+                        .into_response();
+                }
+            }
+        }
+    };
+    match proof {
+        Some(proof) => Json(proof).into_response(),
+        None => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "key is present in the state tree" })),
+        )
+            .into_response(),
+    }
+}
+
+/// [GLASS-NODE] Paginated leaf export so third parties can recompute the state root.
+#[tracing::instrument(skip(state, headers))]
+async fn get_state_leaves(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<LeavesParams>,
+) -> Response {
+    if let Err(unauthorized) = crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+    {
+        return unauthorized;
+    }
+
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LEAVES_LIMIT)
+        .clamp(1, MAX_LEAVES_LIMIT);
+    let offset = params.offset.unwrap_or(0);
+    Json(state.nexus_state.export_leaves_page(offset, limit)).into_response()
+}
+
+/// What-if root with `leaves` appended, e.g. for optimistic UI over pending
+/// transactions. The state is not changed. Requires the `api.read` scope; the tree is
+/// rebuilt on the blocking pool.
+async fn preview_state_root(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<PreviewRootRequest>,
+) -> Response {
+    if let Err(unauthorized) = crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+    {
+        return unauthorized;
+    }
+    if request.leaves.len() > MAX_PREVIEW_LEAVES {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("leaves must contain at most {} entries", MAX_PREVIEW_LEAVES)
+            })),
+        )
+            .into_response();
+    }
+
+    let nexus_state = state.nexus_state.clone();
+    let preview = tokio::task::spawn_blocking(move || {
+        (
+            nexus_state.get_state_root(),
+            nexus_state.preview_root(&request.leaves),
+        )
+    })
+    .await;
+    let Ok((current_root, preview_root)) = preview else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Root preview failed" })),
+        )
+            .into_response();
+    };
+    Json(serde_json::json!({
+        "current_root": current_root,
+        "preview_root": preview_root,
+        "arity": state.nexus_state.arity(),
+    }))
+    .into_response()
+}
+
+/// Whether `roots` descend, one extension at a time, from the confirmed anchor at
+/// `checkpoint`. A broken chain is a 200 with `valid: false` and the first break.
+/// Requires the `api.read` scope, since every root rebuilds a tree.
+async fn verify_root_chain(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<VerifyChainRequest>,
+) -> Response {
+    if let Err(unauthorized) = crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+    {
+        return unauthorized;
+    }
+    if request.roots.len() > MAX_CHAIN_ROOTS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("roots must contain at most {} entries", MAX_CHAIN_ROOTS)
+            })),
+        )
+            .into_response();
+    }
+    let Some(checkpoint) = decode_hash(&request.checkpoint).filter(|b| b.len() == 32) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "checkpoint must be a 32-byte root" })),
+        )
+            .into_response();
+    };
+    let checkpoint = HashEncoding::Hex.encode(&checkpoint);
+
+    let unavailable = |e: anyhow::Error| {
+        tracing::warn!(error = %e, "Root chain verification unavailable");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Root chain verification unavailable" })),
+        )
+            .into_response()
+    };
+    let anchor = match state.storage.confirmed_anchor(&checkpoint).await {
+        Ok(Some(anchor)) => anchor,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "checkpoint is not a confirmed anchor" })),
+            )
+                .into_response()
+        }
+        Err(e) => return unavailable(e),
+    };
+    match continuity::verify_root_chain(
+        state.storage.as_ref(),
+        &state.nexus_state,
+        &anchor,
+        &request.roots,
     )
-        .into_response()
+    .await
+    {
+        Ok(verification) => Json(serde_json::json!({
+            "checkpoint": anchor,
+            "valid": verification.valid,
+            "verified": verification.verified,
+            "leaf_counts": verification.leaf_counts,
+            "failure": verification.failure,
+        }))
+        .into_response(),
+        Err(e) => unavailable(e),
+    }
 }
 
-#[tracing::instrument(skip(state))]
 async fn get_mmr_proof(
     State(state): State<AppState>,
     Query(params): Query<MMRProofParams>,
@@ -411,12 +1022,148 @@ async fn get_mmr_proof(
     }
 }
 
-#[tracing::instrument(skip(state))]
+/// `POST /v1/submit`. The body is read under `SUBMIT_MAX_BODY_BYTES` and parsed here
+/// rather than by the `Json` extractor, so oversized and malformed requests get a
+/// structured `{error, code}` response and a warning naming the sender when it can be read.
+#[tracing::instrument(skip(state, headers, body))]
 async fn submit_transaction(
     State(state): State<AppState>,
-    Json(request): Json<ExecutionRequest>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
 ) -> impl IntoResponse {
-    match state.executor.submit(request).await {
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            tracing::warn!(
+                limit = state.config.submit_max_body_bytes,
+                "Rejected oversized submit request"
+            );
+            return submit_error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+                format!(
+                    "Request body exceeds {} bytes",
+                    state.config.submit_max_body_bytes
+                ),
+            );
+        }
+        Err(rejection) => {
+            tracing::warn!(error = %rejection.body_text(), "Unreadable submit request body");
+            return submit_error(rejection.status(), "unreadable_body", rejection.body_text());
+        }
+    };
+    let request: ExecutionRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            let sender = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v.get("sender")?.as_str().map(str::to_string));
+            tracing::warn!(
+                sender = sender.as_deref().unwrap_or("unknown"),
+                bytes = body.len(),
+                error = %e,
+                "Rejected malformed submit request"
+            );
+            let (status, code) = if e.is_syntax() || e.is_eof() {
+                (StatusCode::BAD_REQUEST, "malformed_json")
+            } else {
+                (StatusCode::UNPROCESSABLE_ENTITY, "invalid_request")
+            };
+            return submit_error(status, code, e.to_string());
+        }
+    };
+    if let Err(e) = crate::executor::check_future_skew(request.timestamp, chrono::Utc::now()) {
+        tracing::warn!(sender = %request.sender, error = %e, "Rejected future-dated submit request");
+        return submit_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "timestamp_in_future",
+            e.to_string(),
+        );
+    }
+    // Submissions are open, but a key that is sent must be valid: it scopes idempotency
+    // keys and the caller's request rate.
+    let caller = match api_key_from_headers(&headers) {
+        None => None,
+        Some(api_key) => match state.storage.api_key_exists(api_key).await {
+            Ok(true) => Some(crate::gateway::audit::caller_fingerprint(api_key)),
+            Ok(false) => {
+                return submit_error(
+                    StatusCode::UNAUTHORIZED,
+                    "invalid_api_key",
+                    "Invalid API key".to_string(),
+                )
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Credential store unavailable");
+                return submit_error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "credential_store_unavailable",
+                    "Credential store unavailable".to_string(),
+                );
+            }
+        },
+    };
+    let idempotency = match headers.get(IDEMPOTENCY_HEADER) {
+        Some(value) => {
+            let key = value.to_str().unwrap_or_default();
+            if let Err(message) = idempotency::validate_key(key) {
+                return submit_error(StatusCode::BAD_REQUEST, "invalid_idempotency_key", message);
+            }
+            let Some(caller) = &caller else {
+                return submit_error(
+                    StatusCode::BAD_REQUEST,
+                    "idempotency_requires_api_key",
+                    "Idempotency keys are scoped to an API key; send one to use them".to_string(),
+                );
+            };
+            Some((
+                idempotency::scoped_key(&caller, key),
+                idempotency::request_hash(&body),
+            ))
+        }
+        None => None,
+    };
+    if let Some((key, hash)) = &idempotency {
+        match state.executor.reserve_idempotency(key, hash).await {
+            Ok(IdempotencyCheck::Fresh) => {}
+            Ok(IdempotencyCheck::Replay(outcome)) => return replayed_submit(&outcome),
+            Ok(IdempotencyCheck::InProgress) => {
+                let mut response = submit_error(
+                    StatusCode::CONFLICT,
+                    "idempotency_in_progress",
+                    "A request with this idempotency key is still being sequenced".to_string(),
+                );
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(1));
+                return response;
+            }
+            Ok(IdempotencyCheck::Conflict) => {
+                return submit_error(
+                    StatusCode::CONFLICT,
+                    "idempotency_conflict",
+                    "Idempotency key was already used for a different request".to_string(),
+                )
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Idempotency store unavailable");
+                return submit_error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "idempotency_unavailable",
+                    "Idempotency store unavailable".to_string(),
+                );
+            }
+        }
+    }
+    let tx_id = request.tx_id.clone();
+    let result = state.executor.submit_as(caller.as_deref(), request).await;
+    if let Some((key, hash)) = idempotency {
+        state
+            .executor
+            .finish_idempotency(&key, hash, &tx_id, &result)
+            .await;
+    }
+    match result {
         Ok(tx_id) => {
             TX_COUNT.inc();
             (
@@ -425,6 +1172,7 @@ async fn submit_transaction(
             )
                 .into_response()
         }
+        Err(e) if e.downcast_ref::<QueueError>().is_some() => queue_error(&e),
         Err(e) => (
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({ "error": e.to_string() })),
@@ -433,110 +1181,1302 @@ async fn submit_transaction(
     }
 }
 
-async fn health_handler(State(state): State<AppState>) -> impl IntoResponse {
-    let safety_mode = crate::safety::is_safety_mode_active(&state.storage)
-        .await
-        .unwrap_or(false);
-
-    Json(HealthResponse {
-        status: "ok".to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-        safety_mode,
-    })
+/// Serves the request if one of the in-flight permits is free and answers 503 otherwise,
+/// so a burst of expensive proof requests fails fast rather than piling up. The `/health`
+/// probe bypasses the limit.
+async fn shed_load(
+    State(in_flight): State<Arc<Semaphore>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+    let Ok(permit) = in_flight.try_acquire_owned() else {
+        SHED_REQUESTS.inc();
+        let mut response = (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "Node is at capacity; retry shortly",
+                "code": "overloaded",
+            })),
+        )
+            .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+        return response;
+    };
+    let response = next.run(request).await;
+    drop(permit);
+    response
 }
 
-/// Proof manifest handler for the narrow proof surface (Issue #149)
-async fn get_proof_manifest(State(state): State<AppState>) -> impl IntoResponse {
-    let safety_mode = crate::safety::is_safety_mode_active(&state.storage)
-        .await
-        .unwrap_or(false);
-
-    // Get MMR information from nexus state using the public get_mmr_state method
-    let (mmr_peaks_raw, mmr_leaf_count) = state.nexus_state.get_mmr_state();
-    let mmr_peaks = mmr_peaks_raw.iter().map(hex::encode).collect::<Vec<_>>();
+/// Span for one HTTP request, tagged with the caller's `x-request-id` or a fresh one so
+/// every log line of the request can be correlated.
+fn request_span(request: &Request<Body>) -> tracing::Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    tracing::info_span!(
+        "http",
+        service = "rest",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+    )
+}
 
-    // Get state root
-    let state_root = {
-        let (root, _) = state.nexus_state.generate_proof("state_root");
-        if root.is_empty() {
-            None
-        } else {
-            Some(root)
+/// 429 with `Retry-After` for a full sequencer queue, 503 for an expired request.
+fn queue_error(e: &anyhow::Error) -> Response {
+    match e.downcast_ref::<QueueError>() {
+        Some(QueueError::Full {
+            depth,
+            estimated_drain,
+        }) => {
+            let drain_secs = estimated_drain.as_secs().max(1);
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(serde_json::json!({
+                    "error": e.to_string(),
+                    "code": "queue_full",
+                    "queue_depth": depth,
+                    "estimated_drain_secs": drain_secs,
+                })),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(drain_secs));
+            response
         }
-    };
+        _ => submit_error(StatusCode::SERVICE_UNAVAILABLE, "expired", e.to_string()),
+    }
+}
 
-    Json(ProofManifest {
-        health: HealthStatus {
-            status: "ok".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-            safety_mode,
-            uptime_seconds: None,
-        },
-        proof_routes: ProofRoutes {
-            proof_endpoint: "/v1/proof?key=<key>".to_string(),
-            mmr_proof_endpoint: "/v1/mmr-proof?index=<n>".to_string(),
-            health_endpoint: "/health".to_string(),
-            submit_endpoint: "/v1/submit".to_string(),
-        },
-        state_root,
-        mmr_info: MmrInfo {
-            leaf_count: Some(mmr_leaf_count),
-            peaks: mmr_peaks,
-            initialized: true,
-        },
-        service: ServiceMetadata {
-            version: env!("CARGO_PKG_VERSION").to_string(),
-            proof_surface_version: "1.0.0".to_string(),
-            supported_chains: vec![
-                "stacks".to_string(),
-                "bitcoin".to_string(),
-                "evm".to_string(),
-                "cosmos".to_string(),
-            ],
-        },
-    })
+/// The response of the first submission under an idempotency key, marked as a replay.
+fn replayed_submit(outcome: &IdempotentOutcome) -> Response {
+    let mut response = if outcome.accepted {
+        (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({ "tx_id": outcome.tx_id })),
+        )
+            .into_response()
+    } else {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": outcome.message })),
+        )
+            .into_response()
+    };
+    response
+        .headers_mut()
+        .insert("idempotent-replayed", HeaderValue::from_static("true"));
+    response
 }
 
-fn init_prometheus_metrics() {
-    let _ = &*TX_COUNT;
-    let _ = &*REBALANCE_COUNT;
+fn submit_error(status: StatusCode, code: &str, message: String) -> Response {
+    (
+        status,
+        Json(serde_json::json!({ "error": message, "code": code })),
+    )
+        .into_response()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Accepted vs rejected sequencing decisions, broken down by reject reason.
+async fn executor_stats_handler(State(state): State<AppState>) -> impl IntoResponse {
+    match state.executor.stats().await {
+        Ok(stats) => (StatusCode::OK, Json(serde_json::json!(stats))).into_response(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Executor stats unavailable");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Executor stats unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Latest aggregated FX/PPP state with its age, staleness, per-currency provenance and
+/// rejected outliers.
+async fn oracle_ppp_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let Some(oracle) = state.oracle.as_ref() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Oracle service disabled" })),
+        )
+            .into_response();
+    };
+    match oracle.snapshot().await {
+        Ok(Some(snapshot)) => (StatusCode::OK, Json(serde_json::json!(snapshot))).into_response(),
+        Ok(None) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "No successful oracle fetch yet" })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Oracle state unavailable");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Oracle state unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Recent state-root anchoring attempts, newest first, failed broadcasts included.
+async fn anchors_handler(
+    State(state): State<AppState>,
+    Query(params): Query<AnchorsParams>,
+) -> impl IntoResponse {
+    let limit = params
+        .limit
+        .unwrap_or(crate::state::anchor::DEFAULT_ANCHORS_LIMIT)
+        .clamp(1, crate::state::anchor::MAX_ANCHORS_LIMIT);
+    match state.storage.anchors(limit).await {
+        Ok(anchors) => Json(serde_json::json!({ "anchors": anchors })).into_response(),
+        Err(e) => {
+            tracing::warn!(error = %e, "State anchors unavailable");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "State anchors unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Active vaults with their oracle-priced LTV and health, riskiest first.
+async fn vaults_handler(
+    State(state): State<AppState>,
+    Query(params): Query<VaultsParams>,
+) -> impl IntoResponse {
+    match state.executor.vault_reports(params.min_ltv_bps).await {
+        Ok(vaults) => Json(serde_json::json!({
+            "rebalance_ltv_threshold_bps": state.executor.dynamic.get().rebalance_ltv_threshold_bps.bps(),
+            "vaults": vaults,
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Vaults unavailable");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Vaults unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Per-provider quotes for one currency from the oracle observation log, oldest first.
+async fn oracle_history_handler(
+    State(state): State<AppState>,
+    Query(params): Query<OracleHistoryParams>,
+) -> impl IntoResponse {
+    let currency = params.currency.trim().to_ascii_uppercase();
+    if currency.is_empty() || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "currency must be a currency code" })),
+        )
+            .into_response();
+    }
+    let from = params.from.unwrap_or(0);
+    let to = params
+        .to
+        .unwrap_or_else(|| chrono::Utc::now().timestamp().max(0) as u64);
+    if from > to {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "from must not be after to" })),
+        )
+            .into_response();
+    }
+    let limit = params.limit.unwrap_or(crate::oracle::MAX_HISTORY_POINTS);
+
+    match crate::oracle::observation_history(state.storage.read_pool(), &currency, from, to, limit)
+        .await
+    {
+        Ok(points) => Json(serde_json::json!({
+            "currency": currency,
+            "from": from,
+            "to": to,
+            "points": points,
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to read oracle history");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Oracle history unavailable" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn health_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(health_report(&state).await)
+}
+
+async fn health_report(state: &AppState) -> HealthResponse {
+    let safety_mode = match crate::safety::is_safety_mode_active(&state.storage).await {
+        Ok(active) => Some(active),
+        Err(e) => {
+            tracing::warn!(error = %e, "Redis unavailable for health check; reporting degraded");
+            None
+        }
+    };
+
+    HealthResponse {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        safety_mode,
+        degraded: safety_mode.is_none(),
+        schema_version: None,
+        raw_drift: None,
+        smoothed_drift: None,
+        network: None,
+        service_restarts: None,
+        last_anchor: None,
+        listen_addrs: None,
+        stacks_rpc_endpoints: None,
+        stacks_rpc_last_served: None,
+        executor_backlog: None,
+        state_root_updated_at: None,
+        leaf_count: None,
+        seconds_since_last_block: None,
+    }
+}
+
+/// Media type for binary protobuf response bodies.
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+/// Whether `Accept` asks for protobuf. The first recognised media type wins; a missing
+/// header, `*/*` or anything unrecognised keeps the JSON default.
+pub fn wants_protobuf(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+    for range in accept.split(',') {
+        let media = range.split(';').next().unwrap_or("").trim();
+        let rejected = range
+            .split(';')
+            .skip(1)
+            .any(|p| matches!(p.trim(), "q=0" | "q=0.0" | "q=0.00" | "q=0.000"));
+        if rejected {
+            continue;
+        }
+        if media.eq_ignore_ascii_case(PROTOBUF_CONTENT_TYPE)
+            || media.eq_ignore_ascii_case("application/protobuf")
+        {
+            return true;
+        }
+        if media.eq_ignore_ascii_case("application/json") {
+            return false;
+        }
+    }
+    false
+}
+
+fn protobuf_response<M: Message>(message: &M) -> Response {
+    (
+        [(axum::http::header::CONTENT_TYPE, PROTOBUF_CONTENT_TYPE)],
+        message.encode_to_vec(),
+    )
+        .into_response()
+}
+
+/// `GET /v1/status`: the health payload plus schema version as JSON, or the gRPC
+/// `StatusResponse` when the client asks for protobuf.
+async fn status_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if !wants_protobuf(&headers) {
+        let mut report = health_report(&state).await;
+        report.schema_version = match state.storage.schema_version().await {
+            Ok(version) => version,
+            Err(e) => {
+                tracing::warn!(error = %e, "Database unavailable for schema version");
+                None
+            }
+        };
+        if let Ok(Some((raw, smoothed))) = crate::safety::drift_sample(&state.storage).await {
+            report.raw_drift = Some(raw);
+            report.smoothed_drift = Some(smoothed);
+        }
+        report.network = Some(state.config.network);
+        report.service_restarts = Some(crate::supervisor::restart_counts());
+        report.listen_addrs = Some(crate::api::listen_addrs());
+        if let Some(rpc) = crate::stacks::rpc::shared() {
+            report.stacks_rpc_endpoints = Some(rpc.statuses());
+            report.stacks_rpc_last_served = rpc.last_served();
+        }
+        report.executor_backlog = Some(state.executor.queue.backlog());
+        report.state_root_updated_at = state.nexus_state.last_updated();
+        report.leaf_count = Some(state.nexus_state.leaf_count() as u64);
+        report.seconds_since_last_block = state.nexus_state.seconds_since_last_block();
+        report.last_anchor = match state.storage.last_confirmed_anchor().await {
+            Ok(anchor) => anchor,
+            Err(e) => {
+                tracing::warn!(error = %e, "Postgres unavailable for the last state anchor");
+                None
+            }
+        };
+        return Json(report).into_response();
+    }
+
+    let processed_height = match state.storage.read_repo().max_processed_height().await {
+        Ok(height) => Some(height),
+        Err(e) => {
+            tracing::warn!(error = %e, "Database unavailable for status; reporting degraded");
+            None
+        }
+    };
+    let safety_mode = crate::safety::is_safety_mode_active(&state.storage)
+        .await
+        .ok();
+    let drift = crate::safety::current_drift(&state.storage).await.ok();
+    let sample = crate::safety::drift_sample(&state.storage)
+        .await
+        .ok()
+        .flatten();
+
+    let (state_root, leaf_count) = state.nexus_state.root_and_leaf_count();
+    protobuf_response(&proto::StatusResponse {
+        state_root,
+        mmr_root: state.nexus_state.get_mmr_root(),
+        processed_height: processed_height.unwrap_or(0),
+        safety_mode,
+        drift,
+        degraded: processed_height.is_none() || safety_mode.is_none() || drift.is_none(),
+        raw_drift: sample.map(|(raw, _)| raw),
+        smoothed_drift: sample.map(|(_, smoothed)| smoothed),
+        state_root_updated_at: state
+            .nexus_state
+            .last_updated()
+            .map(crate::api::grpc::to_proto_timestamp),
+        leaf_count: leaf_count as u64,
+        seconds_since_last_block: state.nexus_state.seconds_since_last_block(),
+    })
+}
+
+/// The `GET /v1/metrics` body, also pushed by `GET /v1/metrics/stream`.
+pub(crate) async fn collect_metrics(state: &AppState) -> MetricsResponse {
+    let counts = match state.storage.read_repo().chain_counts().await {
+        Ok(counts) => Some((counts.transactions, counts.blocks)),
+        Err(e) => {
+            tracing::warn!(error = %e, "Database unavailable for metrics; reporting degraded");
+            None
+        }
+    };
+
+    let safety_mode = crate::safety::is_safety_mode_active(&state.storage)
+        .await
+        .ok();
+
+    MetricsResponse {
+        total_transactions: counts.map(|(tx, _)| tx),
+        total_blocks: counts.map(|(_, blocks)| blocks),
+        safety_mode,
+        uptime_seconds: crate::api::get_uptime(),
+        degraded: counts.is_none() || safety_mode.is_none(),
+        gateway_services: state.gateway.metrics().snapshot(),
+        gateway_breakers: state.gateway.breaker_snapshots(),
+        build: crate::api::build_info(),
+        route_latencies: crate::latency::route_latencies(),
+        sync_block_latency: crate::latency::sync_block_latency(),
+        grpc_latencies: crate::latency::grpc_latencies(),
+        slowest_queries: crate::latency::slowest_queries(crate::latency::SLOWEST_QUERIES),
+    }
+}
+
+/// `GET /v1/metrics`: JSON by default, or the gRPC `MetricsResponse` when the client asks
+/// for protobuf.
+async fn metrics_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let metrics = collect_metrics(&state).await;
+
+    if wants_protobuf(&headers) {
+        let drift = crate::safety::current_drift(&state.storage).await.ok();
+        return protobuf_response(&proto::MetricsResponse {
+            total_transactions: metrics.total_transactions.unwrap_or(0),
+            total_blocks: metrics.total_blocks.unwrap_or(0),
+            safety_mode: metrics.safety_mode,
+            drift,
+            uptime_seconds: metrics.uptime_seconds,
+            degraded: metrics.degraded || drift.is_none(),
+        });
+    }
+
+    Json(metrics).into_response()
+}
+
+/// Prometheus text exposition of every registered collector.
+async fn prometheus_handler(State(state): State<AppState>) -> impl IntoResponse {
+    state.storage.observe_pool_metrics();
+    let encoder = prometheus::TextEncoder::new();
+    match encoder.encode_to_string(&prometheus::gather()) {
+        Ok(body) => (
+            StatusCode::OK,
+            [(
+                axum::http::header::CONTENT_TYPE,
+                encoder.format_type().to_string(),
+            )],
+            body,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// [NEXUS-DIAG-01] Dependency self-test; 503 when any check fails. Requires the
+/// `admin.write` scope. Reports are cached briefly and failure details are redacted.
+async fn diagnostics_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(unauthorized) =
+        crate::api::admin::authorize_for_scope(&state, &headers, "admin.write")
+    {
+        return unauthorized;
+    }
+    let report =
+        crate::diagnostics::cached_diagnostics(&state.config, &state.storage, &state.http_client)
+            .await
+            .redacted();
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
+
+/// Proof manifest handler for the narrow proof surface (Issue #149)
+async fn get_proof_manifest(State(state): State<AppState>) -> impl IntoResponse {
+    let safety_mode = crate::safety::is_safety_mode_active(&state.storage)
+        .await
+        .unwrap_or(false);
+
+    // Get MMR information from nexus state using the public get_mmr_state method
+    let (mmr_peaks_raw, mmr_leaf_count) = state.nexus_state.get_mmr_state();
+    let mmr_peaks = mmr_peaks_raw.iter().map(hex::encode).collect::<Vec<_>>();
+
+    // Get state root
+    let state_root = {
+        let (root, _) = state.nexus_state.generate_proof("state_root");
+        if root.is_empty() {
+            None
+        } else {
+            Some(root)
+        }
+    };
+
+    Json(ProofManifest {
+        health: HealthStatus {
+            status: "ok".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            safety_mode,
+            uptime_seconds: None,
+        },
+        proof_routes: ProofRoutes {
+            proof_endpoint: "/v1/proof?key=<key>".to_string(),
+            mmr_proof_endpoint: "/v1/mmr-proof?index=<n>".to_string(),
+            health_endpoint: "/health".to_string(),
+            submit_endpoint: "/v1/submit".to_string(),
+        },
+        state_root,
+        mmr_info: MmrInfo {
+            leaf_count: Some(mmr_leaf_count),
+            peaks: mmr_peaks,
+            initialized: true,
+        },
+        service: ServiceMetadata {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            proof_surface_version: "1.0.0".to_string(),
+            supported_chains: vec![
+                "stacks".to_string(),
+                "bitcoin".to_string(),
+                "evm".to_string(),
+                "cosmos".to_string(),
+            ],
+        },
+    })
+}
+
+fn init_prometheus_metrics() {
+    let _ = &*TX_COUNT;
+    let _ = &*REBALANCE_COUNT;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
     use crate::executor::rgb::RGBRolloutMode;
     use crate::storage::tableland::TablelandAdapter;
-    use axum::body::Body;
-    use axum::http::Request;
     use http_body_util::BodyExt;
     use serde_json::Value;
     use std::collections::HashSet;
     use tower::ServiceExt;
 
-    async fn test_router_with_state(
-        enabled: bool,
-        rgb_mode: RGBRolloutMode,
-        known_contracts: HashSet<String>,
-    ) -> axum::Router {
+    async fn test_router_with_state(
+        enabled: bool,
+        rgb_mode: RGBRolloutMode,
+        known_contracts: HashSet<String>,
+    ) -> axum::Router {
+        let mut config = Config::default_test();
+        config.experimental_apis_enabled = enabled;
+        let config = Arc::new(config);
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let nexus_state = Arc::new(NexusState::new());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            rgb_mode,
+            known_contracts,
+        ));
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+
+        app_router(
+            storage,
+            nexus_state,
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap()
+    }
+
+    /// As [`test_router_with_state`], with `token` as the static admin token.
+    async fn test_router_with_admin_token(token: &str) -> axum::Router {
+        let mut config = Config::default_test();
+        config.admin_api_token = Some(token.to_string());
+        test_router_over(config, Arc::new(NexusState::new()))
+    }
+
+    fn test_router_over(config: Config, nexus_state: Arc<NexusState>) -> axum::Router {
+        let config = Arc::new(config);
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
+        ));
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        app_router(
+            storage,
+            nexus_state,
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let app = test_router_with_state(true, RGBRolloutMode::Disabled, HashSet::new()).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let res: HealthResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(res.status, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_oracle_identity_falls_back_to_the_node_wallet() {
+        use crate::oracle::push::{OraclePusher, OracleSignerIdentity};
+        use crate::oracle::OracleService;
+
+        let request = || {
+            Request::builder()
+                .uri("/v1/identity/oracle")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let app = test_router_with_state(true, RGBRolloutMode::Disabled, HashSet::new()).await;
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let mut config = Config::default_test();
+        config.oracle_contract_principal =
+            Some("ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ.fx-oracle".to_string());
+        config.rebalance_private_key_hex = Some(hex::encode([8u8; 32]));
+        let config = Arc::new(config);
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let pusher = OraclePusher::from_config(&config, Arc::new(config.stacks_rpc_endpoints()))
+            .unwrap()
+            .unwrap();
+        let oracle = OracleService::with_providers(storage.clone(), Vec::new()).with_pusher(pusher);
+        let app = app_router(
+            storage.clone(),
+            Arc::new(NexusState::new()),
+            Arc::new(NexusExecutor::new(
+                storage.clone(),
+                RGBRolloutMode::Disabled,
+                HashSet::new(),
+            )),
+            Some(Arc::new(oracle)),
+            Arc::new(TablelandAdapter::new(
+                storage,
+                config.tableland_base_url.clone(),
+            )),
+            None,
+            None,
+            config,
+        )
+        .unwrap();
+
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let identity: OracleSignerIdentity = serde_json::from_slice(&body).unwrap();
+        let node_wallet = crate::stacks::StacksSigner::from_hex(&hex::encode([8u8; 32])).unwrap();
+        assert_eq!(identity.public_key, node_wallet.public_key_hex());
+        assert_eq!(identity.address, node_wallet.address(false).to_string());
+        assert!(identity.node_wallet);
+    }
+
+    #[tokio::test]
+    async fn test_status_degrades_when_redis_unavailable() {
+        let mut config = Config::default_test();
+        config.redis_url = "redis://127.0.0.1:1/".to_string();
+        let config = Arc::new(config);
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
+        ));
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        let nexus_state = Arc::new(NexusState::new());
+        let app = app_router(
+            storage,
+            nexus_state.clone(),
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap();
+        let status = |app: Router| async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/v1/status")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<HealthResponse>(&body).unwrap()
+        };
+        let res = status(app.clone()).await;
+        assert_eq!(res.state_root_updated_at, None);
+        assert_eq!(res.leaf_count, Some(0));
+
+        nexus_state.update_state("tx1", 1);
+        let res = status(app.clone()).await;
+        assert_eq!(res.leaf_count, Some(1));
+        assert_eq!(res.state_root_updated_at, nexus_state.last_updated());
+        // A recompute alone says nothing about how old the chain tip is.
+        assert_eq!(res.seconds_since_last_block, None);
+        nexus_state.record_block_time(chrono::Utc::now() - chrono::Duration::seconds(90));
+        let res = status(app.clone()).await;
+        assert!(res.seconds_since_last_block.is_some_and(|age| age >= 90));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let res: HealthResponse = serde_json::from_slice(&body).unwrap();
+        assert!(res.degraded);
+        assert_eq!(res.safety_mode, None);
+        assert_eq!(res.network, Some(Network::Mainnet));
+        assert!(res.service_restarts.is_some());
+    }
+
+    #[test]
+    fn test_wants_protobuf_negotiation() {
+        let accept = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(axum::http::header::ACCEPT, value.parse().unwrap());
+            wants_protobuf(&headers)
+        };
+        assert!(!wants_protobuf(&HeaderMap::new()));
+        assert!(accept("application/x-protobuf"));
+        assert!(accept("application/protobuf, application/json"));
+        assert!(!accept("application/json, application/x-protobuf"));
+        assert!(!accept("application/x-protobuf;q=0, application/json"));
+        assert!(!accept("*/*"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_stream_pushes_metrics_events() {
+        let mut config = Config::default_test();
+        config.database_url = "postgres://127.0.0.1:1/nexus".to_string();
+        config.database_acquire_timeout_secs = 1;
+        config.redis_url = "redis://127.0.0.1:1/".to_string();
+        config.metrics_stream_interval_secs = 1;
+        let config = Arc::new(config);
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
+        ));
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        let app = app_router(
+            storage,
+            Arc::new(NexusState::new()),
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/metrics/stream")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+
+        let mut body = response.into_body();
+        for _ in 0..2 {
+            let frame = tokio::time::timeout(std::time::Duration::from_secs(10), body.frame())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            let text = String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap();
+            let data = text
+                .strip_prefix("event: metrics\ndata: ")
+                .unwrap()
+                .trim_end();
+            let metrics: MetricsResponse = serde_json::from_str(data).unwrap();
+            assert!(metrics.degraded);
+            assert_eq!(metrics.total_transactions, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_returns_protobuf_when_requested() {
+        let mut config = Config::default_test();
+        config.database_url = "postgres://127.0.0.1:1/nexus".to_string();
+        config.database_acquire_timeout_secs = 1;
+        config.redis_url = "redis://127.0.0.1:1/".to_string();
+        let config = Arc::new(config);
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
+        ));
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        let nexus_state = Arc::new(NexusState::new());
+        let app = app_router(
+            storage,
+            nexus_state.clone(),
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/status")
+                    .header("accept", PROTOBUF_CONTENT_TYPE)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            PROTOBUF_CONTENT_TYPE
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let status = proto::StatusResponse::decode(body).unwrap();
+        assert_eq!(status.state_root, nexus_state.get_state_root());
+        assert!(status.degraded);
+        assert_eq!(status.safety_mode, None);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/metrics")
+                    .header("accept", PROTOBUF_CONTENT_TYPE)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let metrics = proto::MetricsResponse::decode(body).unwrap();
+        assert!(metrics.degraded);
+        assert_eq!(metrics.total_transactions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_report_build_info_and_grpc_uptime() {
+        use crate::api::grpc::proto::nexus_service_server::NexusService;
+
+        crate::api::init_start_time();
+        let config = Arc::new(Config::default_test());
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let nexus_state = Arc::new(NexusState::new());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
+        ));
+        let grpc = crate::api::grpc::NexusGrpcService::new(
+            storage.clone(),
+            nexus_state.clone(),
+            executor.clone(),
+            None,
+            true,
+        );
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        let app = app_router(
+            storage,
+            nexus_state,
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let metrics: MetricsResponse = serde_json::from_slice(&body).unwrap();
+        let version = grpc
+            .get_version(tonic::Request::new(proto::VersionRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(metrics.build.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metrics.build.version, version.version);
+        assert!(!metrics.build.git_commit.is_empty());
+        assert!(!metrics.build.rustc_version.is_empty());
+        assert_eq!(metrics.build.git_commit, version.git_commit);
+        assert_eq!(metrics.build.rustc_version, version.rustc_version);
+        assert_eq!(
+            metrics.build.started_at.map(|t| t.to_rfc3339()),
+            Some(version.started_at)
+        );
+        // Both read the same start record; allow for a second boundary between the calls.
+        assert!(version.uptime_seconds.abs_diff(metrics.uptime_seconds) <= 1);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("nexus_build_info{"));
+        assert!(text.contains("nexus_process_start_time_seconds"));
+    }
+
+    #[tokio::test]
+    async fn test_proof_batch_returns_proofs_and_missing_keys() {
+        let app = test_router_with_admin_token("batch-test-token").await;
+        let batch = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/proof/batch")
+                .header("content-type", "application/json")
+                .header("Authorization", "Bearer batch-test-token")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let anonymous = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/proof/batch")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"keys": ["unknown-key"]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(batch(r#"{"keys": ["unknown-key"]}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let res: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(res["missing"], serde_json::json!(["unknown-key"]));
+        assert!(res["proofs"].as_object().unwrap().is_empty());
+
+        let empty = app.oneshot(batch(r#"{"keys": []}"#)).await.unwrap();
+        assert_eq!(empty.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_proof_reports_tree_size_and_path_length() {
+        let nexus_state = Arc::new(NexusState::new());
+        nexus_state.update_state_batch(&["a", "b", "c", "d"].map(str::to_string));
+        let app = test_router_over(Config::default_test(), nexus_state);
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/v1/proof?key=c")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let res: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(res["leaf_index"], 2);
+        assert_eq!(res["tree_size"], 4);
+        assert_eq!(res["path_length"], 2);
+
+        let response = app.oneshot(get("/v1/proof?key=zz")).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let res: Value = serde_json::from_slice(&body).unwrap();
+        assert!(res.get("leaf_index").is_none());
+        assert_eq!(res["tree_size"], 4);
+        assert_eq!(res["path_length"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_proof_encoding_parameter() {
+        let app = test_router_with_state(true, RGBRolloutMode::Disabled, HashSet::new()).await;
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(get("/v1/proof?key=unknown-key&encoding=base64"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let res: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(res["root"], HashEncoding::Base64.encode(&[0u8; 32]));
+
+        let response = app
+            .oneshot(get("/v1/proof?key=unknown-key&encoding=raw"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_non_inclusion_proof_endpoint() {
+        let app = test_router_with_state(true, RGBRolloutMode::Disabled, HashSet::new()).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/proof/absence?key=unknown-key")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let proof: crate::state::NonInclusionProof = serde_json::from_slice(&body).unwrap();
+        assert_eq!(proof.key, "unknown-key");
+        assert!(crate::state::verify_non_inclusion_proof(
+            &proof,
+            crate::state::LeafHashing::default()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_past_root_absence_proof_requires_api_read() {
+        let app = test_router_with_admin_token("absence-test-token").await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/v1/proof/absence?key=unknown-key&root={}",
+                        "ab".repeat(32)
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_past_root_proof_requires_api_read() {
+        let app = test_router_with_admin_token("proof-test-token").await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/proof?key=unknown-key")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let root = serde_json::from_slice::<Value>(&body).unwrap()["root"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let at_root = |token: Option<&str>| {
+            let mut request =
+                Request::builder().uri(format!("/v1/proof?key=unknown-key&root={}", root));
+            if let Some(token) = token {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        let response = app.clone().oneshot(at_root(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .oneshot(at_root(Some("proof-test-token")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_service_request_dispatch_maps_errors() {
+        let app = test_router_with_admin_token("dispatch-test-token").await;
+
+        let anonymous = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/services/bitvm")
+                    .body(Body::from(r#"{"command": "settle"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+        let unknown = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/services/unknown")
+                    .header("Authorization", "Bearer dispatch-test-token")
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+
+        let malformed = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/services/bitvm")
+                    .header("Authorization", "Bearer dispatch-test-token")
+                    .body(Body::from(r#"{"command": "settle"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(malformed.status(), StatusCode::BAD_REQUEST);
+        let body = malformed.into_body().collect().await.unwrap().to_bytes();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["kind"], "bad_request");
+    }
+
+    #[tokio::test]
+    async fn test_gateway_counters_exported_and_audit_log_requires_admin() {
+        let app = test_router_with_admin_token("metrics-test-token").await;
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/services/bitvm")
+                    .header("Authorization", "Bearer metrics-test-token")
+                    .body(Body::from(r#"{"command": "settle"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let scrape = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(scrape.status(), StatusCode::OK);
+        let body = scrape.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("nexus_gateway_requests_total"));
+
+        let anonymous = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/services/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+        let telemetry = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/services/metrics")
+                    .header("Authorization", "Bearer metrics-test-token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(telemetry.status(), StatusCode::OK);
+        let body = telemetry.into_body().collect().await.unwrap().to_bytes();
+        let telemetry: Value = serde_json::from_slice(&body).unwrap();
+        // A malformed request is the caller's fault, not a gateway failure.
+        assert_eq!(telemetry["metrics"]["verification_success"], 1);
+        assert_eq!(telemetry["metrics"]["verification_failure"], 0);
+        assert_eq!(telemetry["services"][0]["service"], "bitvm");
+        assert_eq!(telemetry["services"][0]["rejected"], 1);
+        assert!(telemetry["services"][0].get("last_error").is_none());
+
+        let audit = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/services/bitvm/requests?limit=5")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(audit.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_state_leaves_requires_auth_and_paginates() {
         let mut config = Config::default_test();
-        config.experimental_apis_enabled = enabled;
+        config.admin_api_token = Some("leaves-test-token".to_string());
         let config = Arc::new(config);
         let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
         let nexus_state = Arc::new(NexusState::new());
+        nexus_state.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
         let executor = Arc::new(NexusExecutor::new(
             storage.clone(),
-            rgb_mode,
-            known_contracts,
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
         ));
         let tableland = Arc::new(TablelandAdapter::new(
             storage.clone(),
             config.tableland_base_url.clone(),
         ));
-
-        app_router(
+        let app = app_router(
             storage,
-            nexus_state,
+            nexus_state.clone(),
             executor,
             None,
             tableland,
@@ -544,26 +2484,248 @@ mod tests {
             None,
             config,
         )
+        .unwrap();
+
+        let anonymous = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/state/leaves")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/state/leaves?offset=1&limit=1")
+                    .header("Authorization", "Bearer leaves-test-token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let page: crate::state::LeafPage = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.leaves, vec!["b".to_string()]);
+        assert_eq!(page.root, nexus_state.get_state_root());
     }
 
     #[tokio::test]
-    async fn test_health_check() {
+    async fn test_verify_chain_requires_api_read() {
         let app = test_router_with_state(true, RGBRolloutMode::Disabled, HashSet::new()).await;
-
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/health")
-                    .body(Body::empty())
+                    .method("POST")
+                    .uri("/v1/state/verify-chain")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"checkpoint":"0x00","roots":[]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_preview_root_leaves_state_untouched() {
+        let app = test_router_with_admin_token("preview-test-token").await;
+        let preview = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/state/preview-root")
+                .header("content-type", "application/json")
+                .header("Authorization", "Bearer preview-test-token")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let anonymous = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/state/preview-root")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"leaves": []}"#))
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
 
+        let response = app
+            .clone()
+            .oneshot(preview(r#"{"leaves": ["pending-1", "pending-2"]}"#))
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let res: HealthResponse = serde_json::from_slice(&body).unwrap();
-        assert_eq!(res.status, "ok");
+        let res: Value = serde_json::from_slice(&body).unwrap();
+        let empty_root = res["current_root"].clone();
+        assert_ne!(res["preview_root"], empty_root);
+
+        let again = app.oneshot(preview(r#"{"leaves": []}"#)).await.unwrap();
+        let body = again.into_body().collect().await.unwrap().to_bytes();
+        let res: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(res["current_root"], empty_root);
+        assert_eq!(res["preview_root"], empty_root);
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_requires_admin_scope() {
+        let app = test_router_with_admin_token("diagnostics-test-token").await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/diagnostics")
+                    .header("Authorization", "Bearer wrong-token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_vaults_lists_health_and_filters_by_ltv() {
+        use crate::executor::VaultStatus;
+        use crate::storage::store::InMemoryStore;
+
+        let config = Arc::new(Config::default_test());
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let store = Arc::new(InMemoryStore::new());
+        let vault = |id: &str, debt: u64| VaultStatus {
+            vault_id: id.to_string(),
+            collateral_type: "BTC".to_string(),
+            collateral_amount: 100_000_000u64.into(),
+            debt_amount: debt.into(),
+            ltv_ratio: Default::default(),
+        };
+        store.set_vaults(vec![
+            vault("v-low", 30_000_000_000),
+            vault("v-high", 54_000_000_000),
+        ]);
+        store.set_collateral_prices(std::collections::BTreeMap::from([(
+            "BTC".to_string(),
+            60_000.0,
+        )]));
+        let executor = Arc::new(
+            NexusExecutor::new(storage.clone(), RGBRolloutMode::Disabled, HashSet::new())
+                .with_store(store),
+        );
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        let app = app_router(
+            storage,
+            Arc::new(NexusState::new()),
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            config,
+        )
+        .unwrap();
+        let vaults = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = response.into_body().collect().await.unwrap().to_bytes();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            }
+        };
+
+        let all = vaults("/v1/vaults").await;
+        assert_eq!(all["rebalance_ltv_threshold_bps"], 8_000);
+        assert_eq!(all["vaults"][0]["vault_id"], "v-high");
+        assert_eq!(all["vaults"][0]["ltv_bps"], 9_000);
+        assert_eq!(all["vaults"][0]["health"], "rebalancing");
+        assert_eq!(all["vaults"][1]["health"], "healthy");
+        assert_eq!(all["vaults"][1]["debt_amount"], "30000000000");
+
+        let risky = vaults("/v1/vaults?min_ltv_bps=7000").await;
+        assert_eq!(risky["vaults"].as_array().unwrap().len(), 1);
+        assert_eq!(risky["vaults"][0]["vault_id"], "v-high");
+    }
+
+    #[tokio::test]
+    async fn test_submit_rejects_oversized_and_malformed_bodies() {
+        let app = test_router_with_state(true, RGBRolloutMode::Disabled, HashSet::new()).await;
+        let submit = |body: Vec<u8>| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/submit")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let error = |response: Response| async move {
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        };
+
+        let oversized = vec![b' '; crate::config::DEFAULT_SUBMIT_MAX_BODY_BYTES + 1];
+        let response = app.clone().oneshot(submit(oversized)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error(response).await["code"], "payload_too_large");
+
+        let response = app
+            .clone()
+            .oneshot(submit(br#"{"sender": "SP1", "nonce": "#.to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error(response).await["code"], "malformed_json");
+
+        let response = app
+            .clone()
+            .oneshot(submit(br#"{"sender": "SP1", "nonce": "one"}"#.to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = error(response).await;
+        assert_eq!(body["code"], "invalid_request");
+        assert!(body["error"].as_str().unwrap().contains("invalid type"));
+
+        let mut keyed = submit(
+            br#"{"tx_id":"tx","payload":"","timestamp":"2026-01-01T00:00:00Z","sender":"SP1","nonce":1}"#
+                .to_vec(),
+        );
+        keyed
+            .headers_mut()
+            .insert(IDEMPOTENCY_HEADER, HeaderValue::from_static("has space"));
+        let response = app.clone().oneshot(keyed).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error(response).await["code"], "invalid_idempotency_key");
+
+        // Without an API key there is no scope to keep the key in.
+        let mut anonymous = submit(
+            br#"{"tx_id":"tx","payload":"","timestamp":"2026-01-01T00:00:00Z","sender":"SP1","nonce":1}"#
+                .to_vec(),
+        );
+        anonymous
+            .headers_mut()
+            .insert(IDEMPOTENCY_HEADER, HeaderValue::from_static("retry-1"));
+        let response = app.oneshot(anonymous).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            error(response).await["code"],
+            "idempotency_requires_api_key"
+        );
     }
 
     /// Test for Issue #149: Narrow proof surface manifest endpoint
@@ -742,7 +2904,8 @@ mod tests {
             None,
             None,
             config,
-        );
+        )
+        .unwrap();
 
         let response = app
             .oneshot(
@@ -763,4 +2926,88 @@ mod tests {
         );
         assert_eq!(payload.get("pos").and_then(Value::as_u64), Some(0));
     }
+
+    #[tokio::test]
+    async fn test_rest_server_reports_ephemeral_bound_addr() {
+        let config = Arc::new(Config::default_test());
+        let storage = Arc::new(Storage::from_config_lazy(&config).unwrap());
+        let nexus_state = Arc::new(NexusState::new());
+        let executor = Arc::new(NexusExecutor::new(
+            storage.clone(),
+            RGBRolloutMode::Disabled,
+            HashSet::new(),
+        ));
+        let tableland = Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        ));
+        let gateway = Arc::new(
+            ServiceRegistry::from_config(&config, storage.clone(), nexus_state.clone()).unwrap(),
+        );
+        let server = tokio::spawn(start_rest_server(
+            storage,
+            nexus_state,
+            executor,
+            None,
+            tableland,
+            None,
+            None,
+            "127.0.0.1:0".parse().unwrap(),
+            config,
+            gateway,
+            None,
+        ));
+
+        let mut bound = None;
+        for _ in 0..100 {
+            bound = crate::api::listen_addrs().rest;
+            if bound.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let bound = bound.expect("REST listener records its address");
+        assert!(bound.ip().is_loopback());
+        assert_ne!(bound.port(), 0);
+
+        let response = reqwest::get(format!("http://{}/health", bound))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_in_flight_limit_are_shed_except_health() {
+        let in_flight = Arc::new(Semaphore::new(1));
+        let app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/v1/proof", get(|| async { "proof" }))
+            .layer(axum::middleware::from_fn_with_state(
+                in_flight.clone(),
+                shed_load,
+            ));
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/v1/proof")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(in_flight.available_permits(), 1);
+
+        let held = in_flight.clone().acquire_owned().await.unwrap();
+        let shed_before = SHED_REQUESTS.get();
+        let response = app.clone().oneshot(get("/v1/proof")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["code"], "overloaded");
+        assert!(SHED_REQUESTS.get() > shed_before);
+
+        let response = app.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        drop(held);
+        let response = app.oneshot(get("/v1/proof")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
@@ -1,9 +1,13 @@
+use crate::gateway::breaker::BreakerSnapshot;
+use crate::gateway::ServiceError;
 use lib_conxian_core::gateway::{BisqService, BitVMService, ConxianService, RGBService};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Serialize)]
 pub struct MultiProtocolStatus {
     pub services: Vec<lib_conxian_core::gateway::ServiceStatus>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub breakers: Vec<BreakerSnapshot>,
 }
 
 pub fn get_all_services_status() -> MultiProtocolStatus {
@@ -13,13 +17,160 @@ pub fn get_all_services_status() -> MultiProtocolStatus {
 
     MultiProtocolStatus {
         services: vec![bisq.status(), rgb.status(), bitvm.status()],
+        breakers: Vec::new(),
     }
 }
 use crate::api::rest::AppState;
-use axum::{response::IntoResponse, routing::get, Json, Router};
+use crate::gateway::audit::caller_fingerprint;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+
+/// Default and maximum page sizes for the gateway request audit listing.
+const DEFAULT_AUDIT_LIMIT: i64 = 50;
+const MAX_AUDIT_LIMIT: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub limit: Option<i64>,
+}
+
 pub fn services_routes() -> Router<AppState> {
-    Router::new().route("/status", get(get_services_status_handler))
+    Router::new()
+        .route("/", get(get_services_status_handler))
+        .route("/status", get(get_services_status_handler))
+        .route("/metrics", get(get_services_metrics_handler))
+        .route("/{name}", post(dispatch_service_request_handler))
+        .route("/{name}/requests", get(list_service_requests_handler))
+}
+async fn get_services_status_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(MultiProtocolStatus {
+        services: state.gateway.statuses(),
+        breakers: state.gateway.breaker_snapshots(),
+    })
+}
+
+/// Per-service counters with verification totals in the shape the safety monitor reads
+/// from `GATEWAY_TELEMETRY_URL`, so it can be pointed at this node. Requires the
+/// `api.read` scope.
+async fn get_services_metrics_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(unauthorized) = crate::api::admin::authorize_for_scope(&state, &headers, "api.read")
+    {
+        return unauthorized;
+    }
+    Json(state.gateway.metrics().telemetry()).into_response()
+}
+
+/// Maps a gateway service error onto the HTTP status returned to callers.
+pub fn service_error_status(err: &ServiceError) -> StatusCode {
+    match err {
+        ServiceError::BadRequest(_) => StatusCode::BAD_REQUEST,
+        ServiceError::UpstreamUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        ServiceError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
 }
-async fn get_services_status_handler() -> impl IntoResponse {
-    Json(get_all_services_status())
+
+/// The caller's API key (`x-api-key` or bearer token), if any.
+pub(crate) fn api_key_from_headers(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+        .filter(|key| !key.is_empty())
+}
+
+/// Routes the request body to the named service's `handle_request`, attributed to the
+/// fingerprint of the bearer token it was authorized with. Requires the `api.write` scope.
+async fn dispatch_service_request_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if let Err(unauthorized) = crate::api::admin::authorize_for_scope(&state, &headers, "api.write")
+    {
+        return unauthorized;
+    }
+    let caller = crate::api::admin::bearer_token(&headers).map(|token| caller_fingerprint(&token));
+    match state.gateway.dispatch_from(&name, &body, caller).await {
+        Some(Ok(response)) => (StatusCode::OK, Json(response.body)).into_response(),
+        Some(Err(err)) => {
+            tracing::warn!(service = %name, error = %err, "Gateway service request failed");
+            let retry_after = err.retry_after().map(|d| d.as_secs().max(1));
+            let mut response = (
+                service_error_status(&err),
+                Json(serde_json::json!({
+                    "error": err.message(),
+                    "kind": err.kind(),
+                    "retry_after_secs": retry_after,
+                })),
+            )
+                .into_response();
+            if let Some(secs) = retry_after {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, header::HeaderValue::from(secs));
+            }
+            response
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("Unknown service '{}'", name) })),
+        )
+            .into_response(),
+    }
+}
+
+async fn list_service_requests_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    if let Err(unauthorized) =
+        crate::api::admin::authorize_for_scope(&state, &headers, "admin.write")
+    {
+        return unauthorized;
+    }
+
+    if state.gateway.get(&name).is_none() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("Unknown service '{}'", name) })),
+        )
+            .into_response();
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT);
+    match state.gateway.recent_requests(&name, limit).await {
+        Ok(requests) => Json(serde_json::json!({
+            "service": name,
+            "requests": requests,
+            "metrics": state.gateway.metrics().service(&name),
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!(service = %name, error = %e, "Failed to load gateway audit log");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to load gateway audit log" })),
+            )
+                .into_response()
+        }
+    }
 }
@@ -2,6 +2,7 @@
 //! Handles ISO 20022, PAPSS, and BRICS triggers for TEE-verified proposals.
 
 use crate::api::rest::AppState;
+use crate::latency::timed_query;
 use crate::storage::kwil::{KwilSettlementLogCommitment, KwilSettlementProposalCommitment};
 use axum::http::StatusCode;
 use axum::routing::post;
@@ -9,7 +10,6 @@ use axum::Router;
 use axum::{extract::State, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sqlx::Row;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
@@ -529,7 +529,7 @@ pub async fn settlement_trigger_handler(
         .and_then(|v| v.as_str());
 
     let external_tx_ref = uetr.or(e2e_id).unwrap_or(&payload.external_id).to_string();
-    let _ = sqlx::query(
+    let _ = timed_query("insert_settlement_log", sqlx::query(
         "INSERT INTO cxn_external_settlement_logs (external_tx_reference, settlement_network_origin, fiat_value_pegged, raw_payload)
          VALUES ($1, $2, $3, $4)",
     )
@@ -537,8 +537,7 @@ pub async fn settlement_trigger_handler(
     .bind(&payload.source)
     .bind(fiat_value)
     .bind(&payload.payload)
-    .execute(&state.storage.pg_pool)
-    .await;
+    .execute(&state.storage.pg_pool)).await;
 
     // [CON-330] Pilot: Mirror settlement log to Kwil
     if let Some(kwil) = &state.kwil {
@@ -555,21 +554,13 @@ pub async fn settlement_trigger_handler(
     }
 
     // 5. Get current block height to calculate time-lock
-    let row_res: Result<Option<sqlx::postgres::PgRow>, sqlx::Error> =
-        sqlx::query::<sqlx::Postgres>("SELECT MAX(height) as max_height FROM stacks_blocks")
-            .fetch_optional(&state.storage.pg_pool)
-            .await;
-
-    let current_height: i64 = match row_res {
-        Ok(Some(row)) => row.get::<Option<i64>, _>("max_height").unwrap_or(0),
-        _ => 0,
-    };
+    let current_height = state.storage.repo().max_block_height().await.unwrap_or(0) as i64;
 
     let unlock_height = (current_height + 144) as u64;
     let proposal_id = format!("prop_{}", Uuid::new_v4());
 
     // 6. Persist the proposal as "proposal-only"
-    let res = sqlx::query(
+    let res = timed_query("insert_settlement_proposal", sqlx::query(
         "INSERT INTO settlement_proposals (proposal_id, external_id, source, payload, status, init_height, unlock_height)
          VALUES ($1, $2, $3, $4, 'active', $5, $6)",
     )
@@ -579,8 +570,7 @@ pub async fn settlement_trigger_handler(
     .bind(&payload.payload)
     .bind(current_height)
     .bind(unlock_height as i64)
-    .execute(&state.storage.pg_pool)
-    .await;
+    .execute(&state.storage.pg_pool)).await;
 
     // [CON-330] Pilot: Mirror settlement proposal to Kwil
     if let Some(kwil) = &state.kwil {
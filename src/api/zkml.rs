@@ -138,13 +138,16 @@ mod tests {
             config,
             storage,
             nexus_state,
+            dynamic_config: executor.dynamic.clone(),
             executor,
             oracle: None,
             tableland,
             kwil: None,
             nostr: None,
+            gateway: Arc::new(crate::gateway::ServiceRegistry::with_builtin_services()),
             gateway_url: None,
             http_client: reqwest::Client::new(),
+            metrics_feed: Default::default(),
         };
 
         let payload = ZkmlVerifyRequest {
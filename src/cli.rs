@@ -0,0 +1,393 @@
+//! Command-line interface. Without a subcommand the node serves traffic; the other
+//! subcommands run one operational task against the configured stores and exit, with a
+//! non-zero status when the task fails.
+
+use crate::config::network::Network;
+use crate::config::Config;
+use crate::stacks::transaction::StacksSigner;
+use crate::state::snapshot::{self, StateSnapshot};
+use crate::state::NexusState;
+use crate::storage::store::NexusStore;
+use crate::storage::tableland::TablelandAdapter;
+use crate::storage::Storage;
+use crate::sync::NexusSync;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use k256::ecdsa::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+#[command(name = "conxian-nexus", version, about = "Conxian Nexus Glass Node")]
+pub struct Cli {
+    /// Report dependency health as JSON and exit; non-zero when unhealthy.
+    #[arg(long)]
+    pub check: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Run the node (the default).
+    Serve,
+    /// Apply pending database migrations.
+    Migrate,
+    /// Copy Redis keys written before `REDIS_NAMESPACE` into the configured namespace.
+    MigrateRedis,
+    /// Ingest blocks FROM..=TO from the Stacks API, then rebuild the state tree.
+    Backfill {
+        #[arg(long)]
+        from: u64,
+        #[arg(long)]
+        to: u64,
+    },
+    /// Replay the database into a fresh state tree, write it to a snapshot file and print
+    /// its root. The running node's tree and published root are left untouched.
+    RebuildState {
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Write the state tree, rebuilt from the database, to a snapshot file.
+    ExportSnapshot {
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Verify a snapshot file and install it as the base of the state tree.
+    ImportSnapshot { path: PathBuf },
+    /// Generate a Stacks signing key for the configured network into a keystore file
+    /// created with owner-only permissions; only the public half is printed.
+    Keygen {
+        #[arg(long)]
+        keystore: PathBuf,
+    },
+}
+
+/// A generated key. The keystore file holds this JSON; it is not encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedKey {
+    pub network: Network,
+    pub address: String,
+    pub public_key: String,
+    pub private_key: String,
+}
+
+/// Generates a key for `network` and writes it to `keystore`. An existing keystore file
+/// is never overwritten.
+pub fn keygen(network: Network, keystore: &Path) -> anyhow::Result<GeneratedKey> {
+    let key = loop {
+        // Rejects the negligible fraction of values outside the curve order.
+        if let Ok(key) = SigningKey::from_slice(&rand::random::<[u8; 32]>()) {
+            break key;
+        }
+    };
+    let private_key = hex::encode(key.to_bytes());
+    let signer = StacksSigner::new(key);
+    let generated = GeneratedKey {
+        network,
+        address: signer.address(network.is_mainnet()).to_string(),
+        public_key: signer.public_key_hex(),
+        private_key,
+    };
+    write_keystore(keystore, &generated)
+        .with_context(|| format!("Cannot write keystore {}", keystore.display()))?;
+    Ok(generated)
+}
+
+fn write_keystore(path: &Path, key: &GeneratedKey) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(&serde_json::to_vec_pretty(key)?)?;
+    Ok(())
+}
+
+fn state_tracker(config: &Config) -> NexusState {
+    NexusState::with_arity(config.merkle_arity)
+        .with_leaf_hashing(config.merkle_leaf_hashing)
+        .with_proof_cache_size(config.proof_cache_size)
+}
+
+/// Replays the store's leaves into a fresh state tree. Nothing is written back, so the
+/// root a running node publishes is unaffected.
+pub async fn replay_state(store: &dyn NexusStore, config: &Config) -> anyhow::Result<NexusState> {
+    let state = state_tracker(config);
+    state.set_initial_leaves(store.state_leaves().await?);
+    Ok(state)
+}
+
+/// Writes the state tree, replayed from the store, to `out` at the current sync
+/// watermark, signed with `SNAPSHOT_PRIVATE_KEY` when it is set.
+pub async fn export_snapshot(
+    store: &dyn NexusStore,
+    config: &Config,
+    out: &Path,
+) -> anyhow::Result<StateSnapshot> {
+    let state = replay_state(store, config).await?;
+    let signer = snapshot_signer(config)?;
+    let snapshot = snapshot::export(store, &state, signer.as_ref()).await?;
+    snapshot.write(out)?;
+    Ok(snapshot)
+}
+
+/// Verifies the snapshot at `path`, including its signature against
+/// `SNAPSHOT_TRUSTED_KEYS`, and installs it, so later rebuilds start from its leaves
+/// rather than the transaction history and sync resumes after its height.
+pub async fn import_snapshot(
+    sync: &NexusSync,
+    config: &Config,
+    path: &Path,
+) -> anyhow::Result<StateSnapshot> {
+    let snapshot = StateSnapshot::read(path)?;
+    snapshot::install(
+        &*sync.storage,
+        &sync.state_tracker,
+        &snapshot,
+        &config.snapshot_trusted_keys,
+    )
+    .await?;
+    sync.rebuild_state().await?;
+    Ok(snapshot)
+}
+
+/// The key snapshots are signed with; `None` leaves them unsigned.
+pub fn snapshot_signer(config: &Config) -> anyhow::Result<Option<StacksSigner>> {
+    config
+        .snapshot_private_key
+        .as_deref()
+        .map(StacksSigner::from_hex)
+        .transpose()
+        .with_context(|| format!("Invalid {}", crate::config::ENV_SNAPSHOT_PRIVATE_KEY))
+}
+
+/// Runs a task subcommand to completion. `Serve` is handled by the binary.
+pub async fn run(command: Command, config: &Config) -> anyhow::Result<()> {
+    if let Command::Keygen { keystore } = &command {
+        let key = keygen(config.network, keystore)?;
+        println!("network:     {}", key.network);
+        println!("address:     {}", key.address);
+        println!("public key:  {}", key.public_key);
+        println!("keystore:    {}", keystore.display());
+        return Ok(());
+    }
+
+    let storage = Arc::new(Storage::new(config).await?);
+    if command == Command::Migrate {
+        storage.run_migrations().await?;
+        println!("Migrations applied");
+        return Ok(());
+    }
+    if command == Command::MigrateRedis {
+        let copied = storage.migrate_legacy_redis_keys().await?;
+        println!(
+            "Copied {} legacy Redis keys into {}",
+            copied,
+            storage.redis_namespace()
+        );
+        return Ok(());
+    }
+    storage.require_migrated().await?;
+    match &command {
+        Command::RebuildState { out } => {
+            println!("{}", export_snapshot(&*storage, config, out).await?.root);
+            return Ok(());
+        }
+        Command::ExportSnapshot { out } => {
+            let snapshot = export_snapshot(&*storage, config, out).await?;
+            println!(
+                "Wrote {} leaves (root {}) to {}",
+                snapshot.leaves.len(),
+                snapshot.root,
+                out.display()
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    // Tasks write to Postgres and Redis only; Kwil mirroring is left to the server.
+    let sync = NexusSync::new(
+        storage.clone(),
+        Arc::new(state_tracker(config)),
+        Arc::new(TablelandAdapter::new(
+            storage.clone(),
+            config.tableland_base_url.clone(),
+        )),
+        None,
+        Arc::new(config.stacks_rpc_endpoints()),
+        config.stacks_node_ws_url.clone(),
+    )
+    .with_burn_confirmations(config.sync_burn_confirmations)
+    .with_sync_interval(Duration::from_secs(config.sync_interval_secs))
+    .with_max_event_attempts(config.sync_event_max_attempts)
+    .with_vault_contracts(config.vault_contract_ids.clone())
+    .with_oracle_contract(
+        config.oracle_contract_principal.clone(),
+        config.oracle_contract_function.clone(),
+    );
+
+    match command {
+        Command::Backfill { from, to } => {
+            // Block ingestion folds transactions into the tracker, so start from the
+            // stored tree and rebuild once more to settle on the commit order.
+            sync.rebuild_state().await?;
+            let blocks = sync.backfill(from, to).await?;
+            let root = sync.rebuild_state().await?;
+            println!("Backfilled {} blocks; state root {}", blocks, root);
+        }
+        Command::ImportSnapshot { path } => {
+            let snapshot = import_snapshot(&sync, config, &path).await?;
+            println!(
+                "Installed {} leaves (root {}) from {}",
+                snapshot.leaves.len(),
+                snapshot.root,
+                path.display()
+            );
+        }
+        Command::Serve
+        | Command::Migrate
+        | Command::MigrateRedis
+        | Command::RebuildState { .. }
+        | Command::ExportSnapshot { .. }
+        | Command::Keygen { .. } => unreachable!(),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stacks::address::StacksAddress;
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from(["conxian-nexus"]).unwrap();
+        assert_eq!(cli.command, None);
+        assert!(!cli.check);
+        assert!(
+            Cli::try_parse_from(["conxian-nexus", "--check"])
+                .unwrap()
+                .check
+        );
+
+        let cli = Cli::try_parse_from(["conxian-nexus", "backfill", "--from", "10", "--to", "20"])
+            .unwrap();
+        assert_eq!(cli.command, Some(Command::Backfill { from: 10, to: 20 }));
+        let cli =
+            Cli::try_parse_from(["conxian-nexus", "export-snapshot", "--out", "s.json"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::ExportSnapshot {
+                out: PathBuf::from("s.json")
+            })
+        );
+        let cli = Cli::try_parse_from(["conxian-nexus", "import-snapshot", "s.json"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::ImportSnapshot {
+                path: PathBuf::from("s.json")
+            })
+        );
+
+        let cli = Cli::try_parse_from(["conxian-nexus", "migrate-redis"]).unwrap();
+        assert_eq!(cli.command, Some(Command::MigrateRedis));
+        let cli =
+            Cli::try_parse_from(["conxian-nexus", "rebuild-state", "--out", "s.json"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::RebuildState {
+                out: PathBuf::from("s.json")
+            })
+        );
+        let cli = Cli::try_parse_from(["conxian-nexus", "keygen", "--keystore", "k.json"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Keygen {
+                keystore: PathBuf::from("k.json")
+            })
+        );
+
+        // The private key only ever goes to a keystore file.
+        assert!(Cli::try_parse_from(["conxian-nexus", "keygen"]).is_err());
+        assert!(Cli::try_parse_from(["conxian-nexus", "rebuild-state"]).is_err());
+
+        assert!(Cli::try_parse_from(["conxian-nexus", "backfill", "--from", "10"]).is_err());
+        assert!(
+            Cli::try_parse_from(["conxian-nexus", "backfill", "--from", "x", "--to", "1"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["conxian-nexus", "frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn test_keygen_writes_keystore_once() {
+        let path = std::env::temp_dir().join(format!("nexus-key-{}.json", uuid::Uuid::new_v4()));
+        let key = keygen(Network::Testnet, &path).unwrap();
+        assert!(key.address.starts_with("ST"));
+        let address: StacksAddress = key.address.parse().unwrap();
+        assert!(!address.is_mainnet());
+        assert_eq!(
+            StacksSigner::from_hex(&key.private_key)
+                .unwrap()
+                .public_key_hex(),
+            key.public_key
+        );
+
+        let stored: GeneratedKey = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(stored.private_key, key.private_key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        // Never clobbers an existing key.
+        assert!(keygen(Network::Testnet, &path).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(keygen(Network::Mainnet, &path)
+            .unwrap()
+            .address
+            .starts_with("SP"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_state_writes_a_snapshot_offline() {
+        use crate::storage::store::InMemoryStore;
+
+        let store = InMemoryStore::new();
+        for leaf in ["a", "b", "c"] {
+            store.append_gateway_leaf("dlc", leaf).await.unwrap();
+        }
+        let config = Config::default_test();
+        let path = std::env::temp_dir().join(format!("nexus-state-{}.json", uuid::Uuid::new_v4()));
+
+        let snapshot = export_snapshot(&store, &config, &path).await.unwrap();
+        assert_eq!(snapshot.leaves.len(), 3);
+        assert_eq!(
+            snapshot.root,
+            replay_state(&store, &config)
+                .await
+                .unwrap()
+                .get_state_root()
+        );
+        let written = StateSnapshot::read(&path).unwrap();
+        assert_eq!(written.root, snapshot.root);
+        // Nothing is written back to the store.
+        assert_eq!(store.state_leaves().await.unwrap().len(), 3);
+        // An existing snapshot is replaced by the next rebuild.
+        store.append_gateway_leaf("dlc", "d").await.unwrap();
+        let next = export_snapshot(&store, &config, &path).await.unwrap();
+        assert_ne!(next.root, snapshot.root);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
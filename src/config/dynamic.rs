@@ -0,0 +1,295 @@
+//! Settings that can change without a restart. Services hold a [`DynamicConfigHandle`]
+//! and read it on every cycle; a reload re-reads the configuration file and environment,
+//! validates the result and swaps it in atomically, or leaves the old values in place.
+
+use super::{
+    Config, ConfigError, LoadedConfig, DEFAULT_SAFETY_HEARTBEAT_SECS, DEFAULT_SAFETY_MAX_DRIFT,
+    ENV_REBALANCE_LTV_THRESHOLD_BPS, ENV_SAFETY_HEARTBEAT_SECS,
+};
+use crate::executor::fixed::Ratio;
+use crate::executor::ltv::{BPS_SCALE, REBALANCE_LTV_THRESHOLD_BPS};
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
+
+/// The reloadable subset of [`Config`]; field names match it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DynamicConfig {
+    /// `tracing` filter directives: `RUST_LOG` followed by the `LOG_LEVEL` overrides.
+    pub rust_log: String,
+    /// Smoothed drift, in blocks, above which the safety monitor enters Safety Mode.
+    pub safety_max_drift: u64,
+    /// Seconds between safety heartbeats.
+    pub safety_heartbeat_secs: u64,
+    /// Computed vault LTV at or above which a vault is due for rebalancing.
+    #[serde(with = "crate::executor::fixed::bps")]
+    pub rebalance_ltv_threshold_bps: Ratio,
+}
+
+impl Default for DynamicConfig {
+    fn default() -> Self {
+        Self {
+            rust_log: "info".to_string(),
+            safety_max_drift: DEFAULT_SAFETY_MAX_DRIFT,
+            safety_heartbeat_secs: DEFAULT_SAFETY_HEARTBEAT_SECS,
+            rebalance_ltv_threshold_bps: Ratio::from_bps(REBALANCE_LTV_THRESHOLD_BPS),
+        }
+    }
+}
+
+impl DynamicConfig {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            rust_log: config.log_filter(),
+            safety_max_drift: config.safety_max_drift,
+            safety_heartbeat_secs: config.safety_heartbeat_secs,
+            rebalance_ltv_threshold_bps: config.rebalance_ltv_threshold_bps,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = ConfigError::default();
+        if let Err(e) = EnvFilter::try_new(&self.rust_log) {
+            errors.push(format!(
+                "RUST_LOG '{}' is not a valid filter: {}",
+                self.rust_log, e
+            ));
+        }
+        if self.safety_heartbeat_secs == 0 {
+            errors.push(format!("{} must be at least 1", ENV_SAFETY_HEARTBEAT_SECS));
+        }
+        if self.rebalance_ltv_threshold_bps == Ratio::ZERO
+            || self.rebalance_ltv_threshold_bps > Ratio::ONE
+        {
+            errors.push(format!(
+                "{} must be between 1 and {}",
+                ENV_REBALANCE_LTV_THRESHOLD_BPS, BPS_SCALE
+            ));
+        }
+        if errors.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// `key: old -> new` for each field that differs in `next`.
+    pub fn diff(&self, next: &Self) -> Vec<String> {
+        let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(next))
+        else {
+            return Vec::new();
+        };
+        old.iter()
+            .filter(|(key, value)| new.get(*key) != Some(*value))
+            .map(|(key, value)| format!("{}: {} -> {}", key, value, new[key]))
+            .collect()
+    }
+}
+
+/// Config-file keys that feed [`DynamicConfig`]. `log_level` is folded into `rust_log`.
+const RELOADABLE_KEYS: [&str; 5] = [
+    "rust_log",
+    "log_level",
+    "safety_max_drift",
+    "safety_heartbeat_secs",
+    "rebalance_ltv_threshold_bps",
+];
+
+/// The reloadable keys of a config file.
+fn reloadable_entries(file: &toml::Table) -> toml::Table {
+    file.iter()
+        .filter(|(key, _)| RELOADABLE_KEYS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+type LogReloader = dyn Fn(&str) -> anyhow::Result<()> + Send + Sync;
+
+/// Shared, atomically swappable [`DynamicConfig`]. Clones see the same values.
+#[derive(Clone)]
+pub struct DynamicConfigHandle {
+    current: Arc<ArcSwap<DynamicConfig>>,
+    /// File passed to [`Config::load`] on reload.
+    config_path: Option<PathBuf>,
+    /// Reloadable keys as the file had them at startup; see [`Self::reload`].
+    startup_file: Arc<toml::Table>,
+    /// Applies a new `rust_log` to the running subscriber.
+    log_reloader: Option<Arc<LogReloader>>,
+}
+
+impl Default for DynamicConfigHandle {
+    fn default() -> Self {
+        Self::new(DynamicConfig::default())
+    }
+}
+
+impl DynamicConfigHandle {
+    pub fn new(initial: DynamicConfig) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(initial)),
+            config_path: None,
+            startup_file: Arc::default(),
+            log_reloader: None,
+        }
+    }
+
+    /// Reloads read this file rather than searching for one again.
+    pub fn with_config_path(mut self, path: Option<PathBuf>) -> Self {
+        self.config_path = path;
+        self
+    }
+
+    /// The file as read at startup, against which reloads detect edited keys.
+    pub fn with_startup_file(mut self, file: &toml::Table) -> Self {
+        self.startup_file = Arc::new(reloadable_entries(file));
+        self
+    }
+
+    pub fn with_log_reloader(
+        mut self,
+        reloader: impl Fn(&str) -> anyhow::Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.log_reloader = Some(Arc::new(reloader));
+        self
+    }
+
+    /// The values in force now; hold the result for one cycle at most.
+    pub fn get(&self) -> Arc<DynamicConfig> {
+        self.current.load_full()
+    }
+
+    /// Validates `next` and swaps it in, returning the changed keys. On error nothing
+    /// changes.
+    pub fn apply(&self, next: DynamicConfig) -> Result<Vec<String>, ConfigError> {
+        next.validate()?;
+        let current = self.get();
+        let changed = current.diff(&next);
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+        if current.rust_log != next.rust_log {
+            if let Some(reload_log) = &self.log_reloader {
+                reload_log(&next.rust_log).map_err(|e| ConfigError {
+                    errors: vec![format!("Cannot apply RUST_LOG: {:#}", e)],
+                })?;
+            }
+        }
+        self.current.store(Arc::new(next));
+        tracing::info!(changed = ?changed, "Reloaded dynamic configuration");
+        Ok(changed)
+    }
+
+    /// The whole configuration as it stands on disk and in the environment now, for
+    /// settings applied on demand such as signing keys.
+    pub fn load(&self) -> Result<LoadedConfig, ConfigError> {
+        Config::load(self.config_path.as_deref())
+    }
+
+    /// Re-reads the configuration file and environment. The whole configuration must
+    /// validate; settings outside [`DynamicConfig`] still need a restart to change.
+    ///
+    /// A set environment variable wins over the file, but the environment of a running
+    /// process cannot change, so a reloadable key edited in the file since startup
+    /// takes the file's value even where its variable is set.
+    pub fn reload(&self) -> Result<Vec<String>, ConfigError> {
+        let loaded = self.load()?;
+        for warning in &loaded.warnings {
+            tracing::warn!("{}", warning);
+        }
+        let edited: toml::Table = reloadable_entries(&loaded.file)
+            .into_iter()
+            .filter(|(key, value)| self.startup_file.get(key) != Some(value))
+            .collect();
+        if edited.is_empty() {
+            return self.apply(DynamicConfig::from_config(&loaded.config));
+        }
+        let source = loaded
+            .path
+            .as_ref()
+            .map_or_else(String::new, |p| p.display().to_string());
+        let Ok(serde_json::Value::Object(mut merged)) = serde_json::to_value(&loaded.config) else {
+            return Err(ConfigError {
+                errors: vec!["Configuration is not representable as JSON".to_string()],
+            });
+        };
+        for (key, value) in edited {
+            let value = serde_json::to_value(value).map_err(|e| ConfigError {
+                errors: vec![format!("{}: {}: {}", source, key, e)],
+            })?;
+            merged.insert(key, value);
+        }
+        let config: Config =
+            serde_json::from_value(serde_json::Value::Object(merged)).map_err(|e| ConfigError {
+                errors: vec![format!("{}: {}", source, e)],
+            })?;
+        self.apply(DynamicConfig::from_config(&config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::*;
+    use crate::config::ENV_SAFETY_MAX_DRIFT;
+    #[test]
+    fn test_apply_reports_diff_and_rejects_invalid_values() {
+        let handle = DynamicConfigHandle::default();
+        let changed = handle
+            .apply(DynamicConfig {
+                safety_max_drift: 5,
+                ..DynamicConfig::default()
+            })
+            .unwrap();
+        assert_eq!(changed, vec!["safety_max_drift: 2 -> 5"]);
+
+        let err = handle
+            .apply(DynamicConfig {
+                safety_max_drift: 9,
+                safety_heartbeat_secs: 0,
+                rebalance_ltv_threshold_bps: Ratio::from_bps(20_000),
+                ..DynamicConfig::default()
+            })
+            .unwrap_err();
+        assert_eq!(err.errors.len(), 2);
+        assert_eq!(handle.get().safety_max_drift, 5);
+        assert_eq!(handle.get().safety_heartbeat_secs, 10);
+    }
+
+    #[test]
+    fn test_failed_log_reload_keeps_old_values() {
+        let handle = DynamicConfigHandle::default()
+            .with_log_reloader(|_| Err(anyhow::anyhow!("subscriber gone")));
+        let next = DynamicConfig {
+            rust_log: "debug".to_string(),
+            safety_max_drift: 7,
+            ..DynamicConfig::default()
+        };
+        assert!(handle.apply(next).is_err());
+        assert_eq!(*handle.get(), DynamicConfig::default());
+    }
+
+    #[test]
+    fn test_reload_applies_file_edits_over_the_environment() {
+        let path = std::env::temp_dir().join(format!("nexus-reload-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "safety_max_drift = 3\n").unwrap();
+        std::env::set_var(ENV_SAFETY_MAX_DRIFT, "4");
+        let loaded = Config::load(Some(&path)).unwrap();
+        let handle = DynamicConfigHandle::new(DynamicConfig::from_config(&loaded.config))
+            .with_config_path(Some(path.clone()))
+            .with_startup_file(&loaded.file);
+
+        // Unedited, the environment still wins.
+        let unchanged = handle.reload();
+        std::fs::write(&path, "safety_max_drift = 6\n").unwrap();
+        let edited = handle.reload();
+        std::env::remove_var(ENV_SAFETY_MAX_DRIFT);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(unchanged.unwrap(), Vec::<String>::new());
+        assert_eq!(edited.unwrap(), vec!["safety_max_drift: 4 -> 6"]);
+        assert_eq!(handle.get().safety_max_drift, 6);
+    }
+}
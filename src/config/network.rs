@@ -0,0 +1,193 @@
+//! Stacks network profiles. `NETWORK` picks per-network defaults for the node URLs,
+//! address versions and contract targets; explicitly set variables still win.
+
+use crate::stacks::address::{MAINNET_SINGLESIG, TESTNET_SINGLESIG};
+use crate::stacks::transaction::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Clarinet's default devnet deployer, which owns the locally deployed contracts.
+const DEVNET_DEPLOYER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    /// A local Clarinet devnet; shares testnet's chain id and address versions.
+    Devnet,
+}
+
+impl Network {
+    pub fn is_mainnet(self) -> bool {
+        self == Network::Mainnet
+    }
+
+    pub fn default_rpc_url(self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://api.mainnet.hiro.so/",
+            Network::Testnet => "https://api.testnet.hiro.so/",
+            Network::Devnet => "http://localhost:3999/",
+        }
+    }
+
+    pub fn default_ws_url(self) -> &'static str {
+        match self {
+            Network::Mainnet => "wss://api.mainnet.hiro.so/",
+            Network::Testnet => "wss://api.testnet.hiro.so/",
+            Network::Devnet => "ws://localhost:3999/",
+        }
+    }
+
+    /// Version byte of single-sig addresses derived for this network (`SP…` or `ST…`).
+    pub fn address_version(self) -> u8 {
+        if self.is_mainnet() {
+            MAINNET_SINGLESIG
+        } else {
+            TESTNET_SINGLESIG
+        }
+    }
+
+    /// `network_id` a node on this network reports from `/v2/info`.
+    pub fn chain_id(self) -> u32 {
+        if self.is_mainnet() {
+            CHAIN_ID_MAINNET
+        } else {
+            CHAIN_ID_TESTNET
+        }
+    }
+
+    /// Oracle contract pushed to when `ORACLE_CONTRACT_PRINCIPAL` is unset. Only devnet
+    /// has a fixed deployer; public networks must be configured explicitly.
+    pub fn default_oracle_contract(self) -> Option<String> {
+        match self {
+            Network::Devnet => Some(format!("{}.fx-oracle", DEVNET_DEPLOYER)),
+            Network::Mainnet | Network::Testnet => None,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+        })
+    }
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            other => anyhow::bail!(
+                "Unsupported network '{}' (expected mainnet, testnet or devnet)",
+                other
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeInfo {
+    network_id: u32,
+}
+
+/// `network_id` reported by the node's `/v2/info`.
+pub async fn fetch_network_id(http_client: &reqwest::Client, rpc_url: &str) -> anyhow::Result<u32> {
+    let url = format!("{}/v2/info", rpc_url.trim_end_matches('/'));
+    let resp = http_client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Stacks RPC returned {}", resp.status());
+    }
+    Ok(resp.json::<NodeInfo>().await?.network_id)
+}
+
+/// Refuses to start when the node at `rpc_url` serves a different chain than `network`,
+/// unless `allow_mismatch` is set. An unreachable node is only logged: the safety
+/// monitor handles RPC outages once running.
+pub async fn check_rpc_network(
+    http_client: &reqwest::Client,
+    rpc_url: &str,
+    network: Network,
+    allow_mismatch: bool,
+) -> anyhow::Result<()> {
+    let reported = match fetch_network_id(http_client, rpc_url).await {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::warn!(%network, "Could not read the Stacks node's network id: {:#}", e);
+            return Ok(());
+        }
+    };
+    if reported == network.chain_id() {
+        tracing::info!(%network, network_id = reported, "Stacks node network matches");
+        return Ok(());
+    }
+    let message = format!(
+        "Stacks node at {} reports network id {:#010x}, but NETWORK={} expects {:#010x}",
+        rpc_url,
+        reported,
+        network,
+        network.chain_id()
+    );
+    if allow_mismatch {
+        tracing::warn!(
+            "{}; continuing because ALLOW_NETWORK_MISMATCH is set",
+            message
+        );
+        Ok(())
+    } else {
+        anyhow::bail!("{}. Set ALLOW_NETWORK_MISMATCH=true to run anyway", message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Json, Router};
+
+    async fn node_reporting(network_id: u32) -> String {
+        let app = Router::new().route(
+            "/v2/info",
+            get(move || async move { Json(serde_json::json!({ "network_id": network_id })) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_rpc_network_mismatch_refuses_unless_allowed() {
+        let client = reqwest::Client::new();
+        let testnet_node = node_reporting(CHAIN_ID_TESTNET).await;
+
+        let err = check_rpc_network(&client, &testnet_node, Network::Mainnet, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("NETWORK=mainnet"));
+        assert!(
+            check_rpc_network(&client, &testnet_node, Network::Mainnet, true)
+                .await
+                .is_ok()
+        );
+        assert!(
+            check_rpc_network(&client, &testnet_node, Network::Devnet, false)
+                .await
+                .is_ok()
+        );
+        // Unreachable nodes are left to the safety monitor.
+        assert!(
+            check_rpc_network(&client, "http://127.0.0.1:1/", Network::Mainnet, false)
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! [NEXUS-DIAG-01] End-to-end dependency self-test.
+//!
+//! Composes the connectivity checks an operator needs before taking traffic:
+//! Postgres, Redis, the Stacks RPC, wallet key material and migration status.
+
+use crate::config::Config;
+use crate::latency::timed_query;
+use crate::storage::Storage;
+use lib_conxian_core::Wallet;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Upper bound for any single dependency check.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long [`cached_diagnostics`] reuses a report before checking the dependencies again.
+pub const REPORT_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Detail [`DiagnosticsReport::redacted`] puts in place of a failed check's error.
+pub const REDACTED_DETAIL: &str = "check failed; see the node log";
+
+static CACHED_REPORT: tokio::sync::Mutex<Option<(Instant, DiagnosticsReport)>> =
+    tokio::sync::Mutex::const_new(None);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: Option<String>,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub healthy: bool,
+    pub version: String,
+    pub checks: Vec<CheckResult>,
+}
+
+impl DiagnosticsReport {
+    pub fn from_checks(checks: Vec<CheckResult>) -> Self {
+        Self {
+            healthy: checks.iter().all(|c| c.status != CheckStatus::Failed),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            checks,
+        }
+    }
+
+    /// Replaces the detail of failed checks, which can carry connection strings and
+    /// upstream errors, with [`REDACTED_DETAIL`].
+    pub fn redacted(mut self) -> Self {
+        for check in &mut self.checks {
+            if check.status == CheckStatus::Failed {
+                check.detail = Some(REDACTED_DETAIL.to_string());
+            }
+        }
+        self
+    }
+}
+
+async fn run_check<F>(name: &str, check: F) -> CheckResult
+where
+    F: Future<Output = anyhow::Result<Option<String>>>,
+{
+    let started = Instant::now();
+    let (status, detail) = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(detail)) => (CheckStatus::Ok, detail),
+        Ok(Err(e)) => (CheckStatus::Failed, Some(e.to_string())),
+        Err(_) => (
+            CheckStatus::Failed,
+            Some(format!("timed out after {}s", CHECK_TIMEOUT.as_secs())),
+        ),
+    };
+    CheckResult {
+        name: name.to_string(),
+        status,
+        detail,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn skipped(name: &str, reason: &str) -> CheckResult {
+    CheckResult {
+        name: name.to_string(),
+        status: CheckStatus::Skipped,
+        detail: Some(reason.to_string()),
+        latency_ms: 0,
+    }
+}
+
+/// Checks every external dependency and returns a structured report.
+pub async fn diagnostics(
+    config: &Config,
+    storage: &Storage,
+    http_client: &reqwest::Client,
+) -> DiagnosticsReport {
+    let mut checks = Vec::new();
+
+    checks.push(
+        run_check("database", async {
+            timed_query(
+                "diagnostics_ping",
+                sqlx::query("SELECT 1").execute(&storage.pg_pool),
+            )
+            .await?;
+            Ok(None)
+        })
+        .await,
+    );
+
+    checks.push(
+        run_check("redis", async {
+            let pong: String = storage
+                .with_redis(
+                    |mut conn| async move { redis::cmd("PING").query_async(&mut conn).await },
+                )
+                .await?;
+            Ok(Some(pong))
+        })
+        .await,
+    );
+
+    checks.push(
+        run_check("stacks_rpc", async {
+            // Healthy while any configured endpoint answers; the detail names which.
+            let rpc = crate::stacks::rpc::shared()
+                .unwrap_or_else(|| Arc::new(config.stacks_rpc_endpoints()));
+            rpc.call(|base| async move {
+                let resp = http_client.get(format!("{}/v2/info", base)).send().await?;
+                if !resp.status().is_success() {
+                    anyhow::bail!("Stacks RPC returned {}", resp.status());
+                }
+                Ok(base)
+            })
+            .await
+            .map(Some)
+        })
+        .await,
+    );
+
+    checks.push(match &config.kwil_private_key_hex {
+        Some(key_hex) => {
+            run_check("wallet", async {
+                Wallet::from_private_key_hex(key_hex)
+                    .map_err(|e| anyhow::anyhow!("Invalid KWIL_PRIVATE_KEY_HEX: {}", e))?;
+                Ok(None)
+            })
+            .await
+        }
+        None => skipped("wallet", "KWIL_PRIVATE_KEY_HEX not configured"),
+    });
+
+    checks.push(
+        run_check("migrations", async {
+            let pending = storage.pending_migrations().await?;
+            if !pending.is_empty() {
+                anyhow::bail!("{} pending migration(s): {:?}", pending.len(), pending);
+            }
+            Ok(None)
+        })
+        .await,
+    );
+
+    DiagnosticsReport::from_checks(checks)
+}
+
+/// [`diagnostics`] run at most once per [`REPORT_CACHE_TTL`]: concurrent callers wait for
+/// the run in progress and share its report. Failed checks are logged with their detail.
+pub async fn cached_diagnostics(
+    config: &Config,
+    storage: &Storage,
+    http_client: &reqwest::Client,
+) -> DiagnosticsReport {
+    let mut cached = CACHED_REPORT.lock().await;
+    if let Some((checked_at, report)) = cached.as_ref() {
+        if checked_at.elapsed() < REPORT_CACHE_TTL {
+            return report.clone();
+        }
+    }
+    let report = diagnostics(config, storage, http_client).await;
+    for check in report
+        .checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Failed)
+    {
+        tracing::warn!(
+            check = %check.name,
+            detail = check.detail.as_deref().unwrap_or_default(),
+            "Diagnostics check failed"
+        );
+    }
+    *cached = Some((Instant::now(), report.clone()));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_healthy_ignores_skipped_checks() {
+        let report = DiagnosticsReport::from_checks(vec![
+            CheckResult {
+                name: "database".to_string(),
+                status: CheckStatus::Ok,
+                detail: None,
+                latency_ms: 1,
+            },
+            skipped("wallet", "not configured"),
+        ]);
+        assert!(report.healthy);
+    }
+
+    #[test]
+    fn test_redacted_report_hides_failure_details() {
+        let report = DiagnosticsReport::from_checks(vec![
+            CheckResult {
+                name: "database".to_string(),
+                status: CheckStatus::Failed,
+                detail: Some("error connecting to postgres://nexus:secret@db/nexus".to_string()),
+                latency_ms: 1,
+            },
+            CheckResult {
+                name: "redis".to_string(),
+                status: CheckStatus::Ok,
+                detail: Some("PONG".to_string()),
+                latency_ms: 1,
+            },
+            skipped("wallet", "not configured"),
+        ])
+        .redacted();
+
+        assert!(!report.healthy);
+        assert_eq!(report.checks[0].detail.as_deref(), Some(REDACTED_DETAIL));
+        assert_eq!(report.checks[1].detail.as_deref(), Some("PONG"));
+        assert_eq!(report.checks[2].detail.as_deref(), Some("not configured"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_dependencies_fail_report() {
+        let mut config = Config::default_test();
+        config.stacks_node_rpc_url = "http://127.0.0.1:1".to_string();
+        let storage = Storage::new_lazy("postgres://localhost:1/nexus", "redis://127.0.0.1:1/")
+            .expect("lazy storage should be constructible");
+
+        let report = diagnostics(&config, &storage, &reqwest::Client::new()).await;
+
+        assert!(!report.healthy);
+        let status_of = |name: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.status)
+        };
+        assert_eq!(status_of("redis"), Some(CheckStatus::Failed));
+        assert_eq!(status_of("stacks_rpc"), Some(CheckStatus::Failed));
+        assert_eq!(status_of("wallet"), Some(CheckStatus::Skipped));
+    }
+}
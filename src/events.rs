@@ -0,0 +1,193 @@
+//! Events published on the `events` Redis channel (see [`keys::EVENTS_CHANNEL`]). Each
+//! message is a JSON object tagged by `type` and stamped with [`EVENT_SCHEMA_VERSION`],
+//! e.g. `{"schema_version":1,"type":"root_updated","root":"0x..","height":7,"leaf_count":3}`.
+//! Consumers should use [`NexusEvent::parse`], which maps event types it does not know
+//! to [`NexusEvent::Unknown`] so a newer publisher does not break an older subscriber,
+//! and rejects a schema version other than its own rather than misread its fields.
+//!
+//! [`keys::EVENTS_CHANNEL`]: crate::storage::keys::EVENTS_CHANNEL
+
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+
+/// Bumped when an existing event's fields change incompatibly; new event types and new
+/// optional fields do not bump it.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NexusEvent {
+    /// Safety Mode was entered; `cause` is a [`crate::safety::SafetyTrigger`] string.
+    SafetyTriggered {
+        cause: String,
+        drift: Option<u64>,
+        /// L1 burn height the trigger was observed at, when known.
+        height: Option<u64>,
+    },
+    /// Safety Mode was left; `drill` when it was a drill being ended.
+    SafetyCleared {
+        #[serde(default)]
+        drill: bool,
+    },
+    /// The Stacks RPC failed `failures` times in a row; Safety Mode is held.
+    L1Unreachable {
+        failures: u32,
+    },
+    L1Reachable,
+    /// The latest oracle aggregate is `age_secs` old; Safety Mode is held.
+    OracleStale {
+        age_secs: u64,
+    },
+    OracleFresh,
+    /// The locally processed height is above the L1 tip; Safety Mode is held.
+    HeightAnomaly {
+        processed_height: u64,
+        l1_height: u64,
+    },
+    RootUpdated {
+        root: String,
+        height: u64,
+        leaf_count: u64,
+    },
+    BlockProcessed {
+        hash: String,
+        height: u64,
+        tx_count: u64,
+    },
+    ExecutionSequenced {
+        tx_id: String,
+        sender: String,
+        nonce: u64,
+    },
+    /// An event type this build does not know.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    event: &'a NexusEvent,
+}
+
+impl NexusEvent {
+    /// The `type` tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SafetyTriggered { .. } => "safety_triggered",
+            Self::SafetyCleared { .. } => "safety_cleared",
+            Self::L1Unreachable { .. } => "l1_unreachable",
+            Self::L1Reachable => "l1_reachable",
+            Self::OracleStale { .. } => "oracle_stale",
+            Self::OracleFresh => "oracle_fresh",
+            Self::HeightAnomaly { .. } => "height_anomaly",
+            Self::RootUpdated { .. } => "root_updated",
+            Self::BlockProcessed { .. } => "block_processed",
+            Self::ExecutionSequenced { .. } => "execution_sequenced",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// The channel payload.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&Envelope {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event: self,
+        })
+        .unwrap_or_default()
+    }
+
+    /// Parses a channel payload. Unknown event types become [`NexusEvent::Unknown`];
+    /// malformed JSON, a missing `type`, a known type with missing fields and a
+    /// `schema_version` other than [`EVENT_SCHEMA_VERSION`] fail. A payload without a
+    /// `schema_version` is read as the current version.
+    pub fn parse(payload: &str) -> Result<Self, serde_json::Error> {
+        let value: serde_json::Value = serde_json::from_str(payload)?;
+        if let Some(version) = value.get("schema_version") {
+            let version = u32::deserialize(version)?;
+            if version != EVENT_SCHEMA_VERSION {
+                return Err(serde_json::Error::custom(format!(
+                    "unsupported event schema version {} (expected {})",
+                    version, EVENT_SCHEMA_VERSION
+                )));
+            }
+        }
+        serde_json::from_value(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        let events = [
+            NexusEvent::SafetyTriggered {
+                cause: "drift".to_string(),
+                drift: Some(3),
+                height: Some(103),
+            },
+            NexusEvent::SafetyCleared { drill: true },
+            NexusEvent::L1Unreachable { failures: 5 },
+            NexusEvent::L1Reachable,
+            NexusEvent::OracleStale { age_secs: 900 },
+            NexusEvent::OracleFresh,
+            NexusEvent::HeightAnomaly {
+                processed_height: 105,
+                l1_height: 100,
+            },
+            NexusEvent::RootUpdated {
+                root: "0xabc".to_string(),
+                height: 7,
+                leaf_count: 3,
+            },
+            NexusEvent::BlockProcessed {
+                hash: "0xb7".to_string(),
+                height: 7,
+                tx_count: 2,
+            },
+            NexusEvent::ExecutionSequenced {
+                tx_id: "tx-1".to_string(),
+                sender: "alice".to_string(),
+                nonce: 4,
+            },
+        ];
+        for event in events {
+            let json = event.to_json();
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(value["schema_version"], EVENT_SCHEMA_VERSION);
+            assert_eq!(value["type"], event.kind());
+            assert_eq!(NexusEvent::parse(&json).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn test_unknown_type_parses_as_unknown() {
+        let event =
+            NexusEvent::parse(r#"{"schema_version":1,"type":"vault_liquidated","vault_id":"v1"}"#)
+                .unwrap();
+        assert_eq!(event, NexusEvent::Unknown);
+        // Fields added to a known type are ignored.
+        assert_eq!(
+            NexusEvent::parse(r#"{"schema_version":1,"type":"oracle_fresh","source":"pyth"}"#)
+                .unwrap(),
+            NexusEvent::OracleFresh
+        );
+        assert!(NexusEvent::parse("safety_mode_triggered").is_err());
+        assert!(NexusEvent::parse(r#"{"type":"l1_unreachable"}"#).is_err());
+    }
+
+    #[test]
+    fn test_other_schema_versions_are_rejected() {
+        let err = NexusEvent::parse(r#"{"schema_version":2,"type":"l1_unreachable","failures":3}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("schema version 2"));
+        assert!(NexusEvent::parse(r#"{"schema_version":"1","type":"oracle_fresh"}"#).is_err());
+        assert_eq!(
+            NexusEvent::parse(r#"{"type":"l1_unreachable","failures":3}"#).unwrap(),
+            NexusEvent::L1Unreachable { failures: 3 }
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::latency::timed_query;
 use crate::storage::Storage;
 use ark_bls12_381::{Bls12_381, Fr};
 use ark_crypto_primitives::snark::SNARK;
@@ -105,7 +106,7 @@ impl BitVMAdapter {
         let vk_hash = hex::encode(Sha256::digest(&vk_bytes));
         let pi_hash = hex::encode(Sha256::digest(&public_inputs_concatenated));
 
-        let _ = sqlx::query(
+        let _ = timed_query("verify_transition", sqlx::query(
             "INSERT INTO bitvm_verified_transitions (trace_id, prev_state_root, next_state_root, proof_hash, vk_hash, public_inputs_hash, steps_verified, confidence)
              VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
              ON CONFLICT (trace_id) DO NOTHING"
@@ -118,8 +119,7 @@ impl BitVMAdapter {
         .bind(&pi_hash)
         .bind(steps as i64)
         .bind(confidence)
-        .execute(&self.storage.pg_pool)
-        .await;
+        .execute(&self.storage.pg_pool)).await;
 
         Ok(BitVMVerificationResult {
             valid: true,
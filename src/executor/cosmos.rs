@@ -1,3 +1,4 @@
+use crate::latency::timed_query;
 use crate::storage::Storage;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -50,15 +51,18 @@ impl CosmosAdapter {
         let latest_height = update.trusted_height + 1;
         let trust_level = "T1 (NIP-005 Phase 1)".to_string();
 
-        let _ = sqlx::query(
-            "INSERT INTO cosmos_verified_client_updates (client_id, latest_height, trust_level)
+        let _ = timed_query(
+            "verify_client_update",
+            sqlx::query(
+                "INSERT INTO cosmos_verified_client_updates (client_id, latest_height, trust_level)
              VALUES ($1, $2, $3)
              ON CONFLICT (client_id) DO UPDATE SET latest_height = EXCLUDED.latest_height",
+            )
+            .bind(&update.client_id)
+            .bind(latest_height as i64)
+            .bind(&trust_level)
+            .execute(&self.storage.pg_pool),
         )
-        .bind(&update.client_id)
-        .bind(latest_height as i64)
-        .bind(&trust_level)
-        .execute(&self.storage.pg_pool)
         .await;
 
         Ok(IBCVerificationResult {
@@ -0,0 +1,229 @@
+//! Keeps one periodic cycle (the rebalance cycle) running at a time. A flag stops
+//! overlapping runs in this process and a lock on the store ([`NexusStore::try_acquire_lock`],
+//! `SET NX PX` in Redis) stops them across instances. The store lock expires after its
+//! TTL, so a node that dies mid-cycle cannot hold it for good; while the cycle runs it is
+//! renewed every third of the TTL, so a slow cycle keeps it. [`CycleGuard`] frees both
+//! when it is dropped, including by a panic.
+
+use crate::storage::store::NexusStore;
+use prometheus::{opts, register_int_counter_vec, IntCounterVec};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+lazy_static::lazy_static! {
+    static ref CYCLES_SKIPPED: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "nexus_cycles_skipped_total",
+            "Cycles skipped because the previous one was still running"
+        ),
+        &["cycle"]
+    )
+    .unwrap();
+}
+
+/// A cycle that must not overlap itself.
+pub struct CycleLock {
+    name: &'static str,
+    ttl: Duration,
+    running: Arc<AtomicBool>,
+}
+
+impl CycleLock {
+    /// `ttl` bounds how long a cycle that stopped renewing the lock (a dead node) may
+    /// keep other instances out; make it longer than a renewal round trip.
+    pub fn new(name: &'static str, ttl: Duration) -> Self {
+        Self {
+            name,
+            ttl,
+            running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Starts a cycle, or returns `None` (counted and logged) while one is running here
+    /// or on another instance.
+    pub async fn try_acquire(
+        &self,
+        store: &Arc<dyn NexusStore>,
+    ) -> anyhow::Result<Option<CycleGuard>> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            self.skipped("this node");
+            return Ok(None);
+        }
+        // Clears the flag if the lock is not taken or this future is dropped.
+        let mut guard = CycleGuard {
+            name: self.name,
+            token: uuid::Uuid::new_v4().to_string(),
+            store: store.clone(),
+            running: self.running.clone(),
+            held: false,
+            lost: Arc::new(AtomicBool::new(false)),
+            renewal: None,
+        };
+        if !store
+            .try_acquire_lock(self.name, &guard.token, self.ttl)
+            .await?
+        {
+            self.skipped("another node");
+            return Ok(None);
+        }
+        guard.held = true;
+        guard.renewal = Some(tokio::spawn(renew_lock(
+            store.clone(),
+            self.name,
+            guard.token.clone(),
+            self.ttl,
+            guard.lost.clone(),
+        )));
+        Ok(Some(guard))
+    }
+
+    fn skipped(&self, holder: &str) {
+        CYCLES_SKIPPED.with_label_values(&[self.name]).inc();
+        tracing::warn!(
+            cycle = self.name,
+            holder,
+            "Previous cycle still running; skipping this one"
+        );
+    }
+}
+
+/// Renews the lock every third of `ttl` until it is found taken from `token`, then sets
+/// `lost`. A failed renewal is retried; the lock lapses if the store stays unreachable.
+async fn renew_lock(
+    store: Arc<dyn NexusStore>,
+    name: &'static str,
+    token: String,
+    ttl: Duration,
+    lost: Arc<AtomicBool>,
+) {
+    loop {
+        tokio::time::sleep(ttl / 3).await;
+        match store.renew_lock(name, &token, ttl).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::error!(
+                    cycle = name,
+                    "Cycle lock lost mid-cycle; stopping the cycle"
+                );
+                lost.store(true, Ordering::SeqCst);
+                return;
+            }
+            Err(e) => tracing::warn!(cycle = name, error = %e, "Failed to renew cycle lock"),
+        }
+    }
+}
+
+/// A running cycle; the cycle ends when this is released or dropped.
+pub struct CycleGuard {
+    name: &'static str,
+    token: String,
+    store: Arc<dyn NexusStore>,
+    running: Arc<AtomicBool>,
+    /// Whether the store lock is still ours to release.
+    held: bool,
+    /// Set once a renewal finds the store lock taken by someone else.
+    lost: Arc<AtomicBool>,
+    renewal: Option<JoinHandle<()>>,
+}
+
+impl CycleGuard {
+    /// Whether this cycle still holds the store lock; check before each step another
+    /// instance must not repeat.
+    pub fn is_held(&self) -> bool {
+        !self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Ends the cycle, waiting for the store lock to be released.
+    pub async fn release(mut self) {
+        self.held = false;
+        if let Some(renewal) = self.renewal.take() {
+            renewal.abort();
+        }
+        if let Err(e) = self.store.release_lock(self.name, &self.token).await {
+            tracing::warn!(cycle = self.name, error = %e, "Failed to release cycle lock; it expires with its TTL");
+        }
+    }
+}
+
+impl Drop for CycleGuard {
+    fn drop(&mut self) {
+        if let Some(renewal) = self.renewal.take() {
+            renewal.abort();
+        }
+        if self.held {
+            // Not released normally (an error or a panic): release in the background,
+            // or leave the lock to its TTL outside a runtime.
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                let (name, token, store) = (self.name, self.token.clone(), self.store.clone());
+                runtime.spawn(async move { store.release_lock(name, &token).await });
+            }
+        }
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::store::InMemoryStore;
+
+    #[tokio::test]
+    async fn test_second_acquire_is_refused_until_release() {
+        let store: Arc<dyn NexusStore> = Arc::new(InMemoryStore::new());
+        let lock = CycleLock::new("rebalance", Duration::from_secs(60));
+
+        let guard = lock.try_acquire(&store).await.unwrap().unwrap();
+        assert!(lock.try_acquire(&store).await.unwrap().is_none());
+        guard.release().await;
+        assert!(lock.try_acquire(&store).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_lock_held_elsewhere_is_respected_and_left_alone() {
+        let store: Arc<dyn NexusStore> = Arc::new(InMemoryStore::new());
+        assert!(store
+            .try_acquire_lock("rebalance", "other-node", Duration::from_secs(60))
+            .await
+            .unwrap());
+        let lock = CycleLock::new("rebalance", Duration::from_secs(60));
+        assert!(lock.try_acquire(&store).await.unwrap().is_none());
+
+        // The refused attempt cleared the local flag and did not free the other lock.
+        assert!(lock.try_acquire(&store).await.unwrap().is_none());
+        store.release_lock("rebalance", "other-node").await.unwrap();
+        assert!(lock.try_acquire(&store).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_lock_is_renewed_while_the_cycle_runs() {
+        let store: Arc<dyn NexusStore> = Arc::new(InMemoryStore::new());
+        let ttl = Duration::from_millis(300);
+        let lock = CycleLock::new("rebalance", ttl);
+        let guard = lock.try_acquire(&store).await.unwrap().unwrap();
+
+        // Well past the TTL, the slow cycle still keeps other instances out.
+        tokio::time::sleep(ttl * 3).await;
+        assert!(guard.is_held());
+        assert!(!store
+            .try_acquire_lock("rebalance", "other-node", ttl)
+            .await
+            .unwrap());
+
+        // Once the lock is taken from it, the next renewal notices.
+        store.release_lock("rebalance", &guard.token).await.unwrap();
+        assert!(store
+            .try_acquire_lock("rebalance", "other-node", Duration::from_secs(60))
+            .await
+            .unwrap());
+        tokio::time::sleep(ttl).await;
+        assert!(!guard.is_held());
+        guard.release().await;
+        // Releasing a lost lock leaves the new holder's alone.
+        assert!(!store
+            .try_acquire_lock("rebalance", "this-node", ttl)
+            .await
+            .unwrap());
+    }
+}
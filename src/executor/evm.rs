@@ -1,3 +1,4 @@
+use crate::latency::timed_query;
 use crate::storage::Storage;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -57,7 +58,7 @@ impl EVMAdapter {
         let status = "Receipt proof verified and audited (NIP-005 Phase 1: Structural)".to_string();
         let verified_at_height = 1000000;
 
-        let _ = sqlx::query(
+        let _ = timed_query("verify_receipt_proof", sqlx::query(
             "INSERT INTO evm_verified_receipts (block_hash, transaction_index, receipt_root, status, verified_at_height)
              VALUES ($1, $2, $3, $4, $5)
              ON CONFLICT (block_hash, transaction_index) DO NOTHING"
@@ -67,8 +68,7 @@ impl EVMAdapter {
         .bind(&proof.receipt_root)
         .bind(&status)
         .bind(verified_at_height as i64)
-        .execute(&self.storage.pg_pool)
-        .await;
+        .execute(&self.storage.pg_pool)).await;
 
         Ok(EVMVerificationResult {
             valid: true,
@@ -0,0 +1,281 @@
+//! Fixed-point amounts and ratios for vault accounting. Token amounts are integers in the
+//! asset's base unit and travel through JSON as decimal strings, since values above 2^53
+//! lose precision as JSON numbers. Ratios are whole basis points, so comparing an LTV with
+//! a threshold is exact; floats are only accepted at the edges and rounded once.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A token amount in its asset's base unit (sats, micro-STX, micro-USD).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(u128);
+
+impl Amount {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn new(units: u128) -> Self {
+        Self(units)
+    }
+
+    /// The amount in base units.
+    pub const fn units(self) -> u128 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    pub fn checked_mul(self, factor: u128) -> Option<Self> {
+        self.0.checked_mul(factor).map(Self)
+    }
+
+    /// `self / divisor`, rounded down; `None` for a zero divisor.
+    pub fn checked_div(self, divisor: u128) -> Option<Self> {
+        self.0.checked_div(divisor).map(Self)
+    }
+
+    /// `self * ratio`, rounded down.
+    pub fn checked_mul_ratio(self, ratio: Ratio) -> Option<Self> {
+        let scaled = self.0.checked_mul(ratio.bps() as u128)?;
+        Some(Self(scaled / BPS_SCALE as u128))
+    }
+
+    /// The same value counted in units of `10^-to` rather than `10^-from`, rounded down
+    /// when decimals are dropped.
+    pub fn rescale(self, from: u32, to: u32) -> Option<Self> {
+        if to >= from {
+            self.checked_mul(10u128.checked_pow(to - from)?)
+        } else {
+            self.checked_div(10u128.checked_pow(from - to)?)
+        }
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(units: u64) -> Self {
+        Self(units as u128)
+    }
+}
+
+impl From<u128> for Amount {
+    fn from(units: u128) -> Self {
+        Self(units)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Self)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    /// Accepts a decimal string, or a non-negative integer as older writers send.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AmountVisitor;
+
+        impl Visitor<'_> for AmountVisitor {
+            type Value = Amount;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a non-negative integer amount, as a string or number")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Amount, E> {
+                v.parse()
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Amount, E> {
+                Ok(Amount::from(v))
+            }
+
+            fn visit_u128<E: de::Error>(self, v: u128) -> Result<Amount, E> {
+                Ok(Amount(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Amount, E> {
+                u64::try_from(v)
+                    .map(Amount::from)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+/// Basis points in a whole.
+pub const BPS_SCALE: u64 = 10_000;
+
+/// A non-negative ratio in whole basis points; [`Ratio::ONE`] is 100%. Serialized as the
+/// fraction (`0.85`), which round-trips exactly through [`Ratio::from_f64`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ratio(u64);
+
+impl Ratio {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(BPS_SCALE);
+
+    pub const fn from_bps(bps: u64) -> Self {
+        Self(bps)
+    }
+
+    pub const fn bps(self) -> u64 {
+        self.0
+    }
+
+    /// `numerator / denominator`, rounded down to a basis point. `None` for a zero
+    /// denominator or a ratio beyond `u64::MAX` basis points.
+    pub fn checked_from_fraction(numerator: u128, denominator: u128) -> Option<Self> {
+        let bps = numerator
+            .checked_mul(BPS_SCALE as u128)?
+            .checked_div(denominator)?;
+        u64::try_from(bps).ok().map(Self)
+    }
+
+    /// The ratio nearest `fraction`, halves rounded away from zero; `None` for a
+    /// negative or non-finite value.
+    pub fn from_f64(fraction: f64) -> Option<Self> {
+        let bps = (fraction * BPS_SCALE as f64).round();
+        // 2^64: the first value `u64` cannot hold.
+        if !bps.is_finite() || bps < 0.0 || bps >= 18_446_744_073_709_551_616.0 {
+            return None;
+        }
+        Some(Self(bps as u64))
+    }
+
+    /// The ratio as a fraction, for display and storage only.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / BPS_SCALE as f64
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}bps", self.0)
+    }
+}
+
+impl Serialize for Ratio {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Ratio {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fraction = f64::deserialize(deserializer)?;
+        Self::from_f64(fraction).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Float(fraction), &"a non-negative ratio")
+        })
+    }
+}
+
+/// Serde helpers for a [`Ratio`] written as whole basis points (`8000`), as the
+/// `*_bps` settings and API fields are.
+pub mod bps {
+    use super::Ratio;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ratio: &Ratio, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(ratio.bps())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ratio, D::Error> {
+        u64::deserialize(deserializer).map(Ratio::from_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amounts_above_2_pow_53_round_trip_as_strings() {
+        for units in [
+            0,
+            (1u128 << 53) + 1,
+            u64::MAX as u128,
+            u64::MAX as u128 + 1,
+            u128::MAX,
+        ] {
+            let json = serde_json::to_string(&Amount::new(units)).unwrap();
+            assert_eq!(json, format!("\"{}\"", units));
+            assert_eq!(
+                serde_json::from_str::<Amount>(&json).unwrap().units(),
+                units
+            );
+        }
+        // Older writers send numbers.
+        assert_eq!(
+            serde_json::from_str::<Amount>("9007199254740993").unwrap(),
+            Amount::new(9_007_199_254_740_993)
+        );
+        assert!(serde_json::from_str::<Amount>("-1").is_err());
+        assert!(serde_json::from_str::<Amount>("1.5").is_err());
+        assert!(serde_json::from_str::<Amount>("\"12a\"").is_err());
+    }
+
+    #[test]
+    fn test_every_bps_round_trips_through_its_fraction() {
+        for bps in 0..=2 * BPS_SCALE {
+            let ratio = Ratio::from_bps(bps);
+            assert_eq!(Ratio::from_f64(ratio.to_f64()), Some(ratio));
+            let json = serde_json::to_string(&ratio).unwrap();
+            assert_eq!(serde_json::from_str::<Ratio>(&json).unwrap(), ratio);
+        }
+        // Float drift in the provider value does not move it off the basis point.
+        assert_eq!(Ratio::from_f64(0.1 + 0.7), Some(Ratio::from_bps(8_000)));
+        assert_eq!(Ratio::from_f64(0.85), Some(Ratio::from_bps(8_500)));
+        assert_eq!(Ratio::from_f64(0.00005), Some(Ratio::from_bps(1)));
+        assert_eq!(Ratio::from_f64(-0.1), None);
+        assert_eq!(Ratio::from_f64(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_fraction_rounds_down_and_checks() {
+        assert_eq!(
+            Ratio::checked_from_fraction(1, 3),
+            Some(Ratio::from_bps(3_333))
+        );
+        assert_eq!(
+            Ratio::checked_from_fraction(2, 3),
+            Some(Ratio::from_bps(6_666))
+        );
+        assert_eq!(Ratio::checked_from_fraction(1, 0), None);
+        assert_eq!(Ratio::checked_from_fraction(u128::MAX, 1), None);
+        assert_eq!(
+            Amount::new(999).checked_mul_ratio(Ratio::from_bps(5_000)),
+            Some(Amount::new(499))
+        );
+        assert_eq!(Amount::new(u128::MAX).checked_mul_ratio(Ratio::ONE), None);
+        assert_eq!(Amount::new(1_234_567).rescale(6, 2), Some(Amount::new(123)));
+        assert_eq!(Amount::new(123).rescale(2, 6), Some(Amount::new(1_230_000)));
+    }
+}
@@ -0,0 +1,337 @@
+//! Idempotency keys for transaction submission. A client that retries after a timeout
+//! sends the same `Idempotency-Key` (REST) or `idempotency_key` (gRPC); the first
+//! request's sequencing outcome is kept for [`IDEMPOTENCY_TTL`] under the caller's API
+//! key and the idempotency key, and a retry with the same body gets that outcome back
+//! without being validated or queued again, so it cannot trip the stale-nonce or
+//! front-running checks against its own first attempt. The first request reserves the
+//! key before it is sequenced, so a concurrent retry is refused as in progress rather
+//! than sequenced a second time. Queue refusals release the key instead of recording an
+//! outcome: they are transient and the retry should be sequenced.
+
+use super::{ExecutionRejected, NexusExecutor};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// How long an outcome is replayed.
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long a reservation lasts without an outcome, so a key held by a node that died
+/// mid-submission frees up. Longer than any wait in the sequencing queue.
+pub const IDEMPOTENCY_PENDING_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// REST request header carrying the key.
+pub const IDEMPOTENCY_HEADER: &str = "idempotency-key";
+
+/// Longer keys are refused rather than stored.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// The recorded outcome of the first request under a key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotentOutcome {
+    /// SHA-256 (hex) of the request body, telling a retry from a different request that
+    /// reuses the key.
+    pub request_hash: String,
+    pub tx_id: String,
+    pub accepted: bool,
+    pub message: String,
+}
+
+/// What the store holds under a key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum IdempotencyEntry {
+    /// Reserved by a first request that is still being sequenced.
+    Pending { request_hash: String },
+    /// The first request's outcome.
+    Done(IdempotentOutcome),
+}
+
+/// What a submission carrying an idempotency key should do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyCheck {
+    /// First use of the key, now reserved: sequence the request and
+    /// [finish](NexusExecutor::finish_idempotency) the key with its result.
+    Fresh,
+    /// A retry: answer with the recorded outcome.
+    Replay(IdempotentOutcome),
+    /// A retry while the first request is still being sequenced.
+    InProgress,
+    /// The key was used for a different request.
+    Conflict,
+}
+
+/// Store key for `idempotency_key` used by `caller`, an API key fingerprint. Keys are
+/// only honoured for authenticated callers: a shared anonymous scope would let one
+/// client replay or block another's submissions.
+pub fn scoped_key(caller: &str, idempotency_key: &str) -> String {
+    format!("{}:{}", caller, idempotency_key)
+}
+
+pub fn request_hash(body: &[u8]) -> String {
+    hex::encode(Sha256::digest(body))
+}
+
+/// Checks a client-supplied key: non-empty, printable ASCII, at most
+/// [`MAX_IDEMPOTENCY_KEY_LEN`] bytes.
+pub fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(format!(
+            "Idempotency key must be 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        ));
+    }
+    if !key.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err("Idempotency key must be printable ASCII without spaces".to_string());
+    }
+    Ok(())
+}
+
+impl IdempotentOutcome {
+    /// The outcome of [`NexusExecutor::submit`], or `None` for a queue refusal or any
+    /// other error a retry might not repeat.
+    pub fn from_submit(
+        request_hash: String,
+        tx_id: &str,
+        result: &anyhow::Result<String>,
+    ) -> Option<Self> {
+        let (accepted, message) = match result {
+            Ok(_) => (true, "Accepted".to_string()),
+            Err(e) => (
+                false,
+                e.downcast_ref::<ExecutionRejected>()?.message.to_string(),
+            ),
+        };
+        Some(Self {
+            request_hash,
+            tx_id: tx_id.to_string(),
+            accepted,
+            message,
+        })
+    }
+}
+
+impl NexusExecutor {
+    /// Reserves `scoped_key` for this request, or says why it cannot be sequenced. A
+    /// store outage fails the request rather than risking a second sequencing of a retry.
+    pub async fn reserve_idempotency(
+        &self,
+        scoped_key: &str,
+        request_hash: &str,
+    ) -> anyhow::Result<IdempotencyCheck> {
+        // A second pass covers an entry that expired between the two calls.
+        for _ in 0..2 {
+            if self
+                .store
+                .reserve_idempotency_key(scoped_key, request_hash, IDEMPOTENCY_PENDING_TTL)
+                .await?
+            {
+                return Ok(IdempotencyCheck::Fresh);
+            }
+            match self.store.idempotency_entry(scoped_key).await? {
+                None => continue,
+                Some(IdempotencyEntry::Pending { request_hash: held }) if held == request_hash => {
+                    return Ok(IdempotencyCheck::InProgress)
+                }
+                Some(IdempotencyEntry::Done(outcome)) if outcome.request_hash == request_hash => {
+                    tracing::debug!(tx_id = %outcome.tx_id, "Replaying idempotent submission");
+                    return Ok(IdempotencyCheck::Replay(outcome));
+                }
+                Some(_) => return Ok(IdempotencyCheck::Conflict),
+            }
+        }
+        Ok(IdempotencyCheck::InProgress)
+    }
+
+    /// Settles a key [reserved](Self::reserve_idempotency) for a submission: records the
+    /// outcome for [`IDEMPOTENCY_TTL`], or releases the key when [`NexusExecutor::submit`]
+    /// failed in a way a retry might not repeat. Best-effort: the request has already
+    /// been sequenced, and an unsettled reservation expires after
+    /// [`IDEMPOTENCY_PENDING_TTL`].
+    pub async fn finish_idempotency(
+        &self,
+        scoped_key: &str,
+        request_hash: String,
+        tx_id: &str,
+        result: &anyhow::Result<String>,
+    ) {
+        let settled = match IdempotentOutcome::from_submit(request_hash.clone(), tx_id, result) {
+            Some(outcome) => {
+                self.store
+                    .record_idempotent_outcome(scoped_key, &outcome, IDEMPOTENCY_TTL)
+                    .await
+            }
+            None => {
+                self.store
+                    .release_idempotency_key(scoped_key, &request_hash)
+                    .await
+            }
+        };
+        if let Err(e) = settled {
+            tracing::warn!(tx_id = %tx_id, error = %e, "Failed to settle idempotency key");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecutionRequest, RejectReason};
+    use crate::storage::store::{InMemoryStore, NexusStore};
+    use crate::storage::Storage;
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    fn executor(store: Arc<InMemoryStore>) -> NexusExecutor {
+        NexusExecutor::new(
+            Storage::for_tests(),
+            crate::executor::rgb::RGBRolloutMode::Disabled,
+            std::collections::HashSet::new(),
+        )
+        .with_store(store)
+    }
+
+    fn request(nonce: u64) -> ExecutionRequest {
+        ExecutionRequest {
+            tx_id: format!("tx-{}", nonce),
+            payload: "data".to_string(),
+            timestamp: Utc::now(),
+            sender: "alice".to_string(),
+            nonce,
+            priority: 0,
+            signature: None,
+        }
+    }
+
+    /// What the REST handler does with a keyed submission.
+    async fn submit_once(
+        executor: &NexusExecutor,
+        key: &str,
+        request: ExecutionRequest,
+    ) -> Result<IdempotentOutcome, &'static str> {
+        let hash = request_hash(&serde_json::to_vec(&request).unwrap());
+        match executor.reserve_idempotency(key, &hash).await.unwrap() {
+            IdempotencyCheck::Replay(outcome) => Ok(outcome),
+            IdempotencyCheck::InProgress => Err("in progress"),
+            IdempotencyCheck::Conflict => Err("conflict"),
+            IdempotencyCheck::Fresh => {
+                let tx_id = request.tx_id.clone();
+                let result = executor.submit_as(Some("caller"), request).await;
+                let outcome = IdempotentOutcome::from_submit(hash.clone(), &tx_id, &result);
+                executor
+                    .finish_idempotency(key, hash, &tx_id, &result)
+                    .await;
+                outcome.ok_or("not recorded")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_replays_the_first_outcome() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = executor(store.clone());
+        let key = scoped_key("caller", "retry-1");
+        let first = request(5);
+
+        let outcome = submit_once(&executor, &key, first.clone()).await.unwrap();
+        assert!(outcome.accepted);
+        // Sequenced again, the same nonce would be rejected as stale.
+        let replayed = submit_once(&executor, &key, first).await.unwrap();
+        assert_eq!(replayed, outcome);
+        let stats = executor.stats().await.unwrap();
+        assert_eq!((stats.accepted, stats.rejected), (1, 0));
+
+        // The key is scoped to the caller.
+        let other = scoped_key("other", "retry-1");
+        assert_eq!(
+            executor.reserve_idempotency(&other, "x").await.unwrap(),
+            IdempotencyCheck::Fresh
+        );
+    }
+
+    #[tokio::test]
+    async fn test_different_body_under_same_key_conflicts() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = executor(store);
+        let key = scoped_key("caller", "retry-2");
+        submit_once(&executor, &key, request(5)).await.unwrap();
+        assert_eq!(
+            submit_once(&executor, &key, request(6)).await,
+            Err("conflict")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_during_first_attempt_is_in_progress() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = executor(store.clone());
+        let key = scoped_key("caller", "retry-3");
+        let hash = request_hash(b"body");
+
+        assert_eq!(
+            executor.reserve_idempotency(&key, &hash).await.unwrap(),
+            IdempotencyCheck::Fresh
+        );
+        assert_eq!(
+            executor.reserve_idempotency(&key, &hash).await.unwrap(),
+            IdempotencyCheck::InProgress
+        );
+        assert_eq!(
+            executor.reserve_idempotency(&key, "other").await.unwrap(),
+            IdempotencyCheck::Conflict
+        );
+
+        // A queue refusal releases the key for the retry.
+        let refused = Err(anyhow::anyhow!("queue full"));
+        executor
+            .finish_idempotency(&key, hash.clone(), "tx", &refused)
+            .await;
+        assert_eq!(store.idempotency_entry(&key).await.unwrap(), None);
+        assert_eq!(
+            executor.reserve_idempotency(&key, &hash).await.unwrap(),
+            IdempotencyCheck::Fresh
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outcome_expires_after_ttl() {
+        let store = InMemoryStore::new();
+        let hash = request_hash(b"body");
+        let outcome = IdempotentOutcome {
+            request_hash: hash.clone(),
+            tx_id: "tx".to_string(),
+            accepted: false,
+            message: RejectReason::StaleNonce.to_string(),
+        };
+        assert!(store
+            .reserve_idempotency_key("k", &hash, Duration::from_millis(20))
+            .await
+            .unwrap());
+        assert!(!store
+            .reserve_idempotency_key("k", &hash, Duration::from_millis(20))
+            .await
+            .unwrap());
+        store
+            .record_idempotent_outcome("k", &outcome, Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.idempotency_entry("k").await.unwrap(),
+            Some(IdempotencyEntry::Done(outcome.clone()))
+        );
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(store.idempotency_entry("k").await.unwrap(), None);
+        assert!(store
+            .reserve_idempotency_key("k", &hash, IDEMPOTENCY_TTL)
+            .await
+            .unwrap());
+    }
+
+    #[test]
+    fn test_key_validation() {
+        assert!(validate_key("0b6f1c9e-retry").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("has space").is_err());
+        assert!(validate_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)).is_err());
+    }
+}
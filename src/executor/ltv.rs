@@ -0,0 +1,280 @@
+//! Loan-to-value recomputation from oracle prices. Ratios are [`Ratio`]s in basis points
+//! computed in integer arithmetic from [`Amount`]s; the vault cache's own `ltv_ratio` is
+//! only compared against, never trusted.
+
+pub use crate::executor::fixed::BPS_SCALE;
+use crate::executor::fixed::{Amount, Ratio};
+use crate::executor::VaultStatus;
+use crate::oracle::push::{to_fixed_point, PRICE_DECIMALS};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Debt is denominated in micro-USD.
+pub const DEBT_DECIMALS: u32 = 6;
+/// Vaults at or above this computed LTV are due for rebalancing.
+pub const REBALANCE_LTV_THRESHOLD_BPS: u64 = 8_000;
+/// Largest gap between the cached and computed LTV that is not logged.
+pub const LTV_TOLERANCE_BPS: u64 = 100;
+/// Width of the band below the rebalance threshold in which a vault is at risk.
+pub const AT_RISK_MARGIN_BPS: u64 = 1_000;
+
+/// How close a vault is to rebalancing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultHealth {
+    Healthy,
+    /// Within [`AT_RISK_MARGIN_BPS`] below the rebalance threshold.
+    AtRisk,
+    /// At or above the threshold; the next rebalance cycle acts on it.
+    Rebalancing,
+    /// Unknown collateral or no oracle price, so no LTV.
+    Unpriced,
+}
+
+impl VaultHealth {
+    pub fn classify(ltv: Option<Ratio>, threshold: Ratio) -> Self {
+        match ltv {
+            None => Self::Unpriced,
+            Some(ltv) if ltv >= threshold => Self::Rebalancing,
+            Some(ltv) if ltv >= threshold.saturating_sub(Ratio::from_bps(AT_RISK_MARGIN_BPS)) => {
+                Self::AtRisk
+            }
+            Some(_) => Self::Healthy,
+        }
+    }
+}
+
+/// Decimals of the base unit `collateral_amount` is counted in (sats, micro-STX).
+pub fn collateral_decimals(asset: &str) -> Option<u32> {
+    match asset {
+        "BTC" => Some(8),
+        "STX" => Some(6),
+        _ => None,
+    }
+}
+
+/// `debt / (collateral * price)`, rounded down to a basis point. `price` is USD per whole
+/// collateral unit scaled by `10^PRICE_DECIMALS`. `None` when the collateral is worth
+/// nothing or the result does not fit.
+pub fn compute_ltv(
+    collateral_amount: Amount,
+    collateral_decimals: u32,
+    debt_amount: Amount,
+    price: u128,
+) -> Option<Ratio> {
+    // Both sides are brought to units of 10^-(collateral_decimals + PRICE_DECIMALS) USD.
+    let collateral_value = collateral_amount.checked_mul(price)?;
+    if collateral_value == Amount::ZERO {
+        return None;
+    }
+    let debt_value = debt_amount.rescale(DEBT_DECIMALS, collateral_decimals + PRICE_DECIMALS)?;
+    Ratio::checked_from_fraction(debt_value.units(), collateral_value.units())
+}
+
+/// Largest debt, in micro-USD, at which the collateral sits at `ltv`; the inverse of
+/// [`compute_ltv`], rounded down. `None` when the result does not fit.
+pub fn debt_at_ltv(
+    collateral_amount: Amount,
+    collateral_decimals: u32,
+    price: u128,
+    ltv: Ratio,
+) -> Option<Amount> {
+    collateral_amount
+        .checked_mul(price)?
+        .checked_mul_ratio(ltv)?
+        .rescale(collateral_decimals + PRICE_DECIMALS, DEBT_DECIMALS)
+}
+
+/// `vault`'s LTV from `prices`, as [`assess_vault`] computes it but without logging.
+pub fn priced_ltv(vault: &VaultStatus, prices: &BTreeMap<String, f64>) -> Option<Ratio> {
+    let decimals = collateral_decimals(&vault.collateral_type)?;
+    let price = prices
+        .get(&vault.collateral_type)
+        .and_then(|p| to_fixed_point(*p))
+        .filter(|p| *p > 0)?;
+    compute_ltv(vault.collateral_amount, decimals, vault.debt_amount, price)
+}
+
+/// Recomputes `vault`'s LTV from `prices` (USD per whole unit, keyed by asset), logging
+/// when the cached ratio disagrees by more than [`LTV_TOLERANCE_BPS`]. `None`, with a
+/// warning, when the collateral type is unknown or has no usable price.
+pub fn assess_vault(vault: &VaultStatus, prices: &BTreeMap<String, f64>) -> Option<Ratio> {
+    let Some(decimals) = collateral_decimals(&vault.collateral_type) else {
+        tracing::warn!(
+            vault_id = %vault.vault_id,
+            collateral_type = %vault.collateral_type,
+            "Unknown collateral type; skipping vault"
+        );
+        return None;
+    };
+    let Some(price) = prices
+        .get(&vault.collateral_type)
+        .and_then(|p| to_fixed_point(*p))
+        .filter(|p| *p > 0)
+    else {
+        tracing::warn!(
+            vault_id = %vault.vault_id,
+            collateral_type = %vault.collateral_type,
+            "No oracle price for collateral; skipping vault"
+        );
+        return None;
+    };
+    let Some(ltv) = compute_ltv(vault.collateral_amount, decimals, vault.debt_amount, price) else {
+        tracing::warn!(vault_id = %vault.vault_id, "Vault LTV not computable; skipping vault");
+        return None;
+    };
+    if let Some(cached) = vault
+        .ltv_ratio
+        .filter(|cached| cached.bps().abs_diff(ltv.bps()) > LTV_TOLERANCE_BPS)
+    {
+        tracing::warn!(
+            vault_id = %vault.vault_id,
+            cached_ltv_bps = cached.bps(),
+            computed_ltv_bps = ltv.bps(),
+            "Cached vault LTV disagrees with the oracle-priced LTV"
+        );
+    }
+    Some(ltv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(collateral_type: &str, collateral: u64, debt: u64, cached: f64) -> VaultStatus {
+        VaultStatus {
+            vault_id: "v1".to_string(),
+            collateral_type: collateral_type.to_string(),
+            collateral_amount: collateral.into(),
+            debt_amount: debt.into(),
+            ltv_ratio: Ratio::from_f64(cached),
+        }
+    }
+
+    fn ltv(collateral: u64, decimals: u32, debt: u64, price: u128) -> Option<u64> {
+        compute_ltv(collateral.into(), decimals, debt.into(), price).map(Ratio::bps)
+    }
+
+    #[test]
+    fn test_compute_ltv_fixed_point() {
+        // 1 BTC at $60,000 against $30,000 of debt.
+        let price = to_fixed_point(60_000.0).unwrap();
+        assert_eq!(ltv(100_000_000, 8, 30_000_000_000, price), Some(5_000));
+        // 1,000 STX at $2.50 against $2,000: 80%.
+        let price = to_fixed_point(2.5).unwrap();
+        assert_eq!(ltv(1_000_000_000, 6, 2_000_000_000, price), Some(8_000));
+        // Rounds down.
+        assert_eq!(ltv(3, 6, 1, 100_000_000), Some(3_333));
+        assert_eq!(ltv(0, 8, 1, price), None);
+        assert_eq!(ltv(1, 8, 0, price), Some(0));
+        assert_eq!(ltv(1, 8, u64::MAX, 1), None);
+        // Amounts beyond u64 are priced without truncation.
+        let whale = Amount::new(u64::MAX as u128 * 4);
+        assert_eq!(
+            compute_ltv(whale, 8, Amount::new(u64::MAX as u128), 100_000_000),
+            Some(Ratio::from_bps(250_000))
+        );
+    }
+
+    #[test]
+    fn test_ltv_exactly_at_threshold_rebalances() {
+        let threshold = Ratio::from_bps(REBALANCE_LTV_THRESHOLD_BPS);
+        // 1 BTC at $60,000 against exactly $48,000 is 80%, not 79.99..%.
+        let price = to_fixed_point(60_000.0).unwrap();
+        let at = compute_ltv(
+            Amount::new(100_000_000),
+            8,
+            Amount::new(48_000_000_000),
+            price,
+        );
+        assert_eq!(at, Some(threshold));
+        assert_eq!(
+            VaultHealth::classify(at, threshold),
+            VaultHealth::Rebalancing
+        );
+        let below = compute_ltv(
+            Amount::new(100_000_000),
+            8,
+            Amount::new(47_999_999_999),
+            price,
+        );
+        assert_eq!(VaultHealth::classify(below, threshold), VaultHealth::AtRisk);
+
+        // Across collateral, prices and debts around the boundary, a vault is due exactly
+        // when debt * 10^4 >= threshold * collateral value, compared as integers.
+        for (collateral, decimals) in [(1u64, 8), (3, 6), (100_000_000, 8), (7_777_777, 6)] {
+            for price in [1u128, 3, 99_999_999, 250_000_000, 6_000_000_000_000] {
+                let scale = 10u128.pow(decimals + PRICE_DECIMALS - DEBT_DECIMALS);
+                let value = collateral as u128 * price;
+                let boundary =
+                    (value * threshold.bps() as u128).div_ceil(BPS_SCALE as u128 * scale);
+                for debt in boundary.saturating_sub(2)..=boundary + 2 {
+                    let Ok(debt) = u64::try_from(debt) else {
+                        continue;
+                    };
+                    let due =
+                        debt as u128 * scale * BPS_SCALE as u128 >= threshold.bps() as u128 * value;
+                    let health = VaultHealth::classify(
+                        compute_ltv(collateral.into(), decimals, debt.into(), price),
+                        threshold,
+                    );
+                    assert_eq!(
+                        health == VaultHealth::Rebalancing,
+                        due,
+                        "collateral {} price {} debt {}",
+                        collateral,
+                        price,
+                        debt
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_health_bands() {
+        let classify = |ltv: Option<u64>, threshold: u64| {
+            VaultHealth::classify(ltv.map(Ratio::from_bps), Ratio::from_bps(threshold))
+        };
+        assert_eq!(classify(None, 8_000), VaultHealth::Unpriced);
+        assert_eq!(classify(Some(6_999), 8_000), VaultHealth::Healthy);
+        assert_eq!(classify(Some(7_000), 8_000), VaultHealth::AtRisk);
+        assert_eq!(classify(Some(8_000), 8_000), VaultHealth::Rebalancing);
+        assert_eq!(classify(Some(0), 500), VaultHealth::AtRisk);
+    }
+
+    #[test]
+    fn test_debt_at_ltv_inverts_compute_ltv() {
+        let price = to_fixed_point(60_000.0).unwrap();
+        let target = Ratio::from_bps(7_000);
+        let debt = debt_at_ltv(Amount::new(100_000_000), 8, price, target).unwrap();
+        assert_eq!(debt, Amount::new(42_000_000_000));
+        assert_eq!(
+            compute_ltv(Amount::new(100_000_000), 8, debt, price),
+            Some(target)
+        );
+        assert_eq!(
+            debt_at_ltv(Amount::ZERO, 8, price, target),
+            Some(Amount::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_assess_vault_uses_computed_ltv_when_cache_disagrees() {
+        let prices = BTreeMap::from([("BTC".to_string(), 60_000.0)]);
+        // The cache claims a healthy 10%; prices say 90%.
+        let v = vault("BTC", 100_000_000, 54_000_000_000, 0.1);
+        assert_eq!(assess_vault(&v, &prices), Some(Ratio::from_bps(9_000)));
+    }
+
+    #[test]
+    fn test_assess_vault_skips_missing_price() {
+        let prices = BTreeMap::from([("BTC".to_string(), 60_000.0)]);
+        assert_eq!(assess_vault(&vault("STX", 1, 1, 0.5), &prices), None);
+        assert_eq!(assess_vault(&vault("DOGE", 1, 1, 0.5), &prices), None);
+        assert_eq!(
+            assess_vault(&vault("BTC", 1, 1, 0.5), &BTreeMap::new()),
+            None
+        );
+    }
+}
@@ -1,46 +1,502 @@
 pub mod bitvm;
 pub mod cosmos;
+pub mod cycle;
 pub mod evm;
 pub mod fedimint;
+pub mod fixed;
+pub mod idempotency;
 pub mod lightning;
+pub mod ltv;
+pub mod queue;
+pub mod rebalance;
 pub mod rgb;
 pub mod stacks;
 
+use crate::config::dynamic::DynamicConfigHandle;
+use crate::config::{
+    DEFAULT_EXECUTOR_MAX_QUEUE_AGE_SECS, DEFAULT_EXECUTOR_MAX_QUEUE_DEPTH,
+    DEFAULT_EXECUTOR_SENDER_RATE_LIMIT, DEFAULT_EXECUTOR_SENDER_RATE_WINDOW_SECS,
+};
+use crate::events::NexusEvent;
+use crate::latency::timed_query;
+use crate::oracle::push::to_fixed_point;
+use crate::signing::RotatingSigner;
+use crate::stacks::transaction::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
+use crate::stacks::{sip018, ClarityValue, StacksAddress, StacksSigner};
+use crate::storage::store::NexusStore;
 use crate::storage::Storage;
+use crate::sync::decoder::ConxianAction;
 use chrono::{DateTime, Utc};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Finality level the FSOC check validates against.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FinalityLevel {
+    /// Any observed state, including soft microblock confirmations.
+    #[default]
+    Soft,
+    /// Only state anchored by a hard-finalized burn block.
+    Hard,
+}
+
+impl fmt::Display for FinalityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Soft => write!(f, "soft"),
+            Self::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl FromStr for FinalityLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "soft" => Ok(Self::Soft),
+            "hard" => Ok(Self::Hard),
+            other => anyhow::bail!("Unknown finality level '{}' (expected soft|hard)", other),
+        }
+    }
+}
+
+/// How long a cached latest event time is trusted before it is read again. Bounds how
+/// far behind the FSOC check can fall when the time moves without this node writing,
+/// as when another instance sequences a request or a block is finalized.
+pub const EVENT_TIME_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Latest event time at one finality level, as of when it was read.
+#[derive(Debug, Default)]
+pub struct EventTimeCache(Mutex<Option<(DateTime<Utc>, Instant)>>);
+
+impl EventTimeCache {
+    /// The cached time unless it is older than [`EVENT_TIME_CACHE_TTL`].
+    pub fn get(&self) -> Option<DateTime<Utc>> {
+        self.0
+            .lock()
+            .unwrap()
+            .filter(|(_, read_at)| read_at.elapsed() < EVENT_TIME_CACHE_TTL)
+            .map(|(time, _)| time)
+    }
+
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.0.lock().unwrap() = Some((time, Instant::now()));
+    }
+
+    /// Raises a cached time to `time` after this node wrote an event at it.
+    pub fn advance(&self, time: DateTime<Utc>) {
+        if let Some((cached, _)) = self.0.lock().unwrap().as_mut() {
+            *cached = (*cached).max(time);
+        }
+    }
+}
+
+/// Why the sequencer turned a request away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// Safety Mode (Sovereign Handoff) is active.
+    SafetyMode,
+    /// The request is not newer than the latest sequenced event (FSOC).
+    FrontRunning,
+    /// The nonce is not greater than the sender's last accepted nonce (replay).
+    StaleNonce,
+    /// The sequencer queue was full.
+    QueueFull,
+    /// The request waited in the sequencer queue past its maximum age.
+    Expired,
+    /// The sender's off-chain submissions alone exceeded the per-sender rate limit.
+    OffChainVelocity,
+    /// The sender's off-chain submissions plus its recently ingested on-chain
+    /// transactions exceeded the per-sender rate limit.
+    OnChainVelocity,
+    /// The request carried no valid signature by its sender and came with no API key.
+    InvalidSignature,
+}
+
+impl RejectReason {
+    pub const ALL: [RejectReason; 8] = [
+        Self::SafetyMode,
+        Self::FrontRunning,
+        Self::StaleNonce,
+        Self::QueueFull,
+        Self::Expired,
+        Self::OffChainVelocity,
+        Self::OnChainVelocity,
+        Self::InvalidSignature,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SafetyMode => "safety_mode",
+            Self::FrontRunning => "front_running",
+            Self::StaleNonce => "stale_nonce",
+            Self::QueueFull => "queue_full",
+            Self::Expired => "expired",
+            Self::OffChainVelocity => "off_chain_velocity",
+            Self::OnChainVelocity => "on_chain_velocity",
+            Self::InvalidSignature => "invalid_signature",
+        }
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RejectReason {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown rejection reason '{}'", s))
+    }
+}
+
+/// Whether a recorded request was sequenced or turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    Accepted,
+    Rejected,
+}
+
+impl ExecutionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+impl FromStr for ExecutionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accepted" => Ok(Self::Accepted),
+            "rejected" => Ok(Self::Rejected),
+            other => anyhow::bail!("status '{}' is not accepted or rejected", other),
+        }
+    }
+}
+
+/// Error returned for a rejected request; recover the reason with
+/// `err.downcast_ref::<ExecutionRejected>()`.
+#[derive(Debug, Clone)]
+pub struct ExecutionRejected {
+    pub reason: RejectReason,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ExecutionRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl std::error::Error for ExecutionRejected {}
+
+/// Outcome of one sequencing decision, counted in [`ExecutorStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    Accepted,
+    Rejected(RejectReason),
+}
+
+impl ExecutionOutcome {
+    /// Counter name: `accepted`, or `rejected:<reason>`.
+    pub fn stat_key(self) -> String {
+        match self {
+            Self::Accepted => "accepted".to_string(),
+            Self::Rejected(reason) => format!("rejected:{}", reason),
+        }
+    }
+}
+
+/// Accepted and rejected request counts since the counters were created.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutorStats {
+    pub accepted: u64,
+    pub rejected: u64,
+    /// Rejections per [`RejectReason`]; every reason is listed, including zeroes.
+    pub rejected_by_reason: BTreeMap<String, u64>,
+}
+
+impl ExecutorStats {
+    /// Builds stats from counters keyed by [`ExecutionOutcome::stat_key`]. Unknown keys
+    /// (e.g. reasons retired from this binary) still count towards `rejected`.
+    pub fn from_counters(counters: &HashMap<String, u64>) -> Self {
+        let mut rejected_by_reason: BTreeMap<String, u64> = RejectReason::ALL
+            .iter()
+            .map(|r| (r.as_str().to_string(), 0))
+            .collect();
+        for (key, count) in counters {
+            if let Some(reason) = key.strip_prefix("rejected:") {
+                *rejected_by_reason.entry(reason.to_string()).or_default() += count;
+            }
+        }
+        Self {
+            accepted: counters.get("accepted").copied().unwrap_or(0),
+            rejected: rejected_by_reason.values().sum(),
+            rejected_by_reason,
+        }
+    }
+}
+
+/// Furthest a request timestamp may run ahead of the node's clock. A later timestamp
+/// would sort after every event still to come and always pass the FSOC check.
+pub const MAX_FUTURE_SKEW_SECS: i64 = 30;
+
+/// Rejects `timestamp` when it is more than [`MAX_FUTURE_SKEW_SECS`] ahead of `now`.
+pub fn check_future_skew(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> anyhow::Result<()> {
+    let ahead = (timestamp - now).num_seconds();
+    anyhow::ensure!(
+        ahead <= MAX_FUTURE_SKEW_SECS,
+        "timestamp {} is {}s ahead of the node clock (at most {}s allowed)",
+        timestamp.to_rfc3339(),
+        ahead,
+        MAX_FUTURE_SKEW_SECS
+    );
+    Ok(())
+}
+
+/// Whose request rate `request` counts towards: the API key fingerprint `caller` that
+/// submitted it, or for an unauthenticated submission the sender it claims.
+fn submitter(caller: Option<&str>, request: &ExecutionRequest) -> String {
+    match caller {
+        Some(caller) => format!("key:{}", caller),
+        None => format!("sender:{}", request.sender),
+    }
+}
+
+/// Whose nonces `request` claims from: its sender's own when the sender signed it,
+/// otherwise the sender's as seen through the API key fingerprint `caller`, so no one
+/// can use up the nonces of a sender whose key they do not hold. A bad signature, or
+/// none on an anonymous request, is rejected instead.
+fn nonce_owner(caller: Option<&str>, request: &ExecutionRequest) -> Result<String, RejectReason> {
+    match (&request.signature, caller) {
+        (Some(_), _) if request.verify_signature() => Ok(request.sender.clone()),
+        (None, Some(caller)) => Ok(format!("key:{}:{}", caller, request.sender)),
+        _ => Err(RejectReason::InvalidSignature),
+    }
+}
+
+/// SIP-018 domain name execution requests are signed under.
+pub const EXECUTION_DOMAIN_NAME: &str = "conxian-nexus-execution";
+pub const EXECUTION_DOMAIN_VERSION: &str = "1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionRequest {
     pub tx_id: String,
     pub payload: String,
     pub timestamp: DateTime<Utc>,
     pub sender: String,
+    /// Must exceed the highest nonce already accepted from `sender`.
+    pub nonce: u64,
     #[serde(default)]
     pub priority: i32,
+    /// The sender's SIP-018 signature over [`Self::message`], as 65-byte `r || s ||
+    /// recovery id` hex. Required unless the request comes with an API key; kept in the
+    /// audit log either way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ExecutionRequest {
+    /// Conxian contract function this request calls on one of `contracts`, decoded from
+    /// `payload`.
+    pub fn action(&self, contracts: &[String]) -> Option<ConxianAction> {
+        crate::sync::decoder::decode_payload(&self.payload, contracts)
+    }
+
+    /// SIP-018 domain: [`EXECUTION_DOMAIN_NAME`] on the network of `sender`'s address.
+    pub fn domain(sender: &StacksAddress) -> ClarityValue {
+        let chain_id = if sender.is_mainnet() {
+            CHAIN_ID_MAINNET
+        } else {
+            CHAIN_ID_TESTNET
+        };
+        sip018::domain(EXECUTION_DOMAIN_NAME, EXECUTION_DOMAIN_VERSION, chain_id)
+    }
+
+    /// The signed message: every field but the sender, whom the signature identifies,
+    /// and the signature itself, as a Clarity tuple. The timestamp is in milliseconds.
+    pub fn message(&self) -> ClarityValue {
+        ClarityValue::tuple([
+            ("tx-id", ClarityValue::StringUtf8(self.tx_id.clone())),
+            (
+                "payload-hash",
+                ClarityValue::Buffer(Sha256::digest(self.payload.as_bytes()).to_vec()),
+            ),
+            ("nonce", ClarityValue::UInt(self.nonce.into())),
+            ("priority", ClarityValue::Int(self.priority.into())),
+            (
+                "timestamp",
+                ClarityValue::Int(self.timestamp.timestamp_millis().into()),
+            ),
+        ])
+    }
+
+    /// Signs the request as `signer`, who must hold the key behind `sender`.
+    pub fn sign(mut self, signer: &StacksSigner) -> anyhow::Result<Self> {
+        let sender: StacksAddress = self.sender.parse()?;
+        self.signature =
+            Some(signer.sign_structured_data(&Self::domain(&sender), &self.message())?);
+        Ok(self)
+    }
+
+    /// Whether `signature` is a SIP-018 signature over [`Self::message`] by the key
+    /// behind `sender`.
+    pub fn verify_signature(&self) -> bool {
+        let (Some(signature), Ok(sender)) = (&self.signature, self.sender.parse::<StacksAddress>())
+        else {
+            return false;
+        };
+        let Some(sig) = hex::decode(signature).ok().filter(|sig| sig.len() == 65) else {
+            return false;
+        };
+        let (Ok(signature), Some(recovery_id)) = (
+            Signature::from_slice(&sig[..64]),
+            RecoveryId::from_byte(sig[64]),
+        ) else {
+            return false;
+        };
+        let digest = sip018::structured_data_hash(&Self::domain(&sender), &self.message());
+        VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id)
+            .is_ok_and(|key| StacksAddress::p2pkh(sender.is_mainnet(), &key) == sender)
+    }
+}
+
+/// A request as recorded in the execution audit log, accepted or rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExecutionRecord {
+    /// Position in the order the sequencer decided requests, from 1. Accepted and
+    /// rejected requests share one order.
+    pub sequence: u64,
+    pub tx_id: String,
+    pub sender: String,
+    /// 0 for requests recorded before nonces were.
+    pub nonce: u64,
+    pub priority: i32,
+    /// Hex SHA-256 of the payload.
+    pub payload_hash: String,
+    /// The request's own timestamp.
+    pub timestamp: DateTime<Utc>,
+    /// When the sequencer recorded it.
+    pub recorded_at: DateTime<Utc>,
+    pub status: ExecutionStatus,
+    /// Set when `status` is rejected.
+    pub rejection_reason: Option<RejectReason>,
+    pub signature: Option<String>,
+}
+
+impl ExecutionRecord {
+    /// The record of `request` with `sequence`, accepted unless `rejection` is given.
+    pub fn of(request: &ExecutionRequest, sequence: u64, rejection: Option<RejectReason>) -> Self {
+        Self {
+            sequence,
+            tx_id: request.tx_id.clone(),
+            sender: request.sender.clone(),
+            nonce: request.nonce,
+            priority: request.priority,
+            payload_hash: hex::encode(Sha256::digest(request.payload.as_bytes())),
+            timestamp: request.timestamp,
+            recorded_at: Utc::now(),
+            status: match rejection {
+                Some(_) => ExecutionStatus::Rejected,
+                None => ExecutionStatus::Accepted,
+            },
+            rejection_reason: rejection,
+            signature: request.signature.clone(),
+        }
+    }
+}
+
+/// Which recorded executions to list; every field narrows the result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionFilter {
+    pub sender: Option<String>,
+    pub status: Option<ExecutionStatus>,
+    /// Only requests timestamped at or after this.
+    pub since: Option<DateTime<Utc>>,
+    /// Only requests sequenced after this one, to continue from a previous page.
+    pub after_sequence: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultStatus {
     pub vault_id: String,
-    pub collateral_amount: u64,
-    pub debt_amount: u64,
-    pub ltv_ratio: f64,
+    /// Collateral asset (e.g. `BTC`, `STX`); see [`ltv::collateral_decimals`].
+    #[serde(default)]
+    pub collateral_type: String,
+    /// In the collateral's base unit.
+    pub collateral_amount: fixed::Amount,
+    /// In micro-USD.
+    pub debt_amount: fixed::Amount,
+    /// As cached by the vault writer; only compared against the recomputed LTV. `None`
+    /// when the writer had no price for the collateral.
+    #[serde(default)]
+    pub ltv_ratio: Option<fixed::Ratio>,
+}
+
+/// A vault with its oracle-priced LTV and health.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultReport {
+    #[serde(flatten)]
+    pub vault: VaultStatus,
+    /// Recomputed from oracle prices; `None` when the vault is unpriced.
+    pub ltv_bps: Option<u64>,
+    pub health: ltv::VaultHealth,
 }
 
 pub struct NexusExecutor {
     pub fedimint_adapter: fedimint::FedimintAdapter,
     pub storage: Arc<Storage>,
-    pub latest_event_time_cache: Mutex<Option<DateTime<Utc>>>,
+    /// Safety flags, audit log and vault reads; defaults to `storage`.
+    pub store: Arc<dyn NexusStore>,
+    pub latest_event_time_cache: EventTimeCache,
+    pub latest_hard_event_time_cache: EventTimeCache,
+    /// Finality level used by `validate_transaction` when none is requested explicitly.
+    pub required_finality: FinalityLevel,
     pub rgb_adapter: rgb::RGBAdapter,
     pub lightning_adapter: lightning::LightningResilienceAdapter,
     pub bitvm_adapter: bitvm::BitVMAdapter,
     pub evm_adapter: evm::EVMAdapter,
     pub cosmos_adapter: cosmos::CosmosAdapter,
     pub stacks_adapter: stacks::StacksAdapter,
+    /// Rebalance threshold, read on every rebalance cycle.
+    pub dynamic: DynamicConfigHandle,
+    /// Signs rebalance instructions; unsigned without one. Rotated through
+    /// `POST /admin/v1/rotate-key`.
+    pub rebalance_signer: Option<Arc<RotatingSigner<StacksSigner>>>,
+    /// Chain id of the SIP-018 domain rebalance instructions are signed under.
+    pub chain_id: u32,
+    /// Keeps rebalance cycles from overlapping, on this node and across instances.
+    pub rebalance_lock: cycle::CycleLock,
+    /// Bounds the executions waiting to be sequenced.
+    pub queue: queue::ExecutionQueue,
+    /// Requests (validations and submissions) one sender may make per
+    /// `sender_rate_window`, counting its ingested on-chain transactions too; 0 disables
+    /// the check.
+    pub sender_rate_limit: u64,
+    pub sender_rate_window: Duration,
+    /// Contracts whose calls are decoded into a [`ConxianAction`]; none by default.
+    pub action_contracts: Vec<String>,
 }
 
 impl NexusExecutor {
@@ -57,8 +513,11 @@ impl NexusExecutor {
         let stacks_adapter = stacks::StacksAdapter::new();
         let fedimint_adapter = fedimint::FedimintAdapter::new(storage.clone());
         Self {
+            store: storage.clone(),
             storage,
-            latest_event_time_cache: Mutex::new(None),
+            latest_event_time_cache: EventTimeCache::default(),
+            latest_hard_event_time_cache: EventTimeCache::default(),
+            required_finality: FinalityLevel::default(),
             rgb_adapter,
             lightning_adapter,
             bitvm_adapter,
@@ -66,89 +525,514 @@ impl NexusExecutor {
             cosmos_adapter,
             stacks_adapter,
             fedimint_adapter,
+            dynamic: DynamicConfigHandle::default(),
+            rebalance_signer: None,
+            chain_id: CHAIN_ID_MAINNET,
+            rebalance_lock: cycle::CycleLock::new(
+                rebalance::REBALANCE_CYCLE_LOCK,
+                rebalance::REBALANCE_CYCLE_LOCK_TTL,
+            ),
+            queue: queue::ExecutionQueue::new(
+                DEFAULT_EXECUTOR_MAX_QUEUE_DEPTH,
+                Duration::from_secs(DEFAULT_EXECUTOR_MAX_QUEUE_AGE_SECS),
+            ),
+            sender_rate_limit: DEFAULT_EXECUTOR_SENDER_RATE_LIMIT,
+            sender_rate_window: Duration::from_secs(DEFAULT_EXECUTOR_SENDER_RATE_WINDOW_SECS),
+            action_contracts: Vec::new(),
         }
     }
 
+    /// Sets the default finality level for FSOC validation.
+    pub fn with_required_finality(mut self, finality: FinalityLevel) -> Self {
+        self.required_finality = finality;
+        self
+    }
+
+    pub fn with_dynamic_config(mut self, config: DynamicConfigHandle) -> Self {
+        self.dynamic = config;
+        self
+    }
+
+    pub fn with_rebalance_signer(mut self, signer: Arc<RotatingSigner<StacksSigner>>) -> Self {
+        self.rebalance_signer = Some(signer);
+        self
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Refuses submissions once `max_depth` are queued and expires those that waited
+    /// longer than `max_age` for their turn.
+    pub fn with_queue_limits(mut self, max_depth: usize, max_age: Duration) -> Self {
+        self.queue = queue::ExecutionQueue::new(max_depth, max_age);
+        self
+    }
+
+    /// Rejects a sender's requests beyond `limit` per `window`; 0 disables the check.
+    pub fn with_sender_rate_limit(mut self, limit: u64, window: Duration) -> Self {
+        self.sender_rate_limit = limit;
+        self.sender_rate_window = window;
+        self
+    }
+
+    /// Decodes calls to `contracts` into a [`ConxianAction`].
+    pub fn with_action_contracts(mut self, contracts: Vec<String>) -> Self {
+        self.action_contracts = contracts;
+        self
+    }
+
+    /// Routes safety, audit and vault access through `store` instead of `storage`.
+    pub fn with_store(mut self, store: Arc<dyn NexusStore>) -> Self {
+        self.store = store;
+        self
+    }
+
     /// Checks if the system is in safety mode and blocks submission if so.
     pub async fn check_safety_mode(&self) -> anyhow::Result<()> {
-        if crate::safety::is_safety_mode_active(&self.storage).await? {
-            anyhow::bail!(
-                "System is in Safety Mode (Sovereign Handoff Active). Execution blocked."
-            );
+        if self.store.is_safety_mode_active().await? {
+            self.count(ExecutionOutcome::Rejected(RejectReason::SafetyMode))
+                .await;
+            return Err(ExecutionRejected {
+                reason: RejectReason::SafetyMode,
+                message: "System is in Safety Mode (Sovereign Handoff Active). Execution blocked.",
+            }
+            .into());
         }
         Ok(())
     }
 
+    /// Waits for `request`'s turn in the sequencer queue. A refusal is counted, recorded
+    /// in the audit log as a rejection and returned as a [`queue::QueueError`].
+    pub async fn enter_queue(
+        &self,
+        request: &ExecutionRequest,
+    ) -> anyhow::Result<queue::QueueTurn<'_>> {
+        let e = match self.queue.enter().await {
+            Ok(turn) => return Ok(turn),
+            Err(e) => e,
+        };
+        let reason = match e {
+            queue::QueueError::Full { .. } => RejectReason::QueueFull,
+            queue::QueueError::Expired { .. } => RejectReason::Expired,
+        };
+        self.count(ExecutionOutcome::Rejected(reason)).await;
+        self.record_rejection(request, reason).await;
+        Err(e.into())
+    }
+
+    /// Best-effort, like counting: the caller already gets the rejection as an error.
+    async fn record_rejection(&self, request: &ExecutionRequest, reason: RejectReason) {
+        if let Err(e) = self.store.record_rejection(request, reason).await {
+            tracing::warn!(tx_id = %request.tx_id, error = %e, "Failed to record rejected execution");
+        }
+    }
+
+    /// Counts and records `request`'s rejection for `reason` and returns it as an
+    /// [`ExecutionRejected`].
+    async fn reject(&self, request: &ExecutionRequest, reason: RejectReason) -> anyhow::Error {
+        self.count(ExecutionOutcome::Rejected(reason)).await;
+        self.record_rejection(request, reason).await;
+        ExecutionRejected {
+            reason,
+            message: match reason {
+                RejectReason::StaleNonce => {
+                    "Nonce must be greater than the sender's last accepted nonce"
+                }
+                RejectReason::OffChainVelocity => "Sender exceeded the submission rate limit",
+                RejectReason::OnChainVelocity => {
+                    "Sender exceeded the rate limit counting its on-chain transactions"
+                }
+                RejectReason::InvalidSignature => {
+                    "Request must be signed by its sender or submitted with an API key"
+                }
+                _ => "Transaction validation failed",
+            },
+        }
+        .into()
+    }
+
+    /// Takes `request` back out of `submitter`'s window; best-effort like recording.
+    async fn withdraw_submission(&self, submitter: &str, request: &ExecutionRequest) {
+        if let Err(e) = self
+            .store
+            .withdraw_submission(submitter, &request.tx_id)
+            .await
+        {
+            tracing::warn!(tx_id = %request.tx_id, error = %e, "Failed to withdraw rejected submission");
+        }
+    }
+
+    /// Sequences `request` once its turn in the queue comes. Refusals by the queue are
+    /// [`queue::QueueError`]s, sequencing rejections [`ExecutionRejected`]s.
     pub async fn submit(&self, request: ExecutionRequest) -> anyhow::Result<String> {
-        self.check_safety_mode().await?;
-        if !self.validate_transaction(&request).await? {
-            anyhow::bail!("Transaction validation failed");
+        self.submit_as(None, request).await
+    }
+
+    /// [`Self::submit`] for the caller authenticated by the API key fingerprint `caller`,
+    /// whose request rate the submission counts towards instead of its claimed sender's.
+    /// An unsigned request from a caller draws on nonces kept for that caller alone.
+    #[tracing::instrument(skip_all, fields(tx_id = %request.tx_id))]
+    pub async fn submit_as(
+        &self,
+        caller: Option<&str>,
+        request: ExecutionRequest,
+    ) -> anyhow::Result<String> {
+        let _turn = self.enter_queue(&request).await?;
+        if let Err(e) = self.check_safety_mode().await {
+            if e.is::<ExecutionRejected>() {
+                self.record_rejection(&request, RejectReason::SafetyMode)
+                    .await;
+            }
+            return Err(e);
+        }
+        let submitter = submitter(caller, &request);
+        let nonce_owner = match nonce_owner(caller, &request) {
+            Ok(owner) => owner,
+            Err(reason) => return Err(self.reject(&request, reason).await),
+        };
+        let mut rejection = self
+            .rejection_reason(&request, &submitter, &nonce_owner, self.required_finality)
+            .await?;
+        // The reads in `rejection_reason` can race a concurrent submission; admitting the
+        // request to its submitter's window and claiming the nonce are the atomic checks.
+        // A request refused by the nonce claim leaves the window again, so only accepted
+        // requests count towards the rate.
+        let rate_limited = self.sender_rate_limit > 0;
+        if rejection.is_none()
+            && rate_limited
+            && !self
+                .store
+                .admit_submission(
+                    &submitter,
+                    &request.tx_id,
+                    self.sender_rate_limit,
+                    self.sender_rate_window,
+                )
+                .await?
+        {
+            rejection = Some(RejectReason::OffChainVelocity);
+        }
+        if rejection.is_none() && !self.store.claim_nonce(&nonce_owner, request.nonce).await? {
+            if rate_limited {
+                self.withdraw_submission(&submitter, &request).await;
+            }
+            rejection = Some(RejectReason::StaleNonce);
+        }
+        if let Some(reason) = rejection {
+            return Err(self.reject(&request, reason).await);
         }
 
-        // [Hole 4.1] Expand audit logs to include full payload and priority metadata
-        sqlx::query(
-            "INSERT INTO me_audit_log (tx_id, payload_hash, sender, arrival_time, payload, sequencing_priority)
-             VALUES ($1, $2, $3, $4, $5, $6)",
-        )
-        .bind(&request.tx_id)
-        .bind(hex::encode(Sha256::digest(request.payload.as_bytes())))
-        .bind(&request.sender)
-        .bind(request.timestamp)
-        .bind(&request.payload)
-        .bind(request.priority)
-        .execute(&self.storage.pg_pool)
-        .await?;
-
-        tracing::info!("Transaction {} accepted by FSOC sequencer", request.tx_id);
+        // Nothing was sequenced if the audit row is not written, so the nonce and the
+        // window slot are handed back for a retry and the request is not counted.
+        if let Err(e) = self.store.record_execution(&request).await {
+            if let Err(e) = self.store.release_nonce(&nonce_owner, request.nonce).await {
+                tracing::warn!(tx_id = %request.tx_id, error = %e, "Failed to release the nonce of an unrecorded execution");
+            }
+            if rate_limited {
+                self.withdraw_submission(&submitter, &request).await;
+            }
+            return Err(e);
+        }
+        self.count(ExecutionOutcome::Accepted).await;
+        // Hard finality follows the chain, so only the soft time moves with this write.
+        self.latest_event_time_cache.advance(request.timestamp);
+        // Best-effort, like counting: the request is already sequenced.
+        let event = NexusEvent::ExecutionSequenced {
+            tx_id: request.tx_id.clone(),
+            sender: request.sender.clone(),
+            nonce: request.nonce,
+        };
+        if let Err(e) = self.store.publish_event(&event).await {
+            tracing::warn!(tx_id = %request.tx_id, error = %e, "Failed to publish execution event");
+        }
+
+        tracing::info!(
+            action = ?request.action(&self.action_contracts),
+            nonce = request.nonce,
+            "Transaction {} accepted by FSOC sequencer",
+            request.tx_id
+        );
         Ok(request.tx_id)
     }
 
     pub async fn validate_transaction(&self, request: &ExecutionRequest) -> anyhow::Result<bool> {
-        if let Some(event_time) = self.get_cached_or_fetch_latest_event_time().await? {
-            if request.timestamp <= event_time {
-                return Ok(false);
+        self.validate_transaction_with_finality(request, self.required_finality)
+            .await
+    }
+
+    /// Validates the sender's signature, then against the latest event at the given
+    /// finality level, the sender's last accepted nonce and its request rate, without
+    /// claiming the nonce or counting towards the rate. With `Hard`, only events whose
+    /// transactions landed in hard-finalized blocks count. The decision is counted in
+    /// [`NexusExecutor::stats`].
+    pub async fn validate_transaction_with_finality(
+        &self,
+        request: &ExecutionRequest,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<bool> {
+        let submitter = submitter(None, request);
+        let rejection = match nonce_owner(None, request) {
+            Ok(owner) => {
+                self.rejection_reason(request, &submitter, &owner, finality)
+                    .await?
             }
-        }
-        Ok(true)
+            Err(reason) => Some(reason),
+        };
+        let outcome = match rejection {
+            Some(reason) => ExecutionOutcome::Rejected(reason),
+            None => ExecutionOutcome::Accepted,
+        };
+        self.count(outcome).await;
+        Ok(outcome == ExecutionOutcome::Accepted)
     }
 
-    async fn get_cached_or_fetch_latest_event_time(&self) -> anyhow::Result<Option<DateTime<Utc>>> {
-        {
-            let cache = self.latest_event_time_cache.lock().unwrap();
-            if let Some(t) = *cache {
-                return Ok(Some(t));
+    async fn rejection_reason(
+        &self,
+        request: &ExecutionRequest,
+        submitter: &str,
+        nonce_owner: &str,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<Option<RejectReason>> {
+        if let Some(reason) = self.check_sender_velocity(request, submitter).await? {
+            return Ok(Some(reason));
+        }
+        if self.detect_front_running(request, finality).await? {
+            return Ok(Some(RejectReason::FrontRunning));
+        }
+        if let Some(last) = self.store.last_nonce(nonce_owner).await? {
+            if request.nonce <= last {
+                tracing::warn!(
+                    tx_id = %request.tx_id,
+                    sender = %request.sender,
+                    nonce = request.nonce,
+                    last_nonce = last,
+                    "Replayed or stale nonce rejected by FSOC sequencer"
+                );
+                return Ok(Some(RejectReason::StaleNonce));
             }
         }
+        Ok(None)
+    }
 
-        let row = sqlx::query("SELECT MAX(arrival_time) as last_time FROM me_audit_log")
-            .fetch_one(&self.storage.pg_pool)
+    /// Rejects `request` when the off-chain requests accepted from `submitter` within the
+    /// window, alone or together with the sender's on-chain transactions in blocks from
+    /// that window, have reached the limit. Off-chain requests are never written to
+    /// `stacks_transactions`, so the on-chain count cannot stand in for them.
+    pub async fn check_sender_velocity(
+        &self,
+        request: &ExecutionRequest,
+        submitter: &str,
+    ) -> anyhow::Result<Option<RejectReason>> {
+        if self.sender_rate_limit == 0 {
+            return Ok(None);
+        }
+        let off_chain = self
+            .store
+            .submission_count(submitter, self.sender_rate_window)
             .await?;
+        let reason = if off_chain >= self.sender_rate_limit {
+            Some(RejectReason::OffChainVelocity)
+        } else {
+            let since = Utc::now()
+                - chrono::Duration::from_std(self.sender_rate_window).unwrap_or_default();
+            let on_chain = self
+                .store
+                .sender_transactions_since(&request.sender, since)
+                .await?;
+            (off_chain + on_chain >= self.sender_rate_limit)
+                .then_some(RejectReason::OnChainVelocity)
+        };
+        if let Some(reason) = reason {
+            tracing::warn!(
+                tx_id = %request.tx_id,
+                sender = %request.sender,
+                submitter,
+                off_chain,
+                limit = self.sender_rate_limit,
+                %reason,
+                "Sender velocity limit exceeded"
+            );
+        }
+        Ok(reason)
+    }
 
-        let last_time: Option<DateTime<Utc>> = row.get("last_time");
+    /// Whether `request` is not newer than the latest event visible at `finality`.
+    pub async fn detect_front_running(
+        &self,
+        request: &ExecutionRequest,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<bool> {
+        let front_running = match self.get_cached_or_fetch_latest_event_time(finality).await? {
+            Some(event_time) => request.timestamp <= event_time,
+            None => false,
+        };
+        if front_running {
+            tracing::warn!(
+                tx_id = %request.tx_id,
+                sender = %request.sender,
+                "Front-running attempt rejected by FSOC sequencer"
+            );
+        }
+        Ok(front_running)
+    }
+
+    /// Accepted/rejected counts by reason.
+    pub async fn stats(&self) -> anyhow::Result<ExecutorStats> {
+        self.store.executor_stats().await
+    }
+
+    /// Counting is best-effort: a store outage must not change the sequencing decision.
+    async fn count(&self, outcome: ExecutionOutcome) {
+        if let Err(e) = self.store.record_execution_outcome(outcome).await {
+            tracing::warn!(outcome = %outcome.stat_key(), "Failed to record executor stat: {}", e);
+        }
+    }
+
+    fn event_time_cache(&self, finality: FinalityLevel) -> &EventTimeCache {
+        match finality {
+            FinalityLevel::Soft => &self.latest_event_time_cache,
+            FinalityLevel::Hard => &self.latest_hard_event_time_cache,
+        }
+    }
+
+    async fn get_cached_or_fetch_latest_event_time(
+        &self,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        if let Some(t) = self.event_time_cache(finality).get() {
+            return Ok(Some(t));
+        }
+
+        let last_time = self.store.latest_execution_time(finality).await?;
         if let Some(t) = last_time {
-            let mut cache = self.latest_event_time_cache.lock().unwrap();
-            *cache = Some(t);
+            self.event_time_cache(finality).set(t);
         }
         Ok(last_time)
     }
 
-    pub async fn execute_rebalance(&self) -> anyhow::Result<()> {
-        Ok(())
+    /// Returns a signed instruction for each vault whose LTV, recomputed from oracle
+    /// prices, is at or above the current `rebalance_ltv_threshold_bps`, targeting
+    /// [`rebalance::REBALANCE_TARGET_MARGIN_BPS`] below it. Vaults without a price are
+    /// skipped. Refuses to act while the safety monitor flags the oracle's prices as
+    /// stale, since LTVs computed from them may be wrong. Issues nothing while another
+    /// cycle is still running, here or on another instance.
+    pub async fn execute_rebalance(&self) -> anyhow::Result<Vec<rebalance::SignedRebalance>> {
+        let Some(cycle) = self.rebalance_lock.try_acquire(&self.store).await? else {
+            return Ok(Vec::new());
+        };
+        let due = self.rebalance_cycle(&cycle).await;
+        cycle.release().await;
+        due
+    }
+
+    async fn rebalance_cycle(
+        &self,
+        cycle: &cycle::CycleGuard,
+    ) -> anyhow::Result<Vec<rebalance::SignedRebalance>> {
+        if self.store.is_oracle_stale().await? {
+            anyhow::bail!("Oracle prices are stale; rebalance refused");
+        }
+        let vaults = self.store.vaults().await?;
+        if vaults.is_empty() {
+            return Ok(Vec::new());
+        }
+        let prices = self.store.collateral_prices().await?;
+        let threshold = self.dynamic.get().rebalance_ltv_threshold_bps;
+        let target_ltv = threshold.saturating_sub(fixed::Ratio::from_bps(
+            rebalance::REBALANCE_TARGET_MARGIN_BPS,
+        ));
+        let mut due = Vec::new();
+        for vault in &vaults {
+            let Some(ltv) = ltv::assess_vault(vault, &prices) else {
+                continue;
+            };
+            if ltv < threshold {
+                continue;
+            }
+            // assess_vault only returns an LTV for known collateral with a usable price.
+            let Some(price) = prices
+                .get(&vault.collateral_type)
+                .and_then(|p| to_fixed_point(*p))
+            else {
+                continue;
+            };
+            // Another instance may take over once the lock is lost; leave it the rest.
+            if !cycle.is_held() {
+                tracing::warn!(
+                    issued = due.len(),
+                    "Rebalance cycle lock lost; issuing no further instructions"
+                );
+                break;
+            }
+            let nonce = self.store.next_rebalance_nonce().await?;
+            let Some(instruction) = rebalance::RebalanceInstruction::for_vault(
+                vault,
+                price,
+                target_ltv,
+                self.chain_id,
+                nonce,
+                Utc::now().timestamp(),
+            ) else {
+                tracing::warn!(vault_id = %vault.vault_id, "Rebalance target not computable; skipping vault");
+                continue;
+            };
+            let signer = self.rebalance_signer.as_ref().map(|s| s.current());
+            let signed = instruction.sign(signer.as_deref())?;
+            tracing::info!(
+                vault_id = %vault.vault_id,
+                ltv_bps = ltv.bps(),
+                target_debt = %signed.instruction.target_debt,
+                nonce,
+                tx_id = %signed.tx_id,
+                "Vault LTV at or above the rebalance threshold; instruction issued"
+            );
+            due.push(signed);
+        }
+        Ok(due)
+    }
+
+    /// Active vaults priced as the rebalance cycle prices them, riskiest first with the
+    /// unpriced last. With `min_ltv_bps`, only vaults at or above it are listed.
+    pub async fn vault_reports(
+        &self,
+        min_ltv_bps: Option<u64>,
+    ) -> anyhow::Result<Vec<VaultReport>> {
+        let vaults = self.store.vaults().await?;
+        let prices = self.store.collateral_prices().await?;
+        let threshold = self.dynamic.get().rebalance_ltv_threshold_bps;
+        let mut reports: Vec<VaultReport> = vaults
+            .into_iter()
+            .map(|vault| {
+                let ltv = ltv::priced_ltv(&vault, &prices);
+                VaultReport {
+                    health: ltv::VaultHealth::classify(ltv, threshold),
+                    vault,
+                    ltv_bps: ltv.map(fixed::Ratio::bps),
+                }
+            })
+            .filter(|report| min_ltv_bps.is_none_or(|min| report.ltv_bps >= Some(min)))
+            .collect();
+        reports.sort_by(|a, b| b.ltv_bps.cmp(&a.ltv_bps));
+        Ok(reports)
     }
 
     pub async fn get_latest_fx_rate(&self, symbol: &str) -> Option<f64> {
-        let row =
+        let row = timed_query(
+            "get_latest_fx_rate",
             sqlx::query("SELECT rates FROM oracle_fx_history ORDER BY timestamp DESC LIMIT 1")
-                .fetch_optional(&self.storage.pg_pool)
-                .await
-                .ok()??;
+                .fetch_optional(&self.storage.pg_pool),
+        )
+        .await
+        .ok()??;
 
         let rates: serde_json::Value = row.get("rates");
         rates.get(symbol).and_then(|v| v.as_f64())
     }
 
     pub async fn get_vaults_from_storage(&self) -> anyhow::Result<Vec<VaultStatus>> {
-        Ok(vec![])
+        self.store.vaults().await
     }
 
     /// [Hole 3.1] Manual or automated trigger for Lightning recovery audit.
@@ -165,7 +1049,9 @@ impl NexusExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::store::InMemoryStore;
     use chrono::Utc;
+    use k256::ecdsa::SigningKey;
 
     #[tokio::test]
     async fn test_execution_request_serialization() {
@@ -174,24 +1060,740 @@ mod tests {
             payload: "data".to_string(),
             timestamp: Utc::now(),
             sender: "sender".to_string(),
+            nonce: 7,
             priority: 1,
+            signature: None,
         };
         let serialized = serde_json::to_string(&req).unwrap();
         let deserialized: ExecutionRequest = serde_json::from_str(&serialized).unwrap();
         assert_eq!(req.tx_id, deserialized.tx_id);
         assert_eq!(deserialized.priority, 1);
+        assert_eq!(deserialized.nonce, 7);
+        assert!(serde_json::from_str::<ExecutionRequest>(
+            r#"{"tx_id":"tx","payload":"","timestamp":"2026-01-01T00:00:00Z","sender":"s"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_request_action_ignores_payload_text() {
+        let contracts = vec!["SP000.vault".to_string()];
+        let mut req = request_at("tx1", Utc::now());
+        assert_eq!(req.action(&contracts), None);
+        req.payload = "please liquidate me".to_string();
+        assert_eq!(req.action(&contracts), None);
+        req.payload = r#"{"tx_type":"contract_call","contract_call":{"contract_id":"SP000.vault","function_name":"liquidate"}}"#
+            .to_string();
+        assert_eq!(req.action(&contracts), Some(ConxianAction::Liquidate));
+        assert_eq!(req.action(&[]), None);
+    }
+
+    #[test]
+    fn test_finality_level_parsing() {
+        assert_eq!(
+            "hard".parse::<FinalityLevel>().unwrap(),
+            FinalityLevel::Hard
+        );
+        assert_eq!(
+            " Soft ".parse::<FinalityLevel>().unwrap(),
+            FinalityLevel::Soft
+        );
+        assert!("final".parse::<FinalityLevel>().is_err());
+        assert_eq!(FinalityLevel::default(), FinalityLevel::Soft);
+    }
+
+    #[tokio::test]
+    async fn test_hard_finality_uses_separate_cache() {
+        let executor = NexusExecutor::new(
+            Storage::for_tests(),
+            rgb::RGBRolloutMode::Disabled,
+            std::collections::HashSet::new(),
+        )
+        .with_required_finality(FinalityLevel::Hard);
+        assert_eq!(executor.required_finality, FinalityLevel::Hard);
+
+        let soft_time = Utc::now();
+        let hard_time = soft_time - chrono::Duration::seconds(60);
+        executor.latest_event_time_cache.set(soft_time);
+        executor.latest_hard_event_time_cache.set(hard_time);
+
+        let request = signed_by(
+            "sender",
+            ExecutionRequest {
+                tx_id: "tx-final".to_string(),
+                payload: "data".to_string(),
+                timestamp: soft_time - chrono::Duration::seconds(30),
+                sender: String::new(),
+                nonce: 1,
+                priority: 0,
+                signature: None,
+            },
+        );
+
+        assert!(executor.validate_transaction(&request).await.unwrap());
+        assert!(!executor
+            .validate_transaction_with_finality(&request, FinalityLevel::Soft)
+            .await
+            .unwrap());
+    }
+
+    #[test]
+    fn test_event_time_cache_advances_on_write_and_expires() {
+        let t0 = Utc::now();
+        let cache = EventTimeCache::default();
+        cache.advance(t0);
+        assert_eq!(cache.get(), None);
+
+        cache.set(t0);
+        cache.advance(t0 - chrono::Duration::seconds(1));
+        assert_eq!(cache.get(), Some(t0));
+        let t1 = t0 + chrono::Duration::seconds(1);
+        cache.advance(t1);
+        assert_eq!(cache.get(), Some(t1));
+
+        let stale = EventTimeCache(Mutex::new(Some((
+            t1,
+            Instant::now() - EVENT_TIME_CACHE_TTL,
+        ))));
+        assert_eq!(stale.get(), None);
+    }
+
+    fn in_memory_executor(store: Arc<InMemoryStore>) -> NexusExecutor {
+        NexusExecutor::new(
+            Storage::for_tests(),
+            rgb::RGBRolloutMode::Disabled,
+            std::collections::HashSet::new(),
+        )
+        .with_store(store)
+    }
+
+    /// Test signer standing in for the sender `name`.
+    fn signer(name: &str) -> StacksSigner {
+        StacksSigner::new(SigningKey::from_slice(&Sha256::digest(name.as_bytes())).unwrap())
+    }
+
+    /// Address `name`'s test signer sends from.
+    fn address(name: &str) -> String {
+        signer(name).address(true).to_string()
+    }
+
+    /// `request` sent from `name`'s address and signed by `name`.
+    fn signed_by(name: &str, request: ExecutionRequest) -> ExecutionRequest {
+        ExecutionRequest {
+            sender: address(name),
+            ..request
+        }
+        .sign(&signer(name))
+        .unwrap()
+    }
+
+    /// Later requests carry higher nonces, so only the timing checks decide.
+    fn request_at(tx_id: &str, timestamp: DateTime<Utc>) -> ExecutionRequest {
+        signed_by(
+            "sender",
+            ExecutionRequest {
+                tx_id: tx_id.to_string(),
+                payload: "data".to_string(),
+                timestamp,
+                sender: String::new(),
+                nonce: timestamp.timestamp_micros() as u64,
+                priority: 0,
+                signature: None,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_front_running_request_is_rejected() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store.clone());
+        let now = Utc::now();
+
+        assert_eq!(
+            executor.submit(request_at("tx1", now)).await.unwrap(),
+            "tx1"
+        );
+
+        let earlier = request_at("tx0", now - chrono::Duration::seconds(1));
+        assert!(!executor.validate_transaction(&earlier).await.unwrap());
+        assert!(executor.submit(earlier).await.is_err());
+
+        let later = request_at("tx2", now + chrono::Duration::seconds(1));
+        assert!(executor.validate_transaction(&later).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_hard_finality_ignores_unfinalized_executions() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store.clone());
+        let now = Utc::now();
+        executor.submit(request_at("tx1", now)).await.unwrap();
+
+        let earlier = request_at("tx0", now - chrono::Duration::seconds(1));
+        assert!(executor
+            .validate_transaction_with_finality(&earlier, FinalityLevel::Hard)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_submit_blocked_in_safety_mode() {
+        let store = Arc::new(InMemoryStore::new());
+        store
+            .activate_safety_mode(Some(5), crate::safety::SafetyTrigger::Drift, None)
+            .await
+            .unwrap();
+        let executor = in_memory_executor(store);
+
+        let err = executor
+            .submit(request_at("tx1", Utc::now()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Safety Mode"));
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_outcomes_by_reason() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store.clone());
+        let t0 = Utc::now();
+
+        executor.submit(request_at("tx-1", t0)).await.unwrap();
+        let err = executor
+            .submit(request_at("tx-2", t0 - chrono::Duration::seconds(1)))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExecutionRejected>().unwrap().reason,
+            RejectReason::FrontRunning
+        );
+
+        store
+            .activate_safety_mode(None, crate::safety::SafetyTrigger::ManualDrill, None)
+            .await
+            .unwrap();
+        let err = executor
+            .submit(request_at("tx-3", t0 + chrono::Duration::seconds(1)))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExecutionRejected>().unwrap().reason,
+            RejectReason::SafetyMode
+        );
+
+        let stats = executor.stats().await.unwrap();
+        assert_eq!(stats.accepted, 1);
+        assert_eq!(stats.rejected, 2);
+        assert_eq!(stats.rejected_by_reason["front_running"], 1);
+        assert_eq!(stats.rejected_by_reason["safety_mode"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_nonce_is_rejected() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store.clone());
+        let t0 = Utc::now();
+        let with_nonce = |tx_id: &str, secs: i64, sender: &str, nonce: u64| {
+            signed_by(
+                sender,
+                ExecutionRequest {
+                    nonce,
+                    ..request_at(tx_id, t0 + chrono::Duration::seconds(secs))
+                },
+            )
+        };
+
+        executor
+            .submit(with_nonce("tx-1", 0, "alice", 5))
+            .await
+            .unwrap();
+        // A replay with a fresh timestamp passes FSOC but not the nonce check.
+        let replay = with_nonce("tx-1", 1, "alice", 5);
+        assert!(!executor.validate_transaction(&replay).await.unwrap());
+        let err = executor.submit(replay).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExecutionRejected>().unwrap().reason,
+            RejectReason::StaleNonce
+        );
+        assert!(executor
+            .submit(with_nonce("tx-2", 2, "alice", 4))
+            .await
+            .is_err());
+
+        // Validation alone does not consume the nonce.
+        let next = with_nonce("tx-3", 3, "alice", 6);
+        assert!(executor.validate_transaction(&next).await.unwrap());
+        assert!(executor.validate_transaction(&next).await.unwrap());
+        executor.submit(next).await.unwrap();
+        executor
+            .submit(with_nonce("tx-4", 4, "bob", 1))
+            .await
+            .unwrap();
+
+        assert_eq!(store.last_nonce(&address("alice")).await.unwrap(), Some(6));
+        assert_eq!(store.last_nonce(&address("bob")).await.unwrap(), Some(1));
+        assert_eq!(
+            store.events().last(),
+            Some(&NexusEvent::ExecutionSequenced {
+                tx_id: "tx-4".to_string(),
+                sender: address("bob"),
+                nonce: 1,
+            })
+        );
+        let stats = executor.stats().await.unwrap();
+        assert_eq!(stats.rejected_by_reason["stale_nonce"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_off_chain_burst_is_rate_limited_without_db_rows() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor =
+            in_memory_executor(store.clone()).with_sender_rate_limit(10, Duration::from_secs(60));
+        let t0 = Utc::now();
+
+        let mut reasons = Vec::new();
+        for i in 0..20 {
+            let request = signed_by(
+                "sender",
+                ExecutionRequest {
+                    nonce: i + 1,
+                    ..request_at(
+                        &format!("tx-{}", i),
+                        t0 + chrono::Duration::seconds(i as i64),
+                    )
+                },
+            );
+            if let Err(e) = executor.submit(request).await {
+                reasons.push(e.downcast_ref::<ExecutionRejected>().unwrap().reason);
+            }
+        }
+        assert_eq!(reasons, vec![RejectReason::OffChainVelocity; 10]);
+        assert_eq!(
+            store
+                .sender_transactions_since(&address("sender"), t0 - chrono::Duration::hours(1))
+                .await
+                .unwrap(),
+            0
+        );
+        // Other senders have their own window.
+        let other = signed_by(
+            "other",
+            request_at("tx-other", t0 + chrono::Duration::seconds(30)),
+        );
+        assert!(executor.validate_transaction(&other).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_on_chain_activity_counts_towards_the_limit() {
+        use crate::storage::repo::{NewBlock, NewTransaction};
+
+        let store = Arc::new(InMemoryStore::new());
+        let transactions: Vec<NewTransaction> = (0..4)
+            .map(|i| {
+                NewTransaction::decoded(
+                    &format!("0xchain{}", i),
+                    "0xb1",
+                    None,
+                    Some(address("sender")),
+                    &[],
+                )
+            })
+            .collect();
+        store
+            .ingest_block(
+                &NewBlock::microblock("0xb1", 1).with_block_time(Some(Utc::now())),
+                &transactions,
+            )
+            .await
+            .unwrap();
+        let executor = in_memory_executor(store).with_sender_rate_limit(5, Duration::from_secs(60));
+        let t0 = Utc::now() + chrono::Duration::seconds(1);
+
+        assert!(executor
+            .validate_transaction(&request_at("tx-1", t0))
+            .await
+            .unwrap());
+        let request = request_at("tx-2", t0);
+        let submitter = submitter(None, &request);
+        assert_eq!(
+            executor
+                .check_sender_velocity(&request, &submitter)
+                .await
+                .unwrap(),
+            None
+        );
+        executor.submit(request).await.unwrap();
+        assert_eq!(
+            executor
+                .check_sender_velocity(&request_at("tx-3", t0), &submitter)
+                .await
+                .unwrap(),
+            Some(RejectReason::OnChainVelocity)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_accepted_requests_count_towards_the_limit() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store).with_sender_rate_limit(2, Duration::from_secs(60));
+        let t0 = Utc::now();
+        let at = |i: i64, nonce: u64| {
+            signed_by(
+                "sender",
+                ExecutionRequest {
+                    nonce,
+                    ..request_at(&format!("tx-{}", i), t0 + chrono::Duration::seconds(i))
+                },
+            )
+        };
+
+        executor.submit(at(0, 1)).await.unwrap();
+        for i in 1..5 {
+            let err = executor.submit(at(i, 1)).await.unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<ExecutionRejected>().unwrap().reason,
+                RejectReason::StaleNonce
+            );
+        }
+        executor.submit(at(5, 2)).await.unwrap();
+        let err = executor.submit(at(6, 3)).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExecutionRejected>().unwrap().reason,
+            RejectReason::OffChainVelocity
+        );
+    }
+
+    #[tokio::test]
+    async fn test_velocity_follows_the_authenticated_caller() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store).with_sender_rate_limit(1, Duration::from_secs(60));
+        let t0 = Utc::now();
+        let from = |i: i64, sender: &str| ExecutionRequest {
+            sender: sender.to_string(),
+            signature: None,
+            ..request_at(&format!("tx-{}", i), t0 + chrono::Duration::seconds(i))
+        };
+
+        // Rotating the claimed sender does not reset the caller's window.
+        executor.submit_as(Some("k1"), from(0, "a")).await.unwrap();
+        let err = executor
+            .submit_as(Some("k1"), from(1, "b"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExecutionRejected>().unwrap().reason,
+            RejectReason::OffChainVelocity
+        );
+        // Another caller has its own window, even for the same sender.
+        executor.submit_as(Some("k2"), from(2, "c")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_only_the_sender_can_use_up_its_nonces() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor = in_memory_executor(store.clone());
+        let t0 = Utc::now();
+        let alice = |i: i64, nonce: u64| ExecutionRequest {
+            sender: address("alice"),
+            nonce,
+            signature: None,
+            ..request_at(&format!("tx-{}", i), t0 + chrono::Duration::seconds(i))
+        };
+        let reason = |e: anyhow::Error| e.downcast_ref::<ExecutionRejected>().unwrap().reason;
+
+        // Neither an anonymous unsigned request nor one signed by another key is
+        // taken as alice's.
+        let err = executor.submit(alice(0, u64::MAX)).await.unwrap_err();
+        assert_eq!(reason(err), RejectReason::InvalidSignature);
+        let forged = ExecutionRequest {
+            sender: address("alice"),
+            ..signed_by("mallory", alice(1, u64::MAX))
+        };
+        assert!(!forged.verify_signature());
+        let err = executor.submit(forged).await.unwrap_err();
+        assert_eq!(reason(err), RejectReason::InvalidSignature);
+        // An API key's unsigned requests only use up nonces kept for that key.
+        executor
+            .submit_as(Some("k1"), alice(2, u64::MAX))
+            .await
+            .unwrap();
+        assert_eq!(store.last_nonce(&address("alice")).await.unwrap(), None);
+
+        executor
+            .submit(signed_by("alice", alice(3, 1)))
+            .await
+            .unwrap();
+        assert_eq!(store.last_nonce(&address("alice")).await.unwrap(), Some(1));
+        // The signature covers the nonce.
+        let tampered = ExecutionRequest {
+            nonce: 2,
+            ..signed_by("alice", alice(4, 1))
+        };
+        let err = executor.submit(tampered).await.unwrap_err();
+        assert_eq!(reason(err), RejectReason::InvalidSignature);
+        assert_eq!(
+            executor.stats().await.unwrap().rejected_by_reason["invalid_signature"],
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unrecorded_execution_keeps_its_nonce() {
+        let store = Arc::new(InMemoryStore::new());
+        let executor =
+            in_memory_executor(store.clone()).with_sender_rate_limit(1, Duration::from_secs(60));
+        let request = request_at("tx-1", Utc::now());
+
+        store.fail_next_executions(1);
+        let err = executor.submit(request.clone()).await.unwrap_err();
+        assert!(!err.is::<ExecutionRejected>());
+        assert_eq!(executor.stats().await.unwrap().accepted, 0);
+
+        // The retry gets the same nonce and the window slot the failed attempt held.
+        assert_eq!(executor.submit(request).await.unwrap(), "tx-1");
+        assert_eq!(executor.stats().await.unwrap().accepted, 1);
+    }
+
+    #[test]
+    fn test_stats_from_counters_lists_every_reason() {
+        let stats = ExecutorStats::from_counters(&HashMap::from([
+            ("accepted".to_string(), 5),
+            ("rejected:front_running".to_string(), 2),
+            ("rejected:spam".to_string(), 1),
+        ]));
+        assert_eq!(stats.accepted, 5);
+        assert_eq!(stats.rejected, 3);
+        assert_eq!(stats.rejected_by_reason["safety_mode"], 0);
+        assert_eq!(stats.rejected_by_reason["spam"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_vaults_read_through_store() {
+        let store = Arc::new(InMemoryStore::new());
+        store.set_vaults(vec![VaultStatus {
+            vault_id: "v1".to_string(),
+            collateral_type: "STX".to_string(),
+            collateral_amount: 1000u64.into(),
+            debt_amount: 500u64.into(),
+            ltv_ratio: Some(fixed::Ratio::from_bps(5_000)),
+        }]);
+        let executor = in_memory_executor(store);
+        let vaults = executor.get_vaults_from_storage().await.unwrap();
+        assert_eq!(vaults.len(), 1);
+        assert_eq!(vaults[0].vault_id, "v1");
+    }
+
+    #[tokio::test]
+    async fn test_rebalance_uses_oracle_priced_ltv() {
+        let store = Arc::new(InMemoryStore::new());
+        let vault = |id: &str, collateral_type: &str, debt: u64, cached_bps: u64| VaultStatus {
+            vault_id: id.to_string(),
+            collateral_type: collateral_type.to_string(),
+            collateral_amount: 100_000_000u64.into(),
+            debt_amount: debt.into(),
+            ltv_ratio: Some(fixed::Ratio::from_bps(cached_bps)),
+        };
+        store.set_vaults(vec![
+            // Cached as healthy, but 90% at $60,000.
+            vault("understated", "BTC", 54_000_000_000, 1_000),
+            // Cached as critical, but 50%.
+            vault("overstated", "BTC", 30_000_000_000, 9_500),
+            // No STX price: skipped rather than treated as healthy or due.
+            vault("unpriced", "STX", 1, 9_900),
+        ]);
+        store.set_collateral_prices(BTreeMap::from([("BTC".to_string(), 60_000.0)]));
+        let executor = in_memory_executor(store);
+        let due = executor.execute_rebalance().await.unwrap();
+        assert_eq!(due_ids(&due), vec!["understated"]);
+        // Back to 70%, 10 points under the threshold, without moving collateral.
+        assert_eq!(
+            due[0].instruction.target_debt,
+            fixed::Amount::new(42_000_000_000)
+        );
+        assert_eq!(
+            due[0].instruction.target_collateral,
+            fixed::Amount::new(100_000_000)
+        );
+        assert_eq!(due[0].instruction.nonce, 1);
+        assert_eq!(due[0].signature, None);
+
+        // Every cycle issues fresh nonces, from a counter clients cannot reach.
+        assert!(executor
+            .store
+            .claim_nonce(rebalance::LEGACY_REBALANCE_NONCE_SENDER, u64::MAX)
+            .await
+            .unwrap());
+        let due = executor.execute_rebalance().await.unwrap();
+        assert_eq!(due[0].instruction.nonce, 2);
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_rebalance_cycle_is_skipped() {
+        let store = Arc::new(InMemoryStore::new());
+        store.set_vaults(vec![VaultStatus {
+            vault_id: "due".to_string(),
+            collateral_type: "BTC".to_string(),
+            collateral_amount: 100_000_000u64.into(),
+            debt_amount: 54_000_000_000u64.into(),
+            ltv_ratio: Some(fixed::Ratio::ZERO),
+        }]);
+        store.set_collateral_prices(BTreeMap::from([("BTC".to_string(), 60_000.0)]));
+        let executor = in_memory_executor(store.clone());
+
+        // A cycle running on this node.
+        let running = executor
+            .rebalance_lock
+            .try_acquire(&executor.store)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(executor.execute_rebalance().await.unwrap().is_empty());
+        running.release().await;
+
+        // A cycle running on another instance.
+        store
+            .try_acquire_lock(
+                rebalance::REBALANCE_CYCLE_LOCK,
+                "other-node",
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+        assert!(executor.execute_rebalance().await.unwrap().is_empty());
+        store
+            .release_lock(rebalance::REBALANCE_CYCLE_LOCK, "other-node")
+            .await
+            .unwrap();
+        let due = executor.execute_rebalance().await.unwrap();
+        assert_eq!(due_ids(&due), vec!["due"]);
+        // No instruction nonce was claimed by the skipped cycles.
+        assert_eq!(due[0].instruction.nonce, 1);
+    }
+
+    #[tokio::test]
+    async fn test_vault_reports_flag_health_and_filter_by_ltv() {
+        let store = Arc::new(InMemoryStore::new());
+        let vault = |id: &str, collateral_type: &str, debt: u64| VaultStatus {
+            vault_id: id.to_string(),
+            collateral_type: collateral_type.to_string(),
+            collateral_amount: 100_000_000u64.into(),
+            debt_amount: debt.into(),
+            ltv_ratio: Some(fixed::Ratio::ZERO),
+        };
+        // 50%, 75% and 90% of 1 BTC at $60,000.
+        store.set_vaults(vec![
+            vault("healthy", "BTC", 30_000_000_000),
+            vault("unpriced", "STX", 1),
+            vault("due", "BTC", 54_000_000_000),
+            vault("at-risk", "BTC", 45_000_000_000),
+        ]);
+        store.set_collateral_prices(BTreeMap::from([("BTC".to_string(), 60_000.0)]));
+        let executor = in_memory_executor(store);
+
+        let reports = executor.vault_reports(None).await.unwrap();
+        let summary: Vec<(&str, Option<u64>, ltv::VaultHealth)> = reports
+            .iter()
+            .map(|r| (r.vault.vault_id.as_str(), r.ltv_bps, r.health))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("due", Some(9_000), ltv::VaultHealth::Rebalancing),
+                ("at-risk", Some(7_500), ltv::VaultHealth::AtRisk),
+                ("healthy", Some(5_000), ltv::VaultHealth::Healthy),
+                ("unpriced", None, ltv::VaultHealth::Unpriced),
+            ]
+        );
+
+        let risky = executor.vault_reports(Some(7_500)).await.unwrap();
+        assert_eq!(risky.len(), 2);
+        assert!(risky.iter().all(|r| r.ltv_bps >= Some(7_500)));
+    }
+
+    fn due_ids(due: &[rebalance::SignedRebalance]) -> Vec<&str> {
+        due.iter()
+            .map(|d| d.instruction.vault_id.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reload_changes_rebalance_threshold_without_restart() {
+        use crate::config::dynamic::DynamicConfig;
+
+        let store = Arc::new(InMemoryStore::new());
+        store.set_vaults(vec![VaultStatus {
+            vault_id: "v1".to_string(),
+            collateral_type: "BTC".to_string(),
+            collateral_amount: 100_000_000u64.into(),
+            // 70% at $60,000.
+            debt_amount: 42_000_000_000u64.into(),
+            ltv_ratio: Some(fixed::Ratio::from_bps(7_000)),
+        }]);
+        store.set_collateral_prices(BTreeMap::from([("BTC".to_string(), 60_000.0)]));
+        let dynamic = DynamicConfigHandle::default();
+        let executor = in_memory_executor(store).with_dynamic_config(dynamic.clone());
+        assert!(executor.execute_rebalance().await.unwrap().is_empty());
+
+        let lowered = DynamicConfig {
+            rebalance_ltv_threshold_bps: fixed::Ratio::from_bps(6_500),
+            ..DynamicConfig::default()
+        };
+        assert_eq!(
+            dynamic.apply(lowered).unwrap(),
+            vec!["rebalance_ltv_threshold_bps: 8000 -> 6500"]
+        );
+        assert_eq!(
+            due_ids(&executor.execute_rebalance().await.unwrap()),
+            vec!["v1"]
+        );
+
+        // A bad reload is rejected wholesale and the executor keeps the old threshold.
+        let invalid = DynamicConfig {
+            rebalance_ltv_threshold_bps: fixed::Ratio::from_bps(9_000),
+            rust_log: "nexus=notalevel".to_string(),
+            ..DynamicConfig::default()
+        };
+        assert!(dynamic.apply(invalid).is_err());
+        assert_eq!(
+            dynamic.get().rebalance_ltv_threshold_bps,
+            fixed::Ratio::from_bps(6_500)
+        );
+        assert_eq!(
+            due_ids(&executor.execute_rebalance().await.unwrap()),
+            vec!["v1"]
+        );
     }
 
     #[test]
     fn test_vault_status_serialization() {
         let v = VaultStatus {
             vault_id: "v1".to_string(),
-            collateral_amount: 1000,
-            debt_amount: 800,
-            ltv_ratio: 0.8,
+            collateral_type: "BTC".to_string(),
+            collateral_amount: fixed::Amount::new(u64::MAX as u128 + 1),
+            debt_amount: 800u64.into(),
+            ltv_ratio: Some(fixed::Ratio::from_bps(8_000)),
         };
         let s = serde_json::to_string(&v).unwrap();
+        assert!(s.contains(r#""collateral_amount":"18446744073709551616""#));
+        assert!(s.contains(r#""ltv_ratio":0.8"#));
         let v2: VaultStatus = serde_json::from_str(&s).unwrap();
         assert_eq!(v.vault_id, v2.vault_id);
+        assert_eq!(v2.collateral_amount, v.collateral_amount);
+        assert_eq!(v2.ltv_ratio, v.ltv_ratio);
+
+        // Entries cached by older writers carry numbers.
+        let legacy: VaultStatus = serde_json::from_str(
+            r#"{"vault_id":"v1","collateral_amount":1000,"debt_amount":800,"ltv_ratio":0.8}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.debt_amount, fixed::Amount::new(800));
+        assert_eq!(legacy.ltv_ratio, Some(fixed::Ratio::from_bps(8_000)));
+
+        let unpriced = VaultStatus {
+            ltv_ratio: None,
+            ..v
+        };
+        let s = serde_json::to_string(&unpriced).unwrap();
+        assert!(s.contains(r#""ltv_ratio":null"#));
     }
 }
@@ -0,0 +1,228 @@
+//! Bounded queue in front of the sequencer. Submissions take a place in line and are
+//! sequenced one at a time in arrival order; when the line is full they are refused
+//! with the current depth and an estimate of how long it takes to drain, and an entry
+//! that waited longer than the maximum age is expired instead of sequenced, so a flood
+//! of `/v1/submit` calls degrades into fast rejections rather than unbounded memory.
+
+use prometheus::{opts, register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, MutexGuard};
+
+lazy_static::lazy_static! {
+    static ref QUEUE_DEPTH: IntGauge = register_int_gauge!(opts!(
+        "nexus_executor_queue_depth",
+        "Executions waiting for or being sequenced"
+    ))
+    .unwrap();
+    static ref ENQUEUED: IntCounter = register_int_counter!(opts!(
+        "nexus_executor_enqueued_total",
+        "Executions admitted to the sequencer queue"
+    ))
+    .unwrap();
+    static ref DRAINED: IntCounter = register_int_counter!(opts!(
+        "nexus_executor_drained_total",
+        "Executions that left the sequencer queue, sequenced or expired"
+    ))
+    .unwrap();
+}
+
+/// Weight of the newest sample in the moving average of sequencing time, in percent.
+const SERVICE_TIME_WEIGHT_PCT: u64 = 20;
+
+/// Why a submission did not get its turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueError {
+    /// `depth` executions were already queued.
+    Full {
+        depth: usize,
+        estimated_drain: Duration,
+    },
+    /// Waited `waited` for its turn, longer than the maximum age.
+    Expired { waited: Duration },
+}
+
+impl fmt::Display for QueueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full {
+                depth,
+                estimated_drain,
+            } => write!(
+                f,
+                "Execution queue is full ({} queued, about {}s to drain)",
+                depth,
+                estimated_drain.as_secs().max(1)
+            ),
+            Self::Expired { waited } => write!(
+                f,
+                "Execution expired after {}ms in the queue",
+                waited.as_millis()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueueError {}
+
+/// Coarse queue pressure for `/v1/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backlog {
+    Idle,
+    /// Below half the maximum depth.
+    Normal,
+    High,
+    /// Submissions are being refused.
+    Full,
+}
+
+pub struct ExecutionQueue {
+    max_depth: usize,
+    max_age: Duration,
+    depth: AtomicUsize,
+    /// Held for the duration of one sequencing decision; tokio's mutex is FIFO.
+    turn: Mutex<()>,
+    /// Moving average of time spent holding the turn, in microseconds.
+    service_micros: AtomicU64,
+}
+
+impl ExecutionQueue {
+    pub fn new(max_depth: usize, max_age: Duration) -> Self {
+        Self {
+            max_depth: max_depth.max(1),
+            max_age,
+            depth: AtomicUsize::new(0),
+            turn: Mutex::new(()),
+            service_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Time to sequence everything queued now at the recent average pace.
+    pub fn estimated_drain(&self) -> Duration {
+        Duration::from_micros(self.service_micros.load(Ordering::Relaxed))
+            .saturating_mul(self.depth() as u32)
+    }
+
+    pub fn backlog(&self) -> Backlog {
+        match self.depth() {
+            0 => Backlog::Idle,
+            depth if depth >= self.max_depth => Backlog::Full,
+            depth if depth * 2 >= self.max_depth => Backlog::High,
+            _ => Backlog::Normal,
+        }
+    }
+
+    /// Takes a place in line and waits for the turn. Refuses at once when the queue is
+    /// full; expires the entry when its turn comes after the maximum age.
+    pub async fn enter(&self) -> Result<QueueTurn<'_>, QueueError> {
+        let admitted = self
+            .depth
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |depth| {
+                (depth < self.max_depth).then_some(depth + 1)
+            });
+        if let Err(depth) = admitted {
+            return Err(QueueError::Full {
+                depth,
+                estimated_drain: self.estimated_drain(),
+            });
+        }
+        ENQUEUED.inc();
+        QUEUE_DEPTH.inc();
+        // Leaves the queue on every path, including a caller that gives up waiting.
+        let place = Place { queue: self };
+
+        let enqueued_at = Instant::now();
+        let guard = self.turn.lock().await;
+        let waited = enqueued_at.elapsed();
+        if waited > self.max_age {
+            return Err(QueueError::Expired { waited });
+        }
+        Ok(QueueTurn {
+            place,
+            _guard: guard,
+            started: Instant::now(),
+        })
+    }
+
+    fn record_service_time(&self, elapsed: Duration) {
+        let sample = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let _ = self
+            .service_micros
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |avg| {
+                Some(if avg == 0 {
+                    sample
+                } else {
+                    (avg * (100 - SERVICE_TIME_WEIGHT_PCT) + sample * SERVICE_TIME_WEIGHT_PCT) / 100
+                })
+            });
+    }
+}
+
+struct Place<'a> {
+    queue: &'a ExecutionQueue,
+}
+
+impl Drop for Place<'_> {
+    fn drop(&mut self) {
+        self.queue.depth.fetch_sub(1, Ordering::SeqCst);
+        QUEUE_DEPTH.dec();
+        DRAINED.inc();
+    }
+}
+
+/// The sequencer's turn; the next queued execution proceeds once this is dropped.
+pub struct QueueTurn<'a> {
+    place: Place<'a>,
+    _guard: MutexGuard<'a, ()>,
+    started: Instant,
+}
+
+impl Drop for QueueTurn<'_> {
+    fn drop(&mut self) {
+        self.place.queue.record_service_time(self.started.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_full_queue_refuses_until_a_turn_ends() {
+        let queue = ExecutionQueue::new(1, Duration::from_secs(30));
+        assert_eq!(queue.backlog(), Backlog::Idle);
+        let turn = queue.enter().await.unwrap();
+        assert_eq!(queue.backlog(), Backlog::Full);
+        assert!(matches!(
+            queue.enter().await,
+            Err(QueueError::Full { depth: 1, .. })
+        ));
+        drop(turn);
+        assert_eq!(queue.depth(), 0);
+        assert!(queue.enter().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_entry_waiting_past_max_age_expires() {
+        let queue = ExecutionQueue::new(4, Duration::from_millis(20));
+        let turn = queue.enter().await.unwrap();
+        let waiting = queue.enter();
+        tokio::pin!(waiting);
+        assert!(futures_util::poll!(&mut waiting).is_pending());
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        drop(turn);
+        assert!(matches!(waiting.await, Err(QueueError::Expired { .. })));
+        assert_eq!(queue.depth(), 0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Signed rebalance instructions. Each vault due for rebalancing gets an instruction
+//! carrying its target position, a nonce and a timestamp. The signature is SIP-018
+//! structured data over the instruction as a Clarity tuple, under a domain naming this
+//! format and the chain, so it cannot be replayed, re-targeted or moved across networks.
+
+use crate::executor::fixed::{self, Amount, Ratio};
+use crate::executor::{ltv, VaultStatus};
+use crate::stacks::{sip018, ClarityValue, StacksSigner};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Format version of [`RebalanceInstruction`]; the SIP-018 domain version.
+pub const REBALANCE_INSTRUCTION_VERSION: u32 = 3;
+/// SIP-018 domain name of rebalance instructions.
+pub const REBALANCE_DOMAIN_NAME: &str = "conxian-nexus-rebalance";
+/// Rebalancing brings a vault this far below the threshold that triggered it.
+pub const REBALANCE_TARGET_MARGIN_BPS: u64 = 1_000;
+/// Sender the instruction nonces were claimed under in the executor nonce store before
+/// they got their own counter; the counter starts from it.
+pub const LEGACY_REBALANCE_NONCE_SENDER: &str = "nexus:rebalance";
+/// Name of the lock held for the length of a rebalance cycle.
+pub const REBALANCE_CYCLE_LOCK: &str = "rebalance";
+/// How often the scheduler starts a rebalance cycle.
+pub const REBALANCE_INTERVAL: Duration = Duration::from_secs(60);
+/// How long the cycle lock outlives its last renewal: two scheduler intervals, so a
+/// node that dies mid-cycle holds up at most one cycle elsewhere.
+pub const REBALANCE_CYCLE_LOCK_TTL: Duration =
+    Duration::from_secs(2 * REBALANCE_INTERVAL.as_secs());
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RebalanceInstruction {
+    pub version: u32,
+    /// Chain id of the SIP-018 domain the instruction is signed under.
+    pub chain_id: u32,
+    pub vault_id: String,
+    pub collateral_type: String,
+    /// In the collateral's base unit; collateral is left in place.
+    pub target_collateral: Amount,
+    /// In micro-USD: the debt at which the vault sits at `target_ltv_bps`.
+    pub target_debt: Amount,
+    #[serde(with = "fixed::bps")]
+    pub target_ltv_bps: Ratio,
+    /// Strictly increasing across all instructions this node issues.
+    pub nonce: u64,
+    /// Unix seconds at which the instruction was issued.
+    pub timestamp: i64,
+}
+
+impl RebalanceInstruction {
+    /// Instruction bringing `vault` to `target_ltv` by repaying debt, with `price`
+    /// fixed-point as in [`ltv::compute_ltv`]. `None` when the collateral type is
+    /// unknown or the target debt does not fit.
+    pub fn for_vault(
+        vault: &VaultStatus,
+        price: u128,
+        target_ltv: Ratio,
+        chain_id: u32,
+        nonce: u64,
+        timestamp: i64,
+    ) -> Option<Self> {
+        let decimals = ltv::collateral_decimals(&vault.collateral_type)?;
+        let target_debt = ltv::debt_at_ltv(vault.collateral_amount, decimals, price, target_ltv)?;
+        Some(Self {
+            version: REBALANCE_INSTRUCTION_VERSION,
+            chain_id,
+            vault_id: vault.vault_id.clone(),
+            collateral_type: vault.collateral_type.clone(),
+            target_collateral: vault.collateral_amount,
+            target_debt: target_debt.min(vault.debt_amount),
+            target_ltv_bps: target_ltv,
+            nonce,
+            timestamp,
+        })
+    }
+
+    /// SIP-018 domain: [`REBALANCE_DOMAIN_NAME`] at the instruction's version and chain.
+    pub fn domain(&self) -> ClarityValue {
+        sip018::domain(
+            REBALANCE_DOMAIN_NAME,
+            &self.version.to_string(),
+            self.chain_id,
+        )
+    }
+
+    /// The signed message: every field but the version and chain id, which the domain
+    /// carries, as a Clarity tuple.
+    pub fn message(&self) -> ClarityValue {
+        ClarityValue::tuple([
+            ("vault-id", ClarityValue::StringUtf8(self.vault_id.clone())),
+            (
+                "collateral-type",
+                ClarityValue::StringUtf8(self.collateral_type.clone()),
+            ),
+            (
+                "target-collateral",
+                ClarityValue::UInt(self.target_collateral.units()),
+            ),
+            ("target-debt", ClarityValue::UInt(self.target_debt.units())),
+            (
+                "target-ltv-bps",
+                ClarityValue::UInt(self.target_ltv_bps.bps().into()),
+            ),
+            ("nonce", ClarityValue::UInt(self.nonce.into())),
+            ("timestamp", ClarityValue::Int(self.timestamp.into())),
+        ])
+    }
+
+    /// The SIP-018 structured data hash the signature covers.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        sip018::structured_data_hash(&self.domain(), &self.message())
+    }
+
+    /// Hex [`Self::signing_hash`]; identifies the instruction.
+    pub fn tx_id(&self) -> String {
+        hex::encode(self.signing_hash())
+    }
+
+    pub fn sign(self, signer: Option<&StacksSigner>) -> anyhow::Result<SignedRebalance> {
+        let signature = signer
+            .map(|s| s.sign_structured_data(&self.domain(), &self.message()))
+            .transpose()?;
+        Ok(SignedRebalance {
+            tx_id: self.tx_id(),
+            signature,
+            instruction: self,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRebalance {
+    pub tx_id: String,
+    pub instruction: RebalanceInstruction,
+    /// SIP-018 signature over [`RebalanceInstruction::signing_hash`], as 65-byte
+    /// `r || s || recovery id` hex; `None` when the executor has no rebalance signer.
+    pub signature: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::push::to_fixed_point;
+    use crate::stacks::transaction::CHAIN_ID_MAINNET;
+    use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+
+    fn instruction(nonce: u64) -> RebalanceInstruction {
+        let vault = VaultStatus {
+            vault_id: "v1".to_string(),
+            collateral_type: "BTC".to_string(),
+            collateral_amount: 100_000_000u64.into(),
+            debt_amount: 54_000_000_000u64.into(),
+            ltv_ratio: Some(Ratio::from_bps(9_000)),
+        };
+        let price = to_fixed_point(60_000.0).unwrap();
+        RebalanceInstruction::for_vault(
+            &vault,
+            price,
+            Ratio::from_bps(7_000),
+            CHAIN_ID_MAINNET,
+            nonce,
+            1_751_846_400,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_instruction_targets_lower_ltv() {
+        let instruction = instruction(1);
+        assert_eq!(instruction.target_collateral, Amount::new(100_000_000));
+        assert_eq!(instruction.target_debt, Amount::new(42_000_000_000));
+        let json = serde_json::to_value(&instruction).unwrap();
+        assert_eq!(json["target_debt"], "42000000000");
+        assert_eq!(json["target_ltv_bps"], 7_000);
+        let ClarityValue::Tuple(message) = instruction.message() else {
+            panic!("message is a tuple");
+        };
+        assert_eq!(message["target-debt"], ClarityValue::UInt(42_000_000_000));
+        assert_eq!(message["target-ltv-bps"], ClarityValue::UInt(7_000));
+    }
+
+    #[test]
+    fn test_signature_is_sip018_over_the_instruction() {
+        let key = SigningKey::from_slice(&[5u8; 32]).unwrap();
+        let signer = StacksSigner::new(key.clone());
+        let signed = instruction(1).sign(Some(&signer)).unwrap();
+        assert_eq!(signed.tx_id, signed.instruction.tx_id());
+        let sig = hex::decode(signed.signature.as_deref().unwrap()).unwrap();
+        let recovered = VerifyingKey::recover_from_prehash(
+            &signed.instruction.signing_hash(),
+            &Signature::from_slice(&sig[..64]).unwrap(),
+            RecoveryId::from_byte(sig[64]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(&recovered, key.verifying_key());
+
+        // A new nonce changes the signed hash, so an old signature cannot be replayed.
+        let next = instruction(2).sign(Some(&signer)).unwrap();
+        assert_ne!(next.signature, signed.signature);
+        assert_ne!(next.tx_id, signed.tx_id);
+        // Nor can a mainnet instruction be used on testnet.
+        let mut testnet = instruction(1);
+        testnet.chain_id = crate::stacks::transaction::CHAIN_ID_TESTNET;
+        assert_ne!(testnet.tx_id(), signed.tx_id);
+        assert_eq!(instruction(1).sign(None).unwrap().signature, None);
+    }
+}
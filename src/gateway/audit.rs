@@ -0,0 +1,458 @@
+//! [NEXUS-GW-04] Request audit log and per-service counters for gateway dispatch.
+//! Every dispatched request is recorded with a digest of its full payload so disputes
+//! can be resolved even though only a truncated copy of the payload is stored.
+
+use super::{ServiceError, ServiceResponse};
+use crate::latency::timed_query;
+use crate::storage::Storage;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use prometheus::{
+    histogram_opts, opts, register_histogram_vec, register_int_counter_vec, HistogramVec,
+    IntCounterVec,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Maximum number of bytes of payload/response text kept per audit row.
+pub const MAX_AUDIT_TEXT_BYTES: usize = 2048;
+/// Number of recent latencies retained per service for percentile estimates.
+pub const LATENCY_WINDOW: usize = 1024;
+
+lazy_static::lazy_static! {
+    static ref GATEWAY_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "nexus_gateway_requests_total",
+            "Gateway service requests by service and outcome"
+        ),
+        &["service", "outcome"]
+    )
+    .unwrap();
+
+    static ref GATEWAY_LATENCY: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "nexus_gateway_request_duration_seconds",
+            "Gateway service request latency"
+        ),
+        &["service"]
+    )
+    .unwrap();
+}
+
+/// One audited gateway request/response pair.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GatewayRequestRecord {
+    pub service: String,
+    /// SHA-256 hex digest of the full request payload.
+    pub request_digest: String,
+    pub payload: String,
+    /// `ok`, or the `ServiceError::kind` of the failure.
+    pub outcome: String,
+    pub response: String,
+    pub latency_ms: i64,
+    /// Fingerprint of the caller's API key, never the key itself.
+    pub caller: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl GatewayRequestRecord {
+    pub fn new(
+        service: &str,
+        payload: &str,
+        outcome: &str,
+        response: &str,
+        latency: Duration,
+        caller: Option<String>,
+    ) -> Self {
+        Self {
+            service: service.to_string(),
+            request_digest: request_digest(payload),
+            payload: truncate_utf8(payload, MAX_AUDIT_TEXT_BYTES).to_string(),
+            outcome: outcome.to_string(),
+            response: truncate_utf8(response, MAX_AUDIT_TEXT_BYTES).to_string(),
+            latency_ms: latency.as_millis() as i64,
+            caller,
+            created_at: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.outcome == "ok"
+    }
+}
+
+pub fn request_digest(payload: &str) -> String {
+    hex::encode(Sha256::digest(payload.as_bytes()))
+}
+
+/// Short, non-reversible identifier for an API key.
+pub fn caller_fingerprint(api_key: &str) -> String {
+    hex::encode(&Sha256::digest(api_key.as_bytes())[..8])
+}
+
+/// Truncates to at most `max` bytes without splitting a UTF-8 character.
+pub fn truncate_utf8(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Destination for gateway audit records.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn record(&self, record: &GatewayRequestRecord) -> anyhow::Result<()>;
+
+    /// Most recent records for a service, newest first.
+    async fn recent(&self, service: &str, limit: i64) -> anyhow::Result<Vec<GatewayRequestRecord>>;
+}
+
+/// Audit sink backed by the `gateway_requests` table.
+pub struct PgAuditSink {
+    storage: Arc<Storage>,
+}
+
+impl PgAuditSink {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl AuditSink for PgAuditSink {
+    async fn record(&self, record: &GatewayRequestRecord) -> anyhow::Result<()> {
+        timed_query(
+            "record_gateway_audit",
+            sqlx::query(
+                "INSERT INTO gateway_requests
+             (service, request_digest, payload, outcome, response, latency_ms, caller)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(&record.service)
+            .bind(&record.request_digest)
+            .bind(&record.payload)
+            .bind(&record.outcome)
+            .bind(&record.response)
+            .bind(record.latency_ms)
+            .bind(&record.caller)
+            .execute(&self.storage.pg_pool),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn recent(&self, service: &str, limit: i64) -> anyhow::Result<Vec<GatewayRequestRecord>> {
+        let rows = timed_query("recent_gateway_audit", sqlx::query(
+            "SELECT service, request_digest, payload, outcome, response, latency_ms, caller, created_at
+             FROM gateway_requests
+             WHERE service = $1
+             ORDER BY created_at DESC, id DESC
+             LIMIT $2",
+        )
+        .bind(service)
+        .bind(limit)
+        .fetch_all(&self.storage.pg_pool)).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| GatewayRequestRecord {
+                service: row.get("service"),
+                request_digest: row.get("request_digest"),
+                payload: row.get("payload"),
+                outcome: row.get("outcome"),
+                response: row.get("response"),
+                latency_ms: row.get("latency_ms"),
+                caller: row.get("caller"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+}
+
+/// In-process audit sink, used in tests and when no database is wired.
+#[derive(Default)]
+pub struct MemoryAuditSink {
+    records: Mutex<Vec<GatewayRequestRecord>>,
+}
+
+impl MemoryAuditSink {
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl AuditSink for MemoryAuditSink {
+    async fn record(&self, record: &GatewayRequestRecord) -> anyhow::Result<()> {
+        let mut record = record.clone();
+        record.created_at.get_or_insert_with(Utc::now);
+        self.records.lock().unwrap().push(record);
+        Ok(())
+    }
+
+    async fn recent(&self, service: &str, limit: i64) -> anyhow::Result<Vec<GatewayRequestRecord>> {
+        let records = self.records.lock().unwrap();
+        Ok(records
+            .iter()
+            .rev()
+            .filter(|r| r.service == service)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct ServiceCounters {
+    requests: u64,
+    successes: u64,
+    failures: u64,
+    verified: u64,
+    failed: u64,
+    rejected: u64,
+    last_error: Option<String>,
+    latencies_ms: VecDeque<u64>,
+}
+
+/// Point-in-time counters for one gateway service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceMetricsSnapshot {
+    pub service: String,
+    pub requests: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Requests the service handled, rejections included.
+    #[serde(default)]
+    pub verified: u64,
+    /// Requests that failed upstream or inside the service.
+    #[serde(default)]
+    pub failed: u64,
+    /// Requests rejected as invalid: bad requests and `"verified": false` answers. The
+    /// caller controls these, so they never count as failures.
+    #[serde(default)]
+    pub rejected: u64,
+    /// Error message of the latest failed request. Left out of
+    /// [`GatewayMetrics::telemetry`], since it can echo upstream or request details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// p95 over the last [`LATENCY_WINDOW`] requests.
+    pub p95_latency_ms: u64,
+}
+
+/// Verification totals across services, named as the safety monitor reads them from a
+/// gateway's telemetry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerificationTotals {
+    pub verification_success: u64,
+    pub verification_failure: u64,
+}
+
+/// Body of `GET /v1/services/metrics`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GatewayTelemetry {
+    pub metrics: VerificationTotals,
+    pub services: Vec<ServiceMetricsSnapshot>,
+}
+
+/// How a dispatched request counts towards a service's health.
+enum Verdict {
+    Verified,
+    /// The caller sent something invalid; the service itself is fine.
+    Rejected,
+    /// The service or its upstream failed, with the error message.
+    Failed(String),
+}
+
+fn verdict(result: &Result<ServiceResponse, ServiceError>) -> Verdict {
+    match result {
+        Ok(response) if response.body["verified"] == false => Verdict::Rejected,
+        Ok(_) => Verdict::Verified,
+        Err(ServiceError::BadRequest(_)) => Verdict::Rejected,
+        Err(err) => Verdict::Failed(err.message().to_string()),
+    }
+}
+
+/// Per-service request counters, mirrored into Prometheus.
+#[derive(Default)]
+pub struct GatewayMetrics {
+    services: Mutex<BTreeMap<String, ServiceCounters>>,
+}
+
+impl GatewayMetrics {
+    pub fn observe(
+        &self,
+        service: &str,
+        result: &Result<ServiceResponse, ServiceError>,
+        latency: Duration,
+    ) {
+        let success = result.is_ok();
+        let outcome = if success { "success" } else { "failure" };
+        GATEWAY_REQUESTS
+            .with_label_values(&[service, outcome])
+            .inc();
+        GATEWAY_LATENCY
+            .with_label_values(&[service])
+            .observe(latency.as_secs_f64());
+
+        let mut services = self.services.lock().unwrap();
+        let counters = services.entry(service.to_string()).or_default();
+        counters.requests += 1;
+        if success {
+            counters.successes += 1;
+        } else {
+            counters.failures += 1;
+        }
+        match verdict(result) {
+            Verdict::Verified => counters.verified += 1,
+            Verdict::Rejected => {
+                counters.verified += 1;
+                counters.rejected += 1;
+            }
+            Verdict::Failed(error) => {
+                counters.failed += 1;
+                counters.last_error = Some(error);
+            }
+        }
+        if counters.latencies_ms.len() == LATENCY_WINDOW {
+            counters.latencies_ms.pop_front();
+        }
+        counters.latencies_ms.push_back(latency.as_millis() as u64);
+    }
+
+    pub fn snapshot(&self) -> Vec<ServiceMetricsSnapshot> {
+        let services = self.services.lock().unwrap();
+        services
+            .iter()
+            .map(|(name, c)| ServiceMetricsSnapshot {
+                service: name.clone(),
+                requests: c.requests,
+                successes: c.successes,
+                failures: c.failures,
+                verified: c.verified,
+                failed: c.failed,
+                rejected: c.rejected,
+                last_error: c.last_error.clone(),
+                p95_latency_ms: percentile(c.latencies_ms.iter().copied().collect(), 95),
+            })
+            .collect()
+    }
+
+    pub fn service(&self, service: &str) -> Option<ServiceMetricsSnapshot> {
+        self.snapshot().into_iter().find(|s| s.service == service)
+    }
+
+    /// Totals and per-service counters without error messages, as served by
+    /// `GET /v1/services/metrics`.
+    pub fn telemetry(&self) -> GatewayTelemetry {
+        let mut services = self.snapshot();
+        for service in &mut services {
+            service.last_error = None;
+        }
+        GatewayTelemetry {
+            metrics: VerificationTotals {
+                verification_success: services.iter().map(|s| s.verified).sum(),
+                verification_failure: services.iter().map(|s| s.failed).sum(),
+            },
+            services,
+        }
+    }
+}
+
+/// Nearest-rank percentile; 0 for an empty sample.
+fn percentile(mut samples: Vec<u64>, pct: usize) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    samples.sort_unstable();
+    let rank = (pct * samples.len()).div_ceil(100).max(1);
+    samples[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_utf8_respects_char_boundaries() {
+        assert_eq!(truncate_utf8("hello", 10), "hello");
+        assert_eq!(truncate_utf8("héllo", 2), "h");
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        assert_eq!(percentile(vec![], 95), 0);
+        assert_eq!(percentile((1..=100).collect(), 95), 95);
+        assert_eq!(percentile(vec![7], 95), 7);
+    }
+
+    #[test]
+    fn test_metrics_track_outcomes_per_service() {
+        let metrics = GatewayMetrics::default();
+        let ok = Ok(ServiceResponse::json(
+            serde_json::json!({ "verified": true }),
+        ));
+        let rejected = Ok(ServiceResponse::json(
+            serde_json::json!({ "verified": false, "reason": "SchemaMismatch" }),
+        ));
+        metrics.observe("bisq", &ok, Duration::from_millis(5));
+        metrics.observe(
+            "bisq",
+            &Err(ServiceError::upstream("bisq down")),
+            Duration::from_millis(15),
+        );
+        metrics.observe("rgb", &rejected, Duration::from_millis(1));
+
+        let bisq = metrics.service("bisq").unwrap();
+        assert_eq!((bisq.requests, bisq.successes, bisq.failures), (2, 1, 1));
+        assert_eq!((bisq.verified, bisq.failed), (1, 1));
+        assert_eq!(bisq.last_error.as_deref(), Some("bisq down"));
+        assert_eq!(bisq.p95_latency_ms, 15);
+        assert!(metrics.service("lightning").is_none());
+
+        // Rejections are the caller's doing: counted, but never as failures.
+        metrics.observe(
+            "rgb",
+            &Err(ServiceError::BadRequest("missing asset_id".to_string())),
+            Duration::from_millis(1),
+        );
+        let rgb = metrics.service("rgb").unwrap();
+        assert_eq!((rgb.verified, rgb.failed, rgb.rejected), (2, 0, 2));
+        assert_eq!(rgb.last_error, None);
+        let telemetry = metrics.telemetry();
+        assert_eq!(
+            telemetry.metrics,
+            VerificationTotals {
+                verification_success: 3,
+                verification_failure: 1,
+            }
+        );
+        assert!(telemetry.services.iter().all(|s| s.last_error.is_none()));
+    }
+
+    #[test]
+    fn test_record_digests_full_payload_but_truncates_copy() {
+        let payload = "x".repeat(MAX_AUDIT_TEXT_BYTES + 10);
+        let record = GatewayRequestRecord::new(
+            "bitvm",
+            &payload,
+            "ok",
+            "{}",
+            Duration::from_millis(1),
+            Some(caller_fingerprint("nx_key_abc")),
+        );
+        assert_eq!(record.payload.len(), MAX_AUDIT_TEXT_BYTES);
+        assert_eq!(record.request_digest, request_digest(&payload));
+        assert_eq!(record.caller.as_deref().map(str::len), Some(16));
+    }
+}
@@ -0,0 +1,337 @@
+//! [NEXUS-GW-02] Bisq trade verification against a Bisq daemon HTTP API.
+//! Without `BISQ_API_URL` the service reports trades as unverified rather than implying mediation.
+
+use super::{record_verification, GatewayService, ServiceError, ServiceResponse};
+use crate::storage::Storage;
+use async_trait::async_trait;
+use lib_conxian_core::gateway::{BisqService, ConxianService, ServiceStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Trade phases at or beyond a confirmed deposit; earlier phases are not verifiable.
+pub const VERIFIED_TRADE_PHASES: &[&str] = &[
+    "DEPOSIT_CONFIRMED",
+    "FIAT_SENT",
+    "FIAT_RECEIVED",
+    "PAYOUT_PUBLISHED",
+    "WITHDRAWN",
+];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BisqVerifyRequest {
+    pub trade_id: String,
+    pub amount: u64,
+    #[serde(default)]
+    pub counterparty: Option<String>,
+}
+
+/// Trade details as returned by the Bisq daemon API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BisqTrade {
+    #[serde(alias = "tradeId")]
+    pub trade_id: String,
+    #[serde(alias = "tradeAmountAsLong")]
+    pub amount: u64,
+    #[serde(alias = "phase")]
+    pub state: String,
+    #[serde(alias = "tradingPeerNodeAddress", default)]
+    pub counterparty: Option<String>,
+}
+
+/// Outcome of checking a fetched trade against the caller's claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisqMismatch {
+    Amount,
+    State,
+    Counterparty,
+}
+
+impl BisqMismatch {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Amount => "amount_mismatch",
+            Self::State => "trade_not_confirmed",
+            Self::Counterparty => "counterparty_mismatch",
+        }
+    }
+}
+
+/// Compares a fetched trade with the verification request.
+pub fn check_trade(request: &BisqVerifyRequest, trade: &BisqTrade) -> Result<(), BisqMismatch> {
+    if trade.amount != request.amount {
+        return Err(BisqMismatch::Amount);
+    }
+    if !VERIFIED_TRADE_PHASES.contains(&trade.state.as_str()) {
+        return Err(BisqMismatch::State);
+    }
+    if let Some(expected) = &request.counterparty {
+        if trade.counterparty.as_deref() != Some(expected.as_str()) {
+            return Err(BisqMismatch::Counterparty);
+        }
+    }
+    Ok(())
+}
+
+/// The daemon's URL for `trade_id`, with the id percent-encoded as a single path segment
+/// so it cannot reach another endpoint.
+pub fn trade_url(api_url: &str, trade_id: &str) -> Result<reqwest::Url, ServiceError> {
+    if matches!(trade_id, "." | "..") {
+        return Err(ServiceError::BadRequest("invalid trade_id".to_string()));
+    }
+    let mut url = reqwest::Url::parse(api_url)
+        .map_err(|e| ServiceError::Internal(format!("Invalid BISQ_API_URL: {}", e)))?;
+    url.path_segments_mut()
+        .map_err(|_| ServiceError::Internal("BISQ_API_URL cannot take a path".to_string()))?
+        .pop_if_empty()
+        .extend(["api", "v1", "trades", trade_id]);
+    Ok(url)
+}
+
+/// Bisq gateway service with an optional daemon backend.
+pub struct BisqGateway {
+    api_url: Option<String>,
+    storage: Option<Arc<Storage>>,
+    http_client: reqwest::Client,
+}
+
+impl BisqGateway {
+    pub fn new(api_url: Option<String>, storage: Option<Arc<Storage>>) -> Self {
+        Self {
+            api_url: api_url.map(|u| u.trim_end_matches('/').to_string()),
+            storage,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    async fn fetch_trade(
+        &self,
+        api_url: &str,
+        trade_id: &str,
+    ) -> Result<Option<BisqTrade>, ServiceError> {
+        let url = trade_url(api_url, trade_id)?;
+        let resp = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ServiceError::upstream(format!("Bisq daemon: {}", e)))?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(ServiceError::upstream(format!(
+                "Bisq daemon returned {}",
+                resp.status()
+            )));
+        }
+
+        resp.json::<BisqTrade>()
+            .await
+            .map(Some)
+            .map_err(|e| ServiceError::Internal(format!("Invalid Bisq trade payload: {}", e)))
+    }
+
+    async fn persist(
+        &self,
+        trade_id: &str,
+        verified: bool,
+        reason: Option<&str>,
+        details: &serde_json::Value,
+    ) {
+        if let Some(storage) = &self.storage {
+            if let Err(e) =
+                record_verification(storage, "bisq", trade_id, verified, reason, details).await
+            {
+                tracing::warn!(error = %e, trade_id, "Failed to persist Bisq verification");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl GatewayService for BisqGateway {
+    fn name(&self) -> &'static str {
+        "bisq"
+    }
+
+    fn status(&self) -> ServiceStatus {
+        BisqService.status()
+    }
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError> {
+        let request: BisqVerifyRequest = serde_json::from_str(payload)
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid Bisq request: {}", e)))?;
+        if request.trade_id.trim().is_empty() {
+            return Err(ServiceError::BadRequest("trade_id is required".to_string()));
+        }
+
+        let Some(api_url) = self.api_url.as_deref() else {
+            return Ok(ServiceResponse::json(json!({
+                "trade_id": request.trade_id,
+                "verified": false,
+                "status": "unverified",
+                "reason": "no backend configured",
+            })));
+        };
+
+        let Some(trade) = self.fetch_trade(api_url, &request.trade_id).await? else {
+            let body = json!({
+                "trade_id": request.trade_id,
+                "verified": false,
+                "status": "failed",
+                "reason": "trade_not_found",
+            });
+            self.persist(&request.trade_id, false, Some("trade_not_found"), &body)
+                .await;
+            return Ok(ServiceResponse::json(body));
+        };
+
+        let body = match check_trade(&request, &trade) {
+            Ok(()) => json!({
+                "trade_id": request.trade_id,
+                "verified": true,
+                "status": "verified",
+                "trade": trade,
+            }),
+            Err(mismatch) => json!({
+                "trade_id": request.trade_id,
+                "verified": false,
+                "status": "failed",
+                "reason": mismatch.reason(),
+                "trade": trade,
+            }),
+        };
+        let verified = body["verified"].as_bool().unwrap_or(false);
+        self.persist(&request.trade_id, verified, body["reason"].as_str(), &body)
+            .await;
+        Ok(ServiceResponse::json(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path, http::StatusCode, response::IntoResponse, routing::get, Json, Router,
+    };
+
+    async fn mock_trade(Path(trade_id): Path<String>) -> axum::response::Response {
+        if trade_id != "trade-1" {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        Json(json!({
+            "tradeId": "trade-1",
+            "tradeAmountAsLong": 150_000,
+            "phase": "PAYOUT_PUBLISHED",
+            "tradingPeerNodeAddress": "peer.onion:9999",
+        }))
+        .into_response()
+    }
+
+    async fn spawn_mock_daemon() -> String {
+        let app = Router::new().route("/api/v1/trades/{trade_id}", get(mock_trade));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    async fn verify(gateway: &BisqGateway, payload: serde_json::Value) -> serde_json::Value {
+        gateway
+            .handle_request(&payload.to_string())
+            .await
+            .unwrap()
+            .body
+    }
+
+    #[tokio::test]
+    async fn test_matching_trade_is_verified() {
+        let gateway = BisqGateway::new(Some(spawn_mock_daemon().await), None);
+        let body = verify(
+            &gateway,
+            json!({"trade_id": "trade-1", "amount": 150_000, "counterparty": "peer.onion:9999"}),
+        )
+        .await;
+        assert_eq!(body["verified"], true);
+        assert_eq!(body["trade"]["state"], "PAYOUT_PUBLISHED");
+    }
+
+    #[tokio::test]
+    async fn test_amount_mismatch_is_structured_failure() {
+        let gateway = BisqGateway::new(Some(spawn_mock_daemon().await), None);
+        let body = verify(&gateway, json!({"trade_id": "trade-1", "amount": 1})).await;
+        assert_eq!(body["verified"], false);
+        assert_eq!(body["reason"], "amount_mismatch");
+    }
+
+    #[tokio::test]
+    async fn test_missing_trade_reports_not_found() {
+        let gateway = BisqGateway::new(Some(spawn_mock_daemon().await), None);
+        let body = verify(&gateway, json!({"trade_id": "trade-404", "amount": 1})).await;
+        assert_eq!(body["verified"], false);
+        assert_eq!(body["reason"], "trade_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_without_backend_reports_unverified() {
+        let gateway = BisqGateway::new(None, None);
+        let body = verify(&gateway, json!({"trade_id": "trade-1", "amount": 1})).await;
+        assert_eq!(body["status"], "unverified");
+        assert_eq!(body["reason"], "no backend configured");
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_backend_is_upstream_unavailable() {
+        let gateway = BisqGateway::new(Some("http://127.0.0.1:1".to_string()), None);
+        let err = gateway
+            .handle_request(r#"{"trade_id": "trade-1", "amount": 1}"#)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "upstream_unavailable");
+    }
+
+    #[test]
+    fn test_trade_url_encodes_trade_id_as_one_segment() {
+        assert_eq!(
+            trade_url("http://bisq:9999", "t-1").unwrap().as_str(),
+            "http://bisq:9999/api/v1/trades/t-1"
+        );
+        assert_eq!(
+            trade_url("http://bisq:9999/daemon", "a/../b?x#y")
+                .unwrap()
+                .as_str(),
+            "http://bisq:9999/daemon/api/v1/trades/a%2F..%2Fb%3Fx%23y"
+        );
+        assert_eq!(
+            trade_url("http://bisq:9999", "..").unwrap_err().kind(),
+            "bad_request"
+        );
+    }
+
+    #[test]
+    fn test_check_trade_rejects_unconfirmed_and_wrong_counterparty() {
+        let request = BisqVerifyRequest {
+            trade_id: "t".to_string(),
+            amount: 10,
+            counterparty: Some("a".to_string()),
+        };
+        let mut trade = BisqTrade {
+            trade_id: "t".to_string(),
+            amount: 10,
+            state: "DEPOSIT_PUBLISHED".to_string(),
+            counterparty: Some("a".to_string()),
+        };
+        assert_eq!(check_trade(&request, &trade), Err(BisqMismatch::State));
+
+        trade.state = "DEPOSIT_CONFIRMED".to_string();
+        trade.counterparty = Some("b".to_string());
+        assert_eq!(
+            check_trade(&request, &trade),
+            Err(BisqMismatch::Counterparty)
+        );
+    }
+}
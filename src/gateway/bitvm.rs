@@ -0,0 +1,508 @@
+//! [NEXUS-GW-01] Typed command protocol for the BitVM gateway service.
+//! Commands are parsed strictly from JSON instead of keyword-sniffing the raw payload.
+
+use super::audit::request_digest;
+use super::fees::{fee_for, FeeEntry, FeeLedger};
+use super::{GatewayService, ServiceError, ServiceResponse};
+use crate::signing::MessageSigner;
+use async_trait::async_trait;
+use lib_conxian_core::gateway::{BitVMService, ConxianService, ServiceStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Number of step commitments emitted for a proof.
+pub const PROOF_STEP_COUNT: usize = 4;
+
+/// BitVM commands accepted by the gateway.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BitVMCommand {
+    Prove,
+    Challenge,
+    Verify,
+}
+
+/// Typed BitVM gateway request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BitVMRequest {
+    pub command: BitVMCommand,
+    pub circuit_id: String,
+    pub witness_hex: String,
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+}
+
+/// A parsed gateway payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BitVMEnvelope {
+    Typed(BitVMRequest),
+    /// Deprecated text-mode payload wrapped as `{"legacy": "..."}`.
+    /// Kept for one release while clients migrate to typed commands.
+    Legacy(String),
+}
+
+/// Per-command BitVM gateway response.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum BitVMResponse {
+    Prove {
+        circuit_id: String,
+        proof_steps: Vec<String>,
+    },
+    Challenge {
+        circuit_id: String,
+        fee_tx: String,
+    },
+    Verify {
+        circuit_id: String,
+        verified: bool,
+    },
+    Legacy {
+        output: String,
+    },
+}
+
+/// Structured error returned for malformed or invalid requests.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BitVMError {
+    pub code: &'static str,
+    pub error: String,
+}
+
+impl fmt::Display for BitVMError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.error)
+    }
+}
+
+impl BitVMError {
+    fn new(code: &'static str, error: impl Into<String>) -> Self {
+        Self {
+            code,
+            error: error.into(),
+        }
+    }
+}
+
+/// Parses a raw gateway payload into a typed command or an explicit legacy wrapper.
+pub fn parse_request(payload: &str) -> Result<BitVMEnvelope, BitVMError> {
+    let value: Value = serde_json::from_str(payload)
+        .map_err(|e| BitVMError::new("malformed_json", e.to_string()))?;
+
+    if let Some(obj) = value.as_object() {
+        if let Some(legacy) = obj.get("legacy") {
+            if obj.len() != 1 {
+                return Err(BitVMError::new(
+                    "invalid_request",
+                    "legacy wrapper must not carry additional fields",
+                ));
+            }
+            return match legacy.as_str() {
+                Some(text) => Ok(BitVMEnvelope::Legacy(text.to_string())),
+                None => Err(BitVMError::new(
+                    "invalid_request",
+                    "legacy payload must be a string",
+                )),
+            };
+        }
+    }
+
+    let request: BitVMRequest = serde_json::from_value(value)
+        .map_err(|e| BitVMError::new("invalid_request", e.to_string()))?;
+    validate_request(&request)?;
+    Ok(BitVMEnvelope::Typed(request))
+}
+
+fn validate_request(request: &BitVMRequest) -> Result<(), BitVMError> {
+    if request.circuit_id.trim().is_empty() {
+        return Err(BitVMError::new("invalid_request", "circuit_id is required"));
+    }
+    decode_witness(&request.witness_hex)?;
+    if request.command == BitVMCommand::Verify && proof_root(request).is_none() {
+        return Err(BitVMError::new(
+            "invalid_request",
+            "verify requires metadata.proof_root",
+        ));
+    }
+    Ok(())
+}
+
+fn decode_witness(witness_hex: &str) -> Result<Vec<u8>, BitVMError> {
+    let raw = witness_hex.strip_prefix("0x").unwrap_or(witness_hex);
+    if raw.is_empty() {
+        return Err(BitVMError::new(
+            "invalid_witness",
+            "witness_hex is required",
+        ));
+    }
+    hex::decode(raw).map_err(|e| BitVMError::new("invalid_witness", e.to_string()))
+}
+
+fn proof_root(request: &BitVMRequest) -> Option<&str> {
+    request.metadata.get("proof_root").and_then(Value::as_str)
+}
+
+/// Derives the chained step commitments for a circuit/witness pair.
+/// The final step doubles as the proof root checked by `verify`.
+pub fn derive_proof_steps(circuit_id: &str, witness: &[u8]) -> Vec<String> {
+    let mut steps = Vec::with_capacity(PROOF_STEP_COUNT);
+    let mut prev = [0u8; 32];
+    for index in 0..PROOF_STEP_COUNT {
+        let mut hasher = Sha256::new();
+        hasher.update(prev);
+        hasher.update(circuit_id.as_bytes());
+        hasher.update((index as u64).to_be_bytes());
+        hasher.update(witness);
+        prev = hasher.finalize().into();
+        steps.push(format!("0x{}", hex::encode(prev)));
+    }
+    steps
+}
+
+/// Executes a validated typed request.
+pub fn execute(request: &BitVMRequest) -> Result<BitVMResponse, BitVMError> {
+    validate_request(request)?;
+    let witness = decode_witness(&request.witness_hex)?;
+    let steps = derive_proof_steps(&request.circuit_id, &witness);
+    let root = steps.last().cloned().unwrap_or_default();
+
+    Ok(match request.command {
+        BitVMCommand::Prove => BitVMResponse::Prove {
+            circuit_id: request.circuit_id.clone(),
+            proof_steps: steps,
+        },
+        BitVMCommand::Challenge => {
+            let digest = Sha256::digest(format!("bitvm:challenge:{}:{}", request.circuit_id, root));
+            BitVMResponse::Challenge {
+                circuit_id: request.circuit_id.clone(),
+                fee_tx: format!("0x{}", hex::encode(digest)),
+            }
+        }
+        BitVMCommand::Verify => BitVMResponse::Verify {
+            circuit_id: request.circuit_id.clone(),
+            verified: proof_root(request).is_some_and(|expected| expected == root),
+        },
+    })
+}
+
+/// Parses and dispatches a payload to a typed response.
+pub fn dispatch(payload: &str) -> Result<BitVMResponse, BitVMError> {
+    match parse_request(payload)? {
+        BitVMEnvelope::Typed(request) => execute(&request),
+        BitVMEnvelope::Legacy(text) => {
+            tracing::warn!("BitVM legacy text-mode request received; migrate to typed commands");
+            Ok(BitVMResponse::Legacy {
+                output: BitVMService.handle_request(&text),
+            })
+        }
+    }
+}
+
+/// Fee metering for billable BitVM commands.
+#[derive(Clone)]
+pub struct BitVMFees {
+    pub schedule: BTreeMap<String, u64>,
+    pub ledger: Arc<dyn FeeLedger>,
+    /// Signs every entry's payout payload.
+    pub signer: Arc<dyn MessageSigner>,
+}
+
+/// Typed front-end for the lib-conxian-core `BitVMService`.
+#[derive(Default, Clone)]
+pub struct BitVMGateway {
+    fees: Option<BitVMFees>,
+}
+
+impl BitVMGateway {
+    /// Gateway that records a fee ledger entry for every billable command.
+    pub fn with_fees(fees: BitVMFees) -> Self {
+        Self { fees: Some(fees) }
+    }
+
+    /// Records the scheduled fee for `response`'s command against `caller`, returning the
+    /// ledger id and amount. Billable commands without a caller are refused.
+    async fn charge(
+        &self,
+        response: &BitVMResponse,
+        payload: &str,
+        caller: Option<&str>,
+    ) -> Result<Option<(i64, u64)>, ServiceError> {
+        let Some(fees) = &self.fees else {
+            return Ok(None);
+        };
+        let command = match response {
+            BitVMResponse::Prove { .. } => "prove",
+            BitVMResponse::Challenge { .. } => "challenge",
+            BitVMResponse::Verify { .. } => "verify",
+            BitVMResponse::Legacy { .. } => return Ok(None),
+        };
+        let Some(amount) = fee_for(&fees.schedule, "bitvm", command) else {
+            return Ok(None);
+        };
+
+        let Some(caller) = caller else {
+            return Err(ServiceError::BadRequest(format!(
+                "bitvm {} is billable and needs an authenticated caller",
+                command
+            )));
+        };
+
+        let mut entry = FeeEntry::new(
+            "bitvm",
+            command,
+            Some(caller),
+            &request_digest(payload),
+            amount,
+        );
+        entry.payout_signature = Some(fees.signer.sign_message(&entry.payout_payload));
+        let id = fees.ledger.record(&entry).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to record BitVM service fee");
+            ServiceError::Internal("failed to record service fee".to_string())
+        })?;
+        Ok(Some((id, amount)))
+    }
+}
+
+#[async_trait]
+impl GatewayService for BitVMGateway {
+    fn name(&self) -> &'static str {
+        "bitvm"
+    }
+
+    fn status(&self) -> ServiceStatus {
+        BitVMService.status()
+    }
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError> {
+        self.handle_request_from(payload, None).await
+    }
+
+    async fn handle_request_from(
+        &self,
+        payload: &str,
+        caller: Option<&str>,
+    ) -> Result<ServiceResponse, ServiceError> {
+        let response = dispatch(payload).map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+        let charge = self.charge(&response, payload, caller).await?;
+        let mut body =
+            serde_json::to_value(&response).map_err(|e| ServiceError::Internal(e.to_string()))?;
+        if let (Some((entry_id, amount)), Some(obj)) = (charge, body.as_object_mut()) {
+            obj.insert("fee_entry_id".to_string(), entry_id.into());
+            obj.insert("fee_amount".to_string(), amount.into());
+        }
+        Ok(ServiceResponse::json(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(command: &str, circuit_id: &str, witness: &str) -> String {
+        serde_json::json!({
+            "command": command,
+            "circuit_id": circuit_id,
+            "witness_hex": witness,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_parse_prove_request() {
+        let parsed = parse_request(&typed("prove", "circuit-1", "deadbeef")).unwrap();
+        match parsed {
+            BitVMEnvelope::Typed(req) => {
+                assert_eq!(req.command, BitVMCommand::Prove);
+                assert_eq!(req.circuit_id, "circuit-1");
+                assert!(req.metadata.is_empty());
+            }
+            other => panic!("unexpected envelope: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_keyword_in_payload_does_not_change_command() {
+        let payload = serde_json::json!({
+            "command": "prove",
+            "circuit_id": "challenge-verify-circuit",
+            "witness_hex": "00",
+            "metadata": { "note": "challenge window opens later" }
+        })
+        .to_string();
+
+        let response = execute(match &parse_request(&payload).unwrap() {
+            BitVMEnvelope::Typed(req) => req,
+            other => panic!("unexpected envelope: {:?}", other),
+        })
+        .unwrap();
+        assert!(
+            matches!(response, BitVMResponse::Prove { ref proof_steps, .. } if proof_steps.len() == PROOF_STEP_COUNT)
+        );
+    }
+
+    #[test]
+    fn test_verify_round_trip() {
+        let steps = derive_proof_steps("circuit-1", &[0xde, 0xad]);
+        let root = steps.last().unwrap().clone();
+        let request = BitVMRequest {
+            command: BitVMCommand::Verify,
+            circuit_id: "circuit-1".to_string(),
+            witness_hex: "0xdead".to_string(),
+            metadata: serde_json::json!({ "proof_root": root })
+                .as_object()
+                .unwrap()
+                .clone(),
+        };
+        assert_eq!(
+            execute(&request).unwrap(),
+            BitVMResponse::Verify {
+                circuit_id: "circuit-1".to_string(),
+                verified: true
+            }
+        );
+
+        let mut tampered = request.clone();
+        tampered.witness_hex = "beef".to_string();
+        assert!(matches!(
+            execute(&tampered).unwrap(),
+            BitVMResponse::Verify {
+                verified: false,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_challenge_returns_fee_tx() {
+        let response = BitVMGateway::default()
+            .handle_request(&typed("challenge", "circuit-1", "ab"))
+            .await
+            .unwrap();
+        let value = response.body;
+        assert_eq!(value["command"], "challenge");
+        assert_eq!(value["fee_tx"].as_str().unwrap().len(), 66);
+    }
+
+    #[test]
+    fn test_parse_legacy_wrapper() {
+        let parsed = parse_request(r#"{"legacy": "prove circuit-1"}"#).unwrap();
+        assert_eq!(parsed, BitVMEnvelope::Legacy("prove circuit-1".to_string()));
+    }
+
+    #[test]
+    fn test_rejects_malformed_json() {
+        let err = parse_request("prove this please").unwrap_err();
+        assert_eq!(err.code, "malformed_json");
+    }
+
+    #[test]
+    fn test_rejects_unknown_command() {
+        let err = parse_request(&typed("settle", "circuit-1", "00")).unwrap_err();
+        assert_eq!(err.code, "invalid_request");
+    }
+
+    #[test]
+    fn test_rejects_missing_and_unknown_fields() {
+        let missing = parse_request(r#"{"command": "prove", "circuit_id": "c"}"#).unwrap_err();
+        assert_eq!(missing.code, "invalid_request");
+
+        let unknown = parse_request(
+            r#"{"command": "prove", "circuit_id": "c", "witness_hex": "00", "extra": 1}"#,
+        )
+        .unwrap_err();
+        assert_eq!(unknown.code, "invalid_request");
+    }
+
+    #[test]
+    fn test_rejects_invalid_witness_and_empty_circuit() {
+        let bad_hex = parse_request(&typed("prove", "circuit-1", "zz")).unwrap_err();
+        assert_eq!(bad_hex.code, "invalid_witness");
+
+        let empty = parse_request(&typed("prove", "  ", "00")).unwrap_err();
+        assert_eq!(empty.code, "invalid_request");
+    }
+
+    #[test]
+    fn test_rejects_verify_without_proof_root() {
+        let err = parse_request(&typed("verify", "circuit-1", "00")).unwrap_err();
+        assert_eq!(err.code, "invalid_request");
+    }
+
+    #[test]
+    fn test_rejects_bad_legacy_wrapper() {
+        assert_eq!(
+            parse_request(r#"{"legacy": 42}"#).unwrap_err().code,
+            "invalid_request"
+        );
+        assert_eq!(
+            parse_request(r#"{"legacy": "x", "command": "prove"}"#)
+                .unwrap_err()
+                .code,
+            "invalid_request"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_maps_to_bad_request() {
+        let err = BitVMGateway::default()
+            .handle_request("not json")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "bad_request");
+        assert!(err.message().starts_with("malformed_json"));
+    }
+
+    #[tokio::test]
+    async fn test_prove_calls_record_fee_entries_and_summary_aggregates() {
+        use crate::gateway::fees::MemoryFeeLedger;
+
+        let ledger = Arc::new(MemoryFeeLedger::default());
+        let gateway = BitVMGateway::with_fees(BitVMFees {
+            schedule: crate::gateway::fees::parse_fee_schedule("bitvm.prove=750").unwrap(),
+            ledger: ledger.clone(),
+            signer: Arc::new(
+                crate::signing::RotatingSigner::from_private_key_hex(&"07".repeat(32)).unwrap(),
+            ),
+        });
+        let anonymous = gateway
+            .handle_request(&typed("prove", "circuit-0", "aa"))
+            .await
+            .unwrap_err();
+        assert_eq!(anonymous.kind(), "bad_request");
+
+        let first = gateway
+            .handle_request_from(&typed("prove", "circuit-1", "aa"), Some("caller-a"))
+            .await
+            .unwrap()
+            .body;
+        let second = gateway
+            .handle_request_from(&typed("prove", "circuit-2", "bb"), Some("caller-a"))
+            .await
+            .unwrap()
+            .body;
+        gateway
+            .handle_request_from(&typed("challenge", "circuit-1", "aa"), Some("caller-a"))
+            .await
+            .unwrap();
+
+        assert_eq!(first["fee_entry_id"], 1);
+        assert_eq!(second["fee_entry_id"], 2);
+        assert_eq!(second["fee_amount"], 750);
+        let entries = ledger.entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|e| e.amount == 750 && e.payout_signature.is_some()));
+        assert_ne!(entries[0].request_digest, entries[1].request_digest);
+
+        let summary = ledger.summary(None).await.unwrap();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].caller, "caller-a");
+        assert_eq!((summary[0].entries, summary[0].total_amount), (2, 1500));
+    }
+}
@@ -0,0 +1,238 @@
+//! [NEXUS-GW-07] Per-service circuit breakers.
+//! After `failure_threshold` consecutive upstream failures a service's breaker opens and
+//! requests fail fast until `cooldown` elapses; the breaker then half-opens and lets a
+//! single probe through, closing on success and re-opening on failure. A probe that
+//! reports nothing within another `cooldown` (its request was dropped) is replaced.
+
+use prometheus::{opts, register_int_gauge_vec, IntGaugeVec};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+lazy_static::lazy_static! {
+    static ref BREAKER_STATE: IntGaugeVec = register_int_gauge_vec!(
+        opts!(
+            "nexus_gateway_breaker_state",
+            "Gateway circuit breaker state (0 closed, 1 half-open, 2 open)"
+        ),
+        &["service"]
+    )
+    .unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn gauge_value(self) -> i64 {
+        match self {
+            Self::Closed => 0,
+            Self::HalfOpen => 1,
+            Self::Open => 2,
+        }
+    }
+}
+
+/// Point-in-time view of a service's breaker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BreakerSnapshot {
+    pub service: String,
+    pub state: BreakerState,
+    pub failure_streak: u32,
+    pub last_error: Option<String>,
+    /// Seconds until an open breaker admits a probe.
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    state: BreakerState,
+    failure_streak: u32,
+    last_error: Option<String>,
+    opened_at: Option<Instant>,
+    /// When the current half-open probe was admitted.
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            state: BreakerState::Closed,
+            failure_streak: 0,
+            last_error: None,
+            opened_at: None,
+            probe_started: None,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    fn remaining_cooldown(&self, now: Instant) -> Duration {
+        self.opened_at
+            .map(|opened| self.config.cooldown.saturating_sub(now - opened))
+            .unwrap_or_default()
+    }
+
+    /// Admits a request, or returns how long the caller should wait. An open breaker
+    /// whose cooldown has elapsed half-opens and admits exactly one probe; a half-open
+    /// breaker whose probe has not reported back within `cooldown` admits another.
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let remaining = match self.state {
+            BreakerState::Closed => return Ok(()),
+            BreakerState::HalfOpen => self
+                .probe_started
+                .map(|started| self.config.cooldown.saturating_sub(now - started))
+                .unwrap_or_default(),
+            BreakerState::Open => self.remaining_cooldown(now),
+        };
+        if remaining.is_zero() {
+            self.state = BreakerState::HalfOpen;
+            self.probe_started = Some(now);
+            Ok(())
+        } else {
+            Err(remaining)
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = BreakerState::Closed;
+        self.failure_streak = 0;
+        self.opened_at = None;
+        self.probe_started = None;
+    }
+
+    pub fn record_failure(&mut self, error: &str, now: Instant) {
+        self.failure_streak = self.failure_streak.saturating_add(1);
+        self.last_error = Some(error.to_string());
+        if self.state == BreakerState::HalfOpen
+            || self.failure_streak >= self.config.failure_threshold
+        {
+            self.state = BreakerState::Open;
+            self.opened_at = Some(now);
+            self.probe_started = None;
+        }
+    }
+
+    pub fn snapshot(&self, service: &str, now: Instant) -> BreakerSnapshot {
+        BreakerSnapshot {
+            service: service.to_string(),
+            state: self.state,
+            failure_streak: self.failure_streak,
+            last_error: self.last_error.clone(),
+            retry_after_secs: (self.state == BreakerState::Open)
+                .then(|| self.remaining_cooldown(now).as_secs()),
+        }
+    }
+
+    /// Mirrors the current state into the Prometheus gauge.
+    pub fn publish(&self, service: &str) {
+        BREAKER_STATE
+            .with_label_values(&[service])
+            .set(self.state.gauge_value());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_opens_after_threshold() {
+        let config = BreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(10),
+        };
+        let mut breaker = CircuitBreaker::new(config);
+        let now = Instant::now();
+        breaker.record_failure("down", now);
+        assert_eq!(breaker.try_acquire(now), Ok(()));
+        breaker.record_failure("down", now);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert_eq!(
+            breaker.try_acquire(now + Duration::from_secs(4)),
+            Err(Duration::from_secs(6))
+        );
+        let snapshot = breaker.snapshot("bisq", now);
+        assert_eq!(snapshot.failure_streak, 2);
+        assert_eq!(snapshot.last_error.as_deref(), Some("down"));
+        assert_eq!(snapshot.retry_after_secs, Some(10));
+    }
+
+    #[test]
+    fn test_half_open_admits_single_probe() {
+        let config = BreakerConfig {
+            failure_threshold: 1,
+            cooldown: Duration::from_secs(5),
+        };
+        let mut breaker = CircuitBreaker::new(config);
+        let now = Instant::now();
+        breaker.record_failure("down", now);
+
+        let later = now + Duration::from_secs(5);
+        assert_eq!(breaker.try_acquire(later), Ok(()));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.try_acquire(later).is_err());
+
+        breaker.record_failure("still down", later);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.try_acquire(later).is_err());
+
+        let probe = later + Duration::from_secs(5);
+        assert_eq!(breaker.try_acquire(probe), Ok(()));
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert_eq!(breaker.snapshot("bisq", probe).failure_streak, 0);
+    }
+
+    #[test]
+    fn test_abandoned_probe_is_replaced_after_cooldown() {
+        let config = BreakerConfig {
+            failure_threshold: 1,
+            cooldown: Duration::from_secs(5),
+        };
+        let mut breaker = CircuitBreaker::new(config);
+        let now = Instant::now();
+        breaker.record_failure("down", now);
+
+        // The probe's request is dropped and never records an outcome.
+        let probe = now + Duration::from_secs(5);
+        assert_eq!(breaker.try_acquire(probe), Ok(()));
+        assert_eq!(
+            breaker.try_acquire(probe + Duration::from_secs(2)),
+            Err(Duration::from_secs(3))
+        );
+
+        let retry = probe + Duration::from_secs(5);
+        assert_eq!(breaker.try_acquire(retry), Ok(()));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.try_acquire(retry).is_err());
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+}
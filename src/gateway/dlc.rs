@@ -0,0 +1,578 @@
+//! [NEXUS-GW-06] DLC oracle attestation verification.
+//!
+//! Wire formats (hex-encoded, lengths big-endian):
+//! - announcement: `signature:64 | oracle_pubkey:32 | nonce_count:u16 | nonces:32*n | event_id_len:u16 | event_id`
+//! - attestation: `event_id_len:u16 | event_id | oracle_pubkey:32 | sig_count:u16 | signatures:64*n | (outcome_len:u16 | outcome)*n`
+//!
+//! The announcement signature covers everything after the oracle key. Each attestation
+//! signature is a BIP340 signature over `tagged_hash("DLC/oracle/attestation/v0", outcome)`
+//! whose nonce must equal the pre-committed nonce at the same index. Digit-decomposed
+//! events attest one outcome per nonce; the recovered outcome is their concatenation.
+//! Valid attestations from trusted oracles, submitted by an authenticated caller, are
+//! committed to the state tree once as `dlc:{event_id}`.
+
+use super::{record_verification, GatewayService, LeafCommitter, ServiceError, ServiceResponse};
+use crate::storage::Storage;
+use async_trait::async_trait;
+use k256::schnorr::signature::hazmat::PrehashVerifier;
+use k256::schnorr::{Signature, VerifyingKey};
+use lib_conxian_core::gateway::ServiceStatus;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const ANNOUNCEMENT_TAG: &str = "DLC/oracle/announcement/v0";
+pub const ATTESTATION_TAG: &str = "DLC/oracle/attestation/v0";
+
+/// BIP340-style tagged hash: `sha256(sha256(tag) || sha256(tag) || msg)`.
+pub fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash);
+    hasher.update(tag_hash);
+    hasher.update(msg);
+    hasher.finalize().into()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleAnnouncement {
+    pub signature: [u8; 64],
+    pub oracle_pubkey: [u8; 32],
+    pub nonces: Vec<[u8; 32]>,
+    pub event_id: String,
+    /// Bytes covered by the announcement signature.
+    signed_event: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleAttestation {
+    pub event_id: String,
+    pub oracle_pubkey: [u8; 32],
+    pub signatures: Vec<[u8; 64]>,
+    pub outcomes: Vec<String>,
+}
+
+impl OracleAttestation {
+    pub fn outcome(&self) -> String {
+        self.outcomes.concat()
+    }
+}
+
+/// Why an attestation does not settle the announced event.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DlcRejection {
+    InvalidAnnouncementSignature,
+    OracleMismatch,
+    EventMismatch,
+    NonceCountMismatch,
+    NonceMismatch,
+    InvalidAttestationSignature,
+    OutcomeMismatch,
+    /// Validly signed, by an oracle not in `DLC_ORACLE_PUBKEYS`.
+    UntrustedOracle,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize, field: &str) -> Result<&'a [u8], String> {
+        let out = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| format!("truncated {}", field))?;
+        self.pos += len;
+        Ok(out)
+    }
+
+    fn array<const N: usize>(&mut self, field: &str) -> Result<[u8; N], String> {
+        Ok(self.take(N, field)?.try_into().expect("length checked"))
+    }
+
+    fn u16(&mut self, field: &str) -> Result<usize, String> {
+        Ok(u16::from_be_bytes(self.array(field)?) as usize)
+    }
+
+    fn string(&mut self, field: &str) -> Result<String, String> {
+        let len = self.u16(field)?;
+        String::from_utf8(self.take(len, field)?.to_vec())
+            .map_err(|_| format!("{} is not utf-8", field))
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        if self.pos != self.bytes.len() {
+            return Err(format!("{} trailing bytes", self.bytes.len() - self.pos));
+        }
+        Ok(())
+    }
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.trim().trim_start_matches("0x"))
+        .map_err(|e| format!("{} is not valid hex: {}", field, e))
+}
+
+pub fn parse_announcement(bytes: &[u8]) -> Result<OracleAnnouncement, String> {
+    let mut reader = Reader::new(bytes);
+    let signature = reader.array::<64>("announcement signature")?;
+    let oracle_pubkey = reader.array::<32>("oracle pubkey")?;
+    let signed_start = reader.pos;
+    let count = reader.u16("nonce count")?;
+    if count == 0 {
+        return Err("announcement has no nonces".to_string());
+    }
+    let nonces = (0..count)
+        .map(|_| reader.array::<32>("nonce"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let event_id = reader.string("event id")?;
+    reader.finish()?;
+
+    Ok(OracleAnnouncement {
+        signature,
+        oracle_pubkey,
+        nonces,
+        event_id,
+        signed_event: bytes[signed_start..].to_vec(),
+    })
+}
+
+pub fn parse_attestation(bytes: &[u8]) -> Result<OracleAttestation, String> {
+    let mut reader = Reader::new(bytes);
+    let event_id = reader.string("event id")?;
+    let oracle_pubkey = reader.array::<32>("oracle pubkey")?;
+    let count = reader.u16("signature count")?;
+    let signatures = (0..count)
+        .map(|_| reader.array::<64>("signature"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcomes = (0..count)
+        .map(|_| reader.string("outcome"))
+        .collect::<Result<Vec<_>, _>>()?;
+    reader.finish()?;
+
+    Ok(OracleAttestation {
+        event_id,
+        oracle_pubkey,
+        signatures,
+        outcomes,
+    })
+}
+
+fn bip340_verify(pubkey: &[u8; 32], message: &[u8; 32], signature: &[u8; 64]) -> bool {
+    let Ok(key) = VerifyingKey::from_bytes(pubkey) else {
+        return false;
+    };
+    let Ok(signature) = Signature::try_from(&signature[..]) else {
+        return false;
+    };
+    key.verify_prehash(message, &signature).is_ok()
+}
+
+/// Checks the attestation against the announcement and the claimed outcome,
+/// returning the recovered outcome when every signature is valid.
+pub fn verify_attestation(
+    announcement: &OracleAnnouncement,
+    attestation: &OracleAttestation,
+    claimed_outcome: &str,
+) -> Result<String, DlcRejection> {
+    let announcement_msg = tagged_hash(ANNOUNCEMENT_TAG, &announcement.signed_event);
+    if !bip340_verify(
+        &announcement.oracle_pubkey,
+        &announcement_msg,
+        &announcement.signature,
+    ) {
+        return Err(DlcRejection::InvalidAnnouncementSignature);
+    }
+    if attestation.oracle_pubkey != announcement.oracle_pubkey {
+        return Err(DlcRejection::OracleMismatch);
+    }
+    if attestation.event_id != announcement.event_id {
+        return Err(DlcRejection::EventMismatch);
+    }
+    if attestation.signatures.len() != announcement.nonces.len() {
+        return Err(DlcRejection::NonceCountMismatch);
+    }
+
+    for ((signature, nonce), outcome) in attestation
+        .signatures
+        .iter()
+        .zip(&announcement.nonces)
+        .zip(&attestation.outcomes)
+    {
+        if signature[..32] != nonce[..] {
+            return Err(DlcRejection::NonceMismatch);
+        }
+        let message = tagged_hash(ATTESTATION_TAG, outcome.as_bytes());
+        if !bip340_verify(&announcement.oracle_pubkey, &message, signature) {
+            return Err(DlcRejection::InvalidAttestationSignature);
+        }
+    }
+
+    let outcome = attestation.outcome();
+    if outcome != claimed_outcome {
+        return Err(DlcRejection::OutcomeMismatch);
+    }
+    Ok(outcome)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DlcAttestationRequest {
+    pub announcement: String,
+    pub attestation: String,
+    pub outcome: String,
+}
+
+/// DLC gateway service validating oracle attestations for settlements.
+pub struct DLCGateway {
+    storage: Option<Arc<Storage>>,
+    /// X-only keys of the oracles whose attestations are accepted.
+    trusted_oracles: Vec<[u8; 32]>,
+    leaves: Option<LeafCommitter>,
+}
+
+impl DLCGateway {
+    /// Gateway trusting no oracle until [`Self::with_leaves`] names some.
+    pub fn new(storage: Option<Arc<Storage>>) -> Self {
+        Self {
+            storage,
+            trusted_oracles: Vec::new(),
+            leaves: None,
+        }
+    }
+
+    /// Accepts attestations from `trusted_oracles` and commits them through `leaves`.
+    pub fn with_leaves(mut self, leaves: LeafCommitter, trusted_oracles: Vec<[u8; 32]>) -> Self {
+        self.leaves = Some(leaves);
+        self.trusted_oracles = trusted_oracles;
+        self
+    }
+}
+
+#[async_trait]
+impl GatewayService for DLCGateway {
+    fn name(&self) -> &'static str {
+        "dlc"
+    }
+
+    fn status(&self) -> ServiceStatus {
+        ServiceStatus {
+            service_name: "DLC".to_string(),
+            status: "Active".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError> {
+        self.handle_request_from(payload, None).await
+    }
+
+    /// Verifies the attestation and, when `caller` is set, commits its leaf unless an
+    /// earlier request did.
+    async fn handle_request_from(
+        &self,
+        payload: &str,
+        caller: Option<&str>,
+    ) -> Result<ServiceResponse, ServiceError> {
+        let request: DlcAttestationRequest = serde_json::from_str(payload)
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid DLC request: {}", e)))?;
+        let announcement = decode_hex("announcement", &request.announcement)
+            .and_then(|b| parse_announcement(&b))
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid announcement: {}", e)))?;
+        let attestation = decode_hex("attestation", &request.attestation)
+            .and_then(|b| parse_attestation(&b))
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid attestation: {}", e)))?;
+
+        let verdict =
+            verify_attestation(&announcement, &attestation, &request.outcome).and_then(|outcome| {
+                if self.trusted_oracles.contains(&announcement.oracle_pubkey) {
+                    Ok(outcome)
+                } else {
+                    Err(DlcRejection::UntrustedOracle)
+                }
+            });
+        let leaf = format!("dlc:{}", announcement.event_id);
+        let committed = match (&self.leaves, caller) {
+            (Some(leaves), Some(_)) if verdict.is_ok() => {
+                leaves.commit("dlc", &leaf).await.map_err(|e| {
+                    tracing::error!(error = %e, leaf = %leaf, "Failed to commit DLC attestation");
+                    ServiceError::Internal("failed to commit attestation".to_string())
+                })?
+            }
+            _ => false,
+        };
+        let body = json!({
+            "event_id": announcement.event_id,
+            "oracle_pubkey": hex::encode(announcement.oracle_pubkey),
+            "outcome": attestation.outcome(),
+            "claimed_outcome": request.outcome,
+            "valid": verdict.is_ok(),
+            "reason": verdict.err(),
+            "leaf": verdict.is_ok().then_some(&leaf),
+            "committed": committed,
+        });
+
+        if let Some(storage) = &self.storage {
+            let reason = body["reason"].as_str();
+            if let Err(e) = record_verification(
+                storage,
+                "dlc",
+                &announcement.event_id,
+                verdict.is_ok(),
+                reason,
+                &body,
+            )
+            .await
+            {
+                tracing::warn!(error = %e, "Failed to persist DLC attestation verification");
+            }
+        }
+
+        Ok(ServiceResponse::json(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::elliptic_curve::ff::PrimeField;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use k256::{ProjectivePoint, Scalar};
+
+    fn scalar(bytes: [u8; 32]) -> Scalar {
+        Scalar::from_repr(bytes.into()).unwrap()
+    }
+
+    /// Negates `secret` if needed so its point has an even y; returns it with the x-only key.
+    fn even_y(secret: Scalar) -> (Scalar, [u8; 32]) {
+        let point = (ProjectivePoint::GENERATOR * secret)
+            .to_affine()
+            .to_encoded_point(false);
+        let x: [u8; 32] = point.x().unwrap().as_slice().try_into().unwrap();
+        let y_is_odd = point.y().unwrap()[31] & 1 == 1;
+        (if y_is_odd { -secret } else { secret }, x)
+    }
+
+    /// BIP340 signing with a caller-chosen nonce, as a DLC oracle does.
+    fn sign_with_nonce(secret: Scalar, nonce: Scalar, message: &[u8; 32]) -> [u8; 64] {
+        let (d, pubkey) = even_y(secret);
+        let (k, r) = even_y(nonce);
+        let e = scalar(tagged_hash(
+            "BIP0340/challenge",
+            &[&r[..], &pubkey[..], &message[..]].concat(),
+        ));
+        let s: [u8; 32] = (k + e * d).to_repr().into();
+        [r, s].concat().try_into().unwrap()
+    }
+
+    struct Oracle {
+        secret: Scalar,
+        nonces: Vec<Scalar>,
+    }
+
+    impl Oracle {
+        fn new(nonce_count: u8) -> Self {
+            Self {
+                secret: scalar([7u8; 32]),
+                nonces: (0..nonce_count).map(|i| scalar([11 + i; 32])).collect(),
+            }
+        }
+
+        fn pubkey(&self) -> [u8; 32] {
+            even_y(self.secret).1
+        }
+
+        fn announce(&self, event_id: &str) -> Vec<u8> {
+            let mut event = (self.nonces.len() as u16).to_be_bytes().to_vec();
+            for nonce in &self.nonces {
+                event.extend_from_slice(&even_y(*nonce).1);
+            }
+            event.extend_from_slice(&(event_id.len() as u16).to_be_bytes());
+            event.extend_from_slice(event_id.as_bytes());
+
+            let nonce = scalar([99u8; 32]);
+            let sig = sign_with_nonce(self.secret, nonce, &tagged_hash(ANNOUNCEMENT_TAG, &event));
+            [&sig[..], &self.pubkey()[..], &event[..]].concat()
+        }
+
+        fn attest(&self, event_id: &str, outcomes: &[&str]) -> Vec<u8> {
+            let mut bytes = (event_id.len() as u16).to_be_bytes().to_vec();
+            bytes.extend_from_slice(event_id.as_bytes());
+            bytes.extend_from_slice(&self.pubkey());
+            bytes.extend_from_slice(&(outcomes.len() as u16).to_be_bytes());
+            for (nonce, outcome) in self.nonces.iter().zip(outcomes) {
+                let message = tagged_hash(ATTESTATION_TAG, outcome.as_bytes());
+                bytes.extend_from_slice(&sign_with_nonce(self.secret, *nonce, &message));
+            }
+            for outcome in outcomes {
+                bytes.extend_from_slice(&(outcome.len() as u16).to_be_bytes());
+                bytes.extend_from_slice(outcome.as_bytes());
+            }
+            bytes
+        }
+    }
+
+    fn verify(
+        announcement: &[u8],
+        attestation: &[u8],
+        claimed: &str,
+    ) -> Result<String, DlcRejection> {
+        verify_attestation(
+            &parse_announcement(announcement).unwrap(),
+            &parse_attestation(attestation).unwrap(),
+            claimed,
+        )
+    }
+
+    #[test]
+    fn test_valid_enum_attestation_recovers_outcome() {
+        let oracle = Oracle::new(1);
+        let announcement = oracle.announce("btc-halving-2028");
+        let attestation = oracle.attest("btc-halving-2028", &["YES"]);
+        assert_eq!(
+            verify(&announcement, &attestation, "YES"),
+            Ok("YES".to_string())
+        );
+    }
+
+    #[test]
+    fn test_digit_decomposed_outcome_is_concatenated() {
+        let oracle = Oracle::new(3);
+        let announcement = oracle.announce("btcusd-close");
+        let attestation = oracle.attest("btcusd-close", &["1", "0", "1"]);
+        assert_eq!(
+            verify(&announcement, &attestation, "101"),
+            Ok("101".to_string())
+        );
+    }
+
+    #[test]
+    fn test_claimed_outcome_must_match_signed_outcome() {
+        let oracle = Oracle::new(1);
+        let announcement = oracle.announce("e1");
+        let attestation = oracle.attest("e1", &["NO"]);
+        assert_eq!(
+            verify(&announcement, &attestation, "YES"),
+            Err(DlcRejection::OutcomeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_signature_with_unannounced_nonce_is_rejected() {
+        let oracle = Oracle::new(1);
+        let announcement = oracle.announce("e1");
+        let rogue = Oracle {
+            secret: oracle.secret,
+            nonces: vec![scalar([42u8; 32])],
+        };
+        let attestation = rogue.attest("e1", &["YES"]);
+        assert_eq!(
+            verify(&announcement, &attestation, "YES"),
+            Err(DlcRejection::NonceMismatch)
+        );
+    }
+
+    #[test]
+    fn test_tampered_outcome_fails_signature() {
+        let oracle = Oracle::new(1);
+        let announcement = oracle.announce("e1");
+        let mut attestation = parse_attestation(&oracle.attest("e1", &["YES"])).unwrap();
+        attestation.outcomes = vec!["NO!".to_string()];
+        assert_eq!(
+            verify_attestation(
+                &parse_announcement(&announcement).unwrap(),
+                &attestation,
+                "NO!"
+            ),
+            Err(DlcRejection::InvalidAttestationSignature)
+        );
+    }
+
+    #[test]
+    fn test_forged_announcement_is_rejected() {
+        let oracle = Oracle::new(1);
+        let mut announcement = oracle.announce("e1");
+        let last = announcement.len() - 1;
+        announcement[last] ^= 1;
+        let attestation = oracle.attest("e0", &["YES"]);
+        assert_eq!(
+            verify(&announcement, &attestation, "YES"),
+            Err(DlcRejection::InvalidAnnouncementSignature)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_service_commits_trusted_attestation_once() {
+        use crate::state::NexusState;
+        use crate::storage::store::{InMemoryStore, NexusStore};
+
+        let store = Arc::new(InMemoryStore::new());
+        let state = Arc::new(NexusState::new());
+        let oracle = Oracle::new(1);
+        let gateway = DLCGateway::new(None).with_leaves(
+            LeafCommitter::new(store.clone(), state.clone()),
+            vec![oracle.pubkey()],
+        );
+        let payload = json!({
+            "announcement": hex::encode(oracle.announce("e1")),
+            "attestation": hex::encode(oracle.attest("e1", &["YES"])),
+            "outcome": "YES",
+        })
+        .to_string();
+
+        // Verified, but not committed without an authenticated caller.
+        let body = gateway.handle_request(&payload).await.unwrap().body;
+        assert_eq!(
+            (body["valid"].as_bool(), body["committed"].as_bool()),
+            (Some(true), Some(false))
+        );
+        assert_eq!(state.leaf_count(), 0);
+
+        let body = gateway
+            .handle_request_from(&payload, Some("caller-a"))
+            .await
+            .unwrap()
+            .body;
+        assert_eq!(body["outcome"], "YES");
+        assert_eq!(body["committed"], true);
+        assert!(state.get_leaf_index("dlc:e1").is_some());
+        assert_eq!(
+            store.state_leaves().await.unwrap(),
+            vec!["dlc:e1".to_string()]
+        );
+
+        // A replay is verified again but not appended twice.
+        let body = gateway
+            .handle_request_from(&payload, Some("caller-b"))
+            .await
+            .unwrap()
+            .body;
+        assert_eq!(
+            (body["valid"].as_bool(), body["committed"].as_bool()),
+            (Some(true), Some(false))
+        );
+        assert_eq!(state.leaf_count(), 1);
+
+        let untrusted = DLCGateway::new(None)
+            .with_leaves(LeafCommitter::new(store.clone(), state.clone()), Vec::new())
+            .handle_request_from(&payload, Some("caller-a"))
+            .await
+            .unwrap()
+            .body;
+        assert_eq!(untrusted["valid"], false);
+        assert_eq!(untrusted["reason"], "untrusted_oracle");
+
+        let err = gateway
+            .handle_request(r#"{"announcement": "zz", "attestation": "", "outcome": ""}"#)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "bad_request");
+    }
+}
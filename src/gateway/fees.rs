@@ -0,0 +1,244 @@
+//! [NEXUS-GW-08] Fee ledger for metered gateway requests.
+//! Each billable request records who owes what, for which request, alongside a signed
+//! payout payload the treasury can later settle against.
+
+use crate::latency::timed_query;
+use crate::storage::Storage;
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Fee schedule applied when `SERVICE_FEES` is unset.
+pub const DEFAULT_SERVICE_FEES: &str = "bitvm.prove=1000";
+/// Recipient named in every payout payload.
+pub const PAYOUT_PAYEE: &str = "agent-treasury";
+
+/// Parses a `service.command=amount,...` fee schedule.
+pub fn parse_fee_schedule(spec: &str) -> anyhow::Result<BTreeMap<String, u64>> {
+    let mut schedule = BTreeMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (key, amount) = entry
+            .split_once('=')
+            .with_context(|| format!("fee entry '{}' must be service.command=amount", entry))?;
+        let key = key.trim().to_ascii_lowercase();
+        if key
+            .split_once('.')
+            .is_none_or(|(s, c)| s.is_empty() || c.is_empty())
+        {
+            anyhow::bail!("fee key '{}' must be service.command", key);
+        }
+        let amount = amount
+            .trim()
+            .parse::<u64>()
+            .with_context(|| format!("invalid fee amount for '{}'", key))?;
+        schedule.insert(key, amount);
+    }
+    Ok(schedule)
+}
+
+pub fn default_fee_schedule() -> BTreeMap<String, u64> {
+    parse_fee_schedule(DEFAULT_SERVICE_FEES).expect("default fee schedule is valid")
+}
+
+/// Fee owed for `command` on `service`, if it is billable.
+pub fn fee_for(schedule: &BTreeMap<String, u64>, service: &str, command: &str) -> Option<u64> {
+    schedule.get(&format!("{}.{}", service, command)).copied()
+}
+
+/// One accrued fee.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeEntry {
+    pub service: String,
+    pub command: String,
+    /// Fingerprint of the caller's API key; `None` for anonymous requests.
+    pub caller: Option<String>,
+    pub request_digest: String,
+    pub amount: u64,
+    pub payout_payload: String,
+    pub payout_signature: Option<String>,
+}
+
+impl FeeEntry {
+    /// Builds an entry with its canonical payout payload; signing is left to the caller.
+    pub fn new(
+        service: &str,
+        command: &str,
+        caller: Option<&str>,
+        request_digest: &str,
+        amount: u64,
+    ) -> Self {
+        let payout_payload = serde_json::json!({
+            "payee": PAYOUT_PAYEE,
+            "service": service,
+            "command": command,
+            "caller": caller,
+            "request_digest": request_digest,
+            "amount": amount,
+        })
+        .to_string();
+        Self {
+            service: service.to_string(),
+            command: command.to_string(),
+            caller: caller.map(str::to_string),
+            request_digest: request_digest.to_string(),
+            amount,
+            payout_payload,
+            payout_signature: None,
+        }
+    }
+}
+
+/// Accrued fees for one caller on one service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeeSummary {
+    /// Caller fingerprint, or `anonymous`.
+    pub caller: String,
+    pub service: String,
+    pub entries: u64,
+    pub total_amount: u64,
+}
+
+#[async_trait]
+pub trait FeeLedger: Send + Sync {
+    /// Persists `entry` and returns its ledger id.
+    async fn record(&self, entry: &FeeEntry) -> anyhow::Result<i64>;
+
+    /// Fees accrued since `since` (all time when `None`), grouped by caller and service.
+    async fn summary(&self, since: Option<DateTime<Utc>>) -> anyhow::Result<Vec<FeeSummary>>;
+}
+
+/// Fee ledger backed by the `service_fees` table.
+pub struct PgFeeLedger {
+    storage: Arc<Storage>,
+}
+
+impl PgFeeLedger {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl FeeLedger for PgFeeLedger {
+    async fn record(&self, entry: &FeeEntry) -> anyhow::Result<i64> {
+        let row = timed_query(
+            "record_fee",
+            sqlx::query(
+                "INSERT INTO service_fees
+             (service, command, caller, request_digest, amount, payout_payload, payout_signature)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id",
+            )
+            .bind(&entry.service)
+            .bind(&entry.command)
+            .bind(&entry.caller)
+            .bind(&entry.request_digest)
+            .bind(entry.amount as i64)
+            .bind(&entry.payout_payload)
+            .bind(&entry.payout_signature)
+            .fetch_one(&self.storage.pg_pool),
+        )
+        .await?;
+        Ok(row.get("id"))
+    }
+
+    async fn summary(&self, since: Option<DateTime<Utc>>) -> anyhow::Result<Vec<FeeSummary>> {
+        let rows = timed_query(
+            "fee_summary",
+            sqlx::query(
+                "SELECT COALESCE(caller, 'anonymous') AS caller, service,
+                    COUNT(*) AS entries, SUM(amount)::BIGINT AS total_amount
+             FROM service_fees
+             WHERE $1::TIMESTAMPTZ IS NULL OR created_at >= $1
+             GROUP BY 1, 2
+             ORDER BY 1, 2",
+            )
+            .bind(since)
+            .fetch_all(&self.storage.pg_pool),
+        )
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| FeeSummary {
+                caller: row.get("caller"),
+                service: row.get("service"),
+                entries: row.get::<i64, _>("entries") as u64,
+                total_amount: row.get::<i64, _>("total_amount") as u64,
+            })
+            .collect())
+    }
+}
+
+/// In-process fee ledger, used in tests and when no database is wired.
+#[derive(Default)]
+pub struct MemoryFeeLedger {
+    entries: Mutex<Vec<(DateTime<Utc>, FeeEntry)>>,
+}
+
+impl MemoryFeeLedger {
+    pub fn entries(&self) -> Vec<FeeEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().map(|(_, e)| e.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl FeeLedger for MemoryFeeLedger {
+    async fn record(&self, entry: &FeeEntry) -> anyhow::Result<i64> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push((Utc::now(), entry.clone()));
+        Ok(entries.len() as i64)
+    }
+
+    async fn summary(&self, since: Option<DateTime<Utc>>) -> anyhow::Result<Vec<FeeSummary>> {
+        let entries = self.entries.lock().unwrap();
+        let mut totals: BTreeMap<(String, String), (u64, u64)> = BTreeMap::new();
+        for (created_at, entry) in entries.iter() {
+            if since.is_some_and(|since| *created_at < since) {
+                continue;
+            }
+            let caller = entry.caller.clone().unwrap_or_else(|| "anonymous".into());
+            let total = totals.entry((caller, entry.service.clone())).or_default();
+            total.0 += 1;
+            total.1 += entry.amount;
+        }
+        Ok(totals
+            .into_iter()
+            .map(|((caller, service), (entries, total_amount))| FeeSummary {
+                caller,
+                service,
+                entries,
+                total_amount,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fee_schedule() {
+        let schedule = parse_fee_schedule(" BitVM.prove=1000, bitvm.challenge = 25 ,").unwrap();
+        assert_eq!(fee_for(&schedule, "bitvm", "prove"), Some(1000));
+        assert_eq!(fee_for(&schedule, "bitvm", "challenge"), Some(25));
+        assert_eq!(fee_for(&schedule, "bitvm", "verify"), None);
+
+        assert!(parse_fee_schedule("bitvm=10").is_err());
+        assert!(parse_fee_schedule("bitvm.prove=ten").is_err());
+    }
+
+    #[test]
+    fn test_payout_payload_names_payee_and_amount() {
+        let entry = FeeEntry::new("bitvm", "prove", Some("abcd"), "digest", 1000);
+        let payload: serde_json::Value = serde_json::from_str(&entry.payout_payload).unwrap();
+        assert_eq!(payload["payee"], PAYOUT_PAYEE);
+        assert_eq!(payload["amount"], 1000);
+        assert_eq!(payload["caller"], "abcd");
+    }
+}
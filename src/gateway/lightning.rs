@@ -0,0 +1,652 @@
+//! [NEXUS-GW-05] Lightning payment verification over BOLT11 invoices.
+//!
+//! Invoices are decoded locally (bech32, tagged fields, signature recovery of the
+//! payee). `verify_invoice` proves payment by preimage; when `LND_REST_URL` is set the
+//! invoice's settlement state is additionally looked up on the LND node.
+
+use super::{record_verification, GatewayService, ServiceError, ServiceResponse};
+use crate::storage::Storage;
+use async_trait::async_trait;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use lib_conxian_core::gateway::ServiceStatus;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::Arc;
+
+/// Expiry applied when an invoice carries no `x` field (BOLT11 default).
+pub const DEFAULT_INVOICE_EXPIRY_SECS: u64 = 3600;
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_WORDS: usize = 6;
+const TIMESTAMP_WORDS: usize = 7;
+const SIGNATURE_WORDS: usize = 104;
+const MSAT_PER_BTC: u64 = 100_000_000_000;
+
+/// Decoded BOLT11 invoice.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bolt11Invoice {
+    /// Currency prefix (`bc`, `tb`, `bcrt`, ...).
+    pub network: String,
+    pub amount_msat: Option<u64>,
+    pub timestamp: u64,
+    pub payment_hash: String,
+    pub payment_secret: Option<String>,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub expiry_secs: u64,
+    /// Compressed payee node public key (hex).
+    pub destination: String,
+}
+
+impl Bolt11Invoice {
+    pub fn expires_at(&self) -> u64 {
+        self.timestamp.saturating_add(self.expiry_secs)
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bolt11Error {
+    Bech32(&'static str),
+    InvalidPrefix,
+    InvalidAmount,
+    InvalidDescription,
+    MissingPaymentHash,
+    InvalidSignature,
+}
+
+impl fmt::Display for Bolt11Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bech32(msg) => write!(f, "invalid bech32: {}", msg),
+            Self::InvalidPrefix => write!(f, "invalid invoice prefix"),
+            Self::InvalidAmount => write!(f, "invalid invoice amount"),
+            Self::InvalidDescription => write!(f, "description is not valid UTF-8"),
+            Self::MissingPaymentHash => write!(f, "invoice has no payment hash"),
+            Self::InvalidSignature => write!(f, "invalid invoice signature"),
+        }
+    }
+}
+
+impl std::error::Error for Bolt11Error {}
+
+fn bech32_polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ v as u32;
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+/// Splits a bech32 string into its HRP and data words (checksum removed).
+fn bech32_decode(input: &str) -> Result<(String, Vec<u8>), Bolt11Error> {
+    if input.chars().any(|c| c.is_ascii_lowercase())
+        && input.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err(Bolt11Error::Bech32("mixed case"));
+    }
+    let input = input.to_ascii_lowercase();
+    let sep = input
+        .rfind('1')
+        .ok_or(Bolt11Error::Bech32("missing separator"))?;
+    let (hrp, data) = (&input[..sep], &input[sep + 1..]);
+    if hrp.is_empty() || data.len() < CHECKSUM_WORDS {
+        return Err(Bolt11Error::Bech32("too short"));
+    }
+
+    let words = data
+        .bytes()
+        .map(|c| {
+            BECH32_CHARSET
+                .iter()
+                .position(|&x| x == c)
+                .map(|p| p as u8)
+                .ok_or(Bolt11Error::Bech32("invalid character"))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let expanded = hrp
+        .bytes()
+        .map(|b| b >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|b| b & 31))
+        .chain(words.iter().copied());
+    if bech32_polymod(expanded) != 1 {
+        return Err(Bolt11Error::Bech32("checksum mismatch"));
+    }
+
+    let payload = words[..words.len() - CHECKSUM_WORDS].to_vec();
+    Ok((hrp.to_string(), payload))
+}
+
+/// Regroups 5-bit words into bytes; with `pad` the trailing bits are zero-padded.
+fn words_to_bytes(words: &[u8], pad: bool) -> Vec<u8> {
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    let mut out = Vec::with_capacity(words.len() * 5 / 8 + 1);
+    for &w in words {
+        acc = (acc << 5) | w as u32;
+        bits += 5;
+        while bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if pad && bits > 0 {
+        out.push((acc << (8 - bits)) as u8);
+    }
+    out
+}
+
+fn words_to_u64(words: &[u8]) -> u64 {
+    words.iter().fold(0u64, |acc, &w| (acc << 5) | w as u64)
+}
+
+/// Parses `ln<currency>[<amount><multiplier>]` into (currency, amount in msat).
+fn parse_hrp(hrp: &str) -> Result<(String, Option<u64>), Bolt11Error> {
+    let rest = hrp.strip_prefix("ln").ok_or(Bolt11Error::InvalidPrefix)?;
+    let split = rest
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (network, amount) = rest.split_at(split);
+    if network.is_empty() {
+        return Err(Bolt11Error::InvalidPrefix);
+    }
+    if amount.is_empty() {
+        return Ok((network.to_string(), None));
+    }
+
+    let (digits, multiplier) = match amount.chars().last() {
+        Some(c) if c.is_ascii_digit() => (amount, None),
+        Some(c) => (&amount[..amount.len() - 1], Some(c)),
+        None => return Err(Bolt11Error::InvalidAmount),
+    };
+    let value: u64 = digits.parse().map_err(|_| Bolt11Error::InvalidAmount)?;
+    let msat = match multiplier {
+        None => value.checked_mul(MSAT_PER_BTC),
+        Some('m') => value.checked_mul(MSAT_PER_BTC / 1_000),
+        Some('u') => value.checked_mul(MSAT_PER_BTC / 1_000_000),
+        Some('n') => value.checked_mul(MSAT_PER_BTC / 1_000_000_000),
+        Some('p') if value % 10 == 0 => Some(value / 10),
+        _ => None,
+    }
+    .ok_or(Bolt11Error::InvalidAmount)?;
+    Ok((network.to_string(), Some(msat)))
+}
+
+/// Decodes a BOLT11 invoice, recovering the payee from the signature.
+pub fn decode_invoice(invoice: &str) -> Result<Bolt11Invoice, Bolt11Error> {
+    let invoice = invoice.trim();
+    let invoice = invoice
+        .strip_prefix("lightning:")
+        .or_else(|| invoice.strip_prefix("LIGHTNING:"))
+        .unwrap_or(invoice);
+    let (hrp, words) = bech32_decode(invoice)?;
+    let (network, amount_msat) = parse_hrp(&hrp)?;
+
+    if words.len() < TIMESTAMP_WORDS + SIGNATURE_WORDS {
+        return Err(Bolt11Error::Bech32("data too short"));
+    }
+    let (data, sig_words) = words.split_at(words.len() - SIGNATURE_WORDS);
+    let timestamp = words_to_u64(&data[..TIMESTAMP_WORDS]);
+
+    let mut payment_hash = None;
+    let mut payment_secret = None;
+    let mut description = None;
+    let mut description_hash = None;
+    let mut expiry_secs = DEFAULT_INVOICE_EXPIRY_SECS;
+    let mut payee = None;
+
+    let mut fields = &data[TIMESTAMP_WORDS..];
+    while fields.len() >= 3 {
+        let tag = BECH32_CHARSET[fields[0] as usize];
+        let len = fields[1] as usize * 32 + fields[2] as usize;
+        let value = fields
+            .get(3..3 + len)
+            .ok_or(Bolt11Error::Bech32("truncated tagged field"))?;
+        match (tag, len) {
+            (b'p', 52) => payment_hash = Some(hex::encode(words_to_bytes(value, false))),
+            (b's', 52) => payment_secret = Some(hex::encode(words_to_bytes(value, false))),
+            (b'h', 52) => description_hash = Some(hex::encode(words_to_bytes(value, false))),
+            (b'n', 53) => payee = Some(words_to_bytes(value, false)),
+            (b'd', _) => {
+                description = Some(
+                    String::from_utf8(words_to_bytes(value, false))
+                        .map_err(|_| Bolt11Error::InvalidDescription)?,
+                )
+            }
+            (b'x', _) => expiry_secs = words_to_u64(value),
+            // Unknown fields and known fields of the wrong length are skipped per BOLT11.
+            _ => {}
+        }
+        fields = &fields[3 + len..];
+    }
+    let payment_hash = payment_hash.ok_or(Bolt11Error::MissingPaymentHash)?;
+
+    let sig_bytes = words_to_bytes(sig_words, false);
+    let signature =
+        Signature::from_slice(&sig_bytes[..64]).map_err(|_| Bolt11Error::InvalidSignature)?;
+    let recovery_id = RecoveryId::from_byte(sig_bytes[64]).ok_or(Bolt11Error::InvalidSignature)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(hrp.as_bytes());
+    hasher.update(words_to_bytes(data, true));
+    let digest: [u8; 32] = hasher.finalize().into();
+    let recovered = VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id)
+        .map_err(|_| Bolt11Error::InvalidSignature)?;
+    let recovered = recovered.to_encoded_point(true).as_bytes().to_vec();
+    if payee.as_ref().is_some_and(|n| *n != recovered) {
+        return Err(Bolt11Error::InvalidSignature);
+    }
+
+    Ok(Bolt11Invoice {
+        network,
+        amount_msat,
+        timestamp,
+        payment_hash,
+        payment_secret,
+        description,
+        description_hash,
+        expiry_secs,
+        destination: hex::encode(recovered),
+    })
+}
+
+/// Why a payment could not be verified against its invoice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightningRejection {
+    InvalidPreimage,
+    PreimageMismatch,
+    InvoiceExpired,
+    NotSettled,
+}
+
+impl LightningRejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::InvalidPreimage => "invalid_preimage",
+            Self::PreimageMismatch => "preimage_mismatch",
+            Self::InvoiceExpired => "invoice_expired",
+            Self::NotSettled => "invoice_not_settled",
+        }
+    }
+}
+
+/// Checks that `sha256(preimage)` matches the payment hash and the invoice is live at `now`.
+pub fn verify_preimage(
+    invoice: &Bolt11Invoice,
+    preimage_hex: &str,
+    now: u64,
+) -> Result<(), LightningRejection> {
+    let preimage = hex::decode(preimage_hex.trim_start_matches("0x"))
+        .ok()
+        .filter(|b| b.len() == 32)
+        .ok_or(LightningRejection::InvalidPreimage)?;
+    if hex::encode(Sha256::digest(&preimage)) != invoice.payment_hash {
+        return Err(LightningRejection::PreimageMismatch);
+    }
+    if invoice.is_expired(now) {
+        return Err(LightningRejection::InvoiceExpired);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LightningAction {
+    VerifyInvoice,
+    DecodeInvoice,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightningRequest {
+    pub action: LightningAction,
+    pub bolt11: String,
+    #[serde(default)]
+    pub payment_preimage: Option<String>,
+}
+
+/// Settlement lookup result from LND's `GET /v1/invoice/{r_hash}`.
+#[derive(Debug, Clone, Deserialize)]
+struct LndInvoice {
+    #[serde(default)]
+    settled: bool,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+/// Optional LND REST backend used to confirm settlement.
+#[derive(Clone)]
+pub struct LndBackend {
+    pub rest_url: String,
+    pub macaroon_hex: Option<String>,
+}
+
+/// Lightning gateway service.
+pub struct LightningGateway {
+    lnd: Option<LndBackend>,
+    storage: Option<Arc<Storage>>,
+    http_client: reqwest::Client,
+}
+
+impl LightningGateway {
+    pub fn new(lnd: Option<LndBackend>, storage: Option<Arc<Storage>>) -> Self {
+        Self {
+            lnd: lnd.map(|b| LndBackend {
+                rest_url: b.rest_url.trim_end_matches('/').to_string(),
+                ..b
+            }),
+            storage,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// `Some(settled)` from LND, or `None` when no backend is configured or LND
+    /// does not know the invoice.
+    async fn lookup_settlement(&self, payment_hash: &str) -> Result<Option<bool>, ServiceError> {
+        let Some(lnd) = &self.lnd else {
+            return Ok(None);
+        };
+        let url = format!("{}/v1/invoice/{}", lnd.rest_url, payment_hash);
+        let mut req = self.http_client.get(&url);
+        if let Some(macaroon) = &lnd.macaroon_hex {
+            req = req.header("Grpc-Metadata-macaroon", macaroon);
+        }
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| ServiceError::upstream(format!("LND: {}", e)))?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(ServiceError::upstream(format!(
+                "LND returned {}",
+                resp.status()
+            )));
+        }
+        let invoice: LndInvoice = resp
+            .json()
+            .await
+            .map_err(|e| ServiceError::Internal(format!("Invalid LND invoice payload: {}", e)))?;
+        Ok(Some(
+            invoice.settled || invoice.state.as_deref() == Some("SETTLED"),
+        ))
+    }
+
+    async fn persist(&self, payment_hash: &str, verified: bool, body: &serde_json::Value) {
+        if let Some(storage) = &self.storage {
+            let reason = body["reason"].as_str();
+            if let Err(e) =
+                record_verification(storage, "lightning", payment_hash, verified, reason, body)
+                    .await
+            {
+                tracing::warn!(error = %e, payment_hash, "Failed to persist Lightning verification");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl GatewayService for LightningGateway {
+    fn name(&self) -> &'static str {
+        "lightning"
+    }
+
+    fn status(&self) -> ServiceStatus {
+        ServiceStatus {
+            service_name: "Lightning".to_string(),
+            status: if self.lnd.is_some() {
+                "Active".to_string()
+            } else {
+                "Active (decode only)".to_string()
+            },
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError> {
+        let request: LightningRequest = serde_json::from_str(payload)
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid Lightning request: {}", e)))?;
+        let invoice = decode_invoice(&request.bolt11)
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid BOLT11 invoice: {}", e)))?;
+
+        if request.action == LightningAction::DecodeInvoice {
+            return Ok(ServiceResponse::json(json!({
+                "action": "decode_invoice",
+                "invoice": invoice,
+            })));
+        }
+
+        let preimage = request.payment_preimage.as_deref().ok_or_else(|| {
+            ServiceError::BadRequest("payment_preimage is required for verify_invoice".to_string())
+        })?;
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let settled = self.lookup_settlement(&invoice.payment_hash).await?;
+
+        let verdict = verify_preimage(&invoice, preimage, now).and_then(|()| match settled {
+            Some(false) => Err(LightningRejection::NotSettled),
+            _ => Ok(()),
+        });
+        let body = json!({
+            "action": "verify_invoice",
+            "verified": verdict.is_ok(),
+            "reason": verdict.err().map(|r| r.reason()),
+            "payment_hash": invoice.payment_hash,
+            "amount_msat": invoice.amount_msat,
+            "destination": invoice.destination,
+            "expires_at": invoice.expires_at(),
+            "settled": settled,
+        });
+        self.persist(&invoice.payment_hash, verdict.is_ok(), &body)
+            .await;
+        Ok(ServiceResponse::json(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BOLT11 spec example: donation invoice without amount.
+    const SPEC_DONATION: &str = "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq8rkx3yf5tcsyz3d73gafnh3cax9rn449d9p5uxz9ezhhypd0elx87sjle52x86fux2ypatgddc6k63n7erqz25le42c4u4ecky03ylcqca784w";
+
+    /// 2500u invoice, 60s expiry, preimage 0x01 * 32, signed with the spec node key.
+    const COFFEE: &str = "lnbc2500u1pvjluezpp5wtxkappzcsrlkmgfs6g0zyct0hkhashh7hsaxz7e65slq9fkx7fsdq5xysxxatsyp3k7enxv4jsxqzpud96hndzkpjzaas3r2marf90dz4tgg099546hlj6nqw6dkqh3c89zt8zdjdn0cg95ckxq98asdx0zkq2uatr0dclwqgx77j5dvrt8rugp42e0gg";
+    const COFFEE_TIMESTAMP: u64 = 1_496_314_658;
+
+    /// 10n invoice for the same payment hash with a ~126 year expiry.
+    const LONG_LIVED: &str = "lnbc10n1pvjluezpp5wtxkappzcsrlkmgfs6g0zyct0hkhashh7hsaxz7e65slq9fkx7fsdqsd3hkueedd35hvetyxq8rhxk2qqt6hrv0t8ny02s5ca23zaxfgurypce4gglr6y4ykncvnuqmlgcc84scuwxvf4yd9pm2mvnfsffa3ljaqx8afzh9lpd33xlh95jmuvdvcpjlqtg0";
+
+    const SPEC_NODE_KEY: &str =
+        "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+
+    fn coffee_preimage() -> String {
+        hex::encode([1u8; 32])
+    }
+
+    #[test]
+    fn test_decode_spec_vector() {
+        let invoice = decode_invoice(SPEC_DONATION).unwrap();
+        assert_eq!(invoice.network, "bc");
+        assert_eq!(invoice.amount_msat, None);
+        assert_eq!(invoice.timestamp, COFFEE_TIMESTAMP);
+        assert_eq!(
+            invoice.payment_hash,
+            "0001020304050607080900010203040506070809000102030405060708090102"
+        );
+        assert_eq!(
+            invoice.description.as_deref(),
+            Some("Please consider supporting this project")
+        );
+        assert_eq!(invoice.expiry_secs, DEFAULT_INVOICE_EXPIRY_SECS);
+        assert_eq!(invoice.destination, SPEC_NODE_KEY);
+    }
+
+    #[test]
+    fn test_decode_amount_and_expiry() {
+        let invoice = decode_invoice(COFFEE).unwrap();
+        assert_eq!(invoice.amount_msat, Some(250_000_000));
+        assert_eq!(invoice.expiry_secs, 60);
+        assert_eq!(invoice.description.as_deref(), Some("1 cup coffee"));
+        assert_eq!(invoice.destination, SPEC_NODE_KEY);
+    }
+
+    #[test]
+    fn test_corrupted_invoice_fails_checksum() {
+        let mut tampered = COFFEE.to_string();
+        tampered.replace_range(20..21, "q");
+        assert!(matches!(
+            decode_invoice(&tampered),
+            Err(Bolt11Error::Bech32(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_hrp_amounts() {
+        assert_eq!(parse_hrp("lnbc").unwrap(), ("bc".to_string(), None));
+        assert_eq!(
+            parse_hrp("lntb20m").unwrap(),
+            ("tb".to_string(), Some(2_000_000_000))
+        );
+        assert_eq!(
+            parse_hrp("lnbcrt10p").unwrap(),
+            ("bcrt".to_string(), Some(1))
+        );
+        assert_eq!(parse_hrp("lnbc1p"), Err(Bolt11Error::InvalidAmount));
+        assert_eq!(parse_hrp("bc1"), Err(Bolt11Error::InvalidPrefix));
+    }
+
+    #[test]
+    fn test_verify_preimage_outcomes() {
+        let invoice = decode_invoice(COFFEE).unwrap();
+        let live = COFFEE_TIMESTAMP + 10;
+
+        assert_eq!(verify_preimage(&invoice, &coffee_preimage(), live), Ok(()));
+        assert_eq!(
+            verify_preimage(&invoice, &hex::encode([2u8; 32]), live),
+            Err(LightningRejection::PreimageMismatch)
+        );
+        assert_eq!(
+            verify_preimage(&invoice, "abcd", live),
+            Err(LightningRejection::InvalidPreimage)
+        );
+        assert_eq!(
+            verify_preimage(&invoice, &coffee_preimage(), COFFEE_TIMESTAMP + 60),
+            Err(LightningRejection::InvoiceExpired)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_service_reports_expired_invoice() {
+        let gateway = LightningGateway::new(None, None);
+        let payload = json!({
+            "action": "verify_invoice",
+            "bolt11": COFFEE,
+            "payment_preimage": coffee_preimage(),
+        });
+        let body = gateway
+            .handle_request(&payload.to_string())
+            .await
+            .unwrap()
+            .body;
+        assert_eq!(body["verified"], false);
+        assert_eq!(body["reason"], "invoice_expired");
+        assert_eq!(body["settled"], serde_json::Value::Null);
+    }
+
+    async fn spawn_mock_lnd(settled: bool) -> String {
+        use axum::{routing::get, Json, Router};
+        let app = Router::new().route(
+            "/v1/invoice/{r_hash}",
+            get(move || async move {
+                Json(json!({
+                    "settled": settled,
+                    "state": if settled { "SETTLED" } else { "OPEN" },
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    async fn verify_long_lived(settled: bool, preimage: String) -> serde_json::Value {
+        let gateway = LightningGateway::new(
+            Some(LndBackend {
+                rest_url: spawn_mock_lnd(settled).await,
+                macaroon_hex: None,
+            }),
+            None,
+        );
+        let payload = json!({
+            "action": "verify_invoice",
+            "bolt11": LONG_LIVED,
+            "payment_preimage": preimage,
+        });
+        gateway
+            .handle_request(&payload.to_string())
+            .await
+            .unwrap()
+            .body
+    }
+
+    #[tokio::test]
+    async fn test_settled_invoice_with_correct_preimage_is_verified() {
+        let body = verify_long_lived(true, coffee_preimage()).await;
+        assert_eq!(body["verified"], true);
+        assert_eq!(body["settled"], true);
+        assert_eq!(body["amount_msat"], 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_unsettled_or_wrong_preimage_is_rejected() {
+        let open = verify_long_lived(false, coffee_preimage()).await;
+        assert_eq!(open["reason"], "invoice_not_settled");
+
+        let wrong = verify_long_lived(true, hex::encode([9u8; 32])).await;
+        assert_eq!(wrong["verified"], false);
+        assert_eq!(wrong["reason"], "preimage_mismatch");
+    }
+
+    #[tokio::test]
+    async fn test_service_decode_and_bad_requests() {
+        let gateway = LightningGateway::new(None, None);
+        let decoded = gateway
+            .handle_request(&json!({"action": "decode_invoice", "bolt11": COFFEE}).to_string())
+            .await
+            .unwrap()
+            .body;
+        assert_eq!(decoded["invoice"]["amount_msat"], 250_000_000);
+
+        let missing_preimage = gateway
+            .handle_request(&json!({"action": "verify_invoice", "bolt11": COFFEE}).to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(missing_preimage.kind(), "bad_request");
+
+        let garbage = gateway
+            .handle_request(r#"{"action": "decode_invoice", "bolt11": "lnbc1xyz"}"#)
+            .await
+            .unwrap_err();
+        assert_eq!(garbage.kind(), "bad_request");
+    }
+}
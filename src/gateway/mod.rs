@@ -0,0 +1,716 @@
+//! [NEXUS-GW] Nexus-side gateway front-ends.
+//! Typed request protocols layered over the lib-conxian-core gateway services,
+//! dispatched through an async, fallible service registry.
+
+pub mod audit;
+pub mod bisq;
+pub mod bitvm;
+pub mod breaker;
+pub mod dlc;
+pub mod fees;
+pub mod lightning;
+pub mod rgb;
+
+use crate::config::{Config, ENV_FEE_PAYOUT_PRIVATE_KEY_HEX};
+use crate::latency::timed_query;
+use crate::signing::{MessageSigner, RotatingSigner};
+use crate::state::NexusState;
+use crate::storage::store::NexusStore;
+use crate::storage::Storage;
+use anyhow::Context;
+use async_trait::async_trait;
+use audit::{AuditSink, GatewayMetrics, GatewayRequestRecord, PgAuditSink};
+use breaker::{BreakerConfig, BreakerSnapshot, CircuitBreaker};
+use fees::{FeeLedger, FeeSummary, PgFeeLedger};
+use lib_conxian_core::gateway::{ConxianService, ServiceStatus};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Registry keys of the services shipped with Nexus, enabled unless `ENABLED_SERVICES` narrows them.
+pub const BUILTIN_SERVICES: &[&str] = &["bisq", "bitvm", "dlc", "lightning", "rgb"];
+
+/// Structured response returned by a gateway service.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ServiceResponse {
+    pub body: Value,
+}
+
+impl ServiceResponse {
+    pub fn json(body: Value) -> Self {
+        Self { body }
+    }
+
+    /// Wraps raw service output, preserving it as JSON when it parses as such.
+    pub fn from_text(output: String) -> Self {
+        match serde_json::from_str::<Value>(&output) {
+            Ok(body) => Self { body },
+            Err(_) => Self {
+                body: serde_json::json!({ "message": output }),
+            },
+        }
+    }
+}
+
+/// Failure modes a gateway service can report.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceError {
+    /// The caller sent a malformed or invalid request.
+    BadRequest(String),
+    /// A backing daemon/node could not be reached. `retry_after` is set when the
+    /// service's circuit breaker is open.
+    UpstreamUnavailable {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Anything else that went wrong inside the service.
+    Internal(String),
+}
+
+impl ServiceError {
+    pub fn upstream(message: impl Into<String>) -> Self {
+        Self::UpstreamUnavailable {
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::UpstreamUnavailable { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "bad_request",
+            Self::UpstreamUnavailable { .. } => "upstream_unavailable",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::BadRequest(msg)
+            | Self::UpstreamUnavailable { message: msg, .. }
+            | Self::Internal(msg) => msg,
+        }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+/// Async, fallible gateway service contract.
+#[async_trait]
+pub trait GatewayService: Send + Sync {
+    /// Registry key used to route requests (e.g. `bisq`).
+    fn name(&self) -> &'static str;
+
+    fn status(&self) -> ServiceStatus;
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError>;
+
+    /// Handles a request attributed to `caller` (an API key fingerprint). Services that
+    /// bill or rate-limit per caller override this; the default ignores the caller.
+    async fn handle_request_from(
+        &self,
+        payload: &str,
+        _caller: Option<&str>,
+    ) -> Result<ServiceResponse, ServiceError> {
+        self.handle_request(payload).await
+    }
+}
+
+/// Adapts a synchronous lib-conxian-core service to the async gateway contract.
+pub struct CoreServiceAdapter<S> {
+    name: &'static str,
+    inner: S,
+}
+
+impl<S> CoreServiceAdapter<S> {
+    pub fn new(name: &'static str, inner: S) -> Self {
+        Self { name, inner }
+    }
+}
+
+#[async_trait]
+impl<S> GatewayService for CoreServiceAdapter<S>
+where
+    S: ConxianService + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn status(&self) -> ServiceStatus {
+        self.inner.status()
+    }
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError> {
+        if serde_json::from_str::<Value>(payload).is_err() {
+            return Err(ServiceError::BadRequest(
+                "payload must be valid JSON".to_string(),
+            ));
+        }
+        Ok(ServiceResponse::from_text(
+            self.inner.handle_request(payload),
+        ))
+    }
+}
+
+/// Upserts a verification outcome into `gateway_verifications`, keyed by (service, external_id).
+pub async fn record_verification(
+    storage: &Storage,
+    service: &str,
+    external_id: &str,
+    verified: bool,
+    reason: Option<&str>,
+    details: &Value,
+) -> anyhow::Result<()> {
+    timed_query(
+        "record_verification",
+        sqlx::query(
+            "INSERT INTO gateway_verifications (service, external_id, verified, reason, details)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (service, external_id) DO UPDATE
+         SET verified = EXCLUDED.verified, reason = EXCLUDED.reason,
+             details = EXCLUDED.details, updated_at = NOW()",
+        )
+        .bind(service)
+        .bind(external_id)
+        .bind(verified)
+        .bind(reason)
+        .bind(details)
+        .execute(&storage.pg_pool),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Commits leaves proving verified gateway requests, such as `dlc:{event_id}`, to the
+/// state tree.
+#[derive(Clone)]
+pub struct LeafCommitter {
+    store: Arc<dyn NexusStore>,
+    state: Arc<NexusState>,
+}
+
+impl LeafCommitter {
+    pub fn new(store: Arc<dyn NexusStore>, state: Arc<NexusState>) -> Self {
+        Self { store, state }
+    }
+
+    /// Appends `leaf` to the store and then to the live tree under the state's commit
+    /// lock, so sync's blocks and a rebuild see the same order, and records the new root.
+    /// Returns `false`, leaving the tree alone, when the leaf was committed before.
+    pub async fn commit(&self, service: &str, leaf: &str) -> anyhow::Result<bool> {
+        let _commit = self.state.lock_commits().await;
+        if !self.store.append_gateway_leaf(service, leaf).await? {
+            return Ok(false);
+        }
+        self.state.update_state_batch(&[leaf.to_string()]);
+        let commitment = self.state.commitment();
+        if let Err(e) = self.store.save_state_root(&commitment.root).await {
+            tracing::warn!(root = %commitment.root, error = %e, "Failed to persist state root");
+        }
+        // The leaf is committed either way; the history only serves proofs at this root.
+        let recorded = match self.store.sync_watermark().await {
+            Ok(height) => {
+                self.store
+                    .record_state_root(&commitment, height.unwrap_or(0))
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = recorded {
+            tracing::warn!(root = %commitment.root, error = %e, "Failed to record state root history");
+        }
+        Ok(true)
+    }
+}
+
+/// Signer for fee payout payloads; `None` when no enabled service is metered. Startup
+/// fails when one is and the key is missing: an unsigned or ephemerally signed payout
+/// is useless to the payee verifying it.
+fn payout_signer(config: &Config) -> anyhow::Result<Option<Arc<RotatingSigner>>> {
+    let metered = config.service_fees.keys().any(|key| {
+        key.split_once('.')
+            .is_some_and(|(service, _)| config.enabled_services.iter().any(|e| e == service))
+    });
+    match &config.fee_payout_private_key_hex {
+        Some(key) => {
+            let signer = RotatingSigner::from_private_key_hex(key)
+                .with_context(|| format!("Invalid {}", ENV_FEE_PAYOUT_PRIVATE_KEY_HEX))?;
+            Ok(Some(Arc::new(signer)))
+        }
+        None if metered => anyhow::bail!(
+            "{} must be set to sign fee payouts for metered gateway services",
+            ENV_FEE_PAYOUT_PRIVATE_KEY_HEX
+        ),
+        None => Ok(None),
+    }
+}
+
+/// Named set of gateway services reachable through the API.
+#[derive(Default)]
+pub struct ServiceRegistry {
+    services: BTreeMap<&'static str, Arc<dyn GatewayService>>,
+    metrics: Arc<GatewayMetrics>,
+    audit: Option<Arc<dyn AuditSink>>,
+    breaker_config: BreakerConfig,
+    breakers: Mutex<BTreeMap<&'static str, CircuitBreaker>>,
+    fees: Option<Arc<dyn FeeLedger>>,
+    payout_signer: Option<Arc<RotatingSigner>>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry preloaded with the Bisq, RGB, BitVM, Lightning and DLC services, without
+    /// backends or persistence.
+    pub fn with_builtin_services() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(bisq::BisqGateway::new(None, None)));
+        registry.register(Arc::new(rgb::RGBGateway::with_default_schemas()));
+        registry.register(Arc::new(bitvm::BitVMGateway::default()));
+        registry.register(Arc::new(lightning::LightningGateway::new(None, None)));
+        registry.register(Arc::new(dlc::DLCGateway::new(None)));
+        registry
+    }
+
+    /// Registry wired to the configured backends, persisting verifications and the
+    /// request audit log to storage and committing accepted transfers to the Nexus state.
+    /// Fails when a metered service is enabled without a valid payout key.
+    pub fn from_config(
+        config: &Config,
+        storage: Arc<Storage>,
+        nexus_state: Arc<NexusState>,
+    ) -> anyhow::Result<Self> {
+        let mut registry = Self::with_builtin_services()
+            .with_audit_sink(Arc::new(PgAuditSink::new(storage.clone())))
+            .with_breaker_config(BreakerConfig {
+                failure_threshold: config.gateway_breaker_threshold,
+                cooldown: Duration::from_secs(config.gateway_breaker_cooldown_secs),
+            });
+        registry.register(Arc::new(bisq::BisqGateway::new(
+            config.bisq_api_url.clone(),
+            Some(storage.clone()),
+        )));
+        let leaves = LeafCommitter::new(storage.clone(), nexus_state);
+        registry.register(Arc::new(
+            rgb::RGBGateway::new(config.rgb_accepted_schemas.clone(), Some(storage.clone()))
+                .with_leaves(leaves.clone()),
+        ));
+        let lnd = config
+            .lnd_rest_url
+            .clone()
+            .map(|rest_url| lightning::LndBackend {
+                rest_url,
+                macaroon_hex: config.lnd_macaroon_hex.clone(),
+            });
+        registry.register(Arc::new(lightning::LightningGateway::new(
+            lnd,
+            Some(storage.clone()),
+        )));
+        let trusted_oracles = config
+            .dlc_oracle_pubkeys
+            .iter()
+            .filter_map(|key| hex::decode(key).ok()?.try_into().ok())
+            .collect();
+        registry.register(Arc::new(
+            dlc::DLCGateway::new(Some(storage.clone())).with_leaves(leaves, trusted_oracles),
+        ));
+        let ledger: Arc<dyn FeeLedger> = Arc::new(PgFeeLedger::new(storage));
+        let signer = payout_signer(config)?;
+        if let Some(signer) = &signer {
+            registry.register(Arc::new(bitvm::BitVMGateway::with_fees(bitvm::BitVMFees {
+                schedule: config.service_fees.clone(),
+                ledger: ledger.clone(),
+                signer: signer.clone() as Arc<dyn MessageSigner>,
+            })));
+        }
+        registry.fees = Some(ledger);
+        registry.payout_signer = signer;
+        registry.retain(&config.enabled_services);
+        Ok(registry)
+    }
+
+    /// Drops every service whose name is not in `enabled`.
+    pub fn retain(&mut self, enabled: &[String]) {
+        for name in enabled {
+            if !self.services.contains_key(name.as_str()) {
+                tracing::warn!(service = %name, "ENABLED_SERVICES names an unknown gateway service");
+            }
+        }
+        self.services
+            .retain(|name, _| enabled.iter().any(|e| e == name));
+    }
+
+    /// Records every dispatched request to `sink`.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    /// Applies `config` to every service's circuit breaker, resetting their state.
+    pub fn with_breaker_config(mut self, config: BreakerConfig) -> Self {
+        self.breaker_config = config;
+        self.breakers.get_mut().unwrap().clear();
+        self
+    }
+
+    /// Ledger backing [`Self::fee_summary`].
+    pub fn with_fee_ledger(mut self, ledger: Arc<dyn FeeLedger>) -> Self {
+        self.fees = Some(ledger);
+        self
+    }
+
+    /// Fees accrued since `since`, per caller and service; empty when metering is disabled.
+    pub async fn fee_summary(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> anyhow::Result<Vec<FeeSummary>> {
+        match &self.fees {
+            Some(ledger) => ledger.summary(since).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Key signing fee payout payloads, for rotation; `None` when metering is disabled.
+    pub fn payout_signer(&self) -> Option<&Arc<RotatingSigner>> {
+        self.payout_signer.as_ref()
+    }
+
+    pub fn register(&mut self, service: Arc<dyn GatewayService>) {
+        self.services.insert(service.name(), service);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn GatewayService>> {
+        self.services.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.services.keys().copied().collect()
+    }
+
+    pub fn statuses(&self) -> Vec<ServiceStatus> {
+        self.services.values().map(|s| s.status()).collect()
+    }
+
+    pub fn metrics(&self) -> &GatewayMetrics {
+        &self.metrics
+    }
+
+    /// Breaker state of every registered service; untouched services report closed.
+    pub fn breaker_snapshots(&self) -> Vec<BreakerSnapshot> {
+        let now = Instant::now();
+        let breakers = self.breakers.lock().unwrap();
+        self.services
+            .keys()
+            .map(|name| match breakers.get(name) {
+                Some(breaker) => breaker.snapshot(name, now),
+                None => CircuitBreaker::new(self.breaker_config).snapshot(name, now),
+            })
+            .collect()
+    }
+
+    fn with_breaker<T>(&self, name: &'static str, f: impl FnOnce(&mut CircuitBreaker) -> T) -> T {
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers
+            .entry(name)
+            .or_insert_with(|| CircuitBreaker::new(self.breaker_config));
+        let out = f(breaker);
+        breaker.publish(name);
+        out
+    }
+
+    /// Routes a payload to the named service; `None` when no such service exists.
+    pub async fn dispatch(
+        &self,
+        name: &str,
+        payload: &str,
+    ) -> Option<Result<ServiceResponse, ServiceError>> {
+        self.dispatch_from(name, payload, None).await
+    }
+
+    /// Like [`Self::dispatch`], attributing the request to `caller` (an API key
+    /// fingerprint) in the audit log. Counters are updated before returning; the
+    /// audit row is written in the background so storage latency never reaches callers.
+    ///
+    /// Requests to a service whose breaker is open fail fast with
+    /// `UpstreamUnavailable { retry_after }`. Only upstream failures count against the
+    /// breaker; any other outcome shows the backend answered and closes it.
+    pub async fn dispatch_from(
+        &self,
+        name: &str,
+        payload: &str,
+        caller: Option<String>,
+    ) -> Option<Result<ServiceResponse, ServiceError>> {
+        let service = self.get(name)?;
+        let key = service.name();
+        let started = Instant::now();
+        let result = match self.with_breaker(key, |b| b.try_acquire(started)) {
+            Err(retry_after) => Err(ServiceError::UpstreamUnavailable {
+                message: format!(
+                    "{} circuit breaker is open; retry in {}s",
+                    key,
+                    retry_after.as_secs().max(1)
+                ),
+                retry_after: Some(retry_after),
+            }),
+            Ok(()) => {
+                let result = service
+                    .handle_request_from(payload, caller.as_deref())
+                    .await;
+                self.with_breaker(key, |b| match &result {
+                    Err(err @ ServiceError::UpstreamUnavailable { .. }) => {
+                        b.record_failure(err.message(), Instant::now())
+                    }
+                    _ => b.record_success(),
+                });
+                result
+            }
+        };
+        let latency = started.elapsed();
+
+        self.metrics.observe(name, &result, latency);
+
+        if let Some(sink) = self.audit.clone() {
+            let (outcome, response) = match &result {
+                Ok(response) => ("ok", response.body.to_string()),
+                Err(err) => (err.kind(), err.message().to_string()),
+            };
+            let record =
+                GatewayRequestRecord::new(name, payload, outcome, &response, latency, caller);
+            tokio::spawn(async move {
+                if let Err(e) = sink.record(&record).await {
+                    tracing::warn!(error = %e, service = %record.service, "Failed to audit gateway request");
+                }
+            });
+        }
+
+        Some(result)
+    }
+
+    /// Most recent audited requests for a service; empty when auditing is disabled.
+    pub async fn recent_requests(
+        &self,
+        name: &str,
+        limit: i64,
+    ) -> anyhow::Result<Vec<GatewayRequestRecord>> {
+        match &self.audit {
+            Some(sink) => sink.recent(name, limit).await,
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_response_from_text() {
+        let json = ServiceResponse::from_text(r#"{"ok": true}"#.to_string());
+        assert_eq!(json.body["ok"], true);
+
+        let prose = ServiceResponse::from_text("verified".to_string());
+        assert_eq!(prose.body["message"], "verified");
+    }
+
+    #[test]
+    fn test_builtin_registry_names() {
+        let registry = ServiceRegistry::with_builtin_services();
+        assert_eq!(registry.names(), BUILTIN_SERVICES.to_vec());
+    }
+
+    #[test]
+    fn test_retain_filters_to_enabled_services() {
+        let mut registry = ServiceRegistry::with_builtin_services();
+        registry.retain(&["lightning".to_string(), "bisq".to_string()]);
+        assert_eq!(registry.names(), vec!["bisq", "lightning"]);
+    }
+
+    #[test]
+    fn test_metered_services_require_payout_key() {
+        let mut config = Config::default_test();
+        config.fee_payout_private_key_hex = None;
+        assert!(payout_signer(&config).is_err());
+
+        config.enabled_services.retain(|service| service != "bitvm");
+        assert!(payout_signer(&config).unwrap().is_none());
+
+        config.fee_payout_private_key_hex = Some("07".repeat(32));
+        assert!(payout_signer(&config).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_unknown_service_returns_none() {
+        let registry = ServiceRegistry::with_builtin_services();
+        assert!(registry.dispatch("nope", "{}").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_core_adapter_rejects_non_json() {
+        let adapter = CoreServiceAdapter::new("rgb-core", lib_conxian_core::gateway::RGBService);
+        let err = adapter.handle_request("not json").await.unwrap_err();
+        assert_eq!(err.kind(), "bad_request");
+    }
+
+    #[tokio::test]
+    async fn test_bitvm_dispatch_maps_protocol_errors() {
+        let registry = ServiceRegistry::with_builtin_services();
+        let err = registry
+            .dispatch("bitvm", r#"{"command": "settle"}"#)
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_writes_one_audit_row_and_moves_counters() {
+        let sink = Arc::new(audit::MemoryAuditSink::default());
+        let registry = ServiceRegistry::with_builtin_services().with_audit_sink(sink.clone());
+
+        let result = registry
+            .dispatch_from(
+                "bitvm",
+                r#"{"command": "settle"}"#,
+                Some(audit::caller_fingerprint("nx_key_test")),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_err());
+
+        for _ in 0..50 {
+            if !sink.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(sink.len(), 1);
+
+        let rows = registry.recent_requests("bitvm", 10).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].outcome, "bad_request");
+        assert_eq!(
+            rows[0].request_digest,
+            audit::request_digest(r#"{"command": "settle"}"#)
+        );
+
+        let counters = registry.metrics().service("bitvm").unwrap();
+        assert_eq!((counters.requests, counters.failures), (1, 1));
+    }
+
+    /// Backend that reports upstream outages while `down` is set.
+    struct FlakyBackend {
+        down: std::sync::atomic::AtomicBool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl GatewayService for FlakyBackend {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn status(&self) -> ServiceStatus {
+            ServiceStatus {
+                service_name: "Flaky".to_string(),
+                status: "Active".to_string(),
+                version: "0".to_string(),
+            }
+        }
+
+        async fn handle_request(&self, _payload: &str) -> Result<ServiceResponse, ServiceError> {
+            use std::sync::atomic::Ordering;
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                Err(ServiceError::upstream("connection refused"))
+            } else {
+                Ok(ServiceResponse::json(serde_json::json!({ "ok": true })))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_fails_fast_and_recovers_via_half_open_probe() {
+        use breaker::BreakerState;
+        use std::sync::atomic::Ordering;
+
+        let backend = Arc::new(FlakyBackend {
+            down: true.into(),
+            calls: 0.into(),
+        });
+        let mut registry = ServiceRegistry::new().with_breaker_config(BreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_millis(50),
+        });
+        registry.register(backend.clone());
+        let state = |registry: &ServiceRegistry| registry.breaker_snapshots()[0].state;
+
+        for _ in 0..2 {
+            let err = registry.dispatch("flaky", "{}").await.unwrap().unwrap_err();
+            assert_eq!(err.retry_after(), None);
+        }
+        assert_eq!(state(&registry), BreakerState::Open);
+        let snapshot = &registry.breaker_snapshots()[0];
+        assert_eq!(snapshot.failure_streak, 2);
+        assert_eq!(snapshot.last_error.as_deref(), Some("connection refused"));
+
+        // Open: fails fast without reaching the backend.
+        let err = registry.dispatch("flaky", "{}").await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), "upstream_unavailable");
+        assert!(err.retry_after().is_some());
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+
+        // Half-open probe fails: re-opens.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(registry.dispatch("flaky", "{}").await.unwrap().is_err());
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(state(&registry), BreakerState::Open);
+
+        // Half-open probe succeeds: closes.
+        backend.down.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(registry.dispatch("flaky", "{}").await.unwrap().is_ok());
+        assert_eq!(state(&registry), BreakerState::Closed);
+        assert_eq!(registry.breaker_snapshots()[0].failure_streak, 0);
+
+        let counters = registry.metrics().service("flaky").unwrap();
+        assert_eq!((counters.requests, counters.failures), (5, 4));
+    }
+    #[tokio::test]
+    async fn test_bad_requests_do_not_open_breaker() {
+        let registry =
+            ServiceRegistry::with_builtin_services().with_breaker_config(BreakerConfig {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(30),
+            });
+        for _ in 0..5 {
+            let err = registry
+                .dispatch("bitvm", r#"{"command": "settle"}"#)
+                .await
+                .unwrap()
+                .unwrap_err();
+            assert_eq!(err.kind(), "bad_request");
+        }
+        assert!(registry
+            .breaker_snapshots()
+            .iter()
+            .all(|b| b.state == breaker::BreakerState::Closed && b.failure_streak == 0));
+    }
+}
@@ -0,0 +1,413 @@
+//! [NEXUS-GW-03] RGB consignment parsing and schema validation.
+//!
+//! Consignments use the Nexus v1 envelope:
+//! `b"RGBC" | version:u8 | schema_len:u8 | schema | asset_len:u8 | asset_id | amount:u64be | payload`.
+//! Accepted transfers are recorded and, when an authenticated caller submits them,
+//! committed to the state tree once as `rgb:{asset_id}:{txid}`.
+
+use super::{record_verification, GatewayService, LeafCommitter, ServiceError, ServiceResponse};
+use crate::storage::Storage;
+use async_trait::async_trait;
+use base64::Engine;
+use lib_conxian_core::gateway::{ConxianService, RGBService, ServiceStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+pub const CONSIGNMENT_MAGIC: &[u8; 4] = b"RGBC";
+pub const CONSIGNMENT_VERSION: u8 = 1;
+/// Upper bound keeps amounts representable in Postgres BIGINT columns.
+pub const MAX_TRANSFER_AMOUNT: u64 = i64::MAX as u64;
+pub const DEFAULT_ACCEPTED_SCHEMAS: &[&str] = &["NIA", "LNPBP"];
+
+/// Reason codes for rejected consignments.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum RejectReason {
+    BadEncoding,
+    SchemaMismatch,
+    AssetMismatch,
+    AmountOverflow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejection {
+    pub reason: RejectReason,
+    pub detail: String,
+}
+
+fn reject(reason: RejectReason, detail: impl Into<String>) -> Rejection {
+    Rejection {
+        reason,
+        detail: detail.into(),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RGBTransferRequest {
+    pub asset_id: String,
+    pub amount: u64,
+    pub txid: String,
+    pub schema: String,
+    #[serde(default)]
+    pub consignment_hex: Option<String>,
+    #[serde(default)]
+    pub consignment_base64: Option<String>,
+}
+
+/// Header fields decoded from a consignment envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Consignment {
+    pub version: u8,
+    pub schema_id: String,
+    pub asset_id: String,
+    pub amount: u64,
+}
+
+/// Decodes the hex or base64 consignment blob from the request.
+pub fn decode_blob(request: &RGBTransferRequest) -> Result<Vec<u8>, Rejection> {
+    match (&request.consignment_hex, &request.consignment_base64) {
+        (Some(hex_blob), None) => {
+            let raw = hex_blob.strip_prefix("0x").unwrap_or(hex_blob);
+            hex::decode(raw).map_err(|e| reject(RejectReason::BadEncoding, e.to_string()))
+        }
+        (None, Some(b64)) => base64::engine::general_purpose::STANDARD
+            .decode(b64)
+            .map_err(|e| reject(RejectReason::BadEncoding, e.to_string())),
+        _ => Err(reject(
+            RejectReason::BadEncoding,
+            "exactly one of consignment_hex or consignment_base64 is required",
+        )),
+    }
+}
+
+fn read_prefixed<'a>(
+    bytes: &'a [u8],
+    cursor: &mut usize,
+    field: &str,
+) -> Result<&'a str, Rejection> {
+    let len = *bytes.get(*cursor).ok_or_else(|| {
+        reject(
+            RejectReason::BadEncoding,
+            format!("truncated {} length", field),
+        )
+    })? as usize;
+    *cursor += 1;
+    let raw = bytes
+        .get(*cursor..*cursor + len)
+        .ok_or_else(|| reject(RejectReason::BadEncoding, format!("truncated {}", field)))?;
+    *cursor += len;
+    std::str::from_utf8(raw)
+        .map_err(|_| reject(RejectReason::BadEncoding, format!("{} is not utf-8", field)))
+}
+
+/// Structurally parses a consignment envelope.
+pub fn parse_consignment(bytes: &[u8]) -> Result<Consignment, Rejection> {
+    if bytes.len() < CONSIGNMENT_MAGIC.len() + 1 || !bytes.starts_with(CONSIGNMENT_MAGIC) {
+        return Err(reject(
+            RejectReason::BadEncoding,
+            "missing consignment magic bytes",
+        ));
+    }
+    let version = bytes[4];
+    if version != CONSIGNMENT_VERSION {
+        return Err(reject(
+            RejectReason::BadEncoding,
+            format!("unsupported consignment version {}", version),
+        ));
+    }
+
+    let mut cursor = 5;
+    let schema_id = read_prefixed(bytes, &mut cursor, "schema id")?.to_string();
+    let asset_id = read_prefixed(bytes, &mut cursor, "asset id")?.to_string();
+    let amount_bytes: [u8; 8] = bytes
+        .get(cursor..cursor + 8)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| reject(RejectReason::BadEncoding, "truncated amount"))?;
+
+    Ok(Consignment {
+        version,
+        schema_id,
+        asset_id,
+        amount: u64::from_be_bytes(amount_bytes),
+    })
+}
+
+/// Validates a transfer request and its consignment against the accepted schemas.
+pub fn validate_transfer(
+    request: &RGBTransferRequest,
+    accepted_schemas: &[String],
+) -> Result<Consignment, Rejection> {
+    if !accepted_schemas.iter().any(|s| s == &request.schema) {
+        return Err(reject(
+            RejectReason::SchemaMismatch,
+            format!("schema '{}' is not accepted", request.schema),
+        ));
+    }
+
+    let consignment = parse_consignment(&decode_blob(request)?)?;
+    if consignment.schema_id != request.schema {
+        return Err(reject(
+            RejectReason::SchemaMismatch,
+            format!(
+                "consignment schema '{}' does not match declared '{}'",
+                consignment.schema_id, request.schema
+            ),
+        ));
+    }
+    if consignment.asset_id != request.asset_id {
+        return Err(reject(
+            RejectReason::AssetMismatch,
+            "consignment asset id does not match declared asset_id",
+        ));
+    }
+    if consignment.amount == 0
+        || consignment.amount > MAX_TRANSFER_AMOUNT
+        || consignment.amount != request.amount
+    {
+        return Err(reject(
+            RejectReason::AmountOverflow,
+            format!(
+                "consignment amount {} is out of bounds or differs from declared {}",
+                consignment.amount, request.amount
+            ),
+        ));
+    }
+    Ok(consignment)
+}
+
+/// RGB gateway service validating consignments before accepting transfers.
+pub struct RGBGateway {
+    accepted_schemas: Vec<String>,
+    storage: Option<Arc<Storage>>,
+    leaves: Option<LeafCommitter>,
+}
+
+impl RGBGateway {
+    pub fn new(accepted_schemas: Vec<String>, storage: Option<Arc<Storage>>) -> Self {
+        Self {
+            accepted_schemas,
+            storage,
+            leaves: None,
+        }
+    }
+
+    /// Commits accepted transfers through `leaves`.
+    pub fn with_leaves(mut self, leaves: LeafCommitter) -> Self {
+        self.leaves = Some(leaves);
+        self
+    }
+
+    pub fn with_default_schemas() -> Self {
+        Self::new(
+            DEFAULT_ACCEPTED_SCHEMAS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            None,
+        )
+    }
+}
+
+#[async_trait]
+impl GatewayService for RGBGateway {
+    fn name(&self) -> &'static str {
+        "rgb"
+    }
+
+    fn status(&self) -> ServiceStatus {
+        RGBService.status()
+    }
+
+    async fn handle_request(&self, payload: &str) -> Result<ServiceResponse, ServiceError> {
+        self.handle_request_from(payload, None).await
+    }
+
+    /// Validates the transfer and, when `caller` is set, commits its leaf unless an
+    /// earlier request did.
+    async fn handle_request_from(
+        &self,
+        payload: &str,
+        caller: Option<&str>,
+    ) -> Result<ServiceResponse, ServiceError> {
+        let request: RGBTransferRequest = serde_json::from_str(payload)
+            .map_err(|e| ServiceError::BadRequest(format!("Invalid RGB request: {}", e)))?;
+        if request.txid.len() != 64 || hex::decode(&request.txid).is_err() {
+            return Err(ServiceError::BadRequest(
+                "txid must be 64 hex characters".to_string(),
+            ));
+        }
+
+        let consignment = match validate_transfer(&request, &self.accepted_schemas) {
+            Ok(consignment) => consignment,
+            Err(rejection) => {
+                return Ok(ServiceResponse::json(json!({
+                    "asset_id": request.asset_id,
+                    "txid": request.txid,
+                    "verified": false,
+                    "reason": rejection.reason,
+                    "detail": rejection.detail,
+                })));
+            }
+        };
+
+        let leaf = format!("rgb:{}:{}", request.asset_id, request.txid);
+        let committed = match (&self.leaves, caller) {
+            (Some(leaves), Some(_)) => leaves.commit("rgb", &leaf).await.map_err(|e| {
+                tracing::error!(error = %e, leaf = %leaf, "Failed to commit RGB transfer");
+                ServiceError::Internal("failed to commit transfer".to_string())
+            })?,
+            _ => false,
+        };
+        let body = json!({
+            "asset_id": request.asset_id,
+            "txid": request.txid,
+            "verified": true,
+            "schema": consignment.schema_id,
+            "amount": consignment.amount,
+            "leaf": leaf,
+            "committed": committed,
+        });
+
+        if let Some(storage) = &self.storage {
+            let external_id = format!("{}:{}", request.asset_id, request.txid);
+            if let Err(e) =
+                record_verification(storage, "rgb", &external_id, true, None, &body).await
+            {
+                tracing::warn!(error = %e, "Failed to persist RGB transfer verification");
+            }
+        }
+
+        Ok(ServiceResponse::json(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+    fn build_consignment(schema: &str, asset_id: &str, amount: u64) -> Vec<u8> {
+        let mut bytes = CONSIGNMENT_MAGIC.to_vec();
+        bytes.push(CONSIGNMENT_VERSION);
+        bytes.push(schema.len() as u8);
+        bytes.extend_from_slice(schema.as_bytes());
+        bytes.push(asset_id.len() as u8);
+        bytes.extend_from_slice(asset_id.as_bytes());
+        bytes.extend_from_slice(&amount.to_be_bytes());
+        bytes.extend_from_slice(b"transition-payload");
+        bytes
+    }
+
+    fn request(schema: &str, amount: u64, blob: &[u8]) -> serde_json::Value {
+        json!({
+            "asset_id": "rgb:asset-1",
+            "amount": amount,
+            "txid": TXID,
+            "schema": schema,
+            "consignment_hex": hex::encode(blob),
+        })
+    }
+
+    async fn submit(gateway: &RGBGateway, payload: serde_json::Value) -> serde_json::Value {
+        gateway
+            .handle_request(&payload.to_string())
+            .await
+            .unwrap()
+            .body
+    }
+
+    #[tokio::test]
+    async fn test_valid_consignment_is_accepted_and_committed_once() {
+        use crate::state::NexusState;
+        use crate::storage::store::{InMemoryStore, NexusStore};
+
+        let store = Arc::new(InMemoryStore::new());
+        let state = Arc::new(NexusState::new());
+        let gateway = RGBGateway::new(vec!["NIA".to_string()], None)
+            .with_leaves(LeafCommitter::new(store.clone(), state.clone()));
+        let blob = build_consignment("NIA", "rgb:asset-1", 500);
+        let payload = request("NIA", 500, &blob).to_string();
+
+        // Verified, but not committed without an authenticated caller.
+        let body = submit(&gateway, request("NIA", 500, &blob)).await;
+        assert_eq!(body["verified"], true);
+        assert_eq!(body["committed"], false);
+        assert_eq!(state.leaf_count(), 0);
+
+        let leaf = format!("rgb:rgb:asset-1:{}", TXID);
+        for (caller, committed) in [("caller-a", true), ("caller-b", false)] {
+            let body = gateway
+                .handle_request_from(&payload, Some(caller))
+                .await
+                .unwrap()
+                .body;
+            assert_eq!(body["committed"], committed);
+        }
+        assert!(state.get_leaf_index(&leaf).is_some());
+        assert_eq!(state.leaf_count(), 1);
+        assert_eq!(store.state_leaves().await.unwrap(), vec![leaf]);
+    }
+
+    #[tokio::test]
+    async fn test_base64_consignment_is_accepted() {
+        let gateway = RGBGateway::with_default_schemas();
+        let blob = build_consignment("LNPBP", "rgb:asset-1", 7);
+        let payload = json!({
+            "asset_id": "rgb:asset-1",
+            "amount": 7,
+            "txid": TXID,
+            "schema": "LNPBP",
+            "consignment_base64": base64::engine::general_purpose::STANDARD.encode(&blob),
+        });
+        assert_eq!(submit(&gateway, payload).await["verified"], true);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_encoding() {
+        let gateway = RGBGateway::with_default_schemas();
+        let mut payload = request("NIA", 1, b"");
+        payload["consignment_hex"] = json!("not-hex");
+        assert_eq!(submit(&gateway, payload).await["reason"], "BadEncoding");
+
+        let mut wrong_magic = build_consignment("NIA", "rgb:asset-1", 1);
+        wrong_magic[0] = b'X';
+        let body = submit(&gateway, request("NIA", 1, &wrong_magic)).await;
+        assert_eq!(body["reason"], "BadEncoding");
+
+        let truncated = &build_consignment("NIA", "rgb:asset-1", 1)[..12];
+        let body = submit(&gateway, request("NIA", 1, truncated)).await;
+        assert_eq!(body["reason"], "BadEncoding");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_schema_mismatch() {
+        let gateway = RGBGateway::new(vec!["NIA".to_string()], None);
+        let blob = build_consignment("LNPBP", "rgb:asset-1", 1);
+        let body = submit(&gateway, request("LNPBP", 1, &blob)).await;
+        assert_eq!(body["reason"], "SchemaMismatch");
+
+        let body = submit(&gateway, request("NIA", 1, &blob)).await;
+        assert_eq!(body["reason"], "SchemaMismatch");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_amount_overflow() {
+        let gateway = RGBGateway::with_default_schemas();
+        let blob = build_consignment("NIA", "rgb:asset-1", u64::MAX);
+        let body = submit(&gateway, request("NIA", u64::MAX, &blob)).await;
+        assert_eq!(body["reason"], "AmountOverflow");
+
+        let blob = build_consignment("NIA", "rgb:asset-1", 10);
+        let body = submit(&gateway, request("NIA", 11, &blob)).await;
+        assert_eq!(body["reason"], "AmountOverflow");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_asset_mismatch() {
+        let gateway = RGBGateway::with_default_schemas();
+        let blob = build_consignment("NIA", "rgb:other", 10);
+        let body = submit(&gateway, request("NIA", 10, &blob)).await;
+        assert_eq!(body["reason"], "AssetMismatch");
+    }
+}
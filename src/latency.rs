@@ -0,0 +1,429 @@
+//! Latency of REST routes, gRPC methods, named Postgres queries and sync block
+//! processing. Every sample goes into a Prometheus histogram for `histogram_quantile`
+//! queries, and into a window of recent samples from which `/v1/metrics` reports
+//! p50/p95/p99 directly, so a proof endpoint slowing down as the tree grows shows up
+//! without a Prometheus server. Queries slower than the configured threshold are also
+//! logged by name and duration; bound values never are.
+
+use axum::{body::Body, extract::MatchedPath, http::Request, middleware::Next, response::Response};
+use prometheus::{
+    histogram_opts, register_histogram, register_histogram_vec, Histogram, HistogramVec,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Recent samples kept per route (and for sync) for percentile estimates.
+pub const LATENCY_WINDOW: usize = 1024;
+
+/// Window name of sync block processing.
+const SYNC_BLOCK: &str = "sync_block";
+
+/// Queries reported by [`slowest_queries`] in `/v1/metrics`.
+pub const SLOWEST_QUERIES: usize = 5;
+
+/// Default for `DATABASE_SLOW_QUERY_MS`.
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 500;
+
+/// Sub-millisecond proof lookups up to multi-second rebuilds.
+const BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+lazy_static::lazy_static! {
+    static ref HTTP_LATENCY: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "nexus_http_request_duration_seconds",
+            "REST request latency by route template",
+            BUCKETS.to_vec()
+        ),
+        &["method", "route"]
+    )
+    .unwrap();
+    static ref GRPC_LATENCY: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "nexus_grpc_request_duration_seconds",
+            "gRPC call latency by method",
+            BUCKETS.to_vec()
+        ),
+        &["method"]
+    )
+    .unwrap();
+    static ref QUERY_LATENCY: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "nexus_db_query_duration_seconds",
+            "Postgres query latency by query name",
+            BUCKETS.to_vec()
+        ),
+        &["query"]
+    )
+    .unwrap();
+    static ref SYNC_BLOCK_LATENCY: Histogram = register_histogram!(histogram_opts!(
+        "nexus_sync_block_duration_seconds",
+        "Time to ingest one microblock and fold it into the state tree",
+        BUCKETS.to_vec()
+    ))
+    .unwrap();
+}
+
+/// What a window times; windows of different kinds never share a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Route,
+    Grpc,
+    Query,
+    Sync,
+}
+
+static WINDOWS: Mutex<BTreeMap<(Kind, String), VecDeque<Duration>>> = Mutex::new(BTreeMap::new());
+
+static SLOW_QUERY_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_MS);
+
+tokio::task_local! {
+    /// Threshold set by [`with_slow_query_threshold`] for the task it runs in.
+    static SCOPED_SLOW_QUERY_MS: u64;
+}
+
+/// Percentiles over the last [`LATENCY_WINDOW`] samples, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencySnapshot {
+    /// `METHOD /route/template`, the gRPC method path, the query name, or `sync_block`.
+    pub name: String,
+    pub samples: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+fn observe_window(kind: Kind, name: String, elapsed: Duration) {
+    let mut windows = WINDOWS.lock().unwrap();
+    let window = windows.entry((kind, name)).or_default();
+    if window.len() == LATENCY_WINDOW {
+        window.pop_front();
+    }
+    window.push_back(elapsed);
+}
+
+/// Records one REST request; `route` is the matched template, never the raw path, so
+/// ids in paths do not explode label cardinality.
+pub fn observe_request(method: &str, route: &str, elapsed: Duration) {
+    HTTP_LATENCY
+        .with_label_values(&[method, route])
+        .observe(elapsed.as_secs_f64());
+    observe_window(Kind::Route, format!("{} {}", method, route), elapsed);
+}
+
+/// Records one gRPC call to `method`, e.g. `/nexus.NexusService/GetProof`.
+pub fn observe_grpc(method: &str, elapsed: Duration) {
+    GRPC_LATENCY
+        .with_label_values(&[method])
+        .observe(elapsed.as_secs_f64());
+    observe_window(Kind::Grpc, method.to_string(), elapsed);
+}
+
+/// Records one run of the query `name`, logging it when slower than the threshold.
+pub fn observe_query(name: &str, elapsed: Duration) {
+    QUERY_LATENCY
+        .with_label_values(&[name])
+        .observe(elapsed.as_secs_f64());
+    observe_window(Kind::Query, name.to_string(), elapsed);
+    let threshold = slow_query_threshold();
+    if !threshold.is_zero() && elapsed >= threshold {
+        tracing::warn!(
+            query = name,
+            duration_ms = elapsed.as_millis() as u64,
+            "Slow query"
+        );
+    }
+}
+
+/// Runs `query` and records its latency under `name`.
+pub async fn timed_query<F: Future>(name: &str, query: F) -> F::Output {
+    let started = Instant::now();
+    let output = query.await;
+    observe_query(name, started.elapsed());
+    output
+}
+
+pub fn slow_query_threshold() -> Duration {
+    let ms = SCOPED_SLOW_QUERY_MS
+        .try_with(|ms| *ms)
+        .unwrap_or_else(|_| SLOW_QUERY_MS.load(Ordering::Relaxed));
+    Duration::from_millis(ms)
+}
+
+/// Runs `future` with queries awaited inside it logged at `threshold` rather than the
+/// process-wide threshold, which other tasks keep using.
+pub async fn with_slow_query_threshold<F: Future>(threshold: Duration, future: F) -> F::Output {
+    SCOPED_SLOW_QUERY_MS
+        .scope(threshold.as_millis() as u64, future)
+        .await
+}
+
+/// Queries taking at least `threshold` are logged, none when zero; set from `DATABASE_SLOW_QUERY_MS`.
+pub fn set_slow_query_threshold(threshold: Duration) {
+    SLOW_QUERY_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Records the processing time of one synced block.
+pub fn observe_sync_block(elapsed: Duration) {
+    SYNC_BLOCK_LATENCY.observe(elapsed.as_secs_f64());
+    observe_window(Kind::Sync, SYNC_BLOCK.to_string(), elapsed);
+}
+
+fn snapshot_of(name: &str, window: &VecDeque<Duration>) -> LatencySnapshot {
+    let mut samples: Vec<Duration> = window.iter().copied().collect();
+    samples.sort_unstable();
+    let ms = |pct: usize| percentile(&samples, pct).as_secs_f64() * 1000.0;
+    LatencySnapshot {
+        name: name.to_string(),
+        samples: samples.len() as u64,
+        p50_ms: ms(50),
+        p95_ms: ms(95),
+        p99_ms: ms(99),
+    }
+}
+
+fn snapshots(kind: Kind) -> Vec<LatencySnapshot> {
+    let windows = WINDOWS.lock().unwrap();
+    windows
+        .iter()
+        .filter(|((k, _), _)| *k == kind)
+        .map(|((_, name), window)| snapshot_of(name, window))
+        .collect()
+}
+
+/// Per-route percentiles, ordered by route.
+pub fn route_latencies() -> Vec<LatencySnapshot> {
+    snapshots(Kind::Route)
+}
+
+/// Per-method gRPC percentiles, ordered by method.
+pub fn grpc_latencies() -> Vec<LatencySnapshot> {
+    snapshots(Kind::Grpc)
+}
+
+/// The `limit` queries with the highest p95 over their windows, slowest first.
+pub fn slowest_queries(limit: usize) -> Vec<LatencySnapshot> {
+    let mut queries = snapshots(Kind::Query);
+    queries.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms));
+    queries.truncate(limit);
+    queries
+}
+
+/// Sync block processing percentiles; `None` before the first block.
+pub fn sync_block_latency() -> Option<LatencySnapshot> {
+    let windows = WINDOWS.lock().unwrap();
+    windows
+        .get(&(Kind::Sync, SYNC_BLOCK.to_string()))
+        .map(|window| snapshot_of(SYNC_BLOCK, window))
+}
+
+/// Nearest-rank percentile of sorted `samples`; zero for an empty sample.
+fn percentile(samples: &[Duration], pct: usize) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (pct * samples.len()).div_ceil(100).max(1);
+    samples[rank - 1]
+}
+
+/// Times every routed request. Requests that match no route are not recorded.
+pub async fn record_latency(request: Request<Body>, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let method = request.method().clone();
+    let started = Instant::now();
+    let response = next.run(request).await;
+    if let Some(route) = route {
+        observe_request(method.as_str(), &route, started.elapsed());
+    }
+    response
+}
+
+/// Times every gRPC call by method path. Calls the server does not implement are not
+/// recorded, so made-up paths do not become labels.
+#[derive(Clone, Default)]
+pub struct GrpcLatencyLayer;
+
+impl<S> tower::Layer<S> for GrpcLatencyLayer {
+    type Service = GrpcLatency<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcLatency { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct GrpcLatency<S> {
+    inner: S,
+}
+
+/// `grpc-status` of UNIMPLEMENTED, sent in the headers of a trailers-only response.
+const GRPC_UNIMPLEMENTED: &str = "12";
+
+impl<S, B, ResBody> tower::Service<http::Request<B>> for GrpcLatency<S>
+where
+    S: tower::Service<http::Request<B>, Response = http::Response<ResBody>>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // The clone may not be ready; keep the instance `poll_ready` was called on.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let method = req.uri().path().to_string();
+        Box::pin(async move {
+            let started = Instant::now();
+            let response = inner.call(req).await?;
+            let unimplemented = response
+                .headers()
+                .get("grpc-status")
+                .is_some_and(|status| status == GRPC_UNIMPLEMENTED);
+            if !unimplemented {
+                observe_grpc(&method, started.elapsed());
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_percentiles_over_window() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 50), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 99), Duration::from_millis(99));
+        assert_eq!(percentile(&[], 95), Duration::ZERO);
+
+        for ms in 1..=100 {
+            observe_request("GET", "/test/latency/{id}", Duration::from_millis(ms));
+        }
+        let route = route_latencies()
+            .into_iter()
+            .find(|s| s.name == "GET /test/latency/{id}")
+            .unwrap();
+        assert_eq!(route.samples, 100);
+        assert_eq!(
+            (route.p50_ms, route.p95_ms, route.p99_ms),
+            (50.0, 95.0, 99.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_named_queries_are_timed_and_ranked() {
+        let samples = || {
+            QUERY_LATENCY
+                .with_label_values(&["test_named_query"])
+                .get_sample_count()
+        };
+        let before = samples();
+        assert_eq!(timed_query("test_named_query", async { 7 }).await, 7);
+        assert_eq!(samples(), before + 1);
+
+        observe_query("test_slowest_query", Duration::from_secs(3600));
+        let slowest = slowest_queries(SLOWEST_QUERIES);
+        assert!(slowest.len() <= SLOWEST_QUERIES);
+        assert_eq!(slowest[0].name, "test_slowest_query");
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_is_logged_by_name() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        with_slow_query_threshold(Duration::from_millis(10), async {
+            timed_query(
+                "test_sleeping_query",
+                tokio::time::sleep(Duration::from_millis(30)),
+            )
+            .await;
+            timed_query("test_quick_query", async {}).await;
+        })
+        .await;
+        assert_eq!(
+            slow_query_threshold(),
+            Duration::from_millis(DEFAULT_SLOW_QUERY_MS)
+        );
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Slow query"), "{}", output);
+        assert!(
+            output.contains("query=\"test_sleeping_query\""),
+            "{}",
+            output
+        );
+        assert!(!output.contains("test_quick_query"), "{}", output);
+    }
+
+    /// Runs against a real Postgres when `TEST_DATABASE_URL` is set; skipped otherwise.
+    #[tokio::test]
+    async fn test_pg_sleep_is_logged_as_slow() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            return;
+        };
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        with_slow_query_threshold(Duration::from_millis(20), async {
+            timed_query(
+                "test_pg_sleep",
+                sqlx::query("SELECT pg_sleep(0.05)").execute(&pool),
+            )
+            .await
+            .unwrap();
+        })
+        .await;
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("query=\"test_pg_sleep\""), "{}", output);
+    }
+}
@@ -1,9 +1,18 @@
 pub mod api;
+pub mod cli;
 pub mod config;
+pub mod diagnostics;
+pub mod events;
 pub mod executor;
+pub mod gateway;
+pub mod latency;
+pub mod logging;
 pub mod oracle;
 pub mod orchestrator;
 pub mod safety;
+pub mod signing;
+pub mod stacks;
 pub mod state;
 pub mod storage;
+pub mod supervisor;
 pub mod sync;
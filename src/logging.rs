@@ -0,0 +1,223 @@
+//! Log output. `LOG_FORMAT=pretty` (the default) keeps tracing-subscriber's
+//! human-readable lines; `json` writes one JSON object per line with the event's fields,
+//! the fields of every span in scope (inner spans win) and the node's global fields all
+//! flattened into the top level, so aggregators can index `service`, `height`, `tx_id`
+//! and `request_id` without parsing messages.
+
+use crate::config::Config;
+use crate::stacks::transaction::StacksSigner;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Json => "json",
+        })
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => anyhow::bail!(
+                "Unsupported log format '{}' (expected json or pretty)",
+                other
+            ),
+        }
+    }
+}
+
+/// Fields written on every JSON line: the network and, when a signing key is
+/// configured, a fingerprint of the executor's public key.
+pub fn global_fields(config: &Config) -> Map<String, Value> {
+    let mut fields = Map::new();
+    fields.insert("network".to_string(), config.network.to_string().into());
+    if let Some(fingerprint) = config
+        .rebalance_private_key_hex
+        .as_deref()
+        .and_then(key_fingerprint)
+    {
+        fields.insert("wallet".to_string(), fingerprint.into());
+    }
+    fields
+}
+
+/// First 8 bytes (hex) of the SHA-256 of the key's compressed public key.
+pub fn key_fingerprint(private_key_hex: &str) -> Option<String> {
+    let signer = StacksSigner::from_hex(private_key_hex).ok()?;
+    let public_key = hex::decode(signer.public_key_hex()).ok()?;
+    Some(hex::encode(&Sha256::digest(public_key)[..8]))
+}
+
+/// A JSON-lines fmt layer writing to `writer`.
+pub fn json_layer<S, W>(
+    global: Map<String, Value>,
+    writer: W,
+) -> tracing_subscriber::fmt::Layer<S, JsonFields, JsonFormat, W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .fmt_fields(JsonFields::new())
+        .event_format(JsonFormat { global })
+        .with_writer(writer)
+}
+
+/// Event formatter behind [`json_layer`]. Span fields must be recorded with
+/// [`JsonFields`] to be flattened.
+pub struct JsonFormat {
+    global: Map<String, Value>,
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut line = self.global.clone();
+        line.insert(
+            "timestamp".to_string(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+                .into(),
+        );
+        line.insert("level".to_string(), metadata.level().as_str().into());
+        line.insert("target".to_string(), metadata.target().into());
+        if let Some(scope) = ctx.event_scope() {
+            let mut names = Vec::new();
+            for span in scope.from_root() {
+                names.push(span.name());
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>() {
+                    if let Ok(Value::Object(fields)) = serde_json::from_str(fields.as_str()) {
+                        line.extend(fields);
+                    }
+                }
+            }
+            line.insert("span".to_string(), names.join(":").into());
+        }
+        event.record(&mut JsonVisitor(&mut line));
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::prelude::*;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_flatten_span_event_and_global_fields() {
+        let mut config = Config::default_test();
+        config.network = crate::config::network::Network::Testnet;
+        config.rebalance_private_key_hex = Some("07".repeat(32));
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(json_layer(global_fields(&config), move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let service = tracing::info_span!("service", service = "sync");
+            let _service = service.enter();
+            let block = tracing::info_span!("process_microblock", height = 42u64);
+            let _block = block.enter();
+            tracing::info!(tx_id = "0xabc", "Ingested transaction");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["message"], "Ingested transaction");
+        assert_eq!(line["service"], "sync");
+        assert_eq!(line["height"], 42);
+        assert_eq!(line["tx_id"], "0xabc");
+        assert_eq!(line["network"], "testnet");
+        assert_eq!(line["span"], "service:process_microblock");
+        assert_eq!(
+            line["wallet"].as_str(),
+            key_fingerprint(&"07".repeat(32)).as_deref()
+        );
+    }
+
+    #[test]
+    fn test_log_format_parses() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
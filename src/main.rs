@@ -1,17 +1,32 @@
 use anyhow::Context;
+use clap::Parser;
 use conxian_nexus::api;
 use conxian_nexus::api::billing::nostr::NostrTelemetry;
+use conxian_nexus::cli::{self, Cli, Command};
+use conxian_nexus::config::dynamic::{DynamicConfig, DynamicConfigHandle};
+use conxian_nexus::config::network::check_rpc_network;
 use conxian_nexus::config::{
-    Config, ENV_ORACLE_CONTRACT_PRINCIPAL, ENV_ORACLE_ENABLED, ENV_ORACLE_ENDPOINT_URL,
+    Config, ENV_ANCHOR_PRIVATE_KEY, ENV_ORACLE_CONTRACT_PRINCIPAL, ENV_ORACLE_ENABLED,
+    ENV_ORACLE_ENDPOINT_URL, ENV_ORACLE_FX_PROVIDERS, ENV_REBALANCE_PRIVATE_KEY_HEX,
 };
+use conxian_nexus::diagnostics::diagnostics;
+use conxian_nexus::executor::rebalance::REBALANCE_INTERVAL;
 use conxian_nexus::executor::NexusExecutor;
-use conxian_nexus::oracle::OracleService;
+use conxian_nexus::gateway::ServiceRegistry;
+use conxian_nexus::logging::{self, LogFormat};
+use conxian_nexus::oracle::aggregator::SOURCE_BASE_CURRENCY;
+use conxian_nexus::oracle::push::OraclePusher;
+use conxian_nexus::oracle::{self, OracleService};
 use conxian_nexus::orchestrator::AutonomousOrchestrator;
 use conxian_nexus::safety::NexusSafety;
+use conxian_nexus::signing::RotatingSigner;
+use conxian_nexus::stacks::{self, StacksSigner};
+use conxian_nexus::state::anchor::AnchorService;
 use conxian_nexus::state::NexusState;
 use conxian_nexus::storage::kwil::{KwilAdapter, KwilConfig};
 use conxian_nexus::storage::tableland::TablelandAdapter;
 use conxian_nexus::storage::Storage;
+use conxian_nexus::supervisor::Supervisor;
 use conxian_nexus::sync::NexusSync;
 use lib_conxian_core::Wallet;
 use opentelemetry::{global, trace::TracerProvider};
@@ -19,21 +34,37 @@ use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use opentelemetry_sdk::trace::{self as sdktrace};
 use opentelemetry_sdk::Resource;
-use std::future;
 use std::sync::Arc;
 use tokio::signal;
+use tokio::sync::watch;
 use tokio::time::{self, Duration};
-use tracing_subscriber::{prelude::*, EnvFilter};
+use tracing_subscriber::{prelude::*, reload, EnvFilter};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // Load environment variables
     dotenvy::dotenv().ok();
 
-    let config = Config::from_env().context("Failed to load configuration")?;
-
-    // Initialize tracing
-    let fmt_layer = tracing_subscriber::fmt::layer().with_filter(EnvFilter::new(&config.rust_log));
+    let loaded = match Config::load(None) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let config = loaded.config.clone();
+
+    // Initialize tracing; the filter is swapped when RUST_LOG is reloaded
+    let (log_filter, log_filter_handle) = reload::Layer::new(EnvFilter::new(config.log_filter()));
+    let fmt_layer = match config.log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => {
+            logging::json_layer(logging::global_fields(&config), std::io::stdout).boxed()
+        }
+    }
+    .with_filter(log_filter);
 
     if let Some(endpoint) = &config.otel_exporter_otlp_endpoint {
         global::set_text_map_propagator(TraceContextPropagator::new());
@@ -63,24 +94,119 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Initialize logging using the centralized config
+    for warning in &loaded.warnings {
+        tracing::warn!("{}", warning);
+    }
+    tracing::info!(
+        source = %loaded
+            .path
+            .as_ref()
+            .map_or_else(|| "environment".to_string(), |p| p.display().to_string()),
+        config = ?config,
+        "Effective configuration"
+    );
+
+    // Reloadable settings, re-read on SIGHUP or POST /admin/v1/config/reload
+    let dynamic_config = DynamicConfigHandle::new(DynamicConfig::from_config(&config))
+        .with_config_path(loaded.path.clone())
+        .with_startup_file(&loaded.file)
+        .with_log_reloader(move |directives| {
+            log_filter_handle.reload(EnvFilter::try_new(directives)?)?;
+            Ok(())
+        });
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config, dynamic_config, cli.check).await,
+        command => cli::run(command, &config).await,
+    }
+}
+
+/// Runs every service until a shutdown signal or the first one exits. With `check`,
+/// reports dependency health and exits instead.
+async fn serve(
+    config: Config,
+    dynamic_config: DynamicConfigHandle,
+    check: bool,
+) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        let dynamic_config = dynamic_config.clone();
+        tokio::spawn(async move {
+            let mut hangups = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+                Ok(hangups) => hangups,
+                Err(e) => {
+                    tracing::warn!(
+                        "Cannot listen for SIGHUP; reload via the admin API only: {}",
+                        e
+                    );
+                    return;
+                }
+            };
+            while hangups.recv().await.is_some() {
+                tracing::info!("SIGHUP received; reloading configuration");
+                if let Err(e) = dynamic_config.reload() {
+                    tracing::error!(
+                        "Configuration reload rejected; keeping current values. {}",
+                        e
+                    );
+                }
+            }
+        });
+    }
 
     tracing::info!(
         "Initializing Conxian Nexus (Glass Node v{})...",
         env!("CARGO_PKG_VERSION")
     );
 
+    // Self-test mode: report dependency health and exit without serving traffic
+    if check {
+        let storage = Storage::from_config_lazy(&config)?;
+        let report = diagnostics(&config, &storage, &reqwest::Client::new()).await;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(if report.healthy { 0 } else { 1 });
+    }
+
+    // Refuse to follow a node on a different chain than NETWORK; a fallback is checked
+    // like the primary, since any of them may end up serving sync
+    let stacks_rpc = Arc::new(config.stacks_rpc_endpoints());
+    let network_check_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    for url in stacks_rpc.urls() {
+        check_rpc_network(
+            &network_check_client,
+            url,
+            config.network,
+            config.allow_network_mismatch,
+        )
+        .await?;
+    }
+    stacks::rpc::set_shared(stacks_rpc.clone());
+
     // Initialize Global Start Time
     api::init_start_time();
 
     // Initialize Storage
-    let storage = Arc::new(Storage::from_config(&config).await?);
+    conxian_nexus::latency::set_slow_query_threshold(std::time::Duration::from_millis(
+        config.database_slow_query_ms,
+    ));
+    let storage = Arc::new(Storage::new(&config).await?);
 
     // Run Database Migrations
-    tracing::info!("Running database migrations...");
-    storage.run_migrations().await?;
+    if config.auto_migrate {
+        tracing::info!("Running database migrations...");
+        storage.run_migrations().await?;
+    } else {
+        storage.require_migrated().await?;
+    }
 
     // Initialize State Tracker
-    let state_tracker = Arc::new(NexusState::new());
+    let state_tracker = Arc::new(
+        NexusState::with_arity(config.merkle_arity)
+            .with_leaf_hashing(config.merkle_leaf_hashing)
+            .with_proof_cache_size(config.proof_cache_size),
+    );
 
     // Initialize Executor
     let rgb_mode = if config.experimental_apis_enabled {
@@ -88,11 +214,22 @@ async fn main() -> anyhow::Result<()> {
     } else {
         conxian_nexus::executor::rgb::RGBRolloutMode::Disabled
     };
-    let executor = Arc::new(NexusExecutor::new(
-        storage.clone(),
-        rgb_mode,
-        std::collections::HashSet::new(),
-    ));
+    let executor = Arc::new(
+        NexusExecutor::new(storage.clone(), rgb_mode, std::collections::HashSet::new())
+            .with_required_finality(config.executor_required_finality)
+            .with_queue_limits(
+                config.executor_max_queue_depth,
+                Duration::from_secs(config.executor_max_queue_age_secs),
+            )
+            .with_sender_rate_limit(
+                config.executor_sender_rate_limit,
+                Duration::from_secs(config.executor_sender_rate_window_secs),
+            )
+            .with_dynamic_config(dynamic_config.clone())
+            .with_rebalance_signer(rebalance_signer(&config)?)
+            .with_chain_id(config.network.chain_id())
+            .with_action_contracts(config.vault_contract_ids.clone()),
+    );
 
     // Initialize Tableland Adapter [CON-69]
     let tableland = Arc::new(TablelandAdapter::new(
@@ -140,36 +277,93 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize Oracle Service
     let oracle_service = if config.oracle_enabled {
-        let endpoint_url = config.oracle_endpoint_url.clone().with_context(|| {
-            format!("{ENV_ORACLE_ENABLED}=1 requires {ENV_ORACLE_ENDPOINT_URL}")
-        })?;
-        let contract_principal = config.oracle_contract_principal.clone().with_context(|| {
-            format!("{ENV_ORACLE_ENABLED}=1 requires {ENV_ORACLE_CONTRACT_PRINCIPAL}")
-        })?;
-
-        Some(Arc::new(OracleService::new(
-            storage.clone(),
-            endpoint_url,
-            contract_principal,
-        )))
+        let fx_providers = oracle::providers::from_config(&config, SOURCE_BASE_CURRENCY)
+            .with_context(|| format!("Invalid {ENV_ORACLE_FX_PROVIDERS}"))?;
+
+        if fx_providers.is_empty() {
+            tracing::info!(
+                "OracleService disabled: no FX providers configured (set {} or {})",
+                ENV_ORACLE_ENDPOINT_URL,
+                ENV_ORACLE_FX_PROVIDERS
+            );
+            None
+        } else {
+            let oracle = OracleService::with_providers(storage.clone(), fx_providers)
+                .with_base_currencies(
+                    config.oracle_base_currency.clone(),
+                    config.oracle_extra_base_currencies.clone(),
+                )
+                .with_quorum(config.oracle_min_providers, config.oracle_max_deviation_pct)
+                .with_fetch_interval(Duration::from_secs(config.oracle_fetch_interval_secs))
+                .with_max_state_age(Duration::from_secs(config.oracle_max_state_age_secs));
+            let oracle = match OraclePusher::from_config(&config, stacks_rpc.clone())? {
+                Some(pusher) => {
+                    tracing::info!(contract = %pusher.contract(), "Oracle on-chain pushes enabled");
+                    oracle.with_pusher(pusher)
+                }
+                None => {
+                    tracing::info!(
+                        "Oracle on-chain pushes disabled (set {} to enable)",
+                        ENV_ORACLE_CONTRACT_PRINCIPAL
+                    );
+                    oracle
+                }
+            };
+            Some(Arc::new(oracle))
+        }
     } else {
+        tracing::info!(
+            "OracleService disabled (set {}=1 to enable)",
+            ENV_ORACLE_ENABLED
+        );
         None
     };
 
     // Initialize Services
-    let sync_service = Arc::new(NexusSync::new(
+    let sync_service = Arc::new(
+        NexusSync::new(
+            storage.clone(),
+            state_tracker.clone(),
+            tableland.clone(),
+            kwil.clone(),
+            stacks_rpc.clone(),
+            config.stacks_node_ws_url.clone(),
+        )
+        .with_burn_confirmations(config.sync_burn_confirmations)
+        .with_sync_interval(Duration::from_secs(config.sync_interval_secs))
+        .with_max_event_attempts(config.sync_event_max_attempts)
+        .with_start_height(config.sync_start_height)
+        .with_vault_contracts(config.vault_contract_ids.clone())
+        .with_oracle_contract(
+            config.oracle_contract_principal.clone(),
+            config.oracle_contract_function.clone(),
+        ),
+    );
+    let gateway_registry = Arc::new(ServiceRegistry::from_config(
+        &config,
         storage.clone(),
         state_tracker.clone(),
-        tableland.clone(),
-        kwil.clone(),
-        config.stacks_node_rpc_url.clone(),
-        config.stacks_node_ws_url.clone(),
-    ));
-    let safety_service = Arc::new(NexusSafety::new(
+    )?);
+    let mut safety_service = NexusSafety::new(
         storage.clone(),
-        config.stacks_node_rpc_url.clone(),
-        config.gateway_url.clone(),
-    ));
+        stacks_rpc.clone(),
+        config.gateway_telemetry_url.clone(),
+    )
+    .with_telemetry_token(config.gateway_telemetry_token.clone())
+    .with_rpc_failure_threshold(config.safety_rpc_failure_threshold)
+    .with_dynamic_config(dynamic_config.clone())
+    .with_drift_ema_alpha(config.safety_drift_ema_alpha)
+    .with_gateway_registry(gateway_registry.clone())
+    .with_alert_webhook(config.safety_alert_webhook_url.clone())
+    .with_block_max_age(
+        state_tracker.clone(),
+        Duration::from_secs(config.safety_max_block_age_secs),
+    );
+    if oracle_service.is_some() {
+        safety_service = safety_service
+            .with_oracle_max_age(Duration::from_secs(config.safety_oracle_max_age_secs));
+    }
+    let safety_service = Arc::new(safety_service);
 
     // Initialize Autonomous Orchestrator [NEXUS-ORCH-01]
     let orchestrator = Arc::new(AutonomousOrchestrator::new(
@@ -178,16 +372,45 @@ async fn main() -> anyhow::Result<()> {
         nostr.clone(),
     ));
 
+    // Start a fresh database at SYNC_START_HEIGHT instead of genesis; a synced one keeps
+    // its watermark, so the chain tip is only needed before the first block.
+    if config.sync_start_height.is_some() && sync_service.resume_height().await?.is_none() {
+        let chain_tip = sync_service.chain_tip().await?;
+        if sync_service.seed_start_height(chain_tip).await? {
+            tracing::info!(
+                start_height = config.sync_start_height,
+                chain_tip,
+                "Seeded sync watermark from SYNC_START_HEIGHT"
+            );
+        }
+    }
+
     // Load Initial State from DB
     sync_service.load_initial_state().await?;
 
+    // Long-running services are restarted on failure; one that keeps failing stops the
+    // process so it can be replaced.
+    let supervisor = Supervisor::new(
+        config.supervisor_max_restarts,
+        Duration::from_secs(config.supervisor_restart_window_secs),
+    );
+
+    // Flipped to true on shutdown; tasks holding a receiver finish their current round
+    // and stop.
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
     // Spawn Sync Service
-    let sync_handle = {
+    let mut sync_handle = {
         let sync = sync_service.clone();
+        let sync_shutdown = shutdown_rx.clone();
         tokio::spawn(async move {
-            if let Err(e) = sync.run().await {
-                tracing::error!("Sync service failed: {}", e);
-            }
+            supervisor
+                .supervise("sync", move || {
+                    let sync = sync.clone();
+                    let shutdown = sync_shutdown.clone();
+                    async move { sync.run(shutdown).await }
+                })
+                .await
         })
     };
 
@@ -195,69 +418,87 @@ async fn main() -> anyhow::Result<()> {
     let safety_handle = {
         let safety = safety_service.clone();
         tokio::spawn(async move {
-            if let Err(e) = safety.run_heartbeat().await {
-                tracing::error!("Safety service failed: {}", e);
-            }
+            supervisor
+                .supervise("safety", move || {
+                    let safety = safety.clone();
+                    async move { safety.run_heartbeat().await }
+                })
+                .await
         })
     };
 
-    // Spawn Oracle Service
-    let oracle_handle = if let Some(ref oracle) = oracle_service {
+    // Spawn Oracle Service; disabled services get no task, so neither the supervision
+    // below nor shutdown waits on them.
+    let mut oracle_handle = oracle_service.as_ref().map(|oracle| {
         let oracle_worker = oracle.clone();
-        Some(tokio::spawn(async move {
-            if let Err(e) = oracle_worker.run().await {
-                tracing::error!("Oracle service failed: {}", e);
-            }
-        }))
-    } else {
-        tracing::info!(
-            "OracleService disabled (set {}=1 to enable)",
-            ENV_ORACLE_ENABLED
-        );
-        None
-    };
+        let oracle_shutdown = shutdown_rx.clone();
+        tokio::spawn(async move {
+            supervisor
+                .supervise("oracle", move || {
+                    let oracle = oracle_worker.clone();
+                    let shutdown = oracle_shutdown.clone();
+                    async move { oracle.run(shutdown).await }
+                })
+                .await
+        })
+    });
 
-    let oracle_join = async move {
-        match oracle_handle {
-            Some(handle) => handle.await,
-            None => future::pending::<Result<(), tokio::task::JoinError>>().await,
+    // Spawn State Root Anchoring
+    let anchor_service = AnchorService::from_config(
+        &config,
+        storage.clone(),
+        state_tracker.clone(),
+        stacks_rpc.clone(),
+    )?;
+    match &anchor_service {
+        Some(anchor) => {
+            tracing::info!(contract = %anchor.contract(), "State root anchoring enabled")
         }
-    };
+        None => tracing::info!(
+            "State root anchoring disabled (set {} to enable)",
+            ENV_ANCHOR_PRIVATE_KEY
+        ),
+    }
+    let mut anchor_handle = anchor_service.map(|anchor| {
+        let anchor = Arc::new(anchor);
+        tokio::spawn(async move {
+            supervisor
+                .supervise("anchor", move || {
+                    let anchor = anchor.clone();
+                    async move { anchor.run().await }
+                })
+                .await
+        })
+    });
 
     // Spawn Rebalance Background Task
     let rebalance_executor = executor.clone();
     let rebalance_handle = tokio::spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            if let Err(e) = rebalance_executor.execute_rebalance().await {
-                tracing::error!("Rebalance task failed: {}", e);
-            }
-        }
+        supervisor
+            .supervise("rebalance", move || {
+                run_rebalance(rebalance_executor.clone())
+            })
+            .await
     });
 
     // [NEXUS-04] Spawn Sovereign Health Reporting (Nostr)
     let health_nostr = nostr.clone();
-    let health_report_handle = if let Some(n) = health_nostr {
+    let mut health_report_handle = if let Some(n) = health_nostr {
         let health_storage = storage.clone();
         let health_state = state_tracker.clone();
         Some(tokio::spawn(async move {
             let mut interval = time::interval(Duration::from_secs(300)); // Every 5 mins
             loop {
                 interval.tick().await;
-                let max_height = sqlx::query_scalar::<_, Option<i64>>(
-                    "SELECT MAX(height) FROM stacks_blocks WHERE type = 'burn_block' AND state = 'hard'",
-                )
-                .fetch_one(&health_storage.pg_pool)
-                .await
-                .map_err(|e| {
-                    tracing::error!(error = %e, "Health report height query failed");
-                    e
-                })
-                .ok()
-                .flatten()
-                .unwrap_or(0)
-                .max(0) as u64;
+                let max_height = health_storage
+                    .repo()
+                    .max_hard_burn_height()
+                    .await
+                    .map_err(|e| {
+                        tracing::error!(error = %e, "Health report height query failed");
+                        e
+                    })
+                    .unwrap_or(0);
                 let state_root = health_state.get_state_root();
 
                 if let Err(e) = n
@@ -272,13 +513,6 @@ async fn main() -> anyhow::Result<()> {
         None
     };
 
-    let health_join = async move {
-        match health_report_handle {
-            Some(handle) => handle.await,
-            None => future::pending::<Result<(), tokio::task::JoinError>>().await,
-        }
-    };
-
     // Spawn Autonomous Orchestrator [NEXUS-ORCH-01]
     let orch_worker = orchestrator.clone();
     let orch_handle = tokio::spawn(async move {
@@ -287,6 +521,13 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
+    // TLS material is read up front so a bad certificate stops startup instead of
+    // leaving a listener down.
+    let tls = api::tls::TlsMaterial::from_config(&config)?;
+    if tls.is_none() {
+        tracing::warn!("TLS_CERT_PATH/TLS_KEY_PATH not set; REST and gRPC are served in plaintext");
+    }
+
     // Start REST API Server
     let rest_storage = storage.clone();
     let rest_state = state_tracker.clone();
@@ -295,10 +536,12 @@ async fn main() -> anyhow::Result<()> {
     let rest_tableland = tableland.clone();
     let rest_kwil = kwil.clone();
     let rest_nostr = nostr.clone();
-    let rest_port = config.rest_port;
+    let rest_addr = config.rest_listen_addr();
     let rest_config = Arc::new(config.clone());
+    let rest_gateway = gateway_registry.clone();
+    let rest_tls = tls.clone();
     let rest_handle = tokio::spawn(async move {
-        if let Err(e) = api::rest::start_rest_server(
+        api::rest::start_rest_server(
             rest_storage,
             rest_state,
             rest_executor,
@@ -306,33 +549,34 @@ async fn main() -> anyhow::Result<()> {
             rest_tableland,
             rest_kwil,
             rest_nostr,
-            rest_port,
+            rest_addr,
             rest_config,
+            rest_gateway,
+            rest_tls,
         )
         .await
-        {
-            tracing::error!("REST API server failed: {}", e);
-        }
     });
 
     // Start gRPC API Server
     let grpc_storage = storage.clone();
     let grpc_state = state_tracker.clone();
     let grpc_executor = executor.clone();
-    let grpc_port = config.grpc_port;
+    let grpc_oracle = oracle_service.clone();
+    let grpc_addr = config.grpc_listen_addr();
     let grpc_skip_auth = cfg!(debug_assertions); // Skip auth in debug builds only
+    let grpc_open_reads = config.grpc_open_reads;
     let grpc_handle = tokio::spawn(async move {
-        if let Err(e) = api::grpc::start_grpc_server(
+        api::grpc::start_grpc_server(
             grpc_storage,
             grpc_state,
             grpc_executor,
-            grpc_port,
+            grpc_oracle,
+            grpc_addr,
             grpc_skip_auth,
+            grpc_open_reads,
+            tls,
         )
         .await
-        {
-            tracing::error!("gRPC API server failed: {}", e);
-        }
     });
 
     tracing::info!("All Nexus services are running.");
@@ -346,16 +590,99 @@ async fn main() -> anyhow::Result<()> {
     };
 
     tokio::select! {
-        _ = shutdown => tracing::info!("Shutting down..."),
-        res = sync_handle => tracing::error!("Sync service exited: {:?}", res),
-        res = safety_handle => tracing::error!("Safety service exited: {:?}", res),
-        res = oracle_join => tracing::error!("Oracle service exited: {:?}", res),
-        res = rebalance_handle => tracing::error!("Rebalance task exited: {:?}", res),
-        res = health_join => tracing::error!("Health report task exited: {:?}", res),
-        res = orch_handle => tracing::error!("Orchestrator task exited: {:?}", res),
-        res = rest_handle => tracing::error!("REST handle exited: {:?}", res),
-        res = grpc_handle => tracing::error!("gRPC handle exited: {:?}", res),
+        _ = shutdown => {
+            tracing::info!("Shutting down...");
+            let _ = shutdown_tx.send(true);
+            // Sync persists what it has already received before stopping.
+            if time::timeout(SYNC_DRAIN_TIMEOUT, &mut sync_handle).await.is_err() {
+                tracing::warn!(
+                    "Sync service did not drain within {}s",
+                    SYNC_DRAIN_TIMEOUT.as_secs()
+                );
+            }
+            if let Some(oracle) = oracle_handle.as_mut() {
+                if time::timeout(Duration::from_secs(5), oracle).await.is_err() {
+                    tracing::warn!("Oracle service did not stop within 5s");
+                }
+            }
+            Ok(())
+        }
+        res = &mut sync_handle => supervised_exit("Sync service", res),
+        res = safety_handle => supervised_exit("Safety service", res),
+        res = async { oracle_handle.as_mut().unwrap().await }, if oracle_handle.is_some() => {
+            supervised_exit("Oracle service", res)
+        }
+        res = rebalance_handle => supervised_exit("Rebalance task", res),
+        res = async { anchor_handle.as_mut().unwrap().await }, if anchor_handle.is_some() => {
+            supervised_exit("Anchor service", res)
+        }
+        res = async { health_report_handle.as_mut().unwrap().await },
+            if health_report_handle.is_some() => {
+            tracing::error!("Health report task exited: {:?}", res);
+            Ok(())
+        }
+        res = orch_handle => {
+            tracing::error!("Orchestrator task exited: {:?}", res);
+            Ok(())
+        }
+        res = rest_handle => listener_exit("REST API server", res),
+        res = grpc_handle => listener_exit("gRPC API server", res),
     }
+}
 
-    Ok(())
+/// How long shutdown waits for sync to persist buffered events.
+const SYNC_DRAIN_TIMEOUT: Duration = Duration::from_secs(15);
+
+type ServiceExit = Result<anyhow::Result<()>, tokio::task::JoinError>;
+
+/// A supervised service only finishes when it stops on purpose or its restart circuit
+/// trips; the latter fails the process.
+fn supervised_exit(name: &str, res: ServiceExit) -> anyhow::Result<()> {
+    match res {
+        Ok(Ok(())) => {
+            tracing::error!("{} exited", name);
+            Ok(())
+        }
+        Ok(Err(e)) => Err(e.context(format!("{} gave up after repeated failures", name))),
+        Err(e) => Err(anyhow::anyhow!("{} supervisor failed: {}", name, e)),
+    }
+}
+
+/// A listener only returns when it could not bind or serve; that fails the process rather
+/// than leaving the node up without its API.
+fn listener_exit(name: &str, res: ServiceExit) -> anyhow::Result<()> {
+    match res {
+        Ok(Ok(())) => Err(anyhow::anyhow!("{} stopped", name)),
+        Ok(Err(e)) => Err(e.context(format!("{} failed", name))),
+        Err(e) => Err(anyhow::anyhow!("{} task failed: {}", name, e)),
+    }
+}
+
+/// Rebalance cycle, every [`REBALANCE_INTERVAL`]. Cycle errors are logged; only a panic
+/// ends the task.
+async fn run_rebalance(executor: Arc<NexusExecutor>) -> anyhow::Result<()> {
+    let mut interval = time::interval(REBALANCE_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = executor.execute_rebalance().await {
+            tracing::error!("Rebalance task failed: {}", e);
+        }
+    }
+}
+
+/// Signer for rebalance instructions. Startup fails without one: an unsigned or
+/// ephemerally signed instruction is useless to anyone verifying it.
+fn rebalance_signer(config: &Config) -> anyhow::Result<Arc<RotatingSigner<StacksSigner>>> {
+    let key = config
+        .rebalance_private_key_hex
+        .as_deref()
+        .with_context(|| {
+            format!(
+                "{} must be set to sign rebalance instructions",
+                ENV_REBALANCE_PRIVATE_KEY_HEX
+            )
+        })?;
+    let signer = StacksSigner::from_hex(key)
+        .with_context(|| format!("Invalid {}", ENV_REBALANCE_PRIVATE_KEY_HEX))?;
+    Ok(Arc::new(RotatingSigner::stacks(signer)))
 }
@@ -1,7 +1,13 @@
-use lib_conxian_core::{ContractBridge, Wallet};
-use reqwest::Client;
+use crate::oracle::attestation::PriceAttestation;
+use crate::oracle::providers::{
+    self, ExchangeRateApiProvider, FrankfurterProvider, FxProvider, EXCHANGERATE_API_OPEN_URL,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Currency every upstream FX endpoint quotes its rates against.
+pub const SOURCE_BASE_CURRENCY: &str = "USD";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PppState {
@@ -10,104 +16,384 @@ pub struct PppState {
     pub ppp_indices: HashMap<String, f64>,
     pub confidence_intervals: HashMap<String, f64>,
     pub timestamp: u64,
+    /// The same indices rebased onto each additional configured base, keyed by currency.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bases: BTreeMap<String, BaseIndices>,
+    /// Providers whose quotes were aggregated, per currency.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provenance: BTreeMap<String, Vec<String>>,
+    /// Quotes discarded this round for deviating too far from the median.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejections: Vec<OutlierRejection>,
+    /// Verified publisher signatures behind the contributing quotes, forwarded on-chain.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attestations: Vec<PriceAttestation>,
+    /// USD per unit of each [`COLLATERAL_ASSETS`] entry the providers quoted.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub collateral_prices: BTreeMap<String, f64>,
+}
+
+/// Crypto assets accepted as vault collateral.
+pub const COLLATERAL_ASSETS: &[&str] = &["BTC", "STX"];
+
+/// USD price of each collateral asset quoted in `usd_rates` (units per one USD).
+pub fn collateral_prices(usd_rates: &HashMap<String, f64>) -> BTreeMap<String, f64> {
+    COLLATERAL_ASSETS
+        .iter()
+        .filter_map(|asset| {
+            let rate = *usd_rates
+                .get(*asset)
+                .filter(|r| r.is_finite() && **r > 0.0)?;
+            Some((asset.to_string(), 1.0 / rate))
+        })
+        .collect()
+}
+
+/// A provider's quote discarded for deviating from the cross-provider median by more
+/// than the configured percentage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutlierRejection {
+    pub provider: String,
+    pub currency: String,
+    /// Units of `currency` per one [`SOURCE_BASE_CURRENCY`].
+    pub quote: f64,
+    pub median: f64,
+    pub deviation_pct: f64,
+}
+
+/// Rates (units of each currency per one unit of the base), PPP indices and
+/// confidences quoted against a single base currency.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BaseIndices {
+    pub rates: HashMap<String, f64>,
+    pub ppp_indices: HashMap<String, f64>,
+    pub confidence_intervals: HashMap<String, f64>,
+}
+
+impl BaseIndices {
+    /// Rebases indices quoted against `from` onto `to`. Rates and PPP indices are divided
+    /// by those of `to`; confidences become the lower of the currency's and `to`'s.
+    /// `None` when there is no usable rate for `to`. PPP indices are dropped when `to`
+    /// has none, since they cannot be expressed relative to it.
+    pub fn rebase(&self, from: &str, to: &str) -> Option<BaseIndices> {
+        if from == to {
+            return Some(self.clone());
+        }
+        let mut rates = self.rates.clone();
+        rates.entry(from.to_string()).or_insert(1.0);
+        let pivot = *rates.get(to).filter(|r| r.is_finite() && **r > 0.0)?;
+        let rates = rates.into_iter().map(|(c, r)| (c, r / pivot)).collect();
+
+        let mut ppp = self.ppp_indices.clone();
+        ppp.entry(from.to_string()).or_insert(1.0);
+        let ppp_indices = match ppp.get(to).copied().filter(|p| *p > 0.0) {
+            Some(ppp_pivot) => ppp
+                .into_iter()
+                .filter(|(c, _)| c != to)
+                .map(|(c, p)| (c, p / ppp_pivot))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        let pivot_confidence = self.confidence_intervals.get(to).copied();
+        let confidence_intervals = self
+            .confidence_intervals
+            .iter()
+            .filter(|(c, _)| c.as_str() != to)
+            .map(|(c, conf)| (c.clone(), pivot_confidence.map_or(*conf, |p| conf.min(p))))
+            .chain(pivot_confidence.map(|p| (from.to_string(), p)))
+            .collect();
+
+        Some(BaseIndices {
+            rates,
+            ppp_indices,
+            confidence_intervals,
+        })
+    }
 }
 
-#[derive(Deserialize)]
-struct ExchangeRateResponse {
-    rates: HashMap<String, f64>,
+impl PppState {
+    /// Indices quoted against `base`, whether it is the primary base or an additional one.
+    pub fn for_base(&self, base: &str) -> Option<BaseIndices> {
+        if base == self.base_currency {
+            return Some(BaseIndices {
+                rates: self.rates.clone(),
+                ppp_indices: self.ppp_indices.clone(),
+                confidence_intervals: self.confidence_intervals.clone(),
+            });
+        }
+        self.bases.get(base).cloned()
+    }
+}
+
+/// Default for `ORACLE_MAX_DEVIATION_PCT`.
+pub const DEFAULT_MAX_DEVIATION_PCT: f64 = 10.0;
+
+/// Median of `values`; `None` when empty. Reorders `values`.
+pub fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// One provider's answer in a fetch round, kept for the observation log.
+#[derive(Debug, Clone)]
+pub struct FxObservation {
+    pub provider: &'static str,
+    pub weight: f64,
+    pub state: PppState,
 }
 
 pub struct OracleAggregator {
-    client: Client,
-    endpoints: Vec<(String, f64)>, // (url, weight)
-    contract_principal: String,
+    providers: Vec<(Arc<dyn FxProvider>, f64)>, // (provider, weight)
+    base_currency: String,
+    extra_bases: Vec<String>,
+    /// Agreeing providers required before a currency is published.
+    min_providers: usize,
+    max_deviation_pct: f64,
 }
 
 impl OracleAggregator {
-    pub fn new(endpoint_url: String, contract_principal: String) -> Self {
+    /// Polls `endpoint_url` (ExchangeRate-API response format) alongside the keyless
+    /// ExchangeRate-API and Frankfurter endpoints.
+    pub fn new(endpoint_url: String) -> Self {
+        let defaults: Vec<Arc<dyn FxProvider>> = vec![
+            Arc::new(ExchangeRateApiProvider::with_url(format!(
+                "{}/latest/{}",
+                EXCHANGERATE_API_OPEN_URL, SOURCE_BASE_CURRENCY
+            ))),
+            Arc::new(FrankfurterProvider::new(SOURCE_BASE_CURRENCY)),
+        ];
+        let primary: Arc<dyn FxProvider> =
+            Arc::new(ExchangeRateApiProvider::with_url(endpoint_url));
+        Self::from_providers(providers::weighted(Some(primary), defaults))
+    }
+
+    /// Aggregates exactly `providers`, weighted as given.
+    pub fn from_providers(providers: Vec<(Arc<dyn FxProvider>, f64)>) -> Self {
         Self {
-            client: Client::new(),
-            endpoints: vec![
-                (endpoint_url, 0.5),
-                ("https://open.er-api.com/v6/latest/USD".to_string(), 0.25),
-                (
-                    "https://api.exchangerate.host/latest?base=USD".to_string(),
-                    0.25,
-                ),
-            ],
-            contract_principal,
+            providers,
+            base_currency: SOURCE_BASE_CURRENCY.to_string(),
+            extra_bases: Vec::new(),
+            min_providers: 1,
+            max_deviation_pct: DEFAULT_MAX_DEVIATION_PCT,
         }
     }
 
+    /// Publishes a currency only when at least `min_providers` quotes lie within
+    /// `max_deviation_pct` percent of the median quote.
+    pub fn with_quorum(mut self, min_providers: usize, max_deviation_pct: f64) -> Self {
+        self.min_providers = min_providers.max(1);
+        self.max_deviation_pct = max_deviation_pct;
+        self
+    }
+
+    pub fn provider_count(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Publishes indices against `base`, plus each of `extra` in [`PppState::bases`].
+    pub fn with_base_currencies(mut self, base: String, extra: Vec<String>) -> Self {
+        self.extra_bases = extra.into_iter().filter(|b| *b != base).collect();
+        self.base_currency = base;
+        self
+    }
+
+    /// Rebases indices quoted in [`SOURCE_BASE_CURRENCY`] onto the configured bases.
+    pub fn assemble_state(&self, source: BaseIndices, timestamp: u64) -> anyhow::Result<PppState> {
+        let primary = source
+            .rebase(SOURCE_BASE_CURRENCY, &self.base_currency)
+            .ok_or_else(|| {
+                anyhow::anyhow!("No {} rate available to rebase onto", self.base_currency)
+            })?;
+
+        let mut bases = BTreeMap::new();
+        for base in &self.extra_bases {
+            match source.rebase(SOURCE_BASE_CURRENCY, base) {
+                Some(indices) => {
+                    bases.insert(base.clone(), indices);
+                }
+                None => tracing::warn!("No {} rate available; skipping that base", base),
+            }
+        }
+
+        Ok(PppState {
+            base_currency: self.base_currency.clone(),
+            rates: primary.rates,
+            ppp_indices: primary.ppp_indices,
+            confidence_intervals: primary.confidence_intervals,
+            timestamp,
+            bases,
+            provenance: BTreeMap::new(),
+            rejections: Vec::new(),
+            attestations: Vec::new(),
+            collateral_prices: BTreeMap::new(),
+        })
+    }
+
     pub async fn fetch_universal_fx(
         &self,
     ) -> Result<PppState, Box<dyn std::error::Error + Send + Sync>> {
-        let mut weighted_rates: Vec<(HashMap<String, f64>, f64)> = Vec::new();
-
-        for (url, weight) in &self.endpoints {
-            match self.client.get(url).send().await {
-                Ok(resp) => {
-                    if resp.status().is_success() {
-                        if let Ok(data) = resp.json::<ExchangeRateResponse>().await {
-                            weighted_rates.push((data.rates, *weight));
-                        }
+        self.aggregate(&self.fetch_observations().await)
+    }
+
+    /// Queries every provider once. Failures are logged and left out.
+    pub async fn fetch_observations(&self) -> Vec<FxObservation> {
+        let mut observations = Vec::with_capacity(self.providers.len());
+        for (provider, weight) in &self.providers {
+            match provider.fetch().await {
+                Ok(state) => observations.push(FxObservation {
+                    provider: provider.name(),
+                    weight: *weight,
+                    state,
+                }),
+                Err(e) => tracing::warn!(
+                    provider = provider.name(),
+                    kind = e.kind(),
+                    "FX fetch failed: {}",
+                    e
+                ),
+            }
+        }
+        observations
+    }
+
+    /// Combines provider observations into one state on the configured bases.
+    ///
+    /// Per currency, quotes deviating more than `max_deviation_pct` from the median are
+    /// rejected (and listed in [`PppState::rejections`]); the rest are averaged by
+    /// provider weight. A currency with fewer than `min_providers` agreeing quotes is left
+    /// out, and the round fails when no currency reaches that quorum.
+    pub fn aggregate(
+        &self,
+        observations: &[FxObservation],
+    ) -> Result<PppState, Box<dyn std::error::Error + Send + Sync>> {
+        // currency -> (provider, quote, weight)
+        let mut quotes: BTreeMap<String, Vec<(&'static str, f64, f64)>> = BTreeMap::new();
+        let mut responding = 0;
+
+        for observation in observations {
+            let state = &observation.state;
+            let quoted = BaseIndices {
+                rates: state.rates.clone(),
+                ..Default::default()
+            };
+            match quoted.rebase(&state.base_currency, SOURCE_BASE_CURRENCY) {
+                Some(indices) => {
+                    responding += 1;
+                    for (currency, quote) in indices.rates {
+                        quotes.entry(currency).or_default().push((
+                            observation.provider,
+                            quote,
+                            observation.weight,
+                        ));
                     }
                 }
-                Err(e) => tracing::warn!("Failed to fetch from {}: {}", url, e),
+                None => tracing::warn!(
+                    provider = observation.provider,
+                    "FX provider quoted {} without a {} rate; skipping",
+                    state.base_currency,
+                    SOURCE_BASE_CURRENCY
+                ),
             }
         }
 
-        if weighted_rates.is_empty() {
-            tracing::error!("All Oracle endpoints failed. No rates available.");
-            return Err("Oracle failure".into());
+        if responding < self.min_providers {
+            tracing::error!(
+                responding,
+                min_providers = self.min_providers,
+                "Too few Oracle providers responded"
+            );
+            return Err(format!(
+                "Insufficient quorum: {} of the required {} providers responded",
+                responding, self.min_providers
+            )
+            .into());
         }
 
         let mut aggregated_rates = HashMap::new();
         let mut confidence_intervals = HashMap::new();
-        let mut keys: std::collections::HashSet<String> =
-            weighted_rates[0].0.keys().cloned().collect();
-        for (r, _) in &weighted_rates[1..] {
-            keys.extend(r.keys().cloned());
-        }
+        let mut provenance = BTreeMap::new();
+        let mut rejections = Vec::new();
 
-        for key in keys {
-            let mut weighted_values: Vec<(f64, f64)> = weighted_rates
-                .iter()
-                .filter_map(|(r, w)| r.get(&key).map(|v| (*v, *w)))
-                .collect();
-
-            if !weighted_values.is_empty() {
-                // Reject outliers (values more than 10% from the weighted mean)
-                let total_weight: f64 = weighted_values.iter().map(|(_, w)| w).sum();
-                let weighted_mean: f64 =
-                    weighted_values.iter().map(|(v, w)| v * w).sum::<f64>() / total_weight;
-
-                weighted_values.retain(|(v, _)| {
-                    let diff = (v - weighted_mean).abs() / weighted_mean;
-                    diff < 0.1 // 10% threshold
-                });
-
-                if !weighted_values.is_empty() {
-                    let final_weight: f64 = weighted_values.iter().map(|(_, w)| w).sum();
-                    let final_weighted_mean: f64 =
-                        weighted_values.iter().map(|(v, w)| v * w).sum::<f64>() / final_weight;
-                    aggregated_rates.insert(key.clone(), final_weighted_mean);
-
-                    // Calculate a simple confidence interval (relative standard deviation)
-                    if weighted_values.len() > 1 {
-                        let variance: f64 = weighted_values
-                            .iter()
-                            .map(|(v, w)| w * (v - final_weighted_mean).powi(2))
-                            .sum::<f64>()
-                            / final_weight;
-                        let std_dev = variance.sqrt();
-                        let confidence = 1.0 - (std_dev / final_weighted_mean).min(1.0);
-                        confidence_intervals.insert(key, confidence);
-                    } else {
-                        confidence_intervals.insert(key, 0.5); // Low confidence for single source
-                    }
+        for (currency, currency_quotes) in quotes {
+            let mut values: Vec<f64> = currency_quotes.iter().map(|(_, q, _)| *q).collect();
+            let Some(mid) = median(&mut values) else {
+                continue;
+            };
+
+            let mut agreeing = Vec::with_capacity(currency_quotes.len());
+            for (provider, quote, weight) in currency_quotes {
+                let deviation_pct = (quote - mid).abs() / mid * 100.0;
+                if deviation_pct > self.max_deviation_pct {
+                    tracing::warn!(
+                        provider,
+                        currency = %currency,
+                        quote,
+                        median = mid,
+                        deviation_pct,
+                        "Rejected outlier FX quote"
+                    );
+                    rejections.push(OutlierRejection {
+                        provider: provider.to_string(),
+                        currency: currency.clone(),
+                        quote,
+                        median: mid,
+                        deviation_pct,
+                    });
+                } else {
+                    agreeing.push((provider, quote, weight));
                 }
             }
+
+            if agreeing.len() < self.min_providers {
+                tracing::warn!(
+                    currency = %currency,
+                    agreeing = agreeing.len(),
+                    min_providers = self.min_providers,
+                    "FX quorum not reached; currency not published"
+                );
+                continue;
+            }
+
+            let total_weight: f64 = agreeing.iter().map(|(_, _, w)| w).sum();
+            let mean = agreeing.iter().map(|(_, q, w)| q * w).sum::<f64>() / total_weight;
+            // Calculate a simple confidence interval (relative standard deviation)
+            let confidence = if agreeing.len() > 1 {
+                let variance = agreeing
+                    .iter()
+                    .map(|(_, q, w)| w * (q - mean).powi(2))
+                    .sum::<f64>()
+                    / total_weight;
+                1.0 - (variance.sqrt() / mean).min(1.0)
+            } else {
+                0.5 // Low confidence for single source
+            };
+
+            aggregated_rates.insert(currency.clone(), mean);
+            confidence_intervals.insert(currency.clone(), confidence);
+            provenance.insert(
+                currency,
+                agreeing.iter().map(|(p, _, _)| p.to_string()).collect(),
+            );
+        }
+
+        if !aggregated_rates.keys().any(|currency| {
+            currency != SOURCE_BASE_CURRENCY && !COLLATERAL_ASSETS.contains(&currency.as_str())
+        }) {
+            tracing::error!("No currency reached the Oracle provider quorum. No rates available.");
+            return Err(format!(
+                "Insufficient quorum: no currency has {} agreeing providers",
+                self.min_providers
+            )
+            .into());
         }
 
         // Real-time PPP rates fetched from configured providers.
@@ -120,35 +406,251 @@ impl OracleAggregator {
         ppp_indices.insert("GBP".to_string(), 1.0);
         ppp_indices.insert("JPY".to_string(), 1.0);
 
-        Ok(PppState {
-            base_currency: "USD".to_string(),
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|e| anyhow::anyhow!("Time failure: {}", e))?
+            .as_secs();
+        // Collateral assets are priced separately; they are not FX rates to publish.
+        let collateral_prices = collateral_prices(&aggregated_rates);
+        aggregated_rates.retain(|currency, _| !COLLATERAL_ASSETS.contains(&currency.as_str()));
+        confidence_intervals.retain(|currency, _| !COLLATERAL_ASSETS.contains(&currency.as_str()));
+        let source = BaseIndices {
             rates: aggregated_rates,
             ppp_indices,
             confidence_intervals,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_err(|e| anyhow::anyhow!("Time failure: {}", e))?
-                .as_secs(),
-        })
+        };
+        let mut state = self.assemble_state(source, timestamp)?;
+        // Only attestations for quotes that made it into the aggregate.
+        state.attestations = observations
+            .iter()
+            .flat_map(|o| o.state.attestations.iter().map(move |a| (o.provider, a)))
+            .filter(|(provider, a)| {
+                a.symbol
+                    .split('/')
+                    .map(|currency| currency.to_ascii_uppercase())
+                    .filter(|currency| currency != SOURCE_BASE_CURRENCY)
+                    .any(|currency| {
+                        provenance
+                            .get(&currency)
+                            .is_some_and(|providers| providers.iter().any(|p| p == provider))
+                    })
+            })
+            .map(|(_, a)| a.clone())
+            .collect();
+        state.provenance = provenance;
+        state.rejections = rejections;
+        state.collateral_prices = collateral_prices;
+        Ok(state)
     }
+}
 
-    pub async fn push_state_to_contract(
-        &self,
-        state: PppState,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let wallet = Wallet::new().map_err(|e| anyhow::anyhow!("Wallet creation failed: {}", e))?;
-        let state_json = serde_json::to_string(&state)
-            .map_err(|e| anyhow::anyhow!("State serialization failed: {}", e))?;
-
-        let signed_call = ContractBridge::create_signed_call(
-            &wallet,
-            &self.contract_principal,
-            "update-fx-rates",
-            vec![state_json],
-        )
-        .map_err(|e| anyhow::anyhow!("Contract call signing failed: {}", e))?;
-
-        tracing::info!("Pushing Signed Oracle Call: {:?}", signed_call.payload);
-        Ok(format!("0x{}", signed_call.signature))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd_source() -> BaseIndices {
+        BaseIndices {
+            rates: HashMap::from([
+                ("USD".to_string(), 1.0),
+                ("EUR".to_string(), 0.9),
+                ("ZAR".to_string(), 18.0),
+            ]),
+            ppp_indices: HashMap::from([("EUR".to_string(), 0.8), ("ZAR".to_string(), 0.4)]),
+            confidence_intervals: HashMap::from([
+                ("EUR".to_string(), 0.9),
+                ("ZAR".to_string(), 0.6),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_rebase_divides_by_new_base() {
+        let zar = usd_source().rebase("USD", "ZAR").unwrap();
+        assert_eq!(zar.rates["ZAR"], 1.0);
+        assert!((zar.rates["USD"] - 1.0 / 18.0).abs() < 1e-12);
+        assert!((zar.rates["EUR"] - 0.05).abs() < 1e-12);
+        assert!((zar.ppp_indices["EUR"] - 2.0).abs() < 1e-12);
+        assert!((zar.ppp_indices["USD"] - 2.5).abs() < 1e-12);
+        assert!(!zar.ppp_indices.contains_key("ZAR"));
+        assert_eq!(zar.confidence_intervals["EUR"], 0.6);
+        assert_eq!(zar.confidence_intervals["USD"], 0.6);
+
+        let back = zar.rebase("ZAR", "USD").unwrap();
+        assert!((back.rates["EUR"] - 0.9).abs() < 1e-12);
+        assert!((back.ppp_indices["EUR"] - 0.8).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_aggregates_providers_and_skips_failures() {
+        use crate::oracle::providers::{MockProvider, OracleError};
+
+        let usd: Arc<dyn FxProvider> = Arc::new(MockProvider::new(
+            "USD",
+            HashMap::from([("USD".to_string(), 1.0), ("EUR".to_string(), 0.9)]),
+            1,
+        ));
+        // Quoted against EUR; rebased to USD before aggregation.
+        let eur: Arc<dyn FxProvider> = Arc::new(MockProvider::new(
+            "EUR",
+            HashMap::from([("USD".to_string(), 1.0 / 0.9)]),
+            1,
+        ));
+        let down: Arc<dyn FxProvider> = Arc::new(MockProvider::failing(OracleError::Network(
+            "connection refused".to_string(),
+        )));
+        let aggregator =
+            OracleAggregator::from_providers(vec![(usd, 0.5), (eur, 0.25), (down, 0.25)]);
+
+        let state = aggregator.fetch_universal_fx().await.unwrap();
+        assert!((state.rates["EUR"] - 0.9).abs() < 1e-9);
+        assert!(state.confidence_intervals["EUR"] > 0.99);
+
+        let all_down = OracleAggregator::from_providers(vec![(
+            Arc::new(MockProvider::failing(OracleError::Auth(
+                "invalid-key".to_string(),
+            ))),
+            1.0,
+        )]);
+        assert!(all_down.fetch_universal_fx().await.is_err());
+    }
+
+    #[test]
+    fn test_rebase_requires_rate_for_new_base() {
+        assert!(usd_source().rebase("USD", "JPY").is_none());
+    }
+
+    #[test]
+    fn test_assemble_state_publishes_every_configured_base() {
+        let aggregator = OracleAggregator::new("http://localhost".to_string())
+            .with_base_currencies(
+                "EUR".to_string(),
+                vec!["ZAR".to_string(), "EUR".to_string(), "JPY".to_string()],
+            );
+        let state = aggregator.assemble_state(usd_source(), 42).unwrap();
+        assert_eq!(state.base_currency, "EUR");
+        assert_eq!(state.rates["EUR"], 1.0);
+        assert_eq!(state.bases.keys().collect::<Vec<_>>(), vec!["ZAR"]);
+        assert_eq!(state.for_base("ZAR"), usd_source().rebase("USD", "ZAR"));
+        assert!(state.for_base("EUR").is_some());
+
+        let unknown = OracleAggregator::new("http://localhost".to_string())
+            .with_base_currencies("JPY".to_string(), vec![]);
+        assert!(unknown.assemble_state(usd_source(), 42).is_err());
+    }
+
+    fn eur_quote(provider: &'static str, eur: f64) -> FxObservation {
+        FxObservation {
+            provider,
+            weight: 1.0,
+            state: PppState {
+                base_currency: "USD".to_string(),
+                rates: HashMap::from([("USD".to_string(), 1.0), ("EUR".to_string(), eur)]),
+                ppp_indices: HashMap::new(),
+                confidence_intervals: HashMap::new(),
+                timestamp: 1,
+                bases: BTreeMap::new(),
+                provenance: BTreeMap::new(),
+                rejections: Vec::new(),
+                attestations: Vec::new(),
+                collateral_prices: BTreeMap::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_collateral_prices_invert_usd_rates() {
+        let prices = collateral_prices(&HashMap::from([
+            ("BTC".to_string(), 0.000_02),
+            ("STX".to_string(), 0.0),
+            ("EUR".to_string(), 0.9),
+        ]));
+        assert_eq!(prices.len(), 1);
+        assert!((prices["BTC"] - 50_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_aggregate_prices_collateral_outside_the_fx_rates() {
+        let aggregator = OracleAggregator::new("http://localhost".to_string());
+        let mut crypto = eur_quote("coingecko", 0.9);
+        crypto.state.rates = HashMap::from([
+            ("USD".to_string(), 1.0),
+            ("BTC".to_string(), 1.0 / 60_000.0),
+            ("STX".to_string(), 1.0 / 2.0),
+        ]);
+        let state = aggregator
+            .aggregate(&[eur_quote("frankfurter", 0.9), crypto])
+            .unwrap();
+
+        assert!((state.collateral_prices["BTC"] - 60_000.0).abs() < 1e-6);
+        assert!((state.collateral_prices["STX"] - 2.0).abs() < 1e-9);
+        assert!(!state.rates.contains_key("BTC"));
+        assert!(!state.confidence_intervals.contains_key("STX"));
+        assert!((state.rates["EUR"] - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&mut [4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        assert_eq!(median(&mut []), None);
+    }
+
+    #[test]
+    fn test_aggregate_rejects_outliers_from_median() {
+        let aggregator = OracleAggregator::new("http://localhost".to_string()).with_quorum(2, 5.0);
+        let state = aggregator
+            .aggregate(&[
+                eur_quote("a", 0.90),
+                eur_quote("b", 0.92),
+                eur_quote("glitch", 9.1),
+            ])
+            .unwrap();
+
+        assert!((state.rates["EUR"] - 0.91).abs() < 1e-9);
+        assert_eq!(state.provenance["EUR"], vec!["a", "b"]);
+        assert_eq!(state.rejections.len(), 1);
+        let rejection = &state.rejections[0];
+        assert_eq!(rejection.provider, "glitch");
+        assert_eq!(rejection.currency, "EUR");
+        assert_eq!(rejection.median, 0.92);
+        assert!((rejection.deviation_pct - 889.130434).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_aggregate_requires_quorum() {
+        let aggregator = OracleAggregator::new("http://localhost".to_string()).with_quorum(2, 10.0);
+
+        let err = aggregator.aggregate(&[eur_quote("a", 0.90)]).unwrap_err();
+        assert!(err.to_string().contains("quorum"));
+
+        // Both respond, but neither is within 10% of their median.
+        let err = aggregator
+            .aggregate(&[eur_quote("a", 0.90), eur_quote("b", 2.0)])
+            .unwrap_err();
+        assert!(err.to_string().contains("quorum"));
+    }
+
+    #[test]
+    fn test_aggregate_forwards_attestations_of_contributing_providers() {
+        use crate::oracle::attestation::tests::signed;
+        use k256::ecdsa::SigningKey;
+
+        let publisher = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let mut pyth = eur_quote("pyth", 0.91);
+        pyth.state.attestations = vec![signed(&publisher, "EUR/USD", 109_890, -5)];
+        let aggregator = OracleAggregator::new("http://localhost".to_string());
+
+        let state = aggregator
+            .aggregate(&[eur_quote("a", 0.90), pyth.clone()])
+            .unwrap();
+        assert_eq!(state.attestations, pyth.state.attestations);
+
+        // An outlier's attestation is not forwarded with rates it did not contribute to.
+        let mut glitch = pyth;
+        glitch.state.rates.insert("EUR".to_string(), 9.1);
+        let state = aggregator
+            .aggregate(&[eur_quote("a", 0.90), eur_quote("b", 0.91), glitch])
+            .unwrap();
+        assert!(state.attestations.is_empty());
     }
 }
@@ -0,0 +1,234 @@
+//! [NEXUS-ORACLE-03] Pyth-style signed price attestations.
+//!
+//! A publisher signs each price update (secp256k1 ECDSA over SHA-256 of
+//! [`PriceAttestation::signing_message`]). Updates are only accepted once the signature
+//! verifies against one of the trusted publisher keys, and the attestation then travels
+//! with the aggregate [`PppState`](super::aggregator::PppState) so the oracle contract
+//! can check the same signature on-chain.
+
+use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How far ahead of the local clock a `publish_time` may be, for clock skew.
+pub const MAX_CLOCK_SKEW_SECS: u64 = 60;
+
+/// One signed price update. `price` and `conf` are fixed-point with exponent `expo`,
+/// as in Pyth (`price * 10^expo`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceAttestation {
+    pub feed_id: String,
+    /// Pair quoted as `BASE/QUOTE` (e.g. `EUR/USD`: USD per one EUR).
+    pub symbol: String,
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: u64,
+    /// Compressed SEC1 public key of the publisher (hex).
+    pub publisher: String,
+    /// 64-byte `r || s` ECDSA signature (hex).
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttestationError {
+    UntrustedPublisher(String),
+    InvalidPublisherKey,
+    InvalidSignature,
+    InvalidSymbol(String),
+    NonPositivePrice,
+    FuturePublishTime(u64),
+    StalePublishTime(u64),
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UntrustedPublisher(key) => write!(f, "untrusted publisher {}", key),
+            Self::InvalidPublisherKey => write!(f, "invalid publisher key"),
+            Self::InvalidSignature => write!(f, "signature does not verify"),
+            Self::InvalidSymbol(symbol) => write!(f, "invalid symbol '{}'", symbol),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::FuturePublishTime(t) => write!(f, "publish_time {} is in the future", t),
+            Self::StalePublishTime(t) => write!(f, "publish_time {} is too old", t),
+        }
+    }
+}
+
+impl std::error::Error for AttestationError {}
+
+impl PriceAttestation {
+    /// Bytes covered by the publisher signature.
+    pub fn signing_message(&self) -> Vec<u8> {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.feed_id, self.symbol, self.price, self.conf, self.expo, self.publish_time
+        )
+        .into_bytes()
+    }
+
+    /// Checks that `publisher` is one of `trusted_publishers` (hex SEC1 keys) and that
+    /// `signature` verifies over [`Self::signing_message`].
+    pub fn verify(&self, trusted_publishers: &[String]) -> Result<(), AttestationError> {
+        let publisher = self.publisher.to_ascii_lowercase();
+        if !trusted_publishers
+            .iter()
+            .any(|key| key.eq_ignore_ascii_case(&publisher))
+        {
+            return Err(AttestationError::UntrustedPublisher(publisher));
+        }
+        let key_bytes =
+            hex::decode(&publisher).map_err(|_| AttestationError::InvalidPublisherKey)?;
+        let key = VerifyingKey::from_sec1_bytes(&key_bytes)
+            .map_err(|_| AttestationError::InvalidPublisherKey)?;
+        let sig_bytes =
+            hex::decode(&self.signature).map_err(|_| AttestationError::InvalidSignature)?;
+        let signature =
+            Signature::from_slice(&sig_bytes).map_err(|_| AttestationError::InvalidSignature)?;
+        key.verify(&self.signing_message(), &signature)
+            .map_err(|_| AttestationError::InvalidSignature)?;
+        if self.price <= 0 {
+            return Err(AttestationError::NonPositivePrice);
+        }
+        Ok(())
+    }
+
+    /// Rejects a `publish_time` more than [`MAX_CLOCK_SKEW_SECS`] ahead of `now`, or
+    /// more than `max_age_secs` behind it.
+    pub fn check_publish_time(&self, now: u64, max_age_secs: u64) -> Result<(), AttestationError> {
+        if self.publish_time > now.saturating_add(MAX_CLOCK_SKEW_SECS) {
+            return Err(AttestationError::FuturePublishTime(self.publish_time));
+        }
+        if now.saturating_sub(self.publish_time) > max_age_secs {
+            return Err(AttestationError::StalePublishTime(self.publish_time));
+        }
+        Ok(())
+    }
+
+    /// `price * 10^expo`.
+    pub fn value(&self) -> f64 {
+        self.price as f64 * 10f64.powi(self.expo)
+    }
+
+    /// The attested rate as `(currency, units of currency per one base)`, or `None` when
+    /// neither side of the pair is `base`.
+    pub fn rate_against(&self, base: &str) -> Result<Option<(String, f64)>, AttestationError> {
+        let (left, right) = self
+            .symbol
+            .split_once('/')
+            .filter(|(l, r)| !l.is_empty() && !r.is_empty())
+            .ok_or_else(|| AttestationError::InvalidSymbol(self.symbol.clone()))?;
+        let value = self.value();
+        if !(value.is_finite() && value > 0.0) {
+            return Err(AttestationError::NonPositivePrice);
+        }
+        Ok(if right.eq_ignore_ascii_case(base) {
+            Some((left.to_ascii_uppercase(), 1.0 / value))
+        } else if left.eq_ignore_ascii_case(base) {
+            Some((right.to_ascii_uppercase(), value))
+        } else {
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use k256::ecdsa::{signature::Signer, SigningKey};
+
+    pub(crate) fn signed(
+        key: &SigningKey,
+        symbol: &str,
+        price: i64,
+        expo: i32,
+    ) -> PriceAttestation {
+        let attestation = PriceAttestation {
+            feed_id: format!("feed-{}", symbol),
+            symbol: symbol.to_string(),
+            price,
+            conf: 10,
+            expo,
+            publish_time: 1_751_846_400,
+            publisher: hex::encode(key.verifying_key().to_sec1_bytes()),
+            signature: String::new(),
+        };
+        signed_at(key, attestation)
+    }
+
+    /// `attestation` re-signed by `key`, e.g. after moving its `publish_time`.
+    pub(crate) fn signed_at(
+        key: &SigningKey,
+        mut attestation: PriceAttestation,
+    ) -> PriceAttestation {
+        let signature: Signature = key.sign(&attestation.signing_message());
+        attestation.signature = hex::encode(signature.to_bytes());
+        attestation
+    }
+
+    #[test]
+    fn test_verify_accepts_trusted_signature() {
+        let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let attestation = signed(&key, "EUR/USD", 108_250, -5);
+        let trusted = vec![attestation.publisher.to_ascii_uppercase()];
+        assert_eq!(attestation.verify(&trusted), Ok(()));
+
+        let (currency, rate) = attestation.rate_against("USD").unwrap().unwrap();
+        assert_eq!(currency, "EUR");
+        assert!((rate - 1.0 / 1.0825).abs() < 1e-12);
+        let jpy = signed(&key, "USD/JPY", 14_350, -2);
+        assert_eq!(
+            jpy.rate_against("USD").unwrap(),
+            Some(("JPY".to_string(), 143.5))
+        );
+        assert_eq!(jpy.rate_against("ZAR").unwrap(), None);
+    }
+
+    #[test]
+    fn test_publish_time_is_bounded() {
+        let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let attestation = signed(&key, "EUR/USD", 108_250, -5);
+        let t = attestation.publish_time;
+        assert_eq!(attestation.check_publish_time(t, 300), Ok(()));
+        assert_eq!(attestation.check_publish_time(t + 300, 300), Ok(()));
+        assert_eq!(
+            attestation.check_publish_time(t + 301, 300),
+            Err(AttestationError::StalePublishTime(t))
+        );
+        assert_eq!(
+            attestation.check_publish_time(t - MAX_CLOCK_SKEW_SECS, 300),
+            Ok(())
+        );
+        assert_eq!(
+            attestation.check_publish_time(t - MAX_CLOCK_SKEW_SECS - 1, 300),
+            Err(AttestationError::FuturePublishTime(t))
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_or_untrusted_updates() {
+        let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let trusted = vec![hex::encode(key.verifying_key().to_sec1_bytes())];
+
+        let mut tampered = signed(&key, "EUR/USD", 108_250, -5);
+        tampered.price = 208_250;
+        assert_eq!(
+            tampered.verify(&trusted),
+            Err(AttestationError::InvalidSignature)
+        );
+
+        let mut garbage = signed(&key, "EUR/USD", 108_250, -5);
+        garbage.signature = "zz".to_string();
+        assert_eq!(
+            garbage.verify(&trusted),
+            Err(AttestationError::InvalidSignature)
+        );
+
+        let other = SigningKey::from_slice(&[2u8; 32]).unwrap();
+        let untrusted = signed(&other, "EUR/USD", 108_250, -5);
+        assert!(matches!(
+            untrusted.verify(&trusted),
+            Err(AttestationError::UntrustedPublisher(_))
+        ));
+    }
+}
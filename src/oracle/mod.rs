@@ -1,51 +1,347 @@
-use crate::oracle::aggregator::{OracleAggregator, PppState};
+//! [NEXUS-ORACLE-01] FX/PPP oracle: polls the configured providers on an interval,
+//! logs every provider observation, and publishes the aggregate to Postgres, Redis and
+//! the oracle contract.
+
+use crate::latency::timed_query;
+use crate::oracle::aggregator::{FxObservation, OracleAggregator, PppState};
+use crate::oracle::providers::FxProvider;
+use crate::oracle::push::{OraclePusher, OracleSignerIdentity, PushOutcome};
+use crate::signing::RotatingSigner;
+use crate::stacks::StacksSigner;
+use crate::storage::store::NexusStore;
 use crate::storage::Storage;
+use lazy_static::lazy_static;
+use prometheus::{register_histogram, register_int_gauge, Histogram, IntGauge};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
 use std::sync::Arc;
-use tokio::time::{self, Duration};
+use tokio::sync::watch;
+use tokio::time::{self, Duration, Instant};
 
 pub mod aggregator;
+pub mod attestation;
+pub mod providers;
+pub mod push;
+
+/// Redis key holding the latest aggregate [`PppState`] as JSON.
+pub const ORACLE_PPP_CACHE_KEY: &str = "oracle:ppp";
+/// Upper bound on the delay between fetches while providers keep failing.
+pub const MAX_FETCH_BACKOFF: Duration = Duration::from_secs(15 * 60);
+/// Default age after which the published aggregate is reported as stale.
+pub const DEFAULT_MAX_STATE_AGE: Duration = Duration::from_secs(5 * 60);
+/// Cap on points returned by one [`observation_history`] query.
+pub const MAX_HISTORY_POINTS: i64 = 1000;
+
+lazy_static! {
+    static ref ORACLE_FETCH_LATENCY: Histogram = register_histogram!(
+        "nexus_oracle_fetch_duration_seconds",
+        "Time to query every FX provider in one oracle round"
+    )
+    .unwrap();
+    static ref ORACLE_STALENESS: IntGauge = register_int_gauge!(
+        "nexus_oracle_staleness_seconds",
+        "Seconds since the oracle last published an aggregate FX state, as of the latest round"
+    )
+    .unwrap();
+}
 
 pub struct OracleService {
     storage: Arc<Storage>,
     aggregator: OracleAggregator,
+    fetch_interval: Duration,
+    max_state_age: Duration,
+    pusher: Option<OraclePusher>,
+}
+
+/// The latest aggregate as served to consumers, with its age at read time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleSnapshot {
+    #[serde(flatten)]
+    pub state: PppState,
+    pub age_secs: u64,
+    /// Older than the configured maximum age; consumers should not price against it.
+    pub stale: bool,
+}
+
+impl OracleSnapshot {
+    pub fn new(state: PppState, now: u64, max_age: Duration) -> Self {
+        let age_secs = now.saturating_sub(state.timestamp);
+        Self {
+            state,
+            age_secs,
+            stale: age_secs > max_age.as_secs(),
+        }
+    }
+}
+
+/// One provider's quote for a currency, from `oracle_observations`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ObservationPoint {
+    pub provider: String,
+    pub base_currency: String,
+    pub rate: f64,
+    pub timestamp: i64,
+}
+
+/// Provider quotes for `currency` with `from <= timestamp <= to`, oldest first, at most
+/// `limit` (capped at [`MAX_HISTORY_POINTS`]).
+pub async fn observation_history(
+    pool: &PgPool,
+    currency: &str,
+    from: u64,
+    to: u64,
+    limit: i64,
+) -> sqlx::Result<Vec<ObservationPoint>> {
+    timed_query(
+        "observation_history",
+        sqlx::query_as(
+            "SELECT provider, base_currency, (rates->>$1)::float8 AS rate, timestamp
+         FROM oracle_observations
+         WHERE rates ? $1 AND timestamp BETWEEN $2 AND $3
+         ORDER BY timestamp ASC, id ASC
+         LIMIT $4",
+        )
+        .bind(currency)
+        .bind(from.min(i64::MAX as u64) as i64)
+        .bind(to.min(i64::MAX as u64) as i64)
+        .bind(limit.clamp(1, MAX_HISTORY_POINTS))
+        .fetch_all(pool),
+    )
+    .await
 }
 
 impl OracleService {
-    pub fn new(storage: Arc<Storage>, endpoint_url: String, contract_principal: String) -> Self {
+    pub fn new(storage: Arc<Storage>, endpoint_url: String) -> Self {
+        Self::from_aggregator(storage, OracleAggregator::new(endpoint_url))
+    }
+
+    /// Aggregates exactly `providers`, weighted as given.
+    pub fn with_providers(
+        storage: Arc<Storage>,
+        providers: Vec<(Arc<dyn FxProvider>, f64)>,
+    ) -> Self {
+        Self::from_aggregator(storage, OracleAggregator::from_providers(providers))
+    }
+
+    fn from_aggregator(storage: Arc<Storage>, aggregator: OracleAggregator) -> Self {
         Self {
             storage,
-            aggregator: OracleAggregator::new(endpoint_url, contract_principal),
+            aggregator,
+            fetch_interval: Duration::from_secs(60),
+            max_state_age: DEFAULT_MAX_STATE_AGE,
+            pusher: None,
         }
     }
 
-    pub async fn run(&self) -> anyhow::Result<()> {
-        tracing::info!("Starting OracleService...");
-        let mut interval = time::interval(Duration::from_secs(60));
+    /// Publishes indices against `base`, plus each of `extra` in the same `PppState`.
+    pub fn with_base_currencies(mut self, base: String, extra: Vec<String>) -> Self {
+        self.aggregator = self.aggregator.with_base_currencies(base, extra);
+        self
+    }
+
+    /// See [`OracleAggregator::with_quorum`].
+    pub fn with_quorum(mut self, min_providers: usize, max_deviation_pct: f64) -> Self {
+        self.aggregator = self
+            .aggregator
+            .with_quorum(min_providers, max_deviation_pct);
+        self
+    }
+
+    pub fn with_fetch_interval(mut self, interval: Duration) -> Self {
+        self.fetch_interval = interval;
+        self
+    }
+
+    /// Age after which [`Self::snapshot`] flags the aggregate as stale.
+    pub fn with_max_state_age(mut self, max_age: Duration) -> Self {
+        self.max_state_age = max_age;
+        self
+    }
+
+    /// Publishes each round's aggregate to the oracle contract through `pusher`.
+    pub fn with_pusher(mut self, pusher: OraclePusher) -> Self {
+        self.pusher = Some(pusher);
+        self
+    }
+
+    /// The key on-chain pushes are signed with; `None` when pushes are disabled.
+    pub fn signer_identity(&self) -> Option<OracleSignerIdentity> {
+        self.pusher.as_ref().map(OraclePusher::signer_identity)
+    }
+
+    /// Key signing on-chain pushes, for rotation; `None` when pushes are disabled.
+    pub fn push_signer(&self) -> Option<&Arc<RotatingSigner<StacksSigner>>> {
+        self.pusher.as_ref().map(OraclePusher::signer)
+    }
+
+    /// Fetches every `fetch_interval` until `shutdown` flips to true. After a failed
+    /// round the delay doubles per consecutive failure, up to [`MAX_FETCH_BACKOFF`].
+    pub async fn run(&self, mut shutdown: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!(
+            providers = self.aggregator.provider_count(),
+            interval_secs = self.fetch_interval.as_secs(),
+            "Starting OracleService..."
+        );
+        let mut last_success: Option<Instant> = None;
+        let mut failures = 0u32;
 
         loop {
-            interval.tick().await;
-            match self.aggregator.fetch_universal_fx().await {
-                Ok(state) => {
-                    if let Err(e) = self.persist_fx_state(&state).await {
-                        tracing::error!("Failed to persist FX state: {}", e);
+            if *shutdown.borrow() {
+                break;
+            }
+            match self.fetch_once().await {
+                Ok(_) => {
+                    failures = 0;
+                    last_success = Some(Instant::now());
+                }
+                Err(e) => {
+                    failures = failures.saturating_add(1);
+                    tracing::error!(
+                        consecutive_failures = failures,
+                        staleness_secs = last_success.map(|t| t.elapsed().as_secs()),
+                        "Oracle fetch failed: {}",
+                        e
+                    );
+                }
+            }
+            if let Some(t) = last_success {
+                ORACLE_STALENESS.set(t.elapsed().as_secs() as i64);
+            }
+
+            tokio::select! {
+                _ = time::sleep(fetch_delay(self.fetch_interval, failures)) => {}
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        break;
                     }
-                    // Pushing to contract is optional/best-effort in the loop
-                    let _ = self.aggregator.push_state_to_contract(state).await;
                 }
-                Err(e) => tracing::error!("Oracle fetch failed: {}", e),
             }
         }
+        tracing::info!("OracleService stopping");
+        Ok(())
+    }
+
+    /// One oracle round: query the providers, log each observation, aggregate, and
+    /// publish the result. Fails only when no provider produced usable rates.
+    pub async fn fetch_once(&self) -> anyhow::Result<PppState> {
+        let started = Instant::now();
+        let observations = self.aggregator.fetch_observations().await;
+        let elapsed = started.elapsed();
+        ORACLE_FETCH_LATENCY.observe(elapsed.as_secs_f64());
+        tracing::debug!(
+            latency_ms = elapsed.as_millis() as u64,
+            observations = observations.len(),
+            "Oracle providers queried"
+        );
+
+        for observation in &observations {
+            if let Err(e) = self.persist_observation(observation).await {
+                tracing::warn!(
+                    provider = observation.provider,
+                    "Failed to persist FX observation: {}",
+                    e
+                );
+            }
+        }
+
+        let state = self
+            .aggregator
+            .aggregate(&observations)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        if let Err(e) = self.persist_fx_state(&state).await {
+            tracing::error!("Failed to persist FX state: {}", e);
+        }
+        if let Err(e) = self.cache_latest(&state).await {
+            tracing::warn!("Failed to cache FX state in Redis: {}", e);
+        }
+        ORACLE_STALENESS.set(0);
+        if let Some(pusher) = &self.pusher {
+            let pushed = match self.storage.last_oracle_update().await {
+                Ok(confirmed) => {
+                    pusher
+                        .push(&self.storage.pg_pool, &state, confirmed.as_ref())
+                        .await
+                }
+                Err(e) => Err(e.context("reading the last confirmed oracle update")),
+            };
+            match pushed {
+                Ok(PushOutcome::Broadcast {
+                    txid,
+                    payload_digest,
+                }) => tracing::info!(%txid, %payload_digest, "Oracle state pushed on-chain"),
+                Ok(PushOutcome::Skipped { .. }) => {}
+                Err(e) => tracing::warn!("Failed to push oracle state on-chain: {:#}", e),
+            }
+        }
+        Ok(state)
+    }
+
+    /// The aggregate last written to [`ORACLE_PPP_CACHE_KEY`].
+    pub async fn latest_cached(&self) -> anyhow::Result<Option<PppState>> {
+        let raw: Option<String> = self
+            .storage
+            .with_redis(|mut conn| async move {
+                redis::cmd("GET")
+                    .arg(self.storage.redis_key(ORACLE_PPP_CACHE_KEY))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        raw.map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// The cached aggregate with its current age; `None` before the first successful fetch.
+    pub async fn snapshot(&self) -> anyhow::Result<Option<OracleSnapshot>> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        Ok(self
+            .latest_cached()
+            .await?
+            .map(|state| OracleSnapshot::new(state, now, self.max_state_age)))
+    }
+
+    async fn cache_latest(&self, state: &PppState) -> anyhow::Result<()> {
+        let json = serde_json::to_string(state)?;
+        self.storage
+            .with_redis(|mut conn| {
+                let json = json.clone();
+                async move {
+                    redis::cmd("SET")
+                        .arg(self.storage.redis_key(ORACLE_PPP_CACHE_KEY))
+                        .arg(json)
+                        .query_async::<()>(&mut conn)
+                        .await
+                }
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn persist_observation(&self, observation: &FxObservation) -> anyhow::Result<()> {
+        timed_query("persist_observation", sqlx::query(
+            "INSERT INTO oracle_observations (provider, base_currency, rates, timestamp) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(observation.provider)
+        .bind(&observation.state.base_currency)
+        .bind(serde_json::to_value(&observation.state.rates)?)
+        .bind(observation.state.timestamp as i64)
+        .execute(&self.storage.pg_pool)).await?;
+        Ok(())
     }
 
     async fn persist_fx_state(&self, state: &PppState) -> anyhow::Result<()> {
-        sqlx::query("INSERT INTO oracle_fx_history (base_currency, rates, ppp_indices, confidence_intervals, timestamp) VALUES ($1, $2, $3, $4, $5)")
+        timed_query("persist_fx_state", sqlx::query("INSERT INTO oracle_fx_history (base_currency, rates, ppp_indices, confidence_intervals, timestamp, bases, provenance, rejections, attestations) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)")
             .bind(&state.base_currency)
             .bind(serde_json::to_value(&state.rates)?)
             .bind(serde_json::to_value(&state.ppp_indices)?)
             .bind(serde_json::to_value(&state.confidence_intervals)?)
             .bind(state.timestamp as i64)
-            .execute(&self.storage.pg_pool)
-            .await?;
+            .bind(serde_json::to_value(&state.bases)?)
+            .bind(serde_json::to_value(&state.provenance)?)
+            .bind(serde_json::to_value(&state.rejections)?)
+            .bind(serde_json::to_value(&state.attestations)?)
+            .execute(&self.storage.pg_pool)).await?;
         Ok(())
     }
 
@@ -67,7 +363,14 @@ impl OracleService {
                     .get("currency")
                     .and_then(|v| v.as_str())
                     .unwrap_or("USD");
-                if let Some(oracle_rate) = rates.rates.get(currency) {
+                let base = payload
+                    .get("base_currency")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&rates.base_currency);
+                let oracle_rate = rates
+                    .for_base(base)
+                    .and_then(|indices| indices.rates.get(currency).copied());
+                if let Some(oracle_rate) = oracle_rate {
                     let diff = (payload_rate - oracle_rate).abs() / oracle_rate;
                     if diff > 0.05 {
                         // 5% tolerance
@@ -84,3 +387,89 @@ impl OracleService {
         Ok(true)
     }
 }
+
+/// Delay before the next round: `interval`, doubled per consecutive failure and capped
+/// at [`MAX_FETCH_BACKOFF`] (or `interval`, if that is longer).
+pub fn fetch_delay(interval: Duration, consecutive_failures: u32) -> Duration {
+    let factor = 1u32 << consecutive_failures.min(10);
+    interval
+        .saturating_mul(factor)
+        .min(MAX_FETCH_BACKOFF.max(interval))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::providers::{MockProvider, OracleError};
+
+    fn state_at(timestamp: u64) -> PppState {
+        PppState {
+            base_currency: "USD".to_string(),
+            rates: std::collections::HashMap::from([("EUR".to_string(), 0.9)]),
+            ppp_indices: Default::default(),
+            confidence_intervals: Default::default(),
+            timestamp,
+            bases: Default::default(),
+            provenance: std::collections::BTreeMap::from([(
+                "EUR".to_string(),
+                vec!["frankfurter".to_string()],
+            )]),
+            rejections: Vec::new(),
+            attestations: Vec::new(),
+            collateral_prices: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_reports_fresh_state() {
+        let snapshot = OracleSnapshot::new(state_at(1_000), 1_060, Duration::from_secs(300));
+        assert_eq!(snapshot.age_secs, 60);
+        assert!(!snapshot.stale);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["stale"], false);
+        assert_eq!(json["rates"]["EUR"], 0.9);
+        assert_eq!(json["provenance"]["EUR"][0], "frankfurter");
+    }
+
+    #[test]
+    fn test_snapshot_flags_stale_state() {
+        let snapshot = OracleSnapshot::new(state_at(1_000), 1_301, Duration::from_secs(300));
+        assert_eq!(snapshot.age_secs, 301);
+        assert!(snapshot.stale);
+        // A timestamp ahead of the local clock is treated as brand new.
+        assert_eq!(
+            OracleSnapshot::new(state_at(2_000), 1_000, Duration::from_secs(300)).age_secs,
+            0
+        );
+    }
+
+    #[test]
+    fn test_fetch_delay_backs_off_and_caps() {
+        let interval = Duration::from_secs(60);
+        assert_eq!(fetch_delay(interval, 0), interval);
+        assert_eq!(fetch_delay(interval, 1), Duration::from_secs(120));
+        assert_eq!(fetch_delay(interval, 3), Duration::from_secs(480));
+        assert_eq!(fetch_delay(interval, 40), MAX_FETCH_BACKOFF);
+        let slow = Duration::from_secs(3600);
+        assert_eq!(fetch_delay(slow, 5), slow);
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_on_shutdown_signal() {
+        let provider: Arc<dyn FxProvider> = Arc::new(MockProvider::failing(OracleError::Network(
+            "connection refused".to_string(),
+        )));
+        let service = OracleService::with_providers(Storage::for_tests(), vec![(provider, 1.0)])
+            .with_fetch_interval(Duration::from_secs(3600));
+
+        let (tx, rx) = watch::channel(false);
+        let run = tokio::spawn(async move { service.run(rx).await });
+        tx.send(true).unwrap();
+        time::timeout(Duration::from_secs(5), run)
+            .await
+            .expect("oracle task should stop on shutdown")
+            .unwrap()
+            .unwrap();
+    }
+}
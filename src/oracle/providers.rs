@@ -0,0 +1,729 @@
+//! [NEXUS-ORACLE-02] FX rate providers feeding the oracle aggregator.
+//!
+//! Each provider fetches one upstream's latest rates and returns them as a [`PppState`]
+//! quoted against the provider's own base, with the provider's publication time as the
+//! timestamp. PPP indices and confidences are left to the aggregator.
+
+use crate::config::Config;
+use crate::oracle::aggregator::PppState;
+use crate::oracle::attestation::PriceAttestation;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+pub const EXCHANGERATE_API_URL: &str = "https://v6.exchangerate-api.com/v6";
+/// Keyless ExchangeRate-API endpoint (daily updates, attribution required).
+pub const EXCHANGERATE_API_OPEN_URL: &str = "https://open.er-api.com/v6";
+/// Frankfurter serves the European Central Bank reference rates.
+pub const FRANKFURTER_URL: &str = "https://api.frankfurter.app";
+/// CoinGecko's public API, quoting the vault collateral assets in USD.
+pub const COINGECKO_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// CoinGecko coin id of each [`COLLATERAL_ASSETS`](crate::oracle::aggregator::COLLATERAL_ASSETS) entry.
+const COINGECKO_IDS: [(&str, &str); 2] = [("BTC", "bitcoin"), ("STX", "blockstack")];
+
+/// Provider names accepted in `ORACLE_FX_PROVIDERS`, and its default.
+pub const PROVIDER_NAMES: [&str; 3] = ["exchangerate-api", "frankfurter", "coingecko"];
+/// Opt-in signed provider; also requires `ORACLE_PYTH_URL` and trusted publisher keys.
+pub const PYTH_PROVIDER_NAME: &str = "pyth";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OracleError {
+    /// The provider could not be reached or answered with a server error.
+    Network(String),
+    /// The provider rejected the credentials (missing, invalid or exhausted key).
+    Auth(String),
+    /// The provider answered but the body is not a usable rate table.
+    Payload(String),
+    /// No signed update in the response verified against a trusted publisher.
+    Attestation(String),
+}
+
+impl OracleError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Network(_) => "network",
+            Self::Auth(_) => "auth",
+            Self::Payload(_) => "payload",
+            Self::Attestation(_) => "attestation",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Network(msg) | Self::Auth(msg) | Self::Payload(msg) | Self::Attestation(msg) => {
+                msg
+            }
+        }
+    }
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl std::error::Error for OracleError {}
+
+#[async_trait]
+pub trait FxProvider: Send + Sync {
+    /// Short name used in logs and `ORACLE_FX_PROVIDERS`.
+    fn name(&self) -> &'static str;
+
+    async fn fetch(&self) -> Result<PppState, OracleError>;
+}
+
+/// Share of the aggregate weight given to `ORACLE_ENDPOINT_URL` when it is configured;
+/// the remaining providers split the rest evenly.
+pub const PRIMARY_ENDPOINT_WEIGHT: f64 = 0.5;
+
+/// Weights `primary` at [`PRIMARY_ENDPOINT_WEIGHT`] and shares the remainder (or all of
+/// it, without a primary) evenly across `secondary`.
+pub fn weighted(
+    primary: Option<Arc<dyn FxProvider>>,
+    secondary: Vec<Arc<dyn FxProvider>>,
+) -> Vec<(Arc<dyn FxProvider>, f64)> {
+    let mut providers = Vec::with_capacity(secondary.len() + 1);
+    let mut remaining = 1.0;
+    if let Some(primary) = primary {
+        providers.push((primary, PRIMARY_ENDPOINT_WEIGHT));
+        remaining -= PRIMARY_ENDPOINT_WEIGHT;
+    }
+    let share = remaining / secondary.len().max(1) as f64;
+    providers.extend(secondary.into_iter().map(|p| (p, share)));
+    providers
+}
+
+/// `ORACLE_ENDPOINT_URL` (when set) and the providers selected by `ORACLE_FX_PROVIDERS`,
+/// each quoting against `base`, weighted by [`weighted`]. Empty when neither is configured.
+pub fn from_config(config: &Config, base: &str) -> anyhow::Result<Vec<(Arc<dyn FxProvider>, f64)>> {
+    let primary = config.oracle_endpoint_url.clone().map(|url| {
+        Arc::new(ExchangeRateApiProvider::with_url(url).named("endpoint")) as Arc<dyn FxProvider>
+    });
+    let secondary = config
+        .oracle_fx_providers
+        .iter()
+        .map(|name| -> anyhow::Result<Arc<dyn FxProvider>> {
+            match name.as_str() {
+                "exchangerate-api" => Ok(Arc::new(ExchangeRateApiProvider::new(
+                    config.oracle_exchangerate_api_key.clone(),
+                    base,
+                ))),
+                "frankfurter" => Ok(Arc::new(FrankfurterProvider::new(base))),
+                "coingecko" => Ok(Arc::new(CoinGeckoProvider::new())),
+                PYTH_PROVIDER_NAME => {
+                    let Some(url) = config.oracle_pyth_url.clone() else {
+                        anyhow::bail!("FX provider 'pyth' requires ORACLE_PYTH_URL");
+                    };
+                    if config.oracle_pyth_publisher_keys.is_empty() {
+                        anyhow::bail!("FX provider 'pyth' requires ORACLE_PYTH_PUBLISHER_KEYS");
+                    }
+                    Ok(Arc::new(
+                        PythProvider::new(url, base, config.oracle_pyth_publisher_keys.clone())
+                            .with_max_age_secs(config.safety_oracle_max_age_secs),
+                    ))
+                }
+                other => anyhow::bail!(
+                    "Unknown FX provider '{}' (expected one of {:?} or '{}')",
+                    other,
+                    PROVIDER_NAMES,
+                    PYTH_PROVIDER_NAME
+                ),
+            }
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(weighted(primary, secondary))
+}
+
+async fn get_body(client: &reqwest::Client, url: &str) -> Result<String, OracleError> {
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| OracleError::Network(e.without_url().to_string()))?;
+    let status = resp.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(OracleError::Auth(format!("provider returned {}", status)));
+    }
+    if status.is_server_error() {
+        return Err(OracleError::Network(format!(
+            "provider returned {}",
+            status
+        )));
+    }
+    // 4xx bodies still carry the provider's error description, so they go to the parser.
+    resp.text()
+        .await
+        .map_err(|e| OracleError::Network(e.without_url().to_string()))
+}
+
+fn validated_rates(
+    mut rates: HashMap<String, f64>,
+    base: &str,
+) -> Result<HashMap<String, f64>, OracleError> {
+    if let Some((currency, _)) = rates.iter().find(|(_, r)| !r.is_finite() || **r <= 0.0) {
+        return Err(OracleError::Payload(format!(
+            "non-positive rate for {}",
+            currency
+        )));
+    }
+    rates.insert(base.to_string(), 1.0);
+    if rates.len() < 2 {
+        return Err(OracleError::Payload("no rates in response".to_string()));
+    }
+    Ok(rates)
+}
+
+fn provider_state(base: String, rates: HashMap<String, f64>, timestamp: u64) -> PppState {
+    PppState {
+        base_currency: base,
+        rates,
+        ppp_indices: HashMap::new(),
+        confidence_intervals: HashMap::new(),
+        timestamp,
+        bases: BTreeMap::new(),
+        provenance: BTreeMap::new(),
+        rejections: Vec::new(),
+        attestations: Vec::new(),
+        collateral_prices: BTreeMap::new(),
+    }
+}
+
+/// [ExchangeRate-API](https://www.exchangerate-api.com): the keyed v6 API, or the
+/// keyless open endpoint when no key is configured.
+pub struct ExchangeRateApiProvider {
+    client: reqwest::Client,
+    url: String,
+    name: &'static str,
+}
+
+#[derive(Deserialize)]
+struct ExchangeRateApiResponse {
+    result: String,
+    #[serde(rename = "error-type")]
+    error_type: Option<String>,
+    base_code: Option<String>,
+    time_last_update_unix: Option<u64>,
+    /// Keyed API.
+    conversion_rates: Option<HashMap<String, f64>>,
+    /// Open access endpoint.
+    rates: Option<HashMap<String, f64>>,
+}
+
+impl ExchangeRateApiProvider {
+    pub fn new(api_key: Option<String>, base: &str) -> Self {
+        let url = match api_key {
+            Some(key) => format!("{}/{}/latest/{}", EXCHANGERATE_API_URL, key, base),
+            None => format!("{}/latest/{}", EXCHANGERATE_API_OPEN_URL, base),
+        };
+        Self::with_url(url)
+    }
+
+    /// Polls `url`, which must answer in the ExchangeRate-API response format.
+    pub fn with_url(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            name: "exchangerate-api",
+        }
+    }
+
+    /// Reports observations under `name`, keeping provenance distinct when the same
+    /// response format is polled from more than one URL.
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn parse(body: &str) -> Result<PppState, OracleError> {
+        let resp: ExchangeRateApiResponse = serde_json::from_str(body)
+            .map_err(|e| OracleError::Payload(format!("ExchangeRate-API: {}", e)))?;
+        if resp.result != "success" {
+            let error_type = resp.error_type.unwrap_or_else(|| resp.result.clone());
+            return Err(match error_type.as_str() {
+                "invalid-key" | "inactive-account" | "quota-reached" => {
+                    OracleError::Auth(format!("ExchangeRate-API: {}", error_type))
+                }
+                _ => OracleError::Payload(format!("ExchangeRate-API: {}", error_type)),
+            });
+        }
+        let base = resp
+            .base_code
+            .ok_or_else(|| OracleError::Payload("ExchangeRate-API: missing base_code".into()))?;
+        let timestamp = resp.time_last_update_unix.ok_or_else(|| {
+            OracleError::Payload("ExchangeRate-API: missing time_last_update_unix".into())
+        })?;
+        let rates = resp
+            .conversion_rates
+            .or(resp.rates)
+            .ok_or_else(|| OracleError::Payload("ExchangeRate-API: missing rates".into()))?;
+        let rates = validated_rates(rates, &base)?;
+        Ok(provider_state(base, rates, timestamp))
+    }
+}
+
+#[async_trait]
+impl FxProvider for ExchangeRateApiProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn fetch(&self) -> Result<PppState, OracleError> {
+        Self::parse(&get_body(&self.client, &self.url).await?)
+    }
+}
+
+/// [Frankfurter](https://frankfurter.dev): ECB reference rates, published once per
+/// working day.
+pub struct FrankfurterProvider {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct FrankfurterResponse {
+    base: Option<String>,
+    date: Option<String>,
+    rates: Option<HashMap<String, f64>>,
+    message: Option<String>,
+}
+
+impl FrankfurterProvider {
+    pub fn new(base: &str) -> Self {
+        Self::with_base_url(FRANKFURTER_URL, base)
+    }
+
+    pub fn with_base_url(base_url: &str, base: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{}/latest?from={}", base_url.trim_end_matches('/'), base),
+        }
+    }
+
+    /// Parses a `/latest` response. The timestamp is midnight UTC of the reference date.
+    pub fn parse(body: &str) -> Result<PppState, OracleError> {
+        let resp: FrankfurterResponse = serde_json::from_str(body)
+            .map_err(|e| OracleError::Payload(format!("Frankfurter: {}", e)))?;
+        let (Some(base), Some(date), Some(rates)) = (resp.base, resp.date, resp.rates) else {
+            let message = resp
+                .message
+                .unwrap_or_else(|| "missing base, date or rates".to_string());
+            return Err(OracleError::Payload(format!("Frankfurter: {}", message)));
+        };
+        let timestamp = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| OracleError::Payload(format!("Frankfurter: date '{}': {}", date, e)))?
+            .and_hms_opt(0, 0, 0)
+            .map(|dt| dt.and_utc().timestamp() as u64)
+            .ok_or_else(|| OracleError::Payload(format!("Frankfurter: date '{}'", date)))?;
+        let rates = validated_rates(rates, &base)?;
+        Ok(provider_state(base, rates, timestamp))
+    }
+}
+
+#[async_trait]
+impl FxProvider for FrankfurterProvider {
+    fn name(&self) -> &'static str {
+        "frankfurter"
+    }
+
+    async fn fetch(&self) -> Result<PppState, OracleError> {
+        Self::parse(&get_body(&self.client, &self.url).await?)
+    }
+}
+
+/// [CoinGecko](https://www.coingecko.com) spot prices of the vault collateral assets.
+/// Quoted like a currency, as units per one USD, so the aggregator can invert them into
+/// [`PppState::collateral_prices`].
+pub struct CoinGeckoProvider {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct CoinGeckoPrice {
+    usd: f64,
+    last_updated_at: Option<u64>,
+}
+
+impl CoinGeckoProvider {
+    pub fn new() -> Self {
+        Self::with_base_url(COINGECKO_URL)
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
+        let ids: Vec<&str> = COINGECKO_IDS.iter().map(|(_, id)| *id).collect();
+        Self {
+            client: reqwest::Client::new(),
+            url: format!(
+                "{}/simple/price?ids={}&vs_currencies=usd&include_last_updated_at=true",
+                base_url.trim_end_matches('/'),
+                ids.join(",")
+            ),
+        }
+    }
+
+    /// Parses a `/simple/price` response. The timestamp is the oldest `last_updated_at`.
+    pub fn parse(body: &str) -> Result<PppState, OracleError> {
+        let resp: HashMap<String, CoinGeckoPrice> = serde_json::from_str(body)
+            .map_err(|e| OracleError::Payload(format!("CoinGecko: {}", e)))?;
+        let mut rates = HashMap::new();
+        let mut timestamp: Option<u64> = None;
+        for (asset, id) in COINGECKO_IDS {
+            let Some(price) = resp.get(id) else {
+                continue;
+            };
+            if !(price.usd.is_finite() && price.usd > 0.0) {
+                return Err(OracleError::Payload(format!(
+                    "CoinGecko: non-positive price for {}",
+                    asset
+                )));
+            }
+            rates.insert(asset.to_string(), 1.0 / price.usd);
+            if let Some(updated) = price.last_updated_at {
+                timestamp = Some(timestamp.map_or(updated, |t| t.min(updated)));
+            }
+        }
+        let timestamp = timestamp.ok_or_else(|| {
+            OracleError::Payload("CoinGecko: missing last_updated_at".to_string())
+        })?;
+        let rates = validated_rates(rates, "USD")?;
+        Ok(provider_state("USD".to_string(), rates, timestamp))
+    }
+}
+
+impl Default for CoinGeckoProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl FxProvider for CoinGeckoProvider {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn fetch(&self) -> Result<PppState, OracleError> {
+        Self::parse(&get_body(&self.client, &self.url).await?)
+    }
+}
+
+/// Pyth-style signed price updates. Every update's signature is verified against the
+/// trusted publisher keys and its `publish_time` checked against the clock; updates that
+/// fail are dropped, and the accepted attestations are carried in
+/// [`PppState::attestations`].
+pub struct PythProvider {
+    client: reqwest::Client,
+    url: String,
+    base: String,
+    trusted_publishers: Vec<String>,
+    max_age_secs: u64,
+}
+
+/// Oldest `publish_time` accepted by default, in seconds.
+pub const DEFAULT_PYTH_MAX_AGE_SECS: u64 = 300;
+
+#[derive(Deserialize)]
+struct PythResponse {
+    updates: Vec<PriceAttestation>,
+}
+
+impl PythProvider {
+    pub fn new(url: String, base: &str, trusted_publishers: Vec<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            base: base.to_string(),
+            trusted_publishers,
+            max_age_secs: DEFAULT_PYTH_MAX_AGE_SECS,
+        }
+    }
+
+    /// Updates published more than `max_age_secs` ago are rejected as stale.
+    pub fn with_max_age_secs(mut self, max_age_secs: u64) -> Self {
+        self.max_age_secs = max_age_secs;
+        self
+    }
+
+    /// Parses `{"updates": [...]}`, keeping only verified updates quoted against `base`
+    /// whose `publish_time` is neither ahead of `now` (beyond the allowed clock skew) nor
+    /// older than `max_age_secs`. The timestamp is the oldest accepted `publish_time`.
+    pub fn parse(
+        body: &str,
+        base: &str,
+        trusted_publishers: &[String],
+        now: u64,
+        max_age_secs: u64,
+    ) -> Result<PppState, OracleError> {
+        let resp: PythResponse =
+            serde_json::from_str(body).map_err(|e| OracleError::Payload(format!("Pyth: {}", e)))?;
+        let mut rates = HashMap::new();
+        let mut accepted = Vec::new();
+        for update in resp.updates {
+            let rate = update
+                .verify(trusted_publishers)
+                .and_then(|()| update.check_publish_time(now, max_age_secs))
+                .and_then(|()| update.rate_against(base));
+            match rate {
+                Ok(Some((currency, rate))) => {
+                    rates.insert(currency, rate);
+                    accepted.push(update);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!(
+                    feed_id = %update.feed_id,
+                    symbol = %update.symbol,
+                    error = %e,
+                    "Rejected Pyth price update"
+                ),
+            }
+        }
+        let Some(timestamp) = accepted.iter().map(|a| a.publish_time).min() else {
+            return Err(OracleError::Attestation(
+                "Pyth: no verified price update".to_string(),
+            ));
+        };
+        let rates = validated_rates(rates, base)?;
+        let mut state = provider_state(base.to_string(), rates, timestamp);
+        state.attestations = accepted;
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl FxProvider for PythProvider {
+    fn name(&self) -> &'static str {
+        PYTH_PROVIDER_NAME
+    }
+
+    async fn fetch(&self) -> Result<PppState, OracleError> {
+        let body = get_body(&self.client, &self.url).await?;
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        Self::parse(
+            &body,
+            &self.base,
+            &self.trusted_publishers,
+            now,
+            self.max_age_secs,
+        )
+    }
+}
+
+/// Returns a fixed state (or error) without touching the network. For tests.
+pub struct MockProvider {
+    result: Result<PppState, OracleError>,
+}
+
+impl MockProvider {
+    pub fn new(base: &str, rates: HashMap<String, f64>, timestamp: u64) -> Self {
+        Self {
+            result: Ok(provider_state(base.to_string(), rates, timestamp)),
+        }
+    }
+
+    pub fn failing(error: OracleError) -> Self {
+        Self { result: Err(error) }
+    }
+}
+
+#[async_trait]
+impl FxProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn fetch(&self) -> Result<PppState, OracleError> {
+        self.result.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXCHANGERATE_API_KEYED: &str =
+        include_str!("../../tests/fixtures/oracle/exchangerate_api_latest.json");
+    const EXCHANGERATE_API_OPEN: &str =
+        include_str!("../../tests/fixtures/oracle/exchangerate_api_open_latest.json");
+    const EXCHANGERATE_API_INVALID_KEY: &str =
+        include_str!("../../tests/fixtures/oracle/exchangerate_api_invalid_key.json");
+    const FRANKFURTER_LATEST: &str =
+        include_str!("../../tests/fixtures/oracle/frankfurter_latest.json");
+    const FRANKFURTER_UNKNOWN_BASE: &str =
+        include_str!("../../tests/fixtures/oracle/frankfurter_not_found.json");
+    const COINGECKO_SIMPLE_PRICE: &str =
+        include_str!("../../tests/fixtures/oracle/coingecko_simple_price.json");
+
+    #[test]
+    fn test_parses_exchangerate_api_responses() {
+        let keyed = ExchangeRateApiProvider::parse(EXCHANGERATE_API_KEYED).unwrap();
+        assert_eq!(keyed.base_currency, "USD");
+        assert_eq!(keyed.timestamp, 1_746_057_601);
+        assert_eq!(keyed.rates["USD"], 1.0);
+        assert_eq!(keyed.rates["EUR"], 0.8821);
+        assert_eq!(keyed.rates["ZAR"], 18.5213);
+
+        let open = ExchangeRateApiProvider::parse(EXCHANGERATE_API_OPEN).unwrap();
+        assert_eq!(open.base_currency, "USD");
+        assert_eq!(open.rates["JPY"], 143.02);
+    }
+
+    #[test]
+    fn test_exchangerate_api_key_errors_are_auth_failures() {
+        assert!(matches!(
+            ExchangeRateApiProvider::parse(EXCHANGERATE_API_INVALID_KEY),
+            Err(OracleError::Auth(_))
+        ));
+        assert!(matches!(
+            ExchangeRateApiProvider::parse(r#"{"result":"error","error-type":"unsupported-code"}"#),
+            Err(OracleError::Payload(_))
+        ));
+    }
+
+    #[test]
+    fn test_parses_frankfurter_response() {
+        let state = FrankfurterProvider::parse(FRANKFURTER_LATEST).unwrap();
+        assert_eq!(state.base_currency, "USD");
+        // 2025-05-02T00:00:00Z
+        assert_eq!(state.timestamp, 1_746_144_000);
+        assert_eq!(state.rates["USD"], 1.0);
+        assert_eq!(state.rates["GBP"], 0.75318);
+        assert!(state.ppp_indices.is_empty());
+
+        assert!(matches!(
+            FrankfurterProvider::parse(FRANKFURTER_UNKNOWN_BASE),
+            Err(OracleError::Payload(msg)) if msg.contains("not found")
+        ));
+    }
+
+    #[test]
+    fn test_parses_coingecko_response() {
+        let state = CoinGeckoProvider::parse(COINGECKO_SIMPLE_PRICE).unwrap();
+        assert_eq!(state.base_currency, "USD");
+        assert_eq!(state.timestamp, 1_746_143_987);
+        assert_eq!(state.rates["USD"], 1.0);
+        assert!((1.0 / state.rates["BTC"] - 96_512.0).abs() < 1e-6);
+        assert!((1.0 / state.rates["STX"] - 0.7421).abs() < 1e-9);
+
+        assert!(matches!(
+            CoinGeckoProvider::parse(r#"{"bitcoin":{"usd":0,"last_updated_at":1}}"#),
+            Err(OracleError::Payload(_))
+        ));
+        assert!(matches!(
+            CoinGeckoProvider::parse(r#"{"error_code":429}"#),
+            Err(OracleError::Payload(_))
+        ));
+    }
+
+    #[test]
+    fn test_unparseable_payloads_are_payload_errors() {
+        for body in ["<html>Bad Gateway</html>", "", r#"{"result":"success"}"#] {
+            assert!(matches!(
+                ExchangeRateApiProvider::parse(body),
+                Err(OracleError::Payload(_))
+            ));
+            assert!(matches!(
+                FrankfurterProvider::parse(body),
+                Err(OracleError::Payload(_))
+            ));
+        }
+        assert!(matches!(
+            FrankfurterProvider::parse(
+                r#"{"base":"USD","date":"2025-05-02","rates":{"EUR":-1.0}}"#
+            ),
+            Err(OracleError::Payload(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_provider_is_network_failure() {
+        let provider = FrankfurterProvider::with_base_url("http://127.0.0.1:1", "USD");
+        assert!(matches!(
+            provider.fetch().await,
+            Err(OracleError::Network(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_config_selects_providers() {
+        let mut config = Config::default_test();
+        config.oracle_fx_providers = vec!["frankfurter".to_string()];
+        let providers = from_config(&config, "USD").unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].0.name(), "frankfurter");
+        assert_eq!(providers[0].1, 1.0);
+
+        config.oracle_endpoint_url = Some("http://localhost:9999/latest".to_string());
+        let weights: Vec<f64> = from_config(&config, "USD")
+            .unwrap()
+            .iter()
+            .map(|(_, w)| *w)
+            .collect();
+        assert_eq!(weights, vec![PRIMARY_ENDPOINT_WEIGHT, 0.5]);
+
+        config.oracle_endpoint_url = None;
+        config.oracle_fx_providers.clear();
+        assert!(from_config(&config, "USD").unwrap().is_empty());
+
+        config.oracle_fx_providers = vec!["exchangerate.host".to_string()];
+        assert!(from_config(&config, "USD").is_err());
+
+        // `pyth` needs an endpoint and at least one trusted publisher.
+        config.oracle_fx_providers = vec![PYTH_PROVIDER_NAME.to_string()];
+        assert!(from_config(&config, "USD").is_err());
+        config.oracle_pyth_url = Some("http://localhost:9999/updates".to_string());
+        assert!(from_config(&config, "USD").is_err());
+        config.oracle_pyth_publisher_keys = vec!["02ab".to_string()];
+        assert_eq!(from_config(&config, "USD").unwrap()[0].0.name(), "pyth");
+    }
+
+    #[test]
+    fn test_pyth_keeps_only_verified_updates() {
+        use crate::oracle::attestation::tests::{signed, signed_at};
+        use k256::ecdsa::SigningKey;
+
+        let publisher = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let trusted = vec![hex::encode(publisher.verifying_key().to_sec1_bytes())];
+        let eur = signed(&publisher, "EUR/USD", 108_250, -5);
+        let mut tampered = signed(&publisher, "USD/JPY", 14_350, -2);
+        tampered.price = 1;
+        let untrusted = signed(
+            &SigningKey::from_slice(&[2u8; 32]).unwrap(),
+            "GBP/USD",
+            127_000,
+            -5,
+        );
+        let mut future = signed(&publisher, "GBP/USD", 127_000, -5);
+        future.publish_time += 3_600;
+        let future = signed_at(&publisher, future);
+        let mut stale = signed(&publisher, "CHF/USD", 113_000, -5);
+        stale.publish_time -= 3_600;
+        let stale = signed_at(&publisher, stale);
+        let body = serde_json::json!({ "updates": [&eur, tampered, untrusted, future, stale] })
+            .to_string();
+        let now = eur.publish_time;
+
+        let state = PythProvider::parse(&body, "USD", &trusted, now, 300).unwrap();
+        assert_eq!(state.base_currency, "USD");
+        assert!((state.rates["EUR"] - 1.0 / 1.0825).abs() < 1e-12);
+        assert!(!state.rates.contains_key("JPY"));
+        assert!(!state.rates.contains_key("GBP"));
+        assert!(!state.rates.contains_key("CHF"));
+        assert_eq!(state.attestations, vec![eur.clone()]);
+        assert_eq!(state.timestamp, eur.publish_time);
+
+        let forged = PriceAttestation {
+            price: eur.price * 2,
+            ..eur
+        };
+        let forged = serde_json::json!({ "updates": [forged] }).to_string();
+        assert!(matches!(
+            PythProvider::parse(&forged, "USD", &trusted, now, 300),
+            Err(OracleError::Attestation(_))
+        ));
+    }
+}
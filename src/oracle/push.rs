@@ -0,0 +1,689 @@
+//! [NEXUS-ORACLE-04] Publishes the aggregate PPP state to the oracle contract.
+//!
+//! Each published round becomes one contract call: a list of
+//! `{currency, rate, ppp-index}` tuples, the state's `uint` timestamp, and the signed
+//! price attestations behind the rates, with rates and indices as
+//! [`PRICE_DECIMALS`]-decimal fixed point. The call is signed with the
+//! oracle key and broadcast through the shared [`StacksBroadcaster`]; every attempt is
+//! recorded in `oracle_pushes`. Rounds in which no rate moved more than the configured
+//! deviation since the last update confirmed on-chain are skipped.
+
+use crate::config::{
+    Config, ENV_ORACLE_CONTRACT_PRINCIPAL, ENV_ORACLE_PRIVATE_KEY, ENV_REBALANCE_PRIVATE_KEY_HEX,
+};
+use crate::latency::timed_query;
+use crate::oracle::aggregator::PppState;
+use crate::oracle::attestation::PriceAttestation;
+use crate::signing::RotatingSigner;
+use crate::stacks::{
+    ClarityValue, ContractCall, ContractId, RpcEndpoints, SignedTransaction, StacksBroadcaster,
+    StacksSigner,
+};
+use crate::sync::decoder::OraclePriceUpdate;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Decimal places of the fixed-point `rate` and `ppp-index` arguments.
+pub const PRICE_DECIMALS: u32 = 8;
+const PRICE_SCALE: f64 = 100_000_000.0;
+pub const DEFAULT_CONTRACT_FUNCTION: &str = "update-fx-rates";
+/// Largest rate move, in percent, that does not by itself trigger a push.
+pub const DEFAULT_PUSH_DEVIATION_PCT: f64 = 0.5;
+pub const DEFAULT_PUSH_FEE_USTX: u64 = 10_000;
+
+/// `value` scaled by `10^PRICE_DECIMALS` and rounded half away from zero. The scaling
+/// happens in `f64`, so a value whose nearest binary representation falls just below a
+/// rounding midpoint rounds down. `None` for negative, non-finite or out-of-range values.
+pub fn to_fixed_point(value: f64) -> Option<u128> {
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    let scaled = (value * PRICE_SCALE).round();
+    // 2^128: the first value `u128` cannot hold.
+    if scaled >= 340_282_366_920_938_463_463_374_607_431_768_211_456.0 {
+        return None;
+    }
+    Some(scaled as u128)
+}
+
+/// Contract-call arguments for `state`: one tuple per published rate, sorted by currency,
+/// then the state timestamp, then [`attestation_arg`] of each of the state's attestations
+/// so the contract can verify the publisher signatures. Currencies without a PPP index
+/// are sent with `ppp-index u0`.
+pub fn contract_args(state: &PppState) -> anyhow::Result<Vec<ClarityValue>> {
+    let sorted: BTreeMap<_, _> = state.rates.iter().collect();
+    let mut entries = Vec::with_capacity(sorted.len());
+    for (currency, rate) in sorted {
+        if !currency.is_ascii() {
+            anyhow::bail!("Currency code '{}' is not ASCII", currency);
+        }
+        let rate = to_fixed_point(*rate)
+            .with_context(|| format!("Rate for {} cannot be encoded: {}", currency, rate))?;
+        let ppp = state.ppp_indices.get(currency).copied().unwrap_or(0.0);
+        let ppp = to_fixed_point(ppp)
+            .with_context(|| format!("PPP index for {} cannot be encoded: {}", currency, ppp))?;
+        entries.push(ClarityValue::tuple([
+            ("currency", ClarityValue::StringAscii(currency.clone())),
+            ("ppp-index", ClarityValue::UInt(ppp)),
+            ("rate", ClarityValue::UInt(rate)),
+        ]));
+    }
+    let attestations = state
+        .attestations
+        .iter()
+        .map(attestation_arg)
+        .collect::<anyhow::Result<_>>()?;
+    Ok(vec![
+        ClarityValue::List(entries),
+        ClarityValue::UInt(state.timestamp as u128),
+        ClarityValue::List(attestations),
+    ])
+}
+
+/// `attestation` as a Clarity tuple, with the publisher key and signature as buffers.
+pub fn attestation_arg(attestation: &PriceAttestation) -> anyhow::Result<ClarityValue> {
+    if !attestation.feed_id.is_ascii() || !attestation.symbol.is_ascii() {
+        anyhow::bail!(
+            "Attestation {} ({}) is not ASCII",
+            attestation.feed_id,
+            attestation.symbol
+        );
+    }
+    let publisher = hex::decode(&attestation.publisher)
+        .with_context(|| format!("Attestation {} publisher", attestation.feed_id))?;
+    let signature = hex::decode(&attestation.signature)
+        .with_context(|| format!("Attestation {} signature", attestation.feed_id))?;
+    Ok(ClarityValue::tuple([
+        ("conf", ClarityValue::UInt(attestation.conf.into())),
+        ("expo", ClarityValue::Int(attestation.expo.into())),
+        (
+            "feed-id",
+            ClarityValue::StringAscii(attestation.feed_id.clone()),
+        ),
+        ("price", ClarityValue::Int(attestation.price.into())),
+        (
+            "publish-time",
+            ClarityValue::UInt(attestation.publish_time.into()),
+        ),
+        ("publisher", ClarityValue::Buffer(publisher)),
+        ("signature", ClarityValue::Buffer(signature)),
+        (
+            "symbol",
+            ClarityValue::StringAscii(attestation.symbol.clone()),
+        ),
+    ]))
+}
+
+/// Hex SHA-256 of the serialized arguments, recorded alongside the txid.
+pub fn payload_digest(args: &[ClarityValue]) -> String {
+    let mut hasher = Sha256::new();
+    for arg in args {
+        hasher.update(arg.serialize());
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PushDecision {
+    Push { reason: String },
+    Skip { max_move_pct: f64 },
+}
+
+/// Push when nothing has been pushed yet, when the set of currencies changed, or when
+/// some rate moved more than `threshold_pct` percent since `last`.
+pub fn push_decision(
+    last: Option<&HashMap<String, f64>>,
+    current: &HashMap<String, f64>,
+    threshold_pct: f64,
+) -> PushDecision {
+    let Some(last) = last else {
+        return PushDecision::Push {
+            reason: "no previous push".to_string(),
+        };
+    };
+    if last.len() != current.len() || current.keys().any(|c| !last.contains_key(c)) {
+        return PushDecision::Push {
+            reason: "currency set changed".to_string(),
+        };
+    }
+    let (currency, max_move_pct) = current
+        .iter()
+        .map(|(currency, rate)| {
+            let previous = last[currency];
+            let moved = if previous == 0.0 {
+                if *rate == 0.0 {
+                    0.0
+                } else {
+                    f64::INFINITY
+                }
+            } else {
+                ((rate - previous) / previous).abs() * 100.0
+            };
+            (currency.as_str(), moved)
+        })
+        .fold(
+            ("", 0.0),
+            |max, next| if next.1 > max.1 { next } else { max },
+        );
+    if max_move_pct > threshold_pct {
+        PushDecision::Push {
+            reason: format!("{} moved {:.4}%", currency, max_move_pct),
+        }
+    } else {
+        PushDecision::Skip { max_move_pct }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PushOutcome {
+    Skipped {
+        max_move_pct: f64,
+    },
+    Broadcast {
+        txid: String,
+        payload_digest: String,
+    },
+}
+
+/// The key the oracle signs with: its own, kept apart from the other keys the node signs
+/// with so the oracle contract can authorize it alone, or the node wallet when no oracle
+/// key is configured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleSignerIdentity {
+    /// Compressed SEC1 public key (hex).
+    pub public_key: String,
+    /// Stacks address the pushes are sent from.
+    pub address: String,
+    pub contract: String,
+    /// Whether the pushes are signed with the node wallet because `ORACLE_PRIVATE_KEY` is
+    /// unset.
+    pub node_wallet: bool,
+}
+
+/// Signs and broadcasts PPP state updates to the oracle contract.
+#[derive(Debug, Clone)]
+pub struct OraclePusher {
+    broadcaster: StacksBroadcaster,
+    /// Rotated through `POST /admin/v1/rotate-key`.
+    signer: Arc<RotatingSigner<StacksSigner>>,
+    contract: ContractId,
+    function_name: String,
+    fee_ustx: u64,
+    deviation_pct: f64,
+    node_wallet: bool,
+}
+
+impl OraclePusher {
+    pub fn new(broadcaster: StacksBroadcaster, signer: StacksSigner, contract: ContractId) -> Self {
+        Self {
+            broadcaster,
+            signer: Arc::new(RotatingSigner::stacks(signer)),
+            contract,
+            function_name: DEFAULT_CONTRACT_FUNCTION.to_string(),
+            fee_ustx: DEFAULT_PUSH_FEE_USTX,
+            deviation_pct: DEFAULT_PUSH_DEVIATION_PCT,
+            node_wallet: false,
+        }
+    }
+
+    /// `None` when no oracle contract is configured, i.e. on-chain pushes are disabled.
+    /// Pushes are signed with `ORACLE_PRIVATE_KEY`, falling back to the node wallet
+    /// (`REBALANCE_PRIVATE_KEY_HEX`) when it is unset, and go through `rpc`, the endpoints
+    /// sync reads from.
+    pub fn from_config(config: &Config, rpc: Arc<RpcEndpoints>) -> anyhow::Result<Option<Self>> {
+        let Some(contract) = config.oracle_contract_principal.as_deref() else {
+            if config.oracle_private_key.is_some() {
+                anyhow::bail!("On-chain oracle pushes require {ENV_ORACLE_CONTRACT_PRINCIPAL}");
+            }
+            return Ok(None);
+        };
+        let contract: ContractId = contract
+            .parse()
+            .with_context(|| format!("Invalid {ENV_ORACLE_CONTRACT_PRINCIPAL}"))?;
+        let (signer, node_wallet) = match (
+            config.oracle_private_key.as_deref(),
+            config.rebalance_private_key_hex.as_deref(),
+        ) {
+            (Some(secret), _) => (
+                StacksSigner::from_hex(secret)
+                    .with_context(|| format!("Invalid {ENV_ORACLE_PRIVATE_KEY}"))?,
+                false,
+            ),
+            (None, Some(secret)) => {
+                tracing::warn!(
+                    "{} is unset; signing oracle pushes with the node wallet",
+                    ENV_ORACLE_PRIVATE_KEY
+                );
+                (
+                    StacksSigner::from_hex(secret)
+                        .with_context(|| format!("Invalid {ENV_REBALANCE_PRIVATE_KEY_HEX}"))?,
+                    true,
+                )
+            }
+            (None, None) => anyhow::bail!(
+                "On-chain oracle pushes require {} or the node wallet ({})",
+                ENV_ORACLE_PRIVATE_KEY,
+                ENV_REBALANCE_PRIVATE_KEY_HEX
+            ),
+        };
+        let mut pusher = Self::new(StacksBroadcaster::with_endpoints(rpc), signer, contract)
+            .with_function(config.oracle_contract_function.clone())
+            .with_fee(config.oracle_push_fee_ustx)
+            .with_deviation_pct(config.oracle_push_deviation_pct);
+        pusher.node_wallet = node_wallet;
+        Ok(Some(pusher))
+    }
+
+    pub fn with_function(mut self, function_name: String) -> Self {
+        self.function_name = function_name;
+        self
+    }
+
+    pub fn with_fee(mut self, fee_ustx: u64) -> Self {
+        self.fee_ustx = fee_ustx;
+        self
+    }
+
+    pub fn with_deviation_pct(mut self, deviation_pct: f64) -> Self {
+        self.deviation_pct = deviation_pct;
+        self
+    }
+
+    pub fn contract(&self) -> &ContractId {
+        &self.contract
+    }
+
+    pub fn signer(&self) -> &Arc<RotatingSigner<StacksSigner>> {
+        &self.signer
+    }
+
+    /// Who signs the pushes, on the contract's network.
+    pub fn signer_identity(&self) -> OracleSignerIdentity {
+        let signer = self.signer.current();
+        OracleSignerIdentity {
+            public_key: signer.public_key_hex(),
+            address: signer
+                .address(self.contract.address.is_mainnet())
+                .to_string(),
+            contract: self.contract.to_string(),
+            node_wallet: self.node_wallet,
+        }
+    }
+
+    pub fn contract_call(&self, state: &PppState) -> anyhow::Result<ContractCall> {
+        Ok(ContractCall {
+            contract: self.contract.clone(),
+            function_name: self.function_name.clone(),
+            args: contract_args(state)?,
+        })
+    }
+
+    /// Signs `call` at the sender's next nonce, counting its transactions still in the
+    /// mempool, and broadcasts it regardless of the deviation gate. Returns the signed
+    /// transaction and the txid the node accepted.
+    pub async fn broadcast(
+        &self,
+        call: &ContractCall,
+    ) -> anyhow::Result<(SignedTransaction, String)> {
+        let signer = self.signer.current();
+        let sender = signer.address(self.contract.address.is_mainnet());
+        let nonce = self.broadcaster.next_nonce(&sender).await?;
+        let tx = signer.sign_contract_call(call, nonce, self.fee_ustx)?;
+        let txid = self.broadcaster.broadcast(&tx).await?;
+        Ok((tx, txid))
+    }
+
+    /// Pushes `state` unless no rate moved more than the deviation threshold since
+    /// `confirmed`, the last update sync saw confirmed on-chain, recording the attempt in
+    /// `oracle_pushes`. A broadcast that never confirms leaves the gate where it was.
+    pub async fn push(
+        &self,
+        pool: &PgPool,
+        state: &PppState,
+        confirmed: Option<&OraclePriceUpdate>,
+    ) -> anyhow::Result<PushOutcome> {
+        let last = confirmed.map(confirmed_rates);
+        match push_decision(last.as_ref(), &state.rates, self.deviation_pct) {
+            PushDecision::Skip { max_move_pct } => {
+                tracing::info!(
+                    max_move_pct,
+                    threshold_pct = self.deviation_pct,
+                    "Skipping oracle push: no rate moved past the deviation threshold"
+                );
+                return Ok(PushOutcome::Skipped { max_move_pct });
+            }
+            PushDecision::Push { reason } => {
+                tracing::info!(contract = %self.contract, %reason, "Pushing PPP state on-chain");
+            }
+        }
+
+        let call = self.contract_call(state)?;
+        let digest = payload_digest(&call.args);
+        match self.broadcast(&call).await {
+            Ok((_, txid)) => {
+                record_push(pool, Some(&txid), &digest, state, "broadcast", None).await?;
+                Ok(PushOutcome::Broadcast {
+                    txid,
+                    payload_digest: digest,
+                })
+            }
+            Err(e) => {
+                if let Err(db) =
+                    record_push(pool, None, &digest, state, "failed", Some(&e.to_string())).await
+                {
+                    tracing::warn!("Failed to record oracle push failure: {}", db);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// The rates of a confirmed update, back from [`PRICE_DECIMALS`]-decimal fixed point.
+pub fn confirmed_rates(update: &OraclePriceUpdate) -> HashMap<String, f64> {
+    update
+        .rates
+        .iter()
+        .map(|(currency, rate)| (currency.clone(), *rate as f64 / PRICE_SCALE))
+        .collect()
+}
+
+async fn record_push(
+    pool: &PgPool,
+    txid: Option<&str>,
+    payload_digest: &str,
+    state: &PppState,
+    status: &str,
+    error: Option<&str>,
+) -> anyhow::Result<()> {
+    timed_query("record_push", sqlx::query(
+        "INSERT INTO oracle_pushes (txid, payload_digest, rates, timestamp, status, error) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(txid)
+    .bind(payload_digest)
+    .bind(serde_json::to_value(&state.rates)?)
+    .bind(state.timestamp as i64)
+    .bind(status)
+    .bind(error)
+    .execute(pool)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        routing::{get, post},
+        Json, Router,
+    };
+    use k256::ecdsa::SigningKey;
+    use std::sync::{Arc, Mutex};
+
+    const CONTRACT: &str = "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ.fx-oracle";
+
+    fn state(eur: f64) -> PppState {
+        PppState {
+            base_currency: "USD".to_string(),
+            rates: HashMap::from([("USD".to_string(), 1.0), ("EUR".to_string(), eur)]),
+            ppp_indices: HashMap::from([("EUR".to_string(), 0.8)]),
+            confidence_intervals: HashMap::new(),
+            timestamp: 1_751_846_400,
+            bases: Default::default(),
+            provenance: Default::default(),
+            rejections: Vec::new(),
+            attestations: Vec::new(),
+            collateral_prices: BTreeMap::new(),
+        }
+    }
+
+    /// Mock Stacks node: reports confirmed nonce 7 with two more in the mempool, and
+    /// captures posted transaction bodies.
+    async fn mock_node(posted: Arc<Mutex<Vec<Vec<u8>>>>) -> String {
+        let app = Router::new()
+            .route(
+                "/v2/accounts/{address}",
+                get(|| async { Json(serde_json::json!({"balance": "0x0", "nonce": 7})) }),
+            )
+            .route(
+                "/extended/v1/address/{address}/nonces",
+                get(|| async { Json(serde_json::json!({"possible_next_nonce": 9})) }),
+            )
+            .route(
+                "/v2/transactions",
+                post(
+                    |State(posted): State<Arc<Mutex<Vec<Vec<u8>>>>>, body: Bytes| async move {
+                        let txid = hex::encode(<sha2::Sha512_256 as Digest>::digest(&body));
+                        posted.lock().unwrap().push(body.to_vec());
+                        Json(txid)
+                    },
+                ),
+            )
+            .with_state(posted);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_fixed_point_rounding() {
+        assert_eq!(to_fixed_point(1.0), Some(100_000_000));
+        assert_eq!(to_fixed_point(18.5), Some(1_850_000_000));
+        assert_eq!(to_fixed_point(1.234567894), Some(123_456_789));
+        assert_eq!(to_fixed_point(1.234567896), Some(123_456_790));
+        assert_eq!(to_fixed_point(0.000000004), Some(0));
+        assert_eq!(to_fixed_point(0.000000006), Some(1));
+        assert_eq!(to_fixed_point(0.0), Some(0));
+        assert_eq!(to_fixed_point(-0.01), None);
+        assert_eq!(to_fixed_point(f64::NAN), None);
+        assert_eq!(to_fixed_point(f64::INFINITY), None);
+        assert_eq!(to_fixed_point(1e40), None);
+    }
+
+    #[test]
+    fn test_contract_args_are_sorted_fixed_point_tuples() {
+        let args = contract_args(&state(0.9)).unwrap();
+        let expected_eur = ClarityValue::tuple([
+            ("currency", ClarityValue::StringAscii("EUR".to_string())),
+            ("ppp-index", ClarityValue::UInt(80_000_000)),
+            ("rate", ClarityValue::UInt(90_000_000)),
+        ]);
+        let expected_usd = ClarityValue::tuple([
+            ("currency", ClarityValue::StringAscii("USD".to_string())),
+            ("ppp-index", ClarityValue::UInt(0)),
+            ("rate", ClarityValue::UInt(100_000_000)),
+        ]);
+        assert_eq!(
+            args,
+            vec![
+                ClarityValue::List(vec![expected_eur, expected_usd]),
+                ClarityValue::UInt(1_751_846_400),
+                ClarityValue::List(Vec::new()),
+            ]
+        );
+        assert_ne!(
+            payload_digest(&args),
+            payload_digest(&contract_args(&state(0.91)).unwrap())
+        );
+
+        let mut bad = state(0.9);
+        bad.rates.insert("EUR".to_string(), f64::NAN);
+        assert!(contract_args(&bad).is_err());
+    }
+
+    #[test]
+    fn test_contract_args_forward_attestations() {
+        use crate::oracle::attestation::tests::signed;
+
+        let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let eur = signed(&key, "EUR/USD", 108_250, -5);
+        let mut attested = state(0.9);
+        attested.attestations = vec![eur.clone()];
+        let args = contract_args(&attested).unwrap();
+        let ClarityValue::List(forwarded) = &args[2] else {
+            panic!("expected an attestation list, got {:?}", args[2]);
+        };
+        assert_eq!(forwarded.len(), 1);
+        assert_eq!(
+            forwarded[0].field("price").and_then(|v| v.as_int()),
+            Some(108_250)
+        );
+        assert_eq!(
+            forwarded[0].field("signature"),
+            Some(&ClarityValue::Buffer(hex::decode(&eur.signature).unwrap()))
+        );
+
+        attested.attestations[0].signature = "zz".to_string();
+        assert!(contract_args(&attested).is_err());
+    }
+
+    #[test]
+    fn test_push_decision_skips_small_moves() {
+        let last = state(0.9).rates;
+        assert!(matches!(
+            push_decision(None, &last, 0.5),
+            PushDecision::Push { .. }
+        ));
+
+        match push_decision(Some(&last), &state(0.9027).rates, 0.5) {
+            PushDecision::Skip { max_move_pct } => assert!((max_move_pct - 0.3).abs() < 1e-9),
+            other => panic!("expected skip, got {:?}", other),
+        }
+        match push_decision(Some(&last), &state(0.8946).rates, 0.5) {
+            PushDecision::Push { reason } => assert!(reason.starts_with("EUR moved 0.6")),
+            other => panic!("expected push, got {:?}", other),
+        }
+
+        let mut added = state(0.9).rates;
+        added.insert("JPY".to_string(), 150.0);
+        assert_eq!(
+            push_decision(Some(&last), &added, 0.5),
+            PushDecision::Push {
+                reason: "currency set changed".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_push_decision_gates_on_the_confirmed_update() {
+        let confirmed = OraclePriceUpdate {
+            rates: BTreeMap::from([
+                ("EUR".to_string(), 90_000_000),
+                ("USD".to_string(), 100_000_000),
+            ]),
+            ppp_indices: BTreeMap::new(),
+            timestamp: 1_751_846_400,
+        };
+        let last = confirmed_rates(&confirmed);
+        assert_eq!(last, state(0.9).rates);
+        assert!(matches!(
+            push_decision(Some(&last), &state(0.9027).rates, 0.5),
+            PushDecision::Skip { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_posts_signed_contract_call() {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let url = mock_node(posted.clone()).await;
+        let signer = StacksSigner::new(SigningKey::from_slice(&[9u8; 32]).unwrap());
+        let pusher = OraclePusher::new(
+            StacksBroadcaster::new(&url),
+            signer.clone(),
+            CONTRACT.parse().unwrap(),
+        )
+        .with_fee(3_000);
+
+        let call = pusher.contract_call(&state(0.9)).unwrap();
+        let (tx, txid) = pusher.broadcast(&call).await.unwrap();
+        assert_eq!(txid, format!("0x{}", tx.txid));
+
+        let posted = posted.lock().unwrap();
+        assert_eq!(posted.len(), 1);
+        let body = &posted[0];
+        assert_eq!(body, &tx.bytes);
+        assert_eq!(
+            body[0], 0x80,
+            "testnet contract selects a testnet transaction"
+        );
+        assert_eq!(body[7..27], signer.address(false).hash160);
+        assert_eq!(body[27..35], 9u64.to_be_bytes(), "nonce counts the mempool");
+        assert_eq!(body[35..43], 3_000u64.to_be_bytes());
+
+        let mut payload = vec![0x02, pusher.contract().address.version];
+        payload.extend_from_slice(&pusher.contract().address.hash160);
+        payload.push(b"fx-oracle".len() as u8);
+        payload.extend_from_slice(b"fx-oracle");
+        payload.push(DEFAULT_CONTRACT_FUNCTION.len() as u8);
+        payload.extend_from_slice(DEFAULT_CONTRACT_FUNCTION.as_bytes());
+        payload.extend_from_slice(&3u32.to_be_bytes());
+        for arg in &call.args {
+            payload.extend_from_slice(&arg.serialize());
+        }
+        assert!(body.ends_with(&payload));
+
+        let identity = pusher.signer_identity();
+        assert_eq!(identity.public_key, signer.public_key_hex());
+        assert_eq!(identity.address, signer.address(false).to_string());
+        assert_eq!(identity.contract, CONTRACT);
+        drop(posted);
+
+        let rotated = StacksSigner::new(SigningKey::from_slice(&[10u8; 32]).unwrap());
+        pusher.signer().rotate_stacks(rotated.clone());
+        let (tx, _) = pusher.broadcast(&call).await.unwrap();
+        assert_eq!(tx.bytes[7..27], rotated.address(false).hash160);
+        pusher.broadcast(&call).await.unwrap();
+        let (tx, _) = pusher.broadcast(&call).await.unwrap();
+        assert_eq!(
+            tx.bytes[27..35],
+            11u64.to_be_bytes(),
+            "past the nonces already broadcast"
+        );
+        assert_eq!(
+            pusher.signer_identity().public_key,
+            rotated.public_key_hex()
+        );
+    }
+
+    #[test]
+    fn test_from_config_requires_key_and_contract() {
+        let mut config = Config::default_test();
+        let rpc = Arc::new(config.stacks_rpc_endpoints());
+        assert!(OraclePusher::from_config(&config, rpc.clone())
+            .unwrap()
+            .is_none());
+
+        config.oracle_private_key = Some(hex::encode([9u8; 32]));
+        assert!(OraclePusher::from_config(&config, rpc.clone()).is_err());
+
+        config.oracle_contract_principal = Some(CONTRACT.to_string());
+        let pusher = OraclePusher::from_config(&config, rpc.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(pusher.contract().to_string(), CONTRACT);
+        let oracle_key = StacksSigner::new(SigningKey::from_slice(&[9u8; 32]).unwrap());
+        assert_eq!(
+            pusher.signer_identity().public_key,
+            oracle_key.public_key_hex()
+        );
+        assert!(!pusher.signer_identity().node_wallet);
+
+        // Without an oracle key the node wallet signs; without either there is no signer.
+        config.oracle_private_key = None;
+        assert!(OraclePusher::from_config(&config, rpc.clone()).is_err());
+        config.rebalance_private_key_hex = Some(hex::encode([8u8; 32]));
+        let identity = OraclePusher::from_config(&config, rpc.clone())
+            .unwrap()
+            .unwrap()
+            .signer_identity();
+        let node_wallet = StacksSigner::new(SigningKey::from_slice(&[8u8; 32]).unwrap());
+        assert_eq!(identity.public_key, node_wallet.public_key_hex());
+        assert!(identity.node_wallet);
+    }
+}
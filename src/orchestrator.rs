@@ -2,6 +2,7 @@
 //! Monitors internal services and manages fail-closed/recovery states.
 
 use crate::api::billing::nostr::NostrTelemetry;
+use crate::latency::timed_query;
 use crate::state::NexusState;
 use crate::storage::Storage;
 use std::sync::Arc;
@@ -39,28 +40,16 @@ impl AutonomousOrchestrator {
     }
 
     async fn audit_system_state(&self) -> anyhow::Result<()> {
-        let mut conn = self
-            .storage
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
-
         // 0. Lightning Recovery (SRL-1)
         if let Err(e) = self.audit_lightning_payments().await {
             tracing::error!("Lightning recovery audit failed: {}", e);
         }
 
         // 1. Check for sync drift
-        let drift: u64 = redis::cmd("GET")
-            .arg("nexus:drift")
-            .query_async(&mut conn)
+        let safety_mode = crate::safety::is_safety_mode_active(&self.storage).await?;
+        let drift = crate::safety::current_drift(&self.storage)
             .await
             .unwrap_or(0);
-        let safety_mode: bool = redis::cmd("GET")
-            .arg("nexus:safety_mode")
-            .query_async(&mut conn)
-            .await
-            .unwrap_or(false);
 
         if safety_mode && drift > 10 {
             tracing::warn!(
@@ -97,13 +86,12 @@ impl AutonomousOrchestrator {
         let adapter = crate::executor::lightning::LightningResilienceAdapter::new();
 
         // Find payments that might need recovery
-        let rows = sqlx::query(
+        let rows = timed_query("open_lightning_payments", sqlx::query(
             "SELECT payment_id, payment_hash, amount_msat, status, failure_type, retry_count, created_at, last_updated_at
              FROM lightning_payment_intents
              WHERE status IN ('pending', 'recovering', 'mpp_splitting', 'failed')"
         )
-        .fetch_all(&self.storage.pg_pool)
-        .await?;
+        .fetch_all(&self.storage.pg_pool)).await?;
 
         for row in rows {
             use crate::executor::lightning::{
@@ -146,20 +134,23 @@ impl AutonomousOrchestrator {
                 );
 
                 // Persist the updated state
-                sqlx::query(
-                    "UPDATE lightning_payment_intents
+                timed_query(
+                    "update_lightning_payment",
+                    sqlx::query(
+                        "UPDATE lightning_payment_intents
                      SET status = $1, retry_count = $2, last_updated_at = $3
                      WHERE payment_id = $4",
+                    )
+                    .bind(intent.status.to_string())
+                    .bind(intent.retry_count)
+                    .bind(intent.last_updated_at)
+                    .bind(&intent.payment_id)
+                    .execute(&self.storage.pg_pool),
                 )
-                .bind(intent.status.to_string())
-                .bind(intent.retry_count)
-                .bind(intent.last_updated_at)
-                .bind(&intent.payment_id)
-                .execute(&self.storage.pg_pool)
                 .await?;
 
                 // Audit the recovery event
-                sqlx::query(
+                timed_query("insert_lightning_payment_event", sqlx::query(
                     "INSERT INTO lightning_payment_events (event_id, payment_id, status, metadata)
                      VALUES ($1, $2, $3, $4)",
                 )
@@ -167,8 +158,7 @@ impl AutonomousOrchestrator {
                 .bind(&intent.payment_id)
                 .bind(intent.status.to_string())
                 .bind(format!("Recovery action: {}", action))
-                .execute(&self.storage.pg_pool)
-                .await?;
+                .execute(&self.storage.pg_pool)).await?;
             }
         }
 
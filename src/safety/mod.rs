@@ -1,86 +1,687 @@
 //! nexus-safety module implements the Sovereign Handoff safety protocol.
 //!
 //! It monitors the drift between the Nexus processed state and the Stacks L1
-//! burn-block height, triggering a safety mode if the Nexus falls behind.
+//! burn-block height, triggering a safety mode if the exponential moving average
+//! of the drift shows the Nexus falling behind.
+//! Repeated Stacks RPC failures trip a circuit breaker that raises a separate
+//! "L1 unreachable" state and backs off polling until the node answers again.
+//! When the oracle is running, an aggregate older than the configured maximum age
+//! raises an "oracle stale" state until a fresh aggregate is published; each such
+//! episode is recorded as a [`SafetyIncident`] and posted to the alert webhook.
+//! A newest ingested block whose chain time is older than the configured maximum
+//! holds Safety Mode until a newer block arrives.
 
+use crate::config::dynamic::DynamicConfigHandle;
+use crate::config::{DEFAULT_DRIFT_EMA_ALPHA, DEFAULT_RPC_FAILURE_THRESHOLD};
+use crate::events::NexusEvent;
+use crate::gateway::breaker::BreakerState;
+use crate::gateway::ServiceRegistry;
+use crate::stacks::rpc::get_json;
+use crate::stacks::RpcEndpoints;
+use crate::state::NexusState;
+use crate::storage::keys;
+use crate::storage::store::NexusStore;
 use crate::storage::Storage;
 use reqwest::Client;
+use serde::Serialize;
 use serde_json::Value;
-use sqlx::Row;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::time::{self, Duration};
 
+/// Upper bound on the backed-off heartbeat period while L1 is unreachable.
+pub const MAX_RPC_BACKOFF: Duration = Duration::from_secs(300);
+/// Consecutive heartbeats that must see the processed height above the L1 tip before
+/// it is flagged, so one endpoint answering a block behind does not trip Safety Mode.
+pub const DEFAULT_HEIGHT_ANOMALY_OBSERVATIONS: u32 = 3;
+/// Longest the heartbeat waits on the alert webhook.
+const ALERT_WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Synthetic drift recorded when gateway telemetry, rather than L1 lag, trips Safety Mode.
+const TELEMETRY_FAULT_DRIFT: u64 = 999;
+
+/// Span of gateway telemetry a failure rate is computed over.
+pub const TELEMETRY_WINDOW: Duration = Duration::from_secs(300);
+
+/// Failure rate when gateway verifications are failing often enough to warrant a
+/// safety alert: more than 10% of at least 100 observed requests.
+pub fn telemetry_failure_rate(successes: u64, failures: u64) -> Option<f64> {
+    let total = successes + failures;
+    if total <= 100 {
+        return None;
+    }
+    let rate = failures as f64 / total as f64;
+    (rate > 0.10).then_some(rate)
+}
+
+/// Samples of a source's cumulative verification counts, turning them into counts over
+/// the last [`TELEMETRY_WINDOW`] instead of since the source started.
+#[derive(Debug, Default)]
+struct TelemetryWindow {
+    samples: VecDeque<(Instant, u64, u64)>,
+}
+
+impl TelemetryWindow {
+    /// Records the cumulative `(successes, failures)` seen at `now` and returns their
+    /// increase since the latest sample at least a window old (the oldest one until
+    /// then). Counts that went backwards, as after a restart, start a new window.
+    fn observe(&mut self, now: Instant, successes: u64, failures: u64) -> (u64, u64) {
+        if self
+            .samples
+            .back()
+            .is_some_and(|&(_, s, f)| successes < s || failures < f)
+        {
+            self.samples.clear();
+        }
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|&(at, _, _)| now.duration_since(at) >= TELEMETRY_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, successes, failures));
+        let (_, base_successes, base_failures) = self.samples[0];
+        (successes - base_successes, failures - base_failures)
+    }
+}
+
+/// Change in breaker state caused by the latest RPC outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerTransition {
+    Unchanged,
+    /// The failure threshold was reached; L1 is now considered unreachable.
+    Opened,
+    /// The RPC answered again after the breaker had opened.
+    Closed,
+}
+
+/// Consecutive-failure circuit breaker for the Stacks RPC.
+#[derive(Debug)]
+pub struct RpcCircuitBreaker {
+    threshold: u32,
+    consecutive_failures: u32,
+    open: bool,
+}
+
+impl RpcCircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            consecutive_failures: 0,
+            open: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn record_failure(&mut self) -> BreakerTransition {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if !self.open && self.consecutive_failures >= self.threshold {
+            self.open = true;
+            return BreakerTransition::Opened;
+        }
+        BreakerTransition::Unchanged
+    }
+
+    pub fn record_success(&mut self) -> BreakerTransition {
+        self.consecutive_failures = 0;
+        if std::mem::take(&mut self.open) {
+            return BreakerTransition::Closed;
+        }
+        BreakerTransition::Unchanged
+    }
+
+    /// Delay before the next poll: `base` while closed, doubling per failure past the
+    /// threshold while open, capped at [`MAX_RPC_BACKOFF`] (or `base`, if longer).
+    pub fn next_delay(&self, base: Duration) -> Duration {
+        if !self.open {
+            return base;
+        }
+        let exponent = (self.consecutive_failures - self.threshold).min(16);
+        base.saturating_mul(1 << exponent)
+            .min(MAX_RPC_BACKOFF.max(base))
+    }
+}
+
+/// Exponential moving average of drift samples, so a single slow tick does not freeze
+/// the node while sustained lag still does.
+#[derive(Debug)]
+pub struct DriftEma {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl DriftEma {
+    /// `alpha` is the weight of each new sample, clamped to `(0, 1]`; 1 disables smoothing.
+    pub fn new(alpha: f64) -> Self {
+        let alpha = if alpha.is_finite() {
+            alpha.clamp(f64::MIN_POSITIVE, 1.0)
+        } else {
+            1.0
+        };
+        Self { alpha, value: None }
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    /// Folds in `sample` and returns the new average. The first sample seeds it.
+    pub fn update(&mut self, sample: u64) -> f64 {
+        let sample = sample as f64;
+        let next = match self.value {
+            Some(prev) => self.alpha * sample + (1.0 - self.alpha) * prev,
+            None => sample,
+        };
+        self.value = Some(next);
+        next
+    }
+}
+
 /// Monitors the health and sync status of the Nexus.
 pub struct NexusSafety {
-    storage: Arc<Storage>,
-    max_drift: u64,
-    rpc_url: String,
-    gateway_url: Option<String>,
+    store: Arc<dyn NexusStore>,
+    /// `safety_max_drift` and `safety_heartbeat_secs`, read on every heartbeat.
+    config: DynamicConfigHandle,
+    rpc: Arc<RpcEndpoints>,
+    /// External verification telemetry (`GATEWAY_TELEMETRY_URL`) and its bearer token.
+    telemetry_url: Option<String>,
+    telemetry_token: Option<String>,
+    /// Windowed counts per telemetry source: each in-process service, and the external
+    /// telemetry under `""`.
+    telemetry_windows: Mutex<HashMap<String, TelemetryWindow>>,
     http_client: Client,
+    rpc_breaker: Mutex<RpcCircuitBreaker>,
+    drift_ema: Mutex<DriftEma>,
+    gateway_registry: Option<Arc<ServiceRegistry>>,
+    /// Oracle aggregates older than this raise the oracle-stale state; `None` when the
+    /// oracle is not running.
+    oracle_max_age: Option<Duration>,
+    height_anomaly_observations: u32,
+    /// Consecutive heartbeats that saw the processed height above the L1 tip.
+    height_anomaly_streak: Mutex<u32>,
+    /// Receives incidents as they open and resolve (`SAFETY_ALERT_WEBHOOK_URL`).
+    alert_webhook_url: Option<String>,
+    /// Where the newest block's chain time is read, and how old it may get.
+    block_age: Option<(Arc<NexusState>, Duration)>,
+    /// Whether the newest block was last seen older than the maximum block age.
+    blocks_stale: Mutex<bool>,
+}
+
+/// One episode of a safety trigger, from when it was raised until it cleared.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SafetyIncident {
+    pub id: i64,
+    /// The [`SafetyTrigger`] that raised it.
+    pub cause: String,
+    pub detail: Value,
+    pub opened_at: chrono::DateTime<chrono::Utc>,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub async fn is_safety_mode_active(storage: &Storage) -> anyhow::Result<bool> {
-    let mut conn = storage
-        .redis_client
-        .get_multiplexed_async_connection()
+    let result = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("GET")
+                .arg(storage.redis_key(keys::SAFETY_MODE))
+                .query_async::<bool>(&mut conn)
+                .await
+        })
+        .await;
+    match result {
+        Ok(is_safety_mode) => Ok(is_safety_mode),
+        Err(e) if crate::storage::is_connection_error(&e) => Err(e.into()),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Last drift (in blocks) published by the safety monitor; 0 when none has been recorded.
+pub async fn current_drift(storage: &Storage) -> anyhow::Result<u64> {
+    let drift: Option<u64> = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("GET")
+                .arg(storage.redis_key(keys::DRIFT))
+                .query_async(&mut conn)
+                .await
+        })
+        .await?;
+    Ok(drift.unwrap_or(0))
+}
+
+/// Whether the safety monitor currently considers the Stacks L1 unreachable.
+pub async fn is_l1_unreachable(storage: &Storage) -> anyhow::Result<bool> {
+    let result = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("GET")
+                .arg(storage.redis_key(keys::L1_UNREACHABLE))
+                .query_async::<Option<u32>>(&mut conn)
+                .await
+        })
+        .await;
+    match result {
+        Ok(failures) => Ok(failures.is_some()),
+        Err(e) if crate::storage::is_connection_error(&e) => Err(e.into()),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Latest raw drift sample and its moving average, as recorded by the safety monitor.
+pub async fn drift_sample(storage: &Storage) -> anyhow::Result<Option<(u64, f64)>> {
+    let (raw, smoothed): (Option<u64>, Option<f64>) = storage
+        .with_redis(|mut conn| async move {
+            redis::pipe()
+                .cmd("GET")
+                .arg(storage.redis_key(keys::DRIFT_RAW))
+                .cmd("GET")
+                .arg(storage.redis_key(keys::DRIFT_SMOOTHED))
+                .query_async(&mut conn)
+                .await
+        })
+        .await?;
+    Ok(raw.zip(smoothed))
+}
+
+/// Whether the safety monitor currently considers the oracle's prices stale.
+pub async fn is_oracle_stale(storage: &Storage) -> anyhow::Result<bool> {
+    let result = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("GET")
+                .arg(storage.redis_key(keys::ORACLE_STALE))
+                .query_async::<Option<u64>>(&mut conn)
+                .await
+        })
+        .await;
+    match result {
+        Ok(age) => Ok(age.is_some()),
+        Err(e) if crate::storage::is_connection_error(&e) => Err(e.into()),
+        Err(_) => Ok(false),
+    }
+}
+
+/// What put the Nexus into Safety Mode, recorded under [`keys::SAFETY_REASON`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafetyTrigger {
+    Drift,
+    Telemetry,
+    L1Unreachable,
+    /// The latest oracle aggregate is older than the configured maximum age.
+    OracleStale,
+    /// The locally processed height is above the L1 tip, which only an ingestion bug or
+    /// an RPC serving another chain can cause.
+    HeightAnomaly,
+    /// The newest ingested block's chain time is older than the configured maximum age.
+    BlocksStale,
+    /// Operator-initiated fire-drill; never cleared by the heartbeat.
+    ManualDrill,
+}
+
+impl SafetyTrigger {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Drift => "drift",
+            Self::Telemetry => "telemetry",
+            Self::L1Unreachable => "l1_unreachable",
+            Self::OracleStale => "oracle_stale",
+            Self::HeightAnomaly => "height_anomaly",
+            Self::BlocksStale => "blocks_stale",
+            Self::ManualDrill => "manual_drill",
+        }
+    }
+}
+
+/// Sets the Safety Mode flags and broadcasts the trigger on [`keys::EVENTS_CHANNEL`] as
+/// a [`NexusEvent::SafetyTriggered`]; `height` is the L1 burn height it was observed at.
+pub async fn activate_safety_mode(
+    storage: &Storage,
+    drift: Option<u64>,
+    trigger: SafetyTrigger,
+    height: Option<u64>,
+) -> anyhow::Result<()> {
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .cmd("SET")
+        .arg(storage.redis_key(keys::SAFETY_MODE))
+        .arg(true)
+        .cmd("SET")
+        .arg(storage.redis_key(keys::SAFETY_REASON))
+        .arg(trigger.as_str());
+    if let Some(drift) = drift {
+        pipe.cmd("SET")
+            .arg(storage.redis_key(keys::DRIFT))
+            .arg(drift);
+    }
+    let event = NexusEvent::SafetyTriggered {
+        cause: trigger.as_str().to_string(),
+        drift,
+        height,
+    }
+    .to_json();
+    pipe.cmd("PUBLISH")
+        .arg(storage.redis_key(keys::EVENTS_CHANNEL))
+        .arg(event);
+    let pipe = &pipe;
+    storage
+        .with_redis(|mut conn| async move { pipe.query_async::<()>(&mut conn).await })
         .await?;
-    let is_safety_mode: bool = redis::cmd("GET")
-        .arg("nexus:safety_mode")
-        .query_async::<bool>(&mut conn)
-        .await
-        .unwrap_or(false);
-    Ok(is_safety_mode)
+
+    Ok(())
+}
+
+/// Starts a Sovereign Handoff drill: Safety Mode without any real drift. A reason
+/// Safety Mode was already held for is kept with the drill, so [`end_safety_drill`]
+/// can restore it.
+pub async fn start_safety_drill(storage: &Storage, reason: &str) -> anyhow::Result<()> {
+    let prior_reason = match safety_reason(storage).await? {
+        Some(current) if current == SafetyTrigger::ManualDrill.as_str() => {
+            drill_prior_reason(storage).await?
+        }
+        current => current,
+    };
+    activate_safety_mode(storage, None, SafetyTrigger::ManualDrill, None).await?;
+    let drill = serde_json::json!({
+        "reason": reason,
+        "triggered_at": chrono::Utc::now().to_rfc3339(),
+        "prior_reason": prior_reason,
+    })
+    .to_string();
+    let drill = &drill;
+    storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("SET")
+                .arg(storage.redis_key(keys::SAFETY_DRILL))
+                .arg(drill)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+    Ok(())
+}
+
+/// The reason Safety Mode was held for when the active drill started.
+async fn drill_prior_reason(storage: &Storage) -> anyhow::Result<Option<String>> {
+    let drill: Option<String> = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("GET")
+                .arg(storage.redis_key(keys::SAFETY_DRILL))
+                .query_async(&mut conn)
+                .await
+        })
+        .await?;
+    Ok(drill
+        .and_then(|drill| serde_json::from_str::<Value>(&drill).ok())
+        .and_then(|drill| drill["prior_reason"].as_str().map(str::to_string)))
+}
+
+/// Ends a drill started by [`start_safety_drill`]. Returns `false` (and leaves
+/// Safety Mode untouched) when the active Safety Mode is not a drill. When Safety Mode
+/// was already held for a real fault as the drill started, that reason is restored and
+/// Safety Mode stays on for the heartbeat to clear.
+pub async fn end_safety_drill(storage: &Storage) -> anyhow::Result<bool> {
+    let reason = safety_reason(storage).await?;
+    if reason.as_deref() != Some(SafetyTrigger::ManualDrill.as_str()) {
+        return Ok(false);
+    }
+
+    if let Some(prior) = drill_prior_reason(storage).await? {
+        let prior = &prior;
+        let event = NexusEvent::SafetyTriggered {
+            cause: prior.clone(),
+            drift: None,
+            height: None,
+        }
+        .to_json();
+        let event = &event;
+        storage
+            .with_redis(|mut conn| async move {
+                redis::pipe()
+                    .atomic()
+                    .cmd("SET")
+                    .arg(storage.redis_key(keys::SAFETY_REASON))
+                    .arg(prior)
+                    .cmd("DEL")
+                    .arg(storage.redis_key(keys::SAFETY_DRILL))
+                    .cmd("PUBLISH")
+                    .arg(storage.redis_key(keys::EVENTS_CHANNEL))
+                    .arg(event)
+                    .query_async::<()>(&mut conn)
+                    .await
+            })
+            .await?;
+        return Ok(true);
+    }
+
+    storage
+        .with_redis(|mut conn| async move {
+            redis::pipe()
+                .atomic()
+                .cmd("DEL")
+                .arg(storage.redis_key(keys::SAFETY_MODE))
+                .cmd("DEL")
+                .arg(storage.redis_key(keys::SAFETY_REASON))
+                .cmd("DEL")
+                .arg(storage.redis_key(keys::SAFETY_DRILL))
+                .cmd("PUBLISH")
+                .arg(storage.redis_key(keys::EVENTS_CHANNEL))
+                .arg(NexusEvent::SafetyCleared { drill: true }.to_json())
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+    Ok(true)
+}
+
+/// Recorded reason for the active Safety Mode, if any.
+pub async fn safety_reason(storage: &Storage) -> anyhow::Result<Option<String>> {
+    Ok(storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("GET")
+                .arg(storage.redis_key(keys::SAFETY_REASON))
+                .query_async(&mut conn)
+                .await
+        })
+        .await?)
 }
 
 impl NexusSafety {
-    /// Creates a new safety monitor with a default max drift of 2 blocks.
-    pub fn new(storage: Arc<Storage>, rpc_url: String, gateway_url: Option<String>) -> Self {
+    /// Creates a new safety monitor with the default dynamic settings (a max drift of 2
+    /// blocks).
+    pub fn new(
+        store: Arc<dyn NexusStore>,
+        rpc: Arc<RpcEndpoints>,
+        telemetry_url: Option<String>,
+    ) -> Self {
         Self {
-            storage,
-            max_drift: 2,
-            rpc_url,
-            gateway_url,
+            store,
+            config: DynamicConfigHandle::default(),
+            rpc,
+            telemetry_url,
+            telemetry_token: None,
+            telemetry_windows: Mutex::new(HashMap::new()),
             http_client: Client::new(),
+            rpc_breaker: Mutex::new(RpcCircuitBreaker::new(DEFAULT_RPC_FAILURE_THRESHOLD)),
+            drift_ema: Mutex::new(DriftEma::new(DEFAULT_DRIFT_EMA_ALPHA)),
+            gateway_registry: None,
+            oracle_max_age: None,
+            height_anomaly_observations: DEFAULT_HEIGHT_ANOMALY_OBSERVATIONS,
+            height_anomaly_streak: Mutex::new(0),
+            alert_webhook_url: None,
+            block_age: None,
+            blocks_stale: Mutex::new(false),
+        }
+    }
+
+    /// Reads the max drift and heartbeat period from `config` on every heartbeat.
+    pub fn with_dynamic_config(mut self, config: DynamicConfigHandle) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sends `token` as a bearer token when polling the external telemetry.
+    pub fn with_telemetry_token(mut self, token: Option<String>) -> Self {
+        self.telemetry_token = token;
+        self
+    }
+
+    /// Also watches the in-process gateway registry's counters and circuit breakers.
+    pub fn with_gateway_registry(mut self, registry: Arc<ServiceRegistry>) -> Self {
+        self.gateway_registry = Some(registry);
+        self
+    }
+
+    /// Sets the weight of each new drift sample in the moving average.
+    pub fn with_drift_ema_alpha(self, alpha: f64) -> Self {
+        Self {
+            drift_ema: Mutex::new(DriftEma::new(alpha)),
+            ..self
+        }
+    }
+
+    /// Watches the age of the latest oracle aggregate.
+    pub fn with_oracle_max_age(mut self, max_age: Duration) -> Self {
+        self.oracle_max_age = Some(max_age);
+        self
+    }
+
+    /// Watches the chain time of the newest block ingested into `state`.
+    pub fn with_block_max_age(mut self, state: Arc<NexusState>, max_age: Duration) -> Self {
+        self.block_age = Some((state, max_age));
+        self
+    }
+
+    /// POSTs incidents to `url` as they open and resolve.
+    pub fn with_alert_webhook(mut self, url: Option<String>) -> Self {
+        self.alert_webhook_url = url;
+        self
+    }
+
+    /// Sets how many consecutive heartbeats must see the processed height above the L1
+    /// tip before it is flagged as an anomaly.
+    pub fn with_height_anomaly_observations(mut self, observations: u32) -> Self {
+        self.height_anomaly_observations = observations.max(1);
+        self
+    }
+
+    /// Sets how many consecutive Stacks RPC failures declare L1 unreachable.
+    pub fn with_rpc_failure_threshold(self, threshold: u32) -> Self {
+        Self {
+            rpc_breaker: Mutex::new(RpcCircuitBreaker::new(threshold)),
+            ..self
         }
     }
 
     /// Runs the heartbeat monitor loop.
     pub async fn run_heartbeat(&self) -> anyhow::Result<()> {
-        let mut interval = time::interval(Duration::from_secs(10));
         let gateway_note = self
-            .gateway_url
+            .telemetry_url
             .as_deref()
-            .unwrap_or("(disabled; set GATEWAY_URL to enable)");
+            .unwrap_or("(disabled; set GATEWAY_TELEMETRY_URL to enable)");
+        let config = self.config.get();
         tracing::info!(
-            "Starting NexusSafety heartbeat (max_drift: {} blocks, RPC: {}, Gateway: {})...",
-            self.max_drift,
-            self.rpc_url,
+            "Starting NexusSafety heartbeat (every {}s, max_drift: {} blocks, RPC: {}, Gateway: {})...",
+            config.safety_heartbeat_secs,
+            config.safety_max_drift,
+            self.rpc.urls().join(", "),
             gateway_note
         );
 
         loop {
-            interval.tick().await;
             if let Err(e) = self.check_health().await {
                 tracing::error!("Safety heartbeat error: {}", e);
             }
 
-            if self.gateway_url.is_some() {
+            if let Some((state, _)) = &self.block_age {
+                if let Err(e) = self
+                    .evaluate_block_age(state.seconds_since_last_block())
+                    .await
+                {
+                    tracing::error!("Block freshness check error: {}", e);
+                }
+            }
+
+            if self.oracle_max_age.is_some() {
+                if let Err(e) = self.check_oracle_freshness().await {
+                    tracing::error!("Oracle freshness check error: {}", e);
+                }
+            }
+
+            if self.telemetry_url.is_some() || self.gateway_registry.is_some() {
                 if let Err(e) = self.ingest_gateway_telemetry().await {
                     tracing::error!("Gateway telemetry ingestion error: {}", e);
                 }
             }
+
+            let heartbeat_interval =
+                Duration::from_secs(self.config.get().safety_heartbeat_secs.max(1));
+            let delay = self
+                .rpc_breaker
+                .lock()
+                .unwrap()
+                .next_delay(heartbeat_interval);
+            time::sleep(delay).await;
         }
     }
 
-    /// Ingests telemetry from the Gateway and triggers safety mode if failure rates spike.
+    /// Counts from `source` over the last [`TELEMETRY_WINDOW`].
+    fn windowed(&self, source: &str, successes: u64, failures: u64) -> (u64, u64) {
+        self.telemetry_windows
+            .lock()
+            .unwrap()
+            .entry(source.to_string())
+            .or_default()
+            .observe(Instant::now(), successes, failures)
+    }
+
+    /// Ingests telemetry from the in-process gateway registry and the external Gateway,
+    /// triggering safety mode if failure rates over the last [`TELEMETRY_WINDOW`] spike.
+    /// Only upstream and internal errors count as failures; rejected requests do not.
     async fn ingest_gateway_telemetry(&self) -> anyhow::Result<()> {
-        let gateway_url = match self.gateway_url.as_deref() {
+        if let Some(registry) = &self.gateway_registry {
+            for breaker in registry.breaker_snapshots() {
+                if breaker.state == BreakerState::Open {
+                    tracing::warn!(
+                        service = %breaker.service,
+                        failure_streak = breaker.failure_streak,
+                        last_error = ?breaker.last_error,
+                        "Gateway service circuit breaker is open"
+                    );
+                }
+            }
+            for service in registry.metrics().snapshot() {
+                let (verified, failed) =
+                    self.windowed(&service.service, service.verified, service.failed);
+                if let Some(rate) = telemetry_failure_rate(verified, failed) {
+                    tracing::error!(
+                        "Gateway Circuit Breaker Triggered for {}! Failure Rate: {:.2}% (Success: {}, Failures: {}, Last error: {})",
+                        service.service,
+                        rate * 100.0,
+                        verified,
+                        failed,
+                        service.last_error.as_deref().unwrap_or("none")
+                    );
+                    return self
+                        .trigger_safety_mode(TELEMETRY_FAULT_DRIFT, SafetyTrigger::Telemetry, None)
+                        .await;
+                }
+            }
+        }
+
+        let url = match self.telemetry_url.as_deref() {
             Some(url) => url,
             None => return Ok(()),
         };
 
-        let url = format!("{}/api/v1/state", gateway_url);
-        let resp = self.http_client.get(&url).send().await?;
+        let mut request = self.http_client.get(url);
+        if let Some(token) = &self.telemetry_token {
+            request = request.bearer_auth(token);
+        }
+        let resp = request.send().await?;
 
         if !resp.status().is_success() {
             tracing::warn!("Failed to fetch Gateway state: {}", resp.status());
@@ -95,24 +696,19 @@ impl NexusSafety {
         let failure_count = json["metrics"]["verification_failure"]
             .as_u64()
             .unwrap_or(0);
+        let (success_count, failure_count) = self.windowed("", success_count, failure_count);
 
-        // Define a simple circuit breaker logic based on failures
-        let total_verifications = success_count + failure_count;
-
-        if total_verifications > 100 {
-            let failure_rate = (failure_count as f64) / (total_verifications as f64);
-            // If more than 10% of verifications are failing, trigger an infrastructure-level safety alert
-            if failure_rate > 0.10 {
-                tracing::error!(
-                    "Gateway Circuit Breaker Triggered! Failure Rate: {:.2}% (Success: {}, Failures: {})",
-                    failure_rate * 100.0,
-                    success_count,
-                    failure_count
-                );
+        if let Some(failure_rate) = telemetry_failure_rate(success_count, failure_count) {
+            tracing::error!(
+                "Gateway Circuit Breaker Triggered! Failure Rate: {:.2}% (Success: {}, Failures: {})",
+                failure_rate * 100.0,
+                success_count,
+                failure_count
+            );
 
-                // We reuse the existing safety mode broadcast but flag it as a telemetry alert
-                self.trigger_safety_mode(999).await?; // 999 is a synthetic drift indicating a telemetry fault
-            }
+            // We reuse the existing safety mode broadcast but flag it as a telemetry alert
+            self.trigger_safety_mode(TELEMETRY_FAULT_DRIFT, SafetyTrigger::Telemetry, None)
+                .await?;
         }
 
         Ok(())
@@ -121,119 +717,322 @@ impl NexusSafety {
     /// Checks the health by comparing local processed height with external L1 height.
     #[tracing::instrument(skip(self))]
     async fn check_health(&self) -> anyhow::Result<()> {
-        let current_burn_height = self.get_external_burn_height().await?;
+        let current_burn_height = match self.get_external_burn_height().await {
+            Ok(height) => {
+                let transition = self.rpc_breaker.lock().unwrap().record_success();
+                if transition == BreakerTransition::Closed {
+                    tracing::info!("Stacks RPC reachable again. Clearing L1-unreachable state.");
+                    self.clear_l1_unreachable().await?;
+                }
+                height
+            }
+            Err(e) => {
+                let (transition, failures) = {
+                    let mut breaker = self.rpc_breaker.lock().unwrap();
+                    (breaker.record_failure(), breaker.consecutive_failures())
+                };
+                if transition == BreakerTransition::Opened {
+                    tracing::error!(
+                        "L1 Unreachable! Stacks RPC failed {} consecutive times: {}",
+                        failures,
+                        e
+                    );
+                    self.trigger_l1_unreachable(failures).await?;
+                }
+                return Err(e);
+            }
+        };
+        self.evaluate_drift(current_burn_height).await
+    }
+
+    /// Compares `current_burn_height` with the locally processed height, entering
+    /// Safety Mode when the moving average of the drift exceeds `max_drift` and leaving
+    /// it once the average recovers. A processed height above the L1 tip is not a drift
+    /// of zero but an anomaly: once seen on enough consecutive heartbeats it is published
+    /// and holds Safety Mode until the heights agree again.
+    pub async fn evaluate_drift(&self, current_burn_height: u64) -> anyhow::Result<()> {
         let processed_height = self.get_processed_height().await?;
+        if processed_height > current_burn_height {
+            let streak = {
+                let mut streak = self.height_anomaly_streak.lock().unwrap();
+                *streak += 1;
+                *streak
+            };
+            if streak < self.height_anomaly_observations {
+                tracing::warn!(
+                    "Processed height {} exceeds L1 height {} ({} of {} observations before flagging)",
+                    processed_height,
+                    current_burn_height,
+                    streak,
+                    self.height_anomaly_observations
+                );
+                return Ok(());
+            }
+            if streak > self.height_anomaly_observations {
+                return Ok(());
+            }
+            return self
+                .flag_height_anomaly(processed_height, current_burn_height)
+                .await;
+        }
+        *self.height_anomaly_streak.lock().unwrap() = 0;
 
+        let max_drift = self.config.get().safety_max_drift;
         let delta = Self::calculate_drift(current_burn_height, processed_height);
+        let smoothed = self.drift_ema.lock().unwrap().update(delta);
+        self.store.record_drift_sample(delta, smoothed).await?;
 
-        if delta > self.max_drift {
+        if smoothed > max_drift as f64 {
             tracing::error!(
-                "Sovereign Handoff Triggered! Delta: {} blocks (L1: {}, Local: {})",
+                "Sovereign Handoff Triggered! Smoothed drift: {:.2} blocks, delta: {} (L1: {}, Local: {})",
+                smoothed,
                 delta,
                 current_burn_height,
                 processed_height
             );
-            self.trigger_safety_mode(delta).await?;
+            self.trigger_safety_mode(delta, SafetyTrigger::Drift, Some(current_burn_height))
+                .await?;
         } else {
-            tracing::debug!("Nexus health check passed. Drift: {} blocks", delta);
+            if delta > max_drift {
+                tracing::warn!(
+                    "Drift spike of {} blocks (smoothed {:.2}); below the Safety Mode trigger",
+                    delta,
+                    smoothed
+                );
+            } else {
+                tracing::debug!(
+                    "Nexus health check passed. Drift: {} blocks (smoothed {:.2})",
+                    delta,
+                    smoothed
+                );
+            }
             self.clear_safety_mode_if_needed(delta).await?;
         }
 
         Ok(())
     }
 
+    async fn check_oracle_freshness(&self) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let age = self
+            .store
+            .latest_oracle_timestamp()
+            .await?
+            .map(|published| now.saturating_sub(published));
+        self.evaluate_oracle_age(age).await
+    }
+
+    /// Enters the oracle-stale state (and Safety Mode) when the latest aggregate is
+    /// `age_secs` old and that exceeds the configured maximum, and leaves it once a fresh
+    /// aggregate arrives. `None` means no aggregate has been published yet. Each episode
+    /// is an incident, posted to the alert webhook as it opens and resolves.
+    pub async fn evaluate_oracle_age(&self, age_secs: Option<u64>) -> anyhow::Result<()> {
+        let (Some(max_age), Some(age)) = (self.oracle_max_age, age_secs) else {
+            return Ok(());
+        };
+        let flagged = self.store.is_oracle_stale().await?;
+        if age > max_age.as_secs() {
+            if !flagged {
+                tracing::error!(
+                    "Oracle prices are stale! Latest aggregate is {}s old (max {}s)",
+                    age,
+                    max_age.as_secs()
+                );
+                let incident = self
+                    .store
+                    .open_incident(
+                        SafetyTrigger::OracleStale,
+                        &serde_json::json!({
+                            "age_secs": age,
+                            "max_age_secs": max_age.as_secs(),
+                        }),
+                    )
+                    .await?;
+                self.store.mark_oracle_stale(age).await?;
+                self.send_alert("opened", &incident).await;
+            }
+        } else if flagged {
+            tracing::info!(
+                "Fresh oracle aggregate ({}s old). Clearing oracle-stale state.",
+                age
+            );
+            self.store.clear_oracle_stale().await?;
+            if let Some(incident) = self
+                .store
+                .resolve_incident(SafetyTrigger::OracleStale)
+                .await?
+            {
+                self.send_alert("resolved", &incident).await;
+            }
+            if self.store.safety_reason().await?.as_deref()
+                == Some(SafetyTrigger::OracleStale.as_str())
+                && !self.height_anomaly_flagged()
+                && !*self.blocks_stale.lock().unwrap()
+            {
+                self.store.clear_safety_mode().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Enters Safety Mode when the newest ingested block's chain time is `age_secs` old
+    /// and that exceeds the configured maximum, and holds it until a newer block arrives.
+    /// `None` means no block has reported a time yet. Safety Mode already held by another
+    /// trigger keeps its reason.
+    pub async fn evaluate_block_age(&self, age_secs: Option<u64>) -> anyhow::Result<()> {
+        let (Some((_, max_age)), Some(age)) = (&self.block_age, age_secs) else {
+            return Ok(());
+        };
+        let stale = age > max_age.as_secs();
+        let was_stale = std::mem::replace(&mut *self.blocks_stale.lock().unwrap(), stale);
+        if stale && !was_stale {
+            tracing::error!(
+                "No new block for {}s (max {}s)! Holding Safety Mode until one arrives.",
+                age,
+                max_age.as_secs()
+            );
+            if !self.store.is_safety_mode_active().await? {
+                self.store
+                    .activate_safety_mode(None, SafetyTrigger::BlocksStale, None)
+                    .await?;
+            }
+        } else if !stale && was_stale {
+            tracing::info!("New block ({}s old). Clearing block staleness.", age);
+            if self.store.safety_reason().await?.as_deref()
+                == Some(SafetyTrigger::BlocksStale.as_str())
+                && !self.height_anomaly_flagged()
+                && !self.store.is_oracle_stale().await?
+            {
+                self.store.clear_safety_mode().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes a [`NexusEvent::HeightAnomaly`] and enters Safety Mode; the drift
+    /// average is left untouched. Safety Mode already held by another trigger (or a
+    /// drill) keeps its reason, and stays on while the anomaly lasts.
+    async fn flag_height_anomaly(
+        &self,
+        processed_height: u64,
+        l1_height: u64,
+    ) -> anyhow::Result<()> {
+        tracing::error!(
+            "Processed height {} exceeds L1 height {}! Holding Safety Mode until they agree.",
+            processed_height,
+            l1_height
+        );
+        self.store
+            .publish_event(&NexusEvent::HeightAnomaly {
+                processed_height,
+                l1_height,
+            })
+            .await?;
+        if self.store.is_safety_mode_active().await? {
+            return Ok(());
+        }
+        self.store
+            .activate_safety_mode(None, SafetyTrigger::HeightAnomaly, Some(l1_height))
+            .await
+    }
+
+    /// POSTs `{"status": status, "incident": incident}` to the alert webhook, if one is
+    /// configured. A failed delivery is logged and not retried.
+    async fn send_alert(&self, status: &str, incident: &SafetyIncident) {
+        let Some(url) = &self.alert_webhook_url else {
+            return;
+        };
+        let result = self
+            .http_client
+            .post(url)
+            .timeout(ALERT_WEBHOOK_TIMEOUT)
+            .json(&serde_json::json!({ "status": status, "incident": incident }))
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!(
+                error = %e,
+                cause = %incident.cause,
+                "Safety alert webhook failed"
+            );
+        }
+    }
+
+    /// Whether the current height anomaly has been flagged, and so holds Safety Mode.
+    fn height_anomaly_flagged(&self) -> bool {
+        *self.height_anomaly_streak.lock().unwrap() >= self.height_anomaly_observations
+    }
+
     pub fn calculate_drift(current: u64, processed: u64) -> u64 {
         current.saturating_sub(processed)
     }
 
     async fn get_external_burn_height(&self) -> anyhow::Result<u64> {
-        // Real implementation: calls Stacks node RPC.
-        let url = format!("{}/extended/v1/block?limit=1", self.rpc_url);
-        let resp = self.http_client.get(&url).send().await?;
-        let json: Value = resp.json().await?;
-
-        let height = json["results"][0]["height"]
-            .as_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse block height from Stacks RPC"))?;
-
-        Ok(height)
+        self.rpc
+            .call(|base| async move {
+                let url = format!("{}/extended/v1/block?limit=1", base);
+                let json: Value = get_json(&self.http_client, &url).await?;
+                json["results"][0]["height"].as_u64().ok_or_else(|| {
+                    anyhow::anyhow!("Failed to parse block height from Stacks RPC {}", base)
+                })
+            })
+            .await
     }
 
     async fn get_processed_height(&self) -> anyhow::Result<u64> {
-        let row = sqlx::query("SELECT MAX(height) as max_height FROM stacks_blocks")
-            .fetch_one(&self.storage.pg_pool)
-            .await?;
-
-        let max_height: Option<i64> = row.get("max_height");
-        Ok(max_height.unwrap_or(0) as u64)
+        self.store.max_processed_height().await
     }
 
     /// Triggers Safety Mode and broadcasts it via Redis.
-    async fn trigger_safety_mode(&self, delta: u64) -> anyhow::Result<()> {
-        let mut conn = self
-            .storage
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
+    async fn trigger_safety_mode(
+        &self,
+        delta: u64,
+        trigger: SafetyTrigger,
+        height: Option<u64>,
+    ) -> anyhow::Result<()> {
+        self.store
+            .activate_safety_mode(Some(delta), trigger, height)
+            .await
+    }
 
-        redis::pipe()
-            .atomic()
-            .cmd("SET")
-            .arg("nexus:safety_mode")
-            .arg(true)
-            .cmd("SET")
-            .arg("nexus:drift")
-            .arg(delta)
-            .cmd("PUBLISH")
-            .arg("nexus:events")
-            .arg("safety_mode_triggered")
-            .query_async::<()>(&mut conn)
-            .await?;
+    /// Enters the L1-unreachable state (which also holds Safety Mode) and broadcasts it.
+    async fn trigger_l1_unreachable(&self, failures: u32) -> anyhow::Result<()> {
+        self.store.mark_l1_unreachable(failures).await
+    }
 
-        Ok(())
+    /// Leaves the L1-unreachable state; Safety Mode is left to the drift check that follows.
+    async fn clear_l1_unreachable(&self) -> anyhow::Result<()> {
+        self.store.clear_l1_unreachable().await
     }
 
     async fn clear_safety_mode_if_needed(&self, _delta: u64) -> anyhow::Result<()> {
-        let mut conn = self
-            .storage
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
-        let is_safety_mode: bool = redis::cmd("GET")
-            .arg("nexus:safety_mode")
-            .query_async::<bool>(&mut conn)
-            .await
-            .unwrap_or(false);
+        if self.store.safety_reason().await?.as_deref() == Some(SafetyTrigger::ManualDrill.as_str())
+        {
+            tracing::debug!(
+                "Safety drill in progress; leaving Safety Mode for the operator to clear."
+            );
+            return Ok(());
+        }
+        if self.store.is_oracle_stale().await? {
+            tracing::debug!("Oracle prices still stale; leaving Safety Mode in place.");
+            return Ok(());
+        }
+        if *self.blocks_stale.lock().unwrap() {
+            tracing::debug!("No recent block; leaving Safety Mode in place.");
+            return Ok(());
+        }
 
-        if is_safety_mode {
+        if self.store.is_safety_mode_active().await? {
             tracing::info!("System recovered. Clearing Safety Mode.");
-            redis::pipe()
-                .atomic()
-                .cmd("DEL")
-                .arg("nexus:safety_mode")
-                .cmd("DEL")
-                .arg("nexus:drift")
-                .cmd("PUBLISH")
-                .arg("nexus:events")
-                .arg("safety_mode_cleared")
-                .query_async::<()>(&mut conn)
-                .await?;
+            self.store.clear_safety_mode().await?;
         }
         Ok(())
     }
 
     /// Provides status and proof for "Direct Withdrawal Tenure".
     pub async fn get_direct_exit_status(&self, user_address: &str) -> anyhow::Result<String> {
-        let mut conn = self
-            .storage
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
-        let is_safety_mode: bool = redis::cmd("GET")
-            .arg("nexus:safety_mode")
-            .query_async::<bool>(&mut conn)
-            .await
-            .unwrap_or(false);
+        let is_safety_mode = self.store.is_safety_mode_active().await?;
 
         if is_safety_mode {
             Ok(format!(
@@ -252,6 +1051,335 @@ impl NexusSafety {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::repo::NewBlock;
+    use crate::storage::store::InMemoryStore;
+
+    async fn monitor_at_height(height: u64) -> (Arc<InMemoryStore>, NexusSafety) {
+        let store = Arc::new(InMemoryStore::new());
+        store
+            .ingest_block(&NewBlock::microblock("0xtip", height), &[])
+            .await
+            .unwrap();
+        let safety = NexusSafety::new(
+            store.clone(),
+            Arc::new(RpcEndpoints::single("http://localhost:3999")),
+            None,
+        );
+        (store, safety)
+    }
+
+    #[tokio::test]
+    async fn test_drift_beyond_limit_triggers_safety_mode() {
+        let (store, safety) = monitor_at_height(100).await;
+        safety.evaluate_drift(102).await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+
+        safety.evaluate_drift(103).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        assert_eq!(store.drift(), Some(3));
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("drift")
+        );
+        assert_eq!(
+            store.events(),
+            vec![NexusEvent::SafetyTriggered {
+                cause: "drift".to_string(),
+                drift: Some(3),
+                height: Some(103),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recovered_drift_clears_safety_mode() {
+        let (store, safety) = monitor_at_height(100).await;
+        safety.evaluate_drift(110).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+
+        // The average of 10 decays below 2 only after several healthy samples.
+        for _ in 0..6 {
+            safety.evaluate_drift(101).await.unwrap();
+            assert!(store.is_safety_mode_active().await.unwrap());
+        }
+        safety.evaluate_drift(101).await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        assert_eq!(store.drift(), None);
+        let events = store.events();
+        assert_eq!(
+            events.first().map(NexusEvent::kind),
+            Some("safety_triggered")
+        );
+        assert_eq!(
+            events.last(),
+            Some(&NexusEvent::SafetyCleared { drill: false })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_processed_height_above_l1_is_flagged() {
+        let (store, safety) = monitor_at_height(105).await;
+        for _ in 1..DEFAULT_HEIGHT_ANOMALY_OBSERVATIONS {
+            safety.evaluate_drift(100).await.unwrap();
+        }
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        assert!(store.events().is_empty());
+
+        safety.evaluate_drift(100).await.unwrap();
+        safety.evaluate_drift(100).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("height_anomaly")
+        );
+        assert_eq!(
+            store.events(),
+            vec![
+                NexusEvent::HeightAnomaly {
+                    processed_height: 105,
+                    l1_height: 100,
+                },
+                NexusEvent::SafetyTriggered {
+                    cause: "height_anomaly".to_string(),
+                    drift: None,
+                    height: Some(100),
+                },
+            ]
+        );
+        assert_eq!(store.drift_sample(), None);
+
+        // Cleared once L1 catches up.
+        safety.evaluate_drift(105).await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_stale_block_holds_safety_mode_until_a_new_one() {
+        let (store, safety) = monitor_at_height(100).await;
+        let state = Arc::new(NexusState::new());
+        let safety = safety.with_block_max_age(state.clone(), Duration::from_secs(60));
+        state.record_block_time(chrono::Utc::now() - chrono::Duration::seconds(120));
+
+        safety
+            .evaluate_block_age(state.seconds_since_last_block())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("blocks_stale")
+        );
+        // Heights agreeing with L1 do not lift it while the tip is old.
+        safety.evaluate_drift(100).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+
+        state.record_block_time(chrono::Utc::now());
+        safety
+            .evaluate_block_age(state.seconds_since_last_block())
+            .await
+            .unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_height_anomaly_needs_consecutive_observations() {
+        let (store, safety) = monitor_at_height(105).await;
+        let safety = safety.with_height_anomaly_observations(2);
+        // An endpoint a block behind, then one that is caught up: the streak resets.
+        safety.evaluate_drift(104).await.unwrap();
+        safety.evaluate_drift(105).await.unwrap();
+        safety.evaluate_drift(104).await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        assert!(store.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_height_anomaly_keeps_an_existing_reason() {
+        let (store, safety) = monitor_at_height(105).await;
+        let safety = safety.with_height_anomaly_observations(1);
+        store
+            .activate_safety_mode(Some(3), SafetyTrigger::Drift, Some(108))
+            .await
+            .unwrap();
+        safety.evaluate_drift(100).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("drift")
+        );
+        assert_eq!(
+            store.events().last(),
+            Some(&NexusEvent::HeightAnomaly {
+                processed_height: 105,
+                l1_height: 100,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transient_drift_spike_does_not_trigger() {
+        let (store, safety) = monitor_at_height(100).await;
+        safety.evaluate_drift(100).await.unwrap();
+        safety.evaluate_drift(105).await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        let (raw, smoothed) = store.drift_sample().unwrap();
+        assert_eq!(raw, 5);
+        assert!((smoothed - 1.5).abs() < 1e-9);
+        for _ in 0..10 {
+            safety.evaluate_drift(100).await.unwrap();
+        }
+        assert!(store.events().is_empty());
+
+        // Sustained lag still trips it.
+        let mut ticks = 0;
+        while !store.is_safety_mode_active().await.unwrap() {
+            safety.evaluate_drift(105).await.unwrap();
+            ticks += 1;
+        }
+        assert_eq!(ticks, 2);
+        assert_eq!(store.drift(), Some(5));
+    }
+
+    #[test]
+    fn test_drift_ema() {
+        let mut ema = DriftEma::new(0.5);
+        assert_eq!(ema.value(), None);
+        assert_eq!(ema.update(4), 4.0);
+        assert_eq!(ema.update(0), 2.0);
+        assert_eq!(ema.update(2), 2.0);
+        let mut raw = DriftEma::new(1.0);
+        raw.update(9);
+        assert_eq!(raw.update(1), 1.0);
+        assert_eq!(DriftEma::new(f64::NAN).update(3), 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_leaves_drill_in_place() {
+        let (store, safety) = monitor_at_height(100).await;
+        store
+            .activate_safety_mode(None, SafetyTrigger::ManualDrill, None)
+            .await
+            .unwrap();
+
+        safety.evaluate_drift(100).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        assert_eq!(
+            store.events(),
+            vec![NexusEvent::SafetyTriggered {
+                cause: "manual_drill".to_string(),
+                drift: None,
+                height: None,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aged_oracle_aggregate_raises_oracle_stale() {
+        let (store, safety) = monitor_at_height(100).await;
+        let safety = safety.with_oracle_max_age(Duration::from_secs(300));
+        let executor = crate::executor::NexusExecutor::new(
+            Storage::for_tests(),
+            crate::executor::rgb::RGBRolloutMode::Disabled,
+            std::collections::HashSet::new(),
+        )
+        .with_store(store.clone());
+        executor.execute_rebalance().await.unwrap();
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        store.set_oracle_timestamp(now - 3_600);
+        safety.check_oracle_freshness().await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        assert!(store.oracle_stale().unwrap() >= 3_600);
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("oracle_stale")
+        );
+        let err = executor.execute_rebalance().await.unwrap_err();
+        assert!(err.to_string().contains("stale"));
+
+        // A healthy drift check does not lift Safety Mode while prices are stale.
+        safety.evaluate_drift(100).await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        safety.evaluate_oracle_age(Some(4_000)).await.unwrap();
+        assert_eq!(store.events().len(), 1);
+        assert_eq!(store.events()[0].kind(), "oracle_stale");
+
+        store.set_oracle_timestamp(now);
+        safety.check_oracle_freshness().await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        assert_eq!(store.oracle_stale(), None);
+        assert_eq!(
+            store.events()[1..],
+            [
+                NexusEvent::OracleFresh,
+                NexusEvent::SafetyCleared { drill: false }
+            ]
+        );
+        executor.execute_rebalance().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oracle_stale_incident_is_recorded_and_alerted() {
+        use axum::{routing::post, Json, Router};
+
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let received = alerts.clone();
+        let app = Router::new().route(
+            "/alerts",
+            post(move |Json(body): Json<Value>| {
+                let received = received.clone();
+                async move { received.lock().unwrap().push(body) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/alerts", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let (store, safety) = monitor_at_height(100).await;
+        let safety = safety
+            .with_oracle_max_age(Duration::from_secs(300))
+            .with_alert_webhook(Some(url));
+        // Safety Mode already held for drift keeps that reason.
+        store
+            .activate_safety_mode(Some(5), SafetyTrigger::Drift, Some(105))
+            .await
+            .unwrap();
+
+        safety.evaluate_oracle_age(Some(900)).await.unwrap();
+        safety.evaluate_oracle_age(Some(960)).await.unwrap();
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("drift")
+        );
+        let incidents = store.incidents();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].cause, "oracle_stale");
+        assert_eq!(incidents[0].detail["age_secs"], 900);
+        assert_eq!(incidents[0].resolved_at, None);
+
+        safety.evaluate_oracle_age(Some(10)).await.unwrap();
+        assert!(store.incidents()[0].resolved_at.is_some());
+        // The drift reason is not the oracle's to clear.
+        assert!(store.is_safety_mode_active().await.unwrap());
+
+        let alerts = alerts.lock().unwrap().clone();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0]["status"], "opened");
+        assert_eq!(alerts[0]["incident"]["cause"], "oracle_stale");
+        assert_eq!(alerts[1]["status"], "resolved");
+        assert_eq!(alerts[1]["incident"]["id"], alerts[0]["incident"]["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_oracle_age_ignored_without_max_age_or_aggregate() {
+        let (store, safety) = monitor_at_height(100).await;
+        safety.evaluate_oracle_age(Some(1_000_000)).await.unwrap();
+        let safety = safety.with_oracle_max_age(Duration::from_secs(300));
+        safety.evaluate_oracle_age(None).await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        assert!(store.events().is_empty());
+    }
 
     #[test]
     fn test_calculate_drift() {
@@ -259,4 +1387,141 @@ mod tests {
         assert_eq!(NexusSafety::calculate_drift(100, 102), 0);
         assert_eq!(NexusSafety::calculate_drift(100, 100), 0);
     }
+
+    #[test]
+    fn test_telemetry_failure_rate_threshold() {
+        assert_eq!(telemetry_failure_rate(50, 50), None);
+        assert_eq!(telemetry_failure_rate(180, 20), None);
+        assert_eq!(telemetry_failure_rate(150, 50), Some(0.25));
+    }
+
+    #[test]
+    fn test_telemetry_window_counts_only_recent_requests() {
+        let mut window = TelemetryWindow::default();
+        let t0 = Instant::now();
+        assert_eq!(window.observe(t0, 1_000, 500), (0, 0));
+        assert_eq!(
+            window.observe(t0 + Duration::from_secs(60), 1_100, 520),
+            (100, 20)
+        );
+        // The first sample ages out; counts are taken from the latest one a window old.
+        let later = t0 + Duration::from_secs(60) + TELEMETRY_WINDOW;
+        assert_eq!(window.observe(later, 1_150, 520), (50, 0));
+        // A restarted source resets the window.
+        assert_eq!(window.observe(later, 10, 1), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_rgb_requests_do_not_trip_safety() {
+        let (store, safety) = monitor_at_height(100).await;
+        let registry = Arc::new(ServiceRegistry::with_builtin_services());
+        let safety = safety.with_gateway_registry(registry.clone());
+        let invalid = serde_json::json!({
+            "asset_id": "rgb:asset-1",
+            "amount": 1,
+            "txid": "aa".repeat(32),
+            "schema": "NIA",
+            "consignment_hex": "not-hex",
+        })
+        .to_string();
+
+        safety.ingest_gateway_telemetry().await.unwrap();
+        for _ in 0..101 {
+            let response = registry.dispatch("rgb", &invalid).await.unwrap().unwrap();
+            assert_eq!(response.body["verified"], false);
+        }
+        safety.ingest_gateway_telemetry().await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+        let rgb = registry.metrics().service("rgb").unwrap();
+        assert_eq!((rgb.failed, rgb.rejected), (0, 101));
+    }
+
+    /// Gateway service whose upstream is always down.
+    struct DownService;
+
+    #[async_trait::async_trait]
+    impl crate::gateway::GatewayService for DownService {
+        fn name(&self) -> &'static str {
+            "down"
+        }
+
+        fn status(&self) -> lib_conxian_core::gateway::ServiceStatus {
+            lib_conxian_core::gateway::ServiceStatus {
+                service_name: "Down".to_string(),
+                status: "Active".to_string(),
+                version: "0".to_string(),
+            }
+        }
+
+        async fn handle_request(
+            &self,
+            _payload: &str,
+        ) -> Result<crate::gateway::ServiceResponse, crate::gateway::ServiceError> {
+            Err(crate::gateway::ServiceError::upstream("connection refused"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_gateway_service_trips_safety_in_process() {
+        let (store, safety) = monitor_at_height(100).await;
+        let mut registry = ServiceRegistry::new();
+        registry.register(Arc::new(DownService));
+        let registry = Arc::new(registry);
+        let safety = safety.with_gateway_registry(registry.clone());
+
+        safety.ingest_gateway_telemetry().await.unwrap();
+        for _ in 0..100 {
+            assert!(registry.dispatch("down", "{}").await.unwrap().is_err());
+        }
+        // Not yet enough requests to judge.
+        safety.ingest_gateway_telemetry().await.unwrap();
+        assert!(!store.is_safety_mode_active().await.unwrap());
+
+        assert!(registry.dispatch("down", "{}").await.unwrap().is_err());
+        safety.ingest_gateway_telemetry().await.unwrap();
+        assert!(store.is_safety_mode_active().await.unwrap());
+        assert_eq!(
+            store.safety_reason().await.unwrap().as_deref(),
+            Some("telemetry")
+        );
+    }
+
+    #[test]
+    fn test_rpc_breaker_opens_after_threshold_and_recovers() {
+        let mut breaker = RpcCircuitBreaker::new(3);
+        assert_eq!(breaker.record_failure(), BreakerTransition::Unchanged);
+        assert_eq!(breaker.record_failure(), BreakerTransition::Unchanged);
+        assert_eq!(breaker.record_failure(), BreakerTransition::Opened);
+        assert_eq!(breaker.record_failure(), BreakerTransition::Unchanged);
+        assert!(breaker.is_open());
+
+        assert_eq!(breaker.record_success(), BreakerTransition::Closed);
+        assert_eq!(breaker.record_success(), BreakerTransition::Unchanged);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_rpc_breaker_backs_off_while_open() {
+        let base = Duration::from_secs(10);
+        let mut breaker = RpcCircuitBreaker::new(2);
+        breaker.record_failure();
+        assert_eq!(breaker.next_delay(base), base);
+        breaker.record_failure();
+        assert_eq!(breaker.next_delay(base), base);
+        breaker.record_failure();
+        assert_eq!(breaker.next_delay(base), Duration::from_secs(20));
+        for _ in 0..20 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.next_delay(base), MAX_RPC_BACKOFF);
+
+        breaker.record_success();
+        assert_eq!(breaker.next_delay(base), base);
+
+        let slow = Duration::from_secs(600);
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.next_delay(slow), slow);
+    }
 }
@@ -0,0 +1,188 @@
+//! [NEXUS-SIGN-01] Byte-oriented message signing.
+//!
+//! `lib_conxian_core::Wallet::sign` only accepts `&str`. `MessageSigner` gives Nexus
+//! callers (oracle state, BitVM circuits) a `sign_bytes` entry point with `sign(&str)`
+//! delegating to it, so binary payloads never have to be lossily re-encoded.
+//! [`RotatingSigner`] lets an operator replace a signing key without a restart.
+
+use crate::stacks::transaction::StacksSigner;
+use anyhow::Context;
+use lib_conxian_core::Wallet;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Prefix marking a signature domain over hex-encoded binary input.
+pub const BINARY_MESSAGE_PREFIX: &str = "nexus:bytes:v1:";
+
+pub trait MessageSigner: Send + Sync {
+    /// Signs an arbitrary byte string.
+    fn sign_bytes(&self, message: &[u8]) -> String;
+
+    /// Signs a UTF-8 message; equivalent to `sign_bytes(message.as_bytes())`.
+    fn sign_message(&self, message: &str) -> String {
+        self.sign_bytes(message.as_bytes())
+    }
+}
+
+/// Canonical string a byte message is signed as by the core wallet.
+///
+/// Valid UTF-8 is signed verbatim so existing `Wallet::sign` signatures are unchanged;
+/// anything else is hex-encoded under [`BINARY_MESSAGE_PREFIX`] until the core wallet
+/// exposes raw byte signing.
+pub fn wallet_message(message: &[u8]) -> String {
+    match std::str::from_utf8(message) {
+        Ok(text) if !text.starts_with(BINARY_MESSAGE_PREFIX) => text.to_string(),
+        _ => format!("{}{}", BINARY_MESSAGE_PREFIX, hex::encode(message)),
+    }
+}
+
+impl MessageSigner for Wallet {
+    fn sign_bytes(&self, message: &[u8]) -> String {
+        self.sign(&wallet_message(message))
+    }
+}
+
+/// A shared signer whose key can be swapped while it is in use. A signature is made
+/// wholly with the key that was current when it started. Message signers are the
+/// default; `RotatingSigner<StacksSigner>` rotates a transaction-signing key.
+pub struct RotatingSigner<S: ?Sized = dyn MessageSigner> {
+    current: RwLock<(Arc<S>, Option<String>)>,
+}
+
+impl<S: ?Sized> RotatingSigner<S> {
+    fn from_parts(signer: Arc<S>, public_key: Option<String>) -> Self {
+        Self {
+            current: RwLock::new((signer, public_key)),
+        }
+    }
+
+    /// The current key's public key; `None` for an ephemeral key.
+    pub fn public_key(&self) -> Option<String> {
+        self.current.read().unwrap().1.clone()
+    }
+
+    /// The current key, to make one signature or transaction with.
+    pub fn current(&self) -> Arc<S> {
+        self.current.read().unwrap().0.clone()
+    }
+
+    /// Makes `signer` the current key and returns the previous public key.
+    pub fn rotate(&self, signer: Arc<S>, public_key: Option<String>) -> Option<String> {
+        std::mem::replace(&mut *self.current.write().unwrap(), (signer, public_key)).1
+    }
+}
+
+impl RotatingSigner {
+    /// `public_key` is the signer's compressed SEC1 key (hex), when known; it is only
+    /// reported, never checked.
+    pub fn new(signer: Arc<dyn MessageSigner>, public_key: Option<String>) -> Self {
+        Self::from_parts(signer, public_key)
+    }
+
+    /// A wallet for a 32-byte hex secret.
+    pub fn from_private_key_hex(private_key_hex: &str) -> anyhow::Result<Self> {
+        let (wallet, public_key) = wallet_from_hex(private_key_hex)?;
+        Ok(Self::new(wallet, Some(public_key)))
+    }
+}
+
+impl RotatingSigner<StacksSigner> {
+    pub fn stacks(signer: StacksSigner) -> Self {
+        let public_key = signer.public_key_hex();
+        Self::from_parts(Arc::new(signer), Some(public_key))
+    }
+
+    /// Makes `signer` the current key and returns the previous public key.
+    pub fn rotate_stacks(&self, signer: StacksSigner) -> Option<String> {
+        let public_key = signer.public_key_hex();
+        self.rotate(Arc::new(signer), Some(public_key))
+    }
+}
+
+impl<S: ?Sized> fmt::Debug for RotatingSigner<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatingSigner")
+            .field("public_key", &self.public_key())
+            .finish()
+    }
+}
+
+impl MessageSigner for RotatingSigner {
+    fn sign_bytes(&self, message: &[u8]) -> String {
+        self.current().sign_bytes(message)
+    }
+}
+
+/// A wallet for a 32-byte hex secret, with its public key.
+pub fn wallet_from_hex(private_key_hex: &str) -> anyhow::Result<(Arc<dyn MessageSigner>, String)> {
+    let public_key = StacksSigner::from_hex(private_key_hex)?.public_key_hex();
+    let wallet = Wallet::from_private_key_hex(private_key_hex.trim())
+        .context("Private key is not accepted by the wallet")?;
+    Ok((Arc::new(wallet), public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSigner {
+        seen: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl MessageSigner for RecordingSigner {
+        fn sign_bytes(&self, message: &[u8]) -> String {
+            self.seen.lock().unwrap().push(message.to_vec());
+            hex::encode(message)
+        }
+    }
+
+    #[test]
+    fn test_sign_message_delegates_to_sign_bytes() {
+        let signer = RecordingSigner::default();
+        assert_eq!(signer.sign_message("abc"), "616263");
+        assert_eq!(signer.seen.lock().unwrap().as_slice(), &[b"abc".to_vec()]);
+    }
+
+    #[test]
+    fn test_wallet_message_keeps_utf8_and_hex_encodes_binary() {
+        assert_eq!(wallet_message(b"nexus:kwil:block"), "nexus:kwil:block");
+        assert_eq!(
+            wallet_message(&[0xff, 0x00]),
+            format!("{}ff00", BINARY_MESSAGE_PREFIX)
+        );
+    }
+
+    #[test]
+    fn test_wallet_message_cannot_collide_with_binary_domain() {
+        let spoofed = format!("{}ff00", BINARY_MESSAGE_PREFIX);
+        assert_ne!(
+            wallet_message(spoofed.as_bytes()),
+            wallet_message(&[0xff, 0x00])
+        );
+    }
+
+    struct FixedSigner(&'static str);
+
+    impl MessageSigner for FixedSigner {
+        fn sign_bytes(&self, _message: &[u8]) -> String {
+            self.0.to_string()
+        }
+    }
+
+    #[test]
+    fn test_rotation_swaps_the_key_every_holder_signs_with() {
+        let rotating = Arc::new(RotatingSigner::new(
+            Arc::new(FixedSigner("old")),
+            Some("02aa".to_string()),
+        ));
+        let holder: Arc<dyn MessageSigner> = rotating.clone();
+        assert_eq!(holder.sign_message("m"), "old");
+
+        let previous = rotating.rotate(Arc::new(FixedSigner("new")), Some("02bb".to_string()));
+        assert_eq!(previous.as_deref(), Some("02aa"));
+        assert_eq!(rotating.public_key().as_deref(), Some("02bb"));
+        assert_eq!(holder.sign_message("m"), "new");
+    }
+}
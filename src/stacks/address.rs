@@ -0,0 +1,272 @@
+//! c32check-encoded Stacks addresses and contract identifiers.
+
+use k256::ecdsa::VerifyingKey;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+const C32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Single-sig mainnet address version (`SP…`).
+pub const MAINNET_SINGLESIG: u8 = 22;
+/// Multi-sig mainnet address version (`SM…`).
+pub const MAINNET_MULTISIG: u8 = 20;
+/// Single-sig testnet address version (`ST…`).
+pub const TESTNET_SINGLESIG: u8 = 26;
+/// Multi-sig testnet address version (`SN…`).
+pub const TESTNET_MULTISIG: u8 = 21;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StacksAddress {
+    pub version: u8,
+    pub hash160: [u8; 20],
+}
+
+impl StacksAddress {
+    /// Single-sig (P2PKH) address of a compressed public key.
+    pub fn p2pkh(mainnet: bool, key: &VerifyingKey) -> Self {
+        Self {
+            version: if mainnet {
+                MAINNET_SINGLESIG
+            } else {
+                TESTNET_SINGLESIG
+            },
+            hash160: hash160(&key.to_sec1_bytes()),
+        }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        matches!(self.version, MAINNET_SINGLESIG | MAINNET_MULTISIG)
+    }
+}
+
+impl fmt::Display for StacksAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = self.hash160.to_vec();
+        data.extend_from_slice(&checksum(self.version, &self.hash160));
+        write!(
+            f,
+            "S{}{}",
+            C32_ALPHABET[self.version as usize & 0x1f] as char,
+            c32_encode(&data)
+        )
+    }
+}
+
+impl FromStr for StacksAddress {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let rest = s
+            .strip_prefix('S')
+            .ok_or_else(|| anyhow::anyhow!("Stacks address '{}' must start with 'S'", s))?;
+        let version = rest
+            .bytes()
+            .next()
+            .and_then(c32_value)
+            .ok_or_else(|| anyhow::anyhow!("Invalid Stacks address version in '{}'", s))?;
+        let data = c32_decode(&rest[1..])
+            .filter(|data| data.len() == 24)
+            .ok_or_else(|| anyhow::anyhow!("Invalid c32 payload in Stacks address '{}'", s))?;
+        let mut hash160 = [0u8; 20];
+        hash160.copy_from_slice(&data[..20]);
+        if data[20..] != checksum(version, &hash160) {
+            anyhow::bail!("Checksum mismatch in Stacks address '{}'", s);
+        }
+        Ok(Self { version, hash160 })
+    }
+}
+
+/// A deployed contract, written `ADDRESS.contract-name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContractId {
+    pub address: StacksAddress,
+    pub name: String,
+}
+
+impl fmt::Display for ContractId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.address, self.name)
+    }
+}
+
+impl FromStr for ContractId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, name) = s
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| anyhow::anyhow!("Contract id '{}' must be ADDRESS.name", s))?;
+        if name.is_empty()
+            || name.len() > 128
+            || !name.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("Invalid contract name '{}'", name);
+        }
+        Ok(Self {
+            address: address.parse()?,
+            name: name.to_string(),
+        })
+    }
+}
+
+/// `RIPEMD160(SHA256(data))`.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+fn checksum(version: u8, hash160: &[u8; 20]) -> [u8; 4] {
+    let mut hasher = Sha256::new();
+    hasher.update([version]);
+    hasher.update(hash160);
+    let digest = Sha256::digest(hasher.finalize());
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+fn c32_value(c: u8) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        b'O' => b'0',
+        b'L' | b'I' => b'1',
+        c => c,
+    };
+    C32_ALPHABET.iter().position(|&a| a == c).map(|v| v as u8)
+}
+
+/// Big-endian base-32 encoding; each leading zero byte becomes one leading `0`.
+fn c32_encode(data: &[u8]) -> String {
+    let mut out = Vec::with_capacity(data.len() * 8 / 5 + 1);
+    let mut carry = 0u16;
+    let mut carry_bits = 0;
+    for &byte in data.iter().rev() {
+        carry |= (byte as u16) << carry_bits;
+        carry_bits += 8;
+        while carry_bits >= 5 {
+            out.push(C32_ALPHABET[(carry & 0x1f) as usize]);
+            carry >>= 5;
+            carry_bits -= 5;
+        }
+    }
+    if carry_bits > 0 {
+        out.push(C32_ALPHABET[(carry & 0x1f) as usize]);
+    }
+    while out.last() == Some(&b'0') {
+        out.pop();
+    }
+    out.extend(data.iter().take_while(|b| **b == 0).map(|_| b'0'));
+    out.reverse();
+    out.into_iter().map(char::from).collect()
+}
+
+fn c32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8 + 1);
+    let mut carry = 0u16;
+    let mut carry_bits = 0;
+    for c in input.bytes().rev() {
+        carry |= (c32_value(c)? as u16) << carry_bits;
+        carry_bits += 5;
+        if carry_bits >= 8 {
+            out.push((carry & 0xff) as u8);
+            carry >>= 8;
+            carry_bits -= 8;
+        }
+    }
+    if carry_bits > 0 {
+        out.push(carry as u8);
+    }
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    let zeros = input
+        .bytes()
+        .take_while(|c| c32_value(*c) == Some(0))
+        .count();
+    out.extend(std::iter::repeat_n(0, zeros));
+    out.reverse();
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c32_address_round_trip() {
+        let hash160: [u8; 20] = hex::decode("a46ff88886c2ef9762d970b4d2c63678835bd39d")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let mainnet = StacksAddress {
+            version: MAINNET_SINGLESIG,
+            hash160,
+        };
+        assert_eq!(
+            mainnet.to_string(),
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+        );
+        let testnet = StacksAddress {
+            version: TESTNET_SINGLESIG,
+            hash160,
+        };
+        assert_eq!(
+            testnet.to_string(),
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ"
+        );
+        assert_eq!(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+                .parse::<StacksAddress>()
+                .unwrap(),
+            mainnet
+        );
+        assert!(mainnet.is_mainnet());
+        assert!(!testnet.is_mainnet());
+
+        let boot = StacksAddress {
+            version: MAINNET_SINGLESIG,
+            hash160: [0; 20],
+        };
+        assert_eq!(boot.to_string(), "SP000000000000000000002Q6VF78");
+        assert_eq!(
+            "SP000000000000000000002Q6VF78"
+                .parse::<StacksAddress>()
+                .unwrap(),
+            boot
+        );
+    }
+
+    #[test]
+    fn test_invalid_addresses_are_rejected() {
+        assert!("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8"
+            .parse::<StacksAddress>()
+            .is_err());
+        assert!("XP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+            .parse::<StacksAddress>()
+            .is_err());
+        assert!("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJU"
+            .parse::<StacksAddress>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_contract_id_parsing() {
+        let id: ContractId = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.fx-oracle"
+            .parse()
+            .unwrap();
+        assert_eq!(id.name, "fx-oracle");
+        assert_eq!(
+            id.to_string(),
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.fx-oracle"
+        );
+        assert!("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+            .parse::<ContractId>()
+            .is_err());
+        assert!("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.1oracle"
+            .parse::<ContractId>()
+            .is_err());
+    }
+}
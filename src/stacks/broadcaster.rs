@@ -0,0 +1,176 @@
+//! Submits signed transactions to a Stacks node and reads account nonces from it.
+
+use crate::stacks::address::StacksAddress;
+use crate::stacks::rpc::{get_json, RpcEndpoints};
+use crate::stacks::transaction::SignedTransaction;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastError {
+    /// The node could not be reached or answered with a server error.
+    Network(String),
+    /// The node refused the transaction (bad nonce, fee too low, contract error…).
+    Rejected(String),
+    /// The node answered with a body we could not interpret.
+    Malformed(String),
+}
+
+impl fmt::Display for BroadcastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Network(msg) => write!(f, "Stacks node unreachable: {}", msg),
+            Self::Rejected(msg) => write!(f, "Transaction rejected: {}", msg),
+            Self::Malformed(msg) => write!(f, "Malformed Stacks node response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BroadcastError {}
+
+#[derive(Deserialize)]
+struct AccountResponse {
+    nonce: u64,
+}
+
+#[derive(Deserialize)]
+struct NoncesResponse {
+    possible_next_nonce: u64,
+}
+
+#[derive(Deserialize)]
+struct InfoResponse {
+    burn_block_height: u64,
+}
+
+/// Shared client for everything Nexus submits to the Stacks node, failing over across
+/// the same endpoints sync reads from.
+#[derive(Debug, Clone)]
+pub struct StacksBroadcaster {
+    client: reqwest::Client,
+    rpc: Arc<RpcEndpoints>,
+    /// Highest nonce each sender broadcast through this client, so a transaction still
+    /// in the mempool is never reused.
+    broadcast_nonces: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl StacksBroadcaster {
+    pub fn new(rpc_url: &str) -> Self {
+        Self::with_endpoints(Arc::new(RpcEndpoints::single(rpc_url)))
+    }
+
+    pub fn with_endpoints(rpc: Arc<RpcEndpoints>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rpc,
+            broadcast_nonces: Arc::default(),
+        }
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, BroadcastError> {
+        let client = &self.client;
+        self.rpc
+            .call(|base| async move { get_json(client, &format!("{}{}", base, path)).await })
+            .await
+            .map_err(|e| {
+                if e.downcast_ref::<reqwest::Error>()
+                    .is_some_and(reqwest::Error::is_decode)
+                {
+                    BroadcastError::Malformed(e.to_string())
+                } else {
+                    BroadcastError::Network(format!(
+                        "GET {}: {:#}",
+                        path.split('?').next().unwrap_or(path),
+                        e
+                    ))
+                }
+            })
+    }
+
+    /// Nonce for `address`'s next transaction, counting the ones still in the mempool:
+    /// the API's `possible_next_nonce` (`GET /extended/v1/address/{address}/nonces`), or
+    /// the confirmed [`Self::account_nonce`] on a node without that endpoint, and never
+    /// at or below a nonce this client already broadcast for `address`.
+    pub async fn next_nonce(&self, address: &StacksAddress) -> Result<u64, BroadcastError> {
+        let nonce = match self
+            .get::<NoncesResponse>(&format!("/extended/v1/address/{}/nonces", address))
+            .await
+        {
+            Ok(nonces) => nonces.possible_next_nonce,
+            Err(BroadcastError::Malformed(e)) => return Err(BroadcastError::Malformed(e)),
+            Err(_) => self.account_nonce(address).await?,
+        };
+        let broadcast = self
+            .broadcast_nonces
+            .lock()
+            .unwrap()
+            .get(&address.to_string())
+            .map(|n| n + 1);
+        Ok(broadcast.map_or(nonce, |b| b.max(nonce)))
+    }
+
+    /// Current burn chain height, per `GET /v2/info`.
+    pub async fn burn_block_height(&self) -> Result<u64, BroadcastError> {
+        Ok(self
+            .get::<InfoResponse>("/v2/info")
+            .await?
+            .burn_block_height)
+    }
+
+    /// Next nonce for `address` among its confirmed transactions, per
+    /// `GET /v2/accounts/{address}`; see [`Self::next_nonce`] for one that counts the
+    /// mempool.
+    pub async fn account_nonce(&self, address: &StacksAddress) -> Result<u64, BroadcastError> {
+        let account: AccountResponse = self
+            .get(&format!("/v2/accounts/{}?proof=0", address))
+            .await?;
+        Ok(account.nonce)
+    }
+
+    /// Posts `tx` to `/v2/transactions` and returns the txid the node accepted, `0x`-prefixed.
+    /// An unreachable endpoint or a server error moves on to the next one; a rejection
+    /// is final, since every node would refuse the same transaction.
+    pub async fn broadcast(&self, tx: &SignedTransaction) -> Result<String, BroadcastError> {
+        let client = &self.client;
+        let (status, body) = self
+            .rpc
+            .call(|base| async move {
+                let resp = client
+                    .post(format!("{}/v2/transactions", base))
+                    .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                    .body(tx.bytes.clone())
+                    .send()
+                    .await?;
+                let status = resp.status();
+                let body = resp.text().await?;
+                if status.is_server_error() {
+                    anyhow::bail!("{}: {}", status, body);
+                }
+                Ok((status, body))
+            })
+            .await
+            .map_err(|e| BroadcastError::Network(format!("{:#}", e)))?;
+        if !status.is_success() {
+            return Err(BroadcastError::Rejected(body));
+        }
+        let txid: String = serde_json::from_str(&body)
+            .map_err(|_| BroadcastError::Malformed(format!("expected a txid string: {}", body)))?;
+        let txid = txid.trim_start_matches("0x").to_ascii_lowercase();
+        self.broadcast_nonces
+            .lock()
+            .unwrap()
+            .entry(tx.sender.to_string())
+            .and_modify(|n| *n = (*n).max(tx.nonce))
+            .or_insert(tx.nonce);
+        if txid != tx.txid {
+            tracing::warn!(
+                expected = %tx.txid,
+                returned = %txid,
+                "Stacks node returned a different txid than the one computed locally"
+            );
+        }
+        Ok(format!("0x{}", txid))
+    }
+}
@@ -0,0 +1,411 @@
+//! Consensus serialization of Clarity values: encoding the arguments of the contract
+//! calls Nexus submits, and decoding the hex-serialized values (`0x…`) the Stacks API
+//! reports for contract-call arguments and print events.
+
+use crate::stacks::address::{ContractId, StacksAddress};
+use std::collections::BTreeMap;
+
+const TYPE_INT: u8 = 0x00;
+const TYPE_UINT: u8 = 0x01;
+const TYPE_BUFFER: u8 = 0x02;
+const TYPE_TRUE: u8 = 0x03;
+const TYPE_FALSE: u8 = 0x04;
+const TYPE_PRINCIPAL_STANDARD: u8 = 0x05;
+const TYPE_PRINCIPAL_CONTRACT: u8 = 0x06;
+const TYPE_RESPONSE_OK: u8 = 0x07;
+const TYPE_RESPONSE_ERR: u8 = 0x08;
+const TYPE_OPTIONAL_NONE: u8 = 0x09;
+const TYPE_OPTIONAL_SOME: u8 = 0x0a;
+const TYPE_LIST: u8 = 0x0b;
+const TYPE_TUPLE: u8 = 0x0c;
+const TYPE_STRING_ASCII: u8 = 0x0d;
+const TYPE_STRING_UTF8: u8 = 0x0e;
+
+/// Deepest nesting accepted when decoding, matching the Clarity VM's own limit.
+const MAX_DECODE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClarityValue {
+    Int(i128),
+    UInt(u128),
+    Buffer(Vec<u8>),
+    Bool(bool),
+    Principal(StacksAddress),
+    ContractPrincipal(ContractId),
+    ResponseOk(Box<ClarityValue>),
+    ResponseErr(Box<ClarityValue>),
+    OptionalNone,
+    OptionalSome(Box<ClarityValue>),
+    StringAscii(String),
+    StringUtf8(String),
+    List(Vec<ClarityValue>),
+    /// Fields are serialized in name order, as the Clarity VM expects.
+    Tuple(BTreeMap<String, ClarityValue>),
+}
+
+impl ClarityValue {
+    pub fn tuple<I, K>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (K, ClarityValue)>,
+        K: Into<String>,
+    {
+        Self::Tuple(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.serialize_into(&mut out);
+        out
+    }
+
+    pub fn serialize_into(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Int(v) => {
+                out.push(TYPE_INT);
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+            Self::UInt(v) => {
+                out.push(TYPE_UINT);
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+            Self::Buffer(bytes) => {
+                out.push(TYPE_BUFFER);
+                out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+                out.extend_from_slice(bytes);
+            }
+            Self::Bool(true) => out.push(TYPE_TRUE),
+            Self::Bool(false) => out.push(TYPE_FALSE),
+            Self::Principal(address) => {
+                out.push(TYPE_PRINCIPAL_STANDARD);
+                out.push(address.version);
+                out.extend_from_slice(&address.hash160);
+            }
+            Self::ContractPrincipal(contract) => {
+                out.push(TYPE_PRINCIPAL_CONTRACT);
+                out.push(contract.address.version);
+                out.extend_from_slice(&contract.address.hash160);
+                out.push(contract.name.len() as u8);
+                out.extend_from_slice(contract.name.as_bytes());
+            }
+            Self::ResponseOk(value) => {
+                out.push(TYPE_RESPONSE_OK);
+                value.serialize_into(out);
+            }
+            Self::ResponseErr(value) => {
+                out.push(TYPE_RESPONSE_ERR);
+                value.serialize_into(out);
+            }
+            Self::OptionalNone => out.push(TYPE_OPTIONAL_NONE),
+            Self::OptionalSome(value) => {
+                out.push(TYPE_OPTIONAL_SOME);
+                value.serialize_into(out);
+            }
+            Self::StringAscii(s) => {
+                out.push(TYPE_STRING_ASCII);
+                out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+                out.extend_from_slice(s.as_bytes());
+            }
+            Self::StringUtf8(s) => {
+                out.push(TYPE_STRING_UTF8);
+                out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+                out.extend_from_slice(s.as_bytes());
+            }
+            Self::List(items) => {
+                out.push(TYPE_LIST);
+                out.extend_from_slice(&(items.len() as u32).to_be_bytes());
+                for item in items {
+                    item.serialize_into(out);
+                }
+            }
+            Self::Tuple(fields) => {
+                out.push(TYPE_TUPLE);
+                out.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+                for (name, value) in fields {
+                    out.push(name.len() as u8);
+                    out.extend_from_slice(name.as_bytes());
+                    value.serialize_into(out);
+                }
+            }
+        }
+    }
+
+    /// Decodes one serialized value; trailing bytes are an error.
+    pub fn deserialize(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let value = reader.value(0)?;
+        if reader.pos != bytes.len() {
+            anyhow::bail!(
+                "{} trailing bytes after Clarity value",
+                bytes.len() - reader.pos
+            );
+        }
+        Ok(value)
+    }
+
+    /// Decodes the hex form the Stacks API reports, with or without `0x`.
+    pub fn from_hex(hex_value: &str) -> anyhow::Result<Self> {
+        let bytes = hex::decode(hex_value.trim().trim_start_matches("0x"))
+            .map_err(|_| anyhow::anyhow!("Clarity value is not valid hex"))?;
+        Self::deserialize(&bytes)
+    }
+
+    pub fn as_uint(&self) -> Option<u128> {
+        match self {
+            Self::UInt(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            Self::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// The text of an ASCII or UTF-8 string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::StringAscii(s) | Self::StringUtf8(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// A standard or contract principal, in its `SP…` / `SP….name` form.
+    pub fn as_principal(&self) -> Option<String> {
+        match self {
+            Self::Principal(address) => Some(address.to_string()),
+            Self::ContractPrincipal(contract) => Some(contract.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The field `name` of a tuple.
+    pub fn field(&self, name: &str) -> Option<&ClarityValue> {
+        match self {
+            Self::Tuple(fields) => fields.get(name),
+            _ => None,
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("Clarity value truncated at byte {}", self.pos))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> anyhow::Result<usize> {
+        let bytes: [u8; 4] = self.take(4)?.try_into().expect("4 bytes");
+        Ok(u32::from_be_bytes(bytes) as usize)
+    }
+
+    fn hash160(&mut self) -> anyhow::Result<[u8; 20]> {
+        Ok(self.take(20)?.try_into().expect("20 bytes"))
+    }
+
+    fn address(&mut self) -> anyhow::Result<StacksAddress> {
+        let version = self.u8()?;
+        if version >= 32 {
+            anyhow::bail!("Invalid address version {}", version);
+        }
+        Ok(StacksAddress {
+            version,
+            hash160: self.hash160()?,
+        })
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let len = self.u32()?;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| anyhow::anyhow!("Clarity string is not valid UTF-8"))
+    }
+
+    fn value(&mut self, depth: usize) -> anyhow::Result<ClarityValue> {
+        if depth > MAX_DECODE_DEPTH {
+            anyhow::bail!("Clarity value nested deeper than {}", MAX_DECODE_DEPTH);
+        }
+        let type_id = self.u8()?;
+        Ok(match type_id {
+            TYPE_INT => ClarityValue::Int(i128::from_be_bytes(
+                self.take(16)?.try_into().expect("16 bytes"),
+            )),
+            TYPE_UINT => ClarityValue::UInt(u128::from_be_bytes(
+                self.take(16)?.try_into().expect("16 bytes"),
+            )),
+            TYPE_BUFFER => {
+                let len = self.u32()?;
+                ClarityValue::Buffer(self.take(len)?.to_vec())
+            }
+            TYPE_TRUE => ClarityValue::Bool(true),
+            TYPE_FALSE => ClarityValue::Bool(false),
+            TYPE_PRINCIPAL_STANDARD => ClarityValue::Principal(self.address()?),
+            TYPE_PRINCIPAL_CONTRACT => {
+                let address = self.address()?;
+                let len = self.u8()? as usize;
+                let name = std::str::from_utf8(self.take(len)?)
+                    .map_err(|_| anyhow::anyhow!("Contract name is not valid UTF-8"))?;
+                ClarityValue::ContractPrincipal(ContractId {
+                    address,
+                    name: name.to_string(),
+                })
+            }
+            TYPE_RESPONSE_OK => ClarityValue::ResponseOk(Box::new(self.value(depth + 1)?)),
+            TYPE_RESPONSE_ERR => ClarityValue::ResponseErr(Box::new(self.value(depth + 1)?)),
+            TYPE_OPTIONAL_NONE => ClarityValue::OptionalNone,
+            TYPE_OPTIONAL_SOME => ClarityValue::OptionalSome(Box::new(self.value(depth + 1)?)),
+            TYPE_LIST => {
+                let len = self.u32()?;
+                // Every element takes at least one byte, which bounds the allocation.
+                let mut items = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+                for _ in 0..len {
+                    items.push(self.value(depth + 1)?);
+                }
+                ClarityValue::List(items)
+            }
+            TYPE_TUPLE => {
+                let len = self.u32()?;
+                let mut fields = BTreeMap::new();
+                for _ in 0..len {
+                    let name_len = self.u8()? as usize;
+                    let name = std::str::from_utf8(self.take(name_len)?)
+                        .map_err(|_| anyhow::anyhow!("Tuple field name is not valid UTF-8"))?
+                        .to_string();
+                    let value = self.value(depth + 1)?;
+                    if fields.insert(name.clone(), value).is_some() {
+                        anyhow::bail!("Duplicate tuple field '{}'", name);
+                    }
+                }
+                ClarityValue::Tuple(fields)
+            }
+            TYPE_STRING_ASCII => {
+                let s = self.string()?;
+                if !s.is_ascii() {
+                    anyhow::bail!("string-ascii value contains non-ASCII bytes");
+                }
+                ClarityValue::StringAscii(s)
+            }
+            TYPE_STRING_UTF8 => ClarityValue::StringUtf8(self.string()?),
+            other => anyhow::bail!("Unknown Clarity type id 0x{:02x}", other),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_scalars() {
+        assert_eq!(
+            hex::encode(ClarityValue::UInt(1).serialize()),
+            "0100000000000000000000000000000001"
+        );
+        assert_eq!(
+            hex::encode(ClarityValue::Int(-1).serialize()),
+            "00ffffffffffffffffffffffffffffffff"
+        );
+        assert_eq!(ClarityValue::Bool(true).serialize(), vec![0x03]);
+        assert_eq!(ClarityValue::Bool(false).serialize(), vec![0x04]);
+        assert_eq!(
+            hex::encode(ClarityValue::Buffer(vec![0xde, 0xad]).serialize()),
+            "0200000002dead"
+        );
+        assert_eq!(
+            hex::encode(ClarityValue::StringAscii("EUR".to_string()).serialize()),
+            "0d00000003455552"
+        );
+    }
+
+    #[test]
+    fn test_decode_round_trips_every_type() {
+        let address: StacksAddress = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7".parse().unwrap();
+        let value = ClarityValue::List(vec![
+            ClarityValue::Int(-7),
+            ClarityValue::UInt(u128::MAX),
+            ClarityValue::Buffer(vec![0xde, 0xad]),
+            ClarityValue::Bool(true),
+            ClarityValue::Principal(address),
+            ClarityValue::ContractPrincipal(ContractId {
+                address,
+                name: "conxian-vault".to_string(),
+            }),
+            ClarityValue::ResponseOk(Box::new(ClarityValue::OptionalNone)),
+            ClarityValue::ResponseErr(Box::new(ClarityValue::UInt(3))),
+            ClarityValue::OptionalSome(Box::new(ClarityValue::StringUtf8("€".to_string()))),
+            ClarityValue::tuple([
+                ("rate", ClarityValue::UInt(2)),
+                ("currency", ClarityValue::StringAscii("EUR".to_string())),
+            ]),
+        ]);
+        let encoded = format!("0x{}", hex::encode(value.serialize()));
+        assert_eq!(ClarityValue::from_hex(&encoded).unwrap(), value);
+
+        let ClarityValue::List(items) = value else {
+            unreachable!()
+        };
+        assert_eq!(items[1].as_uint(), Some(u128::MAX));
+        assert_eq!(items[0].as_int(), Some(-7));
+        assert_eq!(
+            items[5].as_principal().as_deref(),
+            Some("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.conxian-vault")
+        );
+        assert_eq!(
+            items[9].field("currency").and_then(|v| v.as_str()),
+            Some("EUR")
+        );
+        assert_eq!(items[9].field("missing"), None);
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_values() {
+        // Truncated uint, trailing byte, unknown type, oversized length.
+        for hex_value in ["0100000000", "0300", "ff", "02ffffffff00", "0b00000002 03"] {
+            assert!(
+                ClarityValue::from_hex(&hex_value.replace(' ', "")).is_err(),
+                "{}",
+                hex_value
+            );
+        }
+        assert!(ClarityValue::from_hex("not hex").is_err());
+        // Nesting beyond the limit.
+        let deep = format!("{}09", "0a".repeat(MAX_DECODE_DEPTH + 1));
+        assert!(ClarityValue::from_hex(&deep).is_err());
+        let ok = format!("{}09", "0a".repeat(MAX_DECODE_DEPTH));
+        assert!(ClarityValue::from_hex(&ok).is_ok());
+    }
+
+    #[test]
+    fn test_serialize_tuple_sorts_field_names() {
+        let value = ClarityValue::List(vec![ClarityValue::tuple([
+            ("rate", ClarityValue::UInt(2)),
+            ("currency", ClarityValue::StringAscii("EUR".to_string())),
+        ])]);
+        assert_eq!(
+            hex::encode(value.serialize()),
+            concat!(
+                "0b00000001",
+                "0c00000002",
+                "0863757272656e6379",
+                "0d00000003455552",
+                "0472617465",
+                "0100000000000000000000000000000002",
+            )
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! [NEXUS-STX-01] Minimal Stacks transaction support: c32check addresses, Clarity value
+//! serialization, single-sig contract-call signing, and broadcasting to a Stacks node.
+//!
+//! Only what Nexus itself submits on-chain is covered; everything else is read through
+//! the node's RPC API.
+
+pub mod address;
+pub mod broadcaster;
+pub mod clarity;
+pub mod rpc;
+pub mod sip018;
+pub mod transaction;
+
+pub use address::{ContractId, StacksAddress};
+pub use broadcaster::{BroadcastError, StacksBroadcaster};
+pub use clarity::ClarityValue;
+pub use rpc::RpcEndpoints;
+pub use transaction::{ContractCall, SignedTransaction, StacksSigner};
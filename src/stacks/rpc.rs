@@ -0,0 +1,528 @@
+//! Failover across several Stacks API endpoints (`STACKS_NODE_RPC_URL` as a
+//! comma-separated list). Reads go to the preferred endpoint first; one that fails is
+//! marked unhealthy for a cooldown and the next endpoint becomes preferred, so a single
+//! node going down does not stall sync or the safety monitor. Unhealthy endpoints are
+//! still tried, last, when every healthy one has failed.
+//!
+//! An endpoint answering 429 (the public Hiro API rate-limits) is not called again
+//! until its `Retry-After` has passed: the other endpoints are tried first, and when
+//! only throttled ones are left the call waits out the earliest of them.
+//!
+//! A 404 is not a failure of the endpoint: a node that lags the others answers 404 for
+//! a block it has not seen yet, so the call moves on to the next endpoint without
+//! marking that one unhealthy.
+
+use prometheus::{
+    opts, register_int_counter_vec, register_int_gauge_vec, IntCounterVec, IntGaugeVec,
+};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a failed endpoint is skipped before it is tried ahead of the others again.
+pub const DEFAULT_UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Back-off after a 429 without a usable `Retry-After`.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Longest `Retry-After` honoured, so a bogus header cannot park sync for hours.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+lazy_static::lazy_static! {
+    static ref ENDPOINT_HEALTHY: IntGaugeVec = register_int_gauge_vec!(
+        opts!(
+            "nexus_stacks_rpc_endpoint_healthy",
+            "Whether a Stacks RPC endpoint answered its last call (1) or is cooling down (0)"
+        ),
+        &["endpoint"]
+    )
+    .unwrap();
+    static ref ENDPOINT_RATE_LIMITED: IntGaugeVec = register_int_gauge_vec!(
+        opts!(
+            "nexus_stacks_rpc_rate_limited",
+            "Whether a Stacks RPC endpoint is backing off after a 429 (1) or not (0)"
+        ),
+        &["endpoint"]
+    )
+    .unwrap();
+    static ref RATE_LIMITED_TOTAL: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "nexus_stacks_rpc_rate_limited_total",
+            "429 responses from a Stacks RPC endpoint"
+        ),
+        &["endpoint"]
+    )
+    .unwrap();
+}
+
+/// A 429 from a Stacks RPC endpoint, with how long it asked to be left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rate limited; retry after {}s",
+            self.retry_after.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// A 404 from a Stacks RPC endpoint: the resource is missing there, which on a lagging
+/// node says nothing about its health.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotFound {
+    pub url: String,
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stacks API returned 404 Not Found for {}", self.url)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// Parses `Retry-After` as delay seconds or an HTTP date, capped at [`MAX_RETRY_AFTER`].
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default()
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+/// GETs `url` and decodes its JSON body. A 429 fails with [`RateLimited`] so
+/// [`RpcEndpoints::call`] backs off that endpoint, and a 404 with [`NotFound`] so it
+/// keeps it healthy; any other non-success status is a plain error.
+pub async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+) -> anyhow::Result<T> {
+    let resp = client.get(url).send().await?;
+    let status = resp.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after)
+            .unwrap_or(DEFAULT_RETRY_AFTER);
+        return Err(RateLimited { retry_after }.into());
+    }
+    if status == StatusCode::NOT_FOUND {
+        return Err(NotFound {
+            url: url.to_string(),
+        }
+        .into());
+    }
+    if !status.is_success() {
+        anyhow::bail!("Stacks API returned {} for {}", status, url);
+    }
+    Ok(resp.json().await?)
+}
+
+/// Point-in-time view of one endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    /// Tried first on the next call.
+    pub preferred: bool,
+    /// Served the most recent successful call.
+    pub last_served: bool,
+    /// Seconds left of a 429 back-off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limited_for_secs: Option<u64>,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    preferred: usize,
+    unhealthy_until: Vec<Option<Instant>>,
+    rate_limited_until: Vec<Option<Instant>>,
+    last_served: Option<usize>,
+}
+
+static SHARED: OnceLock<Arc<RpcEndpoints>> = OnceLock::new();
+
+/// Registers the pool the node's services share, so request handlers call through it
+/// and `/v1/status` reports its health. Only the first registration takes effect.
+pub fn set_shared(endpoints: Arc<RpcEndpoints>) {
+    let _ = SHARED.set(endpoints);
+}
+
+/// The pool registered with [`set_shared`], if any.
+pub fn shared() -> Option<Arc<RpcEndpoints>> {
+    SHARED.get().cloned()
+}
+
+#[derive(Debug)]
+pub struct RpcEndpoints {
+    urls: Vec<String>,
+    cooldown: Duration,
+    state: Mutex<PoolState>,
+}
+
+impl RpcEndpoints {
+    /// Endpoints in order of preference; trailing slashes are dropped.
+    ///
+    /// # Panics
+    ///
+    /// When `urls` is empty.
+    pub fn new(urls: impl IntoIterator<Item = String>) -> Self {
+        let urls: Vec<String> = urls
+            .into_iter()
+            .map(|url| url.trim_end_matches('/').to_string())
+            .collect();
+        assert!(
+            !urls.is_empty(),
+            "at least one Stacks RPC endpoint is required"
+        );
+        for url in &urls {
+            ENDPOINT_HEALTHY.with_label_values(&[url]).set(1);
+            ENDPOINT_RATE_LIMITED.with_label_values(&[url]).set(0);
+        }
+        Self {
+            state: Mutex::new(PoolState {
+                preferred: 0,
+                unhealthy_until: vec![None; urls.len()],
+                rate_limited_until: vec![None; urls.len()],
+                last_served: None,
+            }),
+            urls,
+            cooldown: DEFAULT_UNHEALTHY_COOLDOWN,
+        }
+    }
+
+    pub fn single(url: impl Into<String>) -> Self {
+        Self::new([url.into()])
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The first configured endpoint.
+    pub fn primary(&self) -> &str {
+        &self.urls[0]
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// The endpoint that served the most recent successful call.
+    pub fn last_served(&self) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.last_served.map(|i| self.urls[i].clone())
+    }
+
+    pub fn statuses(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        self.urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| EndpointStatus {
+                url: url.clone(),
+                healthy: !is_cooling(state.unhealthy_until[i], now),
+                preferred: i == state.preferred,
+                last_served: state.last_served == Some(i),
+                rate_limited_for_secs: state.rate_limited_until[i]
+                    .filter(|&until| until > now)
+                    .map(|until| (until - now).as_secs()),
+            })
+            .collect()
+    }
+
+    /// Healthy endpoints in rotation order from the preferred one, then the unhealthy
+    /// ones in the same order, then the rate-limited ones by when they may be called
+    /// again.
+    fn attempt_order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        let rotation = (0..self.urls.len()).map(|i| (state.preferred + i) % self.urls.len());
+        let (mut throttled, rotation): (Vec<usize>, Vec<usize>) =
+            rotation.partition(|&i| is_cooling(state.rate_limited_until[i], now));
+        let (healthy, cooling): (Vec<usize>, Vec<usize>) = rotation
+            .into_iter()
+            .partition(|&i| !is_cooling(state.unhealthy_until[i], now));
+        throttled.sort_by_key(|&i| state.rate_limited_until[i]);
+        healthy
+            .into_iter()
+            .chain(cooling)
+            .chain(throttled)
+            .collect()
+    }
+
+    /// When a rate-limited endpoint may be called again.
+    fn rate_limited_until(&self, index: usize) -> Option<Instant> {
+        let state = self.state.lock().unwrap();
+        state.rate_limited_until[index].filter(|&until| until > Instant::now())
+    }
+
+    fn record_success(&self, index: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.unhealthy_until[index] = None;
+        state.rate_limited_until[index] = None;
+        state.last_served = Some(index);
+        state.preferred = index;
+        ENDPOINT_HEALTHY
+            .with_label_values(&[&self.urls[index]])
+            .set(1);
+        ENDPOINT_RATE_LIMITED
+            .with_label_values(&[&self.urls[index]])
+            .set(0);
+    }
+
+    fn record_failure(&self, index: usize, error: &anyhow::Error) {
+        // The endpoint answered; the next one may simply be further ahead.
+        if error.downcast_ref::<NotFound>().is_some() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.preferred == index {
+            state.preferred = (index + 1) % self.urls.len();
+        }
+        // A throttled endpoint is up; it only wants fewer requests.
+        if let Some(limited) = error.downcast_ref::<RateLimited>() {
+            state.rate_limited_until[index] = Some(Instant::now() + limited.retry_after);
+            ENDPOINT_RATE_LIMITED
+                .with_label_values(&[&self.urls[index]])
+                .set(1);
+            RATE_LIMITED_TOTAL
+                .with_label_values(&[&self.urls[index]])
+                .inc();
+            tracing::warn!(
+                endpoint = %self.urls[index],
+                retry_after_secs = limited.retry_after.as_secs(),
+                "Stacks RPC endpoint is rate limiting; backing off"
+            );
+            return;
+        }
+        state.unhealthy_until[index] = Some(Instant::now() + self.cooldown);
+        ENDPOINT_HEALTHY
+            .with_label_values(&[&self.urls[index]])
+            .set(0);
+        if self.urls.len() > 1 {
+            tracing::warn!(
+                endpoint = %self.urls[index],
+                error = %error,
+                "Stacks RPC endpoint failed; rotating to the next one"
+            );
+        }
+    }
+
+    /// Runs `call` against each endpoint's base URL until one succeeds, returning the
+    /// last error when all fail. A rate-limited endpoint is only called once its
+    /// back-off has passed, waiting for it when nothing else is left.
+    pub async fn call<T, F, Fut>(&self, mut call: F) -> anyhow::Result<T>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut last_error = None;
+        for index in self.attempt_order() {
+            if let Some(until) = self.rate_limited_until(index) {
+                tokio::time::sleep_until(until.into()).await;
+            }
+            match call(self.urls[index].clone()).await {
+                Ok(value) => {
+                    self.record_success(index);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    self.record_failure(index, &e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        let error = last_error.expect("at least one endpoint was tried");
+        if self.urls.len() > 1 {
+            Err(error.context(format!(
+                "All {} Stacks RPC endpoints failed",
+                self.urls.len()
+            )))
+        } else {
+            Err(error)
+        }
+    }
+}
+
+fn is_cooling(until: Option<Instant>, now: Instant) -> bool {
+    until.is_some_and(|until| until > now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_endpoint_rotates_to_the_next() {
+        let pool = RpcEndpoints::new([
+            "http://primary/".to_string(),
+            "http://secondary".to_string(),
+        ]);
+        assert_eq!(pool.primary(), "http://primary");
+
+        let served = pool
+            .call(|base| async move {
+                if base == "http://primary" {
+                    anyhow::bail!("connection refused")
+                }
+                Ok(base)
+            })
+            .await
+            .unwrap();
+        assert_eq!(served, "http://secondary");
+        assert_eq!(pool.last_served().as_deref(), Some("http://secondary"));
+
+        let statuses = pool.statuses();
+        assert!(!statuses[0].healthy);
+        assert!(statuses[1].healthy && statuses[1].preferred && statuses[1].last_served);
+
+        // The cooling primary is only tried after the healthy secondary.
+        let mut tried = Vec::new();
+        pool.call(|base| {
+            tried.push(base.clone());
+            async move { Ok(base) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(tried, vec!["http://secondary".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_endpoints_are_a_last_resort() {
+        let pool = RpcEndpoints::new(["http://a".to_string(), "http://b".to_string()])
+            .with_cooldown(Duration::from_secs(60));
+        let err = pool
+            .call(|_| async { Err::<(), _>(anyhow::anyhow!("down")) })
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("All 2 Stacks RPC endpoints failed"));
+        assert!(pool.statuses().iter().all(|s| !s.healthy));
+
+        // Both are cooling, but still tried; the first to answer becomes healthy again.
+        let served = pool.call(|base| async move { Ok(base) }).await.unwrap();
+        assert_eq!(pool.last_served(), Some(served));
+        assert_eq!(pool.statuses().iter().filter(|s| s.healthy).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_not_found_keeps_the_endpoint_healthy() {
+        let pool = RpcEndpoints::new(["http://lagging".to_string(), "http://ahead".to_string()]);
+        let served = pool
+            .call(|base| async move {
+                if base == "http://lagging" {
+                    return Err(NotFound {
+                        url: format!("{}/extended/v1/block/by_height/9", base),
+                    }
+                    .into());
+                }
+                Ok(base)
+            })
+            .await
+            .unwrap();
+        assert_eq!(served, "http://ahead");
+        assert!(pool.statuses().iter().all(|s| s.healthy));
+
+        // Missing everywhere: the 404 comes back as such, and nothing is marked down.
+        let err = pool
+            .call(|base| async move { Err::<(), _>(NotFound { url: base }.into()) })
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<NotFound>().is_some());
+        assert!(pool.statuses().iter().all(|s| s.healthy));
+    }
+
+    #[test]
+    fn test_retry_after_parsing() {
+        assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after("86400"), Some(MAX_RETRY_AFTER));
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(Duration::ZERO)
+        );
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let parsed = parse_retry_after(&soon).unwrap();
+        assert!(parsed > Duration::from_secs(25) && parsed <= Duration::from_secs(30));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_429_backs_off_for_retry_after() {
+        use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/height",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "1")]).into_response()
+                    } else {
+                        Json(serde_json::json!({"height": 42})).into_response()
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = reqwest::Client::new();
+        let pool = RpcEndpoints::single(base.clone());
+        let fetch = |base: String| {
+            let client = &client;
+            async move {
+                let json: serde_json::Value = get_json(client, &format!("{}/height", base)).await?;
+                Ok(json["height"].as_u64().unwrap())
+            }
+        };
+
+        let err = pool.call(fetch).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RateLimited>(),
+            Some(&RateLimited {
+                retry_after: Duration::from_secs(1)
+            })
+        );
+        let status = &pool.statuses()[0];
+        assert!(status.healthy);
+        assert!(status.rate_limited_for_secs.is_some());
+        assert_eq!(ENDPOINT_RATE_LIMITED.with_label_values(&[&base]).get(), 1);
+
+        // The next call waits out the back-off instead of hitting the endpoint again.
+        let started = Instant::now();
+        assert_eq!(pool.call(fetch).await.unwrap(), 42);
+        assert!(started.elapsed() >= Duration::from_millis(900));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.statuses()[0].rate_limited_for_secs, None);
+        assert_eq!(ENDPOINT_RATE_LIMITED.with_label_values(&[&base]).get(), 0);
+    }
+}
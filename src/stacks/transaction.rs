@@ -0,0 +1,293 @@
+//! Single-sig (P2PKH) contract-call transactions, signed per SIP-005.
+
+use crate::stacks::address::{ContractId, StacksAddress};
+use crate::stacks::clarity::ClarityValue;
+use crate::stacks::sip018;
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use sha2::{Digest, Sha512_256};
+use std::fmt;
+
+pub const TX_VERSION_MAINNET: u8 = 0x00;
+pub const TX_VERSION_TESTNET: u8 = 0x80;
+pub const CHAIN_ID_MAINNET: u32 = 0x0000_0001;
+pub const CHAIN_ID_TESTNET: u32 = 0x8000_0000;
+
+const AUTH_STANDARD: u8 = 0x04;
+const HASH_MODE_P2PKH: u8 = 0x00;
+const KEY_ENCODING_COMPRESSED: u8 = 0x00;
+const ANCHOR_MODE_ANY: u8 = 0x03;
+const POST_CONDITION_MODE_DENY: u8 = 0x02;
+const PAYLOAD_CONTRACT_CALL: u8 = 0x02;
+const MAX_NAME_LEN: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractCall {
+    pub contract: ContractId,
+    pub function_name: String,
+    pub args: Vec<ClarityValue>,
+}
+
+impl ContractCall {
+    fn serialize_payload(&self, out: &mut Vec<u8>) -> anyhow::Result<()> {
+        if self.function_name.is_empty() || self.function_name.len() > MAX_NAME_LEN {
+            anyhow::bail!("Invalid function name '{}'", self.function_name);
+        }
+        out.push(PAYLOAD_CONTRACT_CALL);
+        out.push(self.contract.address.version);
+        out.extend_from_slice(&self.contract.address.hash160);
+        out.push(self.contract.name.len() as u8);
+        out.extend_from_slice(self.contract.name.as_bytes());
+        out.push(self.function_name.len() as u8);
+        out.extend_from_slice(self.function_name.as_bytes());
+        out.extend_from_slice(&(self.args.len() as u32).to_be_bytes());
+        for arg in &self.args {
+            arg.serialize_into(out);
+        }
+        Ok(())
+    }
+}
+
+/// A signed transaction ready for `POST /v2/transactions`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedTransaction {
+    /// Hex `SHA512/256` of [`Self::bytes`], without `0x`.
+    pub txid: String,
+    pub bytes: Vec<u8>,
+    pub sender: StacksAddress,
+    pub nonce: u64,
+    pub fee: u64,
+}
+
+/// secp256k1 key that signs transactions as a single-sig Stacks account.
+#[derive(Clone)]
+pub struct StacksSigner {
+    key: SigningKey,
+}
+
+impl fmt::Debug for StacksSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StacksSigner")
+            .field("public_key", &self.public_key_hex())
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl StacksSigner {
+    pub fn new(key: SigningKey) -> Self {
+        Self { key }
+    }
+
+    /// Accepts a 32-byte hex secret, or the 33-byte form with the `01` compression
+    /// suffix that Stacks wallets export.
+    pub fn from_hex(secret: &str) -> anyhow::Result<Self> {
+        let mut bytes = hex::decode(secret.trim().trim_start_matches("0x"))
+            .map_err(|_| anyhow::anyhow!("Private key is not valid hex"))?;
+        if bytes.len() == 33 && bytes[32] == 0x01 {
+            bytes.truncate(32);
+        }
+        let key = SigningKey::from_slice(&bytes)
+            .map_err(|_| anyhow::anyhow!("Private key is not a valid secp256k1 secret"))?;
+        Ok(Self::new(key))
+    }
+
+    /// Compressed SEC1 public key (hex).
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.key.verifying_key().to_sec1_bytes())
+    }
+
+    pub fn address(&self, mainnet: bool) -> StacksAddress {
+        StacksAddress::p2pkh(mainnet, self.key.verifying_key())
+    }
+
+    /// ECDSA signature over SHA-256 of `message`, as 64-byte `r || s` hex; verifies with
+    /// [`Self::public_key_hex`].
+    pub fn sign_message(&self, message: &[u8]) -> String {
+        let signature: Signature = self.key.sign(message);
+        hex::encode(signature.to_bytes())
+    }
+
+    /// SIP-018 signature over `message` under `domain`, as 65-byte `r || s || recovery
+    /// id` hex: the layout wallets produce and `secp256k1-recover?` accepts.
+    pub fn sign_structured_data(
+        &self,
+        domain: &ClarityValue,
+        message: &ClarityValue,
+    ) -> anyhow::Result<String> {
+        let digest = sip018::structured_data_hash(domain, message);
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(&digest)
+            .map_err(|e| anyhow::anyhow!("Structured data signing failed: {}", e))?;
+        let mut sig = [0u8; 65];
+        sig[..64].copy_from_slice(&signature.to_bytes());
+        sig[64] = recovery_id.to_byte();
+        Ok(hex::encode(sig))
+    }
+
+    /// Signs `call` on the network of the target contract's address.
+    pub fn sign_contract_call(
+        &self,
+        call: &ContractCall,
+        nonce: u64,
+        fee: u64,
+    ) -> anyhow::Result<SignedTransaction> {
+        let sender = self.address(call.contract.address.is_mainnet());
+        let unsigned = serialize_tx(call, &sender, 0, 0, &[0u8; 65])?;
+        let presign = presign_sighash(&Sha512_256::digest(&unsigned).into(), fee, nonce);
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(&presign)
+            .map_err(|e| anyhow::anyhow!("Transaction signing failed: {}", e))?;
+        let mut sig = [0u8; 65];
+        sig[0] = recovery_id.to_byte();
+        sig[1..].copy_from_slice(&signature.to_bytes());
+
+        let bytes = serialize_tx(call, &sender, nonce, fee, &sig)?;
+        Ok(SignedTransaction {
+            txid: hex::encode(Sha512_256::digest(&bytes)),
+            bytes,
+            sender,
+            nonce,
+            fee,
+        })
+    }
+}
+
+/// `SHA512/256(initial_sighash || auth_type || fee || nonce)`.
+fn presign_sighash(initial: &[u8; 32], fee: u64, nonce: u64) -> [u8; 32] {
+    let mut hasher = Sha512_256::new();
+    hasher.update(initial);
+    hasher.update([AUTH_STANDARD]);
+    hasher.update(fee.to_be_bytes());
+    hasher.update(nonce.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn serialize_tx(
+    call: &ContractCall,
+    sender: &StacksAddress,
+    nonce: u64,
+    fee: u64,
+    signature: &[u8; 65],
+) -> anyhow::Result<Vec<u8>> {
+    let mainnet = call.contract.address.is_mainnet();
+    let mut out = Vec::with_capacity(256);
+    out.push(if mainnet {
+        TX_VERSION_MAINNET
+    } else {
+        TX_VERSION_TESTNET
+    });
+    out.extend_from_slice(
+        &(if mainnet {
+            CHAIN_ID_MAINNET
+        } else {
+            CHAIN_ID_TESTNET
+        })
+        .to_be_bytes(),
+    );
+    out.push(AUTH_STANDARD);
+    out.push(HASH_MODE_P2PKH);
+    out.extend_from_slice(&sender.hash160);
+    out.extend_from_slice(&nonce.to_be_bytes());
+    out.extend_from_slice(&fee.to_be_bytes());
+    out.push(KEY_ENCODING_COMPRESSED);
+    out.extend_from_slice(signature);
+    out.push(ANCHOR_MODE_ANY);
+    out.push(POST_CONDITION_MODE_DENY);
+    out.extend_from_slice(&0u32.to_be_bytes());
+    call.serialize_payload(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+    fn call(contract: &str) -> ContractCall {
+        ContractCall {
+            contract: contract.parse().unwrap(),
+            function_name: "update-fx-rates".to_string(),
+            args: vec![ClarityValue::UInt(42)],
+        }
+    }
+
+    #[test]
+    fn test_signed_contract_call_layout_and_signature() {
+        let signer = StacksSigner::new(SigningKey::from_slice(&[7u8; 32]).unwrap());
+        let call = call("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.fx-oracle");
+        let tx = signer.sign_contract_call(&call, 5, 2_000).unwrap();
+        let b = &tx.bytes;
+
+        assert_eq!(b[0], TX_VERSION_MAINNET);
+        assert_eq!(b[1..5], CHAIN_ID_MAINNET.to_be_bytes());
+        assert_eq!(b[5], AUTH_STANDARD);
+        assert_eq!(b[6], HASH_MODE_P2PKH);
+        assert_eq!(b[7..27], tx.sender.hash160);
+        assert_eq!(b[27..35], 5u64.to_be_bytes());
+        assert_eq!(b[35..43], 2_000u64.to_be_bytes());
+        assert_eq!(b[43], KEY_ENCODING_COMPRESSED);
+        assert_eq!(
+            b[109..115],
+            [ANCHOR_MODE_ANY, POST_CONDITION_MODE_DENY, 0, 0, 0, 0]
+        );
+        assert_eq!(b[115], PAYLOAD_CONTRACT_CALL);
+        assert_eq!(b[116], call.contract.address.version);
+        assert!(b.ends_with(&ClarityValue::UInt(42).serialize()));
+        assert_eq!(tx.txid, hex::encode(Sha512_256::digest(b)));
+        assert_eq!(tx.sender, signer.address(true));
+
+        let unsigned = serialize_tx(&call, &tx.sender, 0, 0, &[0u8; 65]).unwrap();
+        let presign = presign_sighash(&Sha512_256::digest(&unsigned).into(), 2_000, 5);
+        let recovery_id = RecoveryId::from_byte(b[44]).unwrap();
+        let signature = Signature::from_slice(&b[45..109]).unwrap();
+        let recovered =
+            VerifyingKey::recover_from_prehash(&presign, &signature, recovery_id).unwrap();
+        assert_eq!(&recovered, signer.key.verifying_key());
+    }
+
+    #[test]
+    fn test_structured_data_signature_recovers_to_signer() {
+        let signer = StacksSigner::new(SigningKey::from_slice(&[7u8; 32]).unwrap());
+        let domain = sip018::domain("Test App", "1.0.0", CHAIN_ID_MAINNET);
+        let message = ClarityValue::StringAscii("Hello World".to_string());
+        let sig = hex::decode(signer.sign_structured_data(&domain, &message).unwrap()).unwrap();
+        assert_eq!(sig.len(), 65);
+
+        let digest = sip018::structured_data_hash(&domain, &message);
+        let recovered = VerifyingKey::recover_from_prehash(
+            &digest,
+            &Signature::from_slice(&sig[..64]).unwrap(),
+            RecoveryId::from_byte(sig[64]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(&recovered, signer.key.verifying_key());
+    }
+
+    #[test]
+    fn test_testnet_contract_selects_testnet_network() {
+        let signer = StacksSigner::new(SigningKey::from_slice(&[7u8; 32]).unwrap());
+        let tx = signer
+            .sign_contract_call(
+                &call("ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ.fx-oracle"),
+                0,
+                1,
+            )
+            .unwrap();
+        assert_eq!(tx.bytes[0], TX_VERSION_TESTNET);
+        assert_eq!(tx.bytes[1..5], CHAIN_ID_TESTNET.to_be_bytes());
+        assert!(tx.sender.to_string().starts_with("ST"));
+    }
+
+    #[test]
+    fn test_from_hex_accepts_compressed_suffix_and_redacts() {
+        let secret = hex::encode([7u8; 32]);
+        let plain = StacksSigner::from_hex(&secret).unwrap();
+        let suffixed = StacksSigner::from_hex(&format!("{}01", secret)).unwrap();
+        assert_eq!(plain.public_key_hex(), suffixed.public_key_hex());
+        assert!(StacksSigner::from_hex("zz").is_err());
+        assert!(StacksSigner::from_hex(&hex::encode([0u8; 32])).is_err());
+        assert!(!format!("{:?}", plain).contains(&secret));
+    }
+}
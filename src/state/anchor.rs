@@ -0,0 +1,610 @@
+//! Anchors the state root on Stacks L1. The anchoring service signs a contract call
+//! carrying the current root, processed height and leaf count every N burn blocks (or
+//! on a timer) and broadcasts it, so every served root has an on-chain commitment it can
+//! be disputed against. Every attempt is recorded in the `anchors` table; sync marks an
+//! anchor confirmed once its call succeeds in an ingested block, failed when the call
+//! aborted, and back to broadcast when a reorg drops the confirming block. The latest
+//! confirmed anchor is reported by `/v1/status`, the history by `GET /v1/anchors`.
+
+use super::{NexusState, RootCommitment};
+use crate::config::{Config, ENV_ANCHOR_CONTRACT_PRINCIPAL};
+use crate::stacks::{
+    ClarityValue, ContractCall, ContractId, RpcEndpoints, StacksBroadcaster, StacksSigner,
+};
+use crate::storage::store::NexusStore;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_ANCHOR_FUNCTION: &str = "anchor-root";
+pub const DEFAULT_ANCHOR_INTERVAL_SECS: u64 = 600;
+pub const DEFAULT_ANCHOR_FEE_USTX: u64 = 10_000;
+/// Default and maximum anchors per `GET /v1/anchors` call.
+pub const DEFAULT_ANCHORS_LIMIT: u32 = 20;
+pub const MAX_ANCHORS_LIMIT: u32 = 100;
+/// How often the service checks whether an anchor is due.
+const ANCHOR_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Wait after the first failed broadcast; it doubles with each further failure, up to
+/// the anchoring interval.
+const ANCHOR_RETRY_BACKOFF: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorStatus {
+    /// Accepted by the node; not yet seen in an ingested block.
+    Broadcast,
+    /// The call succeeded in an ingested block.
+    Confirmed,
+    /// The node could not be reached or refused the call, or the call aborted on-chain;
+    /// retried after a back-off.
+    Failed,
+}
+
+impl AnchorStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Broadcast => "broadcast",
+            Self::Confirmed => "confirmed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for AnchorStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "broadcast" => Ok(Self::Broadcast),
+            "confirmed" => Ok(Self::Confirmed),
+            "failed" => Ok(Self::Failed),
+            other => anyhow::bail!("Unknown anchor status '{}'", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateAnchor {
+    pub root: String,
+    /// Sorted-leaf root anchored with `root`, which non-inclusion proofs verify
+    /// against; `None` on anchors recorded before it was carried.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sorted_root: Option<String>,
+    /// Processed height the root covers.
+    pub height: u64,
+    /// Leaves the root commits to.
+    pub leaf_count: u64,
+    /// Txid of the anchor contract call, `0x`-prefixed.
+    pub tx_id: String,
+    /// Unix seconds at which the call was broadcast.
+    pub anchored_at: i64,
+    /// Burn chain height when the call was broadcast; `None` on anchors recorded before
+    /// it was kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burn_height: Option<u64>,
+    pub status: AnchorStatus,
+    /// Height of the ingested block that included the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmed_height: Option<u64>,
+    /// Why the broadcast failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Contract-call arguments: the root as a 32-byte buffer, the height and leaf count,
+/// then the sorted-leaf root as a 32-byte buffer.
+pub fn anchor_args(commitment: &RootCommitment, height: u64) -> anyhow::Result<Vec<ClarityValue>> {
+    let buffer = |root: &str| {
+        hex::decode(root.trim_start_matches("0x"))
+            .ok()
+            .filter(|b| b.len() == 32)
+            .with_context(|| format!("State root {} is not 32 hex bytes", root))
+    };
+    Ok(vec![
+        ClarityValue::Buffer(buffer(&commitment.root)?),
+        ClarityValue::UInt(height as u128),
+        ClarityValue::UInt(commitment.leaf_count as u128),
+        ClarityValue::Buffer(buffer(&commitment.sorted_root)?),
+    ])
+}
+
+/// Whether `root` should be anchored at `now`, with the burn chain at `burn_height`:
+/// never when it is already the last anchored root, otherwise when nothing was anchored
+/// yet or, with a non-zero `every_burn_blocks`, that many burn blocks passed since the
+/// last anchor; with zero, `interval_secs` passed since it.
+pub fn anchor_due(
+    last: Option<&StateAnchor>,
+    root: &str,
+    burn_height: Option<u64>,
+    now: i64,
+    interval_secs: u64,
+    every_burn_blocks: u64,
+) -> bool {
+    let Some(last) = last else {
+        return true;
+    };
+    if last.root == root {
+        return false;
+    }
+    if every_burn_blocks > 0 {
+        return match (burn_height, last.burn_height) {
+            (Some(height), Some(anchored)) => height >= anchored.saturating_add(every_burn_blocks),
+            // The last anchor predates burn heights being kept.
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+    }
+    let elapsed = now.saturating_sub(last.anchored_at).max(0) as u64;
+    elapsed >= interval_secs
+}
+
+/// Signs and broadcasts state-root anchors to the anchor contract.
+pub struct AnchorService {
+    store: Arc<dyn NexusStore>,
+    state: Arc<NexusState>,
+    broadcaster: StacksBroadcaster,
+    signer: StacksSigner,
+    contract: ContractId,
+    function_name: String,
+    fee_ustx: u64,
+    interval: Duration,
+    every_burn_blocks: u64,
+    retry_backoff: Duration,
+    /// Consecutive failed broadcasts and when the next attempt may go out.
+    retry: Mutex<Option<(u32, Instant)>>,
+}
+
+impl AnchorService {
+    pub fn new(
+        store: Arc<dyn NexusStore>,
+        state: Arc<NexusState>,
+        broadcaster: StacksBroadcaster,
+        signer: StacksSigner,
+        contract: ContractId,
+    ) -> Self {
+        Self {
+            store,
+            state,
+            broadcaster,
+            signer,
+            contract,
+            function_name: DEFAULT_ANCHOR_FUNCTION.to_string(),
+            fee_ustx: DEFAULT_ANCHOR_FEE_USTX,
+            interval: Duration::from_secs(DEFAULT_ANCHOR_INTERVAL_SECS),
+            every_burn_blocks: 0,
+            retry_backoff: ANCHOR_RETRY_BACKOFF,
+            retry: Mutex::new(None),
+        }
+    }
+
+    /// `None` when no anchor key is configured, i.e. anchoring is disabled. Broadcasts
+    /// go through `rpc`, the endpoints sync reads from.
+    pub fn from_config(
+        config: &Config,
+        store: Arc<dyn NexusStore>,
+        state: Arc<NexusState>,
+        rpc: Arc<RpcEndpoints>,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(secret) = config.anchor_private_key.as_deref() else {
+            return Ok(None);
+        };
+        let signer = StacksSigner::from_hex(secret)?;
+        let contract: ContractId = config
+            .anchor_contract_principal
+            .as_deref()
+            .with_context(|| format!("State anchoring requires {ENV_ANCHOR_CONTRACT_PRINCIPAL}"))?
+            .parse()
+            .with_context(|| format!("Invalid {ENV_ANCHOR_CONTRACT_PRINCIPAL}"))?;
+        Ok(Some(
+            Self::new(
+                store,
+                state,
+                StacksBroadcaster::with_endpoints(rpc),
+                signer,
+                contract,
+            )
+            .with_function(config.anchor_contract_function.clone())
+            .with_fee(config.anchor_fee_ustx)
+            .with_schedule(
+                Duration::from_secs(config.anchor_interval_secs),
+                config.anchor_every_blocks,
+            ),
+        ))
+    }
+
+    pub fn with_function(mut self, function_name: String) -> Self {
+        self.function_name = function_name;
+        self
+    }
+
+    pub fn with_fee(mut self, fee_ustx: u64) -> Self {
+        self.fee_ustx = fee_ustx;
+        self
+    }
+
+    /// Re-anchors a changed root every `every_burn_blocks` burn blocks or, when that is
+    /// zero, once `interval` has passed since the last anchor.
+    pub fn with_schedule(mut self, interval: Duration, every_burn_blocks: u64) -> Self {
+        self.interval = interval;
+        self.every_burn_blocks = every_burn_blocks;
+        self
+    }
+
+    /// Wait after the first failed broadcast; it doubles with each further failure, up
+    /// to the anchoring interval.
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    pub fn contract(&self) -> &ContractId {
+        &self.contract
+    }
+
+    /// Anchors the current root if one is due, returning the recorded anchor. A failed
+    /// broadcast is recorded as [`AnchorStatus::Failed`] and returned as an error; failed
+    /// attempts do not count as the last anchor, so the same root goes out again once
+    /// the back-off has passed.
+    pub async fn anchor_once(&self) -> anyhow::Result<Option<StateAnchor>> {
+        if self
+            .retry
+            .lock()
+            .unwrap()
+            .is_some_and(|(_, next)| Instant::now() < next)
+        {
+            return Ok(None);
+        }
+        // Sync commits leaves and advances the watermark under the commit lock, so the
+        // root and height read under it belong together.
+        let (commitment, height) = {
+            let _commit = self.state.lock_commits().await;
+            let height = self.store.sync_watermark().await?.unwrap_or(0);
+            (self.state.commitment(), height)
+        };
+        let last = self.store.last_anchor().await?;
+        let now = chrono::Utc::now().timestamp();
+        let burn_height = if self.every_burn_blocks > 0 {
+            Some(self.broadcaster.burn_block_height().await?)
+        } else {
+            None
+        };
+        if !anchor_due(
+            last.as_ref(),
+            &commitment.root,
+            burn_height,
+            now,
+            self.interval.as_secs(),
+            self.every_burn_blocks,
+        ) {
+            return Ok(None);
+        }
+
+        let call = ContractCall {
+            contract: self.contract.clone(),
+            function_name: self.function_name.clone(),
+            args: anchor_args(&commitment, height)?,
+        };
+        let sender = self.signer.address(self.contract.address.is_mainnet());
+        let nonce = self.broadcaster.next_nonce(&sender).await?;
+        let tx = self
+            .signer
+            .sign_contract_call(&call, nonce, self.fee_ustx)?;
+        let mut anchor = StateAnchor {
+            root: commitment.root,
+            sorted_root: Some(commitment.sorted_root),
+            height,
+            leaf_count: commitment.leaf_count,
+            tx_id: format!("0x{}", tx.txid),
+            anchored_at: now,
+            burn_height,
+            status: AnchorStatus::Broadcast,
+            confirmed_height: None,
+            error: None,
+        };
+        match self.broadcaster.broadcast(&tx).await {
+            Ok(tx_id) => anchor.tx_id = tx_id,
+            Err(e) => {
+                let backoff = self.back_off();
+                anchor.status = AnchorStatus::Failed;
+                anchor.error = Some(e.to_string());
+                if let Err(record) = self.store.record_anchor(&anchor).await {
+                    tracing::warn!(error = %record, "Failed to record failed anchor attempt");
+                }
+                anyhow::bail!(
+                    "Anchor broadcast failed; retrying in {}s: {}",
+                    backoff.as_secs(),
+                    e
+                );
+            }
+        }
+        *self.retry.lock().unwrap() = None;
+        self.store.record_anchor(&anchor).await?;
+        tracing::info!(
+            root = %anchor.root,
+            height = anchor.height,
+            leaf_count = anchor.leaf_count,
+            tx_id = %anchor.tx_id,
+            "Anchored state root on-chain"
+        );
+        Ok(Some(anchor))
+    }
+
+    /// Counts a failed broadcast and returns how long to wait before the next one.
+    fn back_off(&self) -> Duration {
+        let mut retry = self.retry.lock().unwrap();
+        let failures = retry.map_or(0, |(failures, _)| failures) + 1;
+        let backoff = self
+            .retry_backoff
+            .saturating_mul(2u32.saturating_pow(failures - 1))
+            .min(self.interval);
+        *retry = Some((failures, Instant::now() + backoff));
+        backoff
+    }
+
+    /// Runs the anchoring loop. Failed rounds are logged and retried on a later poll.
+    pub async fn run(&self) -> anyhow::Result<()> {
+        tracing::info!(
+            contract = %self.contract,
+            interval_secs = self.interval.as_secs(),
+            every_burn_blocks = self.every_burn_blocks,
+            "Starting state root anchoring"
+        );
+        let mut poll = tokio::time::interval(ANCHOR_POLL_INTERVAL.min(self.interval));
+        loop {
+            poll.tick().await;
+            if let Err(e) = self.anchor_once().await {
+                tracing::error!("State root anchoring failed: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::store::InMemoryStore;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::StatusCode,
+        response::IntoResponse,
+        routing::{get, post},
+        Json, Router,
+    };
+    use k256::ecdsa::SigningKey;
+    use sha2::Digest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    const CONTRACT: &str = "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ.nexus-anchor";
+
+    fn service_over(url: &str, store: Arc<InMemoryStore>, state: Arc<NexusState>) -> AnchorService {
+        AnchorService::new(
+            store,
+            state,
+            StacksBroadcaster::new(url),
+            StacksSigner::new(SigningKey::from_slice(&[5u8; 32]).unwrap()),
+            CONTRACT.parse().unwrap(),
+        )
+    }
+
+    type Posted = Arc<Mutex<Vec<Vec<u8>>>>;
+
+    /// Mock Stacks node: reports confirmed nonce 3, burn height 700, and captures posted
+    /// transaction bodies, failing the first `failures` broadcasts with a 500.
+    async fn mock_node(posted: Posted, failures: usize) -> String {
+        let failures = Arc::new(AtomicUsize::new(failures));
+        let app =
+            Router::new()
+                .route(
+                    "/v2/accounts/{address}",
+                    get(|| async { Json(serde_json::json!({"balance": "0x0", "nonce": 3})) }),
+                )
+                .route(
+                    "/v2/info",
+                    get(|| async { Json(serde_json::json!({"burn_block_height": 700})) }),
+                )
+                .route(
+                    "/v2/transactions",
+                    post(
+                        |State((posted, failures)): State<(Posted, Arc<AtomicUsize>)>,
+                         body: Bytes| async move {
+                            if failures
+                                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                                    n.checked_sub(1)
+                                })
+                                .is_ok()
+                            {
+                                return (StatusCode::INTERNAL_SERVER_ERROR, "mempool unavailable")
+                                    .into_response();
+                            }
+                            let txid = hex::encode(sha2::Sha512_256::digest(&body));
+                            posted.lock().unwrap().push(body.to_vec());
+                            Json(txid).into_response()
+                        },
+                    ),
+                )
+                .with_state((posted, failures));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    fn anchor(root: &str, height: u64, anchored_at: i64) -> StateAnchor {
+        StateAnchor {
+            root: root.to_string(),
+            sorted_root: None,
+            height,
+            leaf_count: 1,
+            tx_id: "0x01".to_string(),
+            anchored_at,
+            burn_height: Some(height),
+            status: AnchorStatus::Broadcast,
+            confirmed_height: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_anchor_due_every_burn_blocks_or_on_interval() {
+        // `anchor` records the burn height as `height`.
+        let last = anchor("0xaa", 100, 1_000);
+        assert!(anchor_due(None, "0xaa", None, 0, 600, 0));
+        assert!(!anchor_due(Some(&last), "0xaa", Some(500), 9_999, 600, 10));
+        // Counting burn blocks, elapsed time does not matter.
+        assert!(!anchor_due(Some(&last), "0xbb", Some(105), 9_999, 600, 10));
+        assert!(anchor_due(Some(&last), "0xbb", Some(110), 1_100, 600, 10));
+        assert!(!anchor_due(Some(&last), "0xbb", None, 9_999, 600, 10));
+        let legacy = StateAnchor {
+            burn_height: None,
+            ..last.clone()
+        };
+        assert!(anchor_due(Some(&legacy), "0xbb", Some(101), 1_100, 600, 10));
+        // Without a burn-block schedule, the interval decides.
+        assert!(!anchor_due(Some(&last), "0xbb", None, 1_100, 600, 0));
+        assert!(anchor_due(Some(&last), "0xbb", None, 1_600, 600, 0));
+    }
+
+    #[test]
+    fn test_anchor_args_encode_roots_and_height() {
+        let mut commitment = RootCommitment {
+            root: format!("0x{}", "ab".repeat(32)),
+            sorted_root: format!("0x{}", "cd".repeat(32)),
+            leaf_count: 7,
+        };
+        assert_eq!(
+            anchor_args(&commitment, 42).unwrap(),
+            vec![
+                ClarityValue::Buffer(vec![0xab; 32]),
+                ClarityValue::UInt(42),
+                ClarityValue::UInt(7),
+                ClarityValue::Buffer(vec![0xcd; 32]),
+            ]
+        );
+        commitment.sorted_root = "0xabcd".to_string();
+        assert!(anchor_args(&commitment, 42).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_anchor_once_broadcasts_and_records_anchor() {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let url = mock_node(posted.clone(), 0).await;
+        let store = Arc::new(InMemoryStore::new());
+        let state = Arc::new(NexusState::new());
+        state.update_state_batch(&["tx-1".to_string()]);
+        let service = service_over(&url, store.clone(), state.clone());
+
+        let anchored = service.anchor_once().await.unwrap().unwrap();
+        assert_eq!(anchored.root, state.get_state_root());
+        assert_eq!(anchored.leaf_count, 1);
+        assert_eq!(anchored.sorted_root, Some(state.get_sorted_root()));
+        assert_eq!(anchored.status, AnchorStatus::Broadcast);
+        assert_eq!(store.last_anchor().await.unwrap(), Some(anchored.clone()));
+        {
+            let posted = posted.lock().unwrap();
+            assert_eq!(posted.len(), 1);
+            assert_eq!(
+                anchored.tx_id,
+                format!("0x{}", hex::encode(sha2::Sha512_256::digest(&posted[0])))
+            );
+            assert_eq!(
+                posted[0][27..35],
+                3u64.to_be_bytes(),
+                "nonce read from the node"
+            );
+        }
+
+        // Unchanged root: nothing to anchor. A new root waits for the interval.
+        assert_eq!(service.anchor_once().await.unwrap(), None);
+        state.update_state_batch(&["tx-2".to_string()]);
+        assert_eq!(service.anchor_once().await.unwrap(), None);
+        assert_eq!(posted.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_anchors_every_burn_blocks_without_reusing_a_pending_nonce() {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let url = mock_node(posted.clone(), 0).await;
+        let store = Arc::new(InMemoryStore::new());
+        let state = Arc::new(NexusState::new());
+        state.update_state_batch(&["tx-1".to_string()]);
+        let service = service_over(&url, store.clone(), state.clone())
+            .with_schedule(Duration::from_secs(1), 5);
+
+        let first = service.anchor_once().await.unwrap().unwrap();
+        assert_eq!(first.burn_height, Some(700));
+        // A new root waits for 5 burn blocks, however long that takes.
+        state.update_state_batch(&["tx-2".to_string()]);
+        tokio::time::sleep(Duration::from_millis(1_100)).await;
+        assert_eq!(service.anchor_once().await.unwrap(), None);
+
+        let mut earlier = anchor("0xold", 0, 0);
+        earlier.burn_height = Some(690);
+        store.record_anchor(&earlier).await.unwrap();
+        service.anchor_once().await.unwrap().unwrap();
+        let posted = posted.lock().unwrap();
+        assert_eq!(posted.len(), 2);
+        // The node still reports confirmed nonce 3; the first anchor is pending with it.
+        assert_eq!(posted[0][27..35], 3u64.to_be_bytes());
+        assert_eq!(posted[1][27..35], 4u64.to_be_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_failed_broadcast_is_recorded_and_retried_after_back_off() {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let url = mock_node(posted.clone(), 1).await;
+        let store = Arc::new(InMemoryStore::new());
+        let state = Arc::new(NexusState::new());
+        state.update_state_batch(&["tx-1".to_string()]);
+        let service = service_over(&url, store.clone(), state.clone())
+            .with_retry_backoff(Duration::from_millis(200));
+
+        assert!(service.anchor_once().await.is_err());
+        let attempts = store.anchors(10).await.unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].status, AnchorStatus::Failed);
+        assert!(attempts[0].error.is_some());
+        assert_eq!(store.last_anchor().await.unwrap(), None);
+
+        // Nothing goes out, or is recorded, during the back-off.
+        assert_eq!(service.anchor_once().await.unwrap(), None);
+        assert_eq!(store.anchors(10).await.unwrap().len(), 1);
+
+        // The schedule is not advanced by the failure: the same root goes out after it.
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let anchored = service.anchor_once().await.unwrap().unwrap();
+        assert_eq!(anchored.root, attempts[0].root);
+        assert_eq!(posted.lock().unwrap().len(), 1);
+        let history = store.anchors(10).await.unwrap();
+        assert_eq!(
+            history.iter().map(|a| a.status).collect::<Vec<_>>(),
+            [AnchorStatus::Broadcast, AnchorStatus::Failed]
+        );
+    }
+
+    #[test]
+    fn test_from_config_requires_key_and_contract() {
+        let store: Arc<dyn NexusStore> = Arc::new(InMemoryStore::new());
+        let state = Arc::new(NexusState::new());
+        let mut config = Config::default_test();
+        let rpc = Arc::new(config.stacks_rpc_endpoints());
+        assert!(
+            AnchorService::from_config(&config, store.clone(), state.clone(), rpc.clone())
+                .unwrap()
+                .is_none()
+        );
+
+        config.anchor_private_key = Some(hex::encode([5u8; 32]));
+        assert!(
+            AnchorService::from_config(&config, store.clone(), state.clone(), rpc.clone()).is_err()
+        );
+
+        config.anchor_contract_principal = Some(CONTRACT.to_string());
+        let service = AnchorService::from_config(&config, store, state, rpc)
+            .unwrap()
+            .unwrap();
+        assert_eq!(service.contract().to_string(), CONTRACT);
+    }
+}
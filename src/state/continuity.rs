@@ -0,0 +1,237 @@
+//! Verifies that a chain of state roots descends from an anchored checkpoint. Leaves
+//! are only ever appended, so every root sync produced commits to a prefix of the
+//! current leaf set, and `state_root_history` records how long that prefix is. A root
+//! extends an earlier one exactly when both rebuild from prefixes of the same leaves and
+//! its prefix is at least as long, so continuity is checked by rebuilding each root from
+//! its recorded leaf count, starting with the checkpoint's own. Each claimed height must
+//! be the one sync recorded for its root.
+
+use super::anchor::StateAnchor;
+use super::{decode_hash, NexusState};
+use crate::storage::store::NexusStore;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Roots accepted per verification; each one rebuilds a tree.
+pub const MAX_CHAIN_ROOTS: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootAtHeight {
+    pub height: u64,
+    pub root: String,
+}
+
+/// Where and why a chain stopped descending from the checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ChainBreak {
+    /// The local leaf set does not rebuild the checkpoint root at its leaf count.
+    CheckpointMismatch,
+    /// Not after the previous root (or the checkpoint, for the first).
+    HeightNotIncreasing { index: usize },
+    /// Never produced by this node's sync.
+    UnknownRoot { index: usize },
+    /// Sync recorded the root at a different height than claimed.
+    HeightMismatch { index: usize, recorded: u64 },
+    /// Commits to fewer leaves than the previous root, so it cannot extend it.
+    LeafSetShrank {
+        index: usize,
+        leaf_count: u64,
+        previous: u64,
+    },
+    /// The recorded leaf count does not rebuild this root.
+    RootMismatch { index: usize, leaf_count: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    /// Roots, in order, shown to extend the checkpoint before any break.
+    pub verified: usize,
+    /// Leaf count behind each verified root.
+    pub leaf_counts: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<ChainBreak>,
+}
+
+fn same_root(a: &str, b: &str) -> bool {
+    matches!((decode_hash(a), decode_hash(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// [`NexusState::root_at`] on the blocking pool: it rebuilds a tree under the leaf lock.
+async fn root_at(state: &Arc<NexusState>, leaf_count: u64) -> anyhow::Result<Option<String>> {
+    let state = state.clone();
+    Ok(tokio::task::spawn_blocking(move || state.root_at(leaf_count as usize)).await?)
+}
+
+/// Checks that `chain`, in order, extends `checkpoint` one root at a time. Roots may be
+/// given in hex or base64; each height must be the one recorded for its root and
+/// increase from the checkpoint's, though the first root may be the checkpoint itself.
+/// Store errors are returned as errors, a broken chain as an invalid
+/// [`ChainVerification`].
+pub async fn verify_root_chain(
+    store: &dyn NexusStore,
+    state: &Arc<NexusState>,
+    checkpoint: &StateAnchor,
+    chain: &[RootAtHeight],
+) -> anyhow::Result<ChainVerification> {
+    let mut result = ChainVerification {
+        valid: false,
+        verified: 0,
+        leaf_counts: Vec::with_capacity(chain.len()),
+        failure: None,
+    };
+    let rebuilt = root_at(state, checkpoint.leaf_count).await?;
+    if !rebuilt.is_some_and(|root| same_root(&root, &checkpoint.root)) {
+        result.failure = Some(ChainBreak::CheckpointMismatch);
+        return Ok(result);
+    }
+
+    let (mut height, mut leaf_count) = (checkpoint.height, checkpoint.leaf_count);
+    for (index, link) in chain.iter().enumerate() {
+        // The first root may be the checkpoint itself.
+        let advances = link.height > height || (index == 0 && link.height == height);
+        let failure = if !advances {
+            Some(ChainBreak::HeightNotIncreasing { index })
+        } else {
+            match store.root_height(&link.root).await? {
+                None => Some(ChainBreak::UnknownRoot { index }),
+                Some(recorded) if recorded != link.height => {
+                    Some(ChainBreak::HeightMismatch { index, recorded })
+                }
+                Some(_) => match store.root_leaf_count(&link.root).await? {
+                    None => Some(ChainBreak::UnknownRoot { index }),
+                    Some(count) if count < leaf_count => Some(ChainBreak::LeafSetShrank {
+                        index,
+                        leaf_count: count,
+                        previous: leaf_count,
+                    }),
+                    Some(count) => {
+                        let rebuilt = root_at(state, count).await?;
+                        if rebuilt.is_some_and(|root| same_root(&root, &link.root)) {
+                            leaf_count = count;
+                            None
+                        } else {
+                            Some(ChainBreak::RootMismatch {
+                                index,
+                                leaf_count: count,
+                            })
+                        }
+                    }
+                },
+            }
+        };
+        if let Some(failure) = failure {
+            result.failure = Some(failure);
+            return Ok(result);
+        }
+        height = link.height;
+        result.verified += 1;
+        result.leaf_counts.push(leaf_count);
+    }
+    result.valid = true;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::anchor::AnchorStatus;
+    use crate::storage::store::InMemoryStore;
+
+    /// Grows the tree block by block, recording every root like sync does.
+    async fn history(store: &InMemoryStore, state: &NexusState) -> Vec<RootAtHeight> {
+        let mut roots = Vec::new();
+        for (height, block) in [(10, ["a", "b"]), (11, ["c", "d"]), (12, ["e", "f"])] {
+            state.update_state_batch(&block.map(str::to_string));
+            let commitment = state.commitment();
+            store.record_state_root(&commitment, height).await.unwrap();
+            roots.push(RootAtHeight {
+                height,
+                root: commitment.root,
+            });
+        }
+        roots
+    }
+
+    fn checkpoint(root: &RootAtHeight, leaf_count: u64) -> StateAnchor {
+        StateAnchor {
+            root: root.root.clone(),
+            sorted_root: None,
+            height: root.height,
+            leaf_count,
+            tx_id: format!("0x{}", "ab".repeat(32)),
+            anchored_at: 0,
+            burn_height: None,
+            status: AnchorStatus::Confirmed,
+            confirmed_height: Some(root.height + 1),
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_descending_from_checkpoint_verifies() {
+        let store = InMemoryStore::new();
+        let state = Arc::new(NexusState::new());
+        let roots = history(&store, &state).await;
+
+        let result = verify_root_chain(&store, &state, &checkpoint(&roots[0], 2), &roots[1..])
+            .await
+            .unwrap();
+        assert!(result.valid, "{:?}", result.failure);
+        assert_eq!(result.verified, 2);
+        assert_eq!(result.leaf_counts, [4, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_breaks_are_located() {
+        let store = InMemoryStore::new();
+        let state = Arc::new(NexusState::new());
+        let roots = history(&store, &state).await;
+        let anchored = checkpoint(&roots[0], 2);
+
+        let wrong_count = checkpoint(&roots[0], 3);
+        let result = verify_root_chain(&store, &state, &wrong_count, &roots[1..])
+            .await
+            .unwrap();
+        assert_eq!(result.failure, Some(ChainBreak::CheckpointMismatch));
+
+        let reversed = [roots[2].clone(), roots[1].clone()];
+        let result = verify_root_chain(&store, &state, &anchored, &reversed)
+            .await
+            .unwrap();
+        assert_eq!(result.verified, 1);
+        assert_eq!(
+            result.failure,
+            Some(ChainBreak::HeightNotIncreasing { index: 1 })
+        );
+
+        let forged = [
+            roots[1].clone(),
+            RootAtHeight {
+                height: 13,
+                root: format!("0x{}", "11".repeat(32)),
+            },
+        ];
+        let result = verify_root_chain(&store, &state, &anchored, &forged)
+            .await
+            .unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.failure, Some(ChainBreak::UnknownRoot { index: 1 }));
+
+        let misdated = [RootAtHeight {
+            height: 12,
+            root: roots[1].root.clone(),
+        }];
+        let result = verify_root_chain(&store, &state, &anchored, &misdated)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.failure,
+            Some(ChainBreak::HeightMismatch {
+                index: 0,
+                recorded: 11
+            })
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Timings of the state tree: how long appending a batch and building a proof take,
+//! labelled by coarse size so growth of the tree shows up as a shift between labels,
+//! the current leaf count, and how far behind the chain a new root is computed.
+
+use prometheus::{
+    histogram_opts, opts, register_histogram, register_histogram_vec, register_int_gauge,
+    Histogram, HistogramVec, IntGauge,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Sub-millisecond appends to multi-second rebuilds of a large tree.
+const DURATION_BUCKETS: &[f64] = &[
+    0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Seconds from a block's chain timestamp to its root.
+const LAG_BUCKETS: &[f64] = &[
+    1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0,
+];
+
+lazy_static::lazy_static! {
+    static ref UPDATE_DURATION: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "nexus_merkle_update_duration_seconds",
+            "Time to append a batch of leaves and recompute the root, by batch size",
+            DURATION_BUCKETS.to_vec()
+        ),
+        &["batch_size"]
+    )
+    .unwrap();
+    static ref PROOF_DURATION: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "nexus_merkle_proof_duration_seconds",
+            "Time to build an inclusion proof not served from the cache, by tree size",
+            DURATION_BUCKETS.to_vec()
+        ),
+        &["tree_size"]
+    )
+    .unwrap();
+    static ref LEAF_COUNT: IntGauge = register_int_gauge!(opts!(
+        "nexus_merkle_leaf_count",
+        "Leaves in the state tree"
+    ))
+    .unwrap();
+    static ref ROOT_UPDATE_LAG: Histogram = register_histogram!(histogram_opts!(
+        "nexus_root_update_lag_seconds",
+        "Time from a block's chain timestamp to the computation of the root including it",
+        LAG_BUCKETS.to_vec()
+    ))
+    .unwrap();
+}
+
+/// Order-of-magnitude label for a count, keeping label cardinality fixed.
+pub fn size_bucket(n: usize) -> &'static str {
+    match n {
+        0..=10 => "<=10",
+        11..=100 => "<=100",
+        101..=1_000 => "<=1k",
+        1_001..=10_000 => "<=10k",
+        10_001..=100_000 => "<=100k",
+        100_001..=1_000_000 => "<=1m",
+        _ => ">1m",
+    }
+}
+
+pub fn observe_update(batch_size: usize, leaf_count: usize, elapsed: Duration) {
+    UPDATE_DURATION
+        .with_label_values(&[size_bucket(batch_size)])
+        .observe(elapsed.as_secs_f64());
+    LEAF_COUNT.set(leaf_count as i64);
+}
+
+pub fn observe_proof(tree_size: usize, elapsed: Duration) {
+    PROOF_DURATION
+        .with_label_values(&[size_bucket(tree_size)])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Records how long after `chain_timestamp` (unix seconds) its root was computed; a
+/// timestamp ahead of the local clock counts as no lag.
+pub fn observe_root_lag(chain_timestamp: u64) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    ROOT_UPDATE_LAG.observe((now - chain_timestamp as f64).max(0.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::NexusState;
+
+    #[test]
+    fn test_batch_update_and_proof_are_timed() {
+        let samples =
+            |vec: &HistogramVec, label| vec.with_label_values(&[label]).get_sample_count();
+        let (updates, proofs) = (
+            samples(&UPDATE_DURATION, "<=100"),
+            samples(&PROOF_DURATION, "<=100"),
+        );
+
+        let state = NexusState::new();
+        let leaves: Vec<String> = (0..20).map(|i| format!("leaf-{}", i)).collect();
+        state.update_state_batch(&leaves);
+        state.generate_merkle_proof("leaf-3").unwrap();
+
+        assert!(samples(&UPDATE_DURATION, "<=100") > updates);
+        assert!(samples(&PROOF_DURATION, "<=100") > proofs);
+        assert_eq!(size_bucket(1), "<=10");
+        assert_eq!(size_bucket(1_000_001), ">1m");
+    }
+}
@@ -1,12 +1,311 @@
+pub mod anchor;
+pub mod continuity;
+pub mod metrics;
+pub mod proof_cache;
+pub mod snapshot;
+
+use crate::config::DEFAULT_PROOF_CACHE_SIZE;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use proof_cache::ProofCache;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
+use std::time::Instant;
 
+const EMPTY_ROOT: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Hex digits in a Stacks transaction id (32 bytes).
+pub const TX_ID_HEX_LEN: usize = 64;
+
+/// Canonical leaf form of a transaction id, applied before hashing and lookup so every
+/// client spelling of the same id maps to the same leaf.
+///
+/// After trimming whitespace, a 32-byte hex id with or without a `0x`/`0X` prefix, in
+/// any case, becomes `0x` followed by 64 lowercase hex digits. Anything else (wrong
+/// length, non-hex characters, application keys) is not a transaction id and is
+/// committed verbatim apart from the trim.
+pub fn canonical_leaf(raw: &str) -> Cow<'_, str> {
+    let trimmed = raw.trim();
+    let digits = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    if !is_tx_id_hex(digits) {
+        return Cow::Borrowed(trimmed);
+    }
+    if trimmed.starts_with("0x") && !digits.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Cow::Borrowed(trimmed);
+    }
+    Cow::Owned(format!("0x{}", digits.to_ascii_lowercase()))
+}
+
+/// Like [`canonical_leaf`], but rejects anything that is not a 32-byte hex id.
+pub fn parse_tx_id(raw: &str) -> anyhow::Result<String> {
+    let canonical = canonical_leaf(raw);
+    match canonical.strip_prefix("0x") {
+        Some(digits) if is_tx_id_hex(digits) => Ok(canonical.into_owned()),
+        _ => anyhow::bail!(
+            "Invalid transaction id '{}': expected {} hex digits with an optional 0x prefix",
+            raw.trim(),
+            TX_ID_HEX_LEN
+        ),
+    }
+}
+
+fn is_tx_id_hex(digits: &str) -> bool {
+    digits.len() == TX_ID_HEX_LEN && digits.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Children per internal node of the state-root tree. A wider tree gives shorter proofs
+/// (fewer levels) with more sibling hashes per level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MerkleArity {
+    #[default]
+    Binary,
+    Quaternary,
+}
+
+impl MerkleArity {
+    pub fn fanout(self) -> usize {
+        match self {
+            Self::Binary => 2,
+            Self::Quaternary => 4,
+        }
+    }
+}
+
+impl fmt::Display for MerkleArity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fanout())
+    }
+}
+
+impl FromStr for MerkleArity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "2" | "binary" => Ok(Self::Binary),
+            "4" | "quaternary" => Ok(Self::Quaternary),
+            other => anyhow::bail!("Unsupported Merkle arity '{}' (expected 2 or 4)", other),
+        }
+    }
+}
+
+/// How a leaf becomes its hash at the bottom of the state tree. Every leaf is hashed
+/// with SHA-256 by default. With `PreHashed`, a leaf that is a 32-byte hex digest in
+/// [`canonical_leaf`] form is taken as its own hash, so the tree matches one built by a
+/// system that commits its own per-transaction digests; other leaves are still hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeafHashing {
+    #[default]
+    Sha256,
+    PreHashed,
+}
+
+impl LeafHashing {
+    /// The tree hash of `leaf`, a leaf in [`canonical_leaf`] form.
+    pub fn leaf_hash(self, leaf: &str) -> [u8; 32] {
+        if self == Self::PreHashed {
+            let digest = leaf
+                .strip_prefix("0x")
+                .filter(|digits| is_tx_id_hex(digits))
+                .and_then(|digits| hex::decode(digits).ok())
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+            if let Some(digest) = digest {
+                return digest;
+            }
+        }
+        Sha256::digest(leaf.as_bytes()).into()
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for LeafHashing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sha256 => "sha256",
+            Self::PreHashed => "prehashed",
+        })
+    }
+}
+
+impl FromStr for LeafHashing {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "prehashed" => Ok(Self::PreHashed),
+            other => anyhow::bail!(
+                "Unsupported leaf hashing '{}' (expected sha256 or prehashed)",
+                other
+            ),
+        }
+    }
+}
+
+/// The tree rules a verifier holds proofs to: its own configuration, never what a proof
+/// says about itself. A proof that could pick its leaf hashing could present an interior
+/// hash as a prehashed leaf, and one that could pick its arity could regroup siblings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeParams {
+    pub arity: MerkleArity,
+    pub leaf_hashing: LeafHashing,
+}
+
+/// How proof hashes are written: `0x`-prefixed lowercase hex (the default) or standard
+/// padded base64, which is about a third shorter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl HashEncoding {
+    pub fn encode(self, hash: &[u8]) -> String {
+        match self {
+            Self::Hex => format!("0x{}", hex::encode(hash)),
+            Self::Base64 => base64::engine::general_purpose::STANDARD.encode(hash),
+        }
+    }
+}
+
+impl fmt::Display for HashEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hex => "hex",
+            Self::Base64 => "base64",
+        })
+    }
+}
+
+impl FromStr for HashEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "hex" => Ok(Self::Hex),
+            "base64" => Ok(Self::Base64),
+            other => anyhow::bail!(
+                "Unsupported hash encoding '{}' (expected hex or base64)",
+                other
+            ),
+        }
+    }
+}
+
+/// Bytes of a hash written in either [`HashEncoding`]: hex with or without `0x`, else
+/// standard base64. A 32-byte hash is 64 hex digits but 44 base64 characters, so the
+/// two cannot be confused.
+pub fn decode_hash(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim();
+    let hex_digits = encoded.strip_prefix("0x").unwrap_or(encoded);
+    if hex_digits.len() % 2 == 0 && hex_digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return hex::decode(hex_digits).ok();
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+}
+
+/// Inclusion proof. Binary trees fill `path`; wider trees fill `levels` instead.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MerkleProof {
     pub leaf: String,
     pub path: Vec<(String, bool)>, // (hash, is_left)
     pub root: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub levels: Vec<ProofLevel>,
+    /// Position of the leaf in the tree the proof was cut from.
+    #[serde(default)]
+    pub leaf_index: u64,
+    /// Leaves in that tree; zero on proofs produced before it was recorded, which are
+    /// verified without the index checks.
+    #[serde(default)]
+    pub tree_size: u64,
+    /// How `leaf` was hashed into the tree; omitted for the default SHA-256.
+    #[serde(default, skip_serializing_if = "LeafHashing::is_default")]
+    pub leaf_hashing: LeafHashing,
+}
+
+/// One level of a k-ary proof: the node's position within its group of `k` children
+/// and the other `k - 1` children in order. A short trailing group is padded with
+/// copies of its last node, as in the binary tree.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ProofLevel {
+    pub position: usize,
+    pub siblings: Vec<String>,
+}
+
+impl MerkleProof {
+    /// The proof with its root and sibling hashes rewritten in `encoding`; the leaf is a
+    /// key, not a hash, and is left as is.
+    pub fn encoded(mut self, encoding: HashEncoding) -> Self {
+        let reencode = |hash: &mut String| {
+            if let Some(bytes) = decode_hash(hash) {
+                *hash = encoding.encode(&bytes);
+            }
+        };
+        reencode(&mut self.root);
+        for (hash, _) in &mut self.path {
+            reencode(hash);
+        }
+        for level in &mut self.levels {
+            level.siblings.iter_mut().for_each(reencode);
+        }
+        self
+    }
+
+    /// Levels between the leaf and the root, in whichever shape the proof has.
+    pub fn path_length(&self) -> usize {
+        self.path.len() + self.levels.len()
+    }
+
+    /// Packs the proof into one hex string for contract-call arguments:
+    /// `leaf_index` (u64 BE) ‖ `tree_size` (u64 BE) ‖ arity (u8) ‖ depth (u8) ‖ root ‖
+    /// the leaf hash, then per level from the leaf up the node's position in its group
+    /// (u8; for binary trees 0 when it is the left child) followed by its `arity - 1`
+    /// siblings. Hashes are 32 bytes. `None` when a hash does not decode to 32 bytes.
+    pub fn to_compact_hex(&self) -> Option<String> {
+        let hash = |encoded: &str| decode_hash(encoded).filter(|bytes| bytes.len() == 32);
+        let (arity, depth) = match self.levels.first() {
+            Some(level) => (level.siblings.len() + 1, self.levels.len()),
+            None => (2, self.path.len()),
+        };
+
+        let mut out = Vec::with_capacity(82 + depth * (1 + (arity - 1) * 32));
+        out.extend_from_slice(&self.leaf_index.to_be_bytes());
+        out.extend_from_slice(&self.tree_size.to_be_bytes());
+        out.push(u8::try_from(arity).ok()?);
+        out.push(u8::try_from(depth).ok()?);
+        out.extend_from_slice(&hash(&self.root)?);
+        out.extend_from_slice(&self.leaf_hashing.leaf_hash(&self.leaf));
+        for (sibling, is_left) in &self.path {
+            out.push(u8::from(!is_left));
+            out.extend_from_slice(&hash(sibling)?);
+        }
+        for level in &self.levels {
+            out.push(u8::try_from(level.position).ok()?);
+            for sibling in &level.siblings {
+                out.extend_from_slice(&hash(sibling)?);
+            }
+        }
+        Some(format!("0x{}", hex::encode(out)))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,13 +317,82 @@ pub struct MMRProof {
     pub root: String,
 }
 
+/// Proofs for a set of keys, all against the same `root`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchProof {
+    pub root: String,
+    pub proofs: BTreeMap<String, MerkleProof>,
+    /// Requested keys that are not leaves of the tree.
+    pub missing: Vec<String>,
+}
+
+/// Evidence that `key` is absent from the sorted-leaf tree committed to by `root`:
+/// inclusion proofs for the adjacent leaves immediately below and above it. A bound
+/// is `None` when `key` sorts before the first leaf or after the last.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NonInclusionProof {
+    pub key: String,
+    pub root: String,
+    pub left: Option<MerkleProof>,
+    pub right: Option<MerkleProof>,
+    /// State root whose leaves the sorted tree holds. Its `state_root_history` entry and
+    /// anchor record `root` as its sorted root.
+    #[serde(default)]
+    pub state_root: String,
+    /// Leaves `state_root` commits to.
+    #[serde(default)]
+    pub leaf_count: u64,
+}
+
+/// A state root with the sorted-leaf root and leaf count computed alongside it, as
+/// recorded in `state_root_history` and anchored on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootCommitment {
+    pub root: String,
+    pub sorted_root: String,
+    pub leaf_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeafPage {
+    pub root: String,
+    /// Fan-out the root was computed with.
+    #[serde(default)]
+    pub arity: MerkleArity,
+    /// Leaf hashing the root was computed with; omitted for the default SHA-256.
+    #[serde(default, skip_serializing_if = "LeafHashing::is_default")]
+    pub leaf_hashing: LeafHashing,
+    pub total: usize,
+    pub offset: usize,
+    pub leaves: Vec<String>,
+}
+
 pub struct NexusState {
     pub state_root: Mutex<String>,
     // Lock ordering invariant: when a method needs both `leaves` and `mmr`, it must lock
     // `leaves` before `mmr` to avoid deadlocks with update paths.
     pub leaves: Mutex<Vec<String>>,
     pub tree_levels: Mutex<Vec<Vec<[u8; 32]>>>,
+    /// Distinct leaves in ascending order with their own tree, used for non-inclusion
+    /// proofs. Rebuilt together with `tree_levels`.
+    pub sorted_leaves: Mutex<Vec<String>>,
+    pub sorted_levels: Mutex<Vec<Vec<[u8; 32]>>>,
     pub mmr: Mutex<MMRFoundation>,
+    /// Fan-out of the state-root tree. The sorted tree behind non-inclusion proofs is
+    /// always binary.
+    arity: MerkleArity,
+    /// Applies to both trees.
+    leaf_hashing: LeafHashing,
+    /// Proofs against the current root, for leaves requested over and over.
+    proof_cache: Mutex<ProofCache>,
+    /// Inclusion proofs built rather than served from `proof_cache`.
+    proofs_built: AtomicU64,
+    /// When the root was last recomputed; `None` until the first update or load.
+    last_updated: Mutex<Option<DateTime<Utc>>>,
+    /// Chain time of the newest block ingested; `None` until one reports a time.
+    last_block_time: Mutex<Option<DateTime<Utc>>>,
+    /// Held from a store commit to the matching state update; see [`Self::lock_commits`].
+    commit_lock: tokio::sync::Mutex<()>,
 }
 
 impl Default for NexusState {
@@ -35,13 +403,61 @@ impl Default for NexusState {
 
 impl NexusState {
     pub fn new() -> Self {
+        Self::with_arity(MerkleArity::Binary)
+    }
+
+    pub fn with_arity(arity: MerkleArity) -> Self {
         Self {
             state_root: Mutex::new(
                 "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
             ),
             leaves: Mutex::new(Vec::new()),
             tree_levels: Mutex::new(Vec::new()),
+            sorted_leaves: Mutex::new(Vec::new()),
+            sorted_levels: Mutex::new(Vec::new()),
             mmr: Mutex::new(MMRFoundation::new()),
+            arity,
+            leaf_hashing: LeafHashing::default(),
+            proof_cache: Mutex::new(ProofCache::new(DEFAULT_PROOF_CACHE_SIZE)),
+            proofs_built: AtomicU64::new(0),
+            last_updated: Mutex::new(None),
+            last_block_time: Mutex::new(None),
+            commit_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Caches up to `size` proofs by `(root, leaf)`; 0 disables the cache.
+    pub fn with_proof_cache_size(self, size: usize) -> Self {
+        *self.proof_cache.lock().unwrap() = ProofCache::new(size);
+        self
+    }
+
+    /// Hashes leaves with `leaf_hashing`; set before any leaf is added.
+    pub fn with_leaf_hashing(mut self, leaf_hashing: LeafHashing) -> Self {
+        self.leaf_hashing = leaf_hashing;
+        self
+    }
+
+    pub fn arity(&self) -> MerkleArity {
+        self.arity
+    }
+
+    /// Serializes writers appending leaves: hold the guard from committing leaves to the
+    /// store until they are appended here, so the tree follows the order the store
+    /// replays them in.
+    pub async fn lock_commits(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.commit_lock.lock().await
+    }
+
+    pub fn leaf_hashing(&self) -> LeafHashing {
+        self.leaf_hashing
+    }
+
+    /// The rules proofs against this tree are verified by.
+    pub fn tree_params(&self) -> TreeParams {
+        TreeParams {
+            arity: self.arity,
+            leaf_hashing: self.leaf_hashing,
         }
     }
 
@@ -49,6 +465,31 @@ impl NexusState {
         self.state_root.lock().unwrap().clone()
     }
 
+    /// When the root was last recomputed, by a block, a load or a restore.
+    pub fn last_updated(&self) -> Option<DateTime<Utc>> {
+        *self.last_updated.lock().unwrap()
+    }
+
+    /// Notes the chain time of an ingested block, keeping the newest seen. Loads and
+    /// restores leave it alone, so it survives a restart once seeded from the store.
+    pub fn record_block_time(&self, at: DateTime<Utc>) {
+        let mut last = self.last_block_time.lock().unwrap();
+        if last.is_none_or(|previous| previous < at) {
+            *last = Some(at);
+        }
+    }
+
+    /// Chain time of the newest ingested block.
+    pub fn last_block_time(&self) -> Option<DateTime<Utc>> {
+        *self.last_block_time.lock().unwrap()
+    }
+
+    /// Whole seconds since [`Self::last_block_time`].
+    pub fn seconds_since_last_block(&self) -> Option<u64> {
+        self.last_block_time()
+            .map(|at| (Utc::now() - at).num_seconds().max(0) as u64)
+    }
+
     pub fn get_mmr_root(&self) -> String {
         self.mmr.lock().unwrap().get_root()
     }
@@ -62,24 +503,42 @@ impl NexusState {
         self.update_state_batch(&[tx_id.to_string()]);
     }
 
+    /// Appends `tx_ids`, in [`canonical_leaf`] form, to the tree and the MMR, in the
+    /// order given. Leaves are append-only: past roots, proofs against them and the MMR
+    /// stay valid, so callers must commit blocks in the order the store replays them
+    /// (see [`crate::sync::NexusSync::process_microblock`]).
     pub fn update_state_batch(&self, tx_ids: &[String]) -> Vec<(u64, [u8; 32])> {
+        let started = Instant::now();
+        let tx_ids: Vec<String> = tx_ids
+            .iter()
+            .map(|tx_id| canonical_leaf(tx_id).into_owned())
+            .collect();
         let mut leaves = self.leaves.lock().unwrap();
-        leaves.extend_from_slice(tx_ids);
+        leaves.extend_from_slice(&tx_ids);
         self.rebuild_tree(&leaves);
+        *self.last_updated.lock().unwrap() = Some(Utc::now());
 
         let mut mmr = self.mmr.lock().unwrap();
         let mut added_nodes = Vec::new();
-        for tx_id in tx_ids {
+        for tx_id in &tx_ids {
             let nodes = mmr.add_leaf(tx_id.as_bytes());
             added_nodes.extend(nodes);
         }
+        metrics::observe_update(tx_ids.len(), leaves.len(), started.elapsed());
         added_nodes
     }
 
+    /// Replaces the leaf set, in [`canonical_leaf`] form, and rebuilds the MMR from it.
     pub fn set_initial_leaves(&self, leaves: Vec<String>) {
+        let started = Instant::now();
+        let leaves: Vec<String> = leaves
+            .iter()
+            .map(|leaf| canonical_leaf(leaf).into_owned())
+            .collect();
         let mut internal_leaves = self.leaves.lock().unwrap();
         *internal_leaves = leaves.clone();
         self.rebuild_tree(&internal_leaves);
+        *self.last_updated.lock().unwrap() = Some(Utc::now());
 
         let mut mmr = self.mmr.lock().unwrap();
         mmr.peaks = Vec::new();
@@ -88,6 +547,7 @@ impl NexusState {
         for leaf in &leaves {
             mmr.add_leaf(leaf.as_bytes());
         }
+        metrics::observe_update(leaves.len(), leaves.len(), started.elapsed());
 
         tracing::info!(
             "Nexus state initialized with {} leaves. Root: {}, MMR Root: {}",
@@ -97,6 +557,49 @@ impl NexusState {
         );
     }
 
+    /// Installs `leaves` only if they hash to `expected_root`, leaving the state untouched
+    /// otherwise. The trees and MMR are built before any lock is taken and swapped in
+    /// together, so readers see either the old state or the restored one.
+    pub fn restore_state(&self, leaves: Vec<String>, expected_root: String) -> anyhow::Result<()> {
+        let started = Instant::now();
+        let leaves: Vec<String> = leaves
+            .iter()
+            .map(|leaf| canonical_leaf(leaf).into_owned())
+            .collect();
+        let levels = build_levels(&leaves, self.arity.fanout(), self.leaf_hashing);
+        let root = levels_root(&levels);
+        if !root.eq_ignore_ascii_case(expected_root.trim()) {
+            anyhow::bail!(
+                "Restored leaves hash to {}, expected {}",
+                root,
+                expected_root
+            );
+        }
+        let sorted = sorted_leaves(&leaves);
+        let sorted_levels = build_levels(&sorted, 2, self.leaf_hashing);
+        let mut restored_mmr = MMRFoundation::new();
+        for leaf in &leaves {
+            restored_mmr.add_leaf(leaf.as_bytes());
+        }
+
+        let count = leaves.len();
+        let mut internal_leaves = self.leaves.lock().unwrap();
+        let mut mmr = self.mmr.lock().unwrap();
+        *internal_leaves = leaves;
+        *self.tree_levels.lock().unwrap() = levels;
+        *self.sorted_leaves.lock().unwrap() = sorted;
+        *self.sorted_levels.lock().unwrap() = sorted_levels;
+        *self.state_root.lock().unwrap() = root.clone();
+        *self.last_updated.lock().unwrap() = Some(Utc::now());
+        *mmr = restored_mmr;
+        drop(mmr);
+        drop(internal_leaves);
+        metrics::observe_update(count, count, started.elapsed());
+
+        tracing::info!(leaves = count, root = %root, "Nexus state restored");
+        Ok(())
+    }
+
     pub fn set_mmr_state(&self, peaks: Vec<[u8; 32]>, size: usize) {
         let mut mmr = self.mmr.lock().unwrap();
         mmr.peaks = peaks;
@@ -105,57 +608,94 @@ impl NexusState {
     }
 
     fn rebuild_tree(&self, leaves: &[String]) {
-        if leaves.is_empty() {
-            *self.state_root.lock().unwrap() =
-                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string();
-            *self.tree_levels.lock().unwrap() = Vec::new();
-            return;
-        }
+        let levels = build_levels(leaves, self.arity.fanout(), self.leaf_hashing);
+        *self.state_root.lock().unwrap() = levels_root(&levels);
+        *self.tree_levels.lock().unwrap() = levels;
 
-        let mut levels = Vec::new();
-        let mut current_level: Vec<[u8; 32]> = leaves
-            .iter()
-            .map(|l| {
-                let mut hasher = Sha256::new();
-                hasher.update(l.as_bytes());
-                hasher.finalize().into()
-            })
-            .collect();
+        let sorted = sorted_leaves(leaves);
+        *self.sorted_levels.lock().unwrap() = build_levels(&sorted, 2, self.leaf_hashing);
+        *self.sorted_leaves.lock().unwrap() = sorted;
+    }
 
-        levels.push(current_level.clone());
+    /// Root of the sorted, de-duplicated leaf tree that non-inclusion proofs commit to.
+    pub fn get_sorted_root(&self) -> String {
+        levels_root(&self.sorted_levels.lock().unwrap())
+    }
 
-        while current_level.len() > 1 {
-            let mut next_level = Vec::with_capacity(current_level.len().div_ceil(2));
-            for chunk in current_level.chunks(2) {
-                let mut hasher = Sha256::new();
-                if chunk.len() == 2 {
-                    hasher.update(chunk[0]);
-                    hasher.update(chunk[1]);
-                } else {
-                    hasher.update(chunk[0]);
-                    hasher.update(chunk[0]);
-                }
-                next_level.push(hasher.finalize().into());
-            }
-            current_level = next_level;
-            levels.push(current_level.clone());
-        }
+    /// Proves `key` is not a leaf by exhibiting its neighbours in the sorted-leaf tree.
+    /// `None` when `key` is a leaf.
+    pub fn generate_non_inclusion_proof(&self, key: &str) -> Option<NonInclusionProof> {
+        // Held so the sorted leaves and levels come from the same rebuild as the root.
+        let leaves = self.leaves.lock().unwrap();
+        let sorted = self.sorted_leaves.lock().unwrap();
+        let levels = self.sorted_levels.lock().unwrap();
+        let mut proof =
+            non_inclusion_proof(&sorted, &levels, &canonical_leaf(key), self.leaf_hashing)?;
+        proof.state_root = self.get_state_root();
+        proof.leaf_count = leaves.len() as u64;
+        Some(proof)
+    }
 
-        *self.state_root.lock().unwrap() = format!("0x{}", hex::encode(current_level[0]));
-        *self.tree_levels.lock().unwrap() = levels;
+    /// As [`Self::generate_non_inclusion_proof`], against the tree as it stood with its
+    /// first `leaf_count` leaves. `None` when `key` was a leaf then or `leaf_count` is out
+    /// of range. The prefix is copied under the leaf lock and sorted and rebuilt after it
+    /// is released.
+    pub fn generate_non_inclusion_proof_at(
+        &self,
+        key: &str,
+        leaf_count: usize,
+    ) -> Option<NonInclusionProof> {
+        let prefix = self.leaf_prefix(leaf_count)?;
+        let sorted = sorted_leaves(&prefix);
+        let levels = build_levels(&sorted, 2, self.leaf_hashing);
+        let mut proof =
+            non_inclusion_proof(&sorted, &levels, &canonical_leaf(key), self.leaf_hashing)?;
+        proof.state_root = levels_root(&build_levels(
+            &prefix,
+            self.arity.fanout(),
+            self.leaf_hashing,
+        ));
+        proof.leaf_count = leaf_count as u64;
+        Some(proof)
     }
 
     pub fn generate_proof(&self, key: &str) -> (String, String) {
+        self.generate_proof_encoded(key, HashEncoding::Hex)
+    }
+
+    /// As [`NexusState::generate_proof`], with the root and proof hashes in `encoding`.
+    pub fn generate_proof_encoded(&self, key: &str, encoding: HashEncoding) -> (String, String) {
         match self.generate_merkle_proof(key) {
             Some(proof) => {
+                let proof = proof.encoded(encoding);
                 let proof_json = serde_json::to_string(&proof).unwrap_or_default();
                 (proof.root.clone(), proof_json)
             }
-            None => (self.get_state_root(), "{}".to_string()),
+            None => {
+                let root = self.get_state_root();
+                let root = decode_hash(&root)
+                    .map(|bytes| encoding.encode(&bytes))
+                    .unwrap_or(root);
+                (root, "{}".to_string())
+            }
         }
     }
 
+    /// Inclusion proof for `key` in [`canonical_leaf`] form; the proof's `leaf` is the
+    /// canonical spelling. Served from the proof cache while the root is unchanged.
     pub fn generate_merkle_proof(&self, key: &str) -> Option<MerkleProof> {
+        let key = canonical_leaf(key);
+        let key = key.as_ref();
+        if let Some(proof) = self
+            .proof_cache
+            .lock()
+            .unwrap()
+            .get(&self.get_state_root(), key)
+        {
+            return Some(proof);
+        }
+
+        let started = Instant::now();
         let leaves = self.leaves.lock().unwrap();
         let levels = self.tree_levels.lock().unwrap();
         let index = leaves.iter().position(|l| l == key)?;
@@ -164,42 +704,177 @@ impl NexusState {
             return None;
         }
 
-        let mut path = Vec::new();
-        let mut idx = index;
+        let proof = inclusion_proof(
+            &levels,
+            index,
+            self.arity,
+            self.leaf_hashing,
+            key.to_string(),
+            self.get_state_root(),
+        );
+        metrics::observe_proof(leaves.len(), started.elapsed());
+        self.proofs_built.fetch_add(1, Ordering::Relaxed);
+        self.proof_cache.lock().unwrap().insert(proof.clone());
+        Some(proof)
+    }
+
+    /// Proofs for every key in `keys` from one snapshot of the tree. Keys that are not
+    /// leaves are listed in `missing` instead of receiving an empty proof. The tree is
+    /// copied under the locks and the proofs built after releasing them, so writers are
+    /// held up only for the copy.
+    pub fn generate_merkle_proofs(&self, keys: &[String]) -> BatchProof {
+        let (leaves, levels, root) = {
+            let leaves = self.leaves.lock().unwrap();
+            let levels = self.tree_levels.lock().unwrap();
+            (leaves.clone(), levels.clone(), self.get_state_root())
+        };
 
-        for level in &levels[..levels.len() - 1] {
-            let sibling_idx = if idx % 2 == 0 {
-                if idx + 1 < level.len() {
-                    idx + 1
-                } else {
-                    idx
+        // First occurrence wins, matching `generate_merkle_proof`.
+        let mut positions: HashMap<&str, usize> = HashMap::with_capacity(leaves.len());
+        for (index, leaf) in leaves.iter().enumerate() {
+            positions.entry(leaf.as_str()).or_insert(index);
+        }
+
+        let mut proofs = BTreeMap::new();
+        let mut missing = Vec::new();
+        for key in keys {
+            if proofs.contains_key(key) || missing.contains(key) {
+                continue;
+            }
+            let leaf = canonical_leaf(key);
+            match positions.get(leaf.as_ref()) {
+                Some(&index) if !levels.is_empty() => {
+                    proofs.insert(
+                        key.clone(),
+                        inclusion_proof(
+                            &levels,
+                            index,
+                            self.arity,
+                            self.leaf_hashing,
+                            leaf.into_owned(),
+                            root.clone(),
+                        ),
+                    );
                 }
-            } else {
-                idx - 1
-            };
+                _ => missing.push(key.clone()),
+            }
+        }
 
-            path.push((
-                format!("0x{}", hex::encode(level[sibling_idx])),
-                idx % 2 == 0,
-            ));
-            idx /= 2;
+        BatchProof {
+            root,
+            proofs,
+            missing,
         }
+    }
+
+    /// Inclusion proof for `key` against the tree as it stood with its first
+    /// `leaf_count` leaves, i.e. against a past root. Leaves are only ever appended, so
+    /// every earlier root commits to a prefix of the current leaf set. `None` when `key`
+    /// is not among those leaves or `leaf_count` is out of range. Never cached: the past
+    /// root is only known once its tree is rebuilt.
+    pub fn generate_merkle_proof_at(&self, key: &str, leaf_count: usize) -> Option<MerkleProof> {
+        let key = canonical_leaf(key);
+        let key = key.as_ref();
+        let prefix = self.leaf_prefix(leaf_count).filter(|p| !p.is_empty())?;
+        let index = prefix.iter().position(|l| l == key)?;
+        let levels = build_levels(&prefix, self.arity.fanout(), self.leaf_hashing);
+        let root = levels_root(&levels);
+        Some(inclusion_proof(
+            &levels,
+            index,
+            self.arity,
+            self.leaf_hashing,
+            key.to_string(),
+            root,
+        ))
+    }
+
+    /// Root of the tree as it stood with its first `leaf_count` leaves; `None` when the
+    /// tree never had that many.
+    pub fn root_at(&self, leaf_count: usize) -> Option<String> {
+        let prefix = self.leaf_prefix(leaf_count)?;
+        Some(levels_root(&build_levels(
+            &prefix,
+            self.arity.fanout(),
+            self.leaf_hashing,
+        )))
+    }
+
+    /// A copy of the first `leaf_count` leaves; `None` when the tree never had that many.
+    fn leaf_prefix(&self, leaf_count: usize) -> Option<Vec<String>> {
+        self.leaves
+            .lock()
+            .unwrap()
+            .get(..leaf_count)
+            .map(<[_]>::to_vec)
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.lock().unwrap().len()
+    }
 
-        Some(MerkleProof {
-            leaf: key.to_string(),
-            path,
+    /// The current root with the number of leaves it commits to, read under the leaf
+    /// lock so an update cannot land between the two.
+    pub fn root_and_leaf_count(&self) -> (String, usize) {
+        let leaves = self.leaves.lock().unwrap();
+        (self.get_state_root(), leaves.len())
+    }
+
+    /// The current root, sorted root and leaf count, read under the leaf lock.
+    pub fn commitment(&self) -> RootCommitment {
+        let leaves = self.leaves.lock().unwrap();
+        RootCommitment {
             root: self.get_state_root(),
-        })
+            sorted_root: self.get_sorted_root(),
+            leaf_count: leaves.len() as u64,
+        }
     }
 
     pub fn get_leaf_index(&self, tx_id: &str) -> Option<usize> {
-        self.leaves.lock().unwrap().iter().position(|l| l == tx_id)
+        let tx_id = canonical_leaf(tx_id);
+        self.leaves.lock().unwrap().iter().position(|l| *l == tx_id)
     }
 
     pub fn get_leaf_by_index(&self, index: usize) -> Option<String> {
         self.leaves.lock().unwrap().get(index).cloned()
     }
 
+    /// Root the tree would have with `additional`, in [`canonical_leaf`] form, appended
+    /// to the current leaves. Nothing is committed.
+    pub fn preview_root(&self, additional: &[String]) -> String {
+        let mut leaves = self.export_leaves();
+        leaves.extend(
+            additional
+                .iter()
+                .map(|leaf| canonical_leaf(leaf).into_owned()),
+        );
+        levels_root(&build_levels(
+            &leaves,
+            self.arity.fanout(),
+            self.leaf_hashing,
+        ))
+    }
+
+    /// Full leaf set in insertion order, sufficient to recompute the state root externally.
+    pub fn export_leaves(&self) -> Vec<String> {
+        self.leaves.lock().unwrap().clone()
+    }
+
+    /// One page of the leaf set with the total count and the root it commits to,
+    /// read under a single lock so the page and root are consistent.
+    pub fn export_leaves_page(&self, offset: usize, limit: usize) -> LeafPage {
+        let leaves = self.leaves.lock().unwrap();
+        let page = leaves.iter().skip(offset).take(limit).cloned().collect();
+        LeafPage {
+            root: self.get_state_root(),
+            arity: self.arity,
+            leaf_hashing: self.leaf_hashing,
+            total: leaves.len(),
+            offset,
+            leaves: page,
+        }
+    }
+
     pub fn get_mmr_proof_metadata(&self, leaf_index: usize) -> Option<(u64, Vec<u64>)> {
         let (leaves_len, node_count) = {
             // Lock ordering is intentional to match the write path (`update_state_batch`,
@@ -287,6 +962,161 @@ impl NexusState {
     }
 }
 
+/// Leaves of the sorted tree: `leaves` sorted and de-duplicated.
+fn sorted_leaves(leaves: &[String]) -> Vec<String> {
+    let mut sorted = leaves.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    sorted
+}
+
+/// Root of the sorted-leaf tree over `leaves`, which non-inclusion proofs are checked
+/// against.
+pub fn sorted_root(leaves: &[String], leaf_hashing: LeafHashing) -> String {
+    levels_root(&build_levels(&sorted_leaves(leaves), 2, leaf_hashing))
+}
+
+/// Bounds of `key` in the sorted tree `levels` over `sorted`; `None` when `key` is a
+/// leaf. The state root fields are left for the caller.
+fn non_inclusion_proof(
+    sorted: &[String],
+    levels: &[Vec<[u8; 32]>],
+    key: &str,
+    leaf_hashing: LeafHashing,
+) -> Option<NonInclusionProof> {
+    let root = levels_root(levels);
+    let index = match sorted.binary_search_by(|leaf| leaf.as_str().cmp(key)) {
+        Ok(_) => return None,
+        Err(index) => index,
+    };
+    let bound = |i: usize| MerkleProof {
+        leaf: sorted[i].clone(),
+        path: merkle_path(levels, i),
+        root: root.clone(),
+        levels: Vec::new(),
+        leaf_index: i as u64,
+        tree_size: sorted.len() as u64,
+        leaf_hashing,
+    };
+
+    Some(NonInclusionProof {
+        key: key.to_string(),
+        left: index.checked_sub(1).map(bound),
+        right: (index < sorted.len()).then(|| bound(index)),
+        root,
+        state_root: String::new(),
+        leaf_count: 0,
+    })
+}
+
+/// Hash levels from the leaves up to the root, `arity` children per node. A short
+/// trailing group is padded with copies of its last node (binary: an odd node is paired
+/// with itself).
+fn build_levels(leaves: &[String], arity: usize, leaf_hashing: LeafHashing) -> Vec<Vec<[u8; 32]>> {
+    if leaves.is_empty() {
+        return Vec::new();
+    }
+
+    let mut levels = Vec::new();
+    let mut current_level: Vec<[u8; 32]> =
+        leaves.iter().map(|l| leaf_hashing.leaf_hash(l)).collect();
+
+    levels.push(current_level.clone());
+
+    while current_level.len() > 1 {
+        current_level = current_level
+            .chunks(arity)
+            .map(|chunk| hash_group(chunk, arity))
+            .collect();
+        levels.push(current_level.clone());
+    }
+    levels
+}
+
+fn hash_group(chunk: &[[u8; 32]], arity: usize) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for i in 0..arity {
+        hasher.update(chunk[i.min(chunk.len() - 1)]);
+    }
+    hasher.finalize().into()
+}
+
+fn levels_root(levels: &[Vec<[u8; 32]>]) -> String {
+    match levels.last() {
+        Some(top) => format!("0x{}", hex::encode(top[0])),
+        None => EMPTY_ROOT.to_string(),
+    }
+}
+
+/// Sibling path from the leaf at `index` up to (but excluding) the root level.
+fn merkle_path(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<(String, bool)> {
+    let mut path = Vec::new();
+    let mut idx = index;
+
+    for level in &levels[..levels.len() - 1] {
+        let sibling_idx = if idx % 2 == 0 {
+            if idx + 1 < level.len() {
+                idx + 1
+            } else {
+                idx
+            }
+        } else {
+            idx - 1
+        };
+
+        path.push((
+            format!("0x{}", hex::encode(level[sibling_idx])),
+            idx % 2 == 0,
+        ));
+        idx /= 2;
+    }
+    path
+}
+
+/// Per-level groups from the leaf at `index` up to (but excluding) the root level.
+fn group_path(levels: &[Vec<[u8; 32]>], index: usize, arity: usize) -> Vec<ProofLevel> {
+    let mut path = Vec::new();
+    let mut idx = index;
+
+    for level in &levels[..levels.len() - 1] {
+        let start = idx - idx % arity;
+        let last = level.len() - 1;
+        path.push(ProofLevel {
+            position: idx % arity,
+            siblings: (0..arity)
+                .filter(|i| start + i != idx)
+                .map(|i| format!("0x{}", hex::encode(level[(start + i).min(last)])))
+                .collect(),
+        });
+        idx /= arity;
+    }
+    path
+}
+
+fn inclusion_proof(
+    levels: &[Vec<[u8; 32]>],
+    index: usize,
+    arity: MerkleArity,
+    leaf_hashing: LeafHashing,
+    leaf: String,
+    root: String,
+) -> MerkleProof {
+    let tree_size = levels.first().map_or(0, Vec::len);
+    let (path, levels) = match arity {
+        MerkleArity::Binary => (merkle_path(levels, index), Vec::new()),
+        wide => (Vec::new(), group_path(levels, index, wide.fanout())),
+    };
+    MerkleProof {
+        leaf,
+        path,
+        root,
+        levels,
+        leaf_index: index as u64,
+        tree_size: tree_size as u64,
+        leaf_hashing,
+    }
+}
+
 /// Helper to get MMR node position for a given leaf index.
 ///
 /// Uses the postorder MMR leaf-position identity: `pos = 2 * leaf_index - popcount(leaf_index)`.
@@ -363,15 +1193,141 @@ pub fn get_mmr_path(pos: u64, leaf_count: u64) -> Vec<u64> {
     path
 }
 
-pub fn verify_merkle_proof(proof: &MerkleProof) -> bool {
-    let mut hasher = Sha256::new();
-    hasher.update(proof.leaf.as_bytes());
-    let mut current_hash: [u8; 32] = hasher.finalize().into();
+/// Leaf index encoded by a proof's left/right flags.
+fn proof_index(proof: &MerkleProof) -> Option<usize> {
+    proof
+        .path
+        .iter()
+        .enumerate()
+        .try_fold(0usize, |index, (level, (_, is_left))| {
+            let bit = usize::from(!is_left).checked_shl(level as u32)?;
+            Some(index | bit)
+        })
+}
+
+/// Whether `proof` is for the last leaf: at every level where it is a left child, its
+/// sibling is itself (the odd-node duplicate) rather than a real right neighbour.
+fn is_rightmost(proof: &MerkleProof, leaf_hashing: LeafHashing) -> bool {
+    let mut current = leaf_hashing.leaf_hash(&proof.leaf);
+
+    for (sibling_hash, is_left) in &proof.path {
+        let Some(sibling) = decode_hash(sibling_hash) else {
+            return false;
+        };
+        let mut hasher = Sha256::new();
+        if *is_left {
+            if sibling != current {
+                return false;
+            }
+            hasher.update(current);
+            hasher.update(current);
+        } else {
+            hasher.update(&sibling);
+            hasher.update(current);
+        }
+        current = hasher.finalize().into();
+    }
+    true
+}
+
+/// Checks a [`NonInclusionProof`] against the sorted tree, which is always binary and
+/// hashed by `leaf_hashing`: both bounds verify against `root`, strictly enclose `key`,
+/// and are adjacent leaves (or the first/last leaf when one side is open).
+pub fn verify_non_inclusion_proof(proof: &NonInclusionProof, leaf_hashing: LeafHashing) -> bool {
+    let params = TreeParams {
+        arity: MerkleArity::Binary,
+        leaf_hashing,
+    };
+    let bound_ok =
+        |bound: &MerkleProof| bound.root == proof.root && verify_merkle_proof(bound, params);
+
+    match (&proof.left, &proof.right) {
+        (None, None) => proof.root == EMPTY_ROOT,
+        (Some(left), None) => {
+            bound_ok(left) && left.leaf < proof.key && is_rightmost(left, leaf_hashing)
+        }
+        (None, Some(right)) => {
+            bound_ok(right) && proof.key < right.leaf && proof_index(right) == Some(0)
+        }
+        (Some(left), Some(right)) => {
+            bound_ok(left)
+                && bound_ok(right)
+                && left.leaf < proof.key
+                && proof.key < right.leaf
+                && match (proof_index(left), proof_index(right)) {
+                    (Some(l), Some(r)) => l.checked_add(1) == Some(r),
+                    _ => false,
+                }
+        }
+    }
+}
+
+/// Levels between a leaf and the root of a tree of `tree_size` leaves with `arity`
+/// children per node. Short groups are padded, so this is `ceil(log_arity(tree_size))`.
+pub fn tree_depth(tree_size: u64, arity: u64) -> usize {
+    let (mut depth, mut width) = (0, 1u64);
+    while width < tree_size {
+        width = width.saturating_mul(arity);
+        depth += 1;
+    }
+    depth
+}
+
+/// Whether a proof's `leaf_index` and `tree_size`, when given, agree with its shape in a
+/// tree of `arity`: the index is inside the tree, there is one level per level of the
+/// tree, and each level puts the node where the index says it is.
+fn position_consistent(proof: &MerkleProof, arity: u64) -> bool {
+    if proof.tree_size == 0 {
+        return true;
+    }
+    if proof.leaf_index >= proof.tree_size {
+        return false;
+    }
+    let positions: Vec<u64> = if proof.levels.is_empty() {
+        proof
+            .path
+            .iter()
+            .map(|(_, is_left)| u64::from(!is_left))
+            .collect()
+    } else {
+        proof
+            .levels
+            .iter()
+            .map(|level| level.position as u64)
+            .collect()
+    };
+    if positions.len() != tree_depth(proof.tree_size, arity) {
+        return false;
+    }
+    let mut index = proof.leaf_index;
+    positions.into_iter().all(|position| {
+        let expected = index % arity;
+        index /= arity;
+        position == expected
+    })
+}
+
+/// Checks an inclusion proof under the verifier's `params`: a binary tree's proof is a
+/// `path`, a wider tree's is `levels` of exactly that arity. Hashes may be in either
+/// [`HashEncoding`]. The leaf is hashed by `params`; a proof cut under other rules does
+/// not verify. A proof that carries its `tree_size` must also place the leaf at
+/// `leaf_index`.
+pub fn verify_merkle_proof(proof: &MerkleProof, params: TreeParams) -> bool {
+    let arity = params.arity.fanout();
+    if proof.leaf_hashing != params.leaf_hashing || !position_consistent(proof, arity as u64) {
+        return false;
+    }
+    if arity != 2 {
+        return proof.path.is_empty() && verify_group_path(proof, arity, params.leaf_hashing);
+    }
+    if !proof.levels.is_empty() {
+        return false;
+    }
+    let mut current_hash = params.leaf_hashing.leaf_hash(&proof.leaf);
 
     for (sibling_hash_str, is_left) in &proof.path {
-        let sibling_hash = match hex::decode(sibling_hash_str.trim_start_matches("0x")) {
-            Ok(h) => h,
-            Err(_) => return false,
+        let Some(sibling_hash) = decode_hash(sibling_hash_str) else {
+            return false;
         };
         let mut hasher = Sha256::new();
         if *is_left {
@@ -384,8 +1340,32 @@ pub fn verify_merkle_proof(proof: &MerkleProof) -> bool {
         current_hash = hasher.finalize().into();
     }
 
-    let final_root = format!("0x{}", hex::encode(current_hash));
-    final_root == proof.root
+    decode_hash(&proof.root).is_some_and(|root| root == current_hash)
+}
+
+fn verify_group_path(proof: &MerkleProof, arity: usize, leaf_hashing: LeafHashing) -> bool {
+    let mut current = leaf_hashing.leaf_hash(&proof.leaf);
+
+    for level in &proof.levels {
+        if level.siblings.len() + 1 != arity || level.position >= arity {
+            return false;
+        }
+        let mut hasher = Sha256::new();
+        let mut siblings = level.siblings.iter();
+        for i in 0..arity {
+            if i == level.position {
+                hasher.update(current);
+                continue;
+            }
+            let Some(Some(sibling)) = siblings.next().map(|s| decode_hash(s)) else {
+                return false;
+            };
+            hasher.update(&sibling);
+        }
+        current = hasher.finalize().into();
+    }
+
+    decode_hash(&proof.root).is_some_and(|root| root == current)
 }
 
 impl Default for MMRFoundation {
@@ -473,6 +1453,65 @@ mod tests {
         assert_eq!(sibs.as_slice(), expected_sibs);
     }
 
+    #[test]
+    fn test_repeated_proofs_are_cached_until_the_root_changes() {
+        let state = NexusState::new();
+        state.update_state_batch(&["tx1".to_string(), "tx2".to_string()]);
+        let built = || state.proofs_built.load(Ordering::Relaxed);
+
+        let first = state.generate_merkle_proof("tx1").unwrap();
+        let second = state.generate_merkle_proof("tx1").unwrap();
+        assert_eq!(built(), 1);
+        assert_eq!(first.root, second.root);
+        assert_eq!(first.path, second.path);
+
+        state.update_state("tx3", 0);
+        let after = state.generate_merkle_proof("tx1").unwrap();
+        assert_eq!(built(), 2);
+        assert_eq!(after.root, state.get_state_root());
+        assert!(verify_merkle_proof(&after, state.tree_params()));
+
+        // Historical proofs bypass the cache.
+        state.generate_merkle_proof_at("tx1", 2).unwrap();
+        assert_eq!(built(), 2);
+
+        let uncached = NexusState::new().with_proof_cache_size(0);
+        uncached.update_state("tx1", 0);
+        uncached.generate_merkle_proof("tx1").unwrap();
+        uncached.generate_merkle_proof("tx1").unwrap();
+        assert_eq!(uncached.proofs_built.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_base64_proofs_verify_and_round_trip() {
+        for arity in [MerkleArity::Binary, MerkleArity::Quaternary] {
+            let state = NexusState::with_arity(arity);
+            let leaves: Vec<String> = (0..5).map(|i| format!("tx{}", i)).collect();
+            state.update_state_batch(&leaves);
+
+            let hex = state.generate_merkle_proof("tx3").unwrap();
+            let base64 = hex.clone().encoded(HashEncoding::Base64);
+            assert!(!base64.root.starts_with("0x"));
+            assert_eq!(base64.root.len(), 44);
+            assert!(verify_merkle_proof(&base64, state.tree_params()));
+            assert_eq!(base64.leaf, "tx3");
+            let back = base64.encoded(HashEncoding::Hex);
+            assert_eq!(back.root, hex.root);
+            assert_eq!(back.path, hex.path);
+            assert_eq!(back.levels, hex.levels);
+
+            let (root, proof_json) = state.generate_proof_encoded("tx3", HashEncoding::Base64);
+            assert_eq!(decode_hash(&root), decode_hash(&state.get_state_root()));
+            let proof: MerkleProof = serde_json::from_str(&proof_json).unwrap();
+            assert!(verify_merkle_proof(&proof, state.tree_params()));
+        }
+        assert_eq!(
+            "BASE64".parse::<HashEncoding>().unwrap(),
+            HashEncoding::Base64
+        );
+        assert!("raw".parse::<HashEncoding>().is_err());
+    }
+
     #[test]
     fn test_new_nexus_state() {
         let state = NexusState::new();
@@ -482,6 +1521,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_proof_at_past_leaf_count_matches_past_root() {
+        for arity in [MerkleArity::Binary, MerkleArity::Quaternary] {
+            let state = NexusState::with_arity(arity);
+            state.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+            let past_root = state.get_state_root();
+            state.update_state_batch(&["d".to_string(), "e".to_string()]);
+
+            let proof = state.generate_merkle_proof_at("b", 3).unwrap();
+            assert_eq!(proof.root, past_root);
+            assert!(verify_merkle_proof(&proof, state.tree_params()));
+            assert!(state.generate_merkle_proof_at("d", 3).is_none());
+            assert!(state.generate_merkle_proof_at("b", 0).is_none());
+            assert!(state.generate_merkle_proof_at("b", 6).is_none());
+
+            let current = state.generate_merkle_proof_at("e", 5).unwrap();
+            assert_eq!(current.root, state.get_state_root());
+        }
+    }
+
     #[test]
     fn test_update_state_batch() {
         let state = NexusState::new();
@@ -493,6 +1552,118 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_later_batches_leave_earlier_roots_provable() {
+        let state = NexusState::new();
+        state.set_initial_leaves(vec!["base".to_string()]);
+        state.update_state_batch(&["b1".to_string(), "b2".to_string()]);
+        let root = state.get_state_root();
+        let mmr_root = state.get_mmr_root();
+
+        // A batch is appended after every earlier leaf, whatever it holds.
+        state.update_state_batch(&["a1".to_string()]);
+        assert_eq!(state.export_leaves(), vec!["base", "b1", "b2", "a1"]);
+        assert_eq!(state.root_at(3).as_deref(), Some(root.as_str()));
+        assert_eq!(state.generate_merkle_proof_at("b1", 3).unwrap().root, root);
+        let replayed = NexusState::new();
+        replayed.set_initial_leaves(state.export_leaves()[..3].to_vec());
+        assert_eq!(replayed.get_mmr_root(), mmr_root);
+    }
+
+    #[test]
+    fn test_root_and_leaf_count_commit_to_each_other() {
+        let state = std::sync::Arc::new(NexusState::new());
+        let writer = {
+            let state = state.clone();
+            std::thread::spawn(move || {
+                for i in 0..50 {
+                    state.update_state_batch(&[format!("tx{}", i)]);
+                }
+            })
+        };
+        for _ in 0..50 {
+            let (root, leaf_count) = state.root_and_leaf_count();
+            if leaf_count > 0 {
+                assert_eq!(state.root_at(leaf_count), Some(root));
+            }
+        }
+        writer.join().unwrap();
+    }
+
+    const TX: &str = "0x8f2a6c3b1d4e5f60718293a4b5c6d7e8f9011223344556677889900aabbccdd";
+
+    #[test]
+    fn test_canonical_leaf_normalizes_tx_id_spellings() {
+        let bare = TX.trim_start_matches("0x");
+        for spelling in [
+            TX.to_string(),
+            bare.to_string(),
+            TX.to_uppercase(),
+            format!("0x{}", bare.to_uppercase()),
+            format!("  {}\n", TX),
+        ] {
+            assert_eq!(canonical_leaf(&spelling), TX, "spelling {:?}", spelling);
+        }
+        assert!(matches!(canonical_leaf(TX), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_canonical_leaf_leaves_non_tx_ids_verbatim() {
+        // One digit short, one too many, a non-hex digit, a doubled prefix.
+        let short = &TX[..TX.len() - 1];
+        let long = format!("{}0", TX);
+        let non_hex = format!("{}g", short);
+        let double_prefix = format!("0x{}", TX);
+        for key in [short, &long, &non_hex, &double_prefix, "tx1", "0x", ""] {
+            assert_eq!(canonical_leaf(key), key);
+            assert!(parse_tx_id(key).is_err(), "{:?} should not parse", key);
+        }
+        assert_eq!(parse_tx_id(&TX.to_uppercase()).unwrap(), TX);
+    }
+
+    #[test]
+    fn test_restore_state_checks_the_expected_root() {
+        let source = NexusState::with_arity(MerkleArity::Quaternary);
+        source.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+        let leaves = source.export_leaves();
+        let root = source.get_state_root();
+
+        let target = NexusState::with_arity(MerkleArity::Quaternary);
+        target.update_state_batch(&["z".to_string()]);
+        let before = target.get_state_root();
+        let mut corrupt = leaves.clone();
+        corrupt.swap(0, 1);
+        assert!(target.restore_state(corrupt, root.clone()).is_err());
+        assert_eq!(target.get_state_root(), before);
+        assert_eq!(target.export_leaves(), vec!["z".to_string()]);
+
+        target.restore_state(leaves, root.to_uppercase()).unwrap();
+        assert_eq!(target.get_state_root(), root);
+        assert_eq!(target.get_mmr_root(), source.get_mmr_root());
+        assert_eq!(target.get_sorted_root(), source.get_sorted_root());
+        assert!(target.generate_merkle_proof("b").is_some());
+    }
+
+    #[test]
+    fn test_tx_id_spellings_share_leaf_root_and_proof() {
+        let bare = TX.trim_start_matches("0x").to_uppercase();
+        let a = NexusState::new();
+        a.update_state_batch(&[TX.to_string(), "tx2".to_string()]);
+        let b = NexusState::new();
+        b.set_initial_leaves(vec![bare.clone(), "tx2".to_string()]);
+
+        assert_eq!(a.get_state_root(), b.get_state_root());
+        assert_eq!(a.get_mmr_root(), b.get_mmr_root());
+        assert_eq!(b.export_leaves()[0], TX);
+        assert_eq!(b.get_leaf_index(&bare), Some(0));
+
+        let proof = a.generate_merkle_proof(&bare).unwrap();
+        assert_eq!(proof.leaf, TX);
+        assert!(verify_merkle_proof(&proof, a.tree_params()));
+        assert!(a.generate_non_inclusion_proof(&bare).is_none());
+        assert!(a.generate_merkle_proofs(&[bare]).missing.is_empty());
+    }
+
     #[test]
     fn test_merkle_proof_verification() {
         let state = NexusState::new();
@@ -500,7 +1671,447 @@ mod tests {
         state.set_initial_leaves(leaves);
 
         let proof = state.generate_merkle_proof("b").unwrap();
-        assert!(verify_merkle_proof(&proof));
+        assert!(verify_merkle_proof(&proof, state.tree_params()));
+    }
+
+    fn sha(parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+
+    fn leaves(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("leaf-{}", i)).collect()
+    }
+
+    #[test]
+    fn test_binary_root_is_unchanged() {
+        let state = NexusState::new();
+        state.set_initial_leaves(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        let (a, b, c) = (sha(&[b"a"]), sha(&[b"b"]), sha(&[b"c"]));
+        let root = sha(&[&sha(&[&a, &b]), &sha(&[&c, &c])]);
+        assert_eq!(state.get_state_root(), format!("0x{}", hex::encode(root)));
+        assert!(state.generate_merkle_proof("c").unwrap().levels.is_empty());
+    }
+
+    #[test]
+    fn test_prehashed_leaves_match_an_external_tree() {
+        let (a, b) = (sha(&[b"commitment-a"]), sha(&[b"commitment-b"]));
+        let digest_a = format!("0x{}", hex::encode(a));
+        // Any spelling of a digest is canonicalized first.
+        let digest_b = hex::encode_upper(b);
+        let state = NexusState::new().with_leaf_hashing(LeafHashing::PreHashed);
+        state.set_initial_leaves(vec![
+            digest_a.clone(),
+            digest_b.clone(),
+            "app-key".to_string(),
+        ]);
+
+        // The digests are the leaf hashes; a leaf that is not one is still hashed.
+        let key = sha(&[b"app-key"]);
+        let root = sha(&[&sha(&[&a, &b]), &sha(&[&key, &key])]);
+        assert_eq!(state.get_state_root(), format!("0x{}", hex::encode(root)));
+        assert_ne!(
+            NexusState::new().preview_root(&[
+                digest_a.clone(),
+                digest_b.clone(),
+                "app-key".to_string()
+            ]),
+            state.get_state_root()
+        );
+
+        for leaf in [&digest_a, &digest_b, "app-key"] {
+            let proof = state.generate_merkle_proof(leaf).unwrap();
+            assert_eq!(proof.leaf_hashing, LeafHashing::PreHashed);
+            assert!(
+                verify_merkle_proof(&proof, state.tree_params()),
+                "leaf {}",
+                leaf
+            );
+            let json = serde_json::to_string(&proof).unwrap();
+            assert!(json.contains(r#""leaf_hashing":"prehashed""#));
+            // The verifier's rule decides, not the proof's.
+            let mut relabelled = proof.clone();
+            relabelled.leaf_hashing = LeafHashing::Sha256;
+            assert!(!verify_merkle_proof(&relabelled, state.tree_params()));
+            assert!(!verify_merkle_proof(&proof, TreeParams::default()));
+        }
+        let compact = state
+            .generate_merkle_proof(&digest_a)
+            .unwrap()
+            .to_compact_hex()
+            .unwrap();
+        assert!(compact.contains(&hex::encode(a)));
+
+        let absent = state.generate_non_inclusion_proof("0x00").unwrap();
+        assert!(verify_non_inclusion_proof(&absent, state.leaf_hashing()));
+        // Default proofs keep their old shape.
+        let plain = NexusState::new();
+        plain.set_initial_leaves(vec!["a".to_string()]);
+        let json = serde_json::to_string(&plain.generate_merkle_proof("a").unwrap()).unwrap();
+        assert!(!json.contains("leaf_hashing"));
+    }
+
+    #[test]
+    fn test_quaternary_root_pads_short_groups() {
+        let state = NexusState::with_arity(MerkleArity::Quaternary);
+        let names = ["a", "b", "c", "d", "e"];
+        state.set_initial_leaves(names.iter().map(|s| s.to_string()).collect());
+        let h: Vec<[u8; 32]> = names.iter().map(|n| sha(&[n.as_bytes()])).collect();
+        let left = sha(&[&h[0], &h[1], &h[2], &h[3]]);
+        let right = sha(&[&h[4], &h[4], &h[4], &h[4]]);
+        let root = sha(&[&left, &right, &right, &right]);
+        assert_eq!(state.get_state_root(), format!("0x{}", hex::encode(root)));
+
+        let proof = state.generate_merkle_proof("e").unwrap();
+        assert!(proof.path.is_empty());
+        assert_eq!(proof.levels.len(), 2);
+        assert_eq!(proof.levels[0].position, 0);
+        assert_eq!(proof.levels[1].position, 1);
+        assert!(verify_merkle_proof(&proof, state.tree_params()));
+    }
+
+    #[test]
+    fn test_every_leaf_verifies_across_arities_and_odd_counts() {
+        for arity in [MerkleArity::Binary, MerkleArity::Quaternary] {
+            for n in 1..=17 {
+                let state = NexusState::with_arity(arity);
+                state.set_initial_leaves(leaves(n));
+                let batch = state.generate_merkle_proofs(&leaves(n));
+                assert!(batch.missing.is_empty());
+                for leaf in leaves(n) {
+                    let proof = state.generate_merkle_proof(&leaf).unwrap();
+                    assert!(
+                        verify_merkle_proof(&proof, state.tree_params()),
+                        "arity {} n {} {}",
+                        arity,
+                        n,
+                        leaf
+                    );
+                    assert!(verify_merkle_proof(
+                        &batch.proofs[&leaf],
+                        state.tree_params()
+                    ));
+
+                    let depth = proof.path.len() + proof.levels.len();
+                    let mut expected = 0;
+                    while arity.fanout().pow(expected) < n {
+                        expected += 1;
+                    }
+                    assert_eq!(depth, expected as usize, "arity {} n {}", arity, n);
+                }
+            }
+        }
+
+        let binary = NexusState::new();
+        let quaternary = NexusState::with_arity(MerkleArity::Quaternary);
+        binary.set_initial_leaves(leaves(5));
+        quaternary.set_initial_leaves(leaves(5));
+        assert_ne!(binary.get_state_root(), quaternary.get_state_root());
+    }
+
+    #[test]
+    fn test_quaternary_proof_rejects_tampering() {
+        let state = NexusState::with_arity(MerkleArity::Quaternary);
+        state.set_initial_leaves(leaves(7));
+        let proof = state.generate_merkle_proof("leaf-5").unwrap();
+        assert!(verify_merkle_proof(&proof, state.tree_params()));
+
+        let mut moved = proof.clone();
+        moved.levels[0].position = 2;
+        assert!(!verify_merkle_proof(&moved, state.tree_params()));
+
+        let mut short = proof.clone();
+        short.levels[1].siblings.pop();
+        assert!(!verify_merkle_proof(&short, state.tree_params()));
+
+        let mut mixed = proof.clone();
+        mixed.path = vec![(proof.root.clone(), true)];
+        assert!(!verify_merkle_proof(&mixed, state.tree_params()));
+
+        let mut forged = proof;
+        forged.leaf = "leaf-6".to_string();
+        assert!(!verify_merkle_proof(&forged, state.tree_params()));
+    }
+
+    #[test]
+    fn test_proof_cannot_choose_its_own_leaf_hashing_or_arity() {
+        let state = NexusState::new();
+        state.set_initial_leaves(leaves(4));
+        let proof = state.generate_merkle_proof("leaf-0").unwrap();
+
+        // An interior node presented as a prehashed "leaf" one level up.
+        let (sibling, _) = &proof.path[0];
+        let leaf = sha(&[b"leaf-0"]);
+        let interior = sha(&[&leaf, &decode_hash(sibling).unwrap()]);
+        let mut forged = proof.clone();
+        forged.leaf = format!("0x{}", hex::encode(interior));
+        forged.path.remove(0);
+        (forged.leaf_index, forged.tree_size) = (0, 0);
+        forged.leaf_hashing = LeafHashing::PreHashed;
+        assert!(!verify_merkle_proof(&forged, state.tree_params()));
+
+        // A binary proof is not accepted by a quaternary verifier, nor the reverse.
+        let quaternary = NexusState::with_arity(MerkleArity::Quaternary);
+        quaternary.set_initial_leaves(leaves(4));
+        let wide = quaternary.generate_merkle_proof("leaf-0").unwrap();
+        assert!(!verify_merkle_proof(&proof, quaternary.tree_params()));
+        assert!(!verify_merkle_proof(&wide, state.tree_params()));
+    }
+
+    #[test]
+    fn test_proofs_carry_index_and_tree_size() {
+        for arity in [MerkleArity::Binary, MerkleArity::Quaternary] {
+            let state = NexusState::with_arity(arity);
+            state.set_initial_leaves(leaves(7));
+            for (i, leaf) in leaves(7).iter().enumerate() {
+                let proof = state.generate_merkle_proof(leaf).unwrap();
+                assert_eq!((proof.leaf_index, proof.tree_size), (i as u64, 7));
+            }
+        }
+        assert_eq!(tree_depth(1, 2), 0);
+        assert_eq!(tree_depth(5, 2), 3);
+        assert_eq!(tree_depth(16, 4), 2);
+        assert_eq!(tree_depth(17, 4), 3);
+    }
+
+    #[test]
+    fn test_index_and_tree_size_are_checked_when_present() {
+        let state = NexusState::new();
+        state.set_initial_leaves(leaves(5));
+        let proof = state.generate_merkle_proof("leaf-4").unwrap();
+        assert!(verify_merkle_proof(&proof, state.tree_params()));
+
+        let mut legacy = proof.clone();
+        (legacy.leaf_index, legacy.tree_size) = (0, 0);
+        assert!(verify_merkle_proof(&legacy, state.tree_params()));
+        let legacy: MerkleProof = serde_json::from_value(serde_json::json!({
+            "leaf": proof.leaf, "path": proof.path, "root": proof.root,
+        }))
+        .unwrap();
+        assert!(verify_merkle_proof(&legacy, state.tree_params()));
+
+        let mut moved = proof.clone();
+        moved.leaf_index = 3;
+        assert!(!verify_merkle_proof(&moved, state.tree_params()));
+
+        let mut outside = proof.clone();
+        outside.leaf_index = 5;
+        assert!(!verify_merkle_proof(&outside, state.tree_params()));
+
+        // Eight leaves still need three levels; two leaves need only one.
+        let mut resized = proof.clone();
+        resized.tree_size = 8;
+        assert!(verify_merkle_proof(&resized, state.tree_params()));
+        resized.tree_size = 2;
+        assert!(!verify_merkle_proof(&resized, state.tree_params()));
+
+        let quaternary = NexusState::with_arity(MerkleArity::Quaternary);
+        quaternary.set_initial_leaves(leaves(7));
+        let mut proof = quaternary.generate_merkle_proof("leaf-5").unwrap();
+        assert!(verify_merkle_proof(&proof, quaternary.tree_params()));
+        proof.leaf_index = 6;
+        assert!(!verify_merkle_proof(&proof, quaternary.tree_params()));
+    }
+
+    #[test]
+    fn test_compact_hex_layout() {
+        let state = NexusState::new();
+        state.set_initial_leaves(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        let proof = state.generate_merkle_proof("c").unwrap();
+        let bytes = hex::decode(&proof.to_compact_hex().unwrap()[2..]).unwrap();
+
+        assert_eq!(bytes.len(), 8 + 8 + 1 + 1 + 32 + 32 + 2 * 33);
+        assert_eq!(bytes[..8], 2u64.to_be_bytes());
+        assert_eq!(bytes[8..16], 3u64.to_be_bytes());
+        assert_eq!(bytes[16..18], [2, 2]);
+        assert_eq!(bytes[18..50], decode_hash(&proof.root).unwrap()[..]);
+        assert_eq!(bytes[50..82], sha(&[b"c"]));
+        // "c" is a left child paired with its own duplicate, then the right child.
+        assert_eq!(bytes[82], 0);
+        assert_eq!(bytes[83..115], sha(&[b"c"]));
+        assert_eq!(bytes[115], 1);
+
+        let base64 = proof.clone().encoded(HashEncoding::Base64);
+        assert_eq!(base64.to_compact_hex(), proof.to_compact_hex());
+        let mut broken = proof;
+        broken.root = "not a hash".to_string();
+        assert_eq!(broken.to_compact_hex(), None);
+    }
+
+    #[test]
+    fn test_non_inclusion_stays_binary_under_quaternary_root() {
+        let state = NexusState::with_arity(MerkleArity::Quaternary);
+        state.set_initial_leaves(vec!["a".to_string(), "c".to_string(), "e".to_string()]);
+        let proof = state.generate_non_inclusion_proof("b").unwrap();
+        assert_eq!(proof.root, state.get_sorted_root());
+        assert!(verify_non_inclusion_proof(&proof, state.leaf_hashing()));
+        assert_eq!(
+            state.export_leaves_page(0, 10).arity,
+            MerkleArity::Quaternary
+        );
+        assert_eq!("4".parse::<MerkleArity>().unwrap(), MerkleArity::Quaternary);
+        assert!("3".parse::<MerkleArity>().is_err());
+    }
+
+    #[test]
+    fn test_batch_proofs_verify_and_report_missing() {
+        let state = NexusState::new();
+        state.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+
+        let batch = state.generate_merkle_proofs(&[
+            "c".to_string(),
+            "a".to_string(),
+            "zz".to_string(),
+            "a".to_string(),
+        ]);
+        assert_eq!(batch.root, state.get_state_root());
+        assert_eq!(batch.proofs.len(), 2);
+        assert_eq!(batch.missing, vec!["zz".to_string()]);
+        for proof in batch.proofs.values() {
+            assert!(verify_merkle_proof(proof, state.tree_params()));
+        }
+        assert_eq!(
+            batch.proofs["c"].path,
+            state.generate_merkle_proof("c").unwrap().path
+        );
+    }
+
+    #[test]
+    fn test_non_inclusion_proof_at_past_root_matches_its_commitment() {
+        let state = NexusState::new();
+        state.update_state_batch(&["b".to_string(), "d".to_string()]);
+        let past = state.commitment();
+        state.update_state_batch(&["c".to_string()]);
+        assert!(state.generate_non_inclusion_proof("c").is_none());
+
+        let proof = state.generate_non_inclusion_proof_at("c", 2).unwrap();
+        assert_eq!(proof.root, past.sorted_root);
+        assert_eq!(proof.state_root, past.root);
+        assert_eq!(proof.leaf_count, 2);
+        assert!(verify_non_inclusion_proof(&proof, state.leaf_hashing()));
+        assert_eq!(
+            sorted_root(&state.export_leaves(), state.leaf_hashing()),
+            state.commitment().sorted_root
+        );
+        assert!(state.generate_non_inclusion_proof_at("c", 4).is_none());
+    }
+
+    #[test]
+    fn test_non_inclusion_proofs_bound_missing_keys() {
+        let state = NexusState::new();
+        state.update_state_batch(&[
+            "d".to_string(),
+            "b".to_string(),
+            "f".to_string(),
+            "b".to_string(),
+            "h".to_string(),
+            "j".to_string(),
+        ]);
+        assert!(state.generate_non_inclusion_proof("f").is_none());
+
+        for key in ["a", "c", "e", "g", "i", "k"] {
+            let proof = state.generate_non_inclusion_proof(key).unwrap();
+            assert_eq!(proof.root, state.get_sorted_root());
+            assert!(
+                verify_non_inclusion_proof(&proof, state.leaf_hashing()),
+                "key {}",
+                key
+            );
+        }
+
+        let middle = state.generate_non_inclusion_proof("e").unwrap();
+        assert_eq!(middle.left.as_ref().unwrap().leaf, "d");
+        assert_eq!(middle.right.as_ref().unwrap().leaf, "f");
+        assert!(state
+            .generate_non_inclusion_proof("a")
+            .unwrap()
+            .left
+            .is_none());
+        assert!(state
+            .generate_non_inclusion_proof("k")
+            .unwrap()
+            .right
+            .is_none());
+
+        let empty = NexusState::new().generate_non_inclusion_proof("a").unwrap();
+        assert!(verify_non_inclusion_proof(&empty, LeafHashing::default()));
+    }
+
+    #[test]
+    fn test_non_inclusion_proof_rejects_gapped_or_forged_bounds() {
+        let state = NexusState::new();
+        state.update_state_batch(&[
+            "b".to_string(),
+            "d".to_string(),
+            "f".to_string(),
+            "h".to_string(),
+        ]);
+
+        // Bounds that skip over a present leaf ("d") are not adjacent.
+        let mut gapped = state.generate_non_inclusion_proof("c").unwrap();
+        gapped.key = "e".to_string();
+        gapped.right = state.generate_non_inclusion_proof("g").unwrap().left;
+        assert!(!verify_non_inclusion_proof(&gapped, state.leaf_hashing()));
+
+        // Dropping the right bound requires the left one to be the last leaf.
+        let mut open = state.generate_non_inclusion_proof("c").unwrap();
+        open.right = None;
+        assert!(!verify_non_inclusion_proof(&open, state.leaf_hashing()));
+
+        // Dropping the left bound requires the right one to be the first leaf.
+        let mut open = state.generate_non_inclusion_proof("e").unwrap();
+        open.left = None;
+        assert!(!verify_non_inclusion_proof(&open, state.leaf_hashing()));
+
+        let mut present = state.generate_non_inclusion_proof("c").unwrap();
+        present.key = "d".to_string();
+        assert!(!verify_non_inclusion_proof(&present, state.leaf_hashing()));
+    }
+
+    #[test]
+    fn test_exported_leaves_recompute_same_root() {
+        let state = NexusState::new();
+        state.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+
+        let mut fetched = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = state.export_leaves_page(offset, 2);
+            assert_eq!(page.total, 3);
+            assert_eq!(page.root, state.get_state_root());
+            if page.leaves.is_empty() {
+                break;
+            }
+            offset += page.leaves.len();
+            fetched.extend(page.leaves);
+        }
+        assert_eq!(fetched, state.export_leaves());
+
+        let auditor = NexusState::new();
+        auditor.set_initial_leaves(fetched);
+        assert_eq!(auditor.get_state_root(), state.get_state_root());
+    }
+
+    #[test]
+    fn test_preview_root_matches_applied_root_without_mutating() {
+        for arity in [MerkleArity::Binary, MerkleArity::Quaternary] {
+            let state = NexusState::with_arity(arity);
+            state.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+            let root = state.get_state_root();
+            let pending = vec!["d".to_string(), format!("0X{}", "AB".repeat(32))];
+
+            let preview = state.preview_root(&pending);
+            assert_ne!(preview, root);
+            assert_eq!(state.get_state_root(), root);
+            assert_eq!(state.export_leaves().len(), 3);
+            assert_eq!(state.preview_root(&[]), root);
+
+            state.update_state_batch(&pending);
+            assert_eq!(state.get_state_root(), preview);
+        }
     }
 
     #[test]
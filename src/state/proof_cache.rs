@@ -0,0 +1,135 @@
+//! Least-recently-used cache of inclusion proofs keyed by `(root, leaf)`. A proof is
+//! only valid against the root it was built for, so entries never need invalidating:
+//! once the root moves on they stop being looked up and age out.
+
+use super::MerkleProof;
+use prometheus::{register_int_counter, IntCounter};
+use std::collections::{BTreeMap, HashMap};
+
+lazy_static::lazy_static! {
+    static ref PROOF_CACHE_HITS: IntCounter = register_int_counter!(
+        "nexus_proof_cache_hits_total",
+        "Inclusion proofs served from the proof cache"
+    )
+    .unwrap();
+    static ref PROOF_CACHE_MISSES: IntCounter = register_int_counter!(
+        "nexus_proof_cache_misses_total",
+        "Inclusion proof lookups that had to build the proof"
+    )
+    .unwrap();
+}
+
+type CacheKey = (String, String);
+
+#[derive(Debug, Default)]
+pub struct ProofCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, (MerkleProof, u64)>,
+    /// Last use of each entry; the first is evicted when the cache is full.
+    recency: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+impl ProofCache {
+    /// A cache holding up to `capacity` proofs; 0 disables caching.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The cached proof of `leaf` against `root`, marking it recently used.
+    pub fn get(&mut self, root: &str, leaf: &str) -> Option<MerkleProof> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let key = (root.to_string(), leaf.to_string());
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(&key) {
+            Some((proof, used)) => {
+                self.recency.remove(used);
+                *used = tick;
+                self.recency.insert(tick, key);
+                PROOF_CACHE_HITS.inc();
+                Some(proof.clone())
+            }
+            None => {
+                PROOF_CACHE_MISSES.inc();
+                None
+            }
+        }
+    }
+
+    /// Caches `proof` under its own root and leaf, evicting the least recently used
+    /// entry when full.
+    pub fn insert(&mut self, proof: MerkleProof) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = (proof.root.clone(), proof.leaf.clone());
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.remove(&key) {
+            self.recency.remove(&used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(key, (proof, self.tick));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof(root: &str, leaf: &str) -> MerkleProof {
+        MerkleProof {
+            leaf: leaf.to_string(),
+            path: Vec::new(),
+            root: root.to_string(),
+            levels: Vec::new(),
+            leaf_index: 0,
+            tree_size: 0,
+            leaf_hashing: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_least_recently_used_entry_is_evicted() {
+        let mut cache = ProofCache::new(2);
+        cache.insert(proof("r1", "a"));
+        cache.insert(proof("r1", "b"));
+        assert!(cache.get("r1", "a").is_some());
+        cache.insert(proof("r1", "c"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("r1", "b").is_none());
+        assert!(cache.get("r1", "a").is_some());
+        assert!(cache.get("r1", "c").is_some());
+        assert!(cache.get("r2", "a").is_none());
+    }
+
+    #[test]
+    fn test_zero_capacity_caches_nothing() {
+        let mut cache = ProofCache::new(0);
+        cache.insert(proof("r1", "a"));
+        assert!(cache.is_empty());
+        assert!(cache.get("r1", "a").is_none());
+    }
+}
@@ -0,0 +1,450 @@
+//! Portable snapshots of the state tree: the leaf set in commit order with the root it
+//! must reproduce, so a node can be rebuilt or bootstrapped without replaying the chain.
+//! A snapshot handed between nodes is signed by its exporter over [`StateSnapshot::digest`]
+//! and only installed when the signing key is trusted.
+
+use super::anchor::StateAnchor;
+use super::{sorted_root, LeafHashing, MerkleArity, NexusState, RootCommitment};
+use crate::stacks::StacksSigner;
+use crate::storage::migrations::embedded_schema_version;
+use crate::storage::store::NexusStore;
+use anyhow::Context;
+use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Format version written by [`StateSnapshot::capture`]; others are rejected on read.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Leaves per chunk of [`StateSnapshot::into_json_chunks`].
+pub const SNAPSHOT_LEAVES_PER_CHUNK: usize = 4096;
+
+/// Why [`install`] did not install a snapshot.
+#[derive(Debug)]
+pub enum InstallError {
+    /// Unsigned, or not signed by one of the trusted keys.
+    Untrusted(anyhow::Error),
+    /// Its version, schema, arity or leaf hashing differ from this node's, or its leaves
+    /// do not reproduce its root.
+    Invalid(anyhow::Error),
+    /// This node already has leaves.
+    NotEmpty,
+    /// Writing or loading it failed.
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for InstallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Untrusted(e) | Self::Invalid(e) | Self::Failed(e) => write!(f, "{:#}", e),
+            Self::NotEmpty => {
+                f.write_str("Snapshots can only be installed on a node without leaves")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InstallError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSignature {
+    /// Compressed SEC1 public key of the exporter (hex).
+    pub public_key: String,
+    /// 64-byte `r || s` ECDSA signature over the snapshot digest (hex).
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub version: u32,
+    /// Latest database migration of the exporting binary; the importer must match it.
+    pub schema_version: i64,
+    pub arity: MerkleArity,
+    /// Omitted for the default SHA-256, so older snapshots and their signatures still
+    /// read.
+    #[serde(default, skip_serializing_if = "LeafHashing::is_default")]
+    pub leaf_hashing: LeafHashing,
+    /// Sync watermark the leaves cover; sync resumes after it.
+    pub height: Option<u64>,
+    pub root: String,
+    /// Latest on-chain anchor of the exporter, so the importer can check continuity
+    /// from it.
+    #[serde(default)]
+    pub checkpoint: Option<StateAnchor>,
+    pub leaves: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SnapshotSignature>,
+}
+
+impl StateSnapshot {
+    pub fn capture(state: &NexusState, height: Option<u64>) -> Self {
+        let page = state.export_leaves_page(0, usize::MAX);
+        Self {
+            version: SNAPSHOT_VERSION,
+            schema_version: embedded_schema_version(),
+            arity: page.arity,
+            leaf_hashing: page.leaf_hashing,
+            height,
+            root: page.root,
+            checkpoint: None,
+            leaves: page.leaves,
+            signature: None,
+        }
+    }
+
+    /// SHA-256 of the snapshot's JSON without its signature. [`Self::sign`] signs it
+    /// as a message, so the ECDSA signature is over SHA-256 of this digest.
+    pub fn digest(&self) -> [u8; 32] {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        Sha256::digest(serde_json::to_vec(&unsigned).unwrap_or_default()).into()
+    }
+
+    pub fn sign(&mut self, signer: &StacksSigner) {
+        self.signature = Some(SnapshotSignature {
+            public_key: signer.public_key_hex(),
+            signature: signer.sign_message(&self.digest()),
+        });
+    }
+
+    /// Fails unless the snapshot is signed by one of `trusted_keys` and the signature
+    /// covers its current contents.
+    pub fn verify_signature(&self, trusted_keys: &[String]) -> anyhow::Result<()> {
+        if trusted_keys.is_empty() {
+            anyhow::bail!("No trusted snapshot keys are configured");
+        }
+        let signed = self.signature.as_ref().context("Snapshot is not signed")?;
+        if !trusted_keys
+            .iter()
+            .any(|key| key.eq_ignore_ascii_case(&signed.public_key))
+        {
+            anyhow::bail!("Snapshot is signed by untrusted key {}", signed.public_key);
+        }
+        let key = hex::decode(&signed.public_key)
+            .ok()
+            .and_then(|bytes| VerifyingKey::from_sec1_bytes(&bytes).ok())
+            .context("Snapshot signer key is not a valid public key")?;
+        let signature = hex::decode(&signed.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .context("Snapshot signature is malformed")?;
+        key.verify(&self.digest(), &signature)
+            .map_err(|_| anyhow::anyhow!("Snapshot signature does not verify"))
+    }
+
+    /// Rebuilds the tree from the leaves, failing unless it reproduces `root`.
+    pub fn verify(&self) -> anyhow::Result<NexusState> {
+        if self.version != SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "Unsupported snapshot version {} (expected {})",
+                self.version,
+                SNAPSHOT_VERSION
+            );
+        }
+        let schema_version = embedded_schema_version();
+        if self.schema_version != schema_version {
+            anyhow::bail!(
+                "Snapshot was exported at schema version {}, but this node is at {}",
+                self.schema_version,
+                schema_version
+            );
+        }
+        let state = NexusState::with_arity(self.arity).with_leaf_hashing(self.leaf_hashing);
+        state
+            .restore_state(self.leaves.clone(), self.root.clone())
+            .context("Snapshot leaves do not match the snapshot's root")?;
+        Ok(state)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Cannot write snapshot to {}", path.display()))
+    }
+
+    /// The snapshot as JSON in chunks: every field but the leaves, then the leaves
+    /// [`SNAPSHOT_LEAVES_PER_CHUNK`] at a time, so a large tree is never serialized into
+    /// one buffer. The leaves come last; the fields read back the same.
+    pub fn into_json_chunks(mut self) -> anyhow::Result<impl Iterator<Item = Vec<u8>>> {
+        let leaves = std::mem::take(&mut self.leaves);
+        let mut head = serde_json::to_value(&self)?;
+        head.as_object_mut()
+            .context("Snapshot does not serialize to an object")?
+            .remove("leaves");
+        let mut head = serde_json::to_vec(&head)?;
+        head.pop();
+        head.extend_from_slice(b",\"leaves\":[");
+        let mut leaves = leaves.into_iter().enumerate().peekable();
+        let body = std::iter::from_fn(move || {
+            leaves.peek()?;
+            let mut chunk = Vec::new();
+            for (i, leaf) in leaves.by_ref().take(SNAPSHOT_LEAVES_PER_CHUNK) {
+                if i > 0 {
+                    chunk.push(b',');
+                }
+                serde_json::to_writer(&mut chunk, &leaf).expect("a string serializes");
+            }
+            Some(chunk)
+        });
+        Ok(std::iter::once(head)
+            .chain(body)
+            .chain(std::iter::once(b"]}".to_vec())))
+    }
+
+    /// Reads and [verifies](Self::verify) a snapshot file.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Cannot read snapshot {}", path.display()))?;
+        let snapshot: Self = serde_json::from_slice(&bytes)
+            .with_context(|| format!("{} is not a state snapshot", path.display()))?;
+        snapshot.verify()?;
+        Ok(snapshot)
+    }
+}
+
+/// Captures `state` at the store's sync watermark with the latest confirmed anchor as
+/// its checkpoint, signed by `signer` when one is given.
+pub async fn export(
+    store: &dyn NexusStore,
+    state: &NexusState,
+    signer: Option<&StacksSigner>,
+) -> anyhow::Result<StateSnapshot> {
+    let mut snapshot = StateSnapshot::capture(state, store.sync_watermark().await?);
+    snapshot.checkpoint = store.last_confirmed_anchor().await?;
+    if let Some(signer) = signer {
+        snapshot.sign(signer);
+    }
+    Ok(snapshot)
+}
+
+/// Checks the signature against `trusted_keys`, the format and the root, then, with
+/// commits paused, makes the snapshot the base of an empty store in one transaction (see
+/// [`NexusStore::install_snapshot`]) and loads it into `state`. Nothing is written
+/// unless every check passes. The checks and the load, which hash every leaf, run off
+/// the async runtime.
+pub async fn install(
+    store: &dyn NexusStore,
+    state: &Arc<NexusState>,
+    snapshot: &StateSnapshot,
+    trusted_keys: &[String],
+) -> Result<(), InstallError> {
+    let checked = snapshot.clone();
+    let trusted_keys = trusted_keys.to_vec();
+    let (arity, leaf_hashing) = (state.arity(), state.leaf_hashing());
+    let commitment =
+        tokio::task::spawn_blocking(move || check(&checked, &trusted_keys, arity, leaf_hashing))
+            .await
+            .map_err(|e| InstallError::Failed(e.into()))??;
+
+    let _commit = state.lock_commits().await;
+    if state.leaf_count() > 0 {
+        return Err(InstallError::NotEmpty);
+    }
+    store
+        .install_snapshot(snapshot, &commitment)
+        .await
+        .map_err(|e| {
+            e.downcast::<InstallError>()
+                .unwrap_or_else(InstallError::Failed)
+        })?;
+    let (loaded, leaves, root) = (
+        state.clone(),
+        snapshot.leaves.clone(),
+        snapshot.root.clone(),
+    );
+    tokio::task::spawn_blocking(move || loaded.restore_state(leaves, root))
+        .await
+        .map_err(|e| InstallError::Failed(e.into()))?
+        .context("Installed snapshot does not rebuild to its root")
+        .map_err(InstallError::Failed)
+}
+
+/// The checks of [`install`], returning the commitment to record.
+fn check(
+    snapshot: &StateSnapshot,
+    trusted_keys: &[String],
+    arity: MerkleArity,
+    leaf_hashing: LeafHashing,
+) -> Result<RootCommitment, InstallError> {
+    snapshot
+        .verify_signature(trusted_keys)
+        .map_err(InstallError::Untrusted)?;
+    snapshot.verify().map_err(InstallError::Invalid)?;
+    if snapshot.arity != arity {
+        return Err(InstallError::Invalid(anyhow::anyhow!(
+            "Snapshot was taken with MERKLE_ARITY={}, but this node uses {}",
+            snapshot.arity,
+            arity
+        )));
+    }
+    if snapshot.leaf_hashing != leaf_hashing {
+        return Err(InstallError::Invalid(anyhow::anyhow!(
+            "Snapshot was taken with MERKLE_LEAF_HASHING={}, but this node uses {}",
+            snapshot.leaf_hashing,
+            leaf_hashing
+        )));
+    }
+    Ok(RootCommitment {
+        root: snapshot.root.clone(),
+        sorted_root: sorted_root(&snapshot.leaves, snapshot.leaf_hashing),
+        leaf_count: snapshot.leaves.len() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::anchor::AnchorStatus;
+    use crate::storage::repo::NewBlock;
+    use crate::storage::store::InMemoryStore;
+    use k256::ecdsa::SigningKey;
+
+    fn signer(seed: u8) -> StacksSigner {
+        StacksSigner::new(SigningKey::from_slice(&[seed; 32]).unwrap())
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_and_tamper_detection() {
+        let state = NexusState::with_arity(MerkleArity::Quaternary);
+        state.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+        let snapshot = StateSnapshot::capture(&state, Some(7));
+        assert_eq!(snapshot.leaves.len(), 3);
+        assert_eq!(
+            snapshot.verify().unwrap().get_state_root(),
+            state.get_state_root()
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("nexus-snapshot-{}.json", uuid::Uuid::new_v4()));
+        snapshot.write(&path).unwrap();
+        assert_eq!(StateSnapshot::read(&path).unwrap(), snapshot);
+
+        let mut tampered = snapshot.clone();
+        tampered.leaves.swap(0, 1);
+        tampered.write(&path).unwrap();
+        assert!(StateSnapshot::read(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        let future = StateSnapshot {
+            version: SNAPSHOT_VERSION + 1,
+            ..snapshot.clone()
+        };
+        assert!(future.verify().is_err());
+        let newer_schema = StateSnapshot {
+            schema_version: snapshot.schema_version + 1,
+            ..snapshot
+        };
+        assert!(newer_schema.verify().is_err());
+    }
+
+    #[test]
+    fn test_json_chunks_read_back_as_the_snapshot() {
+        let state = NexusState::new();
+        let leaves: Vec<String> = (0..SNAPSHOT_LEAVES_PER_CHUNK + 3)
+            .map(|i| format!("leaf-{}", i))
+            .collect();
+        state.update_state_batch(&leaves);
+        let mut snapshot = StateSnapshot::capture(&state, Some(9));
+        snapshot.sign(&signer(7));
+        for snapshot in [snapshot, StateSnapshot::capture(&NexusState::new(), None)] {
+            let chunks: Vec<Vec<u8>> = snapshot.clone().into_json_chunks().unwrap().collect();
+            let json = chunks.concat();
+            assert_eq!(
+                serde_json::from_slice::<StateSnapshot>(&json).unwrap(),
+                snapshot
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_from_one_node_installs_on_another() {
+        let source_store = InMemoryStore::new();
+        let source = NexusState::new();
+        source.update_state_batch(&["a".to_string(), "b".to_string(), "c".to_string()]);
+        source_store
+            .ingest_block(&NewBlock::microblock("0xb42", 42), &[])
+            .await
+            .unwrap();
+        source_store.advance_sync_watermark(42).await.unwrap();
+        let anchor = StateAnchor {
+            root: source.get_state_root(),
+            sorted_root: Some(source.get_sorted_root()),
+            height: 40,
+            leaf_count: 3,
+            tx_id: format!("0x{}", "ab".repeat(32)),
+            anchored_at: 0,
+            burn_height: None,
+            status: AnchorStatus::Confirmed,
+            confirmed_height: Some(41),
+            error: None,
+        };
+        source_store.record_anchor(&anchor).await.unwrap();
+
+        let exporter = signer(7);
+        let snapshot = export(&source_store, &source, Some(&exporter))
+            .await
+            .unwrap();
+        assert_eq!(snapshot.height, Some(42));
+        assert_eq!(snapshot.checkpoint.as_ref(), Some(&anchor));
+
+        let target_store = InMemoryStore::new();
+        let target = Arc::new(NexusState::new());
+        let trusted = vec![exporter.public_key_hex()];
+        assert!(matches!(
+            install(&target_store, &target, &snapshot, &[]).await,
+            Err(InstallError::Untrusted(_))
+        ));
+        assert!(install(
+            &target_store,
+            &target,
+            &snapshot,
+            &[signer(8).public_key_hex()]
+        )
+        .await
+        .is_err());
+        let mut forged = snapshot.clone();
+        forged.leaves.push("d".to_string());
+        assert!(install(&target_store, &target, &forged, &trusted)
+            .await
+            .is_err());
+        let mut wrong_arity =
+            StateSnapshot::capture(&NexusState::with_arity(MerkleArity::Quaternary), None);
+        wrong_arity.sign(&exporter);
+        assert!(matches!(
+            install(&target_store, &target, &wrong_arity, &trusted).await,
+            Err(InstallError::Invalid(_))
+        ));
+
+        install(&target_store, &target, &snapshot, &trusted)
+            .await
+            .unwrap();
+        assert_eq!(target.get_state_root(), source.get_state_root());
+        assert_eq!(target_store.sync_watermark().await.unwrap(), Some(42));
+        assert_eq!(
+            target_store.root_leaf_count(&snapshot.root).await.unwrap(),
+            Some(3)
+        );
+        // The exporter's confirmation is not taken on trust.
+        assert_eq!(target_store.last_confirmed_anchor().await.unwrap(), None);
+        let recorded = target_store.last_anchor().await.unwrap().unwrap();
+        assert_eq!(recorded.status, AnchorStatus::Broadcast);
+        assert_eq!(recorded.confirmed_height, None);
+
+        // A node with leaves is refused, and nothing changes.
+        let mut later = StateSnapshot {
+            height: Some(50),
+            ..snapshot.clone()
+        };
+        later.sign(&exporter);
+        assert!(matches!(
+            install(&target_store, &target, &later, &trusted).await,
+            Err(InstallError::NotEmpty)
+        ));
+        assert_eq!(target_store.sync_watermark().await.unwrap(), Some(42));
+    }
+}
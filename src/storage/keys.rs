@@ -0,0 +1,92 @@
+//! Redis key and channel names. Every name here is relative: [`Storage::redis_key`]
+//! prefixes it with the deployment's `REDIS_NAMESPACE` (default `nexus:<network>:`), so
+//! several deployments can share one Redis without reading each other's flags.
+//!
+//! [`Storage::redis_key`]: crate::storage::Storage::redis_key
+
+pub const STATE_ROOT: &str = "state_root";
+pub const SAFETY_MODE: &str = "safety_mode";
+/// Why Safety Mode is active; a [`crate::safety::SafetyTrigger`] string.
+pub const SAFETY_REASON: &str = "safety_reason";
+pub const SAFETY_DRILL: &str = "safety_drill";
+pub const DRIFT: &str = "drift";
+pub const DRIFT_RAW: &str = "drift:raw";
+pub const DRIFT_SMOOTHED: &str = "drift:smoothed";
+pub const L1_UNREACHABLE: &str = "l1_unreachable";
+pub const ORACLE_STALE: &str = "oracle_stale";
+
+/// Counter holding the last rebalance instruction nonce.
+pub const REBALANCE_NONCE: &str = "rebalance:nonce";
+
+/// Pub/sub channel for safety transitions.
+pub const EVENTS_CHANNEL: &str = "events";
+
+/// Billing hash for one API key.
+pub fn api_key(key: &str) -> String {
+    format!("apikey:{}", key)
+}
+
+/// Recorded submission outcome for a caller-scoped idempotency key.
+pub fn idempotency(scoped_key: &str) -> String {
+    format!("idempotency:{}", scoped_key)
+}
+
+/// Sorted set of the tx ids of `submitter`'s recently admitted off-chain requests,
+/// scored by time (ms).
+pub fn submitter_velocity(submitter: &str) -> String {
+    format!("executor:velocity:{}", submitter)
+}
+
+/// Lock `name` shared by every instance, holding its holder's token.
+pub fn lock(name: &str) -> String {
+    format!("lock:{}", name)
+}
+
+/// Fixed keys written as `nexus:<name>` before keys were namespaced.
+pub const LEGACY_NEXUS_KEYS: &[&str] = &[
+    STATE_ROOT,
+    SAFETY_MODE,
+    SAFETY_REASON,
+    SAFETY_DRILL,
+    DRIFT,
+    DRIFT_RAW,
+    DRIFT_SMOOTHED,
+    L1_UNREACHABLE,
+    ORACLE_STALE,
+    crate::storage::store::EXECUTOR_STATS_KEY,
+    crate::storage::store::EXECUTOR_NONCES_KEY,
+    crate::storage::store::ACTIVE_VAULTS_KEY,
+    crate::oracle::ORACLE_PPP_CACHE_KEY,
+];
+
+/// Per-record key families from before namespacing, as `SCAN` patterns.
+pub const LEGACY_PATTERNS: &[&str] = &[
+    "apikey:*",
+    "nostr_dedup:*",
+    "dlc_bond:*",
+    "nexus:erp:attestation:nonce:v1:*",
+];
+
+/// The namespaced name for a pre-namespacing key: the old `nexus:` prefix is dropped
+/// and everything else kept.
+pub fn legacy_relative_name(legacy: &str) -> &str {
+    legacy.strip_prefix("nexus:").unwrap_or(legacy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_names_map_into_namespace() {
+        assert_eq!(legacy_relative_name("nexus:drift:raw"), DRIFT_RAW);
+        assert_eq!(legacy_relative_name("apikey:k1"), api_key("k1"));
+        assert_eq!(
+            legacy_relative_name("nexus:erp:attestation:nonce:v1:ab"),
+            "erp:attestation:nonce:v1:ab"
+        );
+        for name in LEGACY_NEXUS_KEYS {
+            assert!(!name.starts_with("nexus:"), "{} is already prefixed", name);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! [NEXUS-STORE-03] Applied/pending migration reporting, so a binary and a database
+//! that disagree about the schema fail loudly instead of confusingly.
+
+use crate::config::ENV_AUTO_MIGRATE;
+use crate::storage::Storage;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::{Connection, PgConnection};
+
+/// Migrations embedded in this binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Latest migration embedded in this binary: the schema it expects.
+pub fn embedded_schema_version() -> i64 {
+    MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// A row of `_sqlx_migrations`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    /// Hex checksum recorded when the migration ran.
+    pub checksum: String,
+    pub installed_on: DateTime<Utc>,
+    pub success: bool,
+    /// The copy embedded in this binary no longer matches the recorded checksum.
+    pub checksum_mismatch: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    /// Highest successfully applied version; `None` on an empty database.
+    pub schema_version: Option<i64>,
+    pub applied: Vec<AppliedMigration>,
+    pub has_pending: bool,
+    /// Versions known to this binary that the database has not applied.
+    pub pending: Vec<i64>,
+    /// Versions applied to the database that this binary does not know (it is older
+    /// than the schema).
+    pub unknown: Vec<i64>,
+}
+
+impl Storage {
+    /// Run database migrations
+    pub async fn run_migrations(&self) -> anyhow::Result<()> {
+        let mut conn = self.migration_connection().await?;
+        MIGRATOR.run(&mut conn).await?;
+        conn.close().await?;
+        Ok(())
+    }
+
+    /// A connection outside the pool with no statement timeout: backfills and index
+    /// builds may legitimately run longer than `DATABASE_STATEMENT_TIMEOUT_MS`, and a
+    /// pooled session would keep the lifted limit after the migration.
+    async fn migration_connection(&self) -> anyhow::Result<PgConnection> {
+        let mut conn = PgConnection::connect_with(&self.pg_pool.connect_options()).await?;
+        sqlx::query("SET statement_timeout = 0")
+            .execute(&mut conn)
+            .await?;
+        Ok(conn)
+    }
+
+    /// Versions of embedded migrations not yet applied to the database.
+    pub async fn pending_migrations(&self) -> anyhow::Result<Vec<i64>> {
+        Ok(self.migration_status().await?.pending)
+    }
+
+    /// Installed migrations and how they compare with those embedded in this binary.
+    pub async fn migration_status(&self) -> anyhow::Result<MigrationStatus> {
+        self.migration_status_for(&MIGRATOR).await
+    }
+
+    /// Like [`Storage::migration_status`], against an arbitrary migration set.
+    pub async fn migration_status_for(
+        &self,
+        migrator: &Migrator,
+    ) -> anyhow::Result<MigrationStatus> {
+        let rows: Vec<(i64, String, Vec<u8>, DateTime<Utc>, bool)> =
+            if self.migrations_table_exists().await? {
+                sqlx::query_as(
+                    "SELECT version, description, checksum, installed_on, success
+                 FROM _sqlx_migrations ORDER BY version",
+                )
+                .fetch_all(&self.pg_pool)
+                .await?
+            } else {
+                Vec::new()
+            };
+
+        let known: Vec<_> = migrator
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .collect();
+
+        let applied: Vec<AppliedMigration> = rows
+            .into_iter()
+            .map(
+                |(version, description, checksum, installed_on, success)| AppliedMigration {
+                    checksum_mismatch: known
+                        .iter()
+                        .any(|m| m.version == version && *m.checksum != *checksum),
+                    checksum: hex::encode(&checksum),
+                    version,
+                    description,
+                    installed_on,
+                    success,
+                },
+            )
+            .collect();
+
+        let succeeded = |version: i64| applied.iter().any(|a| a.version == version && a.success);
+        let pending: Vec<i64> = known
+            .iter()
+            .map(|m| m.version)
+            .filter(|v| !succeeded(*v))
+            .collect();
+        let unknown = applied
+            .iter()
+            .map(|a| a.version)
+            .filter(|v| !known.iter().any(|m| m.version == *v))
+            .collect();
+
+        Ok(MigrationStatus {
+            schema_version: applied
+                .iter()
+                .filter(|a| a.success)
+                .map(|a| a.version)
+                .max(),
+            has_pending: !pending.is_empty(),
+            pending,
+            unknown,
+            applied,
+        })
+    }
+
+    /// Highest successfully applied migration version; `None` on an empty database.
+    pub async fn schema_version(&self) -> anyhow::Result<Option<i64>> {
+        if !self.migrations_table_exists().await? {
+            return Ok(None);
+        }
+        Ok(
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = TRUE")
+                .fetch_one(&self.pg_pool)
+                .await?,
+        )
+    }
+
+    /// Fails, listing the pending versions, unless every embedded migration has been applied.
+    pub async fn require_migrated(&self) -> anyhow::Result<()> {
+        self.require_migrated_for(&MIGRATOR).await
+    }
+
+    pub async fn require_migrated_for(&self, migrator: &Migrator) -> anyhow::Result<()> {
+        let status = self.migration_status_for(migrator).await?;
+        if status.has_pending {
+            anyhow::bail!(
+                "{} pending migration(s) {:?} and {}=false; apply them or enable {}",
+                status.pending.len(),
+                status.pending,
+                ENV_AUTO_MIGRATE,
+                ENV_AUTO_MIGRATE
+            );
+        }
+        Ok(())
+    }
+
+    async fn migrations_table_exists(&self) -> anyhow::Result<bool> {
+        Ok(
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pg_pool)
+                .await?,
+        )
+    }
+}
@@ -0,0 +1,550 @@
+//! [NEXUS-STORE-01] Typed repository for the `stacks_blocks` and `stacks_transactions` tables.
+//! All SQL touching these tables lives here; callers work with the structs below.
+//! Queries are checked at runtime (`query_as` + `FromRow`) so the crate still builds
+//! without a database or offline query cache.
+
+use crate::latency::timed_query;
+use crate::sync::decoder::{self, ConxianAction};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use sqlx::{PgConnection, PgExecutor};
+use std::collections::HashSet;
+
+/// A row of `stacks_blocks`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoredBlock {
+    pub hash: String,
+    pub height: i64,
+    /// `microblock` or `burn_block`.
+    #[sqlx(rename = "type")]
+    pub block_type: String,
+    /// `soft`, `hard` or `orphaned`.
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A row of `stacks_transactions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoredTransaction {
+    pub tx_id: String,
+    pub block_hash: String,
+    pub payload: Option<String>,
+    pub sender: Option<String>,
+    /// Decoded [`ConxianAction`], stored by name.
+    pub action: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StoredTransaction {
+    pub fn action(&self) -> Option<ConxianAction> {
+        self.action.as_deref()?.parse().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewBlock {
+    pub hash: String,
+    pub height: u64,
+    pub block_type: String,
+    pub state: String,
+    /// Chain timestamp, when the source reports one.
+    pub block_time: Option<DateTime<Utc>>,
+}
+
+impl NewBlock {
+    /// A microblock, soft until a burn block buries it.
+    pub fn microblock(hash: &str, height: u64) -> Self {
+        Self {
+            hash: hash.to_string(),
+            height,
+            block_type: "microblock".to_string(),
+            state: "soft".to_string(),
+            block_time: None,
+        }
+    }
+
+    /// A freshly observed burn block, soft until buried by enough confirmations.
+    pub fn burn_block(hash: &str, height: u64) -> Self {
+        Self {
+            hash: hash.to_string(),
+            height,
+            block_type: "burn_block".to_string(),
+            state: "soft".to_string(),
+            block_time: None,
+        }
+    }
+
+    /// The block stamped with its chain time.
+    pub fn with_block_time(mut self, block_time: Option<DateTime<Utc>>) -> Self {
+        self.block_time = block_time;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewTransaction {
+    pub tx_id: String,
+    pub block_hash: String,
+    pub payload: Option<String>,
+    pub sender: Option<String>,
+    pub action: Option<ConxianAction>,
+}
+
+impl NewTransaction {
+    /// A transaction whose `action` is decoded from its Stacks API `payload`, counting
+    /// only calls to `contracts`.
+    pub fn decoded(
+        tx_id: &str,
+        block_hash: &str,
+        payload: Option<String>,
+        sender: Option<String>,
+        contracts: &[String],
+    ) -> Self {
+        Self {
+            tx_id: tx_id.to_string(),
+            block_hash: block_hash.to_string(),
+            action: payload
+                .as_deref()
+                .and_then(|payload| decoder::decode_payload(payload, contracts)),
+            payload,
+            sender,
+        }
+    }
+}
+
+/// Transaction and block counts, excluding orphaned blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChainCounts {
+    pub transactions: u64,
+    pub blocks: u64,
+}
+
+/// Repository over a Postgres pool; cheap to construct per call.
+pub struct Repo<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> Repo<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Inserts `block`, ignoring duplicates. Returns whether a row was written.
+    pub async fn insert_block(&self, block: &NewBlock) -> sqlx::Result<bool> {
+        timed_query("insert_block", insert_block(self.pool, block)).await
+    }
+
+    /// Inserts `transactions` in one statement, ignoring duplicates. Returns rows written.
+    pub async fn insert_transactions_batch(
+        &self,
+        transactions: &[NewTransaction],
+    ) -> sqlx::Result<u64> {
+        timed_query(
+            "insert_transactions_batch",
+            insert_transactions(self.pool, transactions),
+        )
+        .await
+        .map(|written| written.len() as u64)
+    }
+
+    /// Writes `block` and its `transactions` in one database transaction, ignoring rows
+    /// already stored so a retried block is a no-op. Returns the ids of the transactions
+    /// written, in input order. The watermark is left alone: it only moves once the
+    /// caller has finished with the height; see [`Repo::advance_sync_watermark`].
+    pub async fn ingest_block(
+        &self,
+        block: &NewBlock,
+        transactions: &[NewTransaction],
+    ) -> sqlx::Result<Vec<String>> {
+        timed_query("ingest_block", async {
+            let mut db_tx = self.pool.begin().await?;
+            insert_block(&mut *db_tx, block).await?;
+            let written = insert_transactions(&mut *db_tx, transactions).await?;
+            db_tx.commit().await?;
+            Ok(written)
+        })
+        .await
+    }
+
+    /// Records `height` as fully processed and moves the watermark over the contiguous
+    /// run of processed heights that follows it; see [`advance_watermark`].
+    pub async fn advance_sync_watermark(&self, height: u64) -> sqlx::Result<()> {
+        timed_query("advance_sync_watermark", async {
+            let mut db_tx = self.pool.begin().await?;
+            advance_watermark(&mut db_tx, height).await?;
+            db_tx.commit().await
+        })
+        .await
+    }
+
+    /// Highest height whose block and transactions have fully committed, if any.
+    pub async fn sync_watermark(&self) -> sqlx::Result<Option<u64>> {
+        let height: Option<i64> = timed_query(
+            "sync_watermark",
+            sqlx::query_scalar("SELECT processed_height FROM sync_progress WHERE id = 1")
+                .fetch_optional(self.pool),
+        )
+        .await?;
+        Ok(height.map(|h| h.max(0) as u64))
+    }
+
+    /// Sets the watermark to `height` unless one exists. Returns whether it was set.
+    pub async fn seed_sync_watermark(&self, height: u64) -> sqlx::Result<bool> {
+        let result = timed_query(
+            "seed_sync_watermark",
+            sqlx::query(
+                "INSERT INTO sync_progress (id, processed_height) VALUES (1, $1)
+                 ON CONFLICT (id) DO NOTHING",
+            )
+            .bind(height as i64)
+            .execute(self.pool),
+        )
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn get_block(&self, hash: &str) -> sqlx::Result<Option<StoredBlock>> {
+        timed_query(
+            "get_block",
+            sqlx::query_as::<_, StoredBlock>(
+                "SELECT hash, height, type, state, created_at FROM stacks_blocks WHERE hash = $1",
+            )
+            .bind(hash)
+            .fetch_optional(self.pool),
+        )
+        .await
+    }
+
+    pub async fn transactions_in_block(
+        &self,
+        block_hash: &str,
+    ) -> sqlx::Result<Vec<StoredTransaction>> {
+        timed_query(
+            "transactions_in_block",
+            sqlx::query_as::<_, StoredTransaction>(
+                "SELECT tx_id, block_hash, payload, sender, action, created_at
+             FROM stacks_transactions WHERE block_hash = $1 ORDER BY tx_id",
+            )
+            .bind(block_hash)
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+
+    /// Up to `limit` of `sender`'s transactions, most recently committed first.
+    pub async fn transactions_by_sender(
+        &self,
+        sender: &str,
+        limit: u32,
+    ) -> sqlx::Result<Vec<StoredTransaction>> {
+        timed_query(
+            "transactions_by_sender",
+            sqlx::query_as::<_, StoredTransaction>(
+                "SELECT tx_id, block_hash, payload, sender, action, created_at
+             FROM stacks_transactions WHERE sender = $1 ORDER BY seq DESC LIMIT $2",
+            )
+            .bind(sender)
+            .bind(limit as i64)
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+
+    /// How many of `sender`'s transactions landed in non-orphaned blocks whose chain
+    /// time is at or after `since`. Blocks without a chain time are not counted.
+    pub async fn sender_transactions_since(
+        &self,
+        sender: &str,
+        since: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let count: i64 = timed_query(
+            "sender_transactions_since",
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM stacks_transactions t \
+                 JOIN stacks_blocks b ON b.hash = t.block_hash \
+                 WHERE t.sender = $1 AND b.block_time >= $2 AND b.state != 'orphaned'",
+            )
+            .bind(sender)
+            .bind(since)
+            .fetch_one(self.pool),
+        )
+        .await?;
+        Ok(count.max(0) as u64)
+    }
+
+    /// Highest recorded block height, including orphaned blocks; 0 when empty.
+    pub async fn max_block_height(&self) -> sqlx::Result<u64> {
+        let height: Option<i64> = timed_query(
+            "max_block_height",
+            sqlx::query_scalar("SELECT MAX(height) FROM stacks_blocks").fetch_one(self.pool),
+        )
+        .await?;
+        Ok(height.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Newest chain time among non-orphaned blocks; `None` when none reports one.
+    pub async fn last_block_time(&self) -> sqlx::Result<Option<DateTime<Utc>>> {
+        timed_query(
+            "last_block_time",
+            sqlx::query_scalar(
+                "SELECT MAX(block_time) FROM stacks_blocks WHERE state != 'orphaned'",
+            )
+            .fetch_one(self.pool),
+        )
+        .await
+    }
+
+    /// Highest height among non-orphaned blocks; 0 when empty.
+    pub async fn max_processed_height(&self) -> sqlx::Result<u64> {
+        let height: Option<i64> = timed_query(
+            "max_processed_height",
+            sqlx::query_scalar("SELECT MAX(height) FROM stacks_blocks WHERE state != 'orphaned'")
+                .fetch_one(self.pool),
+        )
+        .await?;
+        Ok(height.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Highest burn block that has reached hard finality; 0 when none has.
+    pub async fn max_hard_burn_height(&self) -> sqlx::Result<u64> {
+        let height: Option<i64> = timed_query("max_hard_burn_height", sqlx::query_scalar(
+            "SELECT MAX(height) FROM stacks_blocks WHERE type = 'burn_block' AND state = 'hard'",
+        )
+        .fetch_one(self.pool))
+        .await?;
+        Ok(height.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Transactions in non-orphaned blocks, and the number of such blocks.
+    pub async fn chain_counts(&self) -> sqlx::Result<ChainCounts> {
+        let (transactions, blocks): (i64, i64) = timed_query(
+            "chain_counts",
+            sqlx::query_as(
+                "SELECT \
+                (SELECT COUNT(*) FROM stacks_transactions t \
+                 JOIN stacks_blocks b ON t.block_hash = b.hash \
+                 WHERE b.state != 'orphaned') AS tx_count, \
+                (SELECT COUNT(*) FROM stacks_blocks WHERE state != 'orphaned') AS block_count",
+            )
+            .fetch_one(self.pool),
+        )
+        .await?;
+        Ok(ChainCounts {
+            transactions: transactions as u64,
+            blocks: blocks as u64,
+        })
+    }
+
+    /// Promotes soft blocks at or below `height` to hard. Returns the number promoted.
+    pub async fn finalize_blocks_up_to(&self, height: u64) -> sqlx::Result<u64> {
+        let result = timed_query(
+            "finalize_blocks_up_to",
+            sqlx::query(
+                "UPDATE stacks_blocks SET state = 'hard'
+             WHERE state = 'soft' AND height <= $1",
+            )
+            .bind(height as i64)
+            .execute(self.pool),
+        )
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Latest MEV audit arrival time for a transaction in a hard block.
+    pub async fn last_hard_audit_arrival(&self) -> sqlx::Result<Option<DateTime<Utc>>> {
+        timed_query(
+            "last_hard_audit_arrival",
+            sqlx::query_scalar(
+                "SELECT MAX(a.arrival_time) FROM me_audit_log a \
+             JOIN stacks_transactions t ON t.tx_id = a.tx_id \
+             JOIN stacks_blocks b ON b.hash = t.block_hash \
+             WHERE b.state = 'hard'",
+            )
+            .fetch_one(self.pool),
+        )
+        .await
+    }
+
+    /// Transactions per day over the last `days` days, oldest first.
+    pub async fn daily_transaction_counts(&self, days: i32) -> sqlx::Result<Vec<(String, i64)>> {
+        timed_query("daily_transaction_counts", sqlx::query_as(
+            "SELECT to_char(date_trunc('day', created_at), 'YYYY-MM-DD') AS day, COUNT(*) AS count
+             FROM stacks_transactions
+             WHERE created_at >= NOW() - INTERVAL '1 day' * $1::int
+             GROUP BY 1 ORDER BY 1 ASC",
+        )
+        .bind(days)
+        .fetch_all(self.pool))
+        .await
+    }
+
+    /// Distinct senders per day over the last `days` days, oldest first.
+    pub async fn daily_active_senders(&self, days: i32) -> sqlx::Result<Vec<(String, i64)>> {
+        timed_query("daily_active_senders", sqlx::query_as(
+            "SELECT to_char(date_trunc('day', created_at), 'YYYY-MM-DD') AS day, COUNT(DISTINCT sender) AS count
+             FROM stacks_transactions
+             WHERE created_at >= NOW() - INTERVAL '1 day' * $1::int
+             GROUP BY 1 ORDER BY 1 ASC",
+        )
+        .bind(days)
+        .fetch_all(self.pool))
+        .await
+    }
+
+    /// Number of senders per activity tier (`Whale`, `Shark`, `Shrimp`).
+    pub async fn sender_tiers(&self) -> sqlx::Result<Vec<(String, i64)>> {
+        timed_query(
+            "sender_tiers",
+            sqlx::query_as(
+                "SELECT
+                CASE
+                    WHEN count >= 100 THEN 'Whale'
+                    WHEN count >= 10 THEN 'Shark'
+                    ELSE 'Shrimp'
+                END AS tier,
+                COUNT(*) AS count
+             FROM (
+                SELECT sender, COUNT(*) AS count
+                FROM stacks_transactions
+                GROUP BY sender
+             ) AS counts
+             GROUP BY 1",
+            )
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+}
+
+async fn insert_block<'e, E: PgExecutor<'e>>(executor: E, block: &NewBlock) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO stacks_blocks (hash, height, type, state, block_time)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (hash) DO NOTHING",
+    )
+    .bind(&block.hash)
+    .bind(block.height as i64)
+    .bind(&block.block_type)
+    .bind(&block.state)
+    .bind(block.block_time)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Records `height` as ingested and moves the watermark to the end of the run of
+/// ingested heights that follows it, so it never passes a height that is missing. On a
+/// store without a watermark, the first height ingested starts it.
+async fn advance_watermark(conn: &mut PgConnection, height: u64) -> sqlx::Result<()> {
+    sqlx::query(
+        "INSERT INTO sync_progress (id, processed_height) VALUES (1, $1)
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(height as i64)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("INSERT INTO sync_processed_heights (height) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(height as i64)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "WITH RECURSIVE run(height) AS (
+             SELECT processed_height FROM sync_progress WHERE id = 1
+             UNION ALL
+             SELECT run.height + 1 FROM run
+             JOIN sync_processed_heights p ON p.height = run.height + 1
+         )
+         UPDATE sync_progress
+         SET processed_height = (SELECT MAX(height) FROM run), updated_at = NOW()
+         WHERE id = 1",
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "DELETE FROM sync_processed_heights
+         WHERE height <= (SELECT processed_height FROM sync_progress WHERE id = 1)",
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Inserts `transactions`, ignoring duplicates. Returns the ids written, in input order.
+async fn insert_transactions<'e, E: PgExecutor<'e>>(
+    executor: E,
+    transactions: &[NewTransaction],
+) -> sqlx::Result<Vec<String>> {
+    if transactions.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tx_ids: Vec<&str> = transactions.iter().map(|t| t.tx_id.as_str()).collect();
+    let block_hashes: Vec<&str> = transactions.iter().map(|t| t.block_hash.as_str()).collect();
+    let payloads: Vec<Option<&str>> = transactions.iter().map(|t| t.payload.as_deref()).collect();
+    let senders: Vec<Option<&str>> = transactions.iter().map(|t| t.sender.as_deref()).collect();
+    let actions: Vec<Option<&str>> = transactions
+        .iter()
+        .map(|t| t.action.map(ConxianAction::as_str))
+        .collect();
+
+    let written: HashSet<String> = sqlx::query_scalar(
+        "INSERT INTO stacks_transactions (tx_id, block_hash, payload, sender, action)
+         SELECT tx_id, block_hash, payload, sender, action
+         FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::TEXT[], $5::TEXT[])
+             WITH ORDINALITY AS t(tx_id, block_hash, payload, sender, action, n)
+         ORDER BY n
+         ON CONFLICT (tx_id) DO NOTHING
+         RETURNING tx_id",
+    )
+    .bind(&tx_ids)
+    .bind(&block_hashes)
+    .bind(&payloads)
+    .bind(&senders)
+    .bind(&actions)
+    .fetch_all(executor)
+    .await?
+    .into_iter()
+    .collect();
+    Ok(transactions
+        .iter()
+        .filter(|t| written.contains(&t.tx_id))
+        .map(|t| t.tx_id.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burn_block_starts_soft() {
+        let block = NewBlock::burn_block("0xabc", 42);
+        assert_eq!(block.block_type, "burn_block");
+        assert_eq!(block.state, "soft");
+        assert_eq!(block.height, 42);
+    }
+
+    #[test]
+    fn test_decoded_transaction_carries_action() {
+        let payload = r#"{"tx_type":"contract_call","contract_call":{"contract_id":"SP000.vault","function_name":"deposit"}}"#;
+        let contracts = vec!["SP000.vault".to_string()];
+        let tx =
+            NewTransaction::decoded("tx", "0xabc", Some(payload.to_string()), None, &contracts);
+        assert_eq!(tx.action, Some(ConxianAction::Deposit));
+        assert_eq!(
+            NewTransaction::decoded("tx", "0xabc", Some(payload.to_string()), None, &[]).action,
+            None
+        );
+        assert_eq!(
+            NewTransaction::decoded("tx", "0xabc", None, None, &contracts).action,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_transaction_batch_skips_database() {
+        let storage = crate::storage::Storage::for_tests();
+        let written = storage.repo().insert_transactions_batch(&[]).await.unwrap();
+        assert_eq!(written, 0);
+    }
+}
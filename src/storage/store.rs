@@ -0,0 +1,2794 @@
+//! [NEXUS-STORE-02] Storage operations the sync, safety and executor services depend on.
+//! [`Storage`] implements them against Postgres and Redis; [`InMemoryStore`] keeps
+//! everything in process so service logic can be tested without either.
+
+use crate::events::NexusEvent;
+use crate::executor::fixed::Ratio;
+use crate::executor::idempotency::{IdempotencyEntry, IdempotentOutcome};
+use crate::executor::rebalance::LEGACY_REBALANCE_NONCE_SENDER;
+use crate::executor::{
+    ExecutionFilter, ExecutionOutcome, ExecutionRecord, ExecutionRequest, ExecutionStatus,
+    ExecutorStats, FinalityLevel, RejectReason, VaultStatus,
+};
+use crate::latency::timed_query;
+use crate::oracle::aggregator::PppState;
+use crate::oracle::ORACLE_PPP_CACHE_KEY;
+use crate::safety::{SafetyIncident, SafetyTrigger};
+use crate::state::anchor::{AnchorStatus, StateAnchor};
+use crate::state::snapshot::{InstallError, StateSnapshot};
+use crate::state::{canonical_leaf, RootCommitment};
+use crate::storage::keys;
+use crate::storage::repo::{ChainCounts, NewBlock, NewTransaction, StoredTransaction};
+use crate::storage::Storage;
+use crate::sync::decoder::OraclePriceUpdate;
+use crate::sync::vault_indexer::{EventPosition, VaultRecord};
+use crate::sync::DeadLetter;
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Redis hash of executor outcome counters, keyed by [`ExecutionOutcome::stat_key`].
+pub const EXECUTOR_STATS_KEY: &str = "executor:stats";
+
+/// Redis hash of the highest accepted execution nonce, keyed by sender.
+pub const EXECUTOR_NONCES_KEY: &str = "executor:nonces";
+
+/// Redis hash caching each vault's [`VaultStatus`] JSON, keyed by vault id.
+pub const ACTIVE_VAULTS_KEY: &str = "active_vaults";
+
+/// Drops entries older than the window and adds `ARGV[3]` unless `ARGV[4]` submissions
+/// are already in it, returning 1 if it was added, in one step so concurrent
+/// submissions cannot both slip under the limit.
+const ADMIT_SUBMISSION_SCRIPT: &str = r#"
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+if redis.call('ZCARD', KEYS[1]) >= tonumber(ARGV[4]) then
+  return 0
+end
+redis.call('ZADD', KEYS[1], now, ARGV[3])
+redis.call('PEXPIRE', KEYS[1], window)
+return 1
+"#;
+
+/// Deletes `KEYS[1]` only while it holds `ARGV[1]`, so a lock that expired and was
+/// taken by another holder is left alone.
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Extends `KEYS[1]` to `ARGV[2]` ms if it still holds the token `ARGV[1]`.
+const RENEW_LOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+/// Sets `KEYS[1][ARGV[1]]` to `ARGV[2]` only if it exceeds the stored nonce. Both are
+/// canonical decimal `u64`s, compared by length then lexically to stay exact above 2^53.
+const CLAIM_NONCE_SCRIPT: &str = r#"
+local current = redis.call('HGET', KEYS[1], ARGV[1])
+local nonce = ARGV[2]
+if current and (#current > #nonce or (#current == #nonce and current >= nonce)) then
+  return 0
+end
+redis.call('HSET', KEYS[1], ARGV[1], nonce)
+return 1
+"#;
+
+/// Steps `KEYS[1][ARGV[1]]` back from `ARGV[2]` to `ARGV[3]`, or removes it when
+/// `ARGV[3]` is empty, only while it still holds `ARGV[2]`.
+const RELEASE_NONCE_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], ARGV[1]) ~= ARGV[2] then
+  return 0
+end
+if ARGV[3] == '' then
+  redis.call('HDEL', KEYS[1], ARGV[1])
+else
+  redis.call('HSET', KEYS[1], ARGV[1], ARGV[3])
+end
+return 1
+"#;
+
+/// Increments the rebalance nonce counter `KEYS[1]`, first starting it from the nonce
+/// claimed under `ARGV[1]` in the executor nonce hash `KEYS[2]`, where instruction
+/// nonces lived before the counter. `INCR` fails rather than wrap once the counter
+/// reaches `i64::MAX`.
+const NEXT_REBALANCE_NONCE_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+  local legacy = redis.call('HGET', KEYS[2], ARGV[1])
+  if legacy then
+    redis.call('SET', KEYS[1], legacy)
+  end
+end
+return redis.call('INCR', KEYS[1])
+"#;
+
+/// The stored form of a reservation, compared verbatim when it is released.
+fn pending_idempotency_json(request_hash: &str) -> serde_json::Result<String> {
+    serde_json::to_string(&IdempotencyEntry::Pending {
+        request_hash: request_hash.to_string(),
+    })
+}
+
+#[async_trait]
+pub trait NexusStore: Send + Sync {
+    // Blocks and transactions.
+    /// Writes `block` and `transactions`, skipping rows already stored. Returns the ids of
+    /// the transactions written, in input order.
+    async fn ingest_block(
+        &self,
+        block: &NewBlock,
+        transactions: &[NewTransaction],
+    ) -> anyhow::Result<Vec<String>>;
+    async fn insert_block(&self, block: &NewBlock) -> anyhow::Result<bool>;
+    async fn finalize_blocks_up_to(&self, height: u64) -> anyhow::Result<u64>;
+    async fn max_block_height(&self) -> anyhow::Result<u64>;
+    /// Highest height among non-orphaned blocks; 0 when empty.
+    async fn max_processed_height(&self) -> anyhow::Result<u64>;
+    /// Newest chain time among non-orphaned blocks; `None` when none reports one.
+    async fn last_block_time(&self) -> anyhow::Result<Option<DateTime<Utc>>>;
+    async fn chain_counts(&self) -> anyhow::Result<ChainCounts>;
+    /// Up to `limit` of `sender`'s transactions, most recently ingested first.
+    async fn transactions_by_sender(
+        &self,
+        sender: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<StoredTransaction>>;
+    /// How many of `sender`'s transactions landed in non-orphaned blocks whose chain
+    /// time is at or after `since`.
+    async fn sender_transactions_since(
+        &self,
+        sender: &str,
+        since: DateTime<Utc>,
+    ) -> anyhow::Result<u64>;
+
+    // Checkpoints.
+    async fn sync_watermark(&self) -> anyhow::Result<Option<u64>>;
+    /// Sets the watermark to `height` on a store that has none; returns whether it did.
+    async fn seed_sync_watermark(&self, height: u64) -> anyhow::Result<bool>;
+    /// Marks `height` fully processed; the watermark moves over contiguous heights only.
+    async fn advance_sync_watermark(&self, height: u64) -> anyhow::Result<()>;
+    async fn save_state_root(&self, root: &str) -> anyhow::Result<()>;
+    /// Leaves of the state tree in commit order: the latest installed snapshot's, then
+    /// every transaction ingested and gateway leaf appended after it. Sync appends leaves
+    /// in this order, so a rebuild reproduces the live tree.
+    async fn state_leaves(&self) -> anyhow::Result<Vec<String>>;
+    /// Appends `leaf`, proving a request `service` verified, to [`Self::state_leaves`]
+    /// unless it was appended before. Returns whether it was.
+    async fn append_gateway_leaf(&self, service: &str, leaf: &str) -> anyhow::Result<bool>;
+    /// Makes `snapshot` the base of [`Self::state_leaves`] on a node without leaves, all
+    /// in one transaction: sets the sync watermark to its height, records `commitment`
+    /// at that height and its checkpoint as an unconfirmed anchor. Fails with
+    /// [`InstallError::NotEmpty`], changing nothing, when the node already has leaves or
+    /// a snapshot.
+    async fn install_snapshot(
+        &self,
+        snapshot: &StateSnapshot,
+        commitment: &RootCommitment,
+    ) -> anyhow::Result<()>;
+    /// Remembers that `commitment.root` and its sorted root committed to the first
+    /// `leaf_count` leaves of [`Self::state_leaves`], so proofs can later be served
+    /// against them.
+    async fn record_state_root(
+        &self,
+        commitment: &RootCommitment,
+        height: u64,
+    ) -> anyhow::Result<()>;
+    /// Leaf count recorded for `root` by [`Self::record_state_root`].
+    async fn root_leaf_count(&self, root: &str) -> anyhow::Result<Option<u64>>;
+    /// Block height recorded for `root` by [`Self::record_state_root`].
+    async fn root_height(&self, root: &str) -> anyhow::Result<Option<u64>>;
+    /// Appends an anchoring attempt, failed ones included.
+    async fn record_anchor(&self, anchor: &StateAnchor) -> anyhow::Result<()>;
+    /// The most recent anchor that was not a failed broadcast.
+    async fn last_anchor(&self) -> anyhow::Result<Option<StateAnchor>>;
+    /// The most recent anchor seen in an ingested block.
+    async fn last_confirmed_anchor(&self) -> anyhow::Result<Option<StateAnchor>>;
+    /// Up to `limit` anchoring attempts, newest first.
+    async fn anchors(&self, limit: u32) -> anyhow::Result<Vec<StateAnchor>>;
+    /// The latest confirmed anchor of `root`.
+    async fn confirmed_anchor(&self, root: &str) -> anyhow::Result<Option<StateAnchor>>;
+    /// Settles broadcast anchors included in the block at `height`: those whose txid is
+    /// among `succeeded` become confirmed, those among `aborted` failed with the abort
+    /// as their error. Returns how many were confirmed.
+    async fn confirm_anchors(
+        &self,
+        succeeded: &[String],
+        aborted: &[(String, String)],
+        height: u64,
+    ) -> anyhow::Result<u64>;
+    /// Returns anchors confirmed at or above `height` to broadcast when a microblock
+    /// other than `block_hash` was recorded at `height`, i.e. the chain re-organized
+    /// below them. Returns how many were.
+    async fn revert_reorged_anchors(&self, height: u64, block_hash: &str) -> anyhow::Result<u64>;
+
+    // Events.
+    /// Broadcasts `event` on the events channel.
+    async fn publish_event(&self, event: &NexusEvent) -> anyhow::Result<()>;
+
+    // Safety flags.
+    async fn is_safety_mode_active(&self) -> anyhow::Result<bool>;
+    async fn safety_reason(&self) -> anyhow::Result<Option<String>>;
+    /// Enters Safety Mode for `trigger` and broadcasts it; `height` is the L1 burn
+    /// height the trigger was observed at, when known.
+    async fn activate_safety_mode(
+        &self,
+        drift: Option<u64>,
+        trigger: SafetyTrigger,
+        height: Option<u64>,
+    ) -> anyhow::Result<()>;
+    /// Records the latest raw drift and its moving average for status reporting.
+    async fn record_drift_sample(&self, raw: u64, smoothed: f64) -> anyhow::Result<()>;
+    /// Clears Safety Mode, its drift and its reason, and broadcasts the recovery.
+    async fn clear_safety_mode(&self) -> anyhow::Result<()>;
+    /// Enters the L1-unreachable state, which also holds Safety Mode.
+    async fn mark_l1_unreachable(&self, failures: u32) -> anyhow::Result<()>;
+    async fn clear_l1_unreachable(&self) -> anyhow::Result<()>;
+    async fn is_oracle_stale(&self) -> anyhow::Result<bool>;
+    /// Enters the oracle-stale state (which also holds Safety Mode) for an aggregate
+    /// `age_secs` old. A reason Safety Mode is already held for is kept.
+    async fn mark_oracle_stale(&self, age_secs: u64) -> anyhow::Result<()>;
+    async fn clear_oracle_stale(&self) -> anyhow::Result<()>;
+    /// Opens an incident for `trigger` with `detail`, or returns the one already open.
+    async fn open_incident(
+        &self,
+        trigger: SafetyTrigger,
+        detail: &serde_json::Value,
+    ) -> anyhow::Result<SafetyIncident>;
+    /// Resolves the open incident for `trigger`; `None` when none is open.
+    async fn resolve_incident(
+        &self,
+        trigger: SafetyTrigger,
+    ) -> anyhow::Result<Option<SafetyIncident>>;
+
+    // Execution audit.
+    async fn record_execution(&self, request: &ExecutionRequest) -> anyhow::Result<()>;
+    /// Records that `request` was turned away for `reason`. Rejections share the
+    /// execution sequence but never count as sequenced events.
+    async fn record_rejection(
+        &self,
+        request: &ExecutionRequest,
+        reason: RejectReason,
+    ) -> anyhow::Result<()>;
+    /// Latest recorded execution arrival time visible at `finality`.
+    async fn latest_execution_time(
+        &self,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<Option<DateTime<Utc>>>;
+    /// The recorded execution of `tx_id`: its acceptance if it was accepted, else its
+    /// latest rejection; `None` when it was never recorded.
+    async fn execution(&self, tx_id: &str) -> anyhow::Result<Option<ExecutionRecord>>;
+    /// Up to `limit` recorded executions matching `filter`, in sequence order.
+    async fn executions(
+        &self,
+        filter: &ExecutionFilter,
+        limit: u32,
+    ) -> anyhow::Result<Vec<ExecutionRecord>>;
+    async fn record_execution_outcome(&self, outcome: ExecutionOutcome) -> anyhow::Result<()>;
+    async fn executor_stats(&self) -> anyhow::Result<ExecutorStats>;
+    /// Highest nonce accepted from `sender`, if any.
+    async fn last_nonce(&self, sender: &str) -> anyhow::Result<Option<u64>>;
+    /// Atomically records `nonce` as `sender`'s highest accepted nonce; `false` (and no
+    /// change) when it does not exceed the current one.
+    async fn claim_nonce(&self, sender: &str, nonce: u64) -> anyhow::Result<bool>;
+    /// Undoes `sender`'s claim of `nonce` for a request that was not sequenced after all,
+    /// so the nonce can be claimed again; no change once a later nonce was claimed.
+    async fn release_nonce(&self, sender: &str, nonce: u64) -> anyhow::Result<()>;
+    /// Claims the next rebalance instruction nonce from a counter of its own, apart
+    /// from the client nonces; errors instead of wrapping once it is exhausted.
+    async fn next_rebalance_nonce(&self) -> anyhow::Result<u64>;
+    /// How many off-chain requests by `submitter` were admitted within the last `window`.
+    async fn submission_count(&self, submitter: &str, window: Duration) -> anyhow::Result<u64>;
+    /// Admits `tx_id` to `submitter`'s window unless `limit` requests were already
+    /// admitted within the last `window`; returns whether it was.
+    async fn admit_submission(
+        &self,
+        submitter: &str,
+        tx_id: &str,
+        limit: u64,
+        window: Duration,
+    ) -> anyhow::Result<bool>;
+    /// Takes `tx_id` back out of `submitter`'s window.
+    async fn withdraw_submission(&self, submitter: &str, tx_id: &str) -> anyhow::Result<()>;
+    /// The reservation or submission outcome held under a caller-scoped idempotency
+    /// key, until it expires.
+    async fn idempotency_entry(&self, key: &str) -> anyhow::Result<Option<IdempotencyEntry>>;
+    /// Reserves `key` for the request hashing to `request_hash` for `ttl` unless an
+    /// unexpired entry is there; returns whether it was reserved.
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        request_hash: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<bool>;
+    /// Records `outcome` under `key` for `ttl`, replacing its reservation.
+    async fn record_idempotent_outcome(
+        &self,
+        key: &str,
+        outcome: &IdempotentOutcome,
+        ttl: Duration,
+    ) -> anyhow::Result<()>;
+    /// Drops the reservation of `key` if the request hashing to `request_hash` still
+    /// holds it.
+    async fn release_idempotency_key(&self, key: &str, request_hash: &str) -> anyhow::Result<()>;
+
+    // Locks shared by every instance on the store.
+    /// Takes the lock `name` for `ttl` under `token` unless it is held; returns whether
+    /// it was taken.
+    async fn try_acquire_lock(
+        &self,
+        name: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<bool>;
+    /// Extends the lock `name` to `ttl` from now if `token` still holds it; returns
+    /// whether it does.
+    async fn renew_lock(&self, name: &str, token: &str, ttl: Duration) -> anyhow::Result<bool>;
+    /// Releases the lock `name` if `token` still holds it.
+    async fn release_lock(&self, name: &str, token: &str) -> anyhow::Result<()>;
+
+    // Oracle.
+    /// Timestamp (unix seconds) of the latest published oracle aggregate.
+    async fn latest_oracle_timestamp(&self) -> anyhow::Result<Option<u64>>;
+    /// USD collateral prices from the latest published aggregate; empty when none.
+    async fn collateral_prices(&self) -> anyhow::Result<BTreeMap<String, f64>>;
+    /// Records an oracle update call confirmed in the block at `height`; a `tx_id`
+    /// already recorded is ignored.
+    async fn record_oracle_update(
+        &self,
+        tx_id: &str,
+        height: u64,
+        update: &OraclePriceUpdate,
+    ) -> anyhow::Result<()>;
+    /// The latest oracle update confirmed on-chain, by block height.
+    async fn last_oracle_update(&self) -> anyhow::Result<Option<OraclePriceUpdate>>;
+
+    // Vaults.
+    async fn vaults(&self) -> anyhow::Result<Vec<VaultStatus>>;
+    /// The stored records of `vault_ids`, closed vaults included; unknown ids are left out.
+    async fn vault_records(&self, vault_ids: &[String]) -> anyhow::Result<Vec<VaultRecord>>;
+    /// Which of `positions` hold vault events that were already applied.
+    async fn applied_vault_events(
+        &self,
+        positions: &[EventPosition],
+    ) -> anyhow::Result<HashSet<EventPosition>>;
+    /// Saves the records and the positions of the events applied to them (with each
+    /// event's vault id) together, caching active vaults for [`Self::vaults`] and dropping
+    /// closed ones from the cache.
+    async fn save_vault_records(
+        &self,
+        records: &[VaultRecord],
+        events: &[(EventPosition, String)],
+    ) -> anyhow::Result<()>;
+
+    // Sync dead letters.
+    async fn record_dead_letter(&self, letter: &DeadLetter) -> anyhow::Result<()>;
+    /// Up to `limit` dead letters, newest first.
+    async fn dead_letters(&self, limit: u32) -> anyhow::Result<Vec<DeadLetter>>;
+}
+
+type AnchorRow = (
+    String,
+    Option<String>,
+    i64,
+    i64,
+    String,
+    String,
+    Option<String>,
+    Option<i64>,
+    i64,
+    Option<i64>,
+);
+
+type ExecutionRow = (
+    i64,
+    String,
+    String,
+    Option<String>,
+    Option<i32>,
+    String,
+    DateTime<Utc>,
+    DateTime<Utc>,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
+/// Fixed-point prices as JSON decimal strings, which keep the full `u128` range.
+fn fixed_point_json(prices: &BTreeMap<String, u128>) -> serde_json::Value {
+    prices
+        .iter()
+        .map(|(currency, price)| (currency.clone(), price.to_string().into()))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn fixed_point_from_json(value: serde_json::Value) -> anyhow::Result<BTreeMap<String, u128>> {
+    let prices: BTreeMap<String, String> = serde_json::from_value(value)?;
+    prices
+        .into_iter()
+        .map(|(currency, price)| Ok((currency, price.parse()?)))
+        .collect()
+}
+
+/// Selected from the `me_executions` view of accepted and rejected requests.
+const EXECUTION_COLUMNS: &str = "sequence, tx_id, sender, nonce::TEXT, sequencing_priority, \
+     payload_hash, arrival_time, recorded_at, status, rejection_reason, signature";
+
+fn execution_from_row(row: ExecutionRow) -> anyhow::Result<ExecutionRecord> {
+    let (
+        sequence,
+        tx_id,
+        sender,
+        nonce,
+        priority,
+        payload_hash,
+        timestamp,
+        recorded_at,
+        status,
+        rejection_reason,
+        signature,
+    ) = row;
+    Ok(ExecutionRecord {
+        sequence: sequence.max(0) as u64,
+        tx_id,
+        sender,
+        nonce: nonce.map(|n| n.parse()).transpose()?.unwrap_or_default(),
+        priority: priority.unwrap_or_default(),
+        payload_hash,
+        timestamp,
+        recorded_at,
+        status: status.parse()?,
+        rejection_reason: rejection_reason.map(|r| r.parse()).transpose()?,
+        signature,
+    })
+}
+
+impl Storage {
+    /// Up to `limit` rows of `anchors` for `root` (any root when `None`) matching the
+    /// extra `conditions`, newest first.
+    async fn select_anchors(
+        &self,
+        root: Option<&str>,
+        conditions: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<StateAnchor>> {
+        let rows: Vec<AnchorRow> = timed_query(
+            "select_anchors",
+            sqlx::query_as(&format!(
+            "SELECT root, sorted_root, height, leaf_count, tx_id, status, error, confirmed_height, anchored_at, burn_height \
+             FROM anchors WHERE ($2::TEXT IS NULL OR root = $2) {} ORDER BY id DESC LIMIT $1",
+            conditions
+        ))
+            .bind(limit as i64)
+            .bind(root)
+            .fetch_all(self.read_pool()),
+        )
+        .await?;
+        rows.into_iter()
+            .map(
+                |(
+                    root,
+                    sorted_root,
+                    height,
+                    leaf_count,
+                    tx_id,
+                    status,
+                    error,
+                    confirmed_height,
+                    anchored_at,
+                    burn_height,
+                )| {
+                    Ok(StateAnchor {
+                        root,
+                        sorted_root,
+                        height: height.max(0) as u64,
+                        leaf_count: leaf_count.max(0) as u64,
+                        tx_id,
+                        anchored_at,
+                        burn_height: burn_height.map(|h| h.max(0) as u64),
+                        status: status.parse()?,
+                        confirmed_height: confirmed_height.map(|h| h.max(0) as u64),
+                        error,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+async fn insert_state_root<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    commitment: &RootCommitment,
+    height: u64,
+) -> sqlx::Result<()> {
+    timed_query(
+        "record_state_root",
+        sqlx::query(
+            "INSERT INTO state_root_history (root, sorted_root, leaf_count, block_height) \
+             VALUES ($1, $2, $3, $4) ON CONFLICT (root) DO NOTHING",
+        )
+        .bind(&commitment.root)
+        .bind(&commitment.sorted_root)
+        .bind(commitment.leaf_count as i64)
+        .bind(height as i64)
+        .execute(executor),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn insert_anchor<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    anchor: &StateAnchor,
+) -> sqlx::Result<()> {
+    timed_query("record_anchor", sqlx::query(
+        "INSERT INTO anchors (root, height, leaf_count, tx_id, status, error, confirmed_height, anchored_at, sorted_root, burn_height) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+    )
+    .bind(&anchor.root)
+    .bind(anchor.height as i64)
+    .bind(anchor.leaf_count as i64)
+    .bind(canonical_leaf(&anchor.tx_id).as_ref())
+    .bind(anchor.status.as_str())
+    .bind(anchor.error.as_deref())
+    .bind(anchor.confirmed_height.map(|h| h as i64))
+    .bind(anchor.anchored_at)
+    .bind(anchor.sorted_root.as_deref())
+    .bind(anchor.burn_height.map(|h| h as i64))
+    .execute(executor)).await?;
+    Ok(())
+}
+
+/// A snapshot's checkpoint as this node records it: the exporter's word that it was
+/// confirmed is not verified here, so it stays broadcast until seen on-chain.
+fn unconfirmed(checkpoint: &StateAnchor) -> StateAnchor {
+    StateAnchor {
+        status: AnchorStatus::Broadcast,
+        confirmed_height: None,
+        error: None,
+        ..checkpoint.clone()
+    }
+}
+
+#[async_trait]
+impl NexusStore for Storage {
+    async fn ingest_block(
+        &self,
+        block: &NewBlock,
+        transactions: &[NewTransaction],
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(self.repo().ingest_block(block, transactions).await?)
+    }
+
+    async fn insert_block(&self, block: &NewBlock) -> anyhow::Result<bool> {
+        Ok(self.repo().insert_block(block).await?)
+    }
+
+    async fn finalize_blocks_up_to(&self, height: u64) -> anyhow::Result<u64> {
+        Ok(self.repo().finalize_blocks_up_to(height).await?)
+    }
+
+    async fn max_block_height(&self) -> anyhow::Result<u64> {
+        Ok(self.repo().max_block_height().await?)
+    }
+
+    async fn max_processed_height(&self) -> anyhow::Result<u64> {
+        Ok(self.repo().max_processed_height().await?)
+    }
+
+    async fn last_block_time(&self) -> anyhow::Result<Option<DateTime<Utc>>> {
+        Ok(self.repo().last_block_time().await?)
+    }
+
+    async fn chain_counts(&self) -> anyhow::Result<ChainCounts> {
+        Ok(self.repo().chain_counts().await?)
+    }
+
+    async fn transactions_by_sender(
+        &self,
+        sender: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<StoredTransaction>> {
+        Ok(self
+            .read_repo()
+            .transactions_by_sender(sender, limit)
+            .await?)
+    }
+
+    async fn sender_transactions_since(
+        &self,
+        sender: &str,
+        since: DateTime<Utc>,
+    ) -> anyhow::Result<u64> {
+        Ok(self
+            .read_repo()
+            .sender_transactions_since(sender, since)
+            .await?)
+    }
+
+    async fn sync_watermark(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self.repo().sync_watermark().await?)
+    }
+
+    async fn seed_sync_watermark(&self, height: u64) -> anyhow::Result<bool> {
+        Ok(self.repo().seed_sync_watermark(height).await?)
+    }
+
+    async fn advance_sync_watermark(&self, height: u64) -> anyhow::Result<()> {
+        Ok(self.repo().advance_sync_watermark(height).await?)
+    }
+
+    async fn save_state_root(&self, root: &str) -> anyhow::Result<()> {
+        self.with_redis(|mut conn| async move {
+            redis::cmd("SET")
+                .arg(self.redis_key(keys::STATE_ROOT))
+                .arg(root)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn state_leaves(&self) -> anyhow::Result<Vec<String>> {
+        let snapshot: Option<(String, i64)> = timed_query(
+            "latest_state_snapshot",
+            sqlx::query_as(
+                "SELECT leaves::text, last_seq FROM state_snapshots ORDER BY id DESC LIMIT 1",
+            )
+            .fetch_optional(&self.pg_pool),
+        )
+        .await?;
+        let (mut leaves, last_seq) = match snapshot {
+            Some((leaves, last_seq)) => (serde_json::from_str::<Vec<String>>(&leaves)?, last_seq),
+            None => (Vec::new(), 0),
+        };
+        let appended: Vec<String> = timed_query(
+            "leaves_after_snapshot",
+            sqlx::query_scalar(
+                "SELECT leaf FROM (
+                     SELECT tx_id AS leaf, seq FROM stacks_transactions WHERE seq > $1
+                     UNION ALL
+                     SELECT leaf, seq FROM gateway_leaves WHERE seq > $1
+                 ) appended ORDER BY seq",
+            )
+            .bind(last_seq)
+            .fetch_all(&self.pg_pool),
+        )
+        .await?;
+        leaves.extend(appended);
+        Ok(leaves)
+    }
+
+    async fn append_gateway_leaf(&self, service: &str, leaf: &str) -> anyhow::Result<bool> {
+        let result = timed_query(
+            "append_gateway_leaf",
+            sqlx::query(
+                "INSERT INTO gateway_leaves (leaf, service) VALUES ($1, $2)
+                 ON CONFLICT (leaf) DO NOTHING",
+            )
+            .bind(leaf)
+            .bind(service)
+            .execute(&self.pg_pool),
+        )
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn install_snapshot(
+        &self,
+        snapshot: &StateSnapshot,
+        commitment: &RootCommitment,
+    ) -> anyhow::Result<()> {
+        let mut db_tx = self.pg_pool.begin().await?;
+        let has_state: bool = timed_query(
+            "snapshot_target_has_state",
+            sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM stacks_transactions)
+                     OR EXISTS (SELECT 1 FROM gateway_leaves)
+                     OR EXISTS (SELECT 1 FROM state_snapshots)",
+            )
+            .fetch_one(&mut *db_tx),
+        )
+        .await?;
+        if has_state {
+            return Err(InstallError::NotEmpty.into());
+        }
+        timed_query(
+            "insert_state_snapshot",
+            sqlx::query(
+                "INSERT INTO state_snapshots (version, arity, root, height, leaves, last_seq)
+             VALUES ($1, $2, $3, $4, $5::jsonb, 0)",
+            )
+            .bind(snapshot.version as i32)
+            .bind(snapshot.arity.to_string())
+            .bind(&snapshot.root)
+            .bind(snapshot.height.map(|h| h as i64))
+            .bind(serde_json::to_string(&snapshot.leaves)?)
+            .execute(&mut *db_tx),
+        )
+        .await?;
+        if let Some(height) = snapshot.height {
+            timed_query(
+                "snapshot_sync_progress",
+                sqlx::query(
+                    "INSERT INTO sync_progress (id, processed_height) VALUES (1, $1)
+                 ON CONFLICT (id) DO UPDATE
+                 SET processed_height = EXCLUDED.processed_height, updated_at = NOW()",
+                )
+                .bind(height as i64)
+                .execute(&mut *db_tx),
+            )
+            .await?;
+            insert_state_root(&mut *db_tx, commitment, height).await?;
+        }
+        if let Some(checkpoint) = &snapshot.checkpoint {
+            insert_anchor(&mut *db_tx, &unconfirmed(checkpoint)).await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    async fn record_state_root(
+        &self,
+        commitment: &RootCommitment,
+        height: u64,
+    ) -> anyhow::Result<()> {
+        insert_state_root(&self.pg_pool, commitment, height).await?;
+        Ok(())
+    }
+
+    async fn root_leaf_count(&self, root: &str) -> anyhow::Result<Option<u64>> {
+        let count: Option<i64> = timed_query(
+            "root_leaf_count",
+            sqlx::query_scalar("SELECT leaf_count FROM state_root_history WHERE root = $1")
+                .bind(root)
+                .fetch_optional(self.read_pool()),
+        )
+        .await?;
+        Ok(count.map(|c| c.max(0) as u64))
+    }
+
+    async fn root_height(&self, root: &str) -> anyhow::Result<Option<u64>> {
+        let height: Option<i64> = timed_query(
+            "root_height",
+            sqlx::query_scalar("SELECT block_height FROM state_root_history WHERE root = $1")
+                .bind(root)
+                .fetch_optional(self.read_pool()),
+        )
+        .await?;
+        Ok(height.map(|h| h.max(0) as u64))
+    }
+
+    async fn record_anchor(&self, anchor: &StateAnchor) -> anyhow::Result<()> {
+        insert_anchor(&self.pg_pool, anchor).await?;
+        Ok(())
+    }
+
+    async fn last_anchor(&self) -> anyhow::Result<Option<StateAnchor>> {
+        Ok(self
+            .select_anchors(None, "AND status <> 'failed'", 1)
+            .await?
+            .pop())
+    }
+
+    async fn last_confirmed_anchor(&self) -> anyhow::Result<Option<StateAnchor>> {
+        Ok(self
+            .select_anchors(None, "AND status = 'confirmed'", 1)
+            .await?
+            .pop())
+    }
+
+    async fn anchors(&self, limit: u32) -> anyhow::Result<Vec<StateAnchor>> {
+        self.select_anchors(None, "", limit).await
+    }
+
+    async fn confirmed_anchor(&self, root: &str) -> anyhow::Result<Option<StateAnchor>> {
+        Ok(self
+            .select_anchors(Some(root), "AND status = 'confirmed'", 1)
+            .await?
+            .pop())
+    }
+
+    async fn confirm_anchors(
+        &self,
+        succeeded: &[String],
+        aborted: &[(String, String)],
+        height: u64,
+    ) -> anyhow::Result<u64> {
+        let succeeded: Vec<String> = succeeded
+            .iter()
+            .map(|id| canonical_leaf(id).into_owned())
+            .collect();
+        let (aborted, reasons): (Vec<String>, Vec<String>) = aborted
+            .iter()
+            .map(|(id, status)| {
+                (
+                    canonical_leaf(id).into_owned(),
+                    format!("Aborted on-chain: {}", status),
+                )
+            })
+            .unzip();
+        if !aborted.is_empty() {
+            timed_query(
+                "fail_aborted_anchors",
+                sqlx::query(
+                    "UPDATE anchors a SET status = 'failed', error = t.reason \
+                 FROM UNNEST($1::TEXT[], $2::TEXT[]) AS t(tx_id, reason) \
+                 WHERE a.status = 'broadcast' AND a.tx_id = t.tx_id",
+                )
+                .bind(&aborted)
+                .bind(&reasons)
+                .execute(&self.pg_pool),
+            )
+            .await?;
+        }
+        if succeeded.is_empty() {
+            return Ok(0);
+        }
+        let result = timed_query(
+            "confirm_anchors",
+            sqlx::query(
+                "UPDATE anchors SET status = 'confirmed', confirmed_height = $2 \
+             WHERE status = 'broadcast' AND tx_id = ANY($1)",
+            )
+            .bind(&succeeded)
+            .bind(height as i64)
+            .execute(&self.pg_pool),
+        )
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn revert_reorged_anchors(&self, height: u64, block_hash: &str) -> anyhow::Result<u64> {
+        let result = timed_query(
+            "revert_reorged_anchors",
+            sqlx::query(
+                "UPDATE anchors SET status = 'broadcast', confirmed_height = NULL \
+             WHERE status = 'confirmed' AND confirmed_height >= $1 \
+               AND EXISTS (SELECT 1 FROM stacks_blocks \
+                           WHERE type = 'microblock' AND height = $1 AND hash <> $2)",
+            )
+            .bind(height as i64)
+            .bind(block_hash)
+            .execute(&self.pg_pool),
+        )
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn is_safety_mode_active(&self) -> anyhow::Result<bool> {
+        crate::safety::is_safety_mode_active(self).await
+    }
+
+    async fn safety_reason(&self) -> anyhow::Result<Option<String>> {
+        crate::safety::safety_reason(self).await
+    }
+
+    async fn publish_event(&self, event: &NexusEvent) -> anyhow::Result<()> {
+        let payload = event.to_json();
+        let payload = &payload;
+        self.with_redis(|mut conn| async move {
+            redis::cmd("PUBLISH")
+                .arg(self.redis_key(keys::EVENTS_CHANNEL))
+                .arg(payload)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn activate_safety_mode(
+        &self,
+        drift: Option<u64>,
+        trigger: SafetyTrigger,
+        height: Option<u64>,
+    ) -> anyhow::Result<()> {
+        crate::safety::activate_safety_mode(self, drift, trigger, height).await
+    }
+
+    async fn clear_safety_mode(&self) -> anyhow::Result<()> {
+        self.with_redis(|mut conn| async move {
+            redis::pipe()
+                .atomic()
+                .cmd("DEL")
+                .arg(self.redis_key(keys::SAFETY_MODE))
+                .cmd("DEL")
+                .arg(self.redis_key(keys::DRIFT))
+                .cmd("DEL")
+                .arg(self.redis_key(keys::SAFETY_REASON))
+                .cmd("PUBLISH")
+                .arg(self.redis_key(keys::EVENTS_CHANNEL))
+                .arg(NexusEvent::SafetyCleared { drill: false }.to_json())
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_l1_unreachable(&self, failures: u32) -> anyhow::Result<()> {
+        let event = NexusEvent::L1Unreachable { failures }.to_json();
+        let event = &event;
+        self.with_redis(|mut conn| async move {
+            redis::pipe()
+                .atomic()
+                .cmd("SET")
+                .arg(self.redis_key(keys::SAFETY_MODE))
+                .arg(true)
+                .cmd("SET")
+                .arg(self.redis_key(keys::SAFETY_REASON))
+                .arg(SafetyTrigger::L1Unreachable.as_str())
+                .cmd("SET")
+                .arg(self.redis_key(keys::L1_UNREACHABLE))
+                .arg(failures)
+                .cmd("PUBLISH")
+                .arg(self.redis_key(keys::EVENTS_CHANNEL))
+                .arg(event)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn clear_l1_unreachable(&self) -> anyhow::Result<()> {
+        self.with_redis(|mut conn| async move {
+            redis::pipe()
+                .atomic()
+                .cmd("DEL")
+                .arg(self.redis_key(keys::L1_UNREACHABLE))
+                .cmd("PUBLISH")
+                .arg(self.redis_key(keys::EVENTS_CHANNEL))
+                .arg(NexusEvent::L1Reachable.to_json())
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn record_drift_sample(&self, raw: u64, smoothed: f64) -> anyhow::Result<()> {
+        self.with_redis(|mut conn| async move {
+            redis::pipe()
+                .cmd("SET")
+                .arg(self.redis_key(keys::DRIFT_RAW))
+                .arg(raw)
+                .cmd("SET")
+                .arg(self.redis_key(keys::DRIFT_SMOOTHED))
+                .arg(smoothed)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn is_oracle_stale(&self) -> anyhow::Result<bool> {
+        crate::safety::is_oracle_stale(self).await
+    }
+
+    async fn mark_oracle_stale(&self, age_secs: u64) -> anyhow::Result<()> {
+        let event = NexusEvent::OracleStale { age_secs }.to_json();
+        let event = &event;
+        self.with_redis(|mut conn| async move {
+            redis::pipe()
+                .atomic()
+                .cmd("SET")
+                .arg(self.redis_key(keys::SAFETY_MODE))
+                .arg(true)
+                .cmd("SET")
+                .arg(self.redis_key(keys::SAFETY_REASON))
+                .arg(SafetyTrigger::OracleStale.as_str())
+                .arg("NX")
+                .cmd("SET")
+                .arg(self.redis_key(keys::ORACLE_STALE))
+                .arg(age_secs)
+                .cmd("PUBLISH")
+                .arg(self.redis_key(keys::EVENTS_CHANNEL))
+                .arg(event)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn clear_oracle_stale(&self) -> anyhow::Result<()> {
+        self.with_redis(|mut conn| async move {
+            redis::pipe()
+                .atomic()
+                .cmd("DEL")
+                .arg(self.redis_key(keys::ORACLE_STALE))
+                .cmd("PUBLISH")
+                .arg(self.redis_key(keys::EVENTS_CHANNEL))
+                .arg(NexusEvent::OracleFresh.to_json())
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn open_incident(
+        &self,
+        trigger: SafetyTrigger,
+        detail: &serde_json::Value,
+    ) -> anyhow::Result<SafetyIncident> {
+        let row: (i64, String, DateTime<Utc>) = timed_query(
+            "open_incident",
+            sqlx::query_as(
+                "WITH open AS (
+                     SELECT id, detail, opened_at FROM safety_incidents
+                     WHERE cause = $1 AND resolved_at IS NULL
+                 ), inserted AS (
+                     INSERT INTO safety_incidents (cause, detail)
+                     SELECT $1, $2::jsonb WHERE NOT EXISTS (SELECT 1 FROM open)
+                     RETURNING id, detail, opened_at
+                 )
+                 SELECT id, detail::text, opened_at FROM inserted
+                 UNION ALL SELECT id, detail::text, opened_at FROM open",
+            )
+            .bind(trigger.as_str())
+            .bind(detail.to_string())
+            .fetch_one(&self.pg_pool),
+        )
+        .await?;
+        Ok(SafetyIncident {
+            id: row.0,
+            cause: trigger.as_str().to_string(),
+            detail: serde_json::from_str(&row.1)?,
+            opened_at: row.2,
+            resolved_at: None,
+        })
+    }
+
+    async fn resolve_incident(
+        &self,
+        trigger: SafetyTrigger,
+    ) -> anyhow::Result<Option<SafetyIncident>> {
+        let row: Option<(i64, String, DateTime<Utc>, DateTime<Utc>)> = timed_query(
+            "resolve_incident",
+            sqlx::query_as(
+                "UPDATE safety_incidents SET resolved_at = NOW()
+                 WHERE cause = $1 AND resolved_at IS NULL
+                 RETURNING id, detail::text, opened_at, resolved_at",
+            )
+            .bind(trigger.as_str())
+            .fetch_optional(&self.pg_pool),
+        )
+        .await?;
+        row.map(|(id, detail, opened_at, resolved_at)| {
+            Ok(SafetyIncident {
+                id,
+                cause: trigger.as_str().to_string(),
+                detail: serde_json::from_str(&detail)?,
+                opened_at,
+                resolved_at: Some(resolved_at),
+            })
+        })
+        .transpose()
+    }
+
+    async fn record_execution(&self, request: &ExecutionRequest) -> anyhow::Result<()> {
+        // [Hole 4.1] Expand audit logs to include full payload and priority metadata
+        timed_query("record_execution", sqlx::query(
+            "INSERT INTO me_audit_log (tx_id, payload_hash, sender, arrival_time, payload, sequencing_priority, nonce, signature)
+             VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8)",
+        )
+        .bind(&request.tx_id)
+        .bind(hex::encode(Sha256::digest(request.payload.as_bytes())))
+        .bind(&request.sender)
+        .bind(request.timestamp)
+        .bind(&request.payload)
+        .bind(request.priority)
+        .bind(request.nonce.to_string())
+        .bind(request.signature.as_deref())
+        .execute(&self.pg_pool)).await?;
+        Ok(())
+    }
+
+    async fn record_rejection(
+        &self,
+        request: &ExecutionRequest,
+        reason: RejectReason,
+    ) -> anyhow::Result<()> {
+        timed_query(
+            "record_rejection",
+            sqlx::query(
+                "INSERT INTO me_execution_rejections
+                     (tx_id, payload_hash, sender, payload, arrival_time, nonce, sequencing_priority, reason, signature)
+                 VALUES ($1, $2, $3, $4, $5, $6::numeric, $7, $8, $9)",
+            )
+            .bind(&request.tx_id)
+            .bind(hex::encode(Sha256::digest(request.payload.as_bytes())))
+            .bind(&request.sender)
+            .bind(&request.payload)
+            .bind(request.timestamp)
+            .bind(request.nonce.to_string())
+            .bind(request.priority)
+            .bind(reason.as_str())
+            .bind(request.signature.as_deref())
+            .execute(&self.pg_pool),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn latest_execution_time(
+        &self,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        Ok(match finality {
+            FinalityLevel::Soft => {
+                timed_query(
+                    "latest_execution_time",
+                    sqlx::query_scalar("SELECT MAX(arrival_time) FROM me_audit_log")
+                        .fetch_one(&self.pg_pool),
+                )
+                .await?
+            }
+            FinalityLevel::Hard => self.repo().last_hard_audit_arrival().await?,
+        })
+    }
+
+    async fn execution(&self, tx_id: &str) -> anyhow::Result<Option<ExecutionRecord>> {
+        let row: Option<ExecutionRow> = timed_query(
+            "get_execution",
+            sqlx::query_as(&format!(
+                "SELECT {} FROM me_executions WHERE tx_id = $1 \
+                 ORDER BY status = 'accepted' DESC, sequence DESC LIMIT 1",
+                EXECUTION_COLUMNS
+            ))
+            .bind(tx_id)
+            .fetch_optional(self.read_pool()),
+        )
+        .await?;
+        row.map(execution_from_row).transpose()
+    }
+
+    async fn executions(
+        &self,
+        filter: &ExecutionFilter,
+        limit: u32,
+    ) -> anyhow::Result<Vec<ExecutionRecord>> {
+        let rows: Vec<ExecutionRow> = timed_query(
+            "list_executions",
+            sqlx::query_as(&format!(
+                "SELECT {} FROM me_executions \
+             WHERE ($2::TEXT IS NULL OR sender = $2) \
+               AND ($3::TIMESTAMPTZ IS NULL OR arrival_time >= $3) \
+               AND sequence > $4 \
+               AND ($5::TEXT IS NULL OR status = $5) \
+             ORDER BY sequence LIMIT $1",
+                EXECUTION_COLUMNS
+            ))
+            .bind(limit as i64)
+            .bind(filter.sender.as_deref())
+            .bind(filter.since)
+            .bind(filter.after_sequence.unwrap_or(0).min(i64::MAX as u64) as i64)
+            .bind(filter.status.map(ExecutionStatus::as_str))
+            .fetch_all(self.read_pool()),
+        )
+        .await?;
+        rows.into_iter().map(execution_from_row).collect()
+    }
+
+    async fn record_execution_outcome(&self, outcome: ExecutionOutcome) -> anyhow::Result<()> {
+        let field = outcome.stat_key();
+        self.with_redis(|mut conn| {
+            let field = field.clone();
+            async move {
+                redis::cmd("HINCRBY")
+                    .arg(self.redis_key(EXECUTOR_STATS_KEY))
+                    .arg(field)
+                    .arg(1)
+                    .query_async::<()>(&mut conn)
+                    .await
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn executor_stats(&self) -> anyhow::Result<ExecutorStats> {
+        let counters: HashMap<String, u64> = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("HGETALL")
+                    .arg(self.redis_key(EXECUTOR_STATS_KEY))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(ExecutorStats::from_counters(&counters))
+    }
+
+    async fn last_nonce(&self, sender: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self
+            .with_redis(|mut conn| async move {
+                redis::cmd("HGET")
+                    .arg(self.redis_key(EXECUTOR_NONCES_KEY))
+                    .arg(sender)
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?)
+    }
+
+    async fn claim_nonce(&self, sender: &str, nonce: u64) -> anyhow::Result<bool> {
+        let script = redis::Script::new(CLAIM_NONCE_SCRIPT);
+        let claimed: i64 = self
+            .with_redis(|mut conn| {
+                let mut invocation = script.key(self.redis_key(EXECUTOR_NONCES_KEY));
+                invocation.arg(sender).arg(nonce);
+                async move { invocation.invoke_async(&mut conn).await }
+            })
+            .await?;
+        Ok(claimed == 1)
+    }
+
+    async fn release_nonce(&self, sender: &str, nonce: u64) -> anyhow::Result<()> {
+        let script = redis::Script::new(RELEASE_NONCE_SCRIPT);
+        let previous = nonce
+            .checked_sub(1)
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+        let _: i64 = self
+            .with_redis(|mut conn| {
+                let mut invocation = script.key(self.redis_key(EXECUTOR_NONCES_KEY));
+                invocation.arg(sender).arg(nonce).arg(&previous);
+                async move { invocation.invoke_async(&mut conn).await }
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn next_rebalance_nonce(&self) -> anyhow::Result<u64> {
+        let script = redis::Script::new(NEXT_REBALANCE_NONCE_SCRIPT);
+        let nonce: i64 = self
+            .with_redis(|mut conn| {
+                let mut invocation = script
+                    .key(self.redis_key(keys::REBALANCE_NONCE))
+                    .key(self.redis_key(EXECUTOR_NONCES_KEY));
+                invocation.arg(LEGACY_REBALANCE_NONCE_SENDER);
+                async move { invocation.invoke_async(&mut conn).await }
+            })
+            .await
+            .context("Rebalance nonce counter is exhausted or unreadable")?;
+        u64::try_from(nonce).context("Rebalance nonce counter is negative")
+    }
+
+    async fn submission_count(&self, submitter: &str, window: Duration) -> anyhow::Result<u64> {
+        let since_ms = Utc::now().timestamp_millis() - window.as_millis() as i64;
+        let count: u64 = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("ZCOUNT")
+                    .arg(self.redis_key(&keys::submitter_velocity(submitter)))
+                    .arg(format!("({}", since_ms))
+                    .arg("+inf")
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(count)
+    }
+
+    async fn admit_submission(
+        &self,
+        submitter: &str,
+        tx_id: &str,
+        limit: u64,
+        window: Duration,
+    ) -> anyhow::Result<bool> {
+        let script = redis::Script::new(ADMIT_SUBMISSION_SCRIPT);
+        let now_ms = Utc::now().timestamp_millis();
+        let admitted: i64 = self
+            .with_redis(|mut conn| {
+                let mut invocation =
+                    script.key(self.redis_key(&keys::submitter_velocity(submitter)));
+                invocation
+                    .arg(now_ms)
+                    .arg(window.as_millis().max(1) as u64)
+                    .arg(tx_id)
+                    .arg(limit);
+                async move { invocation.invoke_async(&mut conn).await }
+            })
+            .await?;
+        Ok(admitted == 1)
+    }
+
+    async fn withdraw_submission(&self, submitter: &str, tx_id: &str) -> anyhow::Result<()> {
+        self.with_redis(|mut conn| async move {
+            redis::cmd("ZREM")
+                .arg(self.redis_key(&keys::submitter_velocity(submitter)))
+                .arg(tx_id)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn idempotency_entry(&self, key: &str) -> anyhow::Result<Option<IdempotencyEntry>> {
+        let json: Option<String> = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("GET")
+                    .arg(self.redis_key(&keys::idempotency(key)))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        request_hash: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let json = pending_idempotency_json(request_hash)?;
+        let set: Option<String> = self
+            .with_redis(|mut conn| {
+                let json = json.clone();
+                async move {
+                    redis::cmd("SET")
+                        .arg(self.redis_key(&keys::idempotency(key)))
+                        .arg(json)
+                        .arg("NX")
+                        .arg("PX")
+                        .arg(ttl.as_millis().max(1) as u64)
+                        .query_async(&mut conn)
+                        .await
+                }
+            })
+            .await?;
+        Ok(set.is_some())
+    }
+
+    async fn record_idempotent_outcome(
+        &self,
+        key: &str,
+        outcome: &IdempotentOutcome,
+        ttl: Duration,
+    ) -> anyhow::Result<()> {
+        let json = serde_json::to_string(&IdempotencyEntry::Done(outcome.clone()))?;
+        self.with_redis(|mut conn| {
+            let json = json.clone();
+            async move {
+                redis::cmd("SET")
+                    .arg(self.redis_key(&keys::idempotency(key)))
+                    .arg(json)
+                    .arg("PX")
+                    .arg(ttl.as_millis().max(1) as u64)
+                    .query_async::<()>(&mut conn)
+                    .await
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, key: &str, request_hash: &str) -> anyhow::Result<()> {
+        // The lock release script deletes the key only while it holds the given value.
+        let script = redis::Script::new(RELEASE_LOCK_SCRIPT);
+        let json = pending_idempotency_json(request_hash)?;
+        self.with_redis(|mut conn| {
+            let mut invocation = script.key(self.redis_key(&keys::idempotency(key)));
+            invocation.arg(&json);
+            async move { invocation.invoke_async::<i64>(&mut conn).await }
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn try_acquire_lock(
+        &self,
+        name: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let set: Option<String> = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("SET")
+                    .arg(self.redis_key(&keys::lock(name)))
+                    .arg(token)
+                    .arg("NX")
+                    .arg("PX")
+                    .arg(ttl.as_millis().max(1) as u64)
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(set.is_some())
+    }
+
+    async fn renew_lock(&self, name: &str, token: &str, ttl: Duration) -> anyhow::Result<bool> {
+        let script = redis::Script::new(RENEW_LOCK_SCRIPT);
+        let renewed: i64 = self
+            .with_redis(|mut conn| {
+                let mut invocation = script.key(self.redis_key(&keys::lock(name)));
+                invocation.arg(token).arg(ttl.as_millis().max(1) as u64);
+                async move { invocation.invoke_async(&mut conn).await }
+            })
+            .await?;
+        Ok(renewed == 1)
+    }
+
+    async fn release_lock(&self, name: &str, token: &str) -> anyhow::Result<()> {
+        let script = redis::Script::new(RELEASE_LOCK_SCRIPT);
+        self.with_redis(|mut conn| {
+            let mut invocation = script.key(self.redis_key(&keys::lock(name)));
+            invocation.arg(token);
+            async move { invocation.invoke_async::<i64>(&mut conn).await }
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn latest_oracle_timestamp(&self) -> anyhow::Result<Option<u64>> {
+        let cached: Option<String> = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("GET")
+                    .arg(self.redis_key(ORACLE_PPP_CACHE_KEY))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        if let Some(timestamp) = cached
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .and_then(|state| state["timestamp"].as_u64())
+        {
+            return Ok(Some(timestamp));
+        }
+        let timestamp: Option<i64> = timed_query(
+            "latest_oracle_timestamp",
+            sqlx::query_scalar("SELECT MAX(timestamp) FROM oracle_fx_history")
+                .fetch_one(&self.pg_pool),
+        )
+        .await?;
+        Ok(timestamp.map(|t| t.max(0) as u64))
+    }
+
+    async fn collateral_prices(&self) -> anyhow::Result<BTreeMap<String, f64>> {
+        let cached: Option<String> = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("GET")
+                    .arg(self.redis_key(ORACLE_PPP_CACHE_KEY))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(cached
+            .and_then(|json| serde_json::from_str::<PppState>(&json).ok())
+            .map(|state| state.collateral_prices)
+            .unwrap_or_default())
+    }
+
+    async fn record_oracle_update(
+        &self,
+        tx_id: &str,
+        height: u64,
+        update: &OraclePriceUpdate,
+    ) -> anyhow::Result<()> {
+        let timestamp = i64::try_from(update.timestamp).with_context(|| {
+            format!(
+                "Oracle update timestamp {} is out of range",
+                update.timestamp
+            )
+        })?;
+        timed_query(
+            "record_oracle_update",
+            sqlx::query(
+                "INSERT INTO oracle_onchain_updates (tx_id, height, rates, ppp_indices, timestamp)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (tx_id) DO NOTHING",
+            )
+            .bind(tx_id)
+            .bind(height as i64)
+            .bind(fixed_point_json(&update.rates))
+            .bind(fixed_point_json(&update.ppp_indices))
+            .bind(timestamp)
+            .execute(&self.pg_pool),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn last_oracle_update(&self) -> anyhow::Result<Option<OraclePriceUpdate>> {
+        let row: Option<(serde_json::Value, serde_json::Value, i64)> = timed_query(
+            "last_oracle_update",
+            sqlx::query_as(
+                "SELECT rates, ppp_indices, timestamp FROM oracle_onchain_updates
+                 ORDER BY height DESC, timestamp DESC LIMIT 1",
+            )
+            .fetch_optional(&self.pg_pool),
+        )
+        .await?;
+        row.map(|(rates, ppp_indices, timestamp)| {
+            Ok(OraclePriceUpdate {
+                rates: fixed_point_from_json(rates)?,
+                ppp_indices: fixed_point_from_json(ppp_indices)?,
+                timestamp: timestamp.max(0) as u128,
+            })
+        })
+        .transpose()
+    }
+
+    /// From the Redis cache, falling back to the `vaults` table when the cache is empty.
+    async fn vaults(&self) -> anyhow::Result<Vec<VaultStatus>> {
+        let cached: Vec<String> = self
+            .with_redis(|mut conn| async move {
+                redis::cmd("HVALS")
+                    .arg(self.redis_key(ACTIVE_VAULTS_KEY))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        let cached: Vec<VaultStatus> = cached
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect();
+        // Entries cached before `collateral_type` existed cannot be priced; the table has it.
+        if !cached.is_empty() && cached.iter().all(|v| !v.collateral_type.is_empty()) {
+            return Ok(cached);
+        }
+        let rows: Vec<(String, String, String, String, Option<i64>)> = timed_query("active_vaults", sqlx::query_as(
+            "SELECT vault_id, collateral_type, collateral_amount::text, debt_amount::text, ltv_bps
+             FROM vaults WHERE active ORDER BY vault_id",
+        )
+        .fetch_all(self.read_pool())).await?;
+        rows.into_iter()
+            .map(|(vault_id, collateral_type, collateral, debt, ltv_bps)| {
+                Ok(VaultStatus {
+                    vault_id,
+                    collateral_type,
+                    collateral_amount: collateral.parse()?,
+                    debt_amount: debt.parse()?,
+                    ltv_ratio: ltv_bps.map(u64::try_from).transpose()?.map(Ratio::from_bps),
+                })
+            })
+            .collect()
+    }
+
+    async fn vault_records(&self, vault_ids: &[String]) -> anyhow::Result<Vec<VaultRecord>> {
+        type Row = (
+            String,
+            String,
+            String,
+            String,
+            Option<i64>,
+            bool,
+            Option<i64>,
+            Option<i32>,
+            Option<i32>,
+        );
+        let rows: Vec<Row> = timed_query(
+            "vault_records",
+            sqlx::query_as(
+                "SELECT vault_id, collateral_type, collateral_amount::text, debt_amount::text,
+                    ltv_bps, active, last_event_height, last_event_tx_index, last_event_index
+             FROM vaults WHERE vault_id = ANY($1)",
+            )
+            .bind(vault_ids)
+            .fetch_all(&self.pg_pool),
+        )
+        .await?;
+        rows.into_iter()
+            .map(
+                |(id, collateral_type, collateral, debt, ltv_bps, active, height, tx, event)| {
+                    let last_event = match (height, tx, event) {
+                        (Some(height), Some(tx_index), Some(event_index)) => Some(EventPosition {
+                            height: height as u64,
+                            tx_index: tx_index as u32,
+                            event_index: event_index as u32,
+                        }),
+                        _ => None,
+                    };
+                    Ok(VaultRecord {
+                        vault: VaultStatus {
+                            vault_id: id,
+                            collateral_type,
+                            collateral_amount: collateral.parse()?,
+                            debt_amount: debt.parse()?,
+                            ltv_ratio: ltv_bps.map(u64::try_from).transpose()?.map(Ratio::from_bps),
+                        },
+                        active,
+                        last_event,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    async fn applied_vault_events(
+        &self,
+        positions: &[EventPosition],
+    ) -> anyhow::Result<HashSet<EventPosition>> {
+        if positions.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let heights: Vec<i64> = positions.iter().map(|p| p.height as i64).collect();
+        let tx_indexes: Vec<i32> = positions.iter().map(|p| p.tx_index as i32).collect();
+        let event_indexes: Vec<i32> = positions.iter().map(|p| p.event_index as i32).collect();
+        let rows: Vec<(i64, i32, i32)> = timed_query(
+            "applied_vault_events",
+            sqlx::query_as(
+                "SELECT a.height, a.tx_index, a.event_index
+                 FROM applied_vault_events a
+                 JOIN UNNEST($1::BIGINT[], $2::INTEGER[], $3::INTEGER[]) AS p(height, tx_index, event_index)
+                   ON (a.height, a.tx_index, a.event_index) = (p.height, p.tx_index, p.event_index)",
+            )
+            .bind(&heights)
+            .bind(&tx_indexes)
+            .bind(&event_indexes)
+            .fetch_all(&self.pg_pool),
+        )
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(height, tx_index, event_index)| EventPosition {
+                height: height as u64,
+                tx_index: tx_index as u32,
+                event_index: event_index as u32,
+            })
+            .collect())
+    }
+
+    async fn save_vault_records(
+        &self,
+        records: &[VaultRecord],
+        events: &[(EventPosition, String)],
+    ) -> anyhow::Result<()> {
+        let mut db_tx = self.pg_pool.begin().await?;
+        for (position, vault_id) in events {
+            timed_query(
+                "save_vault_records",
+                sqlx::query(
+                    "INSERT INTO applied_vault_events (height, tx_index, event_index, vault_id)
+                     VALUES ($1, $2, $3, $4)
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(position.height as i64)
+                .bind(position.tx_index as i32)
+                .bind(position.event_index as i32)
+                .bind(vault_id)
+                .execute(&mut *db_tx),
+            )
+            .await?;
+        }
+        for record in records {
+            let vault = &record.vault;
+            let position = record.last_event;
+            timed_query(
+                "save_vault_records",
+                sqlx::query(
+                    "INSERT INTO vaults (vault_id, collateral_type, collateral_amount, debt_amount,
+                                     ltv_bps, active, last_event_height, last_event_tx_index,
+                                     last_event_index)
+                 VALUES ($1, $2, $3::numeric, $4::numeric, $5, $6, $7, $8, $9)
+                 ON CONFLICT (vault_id) DO UPDATE
+                 SET collateral_type = EXCLUDED.collateral_type,
+                     collateral_amount = EXCLUDED.collateral_amount,
+                     debt_amount = EXCLUDED.debt_amount,
+                     ltv_bps = EXCLUDED.ltv_bps,
+                     active = EXCLUDED.active,
+                     last_event_height = EXCLUDED.last_event_height,
+                     last_event_tx_index = EXCLUDED.last_event_tx_index,
+                     last_event_index = EXCLUDED.last_event_index,
+                     updated_at = NOW()",
+                )
+                .bind(&vault.vault_id)
+                .bind(&vault.collateral_type)
+                .bind(vault.collateral_amount.to_string())
+                .bind(vault.debt_amount.to_string())
+                .bind(vault.ltv_ratio.map(|ltv| ltv.bps() as i64))
+                .bind(record.active)
+                .bind(position.map(|p| p.height as i64))
+                .bind(position.map(|p| p.tx_index as i32))
+                .bind(position.map(|p| p.event_index as i32))
+                .execute(&mut *db_tx),
+            )
+            .await?;
+        }
+        db_tx.commit().await?;
+
+        let mut active = Vec::new();
+        let mut closed = Vec::new();
+        for record in records {
+            if record.active {
+                active.push((
+                    record.vault.vault_id.clone(),
+                    serde_json::to_string(&record.vault)?,
+                ));
+            } else {
+                closed.push(record.vault.vault_id.clone());
+            }
+        }
+        if !active.is_empty() {
+            self.with_redis(|mut conn| {
+                let mut cmd = redis::cmd("HSET");
+                cmd.arg(self.redis_key(ACTIVE_VAULTS_KEY)).arg(&active);
+                async move { cmd.query_async::<()>(&mut conn).await }
+            })
+            .await?;
+        }
+        if !closed.is_empty() {
+            self.with_redis(|mut conn| {
+                let mut cmd = redis::cmd("HDEL");
+                cmd.arg(self.redis_key(ACTIVE_VAULTS_KEY)).arg(&closed);
+                async move { cmd.query_async::<()>(&mut conn).await }
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn record_dead_letter(&self, letter: &DeadLetter) -> anyhow::Result<()> {
+        timed_query("record_dead_letter", sqlx::query(
+            "INSERT INTO sync_dead_letter (event_type, payload, attempts, last_error, created_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&letter.event_type)
+        .bind(&letter.payload)
+        .bind(letter.attempts as i32)
+        .bind(&letter.last_error)
+        .bind(letter.created_at)
+        .execute(&self.pg_pool)).await?;
+        Ok(())
+    }
+
+    async fn dead_letters(&self, limit: u32) -> anyhow::Result<Vec<DeadLetter>> {
+        let rows: Vec<(i64, String, String, i32, String, DateTime<Utc>)> = timed_query(
+            "dead_letters",
+            sqlx::query_as(
+                "SELECT id, event_type, payload, attempts, last_error, created_at
+             FROM sync_dead_letter ORDER BY id DESC LIMIT $1",
+            )
+            .bind(limit as i64)
+            .fetch_all(self.read_pool()),
+        )
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, event_type, payload, attempts, last_error, created_at)| DeadLetter {
+                    id,
+                    event_type,
+                    payload,
+                    attempts: attempts.max(0) as u32,
+                    last_error,
+                    created_at,
+                },
+            )
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct MemoryState {
+    blocks: BTreeMap<String, NewBlock>,
+    transactions: BTreeMap<String, NewTransaction>,
+    ingested_at: HashMap<String, DateTime<Utc>>,
+    /// Tx ids ingested since the last installed snapshot, in commit order.
+    tx_order: Vec<String>,
+    /// Leaves committed since the last installed snapshot, in commit order: `tx_order`
+    /// interleaved with gateway leaves.
+    leaf_order: Vec<String>,
+    gateway_leaves: HashSet<String>,
+    snapshot_leaves: Vec<String>,
+    watermark: Option<u64>,
+    /// Heights ingested above a gap in front of `watermark`.
+    processed_heights: BTreeSet<u64>,
+    state_root: Option<String>,
+    root_history: HashMap<String, (RootCommitment, u64)>,
+    /// Oldest first.
+    anchors: Vec<StateAnchor>,
+    safety_mode: bool,
+    safety_reason: Option<String>,
+    drift: Option<u64>,
+    l1_unreachable: Option<u32>,
+    oracle_stale: Option<u64>,
+    incidents: Vec<SafetyIncident>,
+    drift_sample: Option<(u64, f64)>,
+    oracle_timestamp: Option<u64>,
+    collateral_prices: BTreeMap<String, f64>,
+    /// Confirmed oracle updates by txid, with their heights.
+    oracle_updates: HashMap<String, (u64, OraclePriceUpdate)>,
+    events: Vec<NexusEvent>,
+    executions: Vec<ExecutionRecord>,
+    outcome_counters: HashMap<String, u64>,
+    nonces: HashMap<String, u64>,
+    rebalance_nonce: u64,
+    sender_submissions: HashMap<String, Vec<(Instant, String)>>,
+    idempotency: HashMap<String, (IdempotencyEntry, Instant)>,
+    locks: HashMap<String, (String, Instant)>,
+    vaults: Vec<VaultStatus>,
+    vault_records: BTreeMap<String, VaultRecord>,
+    applied_vault_events: HashSet<EventPosition>,
+    dead_letters: Vec<DeadLetter>,
+    ingest_failures: u32,
+    root_save_failures: u32,
+    execution_failures: u32,
+}
+
+impl MemoryState {
+    /// As the Postgres store: the watermark only moves over contiguous ingested heights.
+    fn advance_watermark(&mut self, height: u64) {
+        let mut watermark = *self.watermark.get_or_insert(height);
+        self.processed_heights.insert(height);
+        while self.processed_heights.contains(&(watermark + 1)) {
+            watermark += 1;
+        }
+        self.processed_heights.retain(|h| *h > watermark);
+        self.watermark = Some(watermark);
+    }
+}
+
+/// In-process [`NexusStore`] for tests. Broadcasts are recorded instead of published.
+#[derive(Default)]
+pub struct InMemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Events broadcast so far, oldest first.
+    pub fn events(&self) -> Vec<NexusEvent> {
+        self.state.lock().unwrap().events.clone()
+    }
+
+    pub fn drift(&self) -> Option<u64> {
+        self.state.lock().unwrap().drift
+    }
+
+    pub fn state_root(&self) -> Option<String> {
+        self.state.lock().unwrap().state_root.clone()
+    }
+
+    pub fn l1_unreachable(&self) -> Option<u32> {
+        self.state.lock().unwrap().l1_unreachable
+    }
+
+    pub fn drift_sample(&self) -> Option<(u64, f64)> {
+        self.state.lock().unwrap().drift_sample
+    }
+
+    /// Age of the aggregate that put the store into the oracle-stale state.
+    pub fn oracle_stale(&self) -> Option<u64> {
+        self.state.lock().unwrap().oracle_stale
+    }
+
+    /// Safety incidents, oldest first.
+    pub fn incidents(&self) -> Vec<SafetyIncident> {
+        self.state.lock().unwrap().incidents.clone()
+    }
+
+    pub fn set_oracle_timestamp(&self, timestamp: u64) {
+        self.state.lock().unwrap().oracle_timestamp = Some(timestamp);
+    }
+
+    pub fn set_collateral_prices(&self, prices: BTreeMap<String, f64>) {
+        self.state.lock().unwrap().collateral_prices = prices;
+    }
+
+    pub fn set_vaults(&self, vaults: Vec<VaultStatus>) {
+        self.state.lock().unwrap().vaults = vaults;
+    }
+
+    /// Makes the next `count` calls to `ingest_block` fail; 0 clears pending failures.
+    pub fn fail_next_ingests(&self, count: u32) {
+        self.state.lock().unwrap().ingest_failures = count;
+    }
+
+    /// Makes the next `count` calls to `save_state_root` fail; 0 clears pending failures.
+    pub fn fail_next_root_saves(&self, count: u32) {
+        self.state.lock().unwrap().root_save_failures = count;
+    }
+
+    /// Makes the next `count` calls to `record_execution` fail; 0 clears pending failures.
+    pub fn fail_next_executions(&self, count: u32) {
+        self.state.lock().unwrap().execution_failures = count;
+    }
+}
+
+#[async_trait]
+impl NexusStore for InMemoryStore {
+    async fn ingest_block(
+        &self,
+        block: &NewBlock,
+        transactions: &[NewTransaction],
+    ) -> anyhow::Result<Vec<String>> {
+        let mut state = self.state.lock().unwrap();
+        if state.ingest_failures > 0 {
+            state.ingest_failures -= 1;
+            anyhow::bail!("injected ingest failure");
+        }
+        if let Some(tx) = transactions
+            .iter()
+            .find(|tx| tx.block_hash != block.hash && !state.blocks.contains_key(&tx.block_hash))
+        {
+            anyhow::bail!("transaction {} references unknown block", tx.tx_id);
+        }
+        state
+            .blocks
+            .entry(block.hash.clone())
+            .or_insert_with(|| block.clone());
+        let mut written = Vec::new();
+        for tx in transactions {
+            if !state.transactions.contains_key(&tx.tx_id) {
+                state.transactions.insert(tx.tx_id.clone(), tx.clone());
+                state.ingested_at.insert(tx.tx_id.clone(), Utc::now());
+                state.tx_order.push(tx.tx_id.clone());
+                state.leaf_order.push(tx.tx_id.clone());
+                written.push(tx.tx_id.clone());
+            }
+        }
+        Ok(written)
+    }
+
+    async fn insert_block(&self, block: &NewBlock) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        if state.blocks.contains_key(&block.hash) {
+            return Ok(false);
+        }
+        state.blocks.insert(block.hash.clone(), block.clone());
+        Ok(true)
+    }
+
+    async fn finalize_blocks_up_to(&self, height: u64) -> anyhow::Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        let mut promoted = 0;
+        for block in state.blocks.values_mut() {
+            if block.state == "soft" && block.height <= height {
+                block.state = "hard".to_string();
+                promoted += 1;
+            }
+        }
+        Ok(promoted)
+    }
+
+    async fn max_block_height(&self) -> anyhow::Result<u64> {
+        let state = self.state.lock().unwrap();
+        Ok(state.blocks.values().map(|b| b.height).max().unwrap_or(0))
+    }
+
+    async fn max_processed_height(&self) -> anyhow::Result<u64> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .blocks
+            .values()
+            .filter(|b| b.state != "orphaned")
+            .map(|b| b.height)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn last_block_time(&self) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .blocks
+            .values()
+            .filter(|b| b.state != "orphaned")
+            .filter_map(|b| b.block_time)
+            .max())
+    }
+
+    async fn chain_counts(&self) -> anyhow::Result<ChainCounts> {
+        let state = self.state.lock().unwrap();
+        let live = |hash: &str| {
+            state
+                .blocks
+                .get(hash)
+                .is_some_and(|b| b.state != "orphaned")
+        };
+        Ok(ChainCounts {
+            transactions: state
+                .transactions
+                .values()
+                .filter(|tx| live(&tx.block_hash))
+                .count() as u64,
+            blocks: state
+                .blocks
+                .values()
+                .filter(|b| b.state != "orphaned")
+                .count() as u64,
+        })
+    }
+
+    async fn transactions_by_sender(
+        &self,
+        sender: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<StoredTransaction>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .tx_order
+            .iter()
+            .rev()
+            .filter_map(|tx_id| state.transactions.get(tx_id))
+            .filter(|tx| tx.sender.as_deref() == Some(sender))
+            .take(limit as usize)
+            .map(|tx| StoredTransaction {
+                tx_id: tx.tx_id.clone(),
+                block_hash: tx.block_hash.clone(),
+                payload: tx.payload.clone(),
+                sender: tx.sender.clone(),
+                action: tx.action.map(|a| a.to_string()),
+                created_at: state.ingested_at[&tx.tx_id],
+            })
+            .collect())
+    }
+
+    async fn sender_transactions_since(
+        &self,
+        sender: &str,
+        since: DateTime<Utc>,
+    ) -> anyhow::Result<u64> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .transactions
+            .values()
+            .filter(|tx| tx.sender.as_deref() == Some(sender))
+            .filter(|tx| {
+                state.blocks.get(&tx.block_hash).is_some_and(|block| {
+                    block.state != "orphaned" && block.block_time.is_some_and(|t| t >= since)
+                })
+            })
+            .count() as u64)
+    }
+
+    async fn sync_watermark(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().watermark)
+    }
+
+    async fn seed_sync_watermark(&self, height: u64) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        if state.watermark.is_some() {
+            return Ok(false);
+        }
+        state.watermark = Some(height);
+        Ok(true)
+    }
+
+    async fn advance_sync_watermark(&self, height: u64) -> anyhow::Result<()> {
+        self.state.lock().unwrap().advance_watermark(height);
+        Ok(())
+    }
+
+    async fn save_state_root(&self, root: &str) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.root_save_failures > 0 {
+            state.root_save_failures -= 1;
+            anyhow::bail!("injected state root failure");
+        }
+        state.state_root = Some(root.to_string());
+        Ok(())
+    }
+
+    async fn state_leaves(&self) -> anyhow::Result<Vec<String>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .snapshot_leaves
+            .iter()
+            .chain(&state.leaf_order)
+            .cloned()
+            .collect())
+    }
+
+    async fn append_gateway_leaf(&self, _service: &str, leaf: &str) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        if !state.gateway_leaves.insert(leaf.to_string()) {
+            return Ok(false);
+        }
+        state.leaf_order.push(leaf.to_string());
+        Ok(true)
+    }
+
+    async fn install_snapshot(
+        &self,
+        snapshot: &StateSnapshot,
+        commitment: &RootCommitment,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.tx_order.is_empty()
+            || !state.leaf_order.is_empty()
+            || !state.snapshot_leaves.is_empty()
+        {
+            return Err(InstallError::NotEmpty.into());
+        }
+        state.snapshot_leaves = snapshot.leaves.clone();
+        if let Some(height) = snapshot.height {
+            state.watermark = Some(height);
+            state
+                .root_history
+                .entry(commitment.root.clone())
+                .or_insert_with(|| (commitment.clone(), height));
+        }
+        if let Some(checkpoint) = &snapshot.checkpoint {
+            let mut anchor = unconfirmed(checkpoint);
+            anchor.tx_id = canonical_leaf(&anchor.tx_id).into_owned();
+            state.anchors.push(anchor);
+        }
+        Ok(())
+    }
+
+    async fn record_state_root(
+        &self,
+        commitment: &RootCommitment,
+        height: u64,
+    ) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .root_history
+            .entry(commitment.root.clone())
+            .or_insert_with(|| (commitment.clone(), height));
+        Ok(())
+    }
+
+    async fn root_leaf_count(&self, root: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .root_history
+            .get(root)
+            .map(|(c, _)| c.leaf_count))
+    }
+
+    async fn root_height(&self, root: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .root_history
+            .get(root)
+            .map(|(_, height)| *height))
+    }
+
+    async fn record_anchor(&self, anchor: &StateAnchor) -> anyhow::Result<()> {
+        let mut anchor = anchor.clone();
+        anchor.tx_id = canonical_leaf(&anchor.tx_id).into_owned();
+        self.state.lock().unwrap().anchors.push(anchor);
+        Ok(())
+    }
+
+    async fn last_anchor(&self) -> anyhow::Result<Option<StateAnchor>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .anchors
+            .iter()
+            .rev()
+            .find(|a| a.status != AnchorStatus::Failed)
+            .cloned())
+    }
+
+    async fn last_confirmed_anchor(&self) -> anyhow::Result<Option<StateAnchor>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .anchors
+            .iter()
+            .rev()
+            .find(|a| a.status == AnchorStatus::Confirmed)
+            .cloned())
+    }
+
+    async fn anchors(&self, limit: u32) -> anyhow::Result<Vec<StateAnchor>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .anchors
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn confirmed_anchor(&self, root: &str) -> anyhow::Result<Option<StateAnchor>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .anchors
+            .iter()
+            .rev()
+            .find(|a| a.root == root && a.status == AnchorStatus::Confirmed)
+            .cloned())
+    }
+
+    async fn confirm_anchors(
+        &self,
+        succeeded: &[String],
+        aborted: &[(String, String)],
+        height: u64,
+    ) -> anyhow::Result<u64> {
+        let succeeded: Vec<String> = succeeded
+            .iter()
+            .map(|id| canonical_leaf(id).into_owned())
+            .collect();
+        let aborted: HashMap<String, &str> = aborted
+            .iter()
+            .map(|(id, status)| (canonical_leaf(id).into_owned(), status.as_str()))
+            .collect();
+        let mut state = self.state.lock().unwrap();
+        let mut confirmed = 0;
+        for anchor in state.anchors.iter_mut() {
+            if anchor.status != AnchorStatus::Broadcast {
+                continue;
+            }
+            if succeeded.contains(&anchor.tx_id) {
+                anchor.status = AnchorStatus::Confirmed;
+                anchor.confirmed_height = Some(height);
+                confirmed += 1;
+            } else if let Some(status) = aborted.get(&anchor.tx_id) {
+                anchor.status = AnchorStatus::Failed;
+                anchor.error = Some(format!("Aborted on-chain: {}", status));
+            }
+        }
+        Ok(confirmed)
+    }
+
+    async fn revert_reorged_anchors(&self, height: u64, block_hash: &str) -> anyhow::Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        let forked = state.blocks.values().any(|block| {
+            block.block_type == "microblock" && block.height == height && block.hash != block_hash
+        });
+        if !forked {
+            return Ok(0);
+        }
+        let mut reverted = 0;
+        for anchor in state.anchors.iter_mut() {
+            if anchor.status == AnchorStatus::Confirmed
+                && anchor.confirmed_height.is_some_and(|h| h >= height)
+            {
+                anchor.status = AnchorStatus::Broadcast;
+                anchor.confirmed_height = None;
+                reverted += 1;
+            }
+        }
+        Ok(reverted)
+    }
+
+    async fn is_safety_mode_active(&self) -> anyhow::Result<bool> {
+        Ok(self.state.lock().unwrap().safety_mode)
+    }
+
+    async fn safety_reason(&self) -> anyhow::Result<Option<String>> {
+        Ok(self.state.lock().unwrap().safety_reason.clone())
+    }
+
+    async fn publish_event(&self, event: &NexusEvent) -> anyhow::Result<()> {
+        self.state.lock().unwrap().events.push(event.clone());
+        Ok(())
+    }
+
+    async fn activate_safety_mode(
+        &self,
+        drift: Option<u64>,
+        trigger: SafetyTrigger,
+        height: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.safety_mode = true;
+        state.safety_reason = Some(trigger.as_str().to_string());
+        if drift.is_some() {
+            state.drift = drift;
+        }
+        state.events.push(NexusEvent::SafetyTriggered {
+            cause: trigger.as_str().to_string(),
+            drift,
+            height,
+        });
+        Ok(())
+    }
+
+    async fn clear_safety_mode(&self) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.safety_mode = false;
+        state.safety_reason = None;
+        state.drift = None;
+        state
+            .events
+            .push(NexusEvent::SafetyCleared { drill: false });
+        Ok(())
+    }
+
+    async fn mark_l1_unreachable(&self, failures: u32) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.safety_mode = true;
+        state.safety_reason = Some(SafetyTrigger::L1Unreachable.as_str().to_string());
+        state.l1_unreachable = Some(failures);
+        state.events.push(NexusEvent::L1Unreachable { failures });
+        Ok(())
+    }
+
+    async fn clear_l1_unreachable(&self) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.l1_unreachable = None;
+        state.events.push(NexusEvent::L1Reachable);
+        Ok(())
+    }
+
+    async fn record_drift_sample(&self, raw: u64, smoothed: f64) -> anyhow::Result<()> {
+        self.state.lock().unwrap().drift_sample = Some((raw, smoothed));
+        Ok(())
+    }
+
+    async fn is_oracle_stale(&self) -> anyhow::Result<bool> {
+        Ok(self.state.lock().unwrap().oracle_stale.is_some())
+    }
+
+    async fn mark_oracle_stale(&self, age_secs: u64) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.safety_mode = true;
+        state
+            .safety_reason
+            .get_or_insert_with(|| SafetyTrigger::OracleStale.as_str().to_string());
+        state.oracle_stale = Some(age_secs);
+        state.events.push(NexusEvent::OracleStale { age_secs });
+        Ok(())
+    }
+
+    async fn clear_oracle_stale(&self) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.oracle_stale = None;
+        state.events.push(NexusEvent::OracleFresh);
+        Ok(())
+    }
+
+    async fn open_incident(
+        &self,
+        trigger: SafetyTrigger,
+        detail: &serde_json::Value,
+    ) -> anyhow::Result<SafetyIncident> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(open) = state
+            .incidents
+            .iter()
+            .find(|i| i.cause == trigger.as_str() && i.resolved_at.is_none())
+        {
+            return Ok(open.clone());
+        }
+        let incident = SafetyIncident {
+            id: state.incidents.len() as i64 + 1,
+            cause: trigger.as_str().to_string(),
+            detail: detail.clone(),
+            opened_at: Utc::now(),
+            resolved_at: None,
+        };
+        state.incidents.push(incident.clone());
+        Ok(incident)
+    }
+
+    async fn resolve_incident(
+        &self,
+        trigger: SafetyTrigger,
+    ) -> anyhow::Result<Option<SafetyIncident>> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state
+            .incidents
+            .iter_mut()
+            .find(|i| i.cause == trigger.as_str() && i.resolved_at.is_none())
+            .map(|open| {
+                open.resolved_at = Some(Utc::now());
+                open.clone()
+            }))
+    }
+
+    async fn record_execution(&self, request: &ExecutionRequest) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.execution_failures > 0 {
+            state.execution_failures -= 1;
+            anyhow::bail!("injected execution record failure");
+        }
+        let sequence = state.executions.len() as u64 + 1;
+        state
+            .executions
+            .push(ExecutionRecord::of(request, sequence, None));
+        Ok(())
+    }
+
+    async fn record_rejection(
+        &self,
+        request: &ExecutionRequest,
+        reason: RejectReason,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let sequence = state.executions.len() as u64 + 1;
+        state
+            .executions
+            .push(ExecutionRecord::of(request, sequence, Some(reason)));
+        Ok(())
+    }
+
+    async fn latest_execution_time(
+        &self,
+        finality: FinalityLevel,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let state = self.state.lock().unwrap();
+        let visible = |tx_id: &str| match finality {
+            FinalityLevel::Soft => true,
+            FinalityLevel::Hard => state
+                .transactions
+                .get(tx_id)
+                .and_then(|tx| state.blocks.get(&tx.block_hash))
+                .is_some_and(|b| b.state == "hard"),
+        };
+        Ok(state
+            .executions
+            .iter()
+            .filter(|e| e.status == ExecutionStatus::Accepted && visible(&e.tx_id))
+            .map(|e| e.timestamp)
+            .max())
+    }
+
+    async fn execution(&self, tx_id: &str) -> anyhow::Result<Option<ExecutionRecord>> {
+        let state = self.state.lock().unwrap();
+        let recorded = state.executions.iter().filter(|e| e.tx_id == tx_id);
+        Ok(recorded
+            .max_by_key(|e| (e.status == ExecutionStatus::Accepted, e.sequence))
+            .cloned())
+    }
+
+    async fn executions(
+        &self,
+        filter: &ExecutionFilter,
+        limit: u32,
+    ) -> anyhow::Result<Vec<ExecutionRecord>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .executions
+            .iter()
+            .filter(|e| filter.sender.as_ref().is_none_or(|s| &e.sender == s))
+            .filter(|e| filter.status.is_none_or(|status| e.status == status))
+            .filter(|e| filter.since.is_none_or(|since| e.timestamp >= since))
+            .filter(|e| filter.after_sequence.is_none_or(|after| e.sequence > after))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn record_execution_outcome(&self, outcome: ExecutionOutcome) -> anyhow::Result<()> {
+        *self
+            .state
+            .lock()
+            .unwrap()
+            .outcome_counters
+            .entry(outcome.stat_key())
+            .or_default() += 1;
+        Ok(())
+    }
+
+    async fn executor_stats(&self) -> anyhow::Result<ExecutorStats> {
+        Ok(ExecutorStats::from_counters(
+            &self.state.lock().unwrap().outcome_counters,
+        ))
+    }
+
+    async fn last_nonce(&self, sender: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().nonces.get(sender).copied())
+    }
+
+    async fn claim_nonce(&self, sender: &str, nonce: u64) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        match state.nonces.get(sender) {
+            Some(&last) if nonce <= last => Ok(false),
+            _ => {
+                state.nonces.insert(sender.to_string(), nonce);
+                Ok(true)
+            }
+        }
+    }
+
+    async fn release_nonce(&self, sender: &str, nonce: u64) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.nonces.get(sender) == Some(&nonce) {
+            match nonce.checked_sub(1) {
+                Some(previous) => state.nonces.insert(sender.to_string(), previous),
+                None => state.nonces.remove(sender),
+            };
+        }
+        Ok(())
+    }
+
+    async fn next_rebalance_nonce(&self) -> anyhow::Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        state.rebalance_nonce = state
+            .rebalance_nonce
+            .checked_add(1)
+            .context("Rebalance nonce counter is exhausted")?;
+        Ok(state.rebalance_nonce)
+    }
+
+    async fn submission_count(&self, submitter: &str, window: Duration) -> anyhow::Result<u64> {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+        Ok(state
+            .sender_submissions
+            .get(submitter)
+            .map_or(0, |submissions| {
+                submissions
+                    .iter()
+                    .filter(|(at, _)| now.duration_since(*at) < window)
+                    .count() as u64
+            }))
+    }
+
+    async fn admit_submission(
+        &self,
+        submitter: &str,
+        tx_id: &str,
+        limit: u64,
+        window: Duration,
+    ) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let submissions = state
+            .sender_submissions
+            .entry(submitter.to_string())
+            .or_default();
+        submissions.retain(|(at, _)| now.duration_since(*at) < window);
+        if submissions.len() as u64 >= limit {
+            return Ok(false);
+        }
+        submissions.push((now, tx_id.to_string()));
+        Ok(true)
+    }
+
+    async fn withdraw_submission(&self, submitter: &str, tx_id: &str) -> anyhow::Result<()> {
+        if let Some(submissions) = self
+            .state
+            .lock()
+            .unwrap()
+            .sender_submissions
+            .get_mut(submitter)
+        {
+            submissions.retain(|(_, admitted)| admitted != tx_id);
+        }
+        Ok(())
+    }
+
+    async fn idempotency_entry(&self, key: &str) -> anyhow::Result<Option<IdempotencyEntry>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .idempotency
+            .get(key)
+            .filter(|(_, expires)| *expires > Instant::now())
+            .map(|(entry, _)| entry.clone()))
+    }
+
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        request_hash: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if state
+            .idempotency
+            .get(key)
+            .is_some_and(|(_, expires)| *expires > now)
+        {
+            return Ok(false);
+        }
+        let pending = IdempotencyEntry::Pending {
+            request_hash: request_hash.to_string(),
+        };
+        state
+            .idempotency
+            .insert(key.to_string(), (pending, now + ttl));
+        Ok(true)
+    }
+
+    async fn record_idempotent_outcome(
+        &self,
+        key: &str,
+        outcome: &IdempotentOutcome,
+        ttl: Duration,
+    ) -> anyhow::Result<()> {
+        self.state.lock().unwrap().idempotency.insert(
+            key.to_string(),
+            (
+                IdempotencyEntry::Done(outcome.clone()),
+                Instant::now() + ttl,
+            ),
+        );
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, key: &str, request_hash: &str) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.idempotency.get(key).is_some_and(|(entry, _)| {
+            matches!(entry, IdempotencyEntry::Pending { request_hash: held } if held == request_hash)
+        }) {
+            state.idempotency.remove(key);
+        }
+        Ok(())
+    }
+
+    async fn try_acquire_lock(
+        &self,
+        name: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if state
+            .locks
+            .get(name)
+            .is_some_and(|(_, expires)| *expires > now)
+        {
+            return Ok(false);
+        }
+        state
+            .locks
+            .insert(name.to_string(), (token.to_string(), now + ttl));
+        Ok(true)
+    }
+
+    async fn renew_lock(&self, name: &str, token: &str, ttl: Duration) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match state.locks.get_mut(name) {
+            Some((held, expires)) if held == token && *expires > now => {
+                *expires = now + ttl;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release_lock(&self, name: &str, token: &str) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.locks.get(name).is_some_and(|(held, _)| held == token) {
+            state.locks.remove(name);
+        }
+        Ok(())
+    }
+
+    async fn latest_oracle_timestamp(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().oracle_timestamp)
+    }
+
+    async fn collateral_prices(&self) -> anyhow::Result<BTreeMap<String, f64>> {
+        Ok(self.state.lock().unwrap().collateral_prices.clone())
+    }
+
+    async fn record_oracle_update(
+        &self,
+        tx_id: &str,
+        height: u64,
+        update: &OraclePriceUpdate,
+    ) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .oracle_updates
+            .entry(tx_id.to_string())
+            .or_insert_with(|| (height, update.clone()));
+        Ok(())
+    }
+
+    async fn last_oracle_update(&self) -> anyhow::Result<Option<OraclePriceUpdate>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .oracle_updates
+            .values()
+            .max_by_key(|(height, update)| (*height, update.timestamp))
+            .map(|(_, update)| update.clone()))
+    }
+
+    async fn vaults(&self) -> anyhow::Result<Vec<VaultStatus>> {
+        Ok(self.state.lock().unwrap().vaults.clone())
+    }
+
+    async fn vault_records(&self, vault_ids: &[String]) -> anyhow::Result<Vec<VaultRecord>> {
+        let state = self.state.lock().unwrap();
+        Ok(vault_ids
+            .iter()
+            .filter_map(|id| {
+                state.vault_records.get(id).cloned().or_else(|| {
+                    let vault = state.vaults.iter().find(|v| &v.vault_id == id)?;
+                    Some(VaultRecord {
+                        vault: vault.clone(),
+                        active: true,
+                        last_event: None,
+                    })
+                })
+            })
+            .collect())
+    }
+
+    async fn applied_vault_events(
+        &self,
+        positions: &[EventPosition],
+    ) -> anyhow::Result<HashSet<EventPosition>> {
+        let state = self.state.lock().unwrap();
+        Ok(positions
+            .iter()
+            .filter(|position| state.applied_vault_events.contains(*position))
+            .copied()
+            .collect())
+    }
+
+    async fn save_vault_records(
+        &self,
+        records: &[VaultRecord],
+        events: &[(EventPosition, String)],
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .applied_vault_events
+            .extend(events.iter().map(|(position, _)| *position));
+        for record in records {
+            let id = &record.vault.vault_id;
+            state.vault_records.insert(id.clone(), record.clone());
+            state.vaults.retain(|v| &v.vault_id != id);
+            if record.active {
+                state.vaults.push(record.vault.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn record_dead_letter(&self, letter: &DeadLetter) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let id = state.dead_letters.len() as i64 + 1;
+        state.dead_letters.push(DeadLetter {
+            id,
+            ..letter.clone()
+        });
+        Ok(())
+    }
+
+    async fn dead_letters(&self, limit: u32) -> anyhow::Result<Vec<DeadLetter>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .dead_letters
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_ingest_rejects_dangling_transactions() {
+        let store = InMemoryStore::new();
+        let dangling = NewTransaction {
+            tx_id: "tx".to_string(),
+            block_hash: "missing".to_string(),
+            payload: None,
+            sender: None,
+            action: None,
+        };
+        assert!(store
+            .ingest_block(&NewBlock::microblock("0xa", 5), &[dangling])
+            .await
+            .is_err());
+        assert_eq!(store.sync_watermark().await.unwrap(), None);
+        assert_eq!(store.max_block_height().await.unwrap(), 0);
+    }
+}
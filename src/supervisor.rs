@@ -0,0 +1,208 @@
+//! Restarts long-running background services when they fail. A service that returns an
+//! error or panics is restarted with exponential backoff; one that fails more than
+//! `max_restarts` times within `window` trips the circuit, and the supervisor returns
+//! the error so the process can exit and be replaced. A service that returns `Ok` has
+//! stopped on purpose and is not restarted.
+
+use prometheus::{opts, register_int_counter_vec, IntCounterVec};
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+pub const DEFAULT_MAX_RESTARTS: u32 = 5;
+pub const DEFAULT_RESTART_WINDOW: Duration = Duration::from_secs(600);
+const DEFAULT_BASE_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    static ref SERVICE_RESTARTS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "nexus_service_restarts_total",
+            "Restarts of supervised background services after a failure"
+        ),
+        &["service"]
+    )
+    .unwrap();
+
+    static ref RESTART_COUNTS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+}
+
+/// Restarts per supervised service since startup; every supervised service is listed.
+pub fn restart_counts() -> BTreeMap<String, u64> {
+    RESTART_COUNTS.lock().unwrap().clone()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Supervisor {
+    /// Delay before the first restart; doubled for each further restart in the window.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Restarts allowed within `window`; the next failure trips the circuit.
+    pub max_restarts: u32,
+    pub window: Duration,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self {
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            window: DEFAULT_RESTART_WINDOW,
+        }
+    }
+}
+
+impl Supervisor {
+    pub fn new(max_restarts: u32, window: Duration) -> Self {
+        Self {
+            max_restarts,
+            window,
+            ..Self::default()
+        }
+    }
+
+    /// Runs the future returned by `start` on its own task, in a span carrying
+    /// `service = name`, until it returns `Ok`, restarting it after errors and panics.
+    /// Returns an error once the circuit trips.
+    pub async fn supervise<F, Fut>(&self, name: &str, mut start: F) -> anyhow::Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        RESTART_COUNTS
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert(0);
+        let mut failures: VecDeque<Instant> = VecDeque::new();
+        loop {
+            let run = start().instrument(tracing::info_span!("service", service = name));
+            let error = match tokio::spawn(run).await {
+                Ok(Ok(())) => {
+                    tracing::info!(service = name, "Supervised service stopped");
+                    return Ok(());
+                }
+                Ok(Err(e)) => e,
+                Err(e) if e.is_panic() => anyhow::anyhow!("panicked: {}", panic_message(e)),
+                Err(e) => anyhow::anyhow!("task aborted: {}", e),
+            };
+
+            let now = Instant::now();
+            while failures
+                .front()
+                .is_some_and(|t| now.duration_since(*t) > self.window)
+            {
+                failures.pop_front();
+            }
+            if failures.len() >= self.max_restarts as usize {
+                tracing::error!(
+                    service = name,
+                    restarts = failures.len(),
+                    window_secs = self.window.as_secs(),
+                    error = %error,
+                    "Supervised service keeps failing; giving up"
+                );
+                return Err(error.context(format!(
+                    "{} failed {} times within {}s",
+                    name,
+                    failures.len() + 1,
+                    self.window.as_secs()
+                )));
+            }
+
+            let delay = self
+                .base_delay
+                .saturating_mul(1 << failures.len().min(16))
+                .min(self.max_delay);
+            failures.push_back(now);
+            SERVICE_RESTARTS.with_label_values(&[name]).inc();
+            *RESTART_COUNTS
+                .lock()
+                .unwrap()
+                .entry(name.to_string())
+                .or_insert(0) += 1;
+            tracing::warn!(
+                service = name,
+                error = %error,
+                delay_ms = delay.as_millis() as u64,
+                "Supervised service failed; restarting"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn panic_message(e: tokio::task::JoinError) -> String {
+    let payload = e.into_panic();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn fast(max_restarts: u32) -> Supervisor {
+        Supervisor {
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            ..Supervisor::new(max_restarts, Duration::from_secs(60))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_service_restarts_then_trips_circuit() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let err = fast(3)
+            .supervise("test-always-failing", move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) % 2 == 0 {
+                        Err::<(), _>(anyhow::anyhow!("injected failure"))
+                    } else {
+                        panic!("injected panic")
+                    }
+                }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+        assert!(err.to_string().contains("failed 4 times"));
+        assert_eq!(restart_counts()["test-always-failing"], 3);
+        assert_eq!(
+            SERVICE_RESTARTS
+                .with_label_values(&["test-always-failing"])
+                .get(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recovered_service_is_not_restarted_after_clean_exit() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        fast(3)
+            .supervise("test-recovers", move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        panic!("injected panic");
+                    }
+                    anyhow::Ok(())
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(restart_counts()["test-recovers"], 1);
+    }
+}
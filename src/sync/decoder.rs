@@ -0,0 +1,691 @@
+//! [NEXUS-SYNC-04] Recognizes calls to Conxian contract functions in Stacks transaction
+//! payloads. Only the `contract_call` of a `contract_call` transaction to a configured
+//! contract is consulted, so memo text, other payload contents or a lookalike contract
+//! cannot masquerade as an action.
+//! Vault changes are read from the `print` events of the vault contracts, and oracle
+//! prices from the arguments of oracle update calls. Values are decoded from their
+//! serialized `hex` form when the API supplies it, else from the `repr` text.
+
+use crate::executor::fixed::Amount;
+use crate::stacks::ClarityValue;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+/// Deepest nesting accepted in a Clarity value `repr`.
+const MAX_REPR_DEPTH: usize = 16;
+
+/// Conxian contract functions the sequencer and rebalancer act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConxianAction {
+    Deposit,
+    Withdraw,
+    Liquidate,
+    Mint,
+}
+
+impl ConxianAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Deposit => "deposit",
+            Self::Withdraw => "withdraw",
+            Self::Liquidate => "liquidate",
+            Self::Mint => "mint",
+        }
+    }
+
+    /// The action invoked by `function_name`, matched exactly.
+    pub fn from_function_name(function_name: &str) -> Option<Self> {
+        match function_name {
+            "deposit" => Some(Self::Deposit),
+            "withdraw" => Some(Self::Withdraw),
+            "liquidate" => Some(Self::Liquidate),
+            "mint" => Some(Self::Mint),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConxianAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConxianAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_function_name(s).ok_or_else(|| anyhow::anyhow!("Unknown Conxian action '{}'", s))
+    }
+}
+
+#[derive(Deserialize)]
+struct TxPayload {
+    tx_type: String,
+    #[serde(default)]
+    tx_status: Option<String>,
+    /// Position of the transaction in its block.
+    #[serde(default)]
+    tx_index: u32,
+    contract_call: Option<ContractCall>,
+    #[serde(default)]
+    events: Vec<TxEvent>,
+}
+
+#[derive(Deserialize)]
+struct TxEvent {
+    #[serde(default)]
+    event_index: u32,
+    event_type: String,
+    contract_log: Option<ContractLog>,
+}
+
+#[derive(Deserialize)]
+struct ContractLog {
+    #[serde(default)]
+    contract_id: String,
+    #[serde(default)]
+    topic: String,
+    value: ClarityRepr,
+}
+
+#[derive(Deserialize)]
+struct ClarityRepr {
+    #[serde(default)]
+    hex: Option<String>,
+    #[serde(default)]
+    repr: String,
+}
+
+#[derive(Deserialize)]
+struct ContractCall {
+    #[serde(default)]
+    contract_id: String,
+    function_name: String,
+    #[serde(default)]
+    function_args: Vec<FunctionArg>,
+}
+
+#[derive(Deserialize)]
+struct FunctionArg {
+    hex: String,
+}
+
+#[derive(Deserialize)]
+struct TxSender {
+    sender_address: String,
+}
+
+#[derive(Deserialize)]
+struct TxStatus {
+    tx_status: String,
+}
+
+/// Principal that signed the transaction, from the payload's `sender_address`.
+pub fn decode_sender(payload: &str) -> Option<String> {
+    let tx: TxSender = serde_json::from_str(payload).ok()?;
+    let sender = tx.sender_address.trim();
+    (!sender.is_empty()).then(|| sender.to_string())
+}
+
+/// The payload's `tx_status`: `success`, or how it aborted (`abort_by_response`,
+/// `abort_by_post_condition`, ...).
+pub fn decode_tx_status(payload: &str) -> Option<String> {
+    let tx: TxStatus = serde_json::from_str(payload).ok()?;
+    Some(tx.tx_status)
+}
+
+/// Decodes a Stacks API transaction payload (JSON with `tx_type` and `contract_call`).
+/// `None` for anything that is not a contract call to a known Conxian function of one of
+/// `contracts`.
+pub fn decode_payload(payload: &str, contracts: &[String]) -> Option<ConxianAction> {
+    let tx: TxPayload = serde_json::from_str(payload).ok()?;
+    if tx.tx_type != "contract_call" {
+        return None;
+    }
+    let call = tx.contract_call?;
+    if !contracts.contains(&call.contract_id) {
+        return None;
+    }
+    ConxianAction::from_function_name(&call.function_name)
+}
+
+/// A change to one vault printed by a vault contract, as
+/// `(tuple (event "collateral-deposited") (vault-id "v1") (amount u5000))`, or the
+/// vault's full state, as
+/// `(tuple (vault-id "v1") (collateral-type "BTC") (collateral u100000000) (debt u30000000000))`
+/// with an optional `(ltv-bps u5000)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultChange {
+    /// `vault-opened`, with a `collateral-type`; the vault starts empty.
+    Opened { collateral_type: String },
+    /// `collateral-deposited`, with an `amount` in the collateral's base unit.
+    CollateralDeposited { amount: Amount },
+    /// `debt-minted`, with an `amount` in micro-USD.
+    DebtMinted { amount: Amount },
+    /// `vault-closed`.
+    Closed,
+    /// A full-state print, without an `event`; replaces the vault's balances.
+    State {
+        collateral_type: String,
+        /// In the collateral's base unit.
+        collateral: Amount,
+        /// In micro-USD.
+        debt: Amount,
+        ltv_bps: Option<u64>,
+    },
+}
+
+/// A [`VaultChange`] with its place in the block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultPrint {
+    pub tx_index: u32,
+    pub event_index: u32,
+    pub vault_id: String,
+    pub change: VaultChange,
+}
+
+/// Vault changes printed by any of `contracts` in a successful transaction, in event
+/// order. The transaction may call any function; only the contract that printed
+/// matters. Failed or aborted transactions yield nothing, since their prints never took
+/// effect.
+pub fn decode_vault_prints(payload: &str, contracts: &[String]) -> Vec<VaultPrint> {
+    let Ok(tx) = serde_json::from_str::<TxPayload>(payload) else {
+        return Vec::new();
+    };
+    if tx.tx_status.as_deref().is_some_and(|s| s != "success") {
+        return Vec::new();
+    }
+    tx.events
+        .iter()
+        .filter(|event| event.event_type == "smart_contract_log")
+        .filter_map(|event| Some((event.event_index, event.contract_log.as_ref()?)))
+        .filter(|(_, log)| log.topic == "print" && contracts.contains(&log.contract_id))
+        .filter_map(|(event_index, log)| {
+            let value = match &log.value.hex {
+                Some(hex_value) => ClarityValue::from_hex(hex_value).ok()?,
+                None => repr_to_value(&parse_repr(&log.value.repr)?)?,
+            };
+            let (vault_id, change) = vault_change_from_value(&value)?;
+            Some(VaultPrint {
+                tx_index: tx.tx_index,
+                event_index,
+                vault_id,
+                change,
+            })
+        })
+        .collect()
+}
+
+fn vault_change_from_value(value: &ClarityValue) -> Option<(String, VaultChange)> {
+    let amount = |name: &str| Some(Amount::new(value.field(name)?.as_uint()?));
+    let collateral_type = || Some(value.field("collateral-type")?.as_str()?.to_string());
+    let change = match value.field("event") {
+        None => VaultChange::State {
+            collateral_type: collateral_type()?,
+            collateral: amount("collateral")?,
+            debt: amount("debt")?,
+            ltv_bps: match value.field("ltv-bps") {
+                Some(ltv) => Some(ltv.as_uint()?.try_into().ok()?),
+                None => None,
+            },
+        },
+        Some(event) => match event.as_str()? {
+            "vault-opened" => VaultChange::Opened {
+                collateral_type: collateral_type()?,
+            },
+            "collateral-deposited" => VaultChange::CollateralDeposited {
+                amount: amount("amount")?,
+            },
+            "debt-minted" => VaultChange::DebtMinted {
+                amount: amount("amount")?,
+            },
+            "vault-closed" => VaultChange::Closed,
+            _ => return None,
+        },
+    };
+    let vault_id = value.field("vault-id")?;
+    let vault_id = vault_id
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| vault_id.as_principal())?;
+    Some((vault_id, change))
+}
+
+/// The function called and its arguments, decoded from their `hex` form, of a successful
+/// call to `contract`. `None` when the payload is not such a call or any argument fails
+/// to decode.
+pub fn decode_function_args(payload: &str, contract: &str) -> Option<(String, Vec<ClarityValue>)> {
+    let tx: TxPayload = serde_json::from_str(payload).ok()?;
+    if tx.tx_type != "contract_call" || tx.tx_status.as_deref().is_some_and(|s| s != "success") {
+        return None;
+    }
+    let call = tx
+        .contract_call
+        .filter(|call| call.contract_id == contract)?;
+    let args = call
+        .function_args
+        .iter()
+        .map(|arg| ClarityValue::from_hex(&arg.hex).ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((call.function_name, args))
+}
+
+/// Prices published by an oracle update call, in the fixed point of
+/// [`crate::oracle::push::PRICE_DECIMALS`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OraclePriceUpdate {
+    /// Rate per currency.
+    pub rates: BTreeMap<String, u128>,
+    pub ppp_indices: BTreeMap<String, u128>,
+    pub timestamp: u128,
+}
+
+/// Prices from a successful call to `function_name` of the oracle `contract` with the
+/// argument layout of [`crate::oracle::push::contract_args`]: a list of
+/// `{currency, rate, ppp-index}` tuples, then a `uint` timestamp. The forwarded
+/// attestations that follow are not decoded; calls made before they were forwarded have
+/// none.
+pub fn decode_oracle_prices(
+    payload: &str,
+    contract: &str,
+    function_name: &str,
+) -> Option<OraclePriceUpdate> {
+    let (called, args) = decode_function_args(payload, contract)?;
+    if called != function_name {
+        return None;
+    }
+    let [ClarityValue::List(entries), ClarityValue::UInt(timestamp), ..] = args.as_slice() else {
+        return None;
+    };
+    let mut update = OraclePriceUpdate {
+        rates: BTreeMap::new(),
+        ppp_indices: BTreeMap::new(),
+        timestamp: *timestamp,
+    };
+    for entry in entries {
+        let currency = entry.field("currency")?.as_str()?.to_string();
+        update
+            .rates
+            .insert(currency.clone(), entry.field("rate")?.as_uint()?);
+        update
+            .ppp_indices
+            .insert(currency, entry.field("ppp-index")?.as_uint()?);
+    }
+    Some(update)
+}
+
+/// A parsed Clarity value `repr`: atoms (`u5`, `'SP…`, `true`), strings and lists.
+#[derive(Debug, PartialEq)]
+enum Repr {
+    Atom(String),
+    Str(String),
+    List(Vec<Repr>),
+}
+
+fn parse_repr(input: &str) -> Option<Repr> {
+    let mut chars = input.chars().peekable();
+    let value = parse_repr_value(&mut chars, 0)?;
+    skip_whitespace(&mut chars);
+    chars.peek().is_none().then_some(value)
+}
+
+fn parse_repr_value(chars: &mut Peekable<Chars<'_>>, depth: usize) -> Option<Repr> {
+    skip_whitespace(chars);
+    match *chars.peek()? {
+        '(' => {
+            if depth >= MAX_REPR_DEPTH {
+                return None;
+            }
+            chars.next();
+            let mut items = Vec::new();
+            loop {
+                skip_whitespace(chars);
+                if chars.peek() == Some(&')') {
+                    chars.next();
+                    return Some(Repr::List(items));
+                }
+                items.push(parse_repr_value(chars, depth + 1)?);
+            }
+        }
+        ')' => None,
+        '"' => {
+            chars.next();
+            parse_repr_string(chars).map(Repr::Str)
+        }
+        _ => {
+            let mut atom = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' {
+                    break;
+                }
+                chars.next();
+                // `u"…"` is a string-utf8 literal.
+                if c == '"' && atom == "u" {
+                    return parse_repr_string(chars).map(Repr::Str);
+                }
+                atom.push(c);
+            }
+            Some(Repr::Atom(atom))
+        }
+    }
+}
+
+/// Reads up to the closing quote; the opening one is already consumed.
+fn parse_repr_string(chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    let mut out = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(out),
+            '\\' => out.push(chars.next()?),
+            c => out.push(c),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// A `repr` as a Clarity value, for the strings, `uint`s and tuples of them that prints
+/// carry. Principals become their text; tuple fields of other types are dropped.
+fn repr_to_value(value: &Repr) -> Option<ClarityValue> {
+    match value {
+        Repr::Str(text) => Some(ClarityValue::StringAscii(text.clone())),
+        Repr::Atom(atom) => match atom.strip_prefix('\'') {
+            Some(principal) => Some(ClarityValue::StringAscii(principal.to_string())),
+            None => atom.strip_prefix('u')?.parse().ok().map(ClarityValue::UInt),
+        },
+        Repr::List(items) => {
+            let (Repr::Atom(head), fields) = items.split_first()? else {
+                return None;
+            };
+            if head != "tuple" {
+                return None;
+            }
+            let mut tuple = BTreeMap::new();
+            for field in fields {
+                let Repr::List(pair) = field else {
+                    return None;
+                };
+                let [Repr::Atom(key), value] = pair.as_slice() else {
+                    return None;
+                };
+                if let Some(value) = repr_to_value(value) {
+                    tuple.insert(key.clone(), value);
+                }
+            }
+            Some(ClarityValue::Tuple(tuple))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_known_contract_calls() {
+        let payload = r#"{"tx_type":"contract_call","contract_call":{"contract_id":"SP000.conxian-vault","function_name":"liquidate"}}"#;
+        assert_eq!(
+            decode_payload(payload, &vault_contracts()),
+            Some(ConxianAction::Liquidate)
+        );
+        assert_eq!(
+            "withdraw".parse::<ConxianAction>().unwrap(),
+            ConxianAction::Withdraw
+        );
+    }
+
+    #[test]
+    fn test_calls_to_other_contracts_are_not_actions() {
+        let payload = r#"{"tx_type":"contract_call","contract_call":{"contract_id":"SP999.lookalike-vault","function_name":"liquidate"}}"#;
+        assert_eq!(decode_payload(payload, &vault_contracts()), None);
+        let unnamed =
+            r#"{"tx_type":"contract_call","contract_call":{"function_name":"liquidate"}}"#;
+        assert_eq!(decode_payload(unnamed, &vault_contracts()), None);
+        let payload = payload.replace("SP999.lookalike-vault", VAULT);
+        assert_eq!(decode_payload(&payload, &[]), None);
+    }
+
+    #[test]
+    fn test_memo_text_is_not_an_action() {
+        let transfer = r#"{"tx_type":"token_transfer","token_transfer":{"memo":"liquidate"},"contract_call":{"contract_id":"SP000.conxian-vault","function_name":"liquidate"}}"#;
+        assert_eq!(decode_payload(transfer, &vault_contracts()), None);
+        assert_eq!(decode_payload("liquidate", &vault_contracts()), None);
+
+        let lookalike = r#"{"tx_type":"contract_call","contract_call":{"contract_id":"SP000.conxian-vault","function_name":"liquidate-all-memo"}}"#;
+        assert_eq!(decode_payload(lookalike, &vault_contracts()), None);
+    }
+
+    const VAULT: &str = "SP000.conxian-vault";
+
+    fn vault_contracts() -> Vec<String> {
+        vec![VAULT.to_string()]
+    }
+
+    fn vault_call(status: &str, function_name: &str, repr: &str) -> String {
+        serde_json::json!({
+            "tx_type": "contract_call",
+            "tx_status": status,
+            "contract_call": {"function_name": function_name},
+            "events": [
+                {"event_index": 0, "event_type": "stx_asset", "asset": {}},
+                {
+                    "event_index": 1,
+                    "event_type": "smart_contract_log",
+                    "contract_log": {"contract_id": VAULT, "topic": "print", "value": {"repr": repr}},
+                },
+            ],
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_decodes_vault_prints_of_configured_contracts() {
+        let contracts = vec!["SP000.conxian-vaults".to_string()];
+        let tx = |status: &str, contract: &str| {
+            serde_json::json!({
+                "tx_type": "contract_call",
+                "tx_status": status,
+                "tx_index": 7,
+                "contract_call": {"function_name": "open-vault"},
+                "events": [{
+                    "event_index": 3,
+                    "event_type": "smart_contract_log",
+                    "contract_log": {
+                        "contract_id": contract,
+                        "topic": "print",
+                        "value": {"repr": r#"(tuple (event "vault-opened") (vault-id 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7) (collateral-type "STX") (open true))"#},
+                    },
+                }],
+            })
+            .to_string()
+        };
+        assert_eq!(
+            decode_vault_prints(&tx("success", "SP000.conxian-vaults"), &contracts),
+            vec![VaultPrint {
+                tx_index: 7,
+                event_index: 3,
+                vault_id: "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7".to_string(),
+                change: VaultChange::Opened {
+                    collateral_type: "STX".to_string()
+                },
+            }]
+        );
+        assert!(
+            decode_vault_prints(&tx("abort_by_response", "SP000.conxian-vaults"), &contracts)
+                .is_empty()
+        );
+        assert!(decode_vault_prints(&tx("success", "SP000.lookalike"), &contracts).is_empty());
+    }
+
+    #[test]
+    fn test_decodes_full_state_prints() {
+        let repr = r#"(tuple (collateral u100000000) (collateral-type "BTC") (debt u30000000000) (vault-id "v1") (ltv-bps u5000))"#;
+        let prints =
+            decode_vault_prints(&vault_call("success", "deposit", repr), &vault_contracts());
+        assert_eq!(
+            prints,
+            vec![VaultPrint {
+                tx_index: 0,
+                event_index: 1,
+                vault_id: "v1".to_string(),
+                change: VaultChange::State {
+                    collateral_type: "BTC".to_string(),
+                    collateral: Amount::new(100_000_000),
+                    debt: Amount::new(30_000_000_000),
+                    ltv_bps: Some(5_000),
+                },
+            }]
+        );
+
+        let principal = r#"(tuple (vault-id 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7) (collateral-type u"STX") (collateral u1) (debt u0))"#;
+        let prints = decode_vault_prints(
+            &vault_call("success", "withdraw", principal),
+            &vault_contracts(),
+        );
+        assert_eq!(
+            prints[0].vault_id,
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+        );
+        assert!(matches!(
+            prints[0].change,
+            VaultChange::State { ltv_bps: None, .. }
+        ));
+
+        // Only the configured contracts' prints are vault changes.
+        let other = ["SP000.lookalike".to_string()];
+        assert!(decode_vault_prints(&vault_call("success", "deposit", repr), &other).is_empty());
+    }
+
+    #[test]
+    fn test_decodes_full_state_prints_from_hex() {
+        let tuple = ClarityValue::tuple([
+            ("vault-id", ClarityValue::StringAscii("v1".to_string())),
+            (
+                "collateral-type",
+                ClarityValue::StringUtf8("BTC".to_string()),
+            ),
+            ("collateral", ClarityValue::UInt(100_000_000)),
+            ("debt", ClarityValue::UInt(30_000_000_000)),
+        ]);
+        let payload = serde_json::json!({
+            "tx_type": "contract_call",
+            "tx_status": "success",
+            "contract_call": {"function_name": "deposit"},
+            "events": [{
+                "event_type": "smart_contract_log",
+                // The hex form wins over a stale or mismatched repr.
+                "contract_log": {"contract_id": VAULT, "topic": "print", "value": {
+                    "hex": format!("0x{}", hex::encode(tuple.serialize())),
+                    "repr": "(ok true)",
+                }},
+            }],
+        })
+        .to_string();
+        assert_eq!(
+            decode_vault_prints(&payload, &vault_contracts())
+                .into_iter()
+                .map(|print| print.change)
+                .collect::<Vec<_>>(),
+            vec![VaultChange::State {
+                collateral_type: "BTC".to_string(),
+                collateral: Amount::new(100_000_000),
+                debt: Amount::new(30_000_000_000),
+                ltv_bps: None,
+            }]
+        );
+    }
+
+    const ORACLE: &str = "SP000.fx-oracle";
+
+    #[test]
+    fn test_decodes_oracle_prices_from_function_args() {
+        use crate::oracle::push::{contract_args, DEFAULT_CONTRACT_FUNCTION};
+
+        let state = crate::oracle::aggregator::PppState {
+            base_currency: "USD".to_string(),
+            rates: [("USD".to_string(), 1.0), ("EUR".to_string(), 0.9)].into(),
+            ppp_indices: [("EUR".to_string(), 0.8)].into(),
+            confidence_intervals: Default::default(),
+            timestamp: 1_751_846_400,
+            bases: Default::default(),
+            provenance: Default::default(),
+            rejections: Vec::new(),
+            attestations: Vec::new(),
+            collateral_prices: BTreeMap::new(),
+        };
+        let args: Vec<_> = contract_args(&state)
+            .unwrap()
+            .iter()
+            .map(|arg| serde_json::json!({"hex": format!("0x{}", hex::encode(arg.serialize()))}))
+            .collect();
+        let payload = serde_json::json!({
+            "tx_type": "contract_call",
+            "tx_status": "success",
+            "contract_call": {
+                "contract_id": ORACLE,
+                "function_name": DEFAULT_CONTRACT_FUNCTION,
+                "function_args": args,
+            },
+        })
+        .to_string();
+
+        let update = decode_oracle_prices(&payload, ORACLE, DEFAULT_CONTRACT_FUNCTION).unwrap();
+        assert_eq!(update.timestamp, 1_751_846_400);
+        assert_eq!(update.rates["EUR"], 90_000_000);
+        assert_eq!(update.rates["USD"], 100_000_000);
+        assert_eq!(update.ppp_indices["EUR"], 80_000_000);
+        assert_eq!(update.ppp_indices["USD"], 0);
+        assert_eq!(
+            decode_oracle_prices(&payload, ORACLE, "other-function"),
+            None
+        );
+        assert_eq!(
+            decode_oracle_prices(&payload, "SP999.fx-oracle", DEFAULT_CONTRACT_FUNCTION),
+            None
+        );
+    }
+
+    #[test]
+    fn test_amounts_above_u64_decode() {
+        let repr = r#"(tuple (event "collateral-deposited") (vault-id "v1") (amount u18446744073709551616))"#;
+        let prints =
+            decode_vault_prints(&vault_call("success", "deposit", repr), &vault_contracts());
+        assert_eq!(
+            prints[0].change,
+            VaultChange::CollateralDeposited {
+                amount: Amount::new(u64::MAX as u128 + 1)
+            }
+        );
+    }
+
+    #[test]
+    fn test_full_state_prints_require_a_successful_call() {
+        let repr = r#"(tuple (collateral u1) (collateral-type "BTC") (debt u1) (vault-id "v1"))"#;
+        let contracts = vault_contracts();
+        assert!(decode_vault_prints(
+            &vault_call("abort_by_response", "deposit", repr),
+            &contracts
+        )
+        .is_empty());
+        // Missing fields, negative amounts and unbalanced input are not vault states.
+        for repr in [
+            r#"(tuple (collateral u1) (debt u1) (vault-id "v1"))"#,
+            r#"(tuple (collateral -1) (collateral-type "BTC") (debt u1) (vault-id "v1"))"#,
+            r#"(tuple (collateral u1) (collateral-type "BTC") (debt u1) (vault-id "v1")"#,
+            r#"(ok true)"#,
+        ] {
+            assert!(
+                decode_vault_prints(&vault_call("success", "deposit", repr), &contracts).is_empty()
+            );
+        }
+    }
+}
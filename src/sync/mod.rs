@@ -1,42 +1,167 @@
+pub mod decoder;
+pub mod vault_indexer;
+
+use crate::config::{
+    DEFAULT_BURN_CONFIRMATIONS, DEFAULT_SYNC_EVENT_MAX_ATTEMPTS, DEFAULT_SYNC_INTERVAL_SECS,
+};
+use crate::events::NexusEvent;
+use crate::stacks::rpc::get_json;
+use crate::stacks::RpcEndpoints;
 use crate::state::NexusState;
 use crate::storage::kwil::{KwilAdapter, KwilMmrNodeCommitment};
+use crate::storage::repo::{NewBlock, NewTransaction};
+use crate::storage::store::NexusStore;
 use crate::storage::tableland::TablelandAdapter;
-use crate::storage::Storage;
-use futures_util::StreamExt;
+use chrono::{DateTime, Utc};
+use futures_util::{FutureExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
 use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use vault_indexer::VaultIndexer;
+
+/// Delay before the second attempt at a failed sync event; doubled for each further one.
+const EVENT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Per-request timeout for Stacks API reads during a backfill.
+const BACKFILL_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The fields of a Stacks API `/extended/v1/block/by_height/{height}` response that
+/// ingestion uses.
+#[derive(Debug, Deserialize)]
+struct ApiBlock {
+    hash: String,
+    height: u64,
+    parent_block_hash: String,
+    burn_block_hash: String,
+    burn_block_height: u64,
+    /// Unix seconds; older API versions only report the burn block's time.
+    #[serde(default)]
+    block_time: Option<u64>,
+    #[serde(default)]
+    burn_block_time: Option<u64>,
+    #[serde(default)]
+    txs: Vec<String>,
+}
 
-#[derive(Debug, Serialize, Deserialize)]
+/// One page of a Stacks API `/extended/v2/blocks/{height}/transactions` response.
+#[derive(Debug, Deserialize)]
+struct ApiBlockTransactions {
+    total: u64,
+    results: Vec<serde_json::Value>,
+}
+
+impl ApiBlockTransactions {
+    /// Each transaction's JSON keyed by its `tx_id`, the shape of
+    /// [`MicroblockData::payloads`].
+    fn payloads(self) -> impl Iterator<Item = (String, String)> {
+        self.results.into_iter().filter_map(|tx| {
+            let tx_id = tx.get("tx_id")?.as_str()?.to_string();
+            Some((tx_id, tx.to_string()))
+        })
+    }
+}
+
+/// Page size for a block's transactions during a backfill; the API's maximum.
+const BLOCK_TRANSACTIONS_PAGE: u64 = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BurnBlockData {
     pub hash: String,
     pub height: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MicroblockData {
     pub hash: String,
     pub height: u64,
     pub parent_hash: String,
     pub tx_ids: Vec<String>,
+    /// Stacks API transaction JSON, keyed by tx id, for the transactions that carry one.
+    #[serde(default)]
+    pub payloads: HashMap<String, String>,
+    /// Chain timestamp of the block (unix seconds), when the source reports one.
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+}
+
+/// A message on the Stacks event stream, tagged by `type`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncEvent {
+    Microblock(MicroblockData),
+    BurnBlock(BurnBlockData),
+}
+
+impl SyncEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Microblock(_) => "microblock",
+            Self::BurnBlock(_) => "burn_block",
+        }
+    }
+}
+
+/// A sync event that failed every attempt, or an execution that expired in the
+/// sequencer queue (`event_type` `execution`), as kept in `sync_dead_letter`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Assigned by the store; 0 before the letter is recorded.
+    pub id: i64,
+    pub event_type: String,
+    /// The event as JSON, replayable through [`NexusSync::handle_event`]; for an
+    /// execution, the [`crate::executor::ExecutionRequest`].
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: String,
+    pub created_at: DateTime<Utc>,
 }
 
 pub struct NexusSync {
-    pub storage: Arc<Storage>,
+    pub storage: Arc<dyn NexusStore>,
     pub state_tracker: Arc<NexusState>,
     pub tableland: Arc<TablelandAdapter>,
     pub kwil: Option<Arc<KwilAdapter>>,
-    pub rpc_url: String,
+    /// Stacks API endpoints, shared with the safety monitor.
+    pub rpc: Arc<RpcEndpoints>,
     pub ws_url: String,
+    /// Burn blocks (including the tip) that must bury a block before it is marked hard.
+    pub burn_confirmations: u64,
+    /// Delay before reconnecting after the event stream drops.
+    pub sync_interval: Duration,
+    /// Attempts per event before it is dead-lettered.
+    pub max_event_attempts: u32,
+    pub event_retry_base_delay: Duration,
+    /// Blocks below this height are skipped; see [`Self::seed_start_height`].
+    pub start_height: Option<u64>,
+    /// Indexes vault lifecycle prints; `None` when no vault contract is configured.
+    pub vault_indexer: Option<VaultIndexer>,
+    /// Contracts whose calls are decoded into a transaction's action.
+    pub action_contracts: Vec<String>,
+    /// Oracle contract and update function whose confirmed calls are recorded with the
+    /// prices they published; `None` when no oracle contract is configured.
+    pub oracle_contract: Option<(String, String)>,
+    /// Whether a streamed block that arrives without the JSON of every transaction has
+    /// the block's transaction list fetched from the Stacks API before ingestion, so each
+    /// transaction's sender and prints are decoded.
+    pub fetch_payloads: bool,
+}
+
+/// Highest block height buried by `confirmations` burn blocks at `burn_tip`,
+/// counting the tip itself as the first confirmation. `None` until the tip is deep enough.
+pub fn hard_finality_height(burn_tip: u64, confirmations: u64) -> Option<u64> {
+    (burn_tip + 1).checked_sub(confirmations.max(1))
 }
 
 impl NexusSync {
     pub fn new(
-        storage: Arc<Storage>,
+        storage: Arc<dyn NexusStore>,
         state_tracker: Arc<NexusState>,
         tableland: Arc<TablelandAdapter>,
         kwil: Option<Arc<KwilAdapter>>,
-        rpc_url: String,
+        rpc: Arc<RpcEndpoints>,
         ws_url: String,
     ) -> Self {
         Self {
@@ -44,34 +169,480 @@ impl NexusSync {
             state_tracker,
             tableland,
             kwil,
-            rpc_url,
+            rpc,
             ws_url,
+            burn_confirmations: DEFAULT_BURN_CONFIRMATIONS,
+            sync_interval: Duration::from_secs(DEFAULT_SYNC_INTERVAL_SECS),
+            max_event_attempts: DEFAULT_SYNC_EVENT_MAX_ATTEMPTS,
+            event_retry_base_delay: EVENT_RETRY_BASE_DELAY,
+            start_height: None,
+            vault_indexer: None,
+            action_contracts: Vec::new(),
+            oracle_contract: None,
+            fetch_payloads: true,
         }
     }
 
+    pub fn with_burn_confirmations(mut self, confirmations: u64) -> Self {
+        self.burn_confirmations = confirmations.max(1);
+        self
+    }
+
+    pub fn with_sync_interval(mut self, interval: Duration) -> Self {
+        self.sync_interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    pub fn with_max_event_attempts(mut self, attempts: u32) -> Self {
+        self.max_event_attempts = attempts.max(1);
+        self
+    }
+
+    /// Starts ingestion at `height` rather than genesis.
+    pub fn with_start_height(mut self, height: Option<u64>) -> Self {
+        self.start_height = height;
+        self
+    }
+
+    /// Indexes the vault states and lifecycle events printed by `contracts`, and decodes
+    /// calls to them into the stored transaction's action; none when empty.
+    pub fn with_vault_contracts(mut self, contracts: Vec<String>) -> Self {
+        self.action_contracts = contracts.clone();
+        self.vault_indexer = (!contracts.is_empty()).then(|| VaultIndexer::new(contracts));
+        self
+    }
+
+    /// Records the prices of successful `function_name` calls to the oracle `contract`;
+    /// none when `contract` is `None`.
+    pub fn with_oracle_contract(mut self, contract: Option<String>, function_name: String) -> Self {
+        self.oracle_contract = contract.map(|contract| (contract, function_name));
+        self
+    }
+
+    pub fn with_payload_fetch(mut self, enabled: bool) -> Self {
+        self.fetch_payloads = enabled;
+        self
+    }
+
+    /// Height of the latest Stacks block, from the first RPC endpoint that answers.
+    pub async fn chain_tip(&self) -> anyhow::Result<u64> {
+        let http_client = reqwest::Client::builder()
+            .timeout(BACKFILL_REQUEST_TIMEOUT)
+            .build()?;
+        self.rpc
+            .call(|base| {
+                let http_client = &http_client;
+                async move {
+                    let url = format!("{}/extended/v1/block?limit=1", base);
+                    let json: serde_json::Value = get_json(http_client, &url).await?;
+                    json["results"][0]["height"].as_u64().ok_or_else(|| {
+                        anyhow::anyhow!("Failed to parse the chain tip from Stacks RPC {}", base)
+                    })
+                }
+            })
+            .await
+    }
+
+    /// On a fresh database, seeds the watermark just below the start height so ingestion
+    /// begins there. Fails when the start height is past `chain_tip`. Returns whether the
+    /// watermark was seeded; a database that has already synced keeps its own.
+    pub async fn seed_start_height(&self, chain_tip: u64) -> anyhow::Result<bool> {
+        let Some(start) = self.start_height else {
+            return Ok(false);
+        };
+        if start > chain_tip {
+            anyhow::bail!(
+                "SYNC_START_HEIGHT {} is beyond the chain tip {}",
+                start,
+                chain_tip
+            );
+        }
+        self.storage
+            .seed_sync_watermark(start.saturating_sub(1))
+            .await
+    }
+
+    /// Rebuilds the in-memory state tree from the store before sync starts.
     pub async fn load_initial_state(&self) -> anyhow::Result<()> {
+        self.rebuild_state().await.map(|_| ())
+    }
+
+    /// Replaces the state tree with the store's leaves, replayed in commit order, and
+    /// persists the resulting root. Returns the root.
+    pub async fn rebuild_state(&self) -> anyhow::Result<String> {
+        let leaves = self.storage.state_leaves().await?;
+        self.state_tracker.set_initial_leaves(leaves);
+        if let Some(at) = self.storage.last_block_time().await? {
+            self.state_tracker.record_block_time(at);
+        }
+        let root = self.state_tracker.get_state_root();
+        self.persist_root_to_redis(&root).await?;
+        Ok(root)
+    }
+
+    /// Ingests blocks `from..=to` from the Stacks API through the same path as streamed
+    /// events, recording each block's burn block for finality. Fails on the first block
+    /// that cannot be fetched or is dead-lettered. Returns the number of blocks ingested.
+    pub async fn backfill(&self, from: u64, to: u64) -> anyhow::Result<u64> {
+        if from > to {
+            anyhow::bail!("Backfill range is empty: {} > {}", from, to);
+        }
+        let http_client = reqwest::Client::builder()
+            .timeout(BACKFILL_REQUEST_TIMEOUT)
+            .build()?;
+        let mut ingested = 0;
+        for height in from..=to {
+            let block: ApiBlock = self
+                .rpc
+                .call(|base| {
+                    let http_client = &http_client;
+                    async move {
+                        let url = format!("{}/extended/v1/block/by_height/{}", base, height);
+                        get_json(http_client, &url).await
+                    }
+                })
+                .await?;
+            let payloads = self.fetch_block_payloads(&http_client, height).await?;
+            let events = [
+                SyncEvent::Microblock(MicroblockData {
+                    hash: block.hash,
+                    height: block.height,
+                    parent_hash: block.parent_block_hash,
+                    tx_ids: block.txs,
+                    payloads,
+                    timestamp: block.block_time.or(block.burn_block_time),
+                }),
+                SyncEvent::BurnBlock(BurnBlockData {
+                    hash: block.burn_block_hash,
+                    height: block.burn_block_height,
+                }),
+            ];
+            for event in &events {
+                if !self.handle_event_with_retry(event).await {
+                    anyhow::bail!(
+                        "Block {} could not be ingested; see the dead-letter queue",
+                        height
+                    );
+                }
+            }
+            ingested += 1;
+            tracing::debug!(height, "Backfilled block");
+        }
+        Ok(ingested)
+    }
+
+    /// `data` with the JSON the stream left out filled in from the block's transaction
+    /// list; JSON the stream did send is kept. See [`Self::fetch_payloads`].
+    async fn with_payloads(&self, mut data: MicroblockData) -> anyhow::Result<MicroblockData> {
+        let complete = data
+            .tx_ids
+            .iter()
+            .all(|tx_id| data.payloads.contains_key(tx_id));
+        if !self.fetch_payloads || complete {
+            return Ok(data);
+        }
+        let http_client = reqwest::Client::builder()
+            .timeout(BACKFILL_REQUEST_TIMEOUT)
+            .build()?;
+        for (tx_id, payload) in self.fetch_block_payloads(&http_client, data.height).await? {
+            data.payloads.entry(tx_id).or_insert(payload);
+        }
+        Ok(data)
+    }
+
+    /// The transactions of the block at `height` as Stacks API JSON keyed by tx id, so
+    /// their senders, actions and prints are decoded.
+    async fn fetch_block_payloads(
+        &self,
+        http_client: &reqwest::Client,
+        height: u64,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let mut payloads = HashMap::new();
+        let mut offset = 0;
+        loop {
+            let page: ApiBlockTransactions = self
+                .rpc
+                .call(|base| async move {
+                    let url = format!(
+                        "{}/extended/v2/blocks/{}/transactions?limit={}&offset={}",
+                        base, height, BLOCK_TRANSACTIONS_PAGE, offset
+                    );
+                    get_json(http_client, &url).await
+                })
+                .await?;
+            let total = page.total;
+            let fetched = page.results.len() as u64;
+            payloads.extend(page.payloads());
+            offset += fetched;
+            if fetched == 0 || offset >= total {
+                return Ok(payloads);
+            }
+        }
+    }
+
+    /// Follows the Stacks event stream, reconnecting every `sync_interval` after it drops,
+    /// until `shutdown` turns true. An event being persisted when the signal arrives is
+    /// finished, and events already received from the stream are persisted before
+    /// returning, so a graceful shutdown leaves no gap behind the watermark.
+    pub async fn run(&self, mut shutdown: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *shutdown.borrow() {
+                break;
+            }
+            if let Err(e) = self.follow_event_stream(&mut shutdown).await {
+                tracing::warn!(error = %e, "Stacks event stream failed");
+            }
+            if *shutdown.borrow() {
+                break;
+            }
+            tracing::info!(
+                "Reconnecting to Stacks event stream in {}s",
+                self.sync_interval.as_secs()
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(self.sync_interval) => {}
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+        tracing::info!("Sync stopped");
         Ok(())
     }
 
-    pub async fn run(&self) -> anyhow::Result<()> {
+    async fn follow_event_stream(
+        &self,
+        shutdown: &mut watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
         let url_str = self.ws_url.clone();
         let (ws_stream, _) = connect_async(&url_str).await?;
         let (mut _write, mut read) = ws_stream.split();
 
-        while let Some(msg) = read.next().await {
-            let msg = msg?;
-            if msg.is_text() {
-                // Handle message
+        loop {
+            // Only the read is raced against shutdown; handling runs to completion.
+            let msg = tokio::select! {
+                msg = read.next() => msg,
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            match msg {
+                Some(msg) => self.handle_stream_message(msg?).await?,
+                None => return Ok(()),
+            }
+        }
+
+        let mut drained = 0u64;
+        while let Some(Some(Ok(msg))) = read.next().now_or_never() {
+            self.handle_stream_message(msg).await?;
+            drained += 1;
+        }
+        tracing::info!(drained, "Drained buffered stream messages before shutdown");
+        Ok(())
+    }
+
+    async fn handle_stream_message(&self, msg: Message) -> anyhow::Result<()> {
+        if msg.is_text() {
+            match serde_json::from_str::<SyncEvent>(msg.to_text()?) {
+                Ok(event) => {
+                    self.handle_event_with_retry(&event).await;
+                }
+                Err(e) => tracing::debug!(error = %e, "Ignoring unrecognized stream message"),
             }
         }
         Ok(())
     }
 
+    pub async fn handle_event(&self, event: &SyncEvent) -> anyhow::Result<()> {
+        match event {
+            SyncEvent::Microblock(data) => {
+                let data = self.with_payloads(data.clone()).await?;
+                self.process_microblock(data).await
+            }
+            SyncEvent::BurnBlock(data) => self.process_burn_block(data.clone()).await.map(|_| ()),
+        }
+    }
+
+    /// Handles `event` up to `max_event_attempts` times, backing off between attempts,
+    /// and writes it to the dead-letter store when every attempt fails. Events are
+    /// retried in place so later events never overtake an earlier block. Returns whether
+    /// the event was applied.
+    pub async fn handle_event_with_retry(&self, event: &SyncEvent) -> bool {
+        let mut delay = self.event_retry_base_delay;
+        let mut attempt = 1;
+        let last_error = loop {
+            match self.handle_event(event).await {
+                Ok(()) => return true,
+                Err(e) if attempt < self.max_event_attempts => {
+                    tracing::warn!(
+                        event = event.kind(),
+                        attempt,
+                        error = %e,
+                        "Sync event failed; retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => break e,
+            }
+        };
+
+        tracing::error!(
+            event = event.kind(),
+            attempts = attempt,
+            error = %last_error,
+            "Sync event failed every attempt; dead-lettering it"
+        );
+        let letter = DeadLetter {
+            id: 0,
+            event_type: event.kind().to_string(),
+            payload: serde_json::to_string(event).unwrap_or_default(),
+            attempts: attempt,
+            last_error: format!("{:#}", last_error),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = self.storage.record_dead_letter(&letter).await {
+            tracing::error!(error = %e, payload = %letter.payload, "Failed to record dead letter");
+        }
+        false
+    }
+
+    /// Highest height below which every block has been fully processed; ingestion resumes
+    /// after it. A height that failed, including one dead-lettered, holds it back. `None`
+    /// on a fresh database.
+    pub async fn resume_height(&self) -> anyhow::Result<Option<u64>> {
+        self.storage.sync_watermark().await
+    }
+
+    /// Persists the microblock and its transactions, appends the newly written ones to the
+    /// state tree, applies the vault prints, records confirmed oracle updates and settles
+    /// anchors, then advances the `sync_progress` watermark. The commit and the append
+    /// run under the state's commit lock, so leaves follow the store's commit order and a
+    /// rebuild replays the same tree. Retrying a block is safe: stored transactions are
+    /// not appended again, and the watermark only moves once every step for the height
+    /// has succeeded.
+    #[tracing::instrument(skip_all, fields(height = data.height, block = %data.hash))]
     pub async fn process_microblock(&self, data: MicroblockData) -> anyhow::Result<()> {
-        let added_nodes = self.state_tracker.update_state_batch(&data.tx_ids);
-        let root = self.state_tracker.get_state_root();
+        if self.start_height.is_some_and(|start| data.height < start) {
+            tracing::debug!("Skipping block below SYNC_START_HEIGHT");
+            return Ok(());
+        }
+        let started = std::time::Instant::now();
+        let commit = self.state_tracker.lock_commits().await;
+        match self
+            .storage
+            .revert_reorged_anchors(data.height, &data.hash)
+            .await
+        {
+            Ok(0) => {}
+            Ok(reverted) => tracing::warn!(
+                height = data.height,
+                reverted,
+                "Chain re-organized below confirmed state anchors; awaiting them again"
+            ),
+            Err(e) => tracing::warn!(error = %e, "Failed to check state anchors for a reorg"),
+        }
+        let transactions: Vec<NewTransaction> = data
+            .tx_ids
+            .iter()
+            .map(|tx_id| {
+                let payload = data.payloads.get(tx_id).cloned();
+                let sender = payload.as_deref().and_then(decoder::decode_sender);
+                NewTransaction::decoded(tx_id, &data.hash, payload, sender, &self.action_contracts)
+            })
+            .collect();
+        let block_time = data
+            .timestamp
+            .and_then(|t| DateTime::from_timestamp(i64::try_from(t).ok()?, 0));
+        let written = self
+            .storage
+            .ingest_block(
+                &NewBlock::microblock(&data.hash, data.height).with_block_time(block_time),
+                &transactions,
+            )
+            .await?;
+        let added_nodes = self.state_tracker.update_state_batch(&written);
+        if let Some(at) = block_time {
+            self.state_tracker.record_block_time(at);
+        }
+
+        if let Some(indexer) = &self.vault_indexer {
+            indexer.index_block(self.storage.as_ref(), &data).await?;
+        }
+        if let Some((contract, function_name)) = &self.oracle_contract {
+            for tx_id in &data.tx_ids {
+                let Some(update) = data.payloads.get(tx_id).and_then(|payload| {
+                    decoder::decode_oracle_prices(payload, contract, function_name)
+                }) else {
+                    continue;
+                };
+                self.storage
+                    .record_oracle_update(tx_id, data.height, &update)
+                    .await?;
+            }
+        }
+
+        // Only a call known to have succeeded confirms an anchor.
+        let mut succeeded = Vec::new();
+        let mut aborted = Vec::new();
+        for tx_id in &data.tx_ids {
+            match data
+                .payloads
+                .get(tx_id)
+                .and_then(|p| decoder::decode_tx_status(p))
+            {
+                Some(status) if status == "success" => succeeded.push(tx_id.clone()),
+                Some(status) => aborted.push((tx_id.clone(), status)),
+                None => {}
+            }
+        }
+        match self
+            .storage
+            .confirm_anchors(&succeeded, &aborted, data.height)
+            .await
+        {
+            Ok(0) => {}
+            Ok(confirmed) => {
+                tracing::info!(height = data.height, confirmed, "State anchors confirmed")
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to confirm state anchors"),
+        }
+
+        let commitment = self.state_tracker.commitment();
+        let root = commitment.root.clone();
+        if let Some(timestamp) = data.timestamp {
+            crate::state::metrics::observe_root_lag(timestamp);
+        }
 
         self.persist_root_to_redis(&root).await?;
+        // History only serves proofs against past roots; losing an entry must not stall sync.
+        if let Err(e) = self
+            .storage
+            .record_state_root(&commitment, data.height)
+            .await
+        {
+            tracing::warn!(root = %root, error = %e, "Failed to record state root history");
+        }
+        self.storage.advance_sync_watermark(data.height).await?;
+        drop(commit);
+        self.publish(NexusEvent::BlockProcessed {
+            hash: data.hash.clone(),
+            height: data.height,
+            tx_count: data.tx_ids.len() as u64,
+        })
+        .await;
+        self.publish(NexusEvent::RootUpdated {
+            root,
+            height: data.height,
+            leaf_count: commitment.leaf_count,
+        })
+        .await;
 
         if let Some(kwil) = &self.kwil {
             let mmr_commitments: Vec<KwilMmrNodeCommitment> = added_nodes
@@ -87,20 +658,738 @@ impl NexusSync {
                 let _ = kwil.persist_mmr_node(node).await;
             }
         }
+        crate::latency::observe_sync_block(started.elapsed());
         Ok(())
     }
 
-    pub async fn persist_root_to_redis(&self, root: &str) -> anyhow::Result<()> {
-        let mut conn = self
-            .storage
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await?;
-        let _: () = redis::cmd("SET")
-            .arg("nexus:state_root")
-            .arg(root)
-            .query_async::<()>(&mut conn)
+    /// Records a burn block and promotes soft blocks that are now buried by
+    /// `burn_confirmations` burn blocks to hard. Returns the number promoted.
+    #[tracing::instrument(skip_all, fields(height = data.height, block = %data.hash))]
+    pub async fn process_burn_block(&self, data: BurnBlockData) -> anyhow::Result<u64> {
+        self.storage
+            .insert_block(&NewBlock::burn_block(&data.hash, data.height))
             .await?;
-        Ok(())
+
+        let Some(final_height) = hard_finality_height(data.height, self.burn_confirmations) else {
+            return Ok(0);
+        };
+
+        let promoted = self.storage.finalize_blocks_up_to(final_height).await?;
+
+        tracing::debug!(
+            burn_tip = data.height,
+            final_height,
+            promoted,
+            "Applied burn-block finality"
+        );
+        Ok(promoted)
+    }
+
+    pub async fn persist_root_to_redis(&self, root: &str) -> anyhow::Result<()> {
+        self.storage.save_state_root(root).await
+    }
+
+    /// Best-effort: the block is already committed, and subscribers can catch up from
+    /// the status API.
+    async fn publish(&self, event: NexusEvent) {
+        if let Err(e) = self.storage.publish_event(&event).await {
+            tracing::warn!(event = event.kind(), error = %e, "Failed to publish sync event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::store::InMemoryStore;
+
+    fn sync_over(store: Arc<InMemoryStore>) -> NexusSync {
+        NexusSync::new(
+            store,
+            Arc::new(NexusState::new()),
+            Arc::new(TablelandAdapter::new(
+                crate::storage::Storage::for_tests(),
+                "http://localhost:8080".to_string(),
+            )),
+            None,
+            Arc::new(RpcEndpoints::single("http://localhost:3999")),
+            "ws://localhost:3999".to_string(),
+        )
+        .with_payload_fetch(false)
+    }
+
+    #[tokio::test]
+    async fn test_past_roots_stay_provable_after_later_blocks() {
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone());
+        let block = |hash: &str, height: u64, tx_ids: &[&str]| MicroblockData {
+            hash: hash.to_string(),
+            height,
+            parent_hash: String::new(),
+            tx_ids: tx_ids.iter().map(|t| t.to_string()).collect(),
+            payloads: HashMap::new(),
+            timestamp: None,
+        };
+        sync.process_microblock(block("0xm1", 10, &["tx1", "tx2", "tx3"]))
+            .await
+            .unwrap();
+        let old_root = sync.state_tracker.get_state_root();
+        sync.process_microblock(block("0xm2", 11, &["tx4", "tx5"]))
+            .await
+            .unwrap();
+        assert_ne!(sync.state_tracker.get_state_root(), old_root);
+
+        let leaf_count = store.root_leaf_count(&old_root).await.unwrap().unwrap();
+        assert_eq!(leaf_count, 3);
+        assert_eq!(
+            store.events()[..2],
+            [
+                NexusEvent::BlockProcessed {
+                    hash: "0xm1".to_string(),
+                    height: 10,
+                    tx_count: 3,
+                },
+                NexusEvent::RootUpdated {
+                    root: old_root.clone(),
+                    height: 10,
+                    leaf_count: 3,
+                },
+            ]
+        );
+        let proof = sync
+            .state_tracker
+            .generate_merkle_proof_at("tx2", leaf_count as usize)
+            .unwrap();
+        assert_eq!(proof.root, old_root);
+        assert!(crate::state::verify_merkle_proof(
+            &proof,
+            sync.state_tracker.tree_params()
+        ));
+        // tx4 landed after the old root was produced.
+        assert!(sync
+            .state_tracker
+            .generate_merkle_proof_at("tx4", leaf_count as usize)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_microblock_settles_anchors_by_tx_status_and_reorgs() {
+        use crate::state::anchor::{AnchorStatus, StateAnchor};
+
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone());
+        let broadcast = |tx_id: &str| StateAnchor {
+            root: "00".repeat(32),
+            sorted_root: None,
+            height: 9,
+            leaf_count: 0,
+            tx_id: tx_id.to_string(),
+            anchored_at: 1,
+            burn_height: None,
+            status: AnchorStatus::Broadcast,
+            confirmed_height: None,
+            error: None,
+        };
+        let anchor_tx = format!("0x{}", "ab".repeat(32));
+        let aborted_tx = format!("0x{}", "cd".repeat(32));
+        store.record_anchor(&broadcast(&aborted_tx)).await.unwrap();
+        store.record_anchor(&broadcast(&anchor_tx)).await.unwrap();
+        assert_eq!(store.last_confirmed_anchor().await.unwrap(), None);
+
+        let status = |status: &str| serde_json::json!({ "tx_status": status }).to_string();
+        // The node may report the txid without a prefix or in upper case.
+        let block = |hash: &str| MicroblockData {
+            hash: hash.to_string(),
+            height: 12,
+            parent_hash: String::new(),
+            tx_ids: vec!["tx1".to_string(), "AB".repeat(32), aborted_tx.clone()],
+            payloads: HashMap::from([
+                ("AB".repeat(32), status("success")),
+                (aborted_tx.clone(), status("abort_by_response")),
+            ]),
+            timestamp: None,
+        };
+        sync.process_microblock(block("0xm1")).await.unwrap();
+
+        let confirmed = store.last_confirmed_anchor().await.unwrap().unwrap();
+        assert_eq!(confirmed.tx_id, anchor_tx);
+        assert_eq!(confirmed.status, AnchorStatus::Confirmed);
+        assert_eq!(confirmed.confirmed_height, Some(12));
+        let attempts = store.anchors(10).await.unwrap();
+        assert_eq!(attempts[1].status, AnchorStatus::Failed);
+        assert_eq!(
+            attempts[1].error.as_deref(),
+            Some("Aborted on-chain: abort_by_response")
+        );
+
+        // Replaying the same block changes nothing; a different block at the height is a
+        // reorg, and the anchor waits to be seen again.
+        sync.process_microblock(block("0xm1")).await.unwrap();
+        assert!(store.last_confirmed_anchor().await.unwrap().is_some());
+        let mut fork = block("0xm1b");
+        fork.tx_ids.truncate(1);
+        sync.process_microblock(fork).await.unwrap();
+        assert_eq!(store.last_confirmed_anchor().await.unwrap(), None);
+        assert_eq!(
+            store.last_anchor().await.unwrap().unwrap().status,
+            AnchorStatus::Broadcast
+        );
+    }
+
+    #[tokio::test]
+    async fn test_burn_block_finalizes_buried_microblocks() {
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone()).with_burn_confirmations(3);
+        sync.process_microblock(MicroblockData {
+            hash: "0xm1".to_string(),
+            height: 10,
+            parent_hash: "0xm0".to_string(),
+            tx_ids: vec!["tx1".to_string()],
+            payloads: HashMap::new(),
+            timestamp: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(sync.resume_height().await.unwrap(), Some(10));
+        assert!(store.state_root().is_some());
+
+        let promoted = sync
+            .process_burn_block(BurnBlockData {
+                hash: "0xb1".to_string(),
+                height: 11,
+            })
+            .await
+            .unwrap();
+        assert_eq!(promoted, 0);
+
+        let promoted = sync
+            .process_burn_block(BurnBlockData {
+                hash: "0xb2".to_string(),
+                height: 12,
+            })
+            .await
+            .unwrap();
+        assert_eq!(promoted, 1);
+    }
+
+    #[test]
+    fn test_single_confirmation_finalizes_tip() {
+        assert_eq!(hard_finality_height(100, 1), Some(100));
+    }
+
+    #[test]
+    fn test_n_confirmations_lag_the_tip() {
+        assert_eq!(hard_finality_height(100, 6), Some(95));
+        assert_eq!(hard_finality_height(4, 6), None);
+        assert_eq!(hard_finality_height(5, 6), Some(0));
+    }
+
+    #[test]
+    fn test_zero_confirmations_treated_as_one() {
+        assert_eq!(hard_finality_height(7, 0), Some(7));
+    }
+
+    const VAULTS: &str = "SP000.conxian-vaults";
+    const ORACLE: &str = "SP000.fx-oracle";
+
+    #[tokio::test]
+    async fn test_microblock_records_confirmed_oracle_updates() {
+        use crate::oracle::push::{contract_args, DEFAULT_CONTRACT_FUNCTION};
+
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone()).with_oracle_contract(
+            Some(ORACLE.to_string()),
+            DEFAULT_CONTRACT_FUNCTION.to_string(),
+        );
+        let update_call = |contract: &str, eur: f64, timestamp: u64| {
+            let state = crate::oracle::aggregator::PppState {
+                base_currency: "USD".to_string(),
+                rates: [("EUR".to_string(), eur)].into(),
+                ppp_indices: Default::default(),
+                confidence_intervals: Default::default(),
+                timestamp,
+                bases: Default::default(),
+                provenance: Default::default(),
+                rejections: Vec::new(),
+                attestations: Vec::new(),
+                collateral_prices: Default::default(),
+            };
+            let args: Vec<_> = contract_args(&state)
+                .unwrap()
+                .iter()
+                .map(
+                    |arg| serde_json::json!({"hex": format!("0x{}", hex::encode(arg.serialize()))}),
+                )
+                .collect();
+            serde_json::json!({
+                "tx_type": "contract_call",
+                "tx_status": "success",
+                "contract_call": {
+                    "contract_id": contract,
+                    "function_name": DEFAULT_CONTRACT_FUNCTION,
+                    "function_args": args,
+                },
+            })
+            .to_string()
+        };
+        let data = MicroblockData {
+            hash: "0xm1".to_string(),
+            height: 10,
+            parent_hash: String::new(),
+            tx_ids: vec!["tx1".to_string(), "tx2".to_string()],
+            payloads: HashMap::from([
+                ("tx1".to_string(), update_call(ORACLE, 0.9, 100)),
+                ("tx2".to_string(), update_call("SP999.fx-oracle", 0.5, 200)),
+            ]),
+            timestamp: None,
+        };
+        sync.process_microblock(data).await.unwrap();
+
+        let update = store.last_oracle_update().await.unwrap().unwrap();
+        assert_eq!(update.timestamp, 100);
+        assert_eq!(update.rates["EUR"], 90_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_microblock_indexes_printed_vault_states() {
+        use crate::executor::fixed::{Amount, Ratio};
+        use std::collections::BTreeMap;
+
+        let store = Arc::new(InMemoryStore::new());
+        store.set_collateral_prices(BTreeMap::from([("STX".to_string(), 2.5)]));
+        let sync = sync_over(store.clone()).with_vault_contracts(vec![VAULTS.to_string()]);
+        let printed_by = |contract: &str, tx_index: u32, repr: &str| {
+            serde_json::json!({
+                "tx_type": "contract_call",
+                "tx_status": "success",
+                "tx_index": tx_index,
+                "contract_call": {"function_name": "deposit"},
+                "events": [{
+                    "event_type": "smart_contract_log",
+                    "contract_log": {"contract_id": contract, "topic": "print", "value": {"repr": repr}},
+                }],
+            })
+            .to_string()
+        };
+        let call = |tx_index: u32, repr: &str| printed_by(VAULTS, tx_index, repr);
+        let payloads = HashMap::from([
+            (
+                "tx1".to_string(),
+                call(
+                    1,
+                    r#"(tuple (vault-id "v1") (collateral-type "BTC") (collateral u100000000) (debt u30000000000) (ltv-bps u5000))"#,
+                ),
+            ),
+            (
+                "tx2".to_string(),
+                call(
+                    2,
+                    r#"(tuple (vault-id "v2") (collateral-type "STX") (collateral u1000000000) (debt u2000000000))"#,
+                ),
+            ),
+            (
+                "tx3".to_string(),
+                call(
+                    3,
+                    r#"(tuple (vault-id "v1") (collateral-type "BTC") (collateral u50000000) (debt u30000000000) (ltv-bps u10000))"#,
+                ),
+            ),
+            (
+                "tx4".to_string(),
+                call(
+                    4,
+                    r#"(tuple (vault-id "v3") (collateral-type "BTC") (collateral u100000000) (debt u1))"#,
+                ),
+            ),
+            (
+                "tx5".to_string(),
+                printed_by(
+                    "SP000.lookalike",
+                    5,
+                    r#"(tuple (vault-id "v4") (collateral-type "STX") (collateral u1) (debt u1))"#,
+                ),
+            ),
+        ]);
+        let block = MicroblockData {
+            hash: "0xm1".to_string(),
+            height: 10,
+            parent_hash: "0xm0".to_string(),
+            tx_ids: ["tx1", "tx2", "tx3", "tx4", "tx5"]
+                .map(String::from)
+                .to_vec(),
+            payloads,
+            timestamp: None,
+        };
+        sync.process_microblock(block.clone()).await.unwrap();
+
+        let mut vaults = store.vaults().await.unwrap();
+        vaults.sort_by(|a, b| a.vault_id.cmp(&b.vault_id));
+        // The lookalike contract's print is not a vault.
+        assert_eq!(vaults.len(), 3);
+        // The later withdraw supersedes the deposit.
+        assert_eq!(vaults[0].collateral_amount, Amount::new(50_000_000));
+        assert_eq!(vaults[0].ltv_ratio, Some(Ratio::ONE));
+        // No printed LTV: priced from the oracle at $2.50 per STX.
+        assert_eq!(vaults[1].ltv_ratio, Some(Ratio::from_bps(8_000)));
+        // No printed LTV and no BTC price: unpriced, not zero.
+        assert_eq!(vaults[2].ltv_ratio, None);
+
+        // A later lifecycle event applies on top of the printed state, once.
+        let deposit = call(
+            0,
+            r#"(tuple (event "collateral-deposited") (vault-id "v2") (amount u1000000000))"#,
+        );
+        sync.process_microblock(MicroblockData {
+            hash: "0xm2".to_string(),
+            height: 11,
+            parent_hash: "0xm1".to_string(),
+            tx_ids: vec!["tx6".to_string()],
+            payloads: HashMap::from([("tx6".to_string(), deposit)]),
+            timestamp: None,
+        })
+        .await
+        .unwrap();
+        let indexer = sync.vault_indexer.as_ref().unwrap();
+        assert_eq!(
+            indexer.index_block(store.as_ref(), &block).await.unwrap(),
+            0
+        );
+        let v2 = store.vault_records(&["v2".to_string()]).await.unwrap();
+        assert_eq!(v2[0].vault.collateral_amount, Amount::new(2_000_000_000));
+        assert_eq!(v2[0].vault.ltv_ratio, Some(Ratio::from_bps(4_000)));
+    }
+
+    fn microblock_event(hash: &str, height: u64) -> SyncEvent {
+        SyncEvent::Microblock(MicroblockData {
+            hash: hash.to_string(),
+            height,
+            parent_hash: "0xm0".to_string(),
+            tx_ids: vec![format!("{}-tx", hash)],
+            payloads: HashMap::new(),
+            timestamp: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_start_height_seeds_a_fresh_watermark_and_skips_earlier_blocks() {
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone()).with_start_height(Some(100));
+        assert!(sync.seed_start_height(99).await.is_err());
+        assert_eq!(sync.resume_height().await.unwrap(), None);
+
+        assert!(sync.seed_start_height(150).await.unwrap());
+        assert_eq!(sync.resume_height().await.unwrap(), Some(99));
+        // An already-synced database keeps its watermark.
+        assert!(!sync.seed_start_height(150).await.unwrap());
+
+        assert!(
+            sync.handle_event_with_retry(&microblock_event("0xm1", 50))
+                .await
+        );
+        assert_eq!(sync.resume_height().await.unwrap(), Some(99));
+        assert_eq!(sync.state_tracker.leaf_count(), 0);
+        assert!(
+            sync.handle_event_with_retry(&microblock_event("0xm2", 100))
+                .await
+        );
+        assert_eq!(sync.resume_height().await.unwrap(), Some(100));
+        assert_eq!(sync.state_tracker.leaf_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watermark_advances_only_over_contiguous_heights() {
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone());
+        for (hash, height) in [("0xm10", 10), ("0xm12", 12), ("0xm13", 13)] {
+            sync.handle_event(&microblock_event(hash, height))
+                .await
+                .unwrap();
+        }
+        // Height 11 is missing, so resuming must start there.
+        assert_eq!(sync.resume_height().await.unwrap(), Some(10));
+
+        sync.handle_event(&microblock_event("0xm11", 11))
+            .await
+            .unwrap();
+        assert_eq!(sync.resume_height().await.unwrap(), Some(13));
+    }
+
+    #[tokio::test]
+    async fn test_transient_event_failure_is_retried() {
+        let store = Arc::new(InMemoryStore::new());
+        store.fail_next_ingests(2);
+        let mut sync = sync_over(store.clone()).with_max_event_attempts(3);
+        sync.event_retry_base_delay = Duration::ZERO;
+
+        sync.handle_event_with_retry(&microblock_event("0xm1", 10))
+            .await;
+        assert_eq!(sync.resume_height().await.unwrap(), Some(10));
+        assert!(store.dead_letters(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_event_is_dead_lettered() {
+        let store = Arc::new(InMemoryStore::new());
+        store.fail_next_ingests(5);
+        let mut sync = sync_over(store.clone()).with_max_event_attempts(2);
+        sync.event_retry_base_delay = Duration::ZERO;
+
+        let event = microblock_event("0xm1", 10);
+        sync.handle_event_with_retry(&event).await;
+        assert_eq!(sync.resume_height().await.unwrap(), None);
+
+        let letters = store.dead_letters(10).await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].event_type, "microblock");
+        assert_eq!(letters[0].attempts, 2);
+        assert!(letters[0].last_error.contains("injected"));
+
+        // The payload replays once the fault clears.
+        let replayed: SyncEvent = serde_json::from_str(&letters[0].payload).unwrap();
+        store.fail_next_ingests(0);
+        sync.handle_event(&replayed).await.unwrap();
+        assert_eq!(sync.resume_height().await.unwrap(), Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_watermark_stops_below_a_dead_lettered_height() {
+        let store = Arc::new(InMemoryStore::new());
+        let mut sync = sync_over(store.clone()).with_max_event_attempts(1);
+        sync.event_retry_base_delay = Duration::ZERO;
+
+        assert!(
+            sync.handle_event_with_retry(&microblock_event("0xm10", 10))
+                .await
+        );
+        // Height 11 is ingested but fails afterwards, so it is dead-lettered.
+        store.fail_next_root_saves(1);
+        assert!(
+            !sync
+                .handle_event_with_retry(&microblock_event("0xm11", 11))
+                .await
+        );
+        assert!(
+            sync.handle_event_with_retry(&microblock_event("0xm12", 12))
+                .await
+        );
+        assert_eq!(sync.resume_height().await.unwrap(), Some(10));
+        assert_eq!(sync.state_tracker.leaf_count(), 3);
+
+        let letters = store.dead_letters(10).await.unwrap();
+        let replayed: SyncEvent = serde_json::from_str(&letters[0].payload).unwrap();
+        sync.handle_event(&replayed).await.unwrap();
+        assert_eq!(sync.resume_height().await.unwrap(), Some(12));
+        // The replay does not append the block's transactions a second time.
+        assert_eq!(sync.state_tracker.leaf_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_block_appends_its_leaves_once() {
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone());
+        let event = microblock_event("0xm1", 10);
+        sync.handle_event(&event).await.unwrap();
+        let root = sync.state_tracker.get_state_root();
+
+        sync.handle_event(&event).await.unwrap();
+        assert_eq!(sync.state_tracker.leaf_count(), 1);
+        assert_eq!(sync.state_tracker.get_state_root(), root);
+        assert_eq!(store.state_leaves().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_state_replays_commit_order() {
+        let store = Arc::new(InMemoryStore::new());
+        let live = sync_over(store.clone());
+        for (hash, height) in [("0xm2", 11), ("0xm1", 10), ("0xm3", 12)] {
+            live.handle_event(&microblock_event(hash, height))
+                .await
+                .unwrap();
+        }
+        let live_root = live.state_tracker.get_state_root();
+        // Leaves are appended as blocks are committed, never spliced in by height.
+        assert_eq!(
+            live.state_tracker.export_leaves(),
+            vec!["0xm2-tx", "0xm1-tx", "0xm3-tx"]
+        );
+
+        let fresh = sync_over(store.clone());
+        assert_eq!(fresh.rebuild_state().await.unwrap(), live_root);
+        assert_eq!(store.state_root().as_deref(), Some(live_root.as_str()));
+
+        // A node bootstrapped from a snapshot replays later blocks on top of it.
+        let snapshot =
+            crate::state::snapshot::StateSnapshot::capture(&live.state_tracker, Some(12));
+        let commitment = live.state_tracker.commitment();
+        let bootstrapped_store = Arc::new(InMemoryStore::new());
+        bootstrapped_store
+            .install_snapshot(&snapshot, &commitment)
+            .await
+            .unwrap();
+        // Installing over leaves is refused.
+        assert!(store
+            .install_snapshot(&snapshot, &commitment)
+            .await
+            .is_err());
+        let bootstrapped = sync_over(bootstrapped_store.clone());
+        bootstrapped.rebuild_state().await.unwrap();
+        for sync in [&live, &bootstrapped] {
+            sync.handle_event(&microblock_event("0xm4", 13))
+                .await
+                .unwrap();
+        }
+        let rebuilt = sync_over(bootstrapped_store);
+        assert_eq!(
+            rebuilt.rebuild_state().await.unwrap(),
+            live.state_tracker.get_state_root()
+        );
+        assert_eq!(rebuilt.state_tracker.export_leaves().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_persists_events_already_received() {
+        use futures_util::SinkExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (sent_tx, sent_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            for height in 1..=3 {
+                let event = microblock_event(&format!("0xm{}", height), height);
+                ws.send(Message::text(serde_json::to_string(&event).unwrap()))
+                    .await
+                    .unwrap();
+            }
+            let _ = sent_tx.send(());
+            // Keep the stream open: only the shutdown signal may end the run.
+            std::future::pending::<()>().await;
+        });
+
+        let store = Arc::new(InMemoryStore::new());
+        let mut sync = sync_over(store.clone());
+        sync.ws_url = format!("ws://{}", addr);
+        let sync = Arc::new(sync);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let run = tokio::spawn({
+            let sync = sync.clone();
+            async move { sync.run(shutdown_rx).await }
+        });
+
+        sent_rx.await.unwrap();
+        shutdown_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), run)
+            .await
+            .expect("sync stops on shutdown")
+            .unwrap()
+            .unwrap();
+        assert_eq!(sync.resume_height().await.unwrap(), Some(3));
+        assert_eq!(sync.state_tracker.export_leaves().len(), 3);
+    }
+
+    #[test]
+    fn test_sync_event_wire_format() {
+        let event: SyncEvent =
+            serde_json::from_str(r#"{"type":"burn_block","hash":"0xb1","height":7}"#).unwrap();
+        assert_eq!(event.kind(), "burn_block");
+        let event: SyncEvent = serde_json::from_str(
+            r#"{"type":"microblock","hash":"0xm1","height":8,"parent_hash":"0xm0","tx_ids":["a"]}"#,
+        )
+        .unwrap();
+        assert_eq!(event.kind(), "microblock");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_block_takes_senders_from_the_block_transaction_list() {
+        use axum::{routing::get, Json, Router};
+
+        let app = Router::new().route(
+            "/extended/v2/blocks/7/transactions",
+            get(|| async {
+                Json(serde_json::json!({
+                    "limit": 50,
+                    "offset": 0,
+                    "total": 2,
+                    "results": [
+                        {"tx_id": "0xt1", "tx_type": "token_transfer", "sender_address": "SP1"},
+                        {"tx_id": "0xt2", "tx_type": "token_transfer", "sender_address": "SP2"},
+                    ],
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let store = Arc::new(InMemoryStore::new());
+        let mut sync = sync_over(store.clone()).with_payload_fetch(true);
+        sync.rpc = Arc::new(RpcEndpoints::single(base));
+        let block_time = Utc::now();
+        // The stream carried the JSON of one transaction only.
+        let streamed = r#"{"tx_id":"0xt1","tx_type":"token_transfer","sender_address":"SP9"}"#;
+        sync.handle_event(&SyncEvent::Microblock(MicroblockData {
+            hash: "0xm7".to_string(),
+            height: 7,
+            parent_hash: "0xm6".to_string(),
+            tx_ids: vec!["0xt1".to_string(), "0xt2".to_string()],
+            payloads: HashMap::from([("0xt1".to_string(), streamed.to_string())]),
+            timestamp: Some(block_time.timestamp() as u64),
+        }))
+        .await
+        .unwrap();
+
+        let since = block_time - chrono::Duration::seconds(1);
+        for (sender, count) in [("SP9", 1), ("SP2", 1), ("SP1", 0)] {
+            assert_eq!(
+                store
+                    .sender_transactions_since(sender, since)
+                    .await
+                    .unwrap(),
+                count,
+                "{}",
+                sender
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfilled_transactions_carry_sender_and_block_time() {
+        let page: ApiBlockTransactions = serde_json::from_str(
+            r#"{"limit":50,"offset":0,"total":2,"results":[
+                {"tx_id":"0xt1","tx_type":"token_transfer","sender_address":"SP1"},
+                {"tx_type":"coinbase"}
+            ]}"#,
+        )
+        .unwrap();
+        let payloads: HashMap<String, String> = page.payloads().collect();
+        assert_eq!(payloads.len(), 1);
+
+        let store = Arc::new(InMemoryStore::new());
+        let sync = sync_over(store.clone());
+        let block_time = Utc::now();
+        sync.process_microblock(MicroblockData {
+            hash: "0xm1".to_string(),
+            height: 1,
+            parent_hash: "0xm0".to_string(),
+            tx_ids: vec!["0xt1".to_string()],
+            payloads,
+            timestamp: Some(block_time.timestamp() as u64),
+        })
+        .await
+        .unwrap();
+        let since = block_time - chrono::Duration::seconds(1);
+        assert_eq!(
+            store.sender_transactions_since("SP1", since).await.unwrap(),
+            1
+        );
+        // Counted by chain time, not by when the block was ingested.
+        let after = block_time + chrono::Duration::seconds(1);
+        assert_eq!(
+            store.sender_transactions_since("SP1", after).await.unwrap(),
+            0
+        );
     }
 }
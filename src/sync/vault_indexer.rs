@@ -0,0 +1,413 @@
+//! [NEXUS-VAULT-02] Builds vault state from what the vault contracts print: lifecycle
+//! events (`vault-opened`, `collateral-deposited`, `debt-minted`, `vault-closed`) and
+//! full-state tuples, so the rebalancer has vaults to watch without an external writer.
+//! The indexer is the only writer of the `vaults` table. Events apply in `(block height,
+//! tx index, event index)` order within a block, and the store remembers the position of
+//! every event applied, so replaying a block changes nothing while a block that arrives
+//! late still applies.
+
+use super::decoder::{self, VaultChange, VaultPrint};
+use super::MicroblockData;
+use crate::executor::fixed::{Amount, Ratio};
+use crate::executor::{ltv, VaultStatus};
+use crate::storage::store::NexusStore;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Where an event sits on chain; events apply in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventPosition {
+    pub height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+}
+
+/// A vault as the indexer keeps it.
+#[derive(Debug, Clone)]
+pub struct VaultRecord {
+    pub vault: VaultStatus,
+    /// Cleared by `vault-closed`; only active vaults are cached for the executor.
+    pub active: bool,
+    /// Latest event applied.
+    pub last_event: Option<EventPosition>,
+}
+
+/// `record` after `print` at `position`, or `None` when the event does not apply: it
+/// opens a vault that is already open, or changes a vault that is unknown or closed.
+/// Whether the event was applied before is the caller's to check. Fails when a balance
+/// would overflow.
+pub fn apply(
+    record: Option<&VaultRecord>,
+    position: EventPosition,
+    print: &VaultPrint,
+) -> anyhow::Result<Option<VaultRecord>> {
+    let open = record.filter(|r| r.active);
+    let mut next = match (&print.change, open) {
+        (VaultChange::Opened { .. }, Some(_)) => {
+            tracing::warn!(
+                vault = %print.vault_id,
+                height = position.height,
+                tx_index = position.tx_index,
+                event_index = position.event_index,
+                "Ignoring vault-opened for a vault that is already open"
+            );
+            return Ok(None);
+        }
+        (VaultChange::Opened { collateral_type }, None) => VaultRecord {
+            vault: VaultStatus {
+                vault_id: print.vault_id.clone(),
+                collateral_type: collateral_type.clone(),
+                collateral_amount: Amount::ZERO,
+                debt_amount: Amount::ZERO,
+                ltv_ratio: None,
+            },
+            active: true,
+            last_event: record.and_then(|r| r.last_event),
+        },
+        (
+            VaultChange::State {
+                collateral_type,
+                collateral,
+                debt,
+                ltv_bps,
+            },
+            _,
+        ) => VaultRecord {
+            vault: VaultStatus {
+                vault_id: print.vault_id.clone(),
+                collateral_type: collateral_type.clone(),
+                collateral_amount: *collateral,
+                debt_amount: *debt,
+                ltv_ratio: ltv_bps.map(Ratio::from_bps),
+            },
+            active: true,
+            last_event: record.and_then(|r| r.last_event),
+        },
+        (_, Some(record)) => record.clone(),
+        (_, None) => {
+            tracing::warn!(
+                vault = %print.vault_id,
+                height = position.height,
+                tx_index = position.tx_index,
+                event_index = position.event_index,
+                "Ignoring event for a vault that is not open"
+            );
+            return Ok(None);
+        }
+    };
+    let overflow = |balance: &str| {
+        anyhow::anyhow!(
+            "Vault {} {} overflows at height {} tx {} event {}",
+            print.vault_id,
+            balance,
+            position.height,
+            position.tx_index,
+            position.event_index
+        )
+    };
+    match print.change {
+        VaultChange::Opened { .. } | VaultChange::State { .. } => {}
+        VaultChange::CollateralDeposited { amount } => {
+            next.vault.collateral_amount = next
+                .vault
+                .collateral_amount
+                .checked_add(amount)
+                .ok_or_else(|| overflow("collateral"))?;
+            next.vault.ltv_ratio = None;
+        }
+        VaultChange::DebtMinted { amount } => {
+            next.vault.debt_amount = next
+                .vault
+                .debt_amount
+                .checked_add(amount)
+                .ok_or_else(|| overflow("debt"))?;
+            next.vault.ltv_ratio = None;
+        }
+        VaultChange::Closed => next.active = false,
+    }
+    next.last_event = next.last_event.max(Some(position));
+    Ok(Some(next))
+}
+
+/// Applies the vault events of the configured contracts to the store.
+pub struct VaultIndexer {
+    contracts: Vec<String>,
+}
+
+impl VaultIndexer {
+    /// Indexes prints of `contracts` (`SP….name` contract ids).
+    pub fn new(contracts: Vec<String>) -> Self {
+        Self { contracts }
+    }
+
+    /// The block's vault events, in chain order.
+    pub fn events(&self, data: &MicroblockData) -> Vec<(EventPosition, VaultPrint)> {
+        let mut events: Vec<_> = data
+            .tx_ids
+            .iter()
+            .filter_map(|tx_id| data.payloads.get(tx_id))
+            .flat_map(|payload| decoder::decode_vault_prints(payload, &self.contracts))
+            .map(|print| {
+                let position = EventPosition {
+                    height: data.height,
+                    tx_index: print.tx_index,
+                    event_index: print.event_index,
+                };
+                (position, print)
+            })
+            .collect();
+        events.sort_by_key(|(position, _)| *position);
+        events
+    }
+
+    /// Applies the block's vault events that were not applied before, reprices the vaults
+    /// they changed from the latest oracle aggregate and saves them with the events'
+    /// positions. A vault without a price keeps the LTV it printed, if any. Returns the
+    /// number of events applied.
+    pub async fn index_block(
+        &self,
+        store: &dyn NexusStore,
+        data: &MicroblockData,
+    ) -> anyhow::Result<usize> {
+        let mut events = self.events(data);
+        if events.is_empty() {
+            return Ok(0);
+        }
+        let positions: Vec<EventPosition> = events.iter().map(|(position, _)| *position).collect();
+        let seen = store.applied_vault_events(&positions).await?;
+        events.retain(|(position, _)| !seen.contains(position));
+        let vault_ids: Vec<String> = events
+            .iter()
+            .map(|(_, print)| print.vault_id.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut records: BTreeMap<String, VaultRecord> = store
+            .vault_records(&vault_ids)
+            .await?
+            .into_iter()
+            .map(|record| (record.vault.vault_id.clone(), record))
+            .collect();
+
+        let mut changed = BTreeSet::new();
+        let mut applied = Vec::new();
+        for (position, print) in &events {
+            if let Some(next) = apply(records.get(&print.vault_id), *position, print)? {
+                records.insert(print.vault_id.clone(), next);
+                changed.insert(print.vault_id.clone());
+                applied.push((*position, print.vault_id.clone()));
+            }
+        }
+        if applied.is_empty() {
+            return Ok(0);
+        }
+
+        let prices = store.collateral_prices().await?;
+        let updates: Vec<VaultRecord> = changed
+            .iter()
+            .filter_map(|id| records.remove(id))
+            .map(|mut record| {
+                record.vault.ltv_ratio =
+                    ltv::priced_ltv(&record.vault, &prices).or(record.vault.ltv_ratio);
+                record
+            })
+            .collect();
+        store.save_vault_records(&updates, &applied).await?;
+        tracing::debug!(
+            block = %data.hash,
+            events = applied.len(),
+            vaults = updates.len(),
+            "Indexed vault contract events"
+        );
+        Ok(applied.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::store::InMemoryStore;
+    use std::collections::HashMap;
+
+    const VAULTS: &str = "SP3K8BC0PPEVCV7NZ6QSRWPQ2JE9E5B6N3PA0KBR9.conxian-vaults";
+
+    const VAULT_OPENED_V1: &str = include_str!("../../tests/fixtures/vaults/vault_opened_v1.json");
+    const DEBT_MINTED_V1: &str = include_str!("../../tests/fixtures/vaults/debt_minted_v1.json");
+    const VAULT_OPENED_V2: &str = include_str!("../../tests/fixtures/vaults/vault_opened_v2.json");
+    const COLLATERAL_DEPOSITED_V2: &str =
+        include_str!("../../tests/fixtures/vaults/collateral_deposited_v2.json");
+    const VAULT_CLOSED_V2: &str = include_str!("../../tests/fixtures/vaults/vault_closed_v2.json");
+
+    fn block(height: u64, payloads: &[&str]) -> MicroblockData {
+        let mut data = MicroblockData {
+            hash: format!("0xb{}", height),
+            height,
+            parent_hash: String::new(),
+            tx_ids: Vec::new(),
+            payloads: HashMap::new(),
+            timestamp: None,
+        };
+        for payload in payloads {
+            let tx: serde_json::Value = serde_json::from_str(payload).unwrap();
+            let tx_id = tx["tx_id"].as_str().unwrap().to_string();
+            data.tx_ids.push(tx_id.clone());
+            data.payloads.insert(tx_id, payload.to_string());
+        }
+        data
+    }
+
+    async fn active(store: &InMemoryStore) -> Vec<(String, u128, u128)> {
+        let mut vaults: Vec<_> = store
+            .vaults()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|v| {
+                (
+                    v.vault_id,
+                    v.collateral_amount.units(),
+                    v.debt_amount.units(),
+                )
+            })
+            .collect();
+        vaults.sort();
+        vaults
+    }
+
+    #[tokio::test]
+    async fn test_event_sequence_yields_balances_and_active_set() {
+        let store = InMemoryStore::new();
+        store.set_collateral_prices(BTreeMap::from([("BTC".to_string(), 60_000.0)]));
+        let indexer = VaultIndexer::new(vec![VAULTS.to_string()]);
+
+        let opened = block(100, &[VAULT_OPENED_V1]);
+        assert_eq!(indexer.index_block(&store, &opened).await.unwrap(), 2);
+        assert_eq!(
+            active(&store).await,
+            vec![("v1".to_string(), 100_000_000, 0)]
+        );
+
+        // The deposit is listed before the open, but applies after it by tx index; the
+        // other contract's look-alike print is ignored.
+        let next = block(
+            101,
+            &[COLLATERAL_DEPOSITED_V2, VAULT_OPENED_V2, DEBT_MINTED_V1],
+        );
+        assert_eq!(indexer.index_block(&store, &next).await.unwrap(), 3);
+        assert_eq!(
+            active(&store).await,
+            vec![
+                ("v1".to_string(), 100_000_000, 30_000_000_000),
+                ("v2".to_string(), 1_000_000_000, 0),
+            ]
+        );
+        let v1 = store.vault_records(&["v1".to_string()]).await.unwrap();
+        assert_eq!(v1[0].vault.ltv_ratio, Some(Ratio::from_bps(5_000)));
+        assert_eq!(
+            v1[0].last_event,
+            Some(EventPosition {
+                height: 101,
+                tx_index: 0,
+                event_index: 0
+            })
+        );
+
+        // Replaying blocks changes nothing.
+        assert_eq!(indexer.index_block(&store, &next).await.unwrap(), 0);
+        assert_eq!(indexer.index_block(&store, &opened).await.unwrap(), 0);
+        assert_eq!(active(&store).await.len(), 2);
+        assert_eq!(active(&store).await[0].2, 30_000_000_000);
+
+        indexer
+            .index_block(&store, &block(102, &[VAULT_CLOSED_V2]))
+            .await
+            .unwrap();
+        assert_eq!(
+            active(&store).await,
+            vec![("v1".to_string(), 100_000_000, 30_000_000_000)]
+        );
+        let v2 = store.vault_records(&["v2".to_string()]).await.unwrap();
+        assert!(!v2[0].active);
+    }
+
+    #[tokio::test]
+    async fn test_late_block_applies_and_reopening_keeps_balances() {
+        let store = InMemoryStore::new();
+        let indexer = VaultIndexer::new(vec![VAULTS.to_string()]);
+        indexer
+            .index_block(&store, &block(100, &[VAULT_OPENED_V1]))
+            .await
+            .unwrap();
+        indexer
+            .index_block(&store, &block(102, &[DEBT_MINTED_V1]))
+            .await
+            .unwrap();
+
+        // Height 101 arrives after 102. Its deposit still applies; its second
+        // `vault-opened` does not reset the open vault.
+        assert_eq!(
+            indexer
+                .index_block(&store, &block(101, &[VAULT_OPENED_V1]))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            active(&store).await,
+            vec![("v1".to_string(), 200_000_000, 30_000_000_000)]
+        );
+        let v1 = store.vault_records(&["v1".to_string()]).await.unwrap();
+        assert_eq!(v1[0].last_event.map(|p| p.height), Some(102));
+    }
+
+    #[test]
+    fn test_overflowing_balance_is_an_error() {
+        let open = VaultRecord {
+            vault: VaultStatus {
+                vault_id: "v1".to_string(),
+                collateral_type: "BTC".to_string(),
+                collateral_amount: Amount::new(u128::MAX),
+                debt_amount: Amount::ZERO,
+                ltv_ratio: None,
+            },
+            active: true,
+            last_event: None,
+        };
+        let deposit = VaultPrint {
+            tx_index: 0,
+            event_index: 0,
+            vault_id: "v1".to_string(),
+            change: VaultChange::CollateralDeposited {
+                amount: Amount::new(1),
+            },
+        };
+        let position = EventPosition {
+            height: 1,
+            tx_index: 0,
+            event_index: 0,
+        };
+        assert!(apply(Some(&open), position, &deposit).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_contract_and_unopened_vault_are_ignored() {
+        let store = InMemoryStore::new();
+        let other = VaultIndexer::new(vec!["SP000.other-vaults".to_string()]);
+        assert_eq!(
+            other
+                .index_block(&store, &block(100, &[VAULT_OPENED_V1]))
+                .await
+                .unwrap(),
+            0
+        );
+
+        let indexer = VaultIndexer::new(vec![VAULTS.to_string()]);
+        assert_eq!(
+            indexer
+                .index_block(&store, &block(101, &[DEBT_MINTED_V1]))
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(store.vaults().await.unwrap().is_empty());
+    }
+}
@@ -9,6 +9,7 @@ use conxian_nexus::config::Config;
 use conxian_nexus::config::ENV_ADMIN_API_TOKEN;
 use conxian_nexus::executor::rgb::RGBRolloutMode;
 use conxian_nexus::executor::NexusExecutor;
+use conxian_nexus::gateway::ServiceRegistry;
 use conxian_nexus::state::NexusState;
 use conxian_nexus::storage::tableland::TablelandAdapter;
 use conxian_nexus::storage::Storage;
@@ -23,6 +24,7 @@ const RELEASE_APPROVAL_PAYLOAD: &str = r#"{"artifactId":"artifact-1","requestedB
 const RELEASE_DECISION_PAYLOAD: &str = r#"{"artifactId":"artifact-1","decision":"approve","actorId":"actor-1","secondApprover":"actor-2","signatures":["sig1","sig2"]}"#;
 const GOVERNANCE_DECISION_PAYLOAD: &str = r#"{"actionId":"action-1","decision":"approve","actorId":"actor-1","secondApprover":"actor-2","signatures":["sig1","sig2"]}"#;
 const SAFETY_MODE_ACK_PAYLOAD: &str = r#"{"ackBy":"operator-1","reason":"acknowledged"}"#;
+const SAFETY_DRILL_PAYLOAD: &str = r#"{"reason":"quarterly handoff drill"}"#;
 
 #[derive(Clone)]
 struct CanonicalEndpoint {
@@ -100,6 +102,12 @@ fn canonical_admin_v1_endpoints() -> Vec<CanonicalEndpoint> {
             contract_path: "/admin/v1/drift",
             body: None,
         },
+        CanonicalEndpoint {
+            method: Method::GET,
+            request_path: "/admin/v1/migrations",
+            contract_path: "/admin/v1/migrations",
+            body: None,
+        },
         CanonicalEndpoint {
             method: Method::GET,
             request_path: "/admin/v1/safety-mode",
@@ -112,6 +120,18 @@ fn canonical_admin_v1_endpoints() -> Vec<CanonicalEndpoint> {
             contract_path: "/admin/v1/safety-mode/ack",
             body: Some(SAFETY_MODE_ACK_PAYLOAD),
         },
+        CanonicalEndpoint {
+            method: Method::POST,
+            request_path: "/admin/v1/safety/trigger",
+            contract_path: "/admin/v1/safety/trigger",
+            body: Some(SAFETY_DRILL_PAYLOAD),
+        },
+        CanonicalEndpoint {
+            method: Method::POST,
+            request_path: "/admin/v1/safety/clear",
+            contract_path: "/admin/v1/safety/clear",
+            body: None,
+        },
         CanonicalEndpoint {
             method: Method::GET,
             request_path: "/admin/v1/promotion-evidence/release%2F2026.06",
@@ -204,6 +224,7 @@ fn test_router() -> Router {
         tableland,
         kwil: None,
         nostr: None,
+        gateway: Arc::new(ServiceRegistry::with_builtin_services()),
         gateway_url: None,
         http_client: reqwest::Client::new(),
         config: config.clone(),
@@ -743,3 +764,49 @@ async fn test_admin_write_rejects_single_signature() {
         Some("insufficient_approvals")
     );
 }
+
+#[tokio::test]
+async fn test_safety_drill_requires_admin_and_a_reason() {
+    let _env_lock = admin_api_token_lock().lock().await;
+    let _token = ScopedEnvVar::set(ENV_ADMIN_API_TOKEN, Some("expected-admin-token"));
+    let app = test_router();
+
+    let drill = |token: Option<&str>, body: &'static str| {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/admin/v1/safety/trigger")
+            .header(header::HOST, "nexus.test")
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(Body::from(body)).unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(drill(None, SAFETY_DRILL_PAYLOAD))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .clone()
+        .oneshot(drill(Some("expected-admin-token"), r#"{"reason":"   "}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/v1/safety/clear")
+                .header(header::HOST, "nexus.test")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -27,7 +27,43 @@ async fn test_merkle_logic_edge_cases() {
     assert_ne!(root1, root2);
 
     let proof = state.generate_merkle_proof("tx3").unwrap();
-    assert!(conxian_nexus::state::verify_merkle_proof(&proof));
+    assert!(conxian_nexus::state::verify_merkle_proof(
+        &proof,
+        state.tree_params()
+    ));
+    assert_eq!((proof.leaf_index, proof.tree_size), (2, 3));
+    assert_eq!(proof.path.len(), 2);
+    assert!(proof.to_compact_hex().unwrap().starts_with("0x"));
+}
+
+#[tokio::test]
+async fn test_last_updated_advances_with_each_update() {
+    let state = NexusState::new();
+    assert_eq!(state.last_updated(), None);
+    assert_eq!(state.leaf_count(), 0);
+
+    state.update_state("tx1", 1);
+    let first = state.last_updated().unwrap();
+    assert_eq!(state.leaf_count(), 1);
+    assert_eq!(state.seconds_since_last_block(), None);
+
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    state.update_state("tx2", 2);
+    assert!(state.last_updated().unwrap() > first);
+    assert_eq!(state.leaf_count(), 2);
+}
+
+#[test]
+fn test_block_age_follows_chain_time_across_reloads() {
+    let state = NexusState::new();
+    let tip = chrono::Utc::now() - chrono::Duration::seconds(600);
+    state.record_block_time(tip);
+    state.record_block_time(tip - chrono::Duration::seconds(60));
+    assert_eq!(state.last_block_time(), Some(tip));
+
+    // Reloading the tree recomputes the root but does not make the chain tip newer.
+    state.set_initial_leaves(vec!["tx1".to_string()]);
+    assert!(state.seconds_since_last_block().unwrap() >= 600);
 }
 
 #[tokio::test]
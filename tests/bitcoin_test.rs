@@ -542,7 +542,10 @@ async fn test_btc_tx_state_transition() {
         assert!(proof.is_some(), "BTC tx {} must have a Merkle proof", tx);
         let proof = proof.unwrap();
         assert_eq!(proof.leaf, *tx);
-        assert!(conxian_nexus::state::verify_merkle_proof(&proof));
+        assert!(conxian_nexus::state::verify_merkle_proof(
+            &proof,
+            state.tree_params()
+        ));
     }
 
     // Verify the root is consistent
@@ -8,7 +8,9 @@ async fn test_execution_request_priority_serialization() {
         payload: "test".to_string(),
         timestamp: Utc::now(),
         sender: "alice".to_string(),
+        nonce: 1,
         priority: 10,
+        signature: None,
     };
     let json = serde_json::to_string(&req).unwrap();
     let deserialized: ExecutionRequest = serde_json::from_str(&json).unwrap();
@@ -0,0 +1,119 @@
+use chrono::Utc;
+use conxian_nexus::config::Config;
+use conxian_nexus::executor::queue::{Backlog, QueueError};
+use conxian_nexus::executor::rgb::RGBRolloutMode;
+use conxian_nexus::executor::{ExecutionRequest, ExecutionStatus, NexusExecutor, RejectReason};
+use conxian_nexus::stacks::StacksSigner;
+use conxian_nexus::storage::store::{InMemoryStore, NexusStore};
+use conxian_nexus::storage::Storage;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MAX_DEPTH: usize = 8;
+
+fn executor(store: Arc<InMemoryStore>, max_age: Duration) -> Arc<NexusExecutor> {
+    let storage = Arc::new(Storage::from_config_lazy(&Config::default_test()).unwrap());
+    Arc::new(
+        NexusExecutor::new(storage, RGBRolloutMode::Disabled, HashSet::new())
+            .with_store(store)
+            .with_queue_limits(MAX_DEPTH, max_age),
+    )
+}
+
+/// Later-numbered requests are newer, so sequencing them in order never trips FSOC.
+/// Each is signed by a key of its own sender.
+fn request(sender: usize) -> ExecutionRequest {
+    let signer = StacksSigner::from_hex(&format!("{:064x}", sender + 1)).unwrap();
+    ExecutionRequest {
+        tx_id: format!("tx-{}", sender),
+        payload: "data".to_string(),
+        timestamp: Utc::now() + chrono::Duration::milliseconds(sender as i64),
+        sender: signer.address(true).to_string(),
+        nonce: 1,
+        priority: 0,
+        signature: None,
+    }
+    .sign(&signer)
+    .unwrap()
+}
+
+async fn wait_for_depth(executor: &NexusExecutor, depth: usize) {
+    for _ in 0..200 {
+        if executor.queue.depth() == depth {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("queue never reached depth {}", depth);
+}
+
+#[tokio::test]
+async fn test_full_queue_rejects_then_accepts_after_draining() {
+    let store = Arc::new(InMemoryStore::new());
+    let executor = executor(store.clone(), Duration::from_secs(30));
+
+    // Hold the sequencer so submissions pile up behind it.
+    let held = executor.queue.enter().await.unwrap();
+    let waiting: Vec<_> = (1..MAX_DEPTH)
+        .map(|i| {
+            let executor = executor.clone();
+            tokio::spawn(async move { executor.submit(request(i)).await })
+        })
+        .collect();
+    wait_for_depth(&executor, MAX_DEPTH).await;
+    assert_eq!(executor.queue.backlog(), Backlog::Full);
+
+    for i in 0..3 {
+        let err = executor.submit(request(100 + i)).await.unwrap_err();
+        match err.downcast_ref::<QueueError>() {
+            Some(QueueError::Full { depth, .. }) => assert_eq!(*depth, MAX_DEPTH),
+            other => panic!("expected a full queue, got {:?}", other),
+        }
+    }
+
+    drop(held);
+    for handle in waiting {
+        handle.await.unwrap().unwrap();
+    }
+    assert_eq!(executor.queue.depth(), 0);
+    assert_eq!(executor.queue.backlog(), Backlog::Idle);
+    assert_eq!(executor.submit(request(200)).await.unwrap(), "tx-200");
+
+    let stats = store.executor_stats().await.unwrap();
+    assert_eq!(stats.accepted, MAX_DEPTH as u64);
+    assert_eq!(stats.rejected_by_reason["queue_full"], 3);
+}
+
+#[tokio::test]
+async fn test_expired_submissions_are_recorded_as_rejections() {
+    let store = Arc::new(InMemoryStore::new());
+    let executor = executor(store.clone(), Duration::from_millis(20));
+
+    let held = executor.queue.enter().await.unwrap();
+    let stale = {
+        let executor = executor.clone();
+        tokio::spawn(async move { executor.submit(request(1)).await })
+    };
+    wait_for_depth(&executor, 2).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(held);
+
+    let err = stale.await.unwrap().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<QueueError>(),
+        Some(QueueError::Expired { .. })
+    ));
+    // Recorded as a rejection in the execution audit log, not as a sync dead letter.
+    assert!(store.dead_letters(10).await.unwrap().is_empty());
+    let expired = store.execution("tx-1").await.unwrap().unwrap();
+    assert_eq!(expired.status, ExecutionStatus::Rejected);
+    assert_eq!(expired.rejection_reason, Some(RejectReason::Expired));
+    assert_eq!(
+        store.executor_stats().await.unwrap().rejected_by_reason["expired"],
+        1
+    );
+
+    // A fresh submission is sequenced normally.
+    executor.submit(request(2)).await.unwrap();
+}
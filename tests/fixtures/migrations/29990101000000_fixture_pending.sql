@@ -0,0 +1,2 @@
+-- Never applied: stands in for a migration shipped by a newer binary.
+SELECT 1;
@@ -34,12 +34,18 @@ async fn test_merkle_proof_verification() {
         .expect("Proof should be generated");
 
     assert_eq!(proof.leaf, "tx2");
-    assert!(conxian_nexus::state::verify_merkle_proof(&proof));
+    assert!(conxian_nexus::state::verify_merkle_proof(
+        &proof,
+        state.tree_params()
+    ));
 
     // Test with invalid root
     let mut invalid_proof = proof.clone();
     invalid_proof.root = "0x0000".to_string();
-    assert!(!conxian_nexus::state::verify_merkle_proof(&invalid_proof));
+    assert!(!conxian_nexus::state::verify_merkle_proof(
+        &invalid_proof,
+        state.tree_params()
+    ));
 }
 
 #[tokio::test]
@@ -53,7 +59,7 @@ async fn test_leaf_to_root_verification() {
     for leaf in &leaves {
         let proof = state.generate_merkle_proof(leaf).expect("Proof exists");
         assert!(
-            conxian_nexus::state::verify_merkle_proof(&proof),
+            conxian_nexus::state::verify_merkle_proof(&proof, state.tree_params()),
             "Failed to verify leaf: {}",
             leaf
         );
@@ -73,7 +79,10 @@ async fn test_root_to_leaf_consistency() {
 
     let proof1 = state.generate_merkle_proof("data1").unwrap();
     assert_eq!(proof1.root, root2); // Proof should be against the latest root
-    assert!(conxian_nexus::state::verify_merkle_proof(&proof1));
+    assert!(conxian_nexus::state::verify_merkle_proof(
+        &proof1,
+        state.tree_params()
+    ));
 }
 
 #[tokio::test]
@@ -10,7 +10,7 @@ use std::sync::Arc;
 #[tokio::test]
 async fn test_kwil_block_persistence_pilot_signed() {
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => return,
     };
@@ -39,7 +39,7 @@ async fn test_kwil_block_persistence_pilot_signed() {
 #[tokio::test]
 async fn test_kwil_state_root_persistence_pilot_signed() {
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => return,
     };
@@ -65,7 +65,7 @@ async fn test_kwil_state_root_persistence_pilot_signed() {
 #[tokio::test]
 async fn test_kwil_mmr_node_persistence_pilot_signed() {
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => return,
     };
@@ -92,7 +92,7 @@ async fn test_kwil_mmr_node_persistence_pilot_signed() {
 #[tokio::test]
 async fn test_kwil_settlement_proposal_persistence_pilot_signed() {
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => return,
     };
@@ -123,7 +123,7 @@ async fn test_kwil_settlement_proposal_persistence_pilot_signed() {
 #[tokio::test]
 async fn test_kwil_settlement_log_persistence_pilot_signed() {
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => return,
     };
@@ -327,7 +327,7 @@ fn test_ln_collector_dedup_idempotency() {
 async fn test_ln_dlc_bond_lightning_payment_ref() {
     // DLC bond created with a Lightning payment reference as bond_id
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => {
             eprintln!("Skipping test: Database not available");
@@ -392,7 +392,7 @@ async fn test_ln_dlc_bond_lightning_payment_ref() {
 async fn test_ln_dlc_bond_zero_expiry_passes_validation() {
     // Edge case: DLC handler does NOT validate expiry_height — zero is allowed
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => {
             eprintln!("Skipping test: Database not available");
@@ -449,7 +449,7 @@ async fn test_ln_dlc_bond_zero_expiry_passes_validation() {
 async fn test_ln_dlc_bond_short_expiry_channel() {
     // Short-lived bond matching a Lightning channel lifetime (~2 weeks in blocks)
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => {
             eprintln!("Skipping test: Database not available");
@@ -556,7 +556,7 @@ fn test_ln_hmac_sha256_implementation() {
 async fn test_ln_billing_telemetry_invalid_api_key() {
     // Test telemetry endpoint with invalid LN API key
     let config = Config::default_test();
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => {
             eprintln!("Skipping test: Database not available");
@@ -0,0 +1,51 @@
+use conxian_nexus::config::Config;
+use conxian_nexus::storage::migrations::MIGRATOR;
+use conxian_nexus::storage::Storage;
+use sqlx::migrate::Migrator;
+use std::path::Path;
+
+const FIXTURE_VERSION: i64 = 29990101000000;
+
+async fn migrated_storage() -> Option<Storage> {
+    let storage = Storage::new(&Config::default_test()).await.ok()?;
+    storage.run_migrations().await.ok()?;
+    Some(storage)
+}
+
+#[tokio::test]
+async fn test_embedded_migrations_report_nothing_pending() {
+    let Some(storage) = migrated_storage().await else {
+        eprintln!("Skipping migration status tests: database not available");
+        return;
+    };
+
+    let status = storage.migration_status().await.unwrap();
+    assert!(!status.has_pending);
+    assert!(status.unknown.is_empty());
+    assert!(status.applied.iter().all(|m| !m.checksum_mismatch));
+    let latest = MIGRATOR.iter().map(|m| m.version).max();
+    assert_eq!(status.schema_version, latest);
+    assert_eq!(storage.schema_version().await.unwrap(), latest);
+    storage.require_migrated().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fixture_migration_is_detected_as_pending() {
+    let Some(storage) = migrated_storage().await else {
+        eprintln!("Skipping migration status tests: database not available");
+        return;
+    };
+    let fixture = Migrator::new(Path::new("tests/fixtures/migrations"))
+        .await
+        .unwrap();
+
+    let status = storage.migration_status_for(&fixture).await.unwrap();
+    assert!(status.has_pending);
+    assert_eq!(status.pending, vec![FIXTURE_VERSION]);
+    // Everything the real binary applied is unknown to the fixture set.
+    assert_eq!(status.unknown.len(), MIGRATOR.iter().count());
+
+    let err = storage.require_migrated_for(&fixture).await.unwrap_err();
+    assert!(err.to_string().contains(&FIXTURE_VERSION.to_string()));
+    assert!(err.to_string().contains("AUTO_MIGRATE"));
+}
@@ -0,0 +1,101 @@
+use conxian_nexus::config::Config;
+use conxian_nexus::oracle::providers::{FxProvider, MockProvider, OracleError};
+use conxian_nexus::oracle::{observation_history, OracleService, ORACLE_PPP_CACHE_KEY};
+use conxian_nexus::storage::Storage;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+async fn test_storage() -> Option<Arc<Storage>> {
+    let storage = Storage::new(&Config::default_test()).await.ok()?;
+    storage.run_migrations().await.ok()?;
+    storage
+        .with_redis(
+            |mut conn| async move { redis::cmd("PING").query_async::<String>(&mut conn).await },
+        )
+        .await
+        .ok()?;
+    Some(Arc::new(storage))
+}
+
+#[tokio::test]
+async fn test_fetch_records_observations_and_caches_aggregate() {
+    let Some(storage) = test_storage().await else {
+        eprintln!("Skipping oracle service test: database or Redis not available");
+        return;
+    };
+
+    // A timestamp no other run uses, so the rows written here can be found again.
+    let observed_at = 4_000_000_000 + (uuid::Uuid::new_v4().as_u128() % 1_000_000) as u64;
+    let usd: Arc<dyn FxProvider> = Arc::new(MockProvider::new(
+        "USD",
+        HashMap::from([("USD".to_string(), 1.0), ("EUR".to_string(), 0.9)]),
+        observed_at,
+    ));
+    let down: Arc<dyn FxProvider> = Arc::new(MockProvider::failing(OracleError::Auth(
+        "invalid-key".to_string(),
+    )));
+    let service = OracleService::with_providers(storage.clone(), vec![(usd, 0.5), (down, 0.5)]);
+
+    let state = service.fetch_once().await.unwrap();
+    assert!((state.rates["EUR"] - 0.9).abs() < 1e-9);
+
+    let rows: Vec<(String, String, serde_json::Value)> = sqlx::query_as(
+        "SELECT provider, base_currency, rates FROM oracle_observations WHERE timestamp = $1",
+    )
+    .bind(observed_at as i64)
+    .fetch_all(&storage.pg_pool)
+    .await
+    .unwrap();
+    assert_eq!(rows.len(), 1, "only the healthy provider is observed");
+    assert_eq!(rows[0].0, "mock");
+    assert_eq!(rows[0].1, "USD");
+    assert_eq!(rows[0].2["EUR"], 0.9);
+
+    let cached = service.latest_cached().await.unwrap().unwrap();
+    assert_eq!(cached.timestamp, state.timestamp);
+    assert_eq!(cached.rates, state.rates);
+
+    let key = storage.redis_key(ORACLE_PPP_CACHE_KEY);
+    let key = key.as_str();
+    let raw: String = storage
+        .with_redis(
+            |mut conn| async move { redis::cmd("GET").arg(key).query_async(&mut conn).await },
+        )
+        .await
+        .unwrap();
+    assert!(raw.contains("\"base_currency\""));
+}
+
+#[tokio::test]
+async fn test_snapshot_and_observation_history() {
+    let Some(storage) = test_storage().await else {
+        eprintln!("Skipping oracle history test: database or Redis not available");
+        return;
+    };
+
+    let observed_at = 4_000_000_000 + (uuid::Uuid::new_v4().as_u128() % 1_000_000) as u64;
+    let usd: Arc<dyn FxProvider> = Arc::new(MockProvider::new(
+        "USD",
+        HashMap::from([("USD".to_string(), 1.0), ("EUR".to_string(), 0.9)]),
+        observed_at,
+    ));
+    let service = OracleService::with_providers(storage.clone(), vec![(usd, 1.0)]);
+    service.fetch_once().await.unwrap();
+
+    let snapshot = service.snapshot().await.unwrap().unwrap();
+    assert!(!snapshot.stale);
+    assert_eq!(snapshot.state.provenance["EUR"], vec!["mock"]);
+
+    let points = observation_history(&storage.pg_pool, "EUR", observed_at, observed_at, 10)
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 1);
+    assert_eq!(points[0].provider, "mock");
+    assert_eq!(points[0].rate, 0.9);
+
+    // Nothing is observed before 1970-01-01T00:00:02Z.
+    let empty = observation_history(&storage.pg_pool, "EUR", 0, 1, 10)
+        .await
+        .unwrap();
+    assert!(empty.is_empty());
+}
@@ -0,0 +1,109 @@
+use conxian_nexus::config::Config;
+use conxian_nexus::safety::{self, SafetyTrigger};
+use conxian_nexus::storage::keys;
+use conxian_nexus::storage::store::NexusStore;
+use conxian_nexus::storage::Storage;
+
+/// A lazily connected storage under a namespace no other run uses, or `None` without Redis.
+async fn namespaced(label: &str) -> Option<Storage> {
+    let namespace = format!("nexus:test-{}-{}:", label, uuid::Uuid::new_v4());
+    let storage = Storage::from_config_lazy(&Config::default_test())
+        .ok()?
+        .with_redis_namespace(namespace);
+    storage
+        .with_redis(
+            |mut conn| async move { redis::cmd("PING").query_async::<String>(&mut conn).await },
+        )
+        .await
+        .ok()?;
+    Some(storage)
+}
+
+async fn get(storage: &Storage, key: &str) -> Option<String> {
+    storage
+        .with_redis(
+            |mut conn| async move { redis::cmd("GET").arg(key).query_async(&mut conn).await },
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_namespaces_do_not_share_flags_or_nonces() {
+    let (Some(a), Some(b)) = (namespaced("a").await, namespaced("b").await) else {
+        eprintln!("Skipping Redis namespace test: Redis not available");
+        return;
+    };
+
+    safety::activate_safety_mode(&a, Some(9), SafetyTrigger::Drift, None)
+        .await
+        .unwrap();
+    assert!(safety::is_safety_mode_active(&a).await.unwrap());
+    assert_eq!(safety::current_drift(&a).await.unwrap(), 9);
+    assert!(!safety::is_safety_mode_active(&b).await.unwrap());
+    assert_eq!(safety::current_drift(&b).await.unwrap(), 0);
+
+    assert!(a.claim_nonce("alice", 5).await.unwrap());
+    assert_eq!(b.last_nonce("alice").await.unwrap(), None);
+    assert!(b.claim_nonce("alice", 1).await.unwrap());
+
+    a.save_state_root("root-a").await.unwrap();
+    b.save_state_root("root-b").await.unwrap();
+    assert_eq!(
+        get(&a, &a.redis_key(keys::STATE_ROOT)).await.as_deref(),
+        Some("root-a")
+    );
+    assert_eq!(
+        get(&b, &b.redis_key(keys::STATE_ROOT)).await.as_deref(),
+        Some("root-b")
+    );
+}
+
+#[tokio::test]
+async fn test_legacy_keys_are_copied_into_namespace() {
+    let Some(storage) = namespaced("migrate").await else {
+        eprintln!("Skipping Redis key migration test: Redis not available");
+        return;
+    };
+    let api_key = format!("cxl_{}", uuid::Uuid::new_v4().simple());
+    let legacy = keys::api_key(&api_key);
+    let namespaced_key = storage.redis_key(&keys::api_key(&api_key));
+    let (legacy_ref, namespaced_ref) = (legacy.as_str(), namespaced_key.as_str());
+    storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("HSET")
+                .arg(legacy_ref)
+                .arg("usage")
+                .arg(7)
+                .query_async::<()>(&mut conn)
+                .await
+        })
+        .await
+        .unwrap();
+
+    assert!(storage.migrate_legacy_redis_keys().await.unwrap() >= 1);
+    let usage: Option<u64> = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("HGET")
+                .arg(namespaced_ref)
+                .arg("usage")
+                .query_async(&mut conn)
+                .await
+        })
+        .await
+        .unwrap();
+    assert_eq!(usage, Some(7));
+    // A rerun leaves the copy alone.
+    storage.migrate_legacy_redis_keys().await.unwrap();
+
+    let _: () = storage
+        .with_redis(|mut conn| async move {
+            redis::cmd("DEL")
+                .arg(legacy_ref)
+                .arg(namespaced_ref)
+                .query_async(&mut conn)
+                .await
+        })
+        .await
+        .unwrap();
+}
@@ -0,0 +1,54 @@
+use conxian_nexus::config::Config;
+use conxian_nexus::storage::Storage;
+
+fn reconnects_total() -> f64 {
+    let text = prometheus::TextEncoder::new()
+        .encode_to_string(&prometheus::gather())
+        .unwrap();
+    text.lines()
+        .find_map(|line| line.strip_prefix("nexus_redis_reconnects_total "))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0.0)
+}
+
+async fn ping(storage: &Storage) -> redis::RedisResult<String> {
+    storage
+        .with_redis(|mut conn| async move { redis::cmd("PING").query_async(&mut conn).await })
+        .await
+}
+
+#[tokio::test]
+async fn test_shared_connection_recovers_after_redis_drops_clients() {
+    let storage = match Storage::from_config_lazy(&Config::default_test()) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if ping(&storage).await.is_err() {
+        eprintln!("Skipping Redis reconnect test: Redis not available");
+        return;
+    }
+
+    // Simulate a Redis restart by disconnecting every client, including the shared one.
+    let mut admin = storage
+        .redis_client
+        .get_multiplexed_async_connection()
+        .await
+        .unwrap();
+    let _: redis::RedisResult<u64> = redis::cmd("CLIENT")
+        .arg("KILL")
+        .arg("TYPE")
+        .arg("normal")
+        .arg("SKIPME")
+        .arg("yes")
+        .query_async(&mut admin)
+        .await;
+
+    let before = reconnects_total();
+    assert_eq!(ping(&storage).await.unwrap(), "PONG");
+    assert!(reconnects_total() > before);
+
+    // Later callers get the re-established connection without further intervention.
+    let mut conn = storage.redis().await.unwrap();
+    let pong: String = redis::cmd("PING").query_async(&mut conn).await.unwrap();
+    assert_eq!(pong, "PONG");
+}
@@ -16,7 +16,7 @@ use tower::ServiceExt;
 async fn build_test_app() -> Option<axum::Router> {
     let config = Arc::new(Config::default_test());
 
-    let storage = match Storage::from_config(&config).await {
+    let storage = match Storage::new(&config).await {
         Ok(s) => Arc::new(s),
         Err(_) => {
             eprintln!("Skipping settlement integration tests: database not available");
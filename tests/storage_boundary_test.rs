@@ -1,11 +1,23 @@
 #[cfg(test)]
 mod tests {
+    use conxian_nexus::config::Config;
     use conxian_nexus::storage::Storage;
     use std::env;
 
+    fn config_for(database_url: &str, redis_url: &str) -> Config {
+        let mut config = Config::default_test();
+        config.database_url = database_url.to_string();
+        config.redis_url = redis_url.to_string();
+        config
+    }
+
     #[tokio::test]
     async fn test_pg_boundary_check_local_rejection() {
-        let res = Storage::new("postgres://localhost/db", "redis://remote.com@6379").await;
+        let res = Storage::new(&config_for(
+            "postgres://localhost/db",
+            "redis://remote.com@6379",
+        ))
+        .await;
 
         // In release mode (cfg!(debug_assertions) is false), this should fail with boundary violation.
         // In debug mode, it won't fail with boundary violation.
@@ -30,7 +42,11 @@ mod tests {
     #[tokio::test]
     async fn test_pg_boundary_check_unauthenticated_rejection() {
         // postgres://host without : before @ or missing @
-        let res = Storage::new("postgres://remote-db/db", "redis://remote-redis@6379").await;
+        let res = Storage::new(&config_for(
+            "postgres://remote-db/db",
+            "redis://remote-redis@6379",
+        ))
+        .await;
 
         if let Err(e) = res {
             let error_msg = e.to_string();
@@ -43,7 +59,11 @@ mod tests {
     #[tokio::test]
     async fn test_pg_boundary_check_override() {
         env::set_var("NEXUS_ALLOW_UNSAFE_DB", "1");
-        let res = Storage::new("postgres://localhost/db", "redis://remote.com@6379").await;
+        let res = Storage::new(&config_for(
+            "postgres://localhost/db",
+            "redis://remote.com@6379",
+        ))
+        .await;
 
         if let Err(e) = res {
             let error_msg = e.to_string();
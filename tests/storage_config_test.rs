@@ -0,0 +1,87 @@
+use conxian_nexus::config::Config;
+use conxian_nexus::storage::Storage;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_storage_applies_pool_tuning_from_config() {
+    let mut config = Config::default_test();
+    config.database_max_connections = 2;
+    config.database_acquire_timeout_secs = 5;
+    config.database_statement_timeout_ms = 1234;
+
+    let storage = match Storage::new(&config).await {
+        Ok(s) => s,
+        Err(_) => {
+            eprintln!("Skipping storage config test: database not available");
+            return;
+        }
+    };
+
+    assert_eq!(storage.pg_pool.options().get_max_connections(), 2);
+    assert_eq!(
+        storage.pg_pool.options().get_acquire_timeout(),
+        Duration::from_secs(5)
+    );
+
+    let timeout: String = sqlx::query_scalar("SHOW statement_timeout")
+        .fetch_one(&storage.pg_pool)
+        .await
+        .unwrap();
+    assert_eq!(timeout, "1234ms");
+}
+
+#[tokio::test]
+async fn test_read_pool_is_read_only_and_primary_is_not() {
+    let mut config = Config::default_test();
+    config.database_read_url = Some(config.database_url.clone());
+
+    let storage = match Storage::new(&config).await {
+        Ok(s) => s,
+        Err(_) => {
+            eprintln!("Skipping read pool test: database not available");
+            return;
+        }
+    };
+
+    let read_only: String = sqlx::query_scalar("SHOW default_transaction_read_only")
+        .fetch_one(storage.read_pool())
+        .await
+        .unwrap();
+    assert_eq!(read_only, "on");
+    assert!(
+        sqlx::query("CREATE TEMP TABLE nexus_read_pool_probe (id INT)")
+            .execute(storage.read_pool())
+            .await
+            .is_err()
+    );
+
+    let primary: String = sqlx::query_scalar("SHOW default_transaction_read_only")
+        .fetch_one(&storage.pg_pool)
+        .await
+        .unwrap();
+    assert_eq!(primary, "off");
+    storage.read_repo().chain_counts().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_migrations_run_without_the_pool_statement_timeout() {
+    let mut config = Config::default_test();
+    config.database_max_connections = 1;
+    config.database_statement_timeout_ms = 50;
+
+    let storage = match Storage::new(&config).await {
+        Ok(s) => s,
+        Err(_) => {
+            eprintln!("Skipping migration timeout test: database not available");
+            return;
+        }
+    };
+
+    storage.run_migrations().await.unwrap();
+    // The migration session lifted the limit for itself only.
+    let timeout: String = sqlx::query_scalar("SHOW statement_timeout")
+        .fetch_one(&storage.pg_pool)
+        .await
+        .unwrap();
+    assert_eq!(timeout, "50ms");
+}
@@ -0,0 +1,131 @@
+use conxian_nexus::config::Config;
+use conxian_nexus::storage::repo::{NewBlock, NewTransaction};
+use conxian_nexus::storage::Storage;
+use conxian_nexus::sync::decoder::ConxianAction;
+use uuid::Uuid;
+
+async fn test_storage() -> Option<Storage> {
+    let storage = Storage::new(&Config::default_test()).await.ok()?;
+    storage.run_migrations().await.ok()?;
+    Some(storage)
+}
+
+#[tokio::test]
+async fn test_repo_round_trips_blocks_and_transactions() {
+    let Some(storage) = test_storage().await else {
+        eprintln!("Skipping repository tests: database not available");
+        return;
+    };
+    let repo = storage.repo();
+
+    let hash = format!("0x{}", Uuid::new_v4().simple());
+    let block = NewBlock {
+        hash: hash.clone(),
+        height: 7,
+        block_type: "microblock".to_string(),
+        state: "soft".to_string(),
+        block_time: None,
+    };
+    assert!(repo.insert_block(&block).await.unwrap());
+    assert!(!repo.insert_block(&block).await.unwrap());
+
+    let transactions: Vec<NewTransaction> = (0..3)
+        .map(|i| NewTransaction {
+            tx_id: format!("{}-{}", hash, i),
+            block_hash: hash.clone(),
+            payload: Some(format!("payload-{}", i)),
+            sender: (i != 2).then(|| "SP1SENDER".to_string()),
+            action: (i == 0).then_some(ConxianAction::Mint),
+        })
+        .collect();
+    assert_eq!(
+        repo.insert_transactions_batch(&transactions).await.unwrap(),
+        3
+    );
+    assert_eq!(
+        repo.insert_transactions_batch(&transactions).await.unwrap(),
+        0
+    );
+
+    let stored = repo.get_block(&hash).await.unwrap().unwrap();
+    assert_eq!(stored.block_type, "microblock");
+    assert_eq!(stored.state, "soft");
+
+    let stored_txs = repo.transactions_in_block(&hash).await.unwrap();
+    assert_eq!(stored_txs.len(), 3);
+    assert_eq!(stored_txs[2].sender, None);
+    assert_eq!(stored_txs[0].action(), Some(ConxianAction::Mint));
+    assert_eq!(stored_txs[1].action(), None);
+
+    assert!(repo.max_block_height().await.unwrap() >= 7);
+    assert!(repo.chain_counts().await.unwrap().transactions >= 3);
+}
+
+#[tokio::test]
+async fn test_finalize_blocks_up_to_promotes_soft_blocks() {
+    let Some(storage) = test_storage().await else {
+        eprintln!("Skipping repository tests: database not available");
+        return;
+    };
+    let repo = storage.repo();
+
+    let hash = format!("0x{}", Uuid::new_v4().simple());
+    repo.insert_block(&NewBlock::burn_block(&hash, 1))
+        .await
+        .unwrap();
+    assert!(repo.finalize_blocks_up_to(1).await.unwrap() >= 1);
+
+    let stored = repo.get_block(&hash).await.unwrap().unwrap();
+    assert_eq!(stored.state, "hard");
+    assert!(repo.max_hard_burn_height().await.unwrap() >= 1);
+}
+
+#[tokio::test]
+async fn test_sync_watermark_only_advances_with_committed_transactions() {
+    let Some(storage) = test_storage().await else {
+        eprintln!("Skipping repository tests: database not available");
+        return;
+    };
+    let repo = storage.repo();
+    let before = repo.sync_watermark().await.unwrap().unwrap_or(0);
+    let height = before + 1;
+
+    // A transaction referencing an unknown block fails the whole ingestion.
+    let hash = format!("0x{}", Uuid::new_v4().simple());
+    let orphan_tx = NewTransaction {
+        tx_id: format!("{}-orphan", hash),
+        block_hash: format!("0x{}", Uuid::new_v4().simple()),
+        payload: None,
+        sender: None,
+        action: None,
+    };
+    assert!(repo
+        .ingest_block(&NewBlock::microblock(&hash, height), &[orphan_tx])
+        .await
+        .is_err());
+    assert!(repo.get_block(&hash).await.unwrap().is_none());
+    assert_eq!(repo.sync_watermark().await.unwrap().unwrap_or(0), before);
+
+    let tx = NewTransaction {
+        tx_id: format!("{}-0", hash),
+        block_hash: hash.clone(),
+        payload: None,
+        sender: None,
+        action: None,
+    };
+    let written = repo
+        .ingest_block(&NewBlock::microblock(&hash, height), &[tx.clone()])
+        .await
+        .unwrap();
+    assert_eq!(written, vec![tx.tx_id.clone()]);
+    assert_eq!(repo.sync_watermark().await.unwrap().unwrap_or(0), before);
+    // Re-ingesting the same block writes nothing.
+    assert!(repo
+        .ingest_block(&NewBlock::microblock(&hash, height), &[tx])
+        .await
+        .unwrap()
+        .is_empty());
+    repo.advance_sync_watermark(height).await.unwrap();
+    assert_eq!(repo.sync_watermark().await.unwrap(), Some(height));
+    assert_eq!(repo.transactions_in_block(&hash).await.unwrap().len(), 1);
+}